@@ -528,6 +528,7 @@ def external_crates_repository(name, annotations):
                 version = "^0.10",
                 features = [
                     "arithmetic",
+                    "ecdsa",
                 ],
                 default_features = False,
             ),
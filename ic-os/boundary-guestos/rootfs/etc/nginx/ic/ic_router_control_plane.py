@@ -207,11 +207,19 @@ trusted_certs_out = []
 
 nns_node_ids = set([n.node_id for s in data.subnets if s.subnet_id == data.nns_subnet_id for n in s.nodes])
 
+
+
+def node_weight(node):
+    # the control plane only sets a weight once it has health checked the node
+    weight = getattr(node, "weight", None)
+    return 1 if weight is None else weight
+
+
 nodes = [n for s in data.subnets for n in s.nodes]
 upstreams_section = []
 for node in nodes:
 
-    def print_upstream(suffix, max_conns):
+    def print_upstream(suffix, params):
         upstreams_section.append("upstream %s%s {\n" % (node.node_id, suffix))
         sockaddrary = node.socket_addr.split(":")
         port = sockaddrary.pop()
@@ -220,15 +228,16 @@ for node in nodes:
             socket_addr = "[%s]:%s" % (":".join(sockaddrary), port)
         else:
             socket_addr = node.socket_addr
-        upstreams_section.append("  server %s %s;\n" % (socket_addr, max_conns))
+        upstreams_section.append("  server %s;\n" % " ".join([socket_addr] + params))
         upstreams_section.append("}\n")
 
     if generate_upstream_declarations:
+        weight = ["weight=%d" % node_weight(node)]
         # The default is rate limited in nginx conf, not by max_conns
-        print_upstream("", "")
+        print_upstream("", weight)
         # Query calls are rate limited by max_conns
         max_conns = "max_conns=%d" % (50 if (node.node_id in nns_node_ids) else 100)
-        print_upstream("-query", max_conns)
+        print_upstream("-query", weight + [max_conns])
     trusted_certs_out.append(node.tls_certificate_pem)
 
 nginx_lines = []
@@ -261,22 +270,29 @@ ic_router_section.append("nns_subnet_index: %s,\n" % nns_subnet_index)
 
 subnet_node_ids = []
 subnet_nodes = []
+subnet_node_weights = []
 for subnet in sorted(data.subnets, key=lambda s: canister_subnet_ids.index(s.subnet_id)):
     subnet_node_ids.append(' "%s":[\n' % subnet.subnet_id)
     subnet_nodes.append('  "%s":[\n' % subnet.subnet_id)
+    subnet_node_weights.append('  "%s":[\n' % subnet.subnet_id)
     for node in subnet.nodes:
         if not permit_node_addr(node.socket_addr):
             continue
         subnet_node_ids.append("    '%s',\n" % node.node_id)
         subnet_nodes.append("    '%s',\n" % node.socket_addr)
+        subnet_node_weights.append("    %d,\n" % node_weight(node))
     subnet_node_ids.append("  ],\n")
     subnet_nodes.append("  ],\n")
+    subnet_node_weights.append("  ],\n")
 ic_router_section.append("subnet_node_ids: {\n")
 ic_router_section.extend(subnet_node_ids)
 ic_router_section.append("},\n")
 ic_router_section.append("subnet_nodes: {\n")
 ic_router_section.extend(subnet_nodes)
 ic_router_section.append("},\n")
+ic_router_section.append("subnet_node_weights: {\n")
+ic_router_section.extend(subnet_node_weights)
+ic_router_section.append("},\n")
 
 ic_router_lines = []
 with open(ic_router_file, "r") as default_file:
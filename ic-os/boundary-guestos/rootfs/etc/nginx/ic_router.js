@@ -146,6 +146,27 @@ function inferCanisterId(r) {
   return extractCanisterIdFromReferer(r);
 }
 
+// Picks a node with a probability proportional to its weight, or uniformly
+// if the routing table has no weights for the subnet.
+function pick_node_index(node_count, weights) {
+  if (!weights || weights.length != node_count) {
+    return Math.floor(Math.random() * node_count);
+  }
+
+  var total = 0;
+  for (var i = 0; i < node_count; i++) {
+    total += weights[i];
+  }
+  var target = Math.random() * total;
+  for (var j = 0; j < node_count; j++) {
+    target -= weights[j];
+    if (target < 0) {
+      return j;
+    }
+  }
+  return node_count - 1;
+}
+
 function route(r) {
   var canister_id = inferCanisterId(r);
   if (!canister_id) {
@@ -179,7 +200,10 @@ function route(r) {
     return "";
   }
 
-  var node_index = Math.floor(Math.random() * Math.floor(nodes.length));
+  var node_index = pick_node_index(
+    nodes.length,
+    (subnet_table.subnet_node_weights || {})[subnet_id]
+  );
   var node_ids = subnet_table.subnet_node_ids[subnet_id];
   var node_id = node_ids[node_index];
   r.headersOut["x-ic-subnet-id"] = subnet_id;
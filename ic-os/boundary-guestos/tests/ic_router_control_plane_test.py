@@ -0,0 +1,134 @@
+"""
+Tests for ic_router_control_plane.py.
+
+To run them manually, from this directory:
+
+  python3 -m unittest ic_router_control_plane_test
+"""
+import json
+import os
+import shutil
+import subprocess
+import sys
+import tempfile
+import unittest
+
+SCRIPT_DIR = os.path.join(os.path.dirname(os.path.abspath(__file__)), "../rootfs/etc/nginx/ic")
+CONTROL_PLANE = os.path.join(SCRIPT_DIR, "ic_router_control_plane.py")
+
+
+def node(node_id, socket_addr, weight=None):
+    n = {
+        "node_id": node_id,
+        "socket_addr": socket_addr,
+        "tls_certificate_pem": "%s-cert\n" % node_id,
+    }
+    if weight is not None:
+        n["weight"] = weight
+    return n
+
+
+ROUTES = {
+    "registry_version": 1,
+    "nns_subnet_id": "nns-subnet",
+    "canister_routes": [
+        {
+            "subnet_id": "nns-subnet",
+            "start_canister_id": "rwlgt-iiaaa-aaaaa-aaaaa-cai",
+            "end_canister_id": "rwlgt-iiaaa-aaaaa-aaaaa-cai",
+        },
+        {
+            "subnet_id": "app-subnet",
+            "start_canister_id": "rrkah-fqaaa-aaaaa-aaaaq-cai",
+            "end_canister_id": "rrkah-fqaaa-aaaaa-aaaaq-cai",
+        },
+    ],
+    "subnets": [
+        {
+            "subnet_id": "nns-subnet",
+            "nodes": [node("nns-node", "10.0.0.1:8080")],
+        },
+        {
+            "subnet_id": "app-subnet",
+            "nodes": [
+                node("fast-node", "10.0.0.2:8080", weight=10),
+                node("slow-node", "2001:db8::3:8080", weight=3),
+            ],
+        },
+    ],
+}
+
+
+class ControlPlaneTest(unittest.TestCase):
+    def setUp(self):
+        self.dir = tempfile.mkdtemp()
+        self.routes_dir = os.path.join(self.dir, "routes")
+        os.mkdir(self.routes_dir)
+        with open(os.path.join(self.routes_dir, "1.routes"), "w") as f:
+            json.dump(ROUTES, f)
+
+        self.nginx_file = os.path.join(self.dir, "nginx_table.conf")
+        self.njs_file = os.path.join(self.dir, "ic_router_table.js")
+        self.certs_file = os.path.join(self.dir, "trusted_certs.pem")
+        shutil.copyfile(os.path.join(SCRIPT_DIR, "nginx_table.conf"), self.nginx_file)
+        shutil.copyfile(os.path.join(SCRIPT_DIR, "ic_router_table.js"), self.njs_file)
+
+        subprocess.run(
+            [sys.executable, CONTROL_PLANE, self.routes_dir, self.nginx_file, self.njs_file, self.certs_file],
+            check=True,
+            capture_output=True,
+        )
+
+    def tearDown(self):
+        shutil.rmtree(self.dir)
+
+    def read(self, path):
+        with open(path) as f:
+            return f.read()
+
+    def test_upstreams_carry_the_node_weights(self):
+        nginx_conf = self.read(self.nginx_file)
+
+        self.assertIn("upstream fast-node {\n  server 10.0.0.2:8080 weight=10;\n}\n", nginx_conf)
+        self.assertIn(
+            "upstream fast-node-query {\n  server 10.0.0.2:8080 weight=10 max_conns=100;\n}\n",
+            nginx_conf,
+        )
+        self.assertIn("upstream slow-node {\n  server [2001:db8::3]:8080 weight=3;\n}\n", nginx_conf)
+        # nodes the control plane has not scored yet get the default weight
+        self.assertIn(
+            "upstream nns-node-query {\n  server 10.0.0.1:8080 weight=1 max_conns=50;\n}\n",
+            nginx_conf,
+        )
+
+    def test_router_table_carries_the_node_weights(self):
+        router_table = self.read(self.njs_file)
+
+        self.assertIn(
+            "subnet_node_ids: {\n"
+            ' "nns-subnet":[\n'
+            "    'nns-node',\n"
+            "  ],\n"
+            ' "app-subnet":[\n'
+            "    'fast-node',\n"
+            "    'slow-node',\n"
+            "  ],\n"
+            "},\n",
+            router_table,
+        )
+        self.assertIn(
+            "subnet_node_weights: {\n"
+            '  "nns-subnet":[\n'
+            "    1,\n"
+            "  ],\n"
+            '  "app-subnet":[\n'
+            "    10,\n"
+            "    3,\n"
+            "  ],\n"
+            "},\n",
+            router_table,
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -112,6 +112,12 @@ Usage:
       has been confirmed previously already (safe to call as many times
       as you like, will not iniate I/O if nothing to be written).
 
+    rollback
+      Abandon the currently booted, not yet confirmed system and reboot
+      into the previous installation immediately. This is only permitted
+      during the first boot of a newly installed upgrade (i.e. before it
+      has been confirmed).
+
     current
       Output currently booted system (A or B) on stdout and exit.
 
@@ -180,7 +186,9 @@ case "${ACTION}" in
 
     upgrade-commit) ;&
 
-    confirm)
+    confirm) ;&
+
+    rollback)
         # Re-execute script as root (unless root already) for operations that
         # require privilege.
         if [ $(id -u) != 0 ]; then
@@ -239,6 +247,20 @@ case "${ACTION}" in
             write_grubenv "${GRUBENV_FILE}"
         fi
         ;;
+    rollback)
+        if [ "${boot_cycle}" != "failsafe_check" ]; then
+            echo "Cannot roll back a system that is not pending confirmation." >&2
+            exit 1
+        fi
+
+        # Tell boot loader to switch back to the previous system and
+        # treat it as stable, then reboot.
+        boot_alternative="${NEXT_BOOT}"
+        boot_cycle=stable
+        write_grubenv "${GRUBENV_FILE}"
+        sync
+        reboot
+        ;;
     current)
         echo "${CURRENT_ALTERNATIVE}"
         ;;
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     fs::File,
     io::BufWriter,
     net::SocketAddr,
@@ -18,13 +19,14 @@ use axum::{
 };
 use bytes::Buf;
 use clap::Parser;
-use dashmap::DashSet;
+use dashmap::DashMap;
 use futures::{future::TryFutureExt, stream::FuturesUnordered};
 use ic_types::messages::{HttpStatusResponse, ReplicaHealthStatus};
+use mockall::automock;
 use opentelemetry::{baggage::BaggageExt, global, sdk::Resource, trace::FutureExt, KeyValue};
 use opentelemetry_prometheus::PrometheusExporter;
 use prometheus::{Encoder, TextEncoder};
-use registry::{RoutingTable, Subnet};
+use registry::{Node, RoutingTable, Subnet};
 use tokio::{sync::Semaphore, task};
 use tracing::info;
 use url::Url;
@@ -34,7 +36,7 @@ mod registry;
 mod retry;
 
 use crate::{
-    metrics::{MetricParams, WithMetrics},
+    metrics::{MetricParams, ScoreMetrics, WithMetrics},
     registry::{CreateRegistryClient, CreateRegistryClientImpl, Snapshot, Snapshotter},
     retry::WithRetry,
 };
@@ -44,6 +46,12 @@ const SERVICE_NAME: &str = "control-plane";
 const SECOND: Duration = Duration::from_secs(1);
 const MINUTE: Duration = Duration::from_secs(60);
 
+// The weight of the fastest node of a subnet in the route files
+const MAX_NODE_WEIGHT: u32 = 10;
+
+// Weight of the latest health check in a node's smoothed latency
+const LATENCY_SMOOTHING_FACTOR: f64 = 0.3;
+
 #[derive(Parser)]
 #[clap(name = SERVICE_NAME)]
 #[clap(author = "Boundary Node Team <boundary-nodes@dfinity.org>")]
@@ -56,6 +64,11 @@ struct Cli {
 
     #[clap(long, default_value = "127.0.0.1:9090")]
     metrics_addr: SocketAddr,
+
+    /// Nodes whose certified height is more than this many blocks behind the
+    /// highest certified height of their subnet are excluded from the routes
+    #[clap(long, default_value = "20")]
+    max_height_lag: u64,
 }
 
 #[tokio::main]
@@ -136,8 +149,13 @@ async fn main() -> Result<(), Error> {
         MetricParams::new(&meter, SERVICE_NAME, "persist"),
     );
 
-    let check_persist_runner =
-        CheckPersistRunner::new(Arc::clone(&routing_table), checker, persister);
+    let check_persist_runner = CheckPersistRunner::new(
+        Arc::clone(&routing_table),
+        checker,
+        persister,
+        cli.max_height_lag,
+        ScoreMetrics::new(&meter, SERVICE_NAME),
+    );
     let check_persist_runner = WithMetrics(
         check_persist_runner,
         MetricParams::new(&meter, SERVICE_NAME, "run"),
@@ -200,9 +218,19 @@ async fn metrics_handler(
         .unwrap()
 }
 
+/// The outcome of a successful health check of a node.
+#[derive(Clone, Debug, PartialEq)]
+struct CheckResult {
+    /// Time it took the node to answer the status request.
+    latency: Duration,
+    /// Latest certified height reported by the node, if any.
+    certified_height: Option<u64>,
+}
+
+#[automock]
 #[async_trait]
 trait Check: 'static + Send + Sync {
-    async fn check(&self, addr: &str) -> Result<(), Error>;
+    async fn check(&self, addr: &str) -> Result<CheckResult, Error>;
 }
 
 struct Checker {
@@ -217,7 +245,9 @@ impl Checker {
 
 #[async_trait]
 impl Check for Checker {
-    async fn check(&self, addr: &str) -> Result<(), Error> {
+    async fn check(&self, addr: &str) -> Result<CheckResult, Error> {
+        let start_time = Instant::now();
+
         let request = self
             .http_client
             .request(reqwest::Method::GET, format!("http://{addr}/api/v2/status"))
@@ -240,8 +270,11 @@ impl Check for Checker {
             .context("failed to get response bytes")?
             .reader();
 
+        let latency = start_time.elapsed();
+
         let HttpStatusResponse {
             replica_health_status,
+            certified_height,
             ..
         } = serde_cbor::from_reader(response_reader).context("failed to parse cbor response")?;
 
@@ -249,7 +282,10 @@ impl Check for Checker {
             return Err(anyhow!("replica reported unhealthy status"));
         }
 
-        Ok(())
+        Ok(CheckResult {
+            latency,
+            certified_height: certified_height.map(|height| height.get()),
+        })
     }
 }
 
@@ -258,6 +294,7 @@ enum PersistStatus {
     Skipped,
 }
 
+#[automock]
 #[async_trait]
 trait Persist: Send + Sync {
     async fn persist(&mut self, rt: RoutingTable) -> Result<PersistStatus, Error>;
@@ -326,16 +363,87 @@ struct CheckPersistRunner<C: Check, P: Persist> {
     routing_table: Arc<Mutex<Option<RoutingTable>>>,
     checker: Arc<C>,
     persister: P,
+    max_height_lag: u64,
+    // Smoothed latency in seconds, by subnet and node id
+    latencies: HashMap<(String, String), f64>,
+    metrics: ScoreMetrics,
 }
 
 impl<C: Check, P: Persist> CheckPersistRunner<C, P> {
-    fn new(routing_table: Arc<Mutex<Option<RoutingTable>>>, checker: C, persister: P) -> Self {
+    fn new(
+        routing_table: Arc<Mutex<Option<RoutingTable>>>,
+        checker: C,
+        persister: P,
+        max_height_lag: u64,
+        metrics: ScoreMetrics,
+    ) -> Self {
         Self {
             routing_table,
             checker: Arc::new(checker),
             persister,
+            max_height_lag,
+            latencies: HashMap::new(),
+            metrics,
         }
     }
+
+    /// Keeps the healthy nodes of a subnet that are not lagging behind, and
+    /// weights them by their smoothed latency relative to the fastest one.
+    fn score_subnet(
+        &self,
+        subnet: Subnet,
+        check_results: &DashMap<(String, String), CheckResult>,
+    ) -> Subnet {
+        let key = |node: &Node| (subnet.subnet_id.clone(), node.node_id.clone());
+
+        let max_height = subnet
+            .nodes
+            .iter()
+            .filter_map(|node| check_results.get(&key(node))?.certified_height)
+            .max();
+
+        let nodes: Vec<(Node, f64)> = subnet
+            .nodes
+            .iter()
+            .filter_map(|node| {
+                let result = check_results.get(&key(node))?;
+
+                if let (Some(max_height), Some(height)) = (max_height, result.certified_height) {
+                    let height_lag = max_height - height;
+                    self.metrics
+                        .record_height_lag(&subnet.subnet_id, &node.node_id, height_lag);
+
+                    if height_lag > self.max_height_lag {
+                        return None;
+                    }
+                }
+
+                Some((node.clone(), self.latencies[&key(node)]))
+            })
+            .collect();
+
+        let min_latency = nodes
+            .iter()
+            .map(|(_, latency)| *latency)
+            .fold(f64::INFINITY, f64::min);
+
+        let nodes = nodes
+            .into_iter()
+            .map(|(node, latency)| {
+                let weight = ((MAX_NODE_WEIGHT as f64) * min_latency / latency).ceil() as u32;
+                let weight = weight.clamp(1, MAX_NODE_WEIGHT);
+                self.metrics
+                    .record_weight(&subnet.subnet_id, &node.node_id, weight);
+
+                Node {
+                    weight: Some(weight),
+                    ..node
+                }
+            })
+            .collect();
+
+        Subnet { nodes, ..subnet }
+    }
 }
 
 #[async_trait]
@@ -347,7 +455,7 @@ impl<C: Check, P: Persist> Run for CheckPersistRunner<C, P> {
                 .ok_or_else(|| anyhow!("routing_table not available"))?
         };
 
-        let check_results: Arc<DashSet<(String, String)>> = Arc::new(DashSet::new());
+        let check_results: Arc<DashMap<(String, String), CheckResult>> = Arc::new(DashMap::new());
 
         // Perform Health Checks
         let futs = FuturesUnordered::new();
@@ -376,8 +484,8 @@ impl<C: Check, P: Persist> Run for CheckPersistRunner<C, P> {
                         .await
                         .context("failed to check node");
 
-                    if out.is_ok() {
-                        check_results.insert((subnet_id, node_id));
+                    if let Ok(result) = &out {
+                        check_results.insert((subnet_id, node_id), result.clone());
                     }
 
                     out
@@ -389,22 +497,26 @@ impl<C: Check, P: Persist> Run for CheckPersistRunner<C, P> {
             let _ = fut.await?;
         }
 
+        // Update Smoothed Latencies, forgetting nodes that failed their check
+        self.latencies
+            .retain(|key, _| check_results.contains_key(key));
+
+        for entry in check_results.iter() {
+            let latency = entry.value().latency.as_secs_f64();
+            self.latencies
+                .entry(entry.key().clone())
+                .and_modify(|smoothed| {
+                    *smoothed += LATENCY_SMOOTHING_FACTOR * (latency - *smoothed)
+                })
+                .or_insert(latency);
+        }
+
         // Construct Effective Routing Table
         let effective_routing_table = RoutingTable {
             subnets: routing_table
                 .subnets
                 .into_iter()
-                .map(|subnet| Subnet {
-                    nodes: subnet
-                        .nodes
-                        .into_iter()
-                        .filter(|node| {
-                            check_results
-                                .contains(&(subnet.subnet_id.clone(), node.node_id.clone()))
-                        })
-                        .collect(),
-                    ..subnet
-                })
+                .map(|subnet| self.score_subnet(subnet, &check_results))
                 .collect(),
             ..routing_table
         };
@@ -460,7 +572,7 @@ impl<T> WithSemaphore<T> {
 
 #[async_trait]
 impl<T: Check> Check for WithSemaphore<T> {
-    async fn check(&self, addr: &str) -> Result<(), Error> {
+    async fn check(&self, addr: &str) -> Result<CheckResult, Error> {
         let _permit = self.1.acquire().await?;
         self.0.check(addr).await
     }
@@ -480,3 +592,160 @@ impl<T: Persist> Persist for WithDedup<T, RoutingTable> {
         self.0.persist(rt).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::registry::MockSnapshot;
+
+    const SUBNET_ID: &str = "SUBNET_1";
+
+    fn node(index: usize) -> Node {
+        Node {
+            node_id: format!("NODE_{index}"),
+            socket_addr: format!("10.0.0.{index}:8080"),
+            tls_certificate_pem: format!("CERT_{index}"),
+            weight: None,
+        }
+    }
+
+    fn check_result(latency_ms: u64, certified_height: Option<u64>) -> Result<CheckResult, Error> {
+        Ok(CheckResult {
+            latency: Duration::from_millis(latency_ms),
+            certified_height,
+        })
+    }
+
+    // Loads a routing table with a single subnet of `num_nodes` nodes from a
+    // stub registry snapshot
+    async fn load_routing_table(
+        num_nodes: usize,
+    ) -> Result<Arc<Mutex<Option<RoutingTable>>>, Error> {
+        let mut snapshotter = MockSnapshot::new();
+        snapshotter.expect_snapshot().times(1).returning(move || {
+            Ok(RoutingTable {
+                registry_version: 1,
+                nns_subnet_id: SUBNET_ID.to_string(),
+                canister_routes: vec![],
+                subnets: vec![Subnet {
+                    subnet_id: SUBNET_ID.to_string(),
+                    nodes: (1..=num_nodes).map(node).collect(),
+                }],
+            })
+        });
+
+        let routing_table = Arc::new(Mutex::new(None));
+        let mut snapshot_runner = SnapshotRunner::new(snapshotter, Arc::clone(&routing_table));
+        snapshot_runner.run().await?;
+
+        Ok(routing_table)
+    }
+
+    fn weights(rt: &RoutingTable) -> Vec<(String, Option<u32>)> {
+        rt.subnets[0]
+            .nodes
+            .iter()
+            .map(|node| (node.node_id.clone(), node.weight))
+            .collect()
+    }
+
+    fn runner<C: Check>(
+        routing_table: Arc<Mutex<Option<RoutingTable>>>,
+        checker: C,
+        persister: MockPersist,
+    ) -> CheckPersistRunner<C, MockPersist> {
+        CheckPersistRunner::new(
+            routing_table,
+            checker,
+            persister,
+            20, // max_height_lag
+            ScoreMetrics::new(&global::meter("test"), "test"),
+        )
+    }
+
+    #[tokio::test]
+    async fn it_excludes_unhealthy_and_lagging_nodes() -> Result<(), Error> {
+        let routing_table = load_routing_table(4).await?;
+
+        let mut checker = MockCheck::new();
+        checker.expect_check().returning(|addr| match addr {
+            "10.0.0.1:8080" => check_result(10, Some(100)),
+            "10.0.0.2:8080" => check_result(10, Some(85)),
+            "10.0.0.3:8080" => check_result(10, Some(50)),
+            _ => Err(anyhow!("replica reported unhealthy status")),
+        });
+
+        let mut persister = MockPersist::new();
+        persister
+            .expect_persist()
+            .times(1)
+            .withf(|rt| {
+                weights(rt)
+                    == vec![
+                        ("NODE_1".to_string(), Some(MAX_NODE_WEIGHT)),
+                        ("NODE_2".to_string(), Some(MAX_NODE_WEIGHT)),
+                    ]
+            })
+            .returning(|_| Ok(PersistStatus::Completed));
+
+        runner(routing_table, checker, persister).run().await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_weights_nodes_by_smoothed_latency() -> Result<(), Error> {
+        let routing_table = load_routing_table(3).await?;
+
+        // NODE_1 slows down from 10ms to 40ms in the second run
+        let num_checks = AtomicUsize::new(0);
+        let mut checker = MockCheck::new();
+        checker.expect_check().times(6).returning(move |addr| {
+            let first_run = num_checks.fetch_add(1, Ordering::SeqCst) < 3;
+            match addr {
+                "10.0.0.1:8080" if first_run => check_result(10, Some(100)),
+                "10.0.0.1:8080" => check_result(40, Some(101)),
+                "10.0.0.2:8080" => check_result(20, Some(100)),
+                // Nodes that do not report a certified height are not excluded
+                _ => check_result(40, None),
+            }
+        });
+
+        let persisted = Arc::new(Mutex::new(vec![]));
+        let mut persister = MockPersist::new();
+        persister.expect_persist().times(2).returning({
+            let persisted = Arc::clone(&persisted);
+            move |rt| {
+                persisted.lock().unwrap().push(weights(&rt));
+                Ok(PersistStatus::Completed)
+            }
+        });
+
+        let mut runner = runner(routing_table, checker, persister);
+        runner.run().await?;
+        runner.run().await?;
+
+        let weight = |node_id: &str, weight: u32| (node_id.to_string(), Some(weight));
+        assert_eq!(
+            *persisted.lock().unwrap(),
+            vec![
+                vec![
+                    weight("NODE_1", 10),
+                    weight("NODE_2", 5),
+                    weight("NODE_3", 3)
+                ],
+                // After smoothing, NODE_1 is at 19ms and still the fastest
+                vec![
+                    weight("NODE_1", 10),
+                    weight("NODE_2", 10),
+                    weight("NODE_3", 5)
+                ],
+            ]
+        );
+
+        Ok(())
+    }
+}
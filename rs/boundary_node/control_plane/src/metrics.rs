@@ -12,7 +12,7 @@ use tracing::info;
 
 use crate::{
     registry::{CreateRegistryClient, RoutingTable, Snapshot},
-    Check, Persist, PersistStatus, Run,
+    Check, CheckResult, Persist, PersistStatus, Run,
 };
 
 pub struct MetricParams {
@@ -37,6 +37,46 @@ impl MetricParams {
     }
 }
 
+/// Records the scores the health checks assign to each node.
+pub struct ScoreMetrics {
+    pub weight: ValueRecorder<u64>,
+    pub height_lag: ValueRecorder<u64>,
+}
+
+impl ScoreMetrics {
+    pub fn new(meter: &Meter, namespace: &str) -> Self {
+        Self {
+            weight: meter
+                .u64_value_recorder(format!("{namespace}.node_weight"))
+                .with_description("Records the routing weight assigned to each node")
+                .init(),
+            height_lag: meter
+                .u64_value_recorder(format!("{namespace}.node_height_lag"))
+                .with_description(
+                    "Records how many blocks each node's certified height is behind its subnet",
+                )
+                .init(),
+        }
+    }
+
+    pub fn record_weight(&self, subnet_id: &str, node_id: &str, weight: u32) {
+        self.weight
+            .record(weight.into(), &Self::labels(subnet_id, node_id));
+    }
+
+    pub fn record_height_lag(&self, subnet_id: &str, node_id: &str, height_lag: u64) {
+        self.height_lag
+            .record(height_lag, &Self::labels(subnet_id, node_id));
+    }
+
+    fn labels(subnet_id: &str, node_id: &str) -> [KeyValue; 2] {
+        [
+            KeyValue::new("subnet_id", subnet_id.to_string()),
+            KeyValue::new("node_id", node_id.to_string()),
+        ]
+    }
+}
+
 pub struct WithMetrics<T>(pub T, pub MetricParams);
 
 #[async_trait]
@@ -95,7 +135,7 @@ impl<T: Snapshot> Snapshot for WithMetrics<T> {
 
 #[async_trait]
 impl<T: Check> Check for WithMetrics<T> {
-    async fn check(&self, addr: &str) -> Result<(), Error> {
+    async fn check(&self, addr: &str) -> Result<CheckResult, Error> {
         let start_time = Instant::now();
 
         let out = self.0.check(addr).await;
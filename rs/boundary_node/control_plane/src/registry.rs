@@ -10,6 +10,7 @@ use ic_registry_client_helpers::{
     routing_table::RoutingTableRegistry,
     subnet::{SubnetListRegistry, SubnetRegistry},
 };
+use mockall::automock;
 use serde::Serialize;
 
 #[async_trait]
@@ -51,6 +52,10 @@ pub struct Node {
     pub node_id: String,
     pub socket_addr: String,
     pub tls_certificate_pem: String,
+    /// Relative share of the subnet's traffic to route to the node, derived
+    /// from its health checks. Not set in registry snapshots.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u32>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize)]
@@ -74,6 +79,7 @@ pub struct RoutingTable {
     pub subnets: Vec<Subnet>,
 }
 
+#[automock]
 #[async_trait]
 pub trait Snapshot: Send + Sync {
     async fn snapshot(&mut self) -> Result<RoutingTable, Error>;
@@ -146,6 +152,7 @@ impl<T: RegistryClient> Snapshot for Snapshotter<T> {
                             node_id: node_id.to_string(),
                             socket_addr,
                             tls_certificate_pem,
+                            weight: None,
                         };
 
                         let out: Result<Node, Error> = Ok(node_route);
@@ -9,8 +9,11 @@ use chacha20poly1305::{aead::Aead, KeyInit, XChaCha20Poly1305, XNonce};
 use clap::Parser;
 use flate2::{write::GzEncoder, Compression};
 use rand_core::{OsRng, RngCore};
-use rsa::{pkcs8::DecodePublicKey, PaddingScheme, PublicKey, RsaPublicKey};
-use sha2::Sha256;
+use rsa::{
+    pkcs8::{DecodePrivateKey, DecodePublicKey},
+    Hash, PaddingScheme, PublicKey, RsaPrivateKey, RsaPublicKey,
+};
+use sha2::{Digest, Sha256};
 use tar::{Builder, Header};
 
 #[derive(Parser)]
@@ -25,6 +28,13 @@ struct Cli {
 
     #[clap(long, default_value = "denylist.tar.gz")]
     payload_path: PathBuf,
+
+    /// Private key to sign the payload with, no signature is created if unset
+    #[clap(long)]
+    signing_key_path: Option<PathBuf>,
+
+    #[clap(long, default_value = "denylist.tar.gz.sig")]
+    signature_path: PathBuf,
 }
 
 #[tokio::main]
@@ -91,10 +101,28 @@ async fn main() -> Result<(), Error> {
 
     let payload = ar.into_inner().context("failed to finalize tar archive")?;
 
-    let f_payload = File::create(cli.payload_path).context("failed to create payload file")?;
-    let mut enc = GzEncoder::new(f_payload, Compression::default());
-    enc.write_all(&payload).context("failed to write payload")?;
-    enc.finish()?;
+    let mut enc = GzEncoder::new(Vec::new(), Compression::default());
+    enc.write_all(&payload)
+        .context("failed to compress payload")?;
+    let payload = enc.finish()?;
+
+    let mut f_payload = File::create(cli.payload_path).context("failed to create payload file")?;
+    f_payload
+        .write_all(&payload)
+        .context("failed to write payload")?;
+
+    // Sign payload
+    if let Some(signing_key_path) = cli.signing_key_path {
+        let signing_key_pem = std::fs::read_to_string(signing_key_path)?;
+        let signing_key = RsaPrivateKey::from_pkcs8_pem(&signing_key_pem)?;
+
+        let padding = PaddingScheme::new_pkcs1v15_sign(Some(Hash::SHA2_256));
+        let signature = signing_key
+            .sign(padding, &Sha256::digest(&payload))
+            .context("failed to sign payload")?;
+
+        std::fs::write(cli.signature_path, signature).context("failed to write signature")?;
+    }
 
     Ok(())
 }
@@ -8,20 +8,26 @@ use chacha20poly1305::{
 };
 use flate2::read::GzDecoder;
 use rsa::{PaddingScheme, RsaPrivateKey};
+use serde::Deserialize;
+use serde_json as json;
 use sha2::Sha256;
 use tar::Archive;
 
+use crate::Entry;
+
+const DENYLIST_VERSION: u32 = 2;
+
 #[async_trait]
 pub trait Decode: Send + Sync {
-    async fn decode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error>;
+    async fn decode(&self, data: Vec<u8>) -> Result<Vec<Entry>, Error>;
 }
 
 pub struct NopDecoder;
 
 #[async_trait]
 impl Decode for NopDecoder {
-    async fn decode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
-        Ok(data)
+    async fn decode(&self, data: Vec<u8>) -> Result<Vec<Entry>, Error> {
+        parse(&data)
     }
 }
 
@@ -37,7 +43,7 @@ impl Decoder {
 
 #[async_trait]
 impl Decode for Decoder {
-    async fn decode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+    async fn decode(&self, data: Vec<u8>) -> Result<Vec<Entry>, Error> {
         let mut dec = GzDecoder::new(data.as_slice());
 
         let mut data = Vec::new();
@@ -94,6 +100,116 @@ impl Decode for Decoder {
         dec.read_to_end(&mut data)
             .context("failed to decode gzip")?;
 
-        Ok(data)
+        parse(&data)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Denylist {
+    // The original format, a plain list of entries
+    Legacy(Vec<Entry>),
+    Versioned { version: u32, entries: Vec<Entry> },
+}
+
+fn parse(data: &[u8]) -> Result<Vec<Entry>, Error> {
+    let entries = match json::from_slice::<Denylist>(data).context("failed to deserialize json")? {
+        Denylist::Legacy(entries) => entries,
+        Denylist::Versioned { version, entries } if version == DENYLIST_VERSION => entries,
+        Denylist::Versioned { version, .. } => {
+            return Err(anyhow!("unsupported denylist version {version}"))
+        }
+    };
+
+    for entry in &entries {
+        validate(entry).context(format!("invalid entry {}", entry.id))?;
+    }
+
+    Ok(entries)
+}
+
+// Codes and scopes end up as keys and values of the nginx map, so only allow
+// characters that need no quoting there
+fn validate(entry: &Entry) -> Result<(), Error> {
+    let is_token = |s: &str| {
+        !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    };
+
+    if !is_token(&entry.id) {
+        return Err(anyhow!("invalid id"));
+    }
+
+    if !is_token(&entry.code) {
+        return Err(anyhow!("invalid code {}", entry.code));
+    }
+
+    if let Some(scope) = entry.scopes.iter().find(|scope| !is_token(scope)) {
+        return Err(anyhow!("invalid scope {scope}"));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_legacy_format() -> Result<(), Error> {
+        let data = r#"[{"id": "ID_1", "code": "CODE_1", "reason": "REASON_1"}]"#;
+
+        let entries = parse(data.as_bytes())?;
+        assert_eq!(
+            entries,
+            vec![Entry {
+                id: "ID_1".to_string(),
+                code: "CODE_1".to_string(),
+                reason: "REASON_1".to_string(),
+                scopes: vec![],
+                expires_at: None,
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_parses_versioned_format() -> Result<(), Error> {
+        let data = r#"{
+            "version": 2,
+            "entries": [
+                {"id": "ID_1", "code": "CODE_1", "reason": "REASON_1", "scopes": ["CH", "DE"], "expires_at": 1700000000}
+            ]
+        }"#;
+
+        let entries = parse(data.as_bytes())?;
+        assert_eq!(
+            entries,
+            vec![Entry {
+                id: "ID_1".to_string(),
+                code: "CODE_1".to_string(),
+                reason: "REASON_1".to_string(),
+                scopes: vec!["CH".to_string(), "DE".to_string()],
+                expires_at: Some(1700000000),
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_rejects_unknown_version() {
+        let data = r#"{"version": 3, "entries": []}"#;
+
+        assert!(parse(data.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn it_rejects_invalid_scope() {
+        let data = r#"[{"id": "ID_1", "code": "CODE_1", "reason": "REASON_1", "scopes": ["D E"]}]"#;
+
+        assert!(parse(data.as_bytes()).is_err());
     }
 }
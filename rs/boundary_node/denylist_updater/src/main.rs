@@ -3,7 +3,7 @@ use std::{
     net::SocketAddr,
     path::PathBuf,
     sync::Arc,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::{anyhow, Context, Error};
@@ -25,15 +25,17 @@ use nix::{
 use opentelemetry::{global, sdk::Resource, KeyValue};
 use opentelemetry_prometheus::PrometheusExporter;
 use prometheus::{Encoder, TextEncoder};
-use rsa::{pkcs8::DecodePrivateKey, RsaPrivateKey};
+use rsa::{
+    pkcs8::{DecodePrivateKey, DecodePublicKey},
+    RsaPrivateKey, RsaPublicKey,
+};
 use serde::Deserialize;
-use serde_json as json;
 use tokio::{
     fs::{self, File},
     io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader},
     task,
 };
-use tracing::info;
+use tracing::{info, warn};
 
 mod metrics;
 use metrics::{MetricParams, WithMetrics};
@@ -41,6 +43,9 @@ use metrics::{MetricParams, WithMetrics};
 mod decode;
 use decode::{Decode, Decoder, NopDecoder};
 
+mod verify;
+use verify::{Verifier, Verify};
+
 const SERVICE_NAME: &str = "denylist-updater";
 
 const MINUTE: Duration = Duration::from_secs(60);
@@ -64,6 +69,14 @@ struct Cli {
     #[clap(long, default_value = "key.pem")]
     private_key_path: PathBuf,
 
+    /// Public key to verify the signature of the remote denylist with, no verification if unset
+    #[clap(long)]
+    public_key_path: Option<PathBuf>,
+
+    /// URL of the detached signature of the remote denylist, defaults to the remote URL with a `.sig` suffix
+    #[clap(long)]
+    signature_url: Option<String>,
+
     #[clap(long, default_value = "/tmp/denylist.map")]
     local_path: PathBuf,
 
@@ -106,7 +119,28 @@ async fn main() -> Result<(), Error> {
         }
     };
 
-    let remote_lister = RemoteLister::new(http_client, decoder, cli.remote_url.clone());
+    let verifier: Option<Arc<dyn Verify>> = match cli.public_key_path {
+        None => None,
+        Some(public_key_path) => {
+            let public_key_pem = std::fs::read_to_string(public_key_path)?;
+            let public_key = RsaPublicKey::from_public_key_pem(&public_key_pem)?;
+            Some(Arc::new(Verifier::new(public_key)))
+        }
+    };
+
+    let signature_url = cli
+        .signature_url
+        .unwrap_or_else(|| format!("{}.sig", cli.remote_url));
+
+    let remote_lister = RemoteLister::new(
+        http_client,
+        decoder,
+        verifier,
+        cli.remote_url.clone(),
+        signature_url,
+    );
+    let remote_lister = WithExpiry(remote_lister);
+    let remote_lister = WithGlobalScope(remote_lister);
     let remote_lister = WithNormalize(remote_lister);
     let remote_lister = WithMetrics(
         remote_lister,
@@ -182,13 +216,19 @@ async fn metrics_handler(
         .unwrap()
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
 struct Entry {
     id: String,
-    #[allow(dead_code)]
+    // Reason code, returned by the gateway to explain why a request was blocked
     code: String,
     #[allow(dead_code)]
     reason: String,
+    // Regions (e.g ISO 3166 country codes) the entry applies to, everywhere if empty
+    #[serde(default)]
+    scopes: Vec<String>,
+    // Time (in seconds since the UNIX epoch) after which the entry no longer applies
+    #[serde(default)]
+    expires_at: Option<u64>,
 }
 
 #[automock]
@@ -217,15 +257,40 @@ impl List for LocalLister {
         let f = BufReader::new(f);
 
         let mut lines = f.lines();
-        let mut entries = vec![];
+        let mut entries: Vec<Entry> = vec![];
 
+        // Lines are either `ID 1;` (legacy) or `ID "CODE";`, with `ID:SCOPE` as key for scoped
+        // entries written by earlier versions, so that they are replaced on the next update
         while let Some(line) = lines.next_line().await? {
-            if let Some(id) = line.split_whitespace().next() {
-                entries.push(Entry {
+            let mut parts = line.split_whitespace();
+
+            let key = match parts.next() {
+                Some(key) => key,
+                None => continue,
+            };
+
+            let code = match parts
+                .next()
+                .map(|v| v.trim_end_matches(';').trim_matches('"'))
+            {
+                Some("1") | None => "N/A",
+                Some(code) => code,
+            };
+
+            let (id, scope) = match key.split_once(':') {
+                Some((id, scope)) => (id, Some(scope.to_string())),
+                None => (key, None),
+            };
+
+            match entries.last_mut() {
+                Some(entry) if entry.id == id => entry.scopes.extend(scope),
+                _ => entries.push(Entry {
                     id: id.to_string(),
-                    code: "N/A".to_string(),
+                    code: code.to_string(),
                     reason: "N/A".to_string(),
-                });
+                    scopes: scope.into_iter().collect(),
+                    expires_at: None,
+                }),
             }
         }
 
@@ -236,25 +301,32 @@ impl List for LocalLister {
 struct RemoteLister {
     http_client: reqwest::Client,
     decoder: Arc<dyn Decode>,
+    verifier: Option<Arc<dyn Verify>>,
     remote_url: String,
+    signature_url: String,
 }
 
 impl RemoteLister {
-    fn new(http_client: reqwest::Client, decoder: Arc<dyn Decode>, remote_url: String) -> Self {
+    fn new(
+        http_client: reqwest::Client,
+        decoder: Arc<dyn Decode>,
+        verifier: Option<Arc<dyn Verify>>,
+        remote_url: String,
+        signature_url: String,
+    ) -> Self {
         Self {
             http_client,
             decoder,
+            verifier,
             remote_url,
+            signature_url,
         }
     }
-}
 
-#[async_trait]
-impl List for RemoteLister {
-    async fn list(&self) -> Result<Vec<Entry>, Error> {
+    async fn fetch(&self, url: &str) -> Result<Vec<u8>, Error> {
         let request = self
             .http_client
-            .request(reqwest::Method::GET, self.remote_url.clone())
+            .request(reqwest::Method::GET, url)
             .build()
             .context("failed to build request")?;
 
@@ -274,15 +346,33 @@ impl List for RemoteLister {
             .context("failed to get response bytes")?
             .to_vec();
 
-        let data = self
+        Ok(data)
+    }
+}
+
+#[async_trait]
+impl List for RemoteLister {
+    async fn list(&self) -> Result<Vec<Entry>, Error> {
+        let data = self.fetch(&self.remote_url).await?;
+
+        if let Some(verifier) = &self.verifier {
+            let signature = self
+                .fetch(&self.signature_url)
+                .await
+                .context("failed to fetch signature")?;
+
+            verifier
+                .verify(&data, &signature)
+                .await
+                .context("failed to verify response")?;
+        }
+
+        let entries = self
             .decoder
             .decode(data)
             .await
             .context("failed to decode response")?;
 
-        let entries =
-            json::from_slice::<Vec<Entry>>(&data).context("failed to deserialize json response")?;
-
         Ok(entries)
     }
 }
@@ -310,10 +400,11 @@ impl Update for Updater {
             .await
             .context("failed to create file")?;
 
+        // The gateway only looks up canister ids, so scoped entries are blocked everywhere
         for entry in entries {
-            let line = format!("{} 1;\n", entry.id);
+            let line = format!("{} \"{}\";\n", entry.id, entry.code);
 
-            f.write(line.as_bytes())
+            f.write_all(line.as_bytes())
                 .await
                 .context("failed to write entry")?;
         }
@@ -418,7 +509,7 @@ fn eq(a: &[Entry], b: &[Entry]) -> bool {
 
     let (mut a, mut b) = (a.iter(), b.iter());
     while let (Some(a), Some(b)) = (a.next(), b.next()) {
-        if a.id != b.id {
+        if a.id != b.id || a.code != b.code || a.scopes != b.scopes {
             return false;
         }
     }
@@ -473,6 +564,56 @@ impl<T: List> List for WithNormalize<T> {
                 entries.dedup_by(|a, b| a.id == b.id);
                 entries
             })
+            .map(|mut entries| {
+                entries.iter_mut().for_each(|entry| {
+                    entry.scopes.sort();
+                    entry.scopes.dedup();
+                });
+                entries
+            })
+    }
+}
+
+struct WithExpiry<T: List>(T);
+
+#[async_trait]
+impl<T: List> List for WithExpiry<T> {
+    async fn list(&self) -> Result<Vec<Entry>, Error> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .context("failed to get current time")?
+            .as_secs();
+
+        self.0.list().await.map(|entries| {
+            entries
+                .into_iter()
+                .filter(|entry| entry.expires_at.map_or(true, |t| t > now))
+                .collect()
+        })
+    }
+}
+
+struct WithGlobalScope<T: List>(T);
+
+#[async_trait]
+impl<T: List> List for WithGlobalScope<T> {
+    async fn list(&self) -> Result<Vec<Entry>, Error> {
+        self.0.list().await.map(|mut entries| {
+            let mut scoped = 0;
+            for entry in entries.iter_mut().filter(|entry| !entry.scopes.is_empty()) {
+                entry.scopes.clear();
+                scoped += 1;
+            }
+
+            if scoped > 0 {
+                warn!(
+                    msg = "blocking scoped entries everywhere, regions are not supported",
+                    count = scoped,
+                );
+            }
+
+            entries
+        })
     }
 }
 
@@ -532,11 +673,15 @@ mod tests {
                     id: "ID_1".to_string(),
                     code: "N/A".to_string(),
                     reason: "N/A".to_string(),
+                    scopes: vec![],
+                    expires_at: None,
                 },
                 Entry {
                     id: "ID_2".to_string(),
                     code: "N/A".to_string(),
                     reason: "N/A".to_string(),
+                    scopes: vec![],
+                    expires_at: None,
                 }
             ]
         );
@@ -572,6 +717,8 @@ mod tests {
                 id: "ID_1".to_string(),
                 code: "CODE_1".to_string(),
                 reason: "REASON_1".to_string(),
+                scopes: vec![],
+                expires_at: None,
             }])
         });
 
@@ -581,6 +728,8 @@ mod tests {
                 id: "ID_1".to_string(),
                 code: "CODE_1".to_string(),
                 reason: "REASON_1".to_string(),
+                scopes: vec![],
+                expires_at: None,
             }])
         });
 
@@ -601,6 +750,8 @@ mod tests {
                 id: "ID_1".to_string(),
                 code: "CODE_1".to_string(),
                 reason: "REASON_1".to_string(),
+                scopes: vec![],
+                expires_at: None,
             }])
         });
 
@@ -618,6 +769,8 @@ mod tests {
                         id: "ID_1".to_string(),
                         code: "CODE_1".to_string(),
                         reason: "REASON_1".to_string(),
+                        scopes: vec![],
+                        expires_at: None,
                     }],
                 )
             }))
@@ -628,4 +781,234 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn it_lists_locally_with_codes_and_scopes() -> Result<(), Error> {
+        use std::fs::File;
+        use std::io::Write;
+        use tempfile::tempdir;
+
+        let local_dir = tempdir()?;
+
+        let (name, content) = &(
+            "denylist.map",
+            "ID_1 \"CODE_1\";\nID_2:CH \"CODE_2\";\nID_2:DE \"CODE_2\";",
+        );
+
+        let file_path = local_dir.path().join(name);
+        let mut file = File::create(file_path.clone())?;
+        writeln!(file, "{}", content)?;
+
+        let lister = LocalLister::new(file_path.clone());
+
+        let out = lister.list().await?;
+        assert_eq!(
+            out,
+            vec![
+                Entry {
+                    id: "ID_1".to_string(),
+                    code: "CODE_1".to_string(),
+                    reason: "N/A".to_string(),
+                    scopes: vec![],
+                    expires_at: None,
+                },
+                Entry {
+                    id: "ID_2".to_string(),
+                    code: "CODE_2".to_string(),
+                    reason: "N/A".to_string(),
+                    scopes: vec!["CH".to_string(), "DE".to_string()],
+                    expires_at: None,
+                }
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_updates_scoped_entries_globally() -> Result<(), Error> {
+        use tempfile::tempdir;
+
+        let local_dir = tempdir()?;
+        let file_path = local_dir.path().join("denylist.map");
+
+        let entries = vec![
+            Entry {
+                id: "ID_1".to_string(),
+                code: "CODE_1".to_string(),
+                reason: "REASON_1".to_string(),
+                scopes: vec![],
+                expires_at: None,
+            },
+            Entry {
+                id: "ID_2".to_string(),
+                code: "CODE_2".to_string(),
+                reason: "REASON_2".to_string(),
+                scopes: vec!["CH".to_string(), "DE".to_string()],
+                expires_at: Some(1),
+            },
+        ];
+
+        let updater = Updater::new(file_path.clone());
+        updater.update(entries).await?;
+
+        let out = std::fs::read_to_string(file_path.clone())?;
+        assert_eq!(out, "ID_1 \"CODE_1\";\nID_2 \"CODE_2\";\n");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_drops_expired_entries() -> Result<(), Error> {
+        let mut lister = MockList::new();
+        lister.expect_list().times(1).returning(|| {
+            Ok(vec![
+                Entry {
+                    id: "ID_1".to_string(),
+                    code: "CODE_1".to_string(),
+                    reason: "REASON_1".to_string(),
+                    scopes: vec![],
+                    expires_at: Some(1),
+                },
+                Entry {
+                    id: "ID_2".to_string(),
+                    code: "CODE_2".to_string(),
+                    reason: "REASON_2".to_string(),
+                    scopes: vec![],
+                    expires_at: Some(u64::MAX),
+                },
+                Entry {
+                    id: "ID_3".to_string(),
+                    code: "CODE_3".to_string(),
+                    reason: "REASON_3".to_string(),
+                    scopes: vec![],
+                    expires_at: None,
+                },
+            ])
+        });
+
+        let lister = WithExpiry(lister);
+
+        let ids: Vec<String> = lister.list().await?.into_iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec!["ID_2".to_string(), "ID_3".to_string()]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_blocks_scoped_entries_everywhere() -> Result<(), Error> {
+        let mut lister = MockList::new();
+        lister.expect_list().times(1).returning(|| {
+            Ok(vec![
+                Entry {
+                    id: "ID_1".to_string(),
+                    code: "CODE_1".to_string(),
+                    reason: "REASON_1".to_string(),
+                    scopes: vec!["CH".to_string()],
+                    expires_at: None,
+                },
+                Entry {
+                    id: "ID_2".to_string(),
+                    code: "CODE_2".to_string(),
+                    reason: "REASON_2".to_string(),
+                    scopes: vec![],
+                    expires_at: None,
+                },
+            ])
+        });
+
+        let lister = WithGlobalScope(lister);
+
+        let out = lister.list().await?;
+        let ids: Vec<&str> = out.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["ID_1", "ID_2"]);
+        assert!(out.iter().all(|e| e.scopes.is_empty()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_runs_scoped_entries_into_the_map() -> Result<(), Error> {
+        use tempfile::tempdir;
+
+        let local_dir = tempdir()?;
+        let file_path = local_dir.path().join("denylist.map");
+        std::fs::File::create(file_path.clone())?;
+
+        let mut remote_lister = MockList::new();
+        remote_lister.expect_list().times(1).returning(|| {
+            Ok(vec![Entry {
+                id: "ID_1".to_string(),
+                code: "CODE_1".to_string(),
+                reason: "REASON_1".to_string(),
+                scopes: vec!["CH".to_string()],
+                expires_at: None,
+            }])
+        });
+        let remote_lister = WithNormalize(WithGlobalScope(remote_lister));
+
+        let local_lister = WithNormalize(LocalLister::new(file_path.clone()));
+
+        let updater = Updater::new(file_path.clone());
+
+        let mut runner = Runner::new(remote_lister, local_lister, updater);
+        runner.run().await?;
+
+        let out = std::fs::read_to_string(file_path.clone())?;
+        assert_eq!(out, "ID_1 \"CODE_1\";\n");
+
+        // Reading the map back yields the same entry, so it is not rewritten on every run
+        let lister = LocalLister::new(file_path);
+        assert!(eq(
+            &lister.list().await?,
+            &[Entry {
+                id: "ID_1".to_string(),
+                code: "CODE_1".to_string(),
+                reason: "N/A".to_string(),
+                scopes: vec![],
+                expires_at: None,
+            }]
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_runs_neq_code() -> Result<(), Error> {
+        let entry = |code: &str| Entry {
+            id: "ID_1".to_string(),
+            code: code.to_string(),
+            reason: "REASON_1".to_string(),
+            scopes: vec![],
+            expires_at: None,
+        };
+
+        let (remote_entry, local_entry) = (entry("CODE_2"), entry("CODE_1"));
+
+        let mut remote_lister = MockList::new();
+        remote_lister
+            .expect_list()
+            .times(1)
+            .returning(move || Ok(vec![remote_entry.clone()]));
+
+        let mut local_lister = MockList::new();
+        local_lister
+            .expect_list()
+            .times(1)
+            .returning(move || Ok(vec![local_entry.clone()]));
+
+        let mut updater = MockUpdate::new();
+        updater
+            .expect_update()
+            .times(1)
+            .with(predicate::function(|entries: &Vec<Entry>| {
+                entries.len() == 1 && entries[0].code == "CODE_2"
+            }))
+            .returning(|_| Ok(()));
+
+        let mut runner = Runner::new(remote_lister, local_lister, updater);
+        runner.run().await?;
+
+        Ok(())
+    }
 }
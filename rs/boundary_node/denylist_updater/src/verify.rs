@@ -0,0 +1,55 @@
+use anyhow::{Context, Error};
+use async_trait::async_trait;
+use rsa::{Hash, PaddingScheme, PublicKey, RsaPublicKey};
+use sha2::{Digest, Sha256};
+
+#[async_trait]
+pub trait Verify: Send + Sync {
+    async fn verify(&self, data: &[u8], signature: &[u8]) -> Result<(), Error>;
+}
+
+// Verifies a detached RSA PKCS#1 v1.5 signature over the SHA-256 digest of the data
+pub struct Verifier {
+    key: RsaPublicKey,
+}
+
+impl Verifier {
+    pub fn new(key: RsaPublicKey) -> Self {
+        Self { key }
+    }
+}
+
+#[async_trait]
+impl Verify for Verifier {
+    async fn verify(&self, data: &[u8], signature: &[u8]) -> Result<(), Error> {
+        let digest = Sha256::digest(data);
+        let padding = PaddingScheme::new_pkcs1v15_sign(Some(Hash::SHA2_256));
+
+        self.key
+            .verify(padding, &digest, signature)
+            .context("invalid signature")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand_core::OsRng;
+    use rsa::RsaPrivateKey;
+
+    #[tokio::test]
+    async fn it_verifies_signatures() -> Result<(), Error> {
+        let private_key = RsaPrivateKey::new(&mut OsRng, 1024)?;
+        let verifier = Verifier::new(RsaPublicKey::from(&private_key));
+
+        let data = b"[]";
+        let padding = PaddingScheme::new_pkcs1v15_sign(Some(Hash::SHA2_256));
+        let signature = private_key.sign(padding, &Sha256::digest(data))?;
+
+        verifier.verify(data, &signature).await?;
+        assert!(verifier.verify(b"[{}]", &signature).await.is_err());
+
+        Ok(())
+    }
+}
@@ -1,23 +1,31 @@
 //! An agent to talk to the Internet Computer through the public endpoints.
 use crate::{
-    cbor::{parse_canister_query_response, parse_read_state_response, RequestStatus},
+    cbor::{
+        parse_canister_query_response, parse_certificate_tree, parse_node_public_keys,
+        parse_read_state_certificate, parse_request_status, RequestStatus,
+    },
+    certificate::{verify_canister_certificate, verify_subnet_certificate, ReadStateError},
     http_client::{HttpClient, HttpClientConfig},
 };
 use backoff::backoff::Backoff;
+use ed25519_dalek::Verifier;
 use ic_canister_client_sender::Sender;
-use ic_crypto_tree_hash::Path;
+use ic_crypto_tree_hash::{LabeledTree, Path};
 use ic_protobuf::types::v1 as pb;
 use ic_types::{
     consensus::catchup::CatchUpPackageParam,
+    crypto::threshold_sig::ThresholdSigPublicKey,
     messages::{
-        Blob, HttpCallContent, HttpQueryContent, HttpReadStateContent, HttpRequestEnvelope,
-        HttpStatusResponse, MessageId, ReplicaHealthStatus,
+        Blob, HttpCallContent, HttpQueryContent, HttpQueryResponse, HttpReadStateContent,
+        HttpRequestEnvelope, HttpSignedQueryResponse, HttpStatusResponse, MessageId,
+        QueryResponseHash, ReplicaHealthStatus,
     },
-    CanisterId,
+    time::current_time,
+    CanisterId, NodeId, SubnetId, Time,
 };
 use prost::Message;
 use serde_cbor::value::Value as CBOR;
-use std::{error::Error, fmt, sync::Arc, time::Duration, time::Instant};
+use std::{collections::BTreeMap, error::Error, fmt, sync::Arc, time::Duration, time::Instant};
 use tokio::time::sleep_until;
 use url::Url;
 
@@ -35,6 +43,10 @@ const MIN_POLL_INTERVAL: Duration = Duration::from_millis(500);
 const MAX_POLL_INTERVAL: Duration = Duration::from_secs(10);
 const POLL_INTERVAL_MULTIPLIER: f64 = 1.2;
 
+/// Maximum difference between the time of a certificate and the local time
+/// for the certificate to be accepted, if certificates are verified.
+const MAX_CERTIFICATE_AGE: Duration = Duration::from_secs(5 * 60);
+
 /// The HTTP path for query calls on the replica.
 // TODO is this how v1 api works can we just change the URL?
 pub fn query_path(cid: CanisterId) -> String {
@@ -45,11 +57,29 @@ pub fn read_state_path(cid: CanisterId) -> String {
     format!("api/v2/canister/{}/read_state", cid)
 }
 
+/// The HTTP path for reading subnet information from the certified state.
+pub fn subnet_read_state_path(subnet_id: SubnetId) -> String {
+    format!("api/v2/subnet/{}/read_state", subnet_id)
+}
+
 /// The HTTP path for update calls on the replica.
 pub fn update_path(cid: CanisterId) -> String {
     format!("api/v2/canister/{}/call", cid)
 }
 
+/// The HTTP path for update calls that wait for the certified reply.
+pub fn sync_update_path(cid: CanisterId) -> String {
+    format!("api/v3/canister/{}/call", cid)
+}
+
+/// Domain separator of the node signatures on query responses.
+const QUERY_RESPONSE_SIGNATURE_DOMAIN: &[u8] = b"\x0bic-response";
+
+/// DER prefix of an Ed25519 public key, followed by the 32 raw key bytes.
+const ED25519_DER_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
 const NODE_STATUS_PATH: &str = "api/v2/status";
 const CATCH_UP_PACKAGE_PATH: &str = "/_/catch_up_package";
 
@@ -81,6 +111,25 @@ pub struct Agent {
     // How long to wait for queries.
     query_timeout: Duration,
 
+    // Whether update calls are submitted to the synchronous call endpoint,
+    // which returns the certified reply directly if it is available in time.
+    sync_call: bool,
+
+    // The DER-encoded public keys of the nodes whose signatures on query
+    // responses are accepted. If set, query responses must be signed.
+    query_node_public_keys: Option<Arc<BTreeMap<NodeId, Vec<u8>>>>,
+
+    // The root public key of the IC, against which all certificates received
+    // by the agent are verified.
+    root_key: Option<ThresholdSigPublicKey>,
+
+    // Whether certificates are accepted without verification if no root key
+    // is set. Otherwise, reading state without a root key fails.
+    skip_certificate_verification: bool,
+
+    // How far the time of a certificate may be off the local time.
+    max_certificate_age: Duration,
+
     // Per reqwest document, cloning a client does not clone the actual connection pool inside.
     // Therefore directly owning a client as opposed to a reference is the standard way to go.
     http_client: Arc<HttpClient>,
@@ -98,6 +147,16 @@ impl fmt::Debug for Agent {
             .field("url", &self.url)
             .field("ingress_timeout", &self.ingress_timeout)
             .field("query_timeout", &self.query_timeout)
+            .field("sync_call", &self.sync_call)
+            .field(
+                "verify_query_signatures",
+                &self.query_node_public_keys.is_some(),
+            )
+            .field("verify_certificates", &self.root_key.is_some())
+            .field(
+                "skip_certificate_verification",
+                &self.skip_certificate_verification,
+            )
             .field("sender", &self.sender_field)
             .finish()
     }
@@ -136,7 +195,12 @@ impl Agent {
 
     /// This is needed by rust_canister tests
     pub fn new_for_test(&self, sender: Sender) -> Self {
-        Self::build_agent(self.url.clone(), self.http_client.clone(), sender)
+        Self {
+            root_key: self.root_key,
+            skip_certificate_verification: self.skip_certificate_verification,
+            max_certificate_age: self.max_certificate_age,
+            ..Self::build_agent(self.url.clone(), self.http_client.clone(), sender)
+        }
     }
 
     /// Helper to create the agent
@@ -146,6 +210,11 @@ impl Agent {
             url,
             ingress_timeout: INGRESS_TIMEOUT,
             query_timeout: QUERY_TIMEOUT,
+            sync_call: false,
+            query_node_public_keys: None,
+            root_key: None,
+            skip_certificate_verification: false,
+            max_certificate_age: MAX_CERTIFICATE_AGE,
             http_client,
             sender,
             sender_field,
@@ -164,6 +233,65 @@ impl Agent {
         self
     }
 
+    /// Submits update calls to the synchronous call endpoint
+    /// (`/api/v3/canister/<id>/call`). The replica must support it.
+    pub fn with_sync_call(mut self, sync_call: bool) -> Self {
+        self.sync_call = sync_call;
+        self
+    }
+
+    /// Requires query responses to be signed by one of the given nodes. The
+    /// keys can be obtained with [`Agent::node_public_keys`].
+    pub fn with_query_signature_verification(
+        mut self,
+        node_public_keys: BTreeMap<NodeId, Vec<u8>>,
+    ) -> Self {
+        self.query_node_public_keys = Some(Arc::new(node_public_keys));
+        self
+    }
+
+    /// Verifies every certificate the agent receives against `root_key`,
+    /// including subnet delegations and their canister ranges, and rejects
+    /// certificates whose time is too far off the local time.
+    ///
+    /// Without a root key, the agent fails to read any certified state, e.g.
+    /// the status of update calls, unless
+    /// [`Agent::with_insecure_skip_certificate_verification`] is used.
+    pub fn with_root_key(mut self, root_key: ThresholdSigPublicKey) -> Self {
+        self.root_key = Some(root_key);
+        self
+    }
+
+    /// Accepts certificates without verifying them if no root key is set.
+    ///
+    /// This is insecure: the replica, or anyone in between, can then forge
+    /// the replies of update calls and the node keys of subnets. Only use it
+    /// against test networks whose root key is not known to the caller.
+    pub fn with_insecure_skip_certificate_verification(mut self) -> Self {
+        self.skip_certificate_verification = true;
+        self
+    }
+
+    /// Sets how far the time of a certificate may be off the local time for
+    /// the certificate to be accepted. Only applies if a root key is set.
+    pub fn with_max_certificate_age(mut self, max_certificate_age: Duration) -> Self {
+        self.max_certificate_age = max_certificate_age;
+        self
+    }
+
+    /// Requires query responses to be signed by one of the nodes of
+    /// `subnet_id`, whose keys are read from the certified state of the
+    /// subnet. The certificate of the keys is verified against the root key,
+    /// so this also works for subnets other than the root subnet, whose
+    /// certificates are delegated by the root subnet.
+    pub async fn with_query_signature_verification_for_subnet(
+        self,
+        subnet_id: SubnetId,
+    ) -> Result<Self, ReadStateError> {
+        let node_public_keys = self.node_public_keys(subnet_id).await?;
+        Ok(self.with_query_signature_verification(node_public_keys))
+    }
+
     /// Queries the cup endpoint given the provided CatchUpPackageParams.
     pub async fn query_cup_endpoint(
         &self,
@@ -205,7 +333,7 @@ impl Agent {
         method: &str,
         arg: Vec<u8>,
     ) -> Result<Option<Vec<u8>>, String> {
-        let envelope = self
+        let (envelope, request_id) = self
             .prepare_query(canister_id, method, arg)
             .map_err(|e| format!("Failed to prepare query: {}", e))?;
         let bytes = self
@@ -219,6 +347,13 @@ impl Agent {
             .await?;
         let cbor = bytes_to_cbor(bytes)?;
 
+        if let Some(node_public_keys) = &self.query_node_public_keys {
+            let response =
+                serde_cbor::value::from_value::<HttpSignedQueryResponse>(cbor.clone())
+                    .map_err(|e| format!("decoding to HttpSignedQueryResponse failed: {}", e))?;
+            verify_query_signatures(&response, &request_id, node_public_keys)?;
+        }
+
         let call_response = parse_canister_query_response(&cbor)?;
         if call_response.status == "replied" {
             Ok(call_response.reply)
@@ -239,20 +374,42 @@ impl Agent {
         arguments: Vec<u8>,
         nonce: Vec<u8>,
     ) -> Result<Option<Vec<u8>>, String> {
+        // Fail before submitting a call whose reply could not be verified.
+        self.certificate_root_key().map_err(|e| e.to_string())?;
         let deadline = Instant::now() + self.ingress_timeout;
         let mut backoff = get_backoff_policy();
         let (http_body, request_id) = self
             .prepare_update(canister_id, method, arguments, nonce)
             .map_err(|err| format!("{}", err))?;
-        self.http_client
+        let path = if self.sync_call {
+            sync_update_path(*canister_id)
+        } else {
+            update_path(*canister_id)
+        };
+        let bytes = self
+            .http_client
             .post_with_response(
                 &self.url,
-                &update_path(*canister_id),
+                &path,
                 http_body,
                 tokio::time::Instant::from_std(deadline),
             )
             .await?;
 
+        // The synchronous endpoint answers with a request status certificate
+        // once the call has completed, or with an empty `202 Accepted` if it
+        // did not complete in time; in the latter case we fall back to polling.
+        if !bytes.is_empty() {
+            let certificate = parse_read_state_certificate(bytes_to_cbor(bytes)?)?;
+            let tree = self
+                .canister_state_tree(&certificate, canister_id)
+                .map_err(|e| e.to_string())?;
+            let request_status = parse_request_status(&request_id, &tree)?;
+            if let Some(result) = update_result(request_status) {
+                return result;
+            }
+        }
+
         // Check request status for the first time after 2s (~ time between blocks)
         let mut next_poll_time = Instant::now() + Duration::from_secs(2);
 
@@ -265,24 +422,11 @@ impl Agent {
                 .wait_ingress(request_id.clone(), deadline, canister_id)
                 .await
             {
-                Ok(request_status) => match request_status.status.as_ref() {
-                    "replied" => {
-                        return Ok(request_status.reply);
-                    }
-                    "done" => {
-                        return Err(
-                            "The call has completed but the reply/reject data has been pruned."
-                                .to_string(),
-                        );
+                Ok(request_status) => {
+                    if let Some(result) = update_result(request_status) {
+                        return result;
                     }
-                    "unknown" | "received" | "processing" => {}
-                    _ => {
-                        return Err(format!(
-                            "unexpected result: {:?} - {:?}",
-                            request_status.status, request_status.reject_message
-                        ))
-                    }
-                },
+                }
                 Err(e) => return Err(format!("Unexpected error: {:?}", e)),
             }
         }
@@ -292,49 +436,147 @@ impl Agent {
         ))
     }
 
-    /// Requests the status of a pending request once.
+    /// Requests the status of a pending canister update call request exactly
+    /// once using the `read_state` API.
     ///
     /// This is intended to be used in a loop until a final state is reached.
-    ///
-    /// Returns the entire CBOR value from the response, without trying to
-    /// interpret it.
-    async fn request_status_once(
+    pub async fn wait_ingress(
         &self,
         request_id: MessageId,
         deadline: Instant,
         canister_id: &CanisterId,
-    ) -> Result<CBOR, String> {
-        let path = Path::new(vec!["request_status".into(), request_id.into()]);
-        let status_request_body = self
-            .prepare_read_state(&[path])
-            .map_err(|e| format!("Failed to prepare read state: {:?}", e))?;
+    ) -> Result<RequestStatus, String> {
+        let path = Path::new(vec!["request_status".into(), request_id.clone().into()]);
+        let tree = self
+            .read_canister_state(canister_id, &[path], deadline)
+            .await
+            .map_err(|e| e.to_string())?;
+        parse_request_status(&request_id, &tree)
+    }
+
+    /// Reads the given paths from the certified state of `canister_id` and
+    /// returns the tree of the certificate.
+    ///
+    /// The certificate is verified unless verification is skipped, see
+    /// [`Agent::with_insecure_skip_certificate_verification`].
+    pub async fn read_canister_state(
+        &self,
+        canister_id: &CanisterId,
+        paths: &[Path],
+        deadline: Instant,
+    ) -> Result<LabeledTree<Vec<u8>>, ReadStateError> {
+        let certificate = self
+            .read_state(&read_state_path(*canister_id), paths, deadline)
+            .await?;
+        self.canister_state_tree(&certificate, canister_id)
+    }
+
+    /// Reads the given paths from the certified state of `subnet_id` and
+    /// returns the tree of the certificate.
+    ///
+    /// The certificate is verified unless verification is skipped, see
+    /// [`Agent::with_insecure_skip_certificate_verification`].
+    pub async fn read_subnet_state(
+        &self,
+        subnet_id: SubnetId,
+        paths: &[Path],
+        deadline: Instant,
+    ) -> Result<LabeledTree<Vec<u8>>, ReadStateError> {
+        let certificate = self
+            .read_state(&subnet_read_state_path(subnet_id), paths, deadline)
+            .await?;
+        match self.certificate_root_key()? {
+            Some(root_key) => verify_subnet_certificate(
+                &certificate,
+                &subnet_id,
+                root_key,
+                self.max_certificate_age,
+                current_time(),
+            ),
+            None => {
+                parse_certificate_tree(&certificate).map_err(ReadStateError::MalformedCertificate)
+            }
+        }
+    }
 
+    /// Sends a `read_state` request for the given paths to `path` and returns
+    /// the certificate of the response.
+    async fn read_state(
+        &self,
+        path: &str,
+        paths: &[Path],
+        deadline: Instant,
+    ) -> Result<Vec<u8>, ReadStateError> {
+        let body = self.prepare_read_state(paths).map_err(|e| {
+            ReadStateError::Request(format!("Failed to prepare read state: {:?}", e))
+        })?;
         let bytes = self
             .http_client
             .post_with_response(
                 &self.url,
-                &read_state_path(*canister_id),
-                status_request_body,
+                path,
+                body,
                 tokio::time::Instant::from_std(deadline),
             )
-            .await?;
+            .await
+            .map_err(ReadStateError::Request)?;
         bytes_to_cbor(bytes)
+            .and_then(parse_read_state_certificate)
+            .map_err(ReadStateError::Request)
     }
 
-    /// Requests the status of a pending canister update call request exactly
-    /// once using the `read_state` API.
-    ///
-    /// This is intended to be used in a loop until a final state is reached.
-    pub async fn wait_ingress(
+    /// Returns the root key to verify certificates against, or `None` if
+    /// verification is skipped.
+    fn certificate_root_key(&self) -> Result<Option<&ThresholdSigPublicKey>, ReadStateError> {
+        match (&self.root_key, self.skip_certificate_verification) {
+            (Some(root_key), _) => Ok(Some(root_key)),
+            (None, true) => Ok(None),
+            (None, false) => Err(ReadStateError::MissingRootKey),
+        }
+    }
+
+    /// Returns the tree of a certificate of the state of `canister_id`,
+    /// verifying the certificate unless verification is skipped.
+    fn canister_state_tree(
         &self,
-        request_id: MessageId,
-        deadline: Instant,
+        certificate: &[u8],
         canister_id: &CanisterId,
-    ) -> Result<RequestStatus, String> {
-        let cbor = self
-            .request_status_once(request_id.clone(), deadline, canister_id)
+    ) -> Result<LabeledTree<Vec<u8>>, ReadStateError> {
+        match self.certificate_root_key()? {
+            Some(root_key) => verify_canister_certificate(
+                certificate,
+                canister_id,
+                root_key,
+                self.max_certificate_age,
+                current_time(),
+            ),
+            None => {
+                parse_certificate_tree(certificate).map_err(ReadStateError::MalformedCertificate)
+            }
+        }
+    }
+
+    /// Reads the DER-encoded public keys of the nodes of `subnet_id` from the
+    /// certified state of that subnet.
+    ///
+    /// Fails if the subnet does not certify node public keys yet, i.e. if it
+    /// certifies its state with a certification version below 10.
+    ///
+    /// Note that the certificate of the response is not verified if
+    /// verification is skipped.
+    pub async fn node_public_keys(
+        &self,
+        subnet_id: SubnetId,
+    ) -> Result<BTreeMap<NodeId, Vec<u8>>, ReadStateError> {
+        let path = Path::new(vec![
+            "subnet".into(),
+            subnet_id.get().into_vec().into(),
+            "node".into(),
+        ]);
+        let tree = self
+            .read_subnet_state(subnet_id, &[path], Instant::now() + self.query_timeout)
             .await?;
-        parse_read_state_response(&request_id, cbor)
+        parse_node_public_keys(subnet_id, &tree).map_err(ReadStateError::UnexpectedContent)
     }
 
     async fn get_status(&self) -> Result<HttpStatusResponse, String> {
@@ -370,6 +612,82 @@ impl Agent {
     }
 }
 
+/// Maps the status of an update call to its result, or `None` if the call has
+/// not completed yet.
+fn update_result(request_status: RequestStatus) -> Option<Result<Option<Vec<u8>>, String>> {
+    match request_status.status.as_ref() {
+        "replied" => Some(Ok(request_status.reply)),
+        "done" => Some(Err(
+            "The call has completed but the reply/reject data has been pruned.".to_string(),
+        )),
+        "unknown" | "received" | "processing" => None,
+        _ => Some(Err(format!(
+            "unexpected result: {:?} - {:?}",
+            request_status.status, request_status.reject_message
+        ))),
+    }
+}
+
+/// Checks that a query response carries at least one signature and that all of
+/// its signatures are valid signatures of nodes in `node_public_keys`.
+fn verify_query_signatures(
+    response: &HttpSignedQueryResponse,
+    request_id: &MessageId,
+    node_public_keys: &BTreeMap<NodeId, Vec<u8>>,
+) -> Result<(), String> {
+    if response.signatures.is_empty() {
+        return Err("The query response is not signed".to_string());
+    }
+    for node_signature in &response.signatures {
+        let node_id = ic_types::PrincipalId::try_from(node_signature.identity.0.as_slice())
+            .map(NodeId::from)
+            .map_err(|e| format!("Invalid node id in query response signature: {}", e))?;
+        let public_key = node_public_keys
+            .get(&node_id)
+            .ok_or_else(|| format!("The query response is signed by unknown node {}", node_id))?;
+        verify_query_response_signature(
+            &response.response,
+            request_id,
+            node_signature.timestamp,
+            &node_signature.signature.0,
+            public_key,
+        )
+        .map_err(|e| {
+            format!(
+                "Invalid signature of node {} on the query response: {}",
+                node_id, e
+            )
+        })?;
+    }
+    Ok(())
+}
+
+fn verify_query_response_signature(
+    response: &HttpQueryResponse,
+    request_id: &MessageId,
+    timestamp: u64,
+    signature: &[u8],
+    public_key_der: &[u8],
+) -> Result<(), String> {
+    let raw_public_key = public_key_der
+        .strip_prefix(&ED25519_DER_PREFIX[..])
+        .ok_or_else(|| "node public key is not a DER-encoded Ed25519 key".to_string())?;
+    let public_key =
+        ed25519_dalek::PublicKey::from_bytes(raw_public_key).map_err(|e| e.to_string())?;
+    let signature = ed25519_dalek::Signature::try_from(signature).map_err(|e| e.to_string())?;
+
+    let hash = QueryResponseHash::new(
+        response,
+        request_id,
+        Time::from_nanos_since_unix_epoch(timestamp),
+    );
+    let mut message = QUERY_RESPONSE_SIGNATURE_DOMAIN.to_vec();
+    message.extend_from_slice(hash.as_bytes());
+    public_key
+        .verify(&message, &signature)
+        .map_err(|e| e.to_string())
+}
+
 /// Wraps the content into an envelope that contains the message signature.
 ///
 /// Prerequisite: `content` contains a `sender` field that is compatible with
@@ -462,10 +780,16 @@ fn bytes_to_cbor(bytes: Vec<u8>) -> Result<CBOR, String> {
 mod tests {
     use super::*;
     use crate::ed25519_public_key_to_der;
+    use ic_certification_test_utils::{
+        encoded_time, CertificateBuilder, CertificateData::CustomTree, CertificateData::SubnetData,
+    };
+    use ic_crypto_tree_hash::{flatmap, FlatMap, Label};
     use ic_test_utilities::crypto::temp_crypto_component_with_fake_registry;
-    use ic_test_utilities::types::ids::node_test_id;
+    use ic_test_utilities::types::ids::{node_test_id, subnet_test_id};
     use ic_types::malicious_flags::MaliciousFlags;
-    use ic_types::messages::{HttpCanisterUpdate, HttpRequest, HttpUserQuery, UserQuery};
+    use ic_types::messages::{
+        HttpCanisterUpdate, HttpReadStateResponse, HttpRequest, HttpUserQuery, UserQuery,
+    };
     use ic_types::time::current_time;
     use ic_types::{PrincipalId, RegistryVersion, UserId};
     use ic_validator::get_authorized_canisters;
@@ -723,4 +1047,209 @@ mod tests {
             &MaliciousFlags::default(),
         ));
     }
+
+    #[test]
+    fn verify_signed_query_response() {
+        use ed25519_dalek::Signer;
+        use ic_types::messages::{HttpQueryResponseReply, NodeSignature};
+
+        let mut rng = ChaChaRng::seed_from_u64(1_u64);
+        let keypair = ed25519_dalek::Keypair::generate(&mut rng);
+        let node_id = node_test_id(1);
+        let node_public_keys = BTreeMap::from([(
+            node_id,
+            ed25519_public_key_to_der(keypair.public.to_bytes().to_vec()),
+        )]);
+
+        let request_id = MessageId::from([7; 32]);
+        let timestamp = 1_650_000_000_000_000_000;
+        let response = HttpQueryResponse::Replied {
+            reply: HttpQueryResponseReply {
+                arg: Blob(b"reply".to_vec()),
+            },
+        };
+        let hash = QueryResponseHash::new(
+            &response,
+            &request_id,
+            Time::from_nanos_since_unix_epoch(timestamp),
+        );
+        let mut message = QUERY_RESPONSE_SIGNATURE_DOMAIN.to_vec();
+        message.extend_from_slice(hash.as_bytes());
+        let signed_response = HttpSignedQueryResponse {
+            response,
+            signatures: vec![NodeSignature {
+                timestamp,
+                signature: Blob(keypair.sign(&message).to_bytes().to_vec()),
+                identity: Blob(node_id.get().into_vec()),
+            }],
+        };
+
+        assert_ok!(verify_query_signatures(
+            &signed_response,
+            &request_id,
+            &node_public_keys
+        ));
+        // The signature does not cover a different request.
+        assert!(verify_query_signatures(
+            &signed_response,
+            &MessageId::from([8; 32]),
+            &node_public_keys
+        )
+        .is_err());
+        // Signatures of unknown nodes are rejected.
+        assert!(verify_query_signatures(&signed_response, &request_id, &BTreeMap::new()).is_err());
+        // Unsigned responses are rejected.
+        let unsigned_response = HttpSignedQueryResponse {
+            signatures: vec![],
+            ..signed_response
+        };
+        assert!(
+            verify_query_signatures(&unsigned_response, &request_id, &node_public_keys).is_err()
+        );
+    }
+
+    #[test]
+    fn certificates_are_not_accepted_without_root_key() {
+        let agent = Agent::new(Url::parse("http://localhost").unwrap(), Sender::Anonymous);
+        let canister_id = CanisterId::from_u64(1);
+        assert!(matches!(
+            agent.canister_state_tree(&[], &canister_id),
+            Err(ReadStateError::MissingRootKey)
+        ));
+
+        // Skipping verification only leaves the certificate to be decoded.
+        let agent = agent.with_insecure_skip_certificate_verification();
+        assert!(matches!(
+            agent.canister_state_tree(&[], &canister_id),
+            Err(ReadStateError::MalformedCertificate(_))
+        ));
+    }
+
+    /// Starts a fake replica that answers every request with a certificate of
+    /// the tree `tree` derives from the request body, plus the current time.
+    /// The certificate is signed by `subnet_id`, whose delegation by the
+    /// returned root key covers canisters 0 to 10.
+    fn fake_replica<F>(subnet_id: SubnetId, tree: F) -> (Url, ThresholdSigPublicKey)
+    where
+        F: Fn(&[u8]) -> FlatMap<Label, LabeledTree<Vec<u8>>> + Send + Sync + 'static,
+    {
+        use hyper::service::{make_service_fn, service_fn};
+        use hyper::{Body, Response, Server};
+
+        let builder = CertificateBuilder::new(CustomTree(LabeledTree::SubTree(FlatMap::new())))
+            .with_delegation(CertificateBuilder::new(SubnetData {
+                subnet_id,
+                canister_id_ranges: vec![(CanisterId::from_u64(0), CanisterId::from_u64(10))],
+            }));
+        let root_key = builder.get_root_public_key();
+
+        let respond = Arc::new(move |body: &[u8]| {
+            let mut subtrees = tree(body);
+            subtrees
+                .try_append(
+                    Label::from("time"),
+                    LabeledTree::Leaf(encoded_time(current_time().as_nanos_since_unix_epoch())),
+                )
+                .unwrap();
+            let (_cert, _root_key, certificate) = builder
+                .clone()
+                .with_data(CustomTree(LabeledTree::SubTree(subtrees)))
+                .build();
+            serde_cbor::to_vec(&HttpReadStateResponse {
+                certificate: Blob(certificate),
+            })
+            .unwrap()
+        });
+        let make_service = make_service_fn(move |_| {
+            let respond = respond.clone();
+            async move {
+                Ok::<_, hyper::Error>(service_fn(move |request: hyper::Request<Body>| {
+                    let respond = respond.clone();
+                    async move {
+                        let body = hyper::body::to_bytes(request.into_body()).await?;
+                        Ok::<_, hyper::Error>(Response::new(Body::from(respond(&body[..]))))
+                    }
+                }))
+            }
+        });
+
+        let server = Server::bind(&([127, 0, 0, 1], 0).into())
+            .http2_only(true)
+            .serve(make_service);
+        let url = Url::parse(&format!("http://{}", server.local_addr())).unwrap();
+        tokio::spawn(server);
+        (url, root_key)
+    }
+
+    #[tokio::test]
+    async fn execute_update_verifies_delegated_certificate() {
+        let (url, root_key) = fake_replica(subnet_test_id(1), |body| {
+            let envelope: HttpRequestEnvelope<HttpCallContent> =
+                serde_cbor::from_slice(body).unwrap();
+            let HttpCallContent::Call { update } = envelope.content;
+            flatmap![
+                Label::from("request_status") => LabeledTree::SubTree(flatmap![
+                    Label::from(update.id().as_bytes().to_vec()) => LabeledTree::SubTree(flatmap![
+                        Label::from("reply") => LabeledTree::Leaf(b"reply".to_vec()),
+                        Label::from("status") => LabeledTree::Leaf(b"replied".to_vec()),
+                    ]),
+                ]),
+            ]
+        });
+        let agent = Agent::new(url, Sender::Anonymous).with_sync_call(true);
+        let canister_id = CanisterId::from_u64(1);
+
+        let result = agent
+            .clone()
+            .with_root_key(root_key)
+            .execute_update(&canister_id, "foo", vec![], vec![])
+            .await;
+        assert_eq!(result, Ok(Some(b"reply".to_vec())));
+
+        // The same certificate does not verify against another root key.
+        let other_root_key =
+            CertificateBuilder::new(CustomTree(LabeledTree::SubTree(FlatMap::new())))
+                .get_root_public_key();
+        let result = agent
+            .with_root_key(other_root_key)
+            .execute_update(&canister_id, "foo", vec![], vec![])
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn read_subnet_state_verifies_delegated_certificate() {
+        let subnet_id = subnet_test_id(1);
+        let node_id = node_test_id(1);
+        let (url, root_key) = fake_replica(subnet_id, move |_body| {
+            flatmap![
+                Label::from("subnet") => LabeledTree::SubTree(flatmap![
+                    Label::from(subnet_id.get().into_vec()) => LabeledTree::SubTree(flatmap![
+                        Label::from("node") => LabeledTree::SubTree(flatmap![
+                            Label::from(node_id.get().into_vec()) => LabeledTree::SubTree(flatmap![
+                                Label::from("public_key") => LabeledTree::Leaf(vec![1, 2, 3]),
+                            ]),
+                        ]),
+                    ]),
+                ]),
+            ]
+        });
+        let agent = Agent::new(url, Sender::Anonymous).with_root_key(root_key);
+
+        assert_eq!(
+            agent.node_public_keys(subnet_id).await.unwrap(),
+            BTreeMap::from([(node_id, vec![1, 2, 3])])
+        );
+
+        // The delegation is only valid for the subnet it was issued to.
+        let path = Path::new(vec!["subnet".into()]);
+        assert!(agent
+            .read_subnet_state(
+                subnet_test_id(2),
+                &[path],
+                Instant::now() + Duration::from_secs(10)
+            )
+            .await
+            .is_err());
+    }
 }
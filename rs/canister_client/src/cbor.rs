@@ -8,9 +8,10 @@ use ic_types::{
         SignedRequestBytes,
     },
     time::current_time_and_expiry_time,
-    CanisterId,
+    CanisterId, NodeId, PrincipalId, SubnetId,
 };
 use serde::Deserialize;
+use serde_bytes::ByteBuf;
 use serde_cbor::value::Value as CBOR;
 use std::collections::BTreeMap;
 use std::convert::TryFrom;
@@ -40,24 +41,61 @@ impl RequestStatus {
     }
 }
 
+// An auxiliary structure that mirrors the node public keys encoded in a
+// certificate, starting from the root of the tree.
+#[derive(Debug, Deserialize)]
+struct SubnetNodes {
+    subnet: Option<BTreeMap<ByteBuf, NodesOfSubnet>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NodesOfSubnet {
+    node: Option<BTreeMap<ByteBuf, NodePublicKey>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NodePublicKey {
+    public_key: ByteBuf,
+}
+
+/// Extracts the CBOR encoded certificate from a `read_state` response.
+pub(crate) fn parse_read_state_certificate(message: CBOR) -> Result<Vec<u8>, String> {
+    let response = serde_cbor::value::from_value::<HttpReadStateResponse>(message)
+        .map_err(|source| format!("decoding to HttpReadStateResponse failed: {}", source))?;
+
+    Ok(response.certificate.0)
+}
+
+/// Decodes a certificate and returns its tree, without verifying it.
+pub(crate) fn parse_certificate_tree(certificate: &[u8]) -> Result<LabeledTree<Vec<u8>>, String> {
+    let certificate: Certificate = serde_cbor::from_slice(certificate)
+        .map_err(|source| format!("decoding Certificate failed: {}", source))?;
+
+    LabeledTree::try_from(certificate.tree)
+        .map_err(|e| format!("parsing tree in certificate failed: {:?}", e))
+}
+
 /// Given a CBOR response from a `read_state` and a `request_id` extracts
 /// the `RequestStatus` if available.
+///
+/// Note that the certificate of the response is not verified.
 pub fn parse_read_state_response(
     request_id: &MessageId,
     message: CBOR,
 ) -> Result<RequestStatus, String> {
-    let response = serde_cbor::value::from_value::<HttpReadStateResponse>(message)
-        .map_err(|source| format!("decoding to HttpReadStateResponse failed: {}", source))?;
-
-    let certificate: Certificate = serde_cbor::from_slice(response.certificate.as_slice())
-        .map_err(|source| format!("decoding Certificate failed: {}", source))?;
-
-    // Parse the tree.
-    let tree = LabeledTree::try_from(certificate.tree)
-        .map_err(|e| format!("parsing tree in certificate failed: {:?}", e))?;
+    let certificate = parse_read_state_certificate(message)?;
+    let tree = parse_certificate_tree(&certificate)?;
+    parse_request_status(request_id, &tree)
+}
 
+/// Extracts the `RequestStatus` of `request_id` from the tree of a
+/// certificate, if available.
+pub(crate) fn parse_request_status(
+    request_id: &MessageId,
+    tree: &LabeledTree<Vec<u8>>,
+) -> Result<RequestStatus, String> {
     let request_statuses =
-        RequestStatuses::deserialize(tree_deserializer::LabeledTreeDeserializer::new(&tree))
+        RequestStatuses::deserialize(tree_deserializer::LabeledTreeDeserializer::new(tree))
             .map_err(|err| format!("deserializing request statuses failed: {:?}", err))?;
 
     Ok(match request_statuses.request_status {
@@ -68,6 +106,38 @@ pub fn parse_read_state_response(
     })
 }
 
+/// Extracts the DER-encoded public keys of the nodes of `subnet_id` from the
+/// tree of a certificate for the `subnet` subtree.
+pub(crate) fn parse_node_public_keys(
+    subnet_id: SubnetId,
+    tree: &LabeledTree<Vec<u8>>,
+) -> Result<BTreeMap<NodeId, Vec<u8>>, String> {
+    let subnet_nodes =
+        SubnetNodes::deserialize(tree_deserializer::LabeledTreeDeserializer::new(tree))
+            .map_err(|err| format!("deserializing node public keys failed: {:?}", err))?;
+
+    let nodes = subnet_nodes
+        .subnet
+        .and_then(|mut subnets| subnets.remove(subnet_id.get().as_slice()))
+        .and_then(|subnet| subnet.node)
+        .ok_or_else(|| {
+            format!(
+                "no node public keys found for subnet {}; they are only certified from \
+                 certification version 10 on",
+                subnet_id
+            )
+        })?;
+
+    nodes
+        .into_iter()
+        .map(|(node_id, node)| {
+            let node_id = PrincipalId::try_from(node_id.as_slice())
+                .map_err(|err| format!("invalid node id in certificate: {}", err))?;
+            Ok((NodeId::from(node_id), node.public_key.into_vec()))
+        })
+        .collect()
+}
+
 /// Given a CBOR response from a `query`, extract the response.
 pub(crate) fn parse_canister_query_response(message: &CBOR) -> Result<RequestStatus, String> {
     let content = match message {
@@ -178,7 +248,7 @@ impl Agent {
         canister_id: &CanisterId,
         method: &str,
         arguments: Vec<u8>,
-    ) -> Result<Vec<u8>, Box<dyn Error>> {
+    ) -> Result<(Vec<u8>, MessageId), Box<dyn Error>> {
         let content = HttpQueryContent::Query {
             query: HttpUserQuery {
                 canister_id: to_blob(canister_id),
@@ -190,8 +260,9 @@ impl Agent {
             },
         };
 
+        let request_id = content.id();
         let request = sign_query(content, &self.sender)?;
-        Ok(SignedRequestBytes::try_from(request)?.into())
+        Ok((SignedRequestBytes::try_from(request)?.into(), request_id))
     }
 
     /// Prepares and serializes a CBOR read_state request, with the given paths
@@ -0,0 +1,238 @@
+//! Verification of the certificates the agent receives in `read_state`
+//! responses.
+use ic_certification::{
+    verify_canister_state_certificate, verify_subnet_state_certificate, CertificateValidationError,
+};
+use ic_crypto_tree_hash::LabeledTree;
+use ic_types::{crypto::threshold_sig::ThresholdSigPublicKey, CanisterId, SubnetId, Time};
+use std::{error::Error, fmt, time::Duration};
+
+/// Describes an error that occurred while reading the certified state of a
+/// canister or subnet.
+#[derive(Debug)]
+pub enum ReadStateError {
+    /// The request could not be sent, or the response is not a `read_state`
+    /// response.
+    Request(String),
+    /// The agent has no root key to verify the certificate against, and
+    /// verification is not explicitly skipped.
+    MissingRootKey,
+    /// The certificate or its tree could not be decoded.
+    MalformedCertificate(String),
+    /// The certificate is not validly signed by the root key, or by a subnet
+    /// that the root key delegated the canister or subnet to.
+    ForgedCertificate(CertificateValidationError),
+    /// The time of the certificate is further off the local time than the
+    /// agent accepts, e.g. because an old response was replayed.
+    StaleCertificate { certificate_time: Time, now: Time },
+    /// The certified state does not contain the expected data.
+    UnexpectedContent(String),
+}
+
+impl fmt::Display for ReadStateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Request(err) => write!(f, "read_state request failed: {}", err),
+            Self::MissingRootKey => write!(f, "no root key to verify the certificate against"),
+            Self::MalformedCertificate(err) => write!(f, "malformed certificate: {}", err),
+            Self::ForgedCertificate(err) => write!(f, "invalid certificate: {}", err),
+            Self::StaleCertificate {
+                certificate_time,
+                now,
+            } => write!(
+                f,
+                "stale certificate: certificate time {} is too far off the current time {}",
+                certificate_time, now
+            ),
+            Self::UnexpectedContent(err) => write!(f, "unexpected certified state: {}", err),
+        }
+    }
+}
+
+impl Error for ReadStateError {}
+
+impl From<CertificateValidationError> for ReadStateError {
+    fn from(err: CertificateValidationError) -> Self {
+        match err {
+            CertificateValidationError::DeserError(_)
+            | CertificateValidationError::MalformedHashTree(_) => {
+                Self::MalformedCertificate(err.to_string())
+            }
+            err => Self::ForgedCertificate(err),
+        }
+    }
+}
+
+/// Verifies the certificate of a `read_state` response of `canister_id` and
+/// returns its tree.
+///
+/// Verification ensures that the certificate is signed by `root_key`, or by a
+/// subnet whose delegation by `root_key` covers `canister_id`, and that its
+/// time is at most `max_certificate_age` off `now`.
+pub(crate) fn verify_canister_certificate(
+    certificate: &[u8],
+    canister_id: &CanisterId,
+    root_key: &ThresholdSigPublicKey,
+    max_certificate_age: Duration,
+    now: Time,
+) -> Result<LabeledTree<Vec<u8>>, ReadStateError> {
+    let (tree, time) = verify_canister_state_certificate(certificate, canister_id, root_key)?;
+    verify_freshness(time, max_certificate_age, now)?;
+    Ok(tree)
+}
+
+/// Verifies the certificate of a `read_state` response of `subnet_id` and
+/// returns its tree.
+///
+/// Verification ensures that the certificate is signed by `root_key`, or by
+/// `subnet_id` with a delegation by `root_key`, and that its time is at most
+/// `max_certificate_age` off `now`.
+pub(crate) fn verify_subnet_certificate(
+    certificate: &[u8],
+    subnet_id: &SubnetId,
+    root_key: &ThresholdSigPublicKey,
+    max_certificate_age: Duration,
+    now: Time,
+) -> Result<LabeledTree<Vec<u8>>, ReadStateError> {
+    let (tree, time) = verify_subnet_state_certificate(certificate, subnet_id, root_key)?;
+    verify_freshness(time, max_certificate_age, now)?;
+    Ok(tree)
+}
+
+fn verify_freshness(
+    certificate_time: Time,
+    max_certificate_age: Duration,
+    now: Time,
+) -> Result<(), ReadStateError> {
+    let (certificate_nanos, now_nanos) = (
+        certificate_time.as_nanos_since_unix_epoch(),
+        now.as_nanos_since_unix_epoch(),
+    );
+    // Certificates from the future are rejected as well, as they indicate that
+    // either clock is off.
+    let offset =
+        Duration::from_nanos(certificate_nanos.max(now_nanos) - certificate_nanos.min(now_nanos));
+    if offset > max_certificate_age {
+        return Err(ReadStateError::StaleCertificate {
+            certificate_time,
+            now,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_certification_test_utils::{
+        encoded_time, CertificateBuilder, CertificateData::CustomTree, CertificateData::SubnetData,
+    };
+    use ic_crypto_tree_hash::{flatmap, Label};
+    use ic_types::PrincipalId;
+
+    const CERTIFICATE_TIME: u64 = 1_650_000_000_000_000_000;
+    const MAX_CERTIFICATE_AGE: Duration = Duration::from_secs(300);
+
+    fn subnet_id(id: u64) -> SubnetId {
+        SubnetId::from(PrincipalId::new_subnet_test_id(id))
+    }
+
+    fn delegated_certificate(
+        subnet_id: SubnetId,
+        canister_id_ranges: Vec<(CanisterId, CanisterId)>,
+    ) -> CertificateBuilder {
+        CertificateBuilder::new(CustomTree(LabeledTree::SubTree(flatmap![
+            Label::from("time") => LabeledTree::Leaf(encoded_time(CERTIFICATE_TIME)),
+        ])))
+        .with_delegation(CertificateBuilder::new(SubnetData {
+            subnet_id,
+            canister_id_ranges,
+        }))
+    }
+
+    fn now() -> Time {
+        Time::from_nanos_since_unix_epoch(CERTIFICATE_TIME)
+    }
+
+    #[test]
+    fn verify_canister_certificate_with_delegation() {
+        let ranges = vec![(CanisterId::from_u64(0), CanisterId::from_u64(10))];
+        let (_cert, root_key, cbor) = delegated_certificate(subnet_id(1), ranges).build();
+
+        assert!(verify_canister_certificate(
+            &cbor,
+            &CanisterId::from_u64(5),
+            &root_key,
+            MAX_CERTIFICATE_AGE,
+            now()
+        )
+        .is_ok());
+        // The delegation does not cover other canisters.
+        assert!(matches!(
+            verify_canister_certificate(
+                &cbor,
+                &CanisterId::from_u64(11),
+                &root_key,
+                MAX_CERTIFICATE_AGE,
+                now()
+            ),
+            Err(ReadStateError::ForgedCertificate(
+                CertificateValidationError::CanisterIdOutOfRange
+            ))
+        ));
+    }
+
+    #[test]
+    fn verify_subnet_certificate_with_delegation() {
+        let ranges = vec![(CanisterId::from_u64(0), CanisterId::from_u64(10))];
+        let (_cert, root_key, cbor) = delegated_certificate(subnet_id(1), ranges).build();
+
+        assert!(verify_subnet_certificate(
+            &cbor,
+            &subnet_id(1),
+            &root_key,
+            MAX_CERTIFICATE_AGE,
+            now()
+        )
+        .is_ok());
+        // The delegation does not cover other subnets.
+        assert!(matches!(
+            verify_subnet_certificate(&cbor, &subnet_id(2), &root_key, MAX_CERTIFICATE_AGE, now()),
+            Err(ReadStateError::ForgedCertificate(
+                CertificateValidationError::SubnetIdMismatch { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn reject_certificate_of_other_root_key() {
+        let ranges = vec![(CanisterId::from_u64(0), CanisterId::from_u64(10))];
+        let (_cert, _root_key, cbor) = delegated_certificate(subnet_id(1), ranges.clone()).build();
+        let (_cert, other_root_key, _cbor) = delegated_certificate(subnet_id(1), ranges).build();
+
+        assert!(matches!(
+            verify_subnet_certificate(
+                &cbor,
+                &subnet_id(1),
+                &other_root_key,
+                MAX_CERTIFICATE_AGE,
+                now()
+            ),
+            Err(ReadStateError::ForgedCertificate(
+                CertificateValidationError::InvalidSignature(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn reject_stale_certificate() {
+        let ranges = vec![(CanisterId::from_u64(0), CanisterId::from_u64(10))];
+        let (_cert, root_key, cbor) = delegated_certificate(subnet_id(1), ranges).build();
+
+        let later = now() + MAX_CERTIFICATE_AGE + Duration::from_secs(1);
+        assert!(matches!(
+            verify_subnet_certificate(&cbor, &subnet_id(1), &root_key, MAX_CERTIFICATE_AGE, later),
+            Err(ReadStateError::StaleCertificate { .. })
+        ));
+    }
+}
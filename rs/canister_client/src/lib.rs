@@ -2,10 +2,15 @@
 mod agent;
 mod canister_management;
 mod cbor;
+mod certificate;
 mod http_client;
 
-pub use agent::{get_backoff_policy, query_path, read_state_path, update_path, Agent};
+pub use agent::{
+    get_backoff_policy, query_path, read_state_path, subnet_read_state_path, sync_update_path,
+    update_path, Agent,
+};
 pub use cbor::parse_read_state_response;
+pub use certificate::ReadStateError;
 pub use http_client::{HttpClient, HttpClientConfig};
 pub use hyper::StatusCode as HttpStatusCode;
 pub use ic_canister_client_sender::{ed25519_public_key_to_der, Sender};
@@ -25,6 +25,10 @@ pub enum CertificationVersion {
     V8 = 8,
     /// Producing non-empty `StreamHeader::reject_signals`.
     V9 = 9,
+    /// Added node public keys to the subnet topology.
+    V10 = 10,
+    /// Added the metrics of the own subnet to the subnet topology.
+    V11 = 11,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -57,13 +61,26 @@ impl std::convert::TryFrom<u32> for CertificationVersion {
 
 /// The Canonical State certification version that should be used for newly
 /// computed states.
+///
+/// A replica must be able to read a certification version before any replica
+/// on its subnet certifies with it, so new versions are rolled out in two
+/// releases: the first one only raises [MAX_SUPPORTED_CERTIFICATION_VERSION],
+/// the following one raises this constant.
+///
+/// Rollout in progress:
+/// * `V10` (node public keys) is supported by this release; this constant is
+///   raised to `V10` in the next release. Until then `/subnet/<id>/node`
+///   paths are absent from certified states.
+/// * `V11` (metrics of the own subnet) is supported by this release; this
+///   constant is raised to `V11` in the release after the one raising it to
+///   `V10`. Until then `/subnet/<id>/metrics` is absent from certified states.
 pub const CURRENT_CERTIFICATION_VERSION: CertificationVersion = CertificationVersion::V9;
 
 /// Maximum supported certification version.
 ///
 /// The replica will panic if requested to certify using a version higher than
 /// this.
-pub const MAX_SUPPORTED_CERTIFICATION_VERSION: CertificationVersion = CertificationVersion::V9;
+pub const MAX_SUPPORTED_CERTIFICATION_VERSION: CertificationVersion = CertificationVersion::V11;
 
 /// Returns a list of all certification versions up to [MAX_SUPPORTED_CERTIFICATION_VERSION].
 pub fn all_supported_versions() -> impl std::iter::Iterator<Item = CertificationVersion> {
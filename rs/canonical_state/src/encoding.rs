@@ -12,7 +12,7 @@
 
 use crate::CertificationVersion;
 use ic_protobuf::proxy::ProxyDecodeError;
-use ic_replicated_state::metadata_state::SystemMetadata;
+use ic_replicated_state::metadata_state::{SubnetMetrics, SystemMetadata};
 use ic_types::{messages::RequestOrResponse, xnet::StreamHeader, PrincipalId};
 use serde::Serialize;
 use std::collections::BTreeSet;
@@ -113,6 +113,29 @@ pub fn encode_subnet_canister_ranges(ranges: Option<&Vec<(PrincipalId, Principal
     serializer.into_inner()
 }
 
+/// Serializes the metrics of a subnet as a self-describing CBOR map with the
+/// fields `num_canisters`, `canister_state_bytes` and
+/// `update_transactions_total`.
+pub fn encode_subnet_metrics(metrics: &SubnetMetrics) -> Vec<u8> {
+    #[derive(Serialize)]
+    struct EncodedSubnetMetrics {
+        num_canisters: u64,
+        canister_state_bytes: u64,
+        update_transactions_total: u64,
+    }
+
+    let mut serializer = serde_cbor::Serializer::new(vec![]);
+    serializer.self_describe().unwrap();
+    EncodedSubnetMetrics {
+        num_canisters: metrics.num_canisters,
+        canister_state_bytes: metrics.canister_state_bytes.get(),
+        update_transactions_total: metrics.update_transactions_total,
+    }
+    .serialize(&mut serializer)
+    .unwrap();
+    serializer.into_inner()
+}
+
 /// Serializes controllers as a CBOR list.
 ///
 /// From the spec:
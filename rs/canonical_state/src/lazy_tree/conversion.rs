@@ -4,7 +4,7 @@ use super::{blob, fork, num, string, Lazy, LazyFork, LazyTree};
 use crate::{
     encoding::{
         encode_controllers, encode_message, encode_metadata, encode_stream_header,
-        encode_subnet_canister_ranges,
+        encode_subnet_canister_ranges, encode_subnet_metrics,
     },
     CertificationVersion, MAX_SUPPORTED_CERTIFICATION_VERSION,
 };
@@ -13,7 +13,9 @@ use ic_error_types::RejectCode;
 use ic_registry_routing_table::RoutingTable;
 use ic_replicated_state::{
     canister_state::CanisterState,
-    metadata_state::{IngressHistoryState, StreamMap, SubnetTopology, SystemMetadata},
+    metadata_state::{
+        IngressHistoryState, NodeTopology, StreamMap, SubnetMetrics, SubnetTopology, SystemMetadata,
+    },
     replicated_state::ReplicatedStateMessageRouting,
     ExecutionState, ReplicatedState,
 };
@@ -21,7 +23,7 @@ use ic_types::{
     ingress::{IngressState, IngressStatus, WasmResult},
     messages::{MessageId, EXPECTED_MESSAGE_ID_LENGTH},
     xnet::{StreamHeader, StreamIndex, StreamIndexedQueue},
-    CanisterId, PrincipalId, SubnetId,
+    CanisterId, NodeId, PrincipalId, SubnetId,
 };
 use std::collections::BTreeMap;
 use std::convert::{AsRef, TryFrom, TryInto};
@@ -241,6 +243,8 @@ fn state_as_tree(state: &ReplicatedState) -> LazyTree<'_> {
                 subnets_as_tree(
                     &state.metadata.network_topology.subnets,
                     inverted_routing_table,
+                    state.metadata.own_subnet_id,
+                    &state.metadata.subnet_metrics,
                     certification_version,
                 )
             })
@@ -372,11 +376,13 @@ fn canisters_as_tree(
     })
 }
 
-fn subnets_as_tree(
-    subnets: &BTreeMap<SubnetId, SubnetTopology>,
+fn subnets_as_tree<'a>(
+    subnets: &'a BTreeMap<SubnetId, SubnetTopology>,
     inverted_routing_table: Arc<BTreeMap<SubnetId, Vec<(PrincipalId, PrincipalId)>>>,
+    own_subnet_id: SubnetId,
+    own_subnet_metrics: &'a SubnetMetrics,
     certification_version: CertificationVersion,
-) -> LazyTree<'_> {
+) -> LazyTree<'a> {
     fork(MapTransformFork {
         map: subnets,
         certification_version,
@@ -395,12 +401,36 @@ fn subnets_as_tree(
                                 )
                             }
                         }),
+                    )
+                    .with_tree_if(
+                        certification_version > CertificationVersion::V10
+                            && subnet_id == own_subnet_id,
+                        "metrics",
+                        blob(move || encode_subnet_metrics(own_subnet_metrics)),
+                    )
+                    .with_tree_if(
+                        certification_version > CertificationVersion::V9,
+                        "node",
+                        nodes_as_tree(&subnet_topology.nodes, certification_version),
                     ),
             )
         },
     })
 }
 
+fn nodes_as_tree(
+    nodes: &BTreeMap<NodeId, NodeTopology>,
+    certification_version: CertificationVersion,
+) -> LazyTree<'_> {
+    fork(MapTransformFork {
+        map: nodes,
+        certification_version,
+        mk_tree: |_node_id, node_topology, _version| {
+            fork(FiniteMap::default().with_tree("public_key", Blob(&node_topology.public_key[..])))
+        },
+    })
+}
+
 fn canister_metadata_as_tree(
     execution_state: &ExecutionState,
     certification_version: CertificationVersion,
@@ -43,7 +43,9 @@ pub fn traverse<V: Visitor>(state: &ReplicatedState, mut v: V) -> V::Output {
 mod tests {
     use super::*;
     use crate::{
-        encoding::{encode_stream_header, types::SystemMetadata, CborProxyEncoder},
+        encoding::{
+            encode_stream_header, encode_subnet_metrics, types::SystemMetadata, CborProxyEncoder,
+        },
         subtree_visitor::{Pattern, SubtreeVisitor},
         test_visitors::{NoopVisitor, TraceEntry as E, TracingVisitor},
         CertificationVersion,
@@ -57,7 +59,7 @@ mod tests {
             execution_state::{CustomSection, CustomSectionType, WasmBinary, WasmMetadata},
             ExecutionState, ExportedFunctions, Global, NumWasmPages,
         },
-        metadata_state::SubnetTopology,
+        metadata_state::{NodeTopology, SubnetTopology},
         page_map::PageMap,
         testing::ReplicatedStateTesting,
         Memory,
@@ -65,7 +67,7 @@ mod tests {
     use ic_test_utilities::{
         mock_time,
         state::new_canister_state,
-        types::ids::{canister_test_id, subnet_test_id, user_test_id},
+        types::ids::{canister_test_id, node_test_id, subnet_test_id, user_test_id},
     };
     use ic_types::{CanisterId, Cycles, ExecutionRound};
     use ic_wasm_types::CanisterModule;
@@ -768,4 +770,153 @@ mod tests {
             traverse(&state, visitor).0
         );
     }
+
+    #[test]
+    fn test_traverse_subnet_nodes() {
+        let tmpdir = tempfile::Builder::new().prefix("test").tempdir().unwrap();
+        let mut state = ReplicatedState::new_rooted_at(
+            subnet_test_id(1),
+            SubnetType::Application,
+            tmpdir.path().into(),
+        );
+
+        state.metadata.network_topology.subnets = btreemap! {
+            subnet_test_id(0) => SubnetTopology {
+                public_key: vec![1, 2, 3, 4],
+                nodes: btreemap!{
+                    node_test_id(1) => NodeTopology {
+                        ip_address: "::1".to_string(),
+                        http_port: 8080,
+                        public_key: vec![5, 6, 7, 8],
+                    },
+                },
+                subnet_type: SubnetType::Application,
+                subnet_features: SubnetFeatures::default(),
+                ecdsa_keys_held: BTreeSet::new(),
+            },
+        };
+
+        let pattern = Pattern::match_only(
+            "subnet",
+            Pattern::any(Pattern::match_only("node", Pattern::all())),
+        );
+
+        // Node public keys are not part of the tree before certification version 10.
+        let visitor = SubtreeVisitor::new(&pattern, TracingVisitor::new(NoopVisitor));
+        state.metadata.certification_version = CertificationVersion::V9;
+        assert_eq!(
+            vec![
+                E::StartSubtree,
+                edge("subnet"),
+                E::StartSubtree,
+                E::EnterEdge(subnet_test_id(0).get().into_vec()),
+                E::StartSubtree,
+                E::EndSubtree, // subnet
+                E::EndSubtree, // subnets
+                E::EndSubtree, // global
+            ],
+            traverse(&state, visitor).0
+        );
+
+        let visitor = SubtreeVisitor::new(&pattern, TracingVisitor::new(NoopVisitor));
+        state.metadata.certification_version = CertificationVersion::V10;
+        assert_eq!(
+            vec![
+                E::StartSubtree,
+                edge("subnet"),
+                E::StartSubtree,
+                E::EnterEdge(subnet_test_id(0).get().into_vec()),
+                E::StartSubtree,
+                edge("node"),
+                E::StartSubtree,
+                E::EnterEdge(node_test_id(1).get().into_vec()),
+                E::StartSubtree,
+                edge("public_key"),
+                E::VisitBlob(vec![5, 6, 7, 8]),
+                E::EndSubtree, // node
+                E::EndSubtree, // nodes
+                E::EndSubtree, // subnet
+                E::EndSubtree, // subnets
+                E::EndSubtree, // global
+            ],
+            traverse(&state, visitor).0
+        );
+    }
+
+    #[test]
+    fn test_traverse_subnet_metrics() {
+        let tmpdir = tempfile::Builder::new().prefix("test").tempdir().unwrap();
+        let own_subnet_id = subnet_test_id(1);
+        let mut state = ReplicatedState::new_rooted_at(
+            own_subnet_id,
+            SubnetType::Application,
+            tmpdir.path().into(),
+        );
+        let subnet_topology = SubnetTopology {
+            public_key: vec![1, 2, 3, 4],
+            nodes: btreemap! {
+                node_test_id(1) => NodeTopology {
+                    ip_address: "::1".to_string(),
+                    http_port: 8080,
+                    public_key: vec![5, 6, 7, 8],
+                },
+            },
+            subnet_type: SubnetType::Application,
+            subnet_features: SubnetFeatures::default(),
+            ecdsa_keys_held: BTreeSet::new(),
+        };
+        state.metadata.network_topology.subnets = btreemap! {
+            subnet_test_id(0) => subnet_topology.clone(),
+            own_subnet_id => subnet_topology,
+        };
+        state.metadata.subnet_metrics.num_canisters = 5;
+        state.metadata.subnet_metrics.canister_state_bytes = NumBytes::from(1 << 20);
+        state.metadata.subnet_metrics.update_transactions_total = 42;
+
+        let pattern = Pattern::match_only(
+            "subnet",
+            Pattern::any(Pattern::match_only("metrics", Pattern::all())),
+        );
+
+        // Only the own subnet has metrics, starting with certification version 11.
+        let visitor = SubtreeVisitor::new(&pattern, TracingVisitor::new(NoopVisitor));
+        state.metadata.certification_version = CertificationVersion::V10;
+        assert_eq!(
+            vec![
+                E::StartSubtree,
+                edge("subnet"),
+                E::StartSubtree,
+                E::EnterEdge(subnet_test_id(0).get().into_vec()),
+                E::StartSubtree,
+                E::EndSubtree, // subnet
+                E::EnterEdge(own_subnet_id.get().into_vec()),
+                E::StartSubtree,
+                E::EndSubtree, // subnet
+                E::EndSubtree, // subnets
+                E::EndSubtree, // global
+            ],
+            traverse(&state, visitor).0
+        );
+
+        let visitor = SubtreeVisitor::new(&pattern, TracingVisitor::new(NoopVisitor));
+        state.metadata.certification_version = CertificationVersion::V11;
+        assert_eq!(
+            vec![
+                E::StartSubtree,
+                edge("subnet"),
+                E::StartSubtree,
+                E::EnterEdge(subnet_test_id(0).get().into_vec()),
+                E::StartSubtree,
+                E::EndSubtree, // subnet
+                E::EnterEdge(own_subnet_id.get().into_vec()),
+                E::StartSubtree,
+                edge("metrics"),
+                E::VisitBlob(encode_subnet_metrics(&state.metadata.subnet_metrics)),
+                E::EndSubtree, // subnet
+                E::EndSubtree, // subnets
+                E::EndSubtree, // global
+            ],
+            traverse(&state, visitor).0
+        );
+    }
 }
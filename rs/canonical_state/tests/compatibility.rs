@@ -59,7 +59,7 @@ pub(crate) fn arb_valid_versioned_stream_header(
         // 8.
         (
             arb_stream_header(/* sig_min_size */ 0, sig_max_size),
-            Just(CertificationVersion::V8..=CertificationVersion::V9)
+            Just(CertificationVersion::V8..=CertificationVersion::V11)
         ),
     ]
 }
@@ -97,7 +97,7 @@ lazy_static! {
         ),
         #[allow(clippy::redundant_closure)]
         VersionedEncoding::new(
-            CertificationVersion::V0..=CertificationVersion::V9,
+            CertificationVersion::V0..=CertificationVersion::V11,
             "StreamHeader",
             |v| StreamHeaderV8::proxy_encode(v),
             |v| StreamHeaderV8::proxy_decode(v),
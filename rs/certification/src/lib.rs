@@ -42,6 +42,11 @@ pub enum CertificateValidationError {
     MultipleSubnetDelegationsNotAllowed,
     /// The given canister id is not contained in the ranges specified by the subnet delegation.
     CanisterIdOutOfRange,
+    /// The certificate is delegated to a different subnet than the one whose state was read.
+    SubnetIdMismatch {
+        expected: SubnetId,
+        delegated: SubnetId,
+    },
 }
 
 impl fmt::Display for CertificateValidationError {
@@ -72,6 +77,14 @@ impl fmt::Display for CertificateValidationError {
                     "canister id does not match the canister id range specified in the certificate"
                 )
             }
+            Self::SubnetIdMismatch {
+                expected,
+                delegated,
+            } => write!(
+                f,
+                "expected a certificate of subnet {} but the certificate is delegated to subnet {}",
+                expected, delegated
+            ),
         }
     }
 }
@@ -113,34 +126,11 @@ pub fn verify_certificate(
 
     #[derive(Deserialize, Debug)]
     struct ReplicaState {
-        time: Leb128EncodedU64,
         canister: BTreeMap<CanisterId, CanisterView>,
     }
 
-    let certificate: Certificate = parse_certificate(certificate)?;
-
-    let key = if let Some(delegation) = &certificate.delegation {
-        let subnet_id = PrincipalId::try_from(&*delegation.subnet_id)
-            .map(SubnetId::from)
-            .map_err(|err| {
-                CertificateValidationError::DeserError(format!(
-                    "failed to parse delegation subnet id: {}",
-                    err
-                ))
-            })?;
-        verify_delegation_certificate(
-            &delegation.certificate,
-            &subnet_id,
-            root_pk,
-            Some(canister_id),
-        )?
-    } else {
-        *root_pk
-    };
-
-    verify_certificate_signature(&certificate, &key)?;
-
-    let replica_labeled_tree = parse_tree(certificate.tree)?;
+    let (replica_labeled_tree, time) =
+        verify_canister_state_certificate(certificate, canister_id, root_pk)?;
     let replica_state = ReplicaState::deserialize(LabeledTreeDeserializer::new(
         &replica_labeled_tree,
     ))
@@ -169,7 +159,110 @@ pub fn verify_certificate(
         });
     }
 
-    Ok(Time::from_nanos_since_unix_epoch(replica_state.time.0))
+    Ok(time)
+}
+
+/// Verifies the certificate of a `read_state` response of the canister with
+/// ID `canister_id`.
+///
+/// Verification is the same as for `verify_certificate`, except that the tree
+/// is not required to contain certified data of the canister.
+///
+/// Returns the certificate's tree and timestamp, if verification is
+/// successful.
+pub fn verify_canister_state_certificate(
+    certificate: &[u8],
+    canister_id: &CanisterId,
+    root_pk: &ThresholdSigPublicKey,
+) -> Result<(LabeledTree<Vec<u8>>, Time), CertificateValidationError> {
+    verify_state_certificate(certificate, root_pk, |delegation_certificate, subnet_id| {
+        verify_delegation_certificate(
+            delegation_certificate,
+            subnet_id,
+            root_pk,
+            Some(canister_id),
+        )
+    })
+}
+
+/// Verifies the certificate of a `read_state` response of the subnet with ID
+/// `subnet_id`.
+///
+/// Verification is the same as for `verify_certificate`, except that the tree
+/// is not required to contain canister information and, if a delegation is
+/// present, it must be for `subnet_id` instead of covering a canister ID.
+///
+/// Returns the certificate's tree and timestamp, if verification is
+/// successful.
+pub fn verify_subnet_state_certificate(
+    certificate: &[u8],
+    subnet_id: &SubnetId,
+    root_pk: &ThresholdSigPublicKey,
+) -> Result<(LabeledTree<Vec<u8>>, Time), CertificateValidationError> {
+    verify_state_certificate(
+        certificate,
+        root_pk,
+        |delegation_certificate, delegated_subnet_id| {
+            if delegated_subnet_id != subnet_id {
+                return Err(CertificateValidationError::SubnetIdMismatch {
+                    expected: *subnet_id,
+                    delegated: *delegated_subnet_id,
+                });
+            }
+            verify_delegation_certificate(delegation_certificate, subnet_id, root_pk, None)
+        },
+    )
+}
+
+/// Verifies the signature of a certificate, using the key returned by
+/// `verify_delegation` for the delegation certificate and subnet ID if a
+/// delegation is present, and `root_pk` otherwise.
+///
+/// Returns the certificate's tree and timestamp.
+fn verify_state_certificate<F>(
+    certificate: &[u8],
+    root_pk: &ThresholdSigPublicKey,
+    verify_delegation: F,
+) -> Result<(LabeledTree<Vec<u8>>, Time), CertificateValidationError>
+where
+    F: FnOnce(&[u8], &SubnetId) -> Result<ThresholdSigPublicKey, CertificateValidationError>,
+{
+    #[derive(Deserialize, Debug)]
+    struct CertifiedTime {
+        time: Leb128EncodedU64,
+    }
+
+    let certificate: Certificate = parse_certificate(certificate)?;
+
+    let key = if let Some(delegation) = &certificate.delegation {
+        let subnet_id = PrincipalId::try_from(&*delegation.subnet_id)
+            .map(SubnetId::from)
+            .map_err(|err| {
+                CertificateValidationError::DeserError(format!(
+                    "failed to parse delegation subnet id: {}",
+                    err
+                ))
+            })?;
+        verify_delegation(&delegation.certificate, &subnet_id)?
+    } else {
+        *root_pk
+    };
+
+    verify_certificate_signature(&certificate, &key)?;
+
+    let labeled_tree = parse_tree(certificate.tree)?;
+    let certified_time = CertifiedTime::deserialize(LabeledTreeDeserializer::new(&labeled_tree))
+        .map_err(|err| {
+            CertificateValidationError::DeserError(format!(
+                "failed to unpack time from a labeled tree: {}",
+                err
+            ))
+        })?;
+
+    Ok((
+        labeled_tree,
+        Time::from_nanos_since_unix_epoch(certified_time.time.0),
+    ))
 }
 
 /// Verifies a delegation certificate.
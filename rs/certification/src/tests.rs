@@ -14,8 +14,8 @@ use ic_types::crypto::threshold_sig::ThresholdSigPublicKey;
 use ic_types::Time;
 
 use crate::{
-    validate_subnet_delegation_certificate, verify_certificate, CanisterId,
-    CertificateValidationError,
+    validate_subnet_delegation_certificate, verify_canister_state_certificate, verify_certificate,
+    verify_subnet_state_certificate, CanisterId, CertificateValidationError,
 };
 
 #[test]
@@ -703,6 +703,63 @@ fn should_fail_to_validate_delegation_cert_if_subnet_canister_ranges_malformed()
     ));
 }
 
+#[test]
+fn should_validate_canister_state_certificate_with_delegation() {
+    let tree = LabeledTree::SubTree(flatmap![
+        Label::from("request_status") => LabeledTree::SubTree(flatmap![]),
+        Label::from("time") => LabeledTree::Leaf(encoded_time(42)),
+    ]);
+    let (_cert, pk, cbor) = CertificateBuilder::new(CustomTree(tree.clone()))
+        .with_delegation(CertificateBuilder::new(SubnetData {
+            subnet_id: subnet_id(1),
+            canister_id_ranges: vec![(canister_id(0), canister_id(10))],
+        }))
+        .build();
+
+    let (certified_tree, time) = verify_canister_state_certificate(&cbor, &canister_id(1), &pk)
+        .expect("expect valid certificate");
+    assert_eq!(certified_tree, tree);
+    assert_eq!(time, Time::from_nanos_since_unix_epoch(42));
+
+    assert!(matches!(
+        verify_canister_state_certificate(&cbor, &canister_id(11), &pk),
+        Err(CertificateValidationError::CanisterIdOutOfRange)
+    ));
+}
+
+#[test]
+fn should_validate_subnet_state_certificate_with_delegation() {
+    let (_cert, pk, cbor) = CertificateBuilder::new(CustomTree(LabeledTree::SubTree(flatmap![
+        Label::from("time") => LabeledTree::Leaf(encoded_time(42)),
+    ])))
+    .with_delegation(CertificateBuilder::new(SubnetData {
+        subnet_id: subnet_id(1),
+        canister_id_ranges: vec![(canister_id(0), canister_id(10))],
+    }))
+    .build();
+
+    let (_tree, time) = verify_subnet_state_certificate(&cbor, &subnet_id(1), &pk)
+        .expect("expect valid certificate");
+    assert_eq!(time, Time::from_nanos_since_unix_epoch(42));
+}
+
+#[test]
+fn should_fail_subnet_state_certificate_delegated_to_other_subnet() {
+    let (_cert, pk, cbor) = CertificateBuilder::new(CustomTree(LabeledTree::SubTree(flatmap![
+        Label::from("time") => LabeledTree::Leaf(encoded_time(42)),
+    ])))
+    .with_delegation(CertificateBuilder::new(SubnetData {
+        subnet_id: subnet_id(1),
+        canister_id_ranges: vec![(canister_id(0), canister_id(10))],
+    }))
+    .build();
+
+    assert!(matches!(
+        verify_subnet_state_certificate(&cbor, &subnet_id(2), &pk),
+        Err(CertificateValidationError::SubnetIdMismatch { .. })
+    ));
+}
+
 fn random_certified_data() -> Digest {
     let mut random_certified_data: [u8; 32] = [0; 32];
     thread_rng().fill(&mut random_certified_data);
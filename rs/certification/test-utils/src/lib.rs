@@ -124,6 +124,12 @@ impl CertificateBuilder {
         }
     }
 
+    /// Replaces the certified data, keeping the keys and the delegation.
+    pub fn with_data(mut self, data: CertificateData) -> Self {
+        self.data = data;
+        self
+    }
+
     pub fn with_time(mut self, time: u64) -> Self {
         self.time = Some(time);
         self
@@ -163,14 +169,9 @@ impl CertificateBuilder {
     /// # Returns
     /// a tuple of (Certificate, ThresholdSigPublicKey, Vec<u8> containing the cbor encoded certificate)
     pub fn build(&self) -> (Certificate, ThresholdSigPublicKey, Vec<u8>) {
-        let mut b = HashTreeBuilderImpl::new();
         let tree = &self.data.get_tree(self.delegatee_pub_key, self.time);
-        hash_full_tree(&mut b, tree);
-
-        let witness_gen = b.witness_generator().unwrap();
-        let hash_tree_digest = witness_gen.hash_tree().digest();
-        let mixed_tree = witness_gen.mixed_hash_tree(tree).unwrap();
-        let root_hash = CryptoHashOfPartialState::from(CryptoHash(hash_tree_digest.to_vec()));
+        let mixed_tree = mixed_hash_tree(tree);
+        let root_hash = CryptoHashOfPartialState::from(CryptoHash(mixed_tree.digest().to_vec()));
 
         let sig = if let Some(override_sig) = &self.override_sig {
             CombinedThresholdSigOf::from(override_sig.clone())
@@ -230,6 +231,18 @@ pub fn encoded_time(time: u64) -> Vec<u8> {
     encoded_time
 }
 
+/// Returns a hash tree that reveals all of `tree`, e.g. to be certified by a
+/// canister.
+pub fn mixed_hash_tree(tree: &LabeledTree<Vec<u8>>) -> MixedHashTree {
+    let mut b = HashTreeBuilderImpl::new();
+    hash_full_tree(&mut b, tree);
+
+    b.witness_generator()
+        .unwrap()
+        .mixed_hash_tree(tree)
+        .unwrap()
+}
+
 fn hash_full_tree(b: &mut HashTreeBuilderImpl, t: &LabeledTree<Vec<u8>>) {
     match t {
         LabeledTree::Leaf(bytes) => {
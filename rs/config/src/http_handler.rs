@@ -6,6 +6,8 @@ const DEFAULT_IP_ADDR: &str = "0.0.0.0";
 
 const DEFAULT_PORT: u16 = 8080u16;
 
+const DEFAULT_INGRESS_MESSAGE_CERTIFICATE_TIMEOUT_SECONDS: u64 = 10;
+
 #[derive(Debug, Clone, Serialize, PartialEq, Eq, Deserialize)]
 #[serde(rename_all = "snake_case")]
 /// The port configuration. Defaults to using port 8080.
@@ -20,6 +22,14 @@ pub enum PortConfig {
     WritePortTo(PathBuf),
 }
 
+/// A token bucket rate limit: requests are admitted at a sustained rate of
+/// `requests_per_second`, with bursts of up to `burst_size` requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    pub requests_per_second: u32,
+    pub burst_size: u32,
+}
+
 /// The external configuration that can be loaded from a configuration file.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(default)]
@@ -85,6 +95,42 @@ pub struct ExternalConfig {
     //       major security risk for the IC, but developers should not be
     //       tempted to get the IC's root key from this insecure location.
     pub show_root_key_in_status: bool,
+
+    /// Maximum number of seconds the synchronous call endpoint
+    /// (`/api/v3/canister/<id>/call`) waits for the submitted ingress message
+    /// to be executed and certified before falling back to a `202 Accepted`
+    /// response.
+    ///
+    /// ```json5
+    /// {
+    ///   http_handler: {
+    ///     ingress_message_certificate_timeout_seconds: 10
+    ///   }
+    /// }
+    /// ```
+    pub ingress_message_certificate_timeout_seconds: u64,
+
+    /// Rate limit applied to the requests of each sender principal. Only
+    /// requests with valid signatures count towards the limit, and requests
+    /// above it are rejected with `429 Too Many Requests`. Disabled if not set.
+    ///
+    /// ```json5
+    /// {
+    ///   http_handler: {
+    ///     sender_rate_limit: { requests_per_second: 10, burst_size: 50 }
+    ///   }
+    /// }
+    /// ```
+    pub sender_rate_limit: Option<RateLimitConfig>,
+
+    /// Rate limit applied to the requests targeting each canister. Like the
+    /// sender limit, it only counts requests with valid signatures. Disabled
+    /// if not set.
+    pub canister_rate_limit: Option<RateLimitConfig>,
+
+    /// Rate limit applied to the API requests of each client IP address.
+    /// Disabled if not set.
+    pub ip_rate_limit: Option<RateLimitConfig>,
 }
 
 impl Default for ExternalConfig {
@@ -94,6 +140,11 @@ impl Default for ExternalConfig {
             allow_ipv6_my_users_have_no_privacy: None,
             port: None,
             show_root_key_in_status: true,
+            ingress_message_certificate_timeout_seconds:
+                DEFAULT_INGRESS_MESSAGE_CERTIFICATE_TIMEOUT_SECONDS,
+            sender_rate_limit: None,
+            canister_rate_limit: None,
+            ip_rate_limit: None,
         }
     }
 }
@@ -110,6 +161,14 @@ pub struct Config {
     pub port_file_path: Option<PathBuf>,
     /// True if the replica public key is returned from the `/status` endpoint
     pub show_root_key_in_status: bool,
+    /// How long the synchronous call endpoint waits for a certified reply
+    pub ingress_message_certificate_timeout_seconds: u64,
+    /// Rate limit per sender principal, if any
+    pub sender_rate_limit: Option<RateLimitConfig>,
+    /// Rate limit per target canister, if any
+    pub canister_rate_limit: Option<RateLimitConfig>,
+    /// Rate limit per client IP address, if any
+    pub ip_rate_limit: Option<RateLimitConfig>,
 }
 
 impl Default for Config {
@@ -121,6 +180,11 @@ impl Default for Config {
             ),
             port_file_path: None,
             show_root_key_in_status: true,
+            ingress_message_certificate_timeout_seconds:
+                DEFAULT_INGRESS_MESSAGE_CERTIFICATE_TIMEOUT_SECONDS,
+            sender_rate_limit: None,
+            canister_rate_limit: None,
+            ip_rate_limit: None,
         }
     }
 }
@@ -155,6 +219,24 @@ impl TryFrom<ExternalConfig> for Config {
         }?;
 
         config.show_root_key_in_status = ec.show_root_key_in_status;
+        config.ingress_message_certificate_timeout_seconds =
+            ec.ingress_message_certificate_timeout_seconds;
+
+        for rate_limit in [
+            ec.sender_rate_limit,
+            ec.canister_rate_limit,
+            ec.ip_rate_limit,
+        ]
+        .iter()
+        .flatten()
+        {
+            if rate_limit.requests_per_second == 0 || rate_limit.burst_size == 0 {
+                return Err("rate limits must admit at least one request per second and burst");
+            }
+        }
+        config.sender_rate_limit = ec.sender_rate_limit;
+        config.canister_rate_limit = ec.canister_rate_limit;
+        config.ip_rate_limit = ec.ip_rate_limit;
         Ok(config)
     }
 }
@@ -4,7 +4,7 @@
 
 use super::pre_signer::{EcdsaTranscriptBuilder, EcdsaTranscriptBuilderImpl};
 use super::signer::{EcdsaSignatureBuilder, EcdsaSignatureBuilderImpl};
-use super::utils::EcdsaBlockReaderImpl;
+use super::utils::{algorithm_for_key_id, EcdsaBlockReaderImpl};
 use crate::consensus::{
     crypto::ConsensusCrypto, metrics::EcdsaPayloadMetrics, pool_reader::PoolReader,
 };
@@ -642,6 +642,7 @@ pub(crate) fn create_data_payload_helper(
         next_interval_registry_version,
         subnet_id,
         current_key_transcript.as_ref(),
+        algorithm_for_key_id(&ecdsa_payload.key_transcript.key_id),
         &mut ecdsa_payload.key_transcript.next_in_creation,
         &mut ecdsa_payload.uid_generator,
         transcript_builder,
@@ -688,6 +689,7 @@ pub(crate) fn create_data_payload_helper(
 fn new_random_config(
     subnet_nodes: &[NodeId],
     summary_registry_version: RegistryVersion,
+    algorithm_id: AlgorithmId,
     uid_generator: &mut ecdsa::EcdsaUIDGenerator,
 ) -> Result<ecdsa::RandomTranscriptParams, EcdsaPayloadError> {
     let transcript_id = uid_generator.next_transcript_id();
@@ -698,7 +700,7 @@ fn new_random_config(
         dealers,
         receivers,
         summary_registry_version,
-        AlgorithmId::ThresholdEcdsaSecp256k1,
+        algorithm_id,
     ))
 }
 
@@ -715,6 +717,7 @@ fn make_new_quadruples_if_needed(
         make_new_quadruples_if_needed_helper(
             &node_ids,
             key_transcript.registry_version(),
+            key_transcript.algorithm_id(),
             ecdsa_config,
             ecdsa_payload,
         )
@@ -726,6 +729,7 @@ fn make_new_quadruples_if_needed(
 fn make_new_quadruples_if_needed_helper(
     subnet_nodes: &[NodeId],
     registry_version: RegistryVersion,
+    algorithm_id: AlgorithmId,
     ecdsa_config: &EcdsaConfig,
     ecdsa_payload: &mut ecdsa::EcdsaPayload,
 ) -> Result<(), EcdsaPayloadError> {
@@ -735,8 +739,10 @@ fn make_new_quadruples_if_needed_helper(
         let quadruples_in_creation = &mut ecdsa_payload.quadruples_in_creation;
         let uid_generator = &mut ecdsa_payload.uid_generator;
         for _ in 0..(quadruples_to_create - unassigned_quadruples) {
-            let kappa_config = new_random_config(subnet_nodes, registry_version, uid_generator)?;
-            let lambda_config = new_random_config(subnet_nodes, registry_version, uid_generator)?;
+            let kappa_config =
+                new_random_config(subnet_nodes, registry_version, algorithm_id, uid_generator)?;
+            let lambda_config =
+                new_random_config(subnet_nodes, registry_version, algorithm_id, uid_generator)?;
             quadruples_in_creation.insert(
                 uid_generator.next_quadruple_id(),
                 ecdsa::QuadrupleInCreation::new(kappa_config, lambda_config),
@@ -1043,6 +1049,7 @@ fn update_next_key_transcript(
     next_interval_registry_version: RegistryVersion,
     subnet_id: SubnetId,
     current_key_transcript: Option<&ecdsa::UnmaskedTranscriptWithAttributes>,
+    algorithm_id: AlgorithmId,
     next_key_transcript_creation: &mut ecdsa::KeyTranscriptCreation,
     uid_generator: &mut ecdsa::EcdsaUIDGenerator,
     transcript_cache: &dyn EcdsaTranscriptBuilder,
@@ -1054,6 +1061,7 @@ fn update_next_key_transcript(
         &receivers,
         next_interval_registry_version,
         current_key_transcript,
+        algorithm_id,
         next_key_transcript_creation,
         uid_generator,
         transcript_cache,
@@ -1066,6 +1074,7 @@ fn update_next_key_transcript_helper(
     receivers: &[NodeId],
     registry_version: RegistryVersion,
     current_key_transcript: Option<&ecdsa::UnmaskedTranscriptWithAttributes>,
+    algorithm_id: AlgorithmId,
     next_key_transcript_creation: &mut ecdsa::KeyTranscriptCreation,
     uid_generator: &mut ecdsa::EcdsaUIDGenerator,
     transcript_cache: &dyn EcdsaTranscriptBuilder,
@@ -1129,7 +1138,7 @@ fn update_next_key_transcript_helper(
                     dealers_set,
                     receivers_set,
                     registry_version,
-                    AlgorithmId::ThresholdEcdsaSecp256k1,
+                    algorithm_id,
                 ),
             );
         }
@@ -1737,13 +1746,14 @@ mod tests {
     fn create_new_quadruple_in_creation(
         subnet_nodes: &[NodeId],
         registry_version: RegistryVersion,
+        algorithm_id: AlgorithmId,
         uid_generator: &mut ecdsa::EcdsaUIDGenerator,
         quadruples_in_creation: &mut BTreeMap<ecdsa::QuadrupleId, ecdsa::QuadrupleInCreation>,
     ) -> (ecdsa::RandomTranscriptParams, ecdsa::RandomTranscriptParams) {
         let kappa_config_ref =
-            new_random_config(subnet_nodes, registry_version, uid_generator).unwrap();
+            new_random_config(subnet_nodes, registry_version, algorithm_id, uid_generator).unwrap();
         let lambda_config_ref =
-            new_random_config(subnet_nodes, registry_version, uid_generator).unwrap();
+            new_random_config(subnet_nodes, registry_version, algorithm_id, uid_generator).unwrap();
         quadruples_in_creation.insert(
             uid_generator.next_quadruple_id(),
             ecdsa::QuadrupleInCreation::new(kappa_config_ref.clone(), lambda_config_ref.clone()),
@@ -1783,6 +1793,7 @@ mod tests {
         let result = make_new_quadruples_if_needed_helper(
             &subnet_nodes,
             summary_registry_version,
+            AlgorithmId::ThresholdEcdsaSecp256k1,
             &ecdsa_config,
             &mut ecdsa_payload,
         );
@@ -1852,6 +1863,7 @@ mod tests {
         let (_kappa_config_ref, _lambda_config_ref) = create_new_quadruple_in_creation(
             &subnet_nodes,
             registry_version,
+            AlgorithmId::ThresholdEcdsaSecp256k1,
             &mut ecdsa_payload.uid_generator,
             &mut ecdsa_payload.quadruples_in_creation,
         );
@@ -1859,6 +1871,7 @@ mod tests {
         let (_kappa_config_ref, _lambda_config_ref) = create_new_quadruple_in_creation(
             &subnet_nodes,
             registry_version,
+            AlgorithmId::ThresholdEcdsaSecp256k1,
             &mut ecdsa_payload.uid_generator,
             &mut ecdsa_payload.quadruples_in_creation,
         );
@@ -1877,14 +1890,14 @@ mod tests {
         let request_id_0 = *new_requests.keys().next().unwrap();
         assert_eq!(request_id_0.quadruple_id, quadruple_id_0);
         // Now we are going to make quadruple_id_1 available.
-        let sig_inputs = create_sig_inputs(10);
+        let sig_inputs = create_sig_inputs(10, AlgorithmId::ThresholdEcdsaSecp256k1);
         let quadruple_ref = &sig_inputs.sig_inputs_ref.presig_quadruple_ref;
         ecdsa_payload
             .available_quadruples
             .insert(quadruple_id_1, quadruple_ref.clone());
         ecdsa_payload.quadruples_in_creation.remove(&quadruple_id_1);
         /*
-        let sig_inputs = create_sig_inputs(11);
+        let sig_inputs = create_sig_inputs(11, AlgorithmId::ThresholdEcdsaSecp256k1);
         let quadruple_ref = &sig_inputs.sig_inputs_ref.presig_quadruple_ref;
         ecdsa_payload.available_quadruples.insert(
             ecdsa_payload.uid_generator.next_quadruple_id(),
@@ -2003,13 +2016,13 @@ mod tests {
         // Because there is no quadruples available, expect empty return
         assert!(result.is_empty());
         // Add a quadruple
-        let sig_inputs = create_sig_inputs(10);
+        let sig_inputs = create_sig_inputs(10, AlgorithmId::ThresholdEcdsaSecp256k1);
         let quadruple_id = ecdsa_payload.uid_generator.next_quadruple_id();
         let quadruple_ref = &sig_inputs.sig_inputs_ref.presig_quadruple_ref;
         ecdsa_payload
             .available_quadruples
             .insert(quadruple_id, quadruple_ref.clone());
-        let sig_inputs = create_sig_inputs(11);
+        let sig_inputs = create_sig_inputs(11, AlgorithmId::ThresholdEcdsaSecp256k1);
         let quadruple_ref = &sig_inputs.sig_inputs_ref.presig_quadruple_ref;
         ecdsa_payload.available_quadruples.insert(
             ecdsa_payload.uid_generator.next_quadruple_id(),
@@ -2074,13 +2087,13 @@ mod tests {
         let mut ecdsa_payload = empty_ecdsa_payload(subnet_id);
         let height = Height::from(1);
         // Add quadruples
-        let sig_inputs = create_sig_inputs(10);
+        let sig_inputs = create_sig_inputs(10, AlgorithmId::ThresholdEcdsaSecp256k1);
         let quadruple_ref = &sig_inputs.sig_inputs_ref.presig_quadruple_ref;
         ecdsa_payload.available_quadruples.insert(
             ecdsa_payload.uid_generator.next_quadruple_id(),
             quadruple_ref.clone(),
         );
-        let sig_inputs = create_sig_inputs(11);
+        let sig_inputs = create_sig_inputs(11, AlgorithmId::ThresholdEcdsaSecp256k1);
         let quadruple_ref = &sig_inputs.sig_inputs_ref.presig_quadruple_ref;
         let quadruple_id = ecdsa_payload.uid_generator.next_quadruple_id();
         ecdsa_payload
@@ -2142,13 +2155,13 @@ mod tests {
         // Because there is no quadruples available, expect empty return
         assert!(result.is_empty());
         // Add a quadruple
-        let sig_inputs = create_sig_inputs(10);
+        let sig_inputs = create_sig_inputs(10, AlgorithmId::ThresholdEcdsaSecp256k1);
         let quadruple_id = ecdsa_payload.uid_generator.next_quadruple_id();
         let quadruple_ref = &sig_inputs.sig_inputs_ref.presig_quadruple_ref;
         ecdsa_payload
             .available_quadruples
             .insert(quadruple_id, quadruple_ref.clone());
-        let sig_inputs = create_sig_inputs(11);
+        let sig_inputs = create_sig_inputs(11, AlgorithmId::ThresholdEcdsaSecp256k1);
         let quadruple_ref = &sig_inputs.sig_inputs_ref.presig_quadruple_ref;
         ecdsa_payload.available_quadruples.insert(
             ecdsa_payload.uid_generator.next_quadruple_id(),
@@ -2183,6 +2196,15 @@ mod tests {
 
     #[test]
     fn test_ecdsa_update_next_key_transcript() {
+        update_next_key_transcript_test(AlgorithmId::ThresholdEcdsaSecp256k1);
+    }
+
+    #[test]
+    fn test_ecdsa_update_next_key_transcript_secp256r1() {
+        update_next_key_transcript_test(AlgorithmId::ThresholdEcdsaSecp256r1);
+    }
+
+    fn update_next_key_transcript_test(algorithm: AlgorithmId) {
         let num_of_nodes = 4;
         let subnet_id = subnet_test_id(1);
         let env = CanisterThresholdSigTestEnvironment::new(num_of_nodes);
@@ -2206,6 +2228,7 @@ mod tests {
             &subnet_nodes,
             registry_version,
             None,
+            algorithm,
             &mut payload.key_transcript.next_in_creation,
             &mut payload.uid_generator,
             &transcript_builder,
@@ -2239,6 +2262,7 @@ mod tests {
             &subnet_nodes,
             registry_version,
             None,
+            algorithm,
             &mut payload.key_transcript.next_in_creation,
             &mut payload.uid_generator,
             &transcript_builder,
@@ -2278,6 +2302,7 @@ mod tests {
             &subnet_nodes,
             registry_version,
             None,
+            algorithm,
             &mut payload.key_transcript.next_in_creation,
             &mut payload.uid_generator,
             &transcript_builder,
@@ -2286,6 +2311,7 @@ mod tests {
         );
         let completed_transcript = result.unwrap().unwrap();
         assert_eq!(completed_transcript, unmasked_transcript);
+        assert_eq!(completed_transcript.algorithm_id, algorithm);
         let current_key_transcript = ecdsa::UnmaskedTranscriptWithAttributes::new(
             completed_transcript.to_attributes(),
             ecdsa::UnmaskedTranscript::try_from((cur_height, &unmasked_transcript)).unwrap(),
@@ -2315,6 +2341,7 @@ mod tests {
             &subnet_nodes,
             registry_version,
             Some(&current_key_transcript),
+            algorithm,
             &mut payload.key_transcript.next_in_creation,
             &mut payload.uid_generator,
             &transcript_builder,
@@ -2349,6 +2376,7 @@ mod tests {
             &subnet_nodes,
             registry_version,
             Some(&current_key_transcript),
+            algorithm,
             &mut payload.key_transcript.next_in_creation,
             &mut payload.uid_generator,
             &transcript_builder,
@@ -2399,6 +2427,7 @@ mod tests {
             &subnet_nodes,
             registry_version,
             None,
+            AlgorithmId::ThresholdEcdsaSecp256k1,
             &mut payload.key_transcript.next_in_creation,
             &mut payload.uid_generator,
             &transcript_builder,
@@ -2432,6 +2461,7 @@ mod tests {
             &subnet_nodes,
             registry_version,
             None,
+            AlgorithmId::ThresholdEcdsaSecp256k1,
             &mut payload.key_transcript.next_in_creation,
             &mut payload.uid_generator,
             &transcript_builder,
@@ -2471,6 +2501,7 @@ mod tests {
             &subnet_nodes,
             registry_version,
             None,
+            AlgorithmId::ThresholdEcdsaSecp256k1,
             &mut payload.key_transcript.next_in_creation,
             &mut payload.uid_generator,
             &transcript_builder,
@@ -2520,6 +2551,7 @@ mod tests {
             &subnet_nodes,
             registry_version,
             None,
+            AlgorithmId::ThresholdEcdsaSecp256k1,
             &mut payload.key_transcript.next_in_creation,
             &mut payload.uid_generator,
             &transcript_builder,
@@ -2558,6 +2590,7 @@ mod tests {
             &target_subnet_nodes,
             registry_version,
             None,
+            AlgorithmId::ThresholdEcdsaSecp256k1,
             &mut payload.key_transcript.next_in_creation,
             &mut payload.uid_generator,
             &transcript_builder,
@@ -2663,12 +2696,20 @@ mod tests {
 
     #[test]
     fn test_ecdsa_update_quadruples_in_creation() {
+        update_quadruples_in_creation_test(AlgorithmId::ThresholdEcdsaSecp256k1);
+    }
+
+    #[test]
+    fn test_ecdsa_update_quadruples_in_creation_secp256r1() {
+        update_quadruples_in_creation_test(AlgorithmId::ThresholdEcdsaSecp256r1);
+    }
+
+    fn update_quadruples_in_creation_test(algorithm: AlgorithmId) {
         let num_of_nodes = 4;
         let subnet_id = subnet_test_id(1);
         let env = CanisterThresholdSigTestEnvironment::new(num_of_nodes);
         let registry_version = env.newest_registry_version;
         let subnet_nodes = env.receivers().into_iter().collect::<Vec<_>>();
-        let algorithm = AlgorithmId::ThresholdEcdsaSecp256k1;
         let mut block_reader = TestEcdsaBlockReader::new();
         let transcript_builder = TestEcdsaTranscriptBuilder::new();
 
@@ -2685,6 +2726,7 @@ mod tests {
         let (kappa_config_ref, lambda_config_ref) = create_new_quadruple_in_creation(
             &subnet_nodes,
             registry_version,
+            algorithm,
             &mut payload.uid_generator,
             &mut payload.quadruples_in_creation,
         );
@@ -3073,8 +3115,16 @@ mod tests {
             let mut reshare_refs = BTreeMap::new();
             reshare_refs.insert(*reshare_key_transcript_ref.as_ref(), reshare_key_transcript);
 
-            let inputs_1 = create_sig_inputs_with_height(91, summary_height);
-            let inputs_2 = create_sig_inputs_with_height(92, summary_height);
+            let inputs_1 = create_sig_inputs_with_height(
+                91,
+                summary_height,
+                AlgorithmId::ThresholdEcdsaSecp256k1,
+            );
+            let inputs_2 = create_sig_inputs_with_height(
+                92,
+                summary_height,
+                AlgorithmId::ThresholdEcdsaSecp256k1,
+            );
             let summary_block = create_summary_block_with_transcripts(
                 subnet_id,
                 summary_height,
@@ -3091,8 +3141,16 @@ mod tests {
 
             // Create payload blocks with transcripts
             let payload_height_1 = Height::new(10);
-            let inputs_1 = create_sig_inputs_with_height(93, payload_height_1);
-            let inputs_2 = create_sig_inputs_with_height(94, payload_height_1);
+            let inputs_1 = create_sig_inputs_with_height(
+                93,
+                payload_height_1,
+                AlgorithmId::ThresholdEcdsaSecp256k1,
+            );
+            let inputs_2 = create_sig_inputs_with_height(
+                94,
+                payload_height_1,
+                AlgorithmId::ThresholdEcdsaSecp256k1,
+            );
             let reshare_key_transcript = create_key_transcript();
             let reshare_key_transcript_ref =
                 ecdsa::UnmaskedTranscript::try_from((payload_height_1, &reshare_key_transcript))
@@ -3163,6 +3221,7 @@ mod tests {
             let (kappa_config_ref, _lambda_config_ref) = create_new_quadruple_in_creation(
                 &subnet_nodes,
                 env.newest_registry_version,
+                AlgorithmId::ThresholdEcdsaSecp256k1,
                 &mut ecdsa_payload.uid_generator,
                 &mut ecdsa_payload.quadruples_in_creation,
             );
@@ -3343,8 +3402,16 @@ mod tests {
             let mut reshare_refs = BTreeMap::new();
             reshare_refs.insert(*reshare_key_transcript_ref.as_ref(), reshare_key_transcript);
 
-            let inputs_1 = create_sig_inputs_with_height(91, summary_height);
-            let inputs_2 = create_sig_inputs_with_height(92, summary_height);
+            let inputs_1 = create_sig_inputs_with_height(
+                91,
+                summary_height,
+                AlgorithmId::ThresholdEcdsaSecp256k1,
+            );
+            let inputs_2 = create_sig_inputs_with_height(
+                92,
+                summary_height,
+                AlgorithmId::ThresholdEcdsaSecp256k1,
+            );
             let summary_block = create_summary_block_with_transcripts(
                 subnet_id,
                 summary_height,
@@ -3361,8 +3428,16 @@ mod tests {
 
             // Create payload blocks with transcripts
             let payload_height_1 = Height::new(10);
-            let inputs_1 = create_sig_inputs_with_height(93, payload_height_1);
-            let inputs_2 = create_sig_inputs_with_height(94, payload_height_1);
+            let inputs_1 = create_sig_inputs_with_height(
+                93,
+                payload_height_1,
+                AlgorithmId::ThresholdEcdsaSecp256k1,
+            );
+            let inputs_2 = create_sig_inputs_with_height(
+                94,
+                payload_height_1,
+                AlgorithmId::ThresholdEcdsaSecp256k1,
+            );
             let reshare_key_transcript = create_key_transcript();
             let reshare_key_transcript_ref =
                 ecdsa::UnmaskedTranscript::try_from((payload_height_1, &reshare_key_transcript))
@@ -3425,6 +3500,7 @@ mod tests {
             let (kappa_config_ref, _lambda_config_ref) = create_new_quadruple_in_creation(
                 &subnet_nodes,
                 env.newest_registry_version,
+                AlgorithmId::ThresholdEcdsaSecp256k1,
                 &mut ecdsa_payload.uid_generator,
                 &mut ecdsa_payload.quadruples_in_creation,
             );
@@ -1397,65 +1397,67 @@ mod tests {
     // Tests the Action logic
     #[test]
     fn test_ecdsa_pre_signer_action() {
-        let (id_1, id_2, id_3, id_4) = (
-            create_transcript_id(1),
-            create_transcript_id(2),
-            create_transcript_id(3),
-            create_transcript_id(4),
-        );
-
-        // The finalized block requests transcripts 1, 2, 3
-        let nodes = [NODE_1];
-        let block_reader = TestEcdsaBlockReader::for_pre_signer_test(
-            Height::from(100),
-            vec![
-                create_transcript_param(id_1, &nodes, &nodes),
-                create_transcript_param(id_2, &nodes, &nodes),
-                create_transcript_param(id_3, &nodes, &nodes),
-            ],
-        );
-        let mut requested = BTreeMap::new();
-        for transcript_params_ref in block_reader.requested_transcripts() {
-            requested.insert(transcript_params_ref.transcript_id, transcript_params_ref);
-        }
-
-        // Message from a node ahead of us
-        assert_eq!(
-            Action::action(&block_reader, &requested, Some(Height::from(200)), &id_4),
-            Action::Defer
-        );
+        for algorithm in all_ecdsa_algorithms() {
+            let (id_1, id_2, id_3, id_4) = (
+                create_transcript_id(1),
+                create_transcript_id(2),
+                create_transcript_id(3),
+                create_transcript_id(4),
+            );
+
+            // The finalized block requests transcripts 1, 2, 3
+            let nodes = [NODE_1];
+            let block_reader = TestEcdsaBlockReader::for_pre_signer_test(
+                Height::from(100),
+                vec![
+                    create_transcript_param(id_1, &nodes, &nodes, algorithm),
+                    create_transcript_param(id_2, &nodes, &nodes, algorithm),
+                    create_transcript_param(id_3, &nodes, &nodes, algorithm),
+                ],
+            );
+            let mut requested = BTreeMap::new();
+            for transcript_params_ref in block_reader.requested_transcripts() {
+                requested.insert(transcript_params_ref.transcript_id, transcript_params_ref);
+            }
 
-        // Messages for transcripts not being currently requested
-        assert_eq!(
-            Action::action(
-                &block_reader,
-                &requested,
-                Some(Height::from(100)),
-                &create_transcript_id(234)
-            ),
-            Action::Drop
-        );
-        assert_eq!(
-            Action::action(
-                &block_reader,
-                &requested,
-                Some(Height::from(10)),
-                &create_transcript_id(234)
-            ),
-            Action::Drop
-        );
+            // Message from a node ahead of us
+            assert_eq!(
+                Action::action(&block_reader, &requested, Some(Height::from(200)), &id_4),
+                Action::Defer
+            );
+
+            // Messages for transcripts not being currently requested
+            assert_eq!(
+                Action::action(
+                    &block_reader,
+                    &requested,
+                    Some(Height::from(100)),
+                    &create_transcript_id(234)
+                ),
+                Action::Drop
+            );
+            assert_eq!(
+                Action::action(
+                    &block_reader,
+                    &requested,
+                    Some(Height::from(10)),
+                    &create_transcript_id(234)
+                ),
+                Action::Drop
+            );
 
-        // Messages for transcripts currently requested
-        let action = Action::action(&block_reader, &requested, Some(Height::from(100)), &id_1);
-        match action {
-            Action::Process(_) => {}
-            _ => panic!("Unexpected action: {:?}", action),
-        }
+            // Messages for transcripts currently requested
+            let action = Action::action(&block_reader, &requested, Some(Height::from(100)), &id_1);
+            match action {
+                Action::Process(_) => {}
+                _ => panic!("Unexpected action: {:?}", action),
+            }
 
-        let action = Action::action(&block_reader, &requested, Some(Height::from(10)), &id_2);
-        match action {
-            Action::Process(_) => {}
-            _ => panic!("Unexpected action: {:?}", action),
+            let action = Action::action(&block_reader, &requested, Some(Height::from(10)), &id_2);
+            match action {
+                Action::Process(_) => {}
+                _ => panic!("Unexpected action: {:?}", action),
+            }
         }
     }
 
@@ -1463,116 +1465,134 @@ mod tests {
     // in progress are filtered out.
     #[test]
     fn test_ecdsa_send_dealings() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, pre_signer) =
-                    create_pre_signer_dependencies(pool_config, logger);
-                let (id_1, id_2, id_3, id_4, id_5) = (
-                    create_transcript_id(1),
-                    create_transcript_id(2),
-                    create_transcript_id(3),
-                    create_transcript_id(4),
-                    create_transcript_id(5),
-                );
-
-                // Set up the ECDSA pool. Pool has dealings for transcripts 1, 2, 3.
-                // Only dealing for transcript 1 is issued by us.
-                let dealing_1 = create_dealing(id_1, NODE_1);
-                let dealing_2 = create_dealing(id_2, NODE_2);
-                let dealing_3 = create_dealing(id_3, NODE_3);
-                let change_set = vec![
-                    EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaSignedDealing(dealing_1)),
-                    EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaSignedDealing(dealing_2)),
-                    EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaSignedDealing(dealing_3)),
-                ];
-                ecdsa_pool.apply_changes(change_set);
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, pre_signer) =
+                        create_pre_signer_dependencies(pool_config, logger);
+                    let (id_1, id_2, id_3, id_4, id_5) = (
+                        create_transcript_id(1),
+                        create_transcript_id(2),
+                        create_transcript_id(3),
+                        create_transcript_id(4),
+                        create_transcript_id(5),
+                    );
 
-                // Set up the transcript creation request
-                // The block requests transcripts 1, 4, 5
-                let t1 = create_transcript_param(id_1, &[NODE_1], &[NODE_2]);
-                let t2 = create_transcript_param(id_4, &[NODE_1], &[NODE_3]);
-                let t3 = create_transcript_param(id_5, &[NODE_1], &[NODE_4]);
-                let block_reader =
-                    TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t1, t2, t3]);
-                let transcript_loader: TestEcdsaTranscriptLoader = Default::default();
-
-                // Since transcript 1 is already in progress, we should issue
-                // dealings only for transcripts 4, 5
-                let change_set =
-                    pre_signer.send_dealings(&ecdsa_pool, &transcript_loader, &block_reader);
-                assert_eq!(change_set.len(), 2);
-                assert!(is_dealing_added_to_validated(&change_set, &id_4,));
-                assert!(is_dealing_added_to_validated(&change_set, &id_5,));
+                    // Set up the ECDSA pool. Pool has dealings for transcripts 1, 2, 3.
+                    // Only dealing for transcript 1 is issued by us.
+                    let dealing_1 = create_dealing(id_1, NODE_1);
+                    let dealing_2 = create_dealing(id_2, NODE_2);
+                    let dealing_3 = create_dealing(id_3, NODE_3);
+                    let change_set = vec![
+                        EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaSignedDealing(
+                            dealing_1,
+                        )),
+                        EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaSignedDealing(
+                            dealing_2,
+                        )),
+                        EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaSignedDealing(
+                            dealing_3,
+                        )),
+                    ];
+                    ecdsa_pool.apply_changes(change_set);
+
+                    // Set up the transcript creation request
+                    // The block requests transcripts 1, 4, 5
+                    let t1 = create_transcript_param(id_1, &[NODE_1], &[NODE_2], algorithm);
+                    let t2 = create_transcript_param(id_4, &[NODE_1], &[NODE_3], algorithm);
+                    let t3 = create_transcript_param(id_5, &[NODE_1], &[NODE_4], algorithm);
+                    let block_reader = TestEcdsaBlockReader::for_pre_signer_test(
+                        Height::from(100),
+                        vec![t1, t2, t3],
+                    );
+                    let transcript_loader: TestEcdsaTranscriptLoader = Default::default();
+
+                    // Since transcript 1 is already in progress, we should issue
+                    // dealings only for transcripts 4, 5
+                    let change_set =
+                        pre_signer.send_dealings(&ecdsa_pool, &transcript_loader, &block_reader);
+                    assert_eq!(change_set.len(), 2);
+                    assert!(is_dealing_added_to_validated(&change_set, &id_4,));
+                    assert!(is_dealing_added_to_validated(&change_set, &id_5,));
+                })
             })
-        })
+        }
     }
 
     // Tests that dealing is not issued if the node is in the list of dealers
     // specified by the transcript params
     #[test]
     fn test_ecdsa_non_dealers_dont_send_dealings() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (ecdsa_pool, pre_signer) = create_pre_signer_dependencies(pool_config, logger);
-                let (id_1, id_2) = (create_transcript_id(1), create_transcript_id(2));
-
-                // transcript 1 has NODE_1 as a dealer
-                let t1 = create_transcript_param(id_1, &[NODE_1], &[NODE_1]);
-
-                // Transcript 2 doesn't have NODE_1 as a dealer
-                let t2 = create_transcript_param(id_2, &[NODE_2], &[NODE_2]);
-
-                // Transcript 2 should not result in a dealing
-                let block_reader =
-                    TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t1, t2]);
-                let transcript_loader: TestEcdsaTranscriptLoader = Default::default();
-
-                let change_set =
-                    pre_signer.send_dealings(&ecdsa_pool, &transcript_loader, &block_reader);
-                assert_eq!(change_set.len(), 1);
-                assert!(is_dealing_added_to_validated(&change_set, &id_1,));
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (ecdsa_pool, pre_signer) =
+                        create_pre_signer_dependencies(pool_config, logger);
+                    let (id_1, id_2) = (create_transcript_id(1), create_transcript_id(2));
+
+                    // transcript 1 has NODE_1 as a dealer
+                    let t1 = create_transcript_param(id_1, &[NODE_1], &[NODE_1], algorithm);
+
+                    // Transcript 2 doesn't have NODE_1 as a dealer
+                    let t2 = create_transcript_param(id_2, &[NODE_2], &[NODE_2], algorithm);
+
+                    // Transcript 2 should not result in a dealing
+                    let block_reader =
+                        TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t1, t2]);
+                    let transcript_loader: TestEcdsaTranscriptLoader = Default::default();
+
+                    let change_set =
+                        pre_signer.send_dealings(&ecdsa_pool, &transcript_loader, &block_reader);
+                    assert_eq!(change_set.len(), 1);
+                    assert!(is_dealing_added_to_validated(&change_set, &id_1,));
+                })
             })
-        })
+        }
     }
 
     // Tests that complaints are generated and added to the pool if loading transcript
     // results in complaints.
     #[test]
     fn test_ecdsa_send_dealings_with_complaints() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (ecdsa_pool, pre_signer) = create_pre_signer_dependencies(pool_config, logger);
-                let (id_1, id_2, id_3) = (
-                    create_transcript_id(1),
-                    create_transcript_id(2),
-                    create_transcript_id(3),
-                );
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (ecdsa_pool, pre_signer) =
+                        create_pre_signer_dependencies(pool_config, logger);
+                    let (id_1, id_2, id_3) = (
+                        create_transcript_id(1),
+                        create_transcript_id(2),
+                        create_transcript_id(3),
+                    );
 
-                // Set up the transcript creation request
-                // The block requests transcripts 1, 2, 3
-                let t1 = create_transcript_param(id_1, &[NODE_1], &[NODE_2]);
-                let t2 = create_transcript_param(id_2, &[NODE_1], &[NODE_3]);
-                let t3 = create_transcript_param(id_3, &[NODE_1], &[NODE_4]);
-                let block_reader =
-                    TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t1, t2, t3]);
-                let transcript_loader =
-                    TestEcdsaTranscriptLoader::new(TestTranscriptLoadStatus::Complaints);
-
-                let change_set =
-                    pre_signer.send_dealings(&ecdsa_pool, &transcript_loader, &block_reader);
-                let complaints = transcript_loader.returned_complaints();
-                assert_eq!(change_set.len(), complaints.len());
-                assert_eq!(change_set.len(), 3);
-                for complaint in complaints {
-                    assert!(is_complaint_added_to_validated(
-                        &change_set,
-                        &complaint.content.idkg_complaint.transcript_id,
-                        &NODE_1,
-                        &NODE_1,
-                    ));
-                }
+                    // Set up the transcript creation request
+                    // The block requests transcripts 1, 2, 3
+                    let t1 = create_transcript_param(id_1, &[NODE_1], &[NODE_2], algorithm);
+                    let t2 = create_transcript_param(id_2, &[NODE_1], &[NODE_3], algorithm);
+                    let t3 = create_transcript_param(id_3, &[NODE_1], &[NODE_4], algorithm);
+                    let block_reader = TestEcdsaBlockReader::for_pre_signer_test(
+                        Height::from(100),
+                        vec![t1, t2, t3],
+                    );
+                    let transcript_loader =
+                        TestEcdsaTranscriptLoader::new(TestTranscriptLoadStatus::Complaints);
+
+                    let change_set =
+                        pre_signer.send_dealings(&ecdsa_pool, &transcript_loader, &block_reader);
+                    let complaints = transcript_loader.returned_complaints();
+                    assert_eq!(change_set.len(), complaints.len());
+                    assert_eq!(change_set.len(), 3);
+                    for complaint in complaints {
+                        assert!(is_complaint_added_to_validated(
+                            &change_set,
+                            &complaint.content.idkg_complaint.transcript_id,
+                            &NODE_1,
+                            &NODE_1,
+                        ));
+                    }
+                })
             })
-        })
+        }
     }
 
     // Tests that received dealings are accepted/processed for eligible transcript
@@ -1580,253 +1600,265 @@ mod tests {
     // TODO: mock crypto and test failure path
     #[test]
     fn test_ecdsa_validate_dealings() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, pre_signer) =
-                    create_pre_signer_dependencies(pool_config, logger);
-                let time_source = FastForwardTimeSource::new();
-                let (id_2, id_3, id_4) = (
-                    create_transcript_id_with_height(2, Height::from(100)),
-                    create_transcript_id_with_height(3, Height::from(10)),
-                    create_transcript_id_with_height(4, Height::from(5)),
-                );
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, pre_signer) =
+                        create_pre_signer_dependencies(pool_config, logger);
+                    let time_source = FastForwardTimeSource::new();
+                    let (id_2, id_3, id_4) = (
+                        create_transcript_id_with_height(2, Height::from(100)),
+                        create_transcript_id_with_height(3, Height::from(10)),
+                        create_transcript_id_with_height(4, Height::from(5)),
+                    );
 
-                // Set up the transcript creation request
-                // The block requests transcripts 2, 3
-                let t2 = create_transcript_param(id_2, &[NODE_2], &[NODE_1]);
-                let t3 = create_transcript_param(id_3, &[NODE_2], &[NODE_1]);
-                let block_reader =
-                    TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t2, t3]);
-
-                // Set up the ECDSA pool
-                // A dealing for a transcript that is requested by finalized block (accepted)
-                let dealing = create_dealing(id_2, NODE_2);
-                let msg_id_2 = dealing.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSignedDealing(dealing),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // A dealing for a transcript that is requested by finalized block (accepted)
-                let dealing = create_dealing(id_3, NODE_2);
-                let msg_id_3 = dealing.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSignedDealing(dealing),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // A dealing for a transcript that is not requested by finalized block (dropped)
-                let dealing = create_dealing(id_4, NODE_2);
-                let msg_id_4 = dealing.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSignedDealing(dealing),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                let change_set = pre_signer.validate_dealings(&ecdsa_pool, &block_reader);
-                assert_eq!(change_set.len(), 3);
-                assert!(is_moved_to_validated(&change_set, &msg_id_2));
-                assert!(is_moved_to_validated(&change_set, &msg_id_3));
-                assert!(is_removed_from_unvalidated(&change_set, &msg_id_4));
+                    // Set up the transcript creation request
+                    // The block requests transcripts 2, 3
+                    let t2 = create_transcript_param(id_2, &[NODE_2], &[NODE_1], algorithm);
+                    let t3 = create_transcript_param(id_3, &[NODE_2], &[NODE_1], algorithm);
+                    let block_reader =
+                        TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t2, t3]);
+
+                    // Set up the ECDSA pool
+                    // A dealing for a transcript that is requested by finalized block (accepted)
+                    let dealing = create_dealing(id_2, NODE_2);
+                    let msg_id_2 = dealing.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSignedDealing(dealing),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // A dealing for a transcript that is requested by finalized block (accepted)
+                    let dealing = create_dealing(id_3, NODE_2);
+                    let msg_id_3 = dealing.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSignedDealing(dealing),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // A dealing for a transcript that is not requested by finalized block (dropped)
+                    let dealing = create_dealing(id_4, NODE_2);
+                    let msg_id_4 = dealing.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSignedDealing(dealing),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    let change_set = pre_signer.validate_dealings(&ecdsa_pool, &block_reader);
+                    assert_eq!(change_set.len(), 3);
+                    assert!(is_moved_to_validated(&change_set, &msg_id_2));
+                    assert!(is_moved_to_validated(&change_set, &msg_id_3));
+                    assert!(is_removed_from_unvalidated(&change_set, &msg_id_4));
+                })
             })
-        })
+        }
     }
 
     // Tests that duplicate dealings from a dealer for the same transcript
     // are dropped.
     #[test]
     fn test_ecdsa_duplicate_dealing() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, pre_signer) =
-                    create_pre_signer_dependencies(pool_config, logger);
-                let time_source = FastForwardTimeSource::new();
-                let id_2 = create_transcript_id_with_height(2, Height::from(100));
-
-                // Set up the ECDSA pool
-                // Validated pool has: {transcript 2, dealer = NODE_2}
-                let dealing = create_dealing(id_2, NODE_2);
-                let change_set = vec![EcdsaChangeAction::AddToValidated(
-                    EcdsaMessage::EcdsaSignedDealing(dealing),
-                )];
-                ecdsa_pool.apply_changes(change_set);
-
-                // Unvalidated pool has: {transcript 2, dealer = NODE_2, height = 100}
-                let dealing = create_dealing(id_2, NODE_2);
-                let msg_id_2 = dealing.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSignedDealing(dealing),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                let t2 = create_transcript_param(id_2, &[NODE_2], &[NODE_1]);
-                let block_reader =
-                    TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t2]);
-
-                let change_set = pre_signer.validate_dealings(&ecdsa_pool, &block_reader);
-                assert_eq!(change_set.len(), 1);
-                assert!(is_handle_invalid(&change_set, &msg_id_2));
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, pre_signer) =
+                        create_pre_signer_dependencies(pool_config, logger);
+                    let time_source = FastForwardTimeSource::new();
+                    let id_2 = create_transcript_id_with_height(2, Height::from(100));
+
+                    // Set up the ECDSA pool
+                    // Validated pool has: {transcript 2, dealer = NODE_2}
+                    let dealing = create_dealing(id_2, NODE_2);
+                    let change_set = vec![EcdsaChangeAction::AddToValidated(
+                        EcdsaMessage::EcdsaSignedDealing(dealing),
+                    )];
+                    ecdsa_pool.apply_changes(change_set);
+
+                    // Unvalidated pool has: {transcript 2, dealer = NODE_2, height = 100}
+                    let dealing = create_dealing(id_2, NODE_2);
+                    let msg_id_2 = dealing.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSignedDealing(dealing),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    let t2 = create_transcript_param(id_2, &[NODE_2], &[NODE_1], algorithm);
+                    let block_reader =
+                        TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t2]);
+
+                    let change_set = pre_signer.validate_dealings(&ecdsa_pool, &block_reader);
+                    assert_eq!(change_set.len(), 1);
+                    assert!(is_handle_invalid(&change_set, &msg_id_2));
+                })
             })
-        })
+        }
     }
 
     // Tests that duplicate dealings from a dealer for the same transcript
     // in the unvalidated pool are dropped.
     #[test]
     fn test_ecdsa_duplicate_dealing_in_batch() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, pre_signer) =
-                    create_pre_signer_dependencies(pool_config, logger);
-                let time_source = FastForwardTimeSource::new();
-                let id_2 = create_transcript_id_with_height(2, Height::from(100));
-
-                // Set up the ECDSA pool
-                // Unvalidated pool has: {transcript 2, dealer = NODE_2, height = 100, internal_dealing_raw = vec[1]}
-                let mut dealing = create_dealing(id_2, NODE_2);
-                dealing.content.internal_dealing_raw = vec![1];
-                let msg_id_2_a = dealing.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSignedDealing(dealing),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // Unvalidated pool has: {transcript 2, dealer = NODE_2, height = 100, , internal_dealing_raw = vec[2]}
-                let mut dealing = create_dealing(id_2, NODE_2);
-                dealing.content.internal_dealing_raw = vec![2];
-                let msg_id_2_b = dealing.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSignedDealing(dealing),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // Unvalidated pool has: {transcript 2, dealer = NODE_3, height = 100, , internal_dealing_raw = vec[3]}
-                let mut dealing = create_dealing(id_2, NODE_3);
-                dealing.content.internal_dealing_raw = vec![3];
-                let msg_id_3 = dealing.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSignedDealing(dealing),
-                    peer_id: NODE_3,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                let t2 = create_transcript_param(id_2, &[NODE_2, NODE_3], &[NODE_1]);
-                let block_reader =
-                    TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t2]);
-
-                // One of msg_id_2_a or msg_id_2_b should be accepted, the other one dropped
-                let change_set = pre_signer.validate_dealings(&ecdsa_pool, &block_reader);
-                assert_eq!(change_set.len(), 3);
-                if is_moved_to_validated(&change_set, &msg_id_2_a) {
-                    assert!(is_handle_invalid(&change_set, &msg_id_2_b));
-                } else if is_moved_to_validated(&change_set, &msg_id_2_b) {
-                    assert!(is_handle_invalid(&change_set, &msg_id_2_a));
-                } else {
-                    panic!("Neither dealing was accepted");
-                }
-                assert!(is_moved_to_validated(&change_set, &msg_id_3));
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, pre_signer) =
+                        create_pre_signer_dependencies(pool_config, logger);
+                    let time_source = FastForwardTimeSource::new();
+                    let id_2 = create_transcript_id_with_height(2, Height::from(100));
+
+                    // Set up the ECDSA pool
+                    // Unvalidated pool has: {transcript 2, dealer = NODE_2, height = 100, internal_dealing_raw = vec[1]}
+                    let mut dealing = create_dealing(id_2, NODE_2);
+                    dealing.content.internal_dealing_raw = vec![1];
+                    let msg_id_2_a = dealing.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSignedDealing(dealing),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // Unvalidated pool has: {transcript 2, dealer = NODE_2, height = 100, , internal_dealing_raw = vec[2]}
+                    let mut dealing = create_dealing(id_2, NODE_2);
+                    dealing.content.internal_dealing_raw = vec![2];
+                    let msg_id_2_b = dealing.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSignedDealing(dealing),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // Unvalidated pool has: {transcript 2, dealer = NODE_3, height = 100, , internal_dealing_raw = vec[3]}
+                    let mut dealing = create_dealing(id_2, NODE_3);
+                    dealing.content.internal_dealing_raw = vec![3];
+                    let msg_id_3 = dealing.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSignedDealing(dealing),
+                        peer_id: NODE_3,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    let t2 = create_transcript_param(id_2, &[NODE_2, NODE_3], &[NODE_1], algorithm);
+                    let block_reader =
+                        TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t2]);
+
+                    // One of msg_id_2_a or msg_id_2_b should be accepted, the other one dropped
+                    let change_set = pre_signer.validate_dealings(&ecdsa_pool, &block_reader);
+                    assert_eq!(change_set.len(), 3);
+                    if is_moved_to_validated(&change_set, &msg_id_2_a) {
+                        assert!(is_handle_invalid(&change_set, &msg_id_2_b));
+                    } else if is_moved_to_validated(&change_set, &msg_id_2_b) {
+                        assert!(is_handle_invalid(&change_set, &msg_id_2_a));
+                    } else {
+                        panic!("Neither dealing was accepted");
+                    }
+                    assert!(is_moved_to_validated(&change_set, &msg_id_3));
+                })
             })
-        })
+        }
     }
 
     // Tests that dealings from a dealer that is not in the dealer list for the
     // transcript are dropped.
     #[test]
     fn test_ecdsa_unexpected_dealing() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, pre_signer) =
-                    create_pre_signer_dependencies(pool_config, logger);
-                let time_source = FastForwardTimeSource::new();
-                let id_2 = create_transcript_id_with_height(2, Height::from(100));
-
-                // Unvalidated pool has: {transcript 2, dealer = NODE_2, height = 100}
-                let dealing = create_dealing(id_2, NODE_2);
-                let msg_id_2 = dealing.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSignedDealing(dealing),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // NODE_2 is not in the dealer list
-                let t2 = create_transcript_param(id_2, &[NODE_3], &[NODE_1]);
-                let block_reader =
-                    TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t2]);
-
-                let change_set = pre_signer.validate_dealings(&ecdsa_pool, &block_reader);
-                assert_eq!(change_set.len(), 1);
-                assert!(is_handle_invalid(&change_set, &msg_id_2));
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, pre_signer) =
+                        create_pre_signer_dependencies(pool_config, logger);
+                    let time_source = FastForwardTimeSource::new();
+                    let id_2 = create_transcript_id_with_height(2, Height::from(100));
+
+                    // Unvalidated pool has: {transcript 2, dealer = NODE_2, height = 100}
+                    let dealing = create_dealing(id_2, NODE_2);
+                    let msg_id_2 = dealing.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSignedDealing(dealing),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // NODE_2 is not in the dealer list
+                    let t2 = create_transcript_param(id_2, &[NODE_3], &[NODE_1], algorithm);
+                    let block_reader =
+                        TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t2]);
+
+                    let change_set = pre_signer.validate_dealings(&ecdsa_pool, &block_reader);
+                    assert_eq!(change_set.len(), 1);
+                    assert!(is_handle_invalid(&change_set, &msg_id_2));
+                })
             })
-        })
+        }
     }
 
     // Tests that support shares are sent to eligible dealings
     #[test]
     fn test_ecdsa_send_support() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, pre_signer) =
-                    create_pre_signer_dependencies(pool_config, logger);
-                let id = create_transcript_id(1);
-
-                // We haven't sent support yet, and we are in the receiver list
-                let dealing = create_dealing(id, NODE_2);
-                let change_set = vec![EcdsaChangeAction::AddToValidated(
-                    EcdsaMessage::EcdsaSignedDealing(dealing),
-                )];
-                ecdsa_pool.apply_changes(change_set);
-                let t = create_transcript_param(id, &[NODE_2], &[NODE_1]);
-
-                let block_reader =
-                    TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
-                let change_set = pre_signer.send_dealing_support(&ecdsa_pool, &block_reader);
-                assert_eq!(change_set.len(), 1);
-                assert!(is_dealing_support_added_to_validated(
-                    &change_set,
-                    &id,
-                    &NODE_2,
-                ));
-                ecdsa_pool.apply_changes(change_set);
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, pre_signer) =
+                        create_pre_signer_dependencies(pool_config, logger);
+                    let id = create_transcript_id(1);
+
+                    // We haven't sent support yet, and we are in the receiver list
+                    let dealing = create_dealing(id, NODE_2);
+                    let change_set = vec![EcdsaChangeAction::AddToValidated(
+                        EcdsaMessage::EcdsaSignedDealing(dealing),
+                    )];
+                    ecdsa_pool.apply_changes(change_set);
+                    let t = create_transcript_param(id, &[NODE_2], &[NODE_1], algorithm);
+
+                    let block_reader =
+                        TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
+                    let change_set = pre_signer.send_dealing_support(&ecdsa_pool, &block_reader);
+                    assert_eq!(change_set.len(), 1);
+                    assert!(is_dealing_support_added_to_validated(
+                        &change_set,
+                        &id,
+                        &NODE_2,
+                    ));
+                    ecdsa_pool.apply_changes(change_set);
 
-                // Since we already issued support for the dealing, it should not produce any
-                // more support.
-                let change_set = pre_signer.send_dealing_support(&ecdsa_pool, &block_reader);
-                assert!(change_set.is_empty());
+                    // Since we already issued support for the dealing, it should not produce any
+                    // more support.
+                    let change_set = pre_signer.send_dealing_support(&ecdsa_pool, &block_reader);
+                    assert!(change_set.is_empty());
+                })
             })
-        })
+        }
     }
 
     // Tests that support shares are not sent by nodes not in the receiver list for
     // the transcript
     #[test]
     fn test_ecdsa_non_receivers_dont_send_support() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, pre_signer) =
-                    create_pre_signer_dependencies(pool_config, logger);
-                let id = create_transcript_id(1);
-
-                // We are not in the receiver list for the transcript
-                let dealing = create_dealing(id, NODE_2);
-                let change_set = vec![EcdsaChangeAction::AddToValidated(
-                    EcdsaMessage::EcdsaSignedDealing(dealing),
-                )];
-                ecdsa_pool.apply_changes(change_set);
-                let t = create_transcript_param(id, &[NODE_2], &[NODE_3]);
-
-                let block_reader =
-                    TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
-                let change_set = pre_signer.send_dealing_support(&ecdsa_pool, &block_reader);
-                assert!(change_set.is_empty());
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, pre_signer) =
+                        create_pre_signer_dependencies(pool_config, logger);
+                    let id = create_transcript_id(1);
+
+                    // We are not in the receiver list for the transcript
+                    let dealing = create_dealing(id, NODE_2);
+                    let change_set = vec![EcdsaChangeAction::AddToValidated(
+                        EcdsaMessage::EcdsaSignedDealing(dealing),
+                    )];
+                    ecdsa_pool.apply_changes(change_set);
+                    let t = create_transcript_param(id, &[NODE_2], &[NODE_3], algorithm);
+
+                    let block_reader =
+                        TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
+                    let change_set = pre_signer.send_dealing_support(&ecdsa_pool, &block_reader);
+                    assert!(change_set.is_empty());
+                })
             })
-        })
+        }
     }
 
     // Tests that support shares are not sent for transcripts we are not building
@@ -1856,395 +1888,430 @@ mod tests {
     // transcript requests, and others dealings are either deferred or dropped.
     #[test]
     fn test_ecdsa_validate_dealing_support() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, pre_signer) =
-                    create_pre_signer_dependencies(pool_config, logger);
-                let time_source = FastForwardTimeSource::new();
-                let (id_2, id_3, id_4) = (
-                    create_transcript_id_with_height(2, Height::from(25)),
-                    create_transcript_id_with_height(3, Height::from(10)),
-                    create_transcript_id_with_height(4, Height::from(5)),
-                );
-
-                // Set up the transcript creation request
-                // The block requests transcripts 2, 3
-                let t2 = create_transcript_param(id_2, &[NODE_2], &[NODE_3]);
-                let t3 = create_transcript_param(id_3, &[NODE_2], &[NODE_3]);
-                let block_reader =
-                    TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t2, t3]);
-
-                // Set up the ECDSA pool
-                // A dealing for a transcript that is requested by finalized block,
-                // and we already have the dealing(share accepted)
-                let (dealing, support) = create_support(id_2, NODE_2, NODE_3);
-                let change_set = vec![EcdsaChangeAction::AddToValidated(
-                    EcdsaMessage::EcdsaSignedDealing(dealing),
-                )];
-                ecdsa_pool.apply_changes(change_set);
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, pre_signer) =
+                        create_pre_signer_dependencies(pool_config, logger);
+                    let time_source = FastForwardTimeSource::new();
+                    let (id_2, id_3, id_4) = (
+                        create_transcript_id_with_height(2, Height::from(25)),
+                        create_transcript_id_with_height(3, Height::from(10)),
+                        create_transcript_id_with_height(4, Height::from(5)),
+                    );
 
-                let msg_id_2 = support.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaDealingSupport(support),
-                    peer_id: NODE_3,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // A dealing for a transcript that is requested by finalized block,
-                // but we don't have the dealing yet(share deferred)
-                let (_, support) = create_support(id_3, NODE_2, NODE_3);
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaDealingSupport(support),
-                    peer_id: NODE_3,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // A dealing for a transcript that is not requested by finalized block
-                // (share dropped)
-                let (_, support) = create_support(id_4, NODE_2, NODE_3);
-                let msg_id_4 = support.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaDealingSupport(support),
-                    peer_id: NODE_3,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                let change_set = pre_signer.validate_dealing_support(&ecdsa_pool, &block_reader);
-                assert_eq!(change_set.len(), 2);
-                assert!(is_moved_to_validated(&change_set, &msg_id_2));
-                assert!(is_removed_from_unvalidated(&change_set, &msg_id_4));
+                    // Set up the transcript creation request
+                    // The block requests transcripts 2, 3
+                    let t2 = create_transcript_param(id_2, &[NODE_2], &[NODE_3], algorithm);
+                    let t3 = create_transcript_param(id_3, &[NODE_2], &[NODE_3], algorithm);
+                    let block_reader =
+                        TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t2, t3]);
+
+                    // Set up the ECDSA pool
+                    // A dealing for a transcript that is requested by finalized block,
+                    // and we already have the dealing(share accepted)
+                    let (dealing, support) = create_support(id_2, NODE_2, NODE_3);
+                    let change_set = vec![EcdsaChangeAction::AddToValidated(
+                        EcdsaMessage::EcdsaSignedDealing(dealing),
+                    )];
+                    ecdsa_pool.apply_changes(change_set);
+
+                    let msg_id_2 = support.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaDealingSupport(support),
+                        peer_id: NODE_3,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // A dealing for a transcript that is requested by finalized block,
+                    // but we don't have the dealing yet(share deferred)
+                    let (_, support) = create_support(id_3, NODE_2, NODE_3);
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaDealingSupport(support),
+                        peer_id: NODE_3,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // A dealing for a transcript that is not requested by finalized block
+                    // (share dropped)
+                    let (_, support) = create_support(id_4, NODE_2, NODE_3);
+                    let msg_id_4 = support.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaDealingSupport(support),
+                        peer_id: NODE_3,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    let change_set =
+                        pre_signer.validate_dealing_support(&ecdsa_pool, &block_reader);
+                    assert_eq!(change_set.len(), 2);
+                    assert!(is_moved_to_validated(&change_set, &msg_id_2));
+                    assert!(is_removed_from_unvalidated(&change_set, &msg_id_4));
+                })
             })
-        })
+        }
     }
 
     // Tests that duplicate support from a node for the same dealing
     // are dropped.
     #[test]
     fn test_ecdsa_duplicate_support_from_node() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, pre_signer) =
-                    create_pre_signer_dependencies(pool_config, logger);
-                let time_source = FastForwardTimeSource::new();
-                let id = create_transcript_id_with_height(1, Height::from(100));
-
-                // Set up the ECDSA pool
-                // Validated pool has: support {transcript 2, dealer = NODE_2, signer = NODE_3}
-                let (dealing, support) = create_support(id, NODE_2, NODE_3);
-                let change_set = vec![EcdsaChangeAction::AddToValidated(
-                    EcdsaMessage::EcdsaSignedDealing(dealing),
-                )];
-                ecdsa_pool.apply_changes(change_set);
-
-                let change_set = vec![EcdsaChangeAction::AddToValidated(
-                    EcdsaMessage::EcdsaDealingSupport(support.clone()),
-                )];
-                ecdsa_pool.apply_changes(change_set);
-
-                // Unvalidated pool has: duplicate of the same support share
-                let msg_id = support.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaDealingSupport(support),
-                    peer_id: NODE_3,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                let t = create_transcript_param(id, &[NODE_2], &[NODE_3]);
-                let block_reader =
-                    TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
-
-                let change_set = pre_signer.validate_dealing_support(&ecdsa_pool, &block_reader);
-                assert_eq!(change_set.len(), 1);
-                assert!(is_handle_invalid(&change_set, &msg_id));
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, pre_signer) =
+                        create_pre_signer_dependencies(pool_config, logger);
+                    let time_source = FastForwardTimeSource::new();
+                    let id = create_transcript_id_with_height(1, Height::from(100));
+
+                    // Set up the ECDSA pool
+                    // Validated pool has: support {transcript 2, dealer = NODE_2, signer = NODE_3}
+                    let (dealing, support) = create_support(id, NODE_2, NODE_3);
+                    let change_set = vec![EcdsaChangeAction::AddToValidated(
+                        EcdsaMessage::EcdsaSignedDealing(dealing),
+                    )];
+                    ecdsa_pool.apply_changes(change_set);
+
+                    let change_set = vec![EcdsaChangeAction::AddToValidated(
+                        EcdsaMessage::EcdsaDealingSupport(support.clone()),
+                    )];
+                    ecdsa_pool.apply_changes(change_set);
+
+                    // Unvalidated pool has: duplicate of the same support share
+                    let msg_id = support.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaDealingSupport(support),
+                        peer_id: NODE_3,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    let t = create_transcript_param(id, &[NODE_2], &[NODE_3], algorithm);
+                    let block_reader =
+                        TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
+
+                    let change_set =
+                        pre_signer.validate_dealing_support(&ecdsa_pool, &block_reader);
+                    assert_eq!(change_set.len(), 1);
+                    assert!(is_handle_invalid(&change_set, &msg_id));
+                })
             })
-        })
+        }
     }
 
     // Tests that support from a node that is not in the receiver list for the
     // transcript are dropped.
     #[test]
     fn test_ecdsa_unexpected_support_from_node() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, pre_signer) =
-                    create_pre_signer_dependencies(pool_config, logger);
-                let time_source = FastForwardTimeSource::new();
-                let id = create_transcript_id_with_height(1, Height::from(10));
-
-                // Unvalidated pool has: support {transcript 2, dealer = NODE_2, signer =
-                // NODE_3}
-                let (_, support) = create_support(id, NODE_2, NODE_3);
-                let msg_id = support.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaDealingSupport(support),
-                    peer_id: NODE_3,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // NODE_3 is not in the receiver list
-                let t = create_transcript_param(id, &[NODE_2], &[NODE_4]);
-                let block_reader =
-                    TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
-                let change_set = pre_signer.validate_dealing_support(&ecdsa_pool, &block_reader);
-                assert_eq!(change_set.len(), 1);
-                assert!(is_handle_invalid(&change_set, &msg_id));
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, pre_signer) =
+                        create_pre_signer_dependencies(pool_config, logger);
+                    let time_source = FastForwardTimeSource::new();
+                    let id = create_transcript_id_with_height(1, Height::from(10));
+
+                    // Unvalidated pool has: support {transcript 2, dealer = NODE_2, signer =
+                    // NODE_3}
+                    let (_, support) = create_support(id, NODE_2, NODE_3);
+                    let msg_id = support.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaDealingSupport(support),
+                        peer_id: NODE_3,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // NODE_3 is not in the receiver list
+                    let t = create_transcript_param(id, &[NODE_2], &[NODE_4], algorithm);
+                    let block_reader =
+                        TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
+                    let change_set =
+                        pre_signer.validate_dealing_support(&ecdsa_pool, &block_reader);
+                    assert_eq!(change_set.len(), 1);
+                    assert!(is_handle_invalid(&change_set, &msg_id));
+                })
             })
-        })
+        }
     }
 
     // Tests that support with a meta data mismatch is dropped.
     #[test]
     fn test_ecdsa_dealing_support_meta_data_mismatch() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, pre_signer) =
-                    create_pre_signer_dependencies(pool_config, logger);
-                let time_source = FastForwardTimeSource::new();
-                let id = create_transcript_id_with_height(1, Height::from(10));
-
-                // Set up the ECDSA pool
-                // A dealing for a transcript that is requested by finalized block,
-                // and we already have the dealing(share accepted)
-                let (dealing, mut support) = create_support(id, NODE_2, NODE_3);
-                let change_set = vec![EcdsaChangeAction::AddToValidated(
-                    EcdsaMessage::EcdsaSignedDealing(dealing),
-                )];
-                ecdsa_pool.apply_changes(change_set);
-
-                support.dealer_id = NODE_3;
-                let msg_id = support.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaDealingSupport(support),
-                    peer_id: NODE_3,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // Set up the transcript creation request
-                // The block requests transcripts 1
-                let t = create_transcript_param(id, &[NODE_2], &[NODE_3]);
-                let block_reader =
-                    TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
-                let change_set = pre_signer.validate_dealing_support(&ecdsa_pool, &block_reader);
-                assert_eq!(change_set.len(), 1);
-                assert!(is_handle_invalid(&change_set, &msg_id));
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, pre_signer) =
+                        create_pre_signer_dependencies(pool_config, logger);
+                    let time_source = FastForwardTimeSource::new();
+                    let id = create_transcript_id_with_height(1, Height::from(10));
+
+                    // Set up the ECDSA pool
+                    // A dealing for a transcript that is requested by finalized block,
+                    // and we already have the dealing(share accepted)
+                    let (dealing, mut support) = create_support(id, NODE_2, NODE_3);
+                    let change_set = vec![EcdsaChangeAction::AddToValidated(
+                        EcdsaMessage::EcdsaSignedDealing(dealing),
+                    )];
+                    ecdsa_pool.apply_changes(change_set);
+
+                    support.dealer_id = NODE_3;
+                    let msg_id = support.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaDealingSupport(support),
+                        peer_id: NODE_3,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // Set up the transcript creation request
+                    // The block requests transcripts 1
+                    let t = create_transcript_param(id, &[NODE_2], &[NODE_3], algorithm);
+                    let block_reader =
+                        TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
+                    let change_set =
+                        pre_signer.validate_dealing_support(&ecdsa_pool, &block_reader);
+                    assert_eq!(change_set.len(), 1);
+                    assert!(is_handle_invalid(&change_set, &msg_id));
+                })
             })
-        })
+        }
     }
 
     // Tests that support with a dealing hash mismatch is dropped.
     #[test]
     fn test_ecdsa_dealing_support_hash_mismatch() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, pre_signer) =
-                    create_pre_signer_dependencies(pool_config, logger);
-                let time_source = FastForwardTimeSource::new();
-                let id = create_transcript_id_with_height(1, Height::from(10));
-
-                // Set up the ECDSA pool
-                // A dealing for a transcript that is requested by finalized block,
-                // and we already have the dealing(share accepted)
-                let (dealing, mut support) = create_support(id, NODE_2, NODE_3);
-                let change_set = vec![EcdsaChangeAction::AddToValidated(
-                    EcdsaMessage::EcdsaSignedDealing(dealing),
-                )];
-                ecdsa_pool.apply_changes(change_set);
-
-                support.dealing_hash = CryptoHashOf::new(CryptoHash(vec![]));
-                let msg_id = support.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaDealingSupport(support),
-                    peer_id: NODE_3,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // Set up the transcript creation request
-                // The block requests transcripts 1
-                let t = create_transcript_param(id, &[NODE_2], &[NODE_3]);
-                let block_reader =
-                    TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
-                let change_set = pre_signer.validate_dealing_support(&ecdsa_pool, &block_reader);
-                assert_eq!(change_set.len(), 1);
-                assert!(is_removed_from_unvalidated(&change_set, &msg_id));
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, pre_signer) =
+                        create_pre_signer_dependencies(pool_config, logger);
+                    let time_source = FastForwardTimeSource::new();
+                    let id = create_transcript_id_with_height(1, Height::from(10));
+
+                    // Set up the ECDSA pool
+                    // A dealing for a transcript that is requested by finalized block,
+                    // and we already have the dealing(share accepted)
+                    let (dealing, mut support) = create_support(id, NODE_2, NODE_3);
+                    let change_set = vec![EcdsaChangeAction::AddToValidated(
+                        EcdsaMessage::EcdsaSignedDealing(dealing),
+                    )];
+                    ecdsa_pool.apply_changes(change_set);
+
+                    support.dealing_hash = CryptoHashOf::new(CryptoHash(vec![]));
+                    let msg_id = support.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaDealingSupport(support),
+                        peer_id: NODE_3,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // Set up the transcript creation request
+                    // The block requests transcripts 1
+                    let t = create_transcript_param(id, &[NODE_2], &[NODE_3], algorithm);
+                    let block_reader =
+                        TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
+                    let change_set =
+                        pre_signer.validate_dealing_support(&ecdsa_pool, &block_reader);
+                    assert_eq!(change_set.len(), 1);
+                    assert!(is_removed_from_unvalidated(&change_set, &msg_id));
+                })
             })
-        })
+        }
     }
 
     // Tests purging of dealings from unvalidated pool
     #[test]
     fn test_ecdsa_purge_unvalidated_dealings() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, pre_signer) =
-                    create_pre_signer_dependencies(pool_config, logger);
-                let time_source = FastForwardTimeSource::new();
-                let (id_1, id_2, id_3) = (
-                    create_transcript_id_with_height(1, Height::from(20)),
-                    create_transcript_id_with_height(2, Height::from(20)),
-                    create_transcript_id_with_height(3, Height::from(200)),
-                );
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, pre_signer) =
+                        create_pre_signer_dependencies(pool_config, logger);
+                    let time_source = FastForwardTimeSource::new();
+                    let (id_1, id_2, id_3) = (
+                        create_transcript_id_with_height(1, Height::from(20)),
+                        create_transcript_id_with_height(2, Height::from(20)),
+                        create_transcript_id_with_height(3, Height::from(200)),
+                    );
 
-                // Dealing 1: height <= current_height, in_progress (not purged)
-                let dealing_1 = create_dealing(id_1, NODE_2);
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSignedDealing(dealing_1),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // Dealing 2: height <= current_height, !in_progress (purged)
-                let dealing_2 = create_dealing(id_2, NODE_2);
-                let msg_id_2 = dealing_2.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSignedDealing(dealing_2),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // Dealing 3: height > current_height (not purged)
-                let dealing_3 = create_dealing(id_3, NODE_2);
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSignedDealing(dealing_3),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                let t = create_transcript_param(id_1, &[NODE_2], &[NODE_4]);
-                let block_reader =
-                    TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
-                let change_set = pre_signer.purge_artifacts(&ecdsa_pool, &block_reader);
-                assert_eq!(change_set.len(), 1);
-                assert!(is_removed_from_unvalidated(&change_set, &msg_id_2));
+                    // Dealing 1: height <= current_height, in_progress (not purged)
+                    let dealing_1 = create_dealing(id_1, NODE_2);
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSignedDealing(dealing_1),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // Dealing 2: height <= current_height, !in_progress (purged)
+                    let dealing_2 = create_dealing(id_2, NODE_2);
+                    let msg_id_2 = dealing_2.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSignedDealing(dealing_2),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // Dealing 3: height > current_height (not purged)
+                    let dealing_3 = create_dealing(id_3, NODE_2);
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSignedDealing(dealing_3),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    let t = create_transcript_param(id_1, &[NODE_2], &[NODE_4], algorithm);
+                    let block_reader =
+                        TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
+                    let change_set = pre_signer.purge_artifacts(&ecdsa_pool, &block_reader);
+                    assert_eq!(change_set.len(), 1);
+                    assert!(is_removed_from_unvalidated(&change_set, &msg_id_2));
+                })
             })
-        })
+        }
     }
 
     // Tests purging of dealings from validated pool
     #[test]
     fn test_ecdsa_purge_validated_dealings() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, pre_signer) =
-                    create_pre_signer_dependencies(pool_config, logger);
-                let (id_1, id_2, id_3) = (
-                    create_transcript_id_with_height(1, Height::from(20)),
-                    create_transcript_id_with_height(2, Height::from(20)),
-                    create_transcript_id_with_height(3, Height::from(200)),
-                );
-
-                // Dealing 1: height <= current_height, in_progress (not purged)
-                let dealing_1 = create_dealing(id_1, NODE_2);
-
-                // Dealing 2: height <= current_height, !in_progress (purged)
-                let dealing_2 = create_dealing(id_2, NODE_2);
-                let msg_id_2 = dealing_2.message_id();
-
-                // Dealing 3: height > current_height (not purged)
-                let dealing_3 = create_dealing(id_3, NODE_2);
-
-                let change_set = vec![
-                    EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaSignedDealing(dealing_1)),
-                    EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaSignedDealing(dealing_2)),
-                    EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaSignedDealing(dealing_3)),
-                ];
-                ecdsa_pool.apply_changes(change_set);
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, pre_signer) =
+                        create_pre_signer_dependencies(pool_config, logger);
+                    let (id_1, id_2, id_3) = (
+                        create_transcript_id_with_height(1, Height::from(20)),
+                        create_transcript_id_with_height(2, Height::from(20)),
+                        create_transcript_id_with_height(3, Height::from(200)),
+                    );
 
-                let t = create_transcript_param(id_1, &[NODE_2], &[NODE_4]);
-                let block_reader =
-                    TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
-                let change_set = pre_signer.purge_artifacts(&ecdsa_pool, &block_reader);
-                assert_eq!(change_set.len(), 1);
-                assert!(is_removed_from_validated(&change_set, &msg_id_2));
+                    // Dealing 1: height <= current_height, in_progress (not purged)
+                    let dealing_1 = create_dealing(id_1, NODE_2);
+
+                    // Dealing 2: height <= current_height, !in_progress (purged)
+                    let dealing_2 = create_dealing(id_2, NODE_2);
+                    let msg_id_2 = dealing_2.message_id();
+
+                    // Dealing 3: height > current_height (not purged)
+                    let dealing_3 = create_dealing(id_3, NODE_2);
+
+                    let change_set = vec![
+                        EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaSignedDealing(
+                            dealing_1,
+                        )),
+                        EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaSignedDealing(
+                            dealing_2,
+                        )),
+                        EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaSignedDealing(
+                            dealing_3,
+                        )),
+                    ];
+                    ecdsa_pool.apply_changes(change_set);
+
+                    let t = create_transcript_param(id_1, &[NODE_2], &[NODE_4], algorithm);
+                    let block_reader =
+                        TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
+                    let change_set = pre_signer.purge_artifacts(&ecdsa_pool, &block_reader);
+                    assert_eq!(change_set.len(), 1);
+                    assert!(is_removed_from_validated(&change_set, &msg_id_2));
+                })
             })
-        })
+        }
     }
 
     // Tests purging of dealing support from unvalidated pool
     #[test]
     fn test_ecdsa_purge_unvalidated_dealing_support() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, pre_signer) =
-                    create_pre_signer_dependencies(pool_config, logger);
-                let time_source = FastForwardTimeSource::new();
-                let (id_1, id_2, id_3) = (
-                    create_transcript_id_with_height(1, Height::from(20)),
-                    create_transcript_id_with_height(2, Height::from(20)),
-                    create_transcript_id_with_height(3, Height::from(200)),
-                );
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, pre_signer) =
+                        create_pre_signer_dependencies(pool_config, logger);
+                    let time_source = FastForwardTimeSource::new();
+                    let (id_1, id_2, id_3) = (
+                        create_transcript_id_with_height(1, Height::from(20)),
+                        create_transcript_id_with_height(2, Height::from(20)),
+                        create_transcript_id_with_height(3, Height::from(200)),
+                    );
 
-                // Support 1: height <= current_height, in_progress (not purged)
-                let (_, support_1) = create_support(id_1, NODE_2, NODE_3);
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaDealingSupport(support_1),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // Dealing 2: height <= current_height, !in_progress (purged)
-                let (_, support_2) = create_support(id_2, NODE_2, NODE_3);
-                let msg_id_2 = support_2.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaDealingSupport(support_2),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // Dealing 3: height > current_height (not purged)
-                let (_, support_3) = create_support(id_3, NODE_2, NODE_3);
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaDealingSupport(support_3),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                let t = create_transcript_param(id_1, &[NODE_2], &[NODE_4]);
-                let block_reader =
-                    TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
-                let change_set = pre_signer.purge_artifacts(&ecdsa_pool, &block_reader);
-                assert_eq!(change_set.len(), 1);
-                assert!(is_removed_from_unvalidated(&change_set, &msg_id_2));
+                    // Support 1: height <= current_height, in_progress (not purged)
+                    let (_, support_1) = create_support(id_1, NODE_2, NODE_3);
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaDealingSupport(support_1),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // Dealing 2: height <= current_height, !in_progress (purged)
+                    let (_, support_2) = create_support(id_2, NODE_2, NODE_3);
+                    let msg_id_2 = support_2.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaDealingSupport(support_2),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // Dealing 3: height > current_height (not purged)
+                    let (_, support_3) = create_support(id_3, NODE_2, NODE_3);
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaDealingSupport(support_3),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    let t = create_transcript_param(id_1, &[NODE_2], &[NODE_4], algorithm);
+                    let block_reader =
+                        TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
+                    let change_set = pre_signer.purge_artifacts(&ecdsa_pool, &block_reader);
+                    assert_eq!(change_set.len(), 1);
+                    assert!(is_removed_from_unvalidated(&change_set, &msg_id_2));
+                })
             })
-        })
+        }
     }
 
     // Tests purging of dealing support from validated pool
     #[test]
     fn test_ecdsa_purge_validated_dealing_support() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, pre_signer) =
-                    create_pre_signer_dependencies(pool_config, logger);
-                let (id_1, id_2, id_3) = (
-                    create_transcript_id_with_height(1, Height::from(20)),
-                    create_transcript_id_with_height(2, Height::from(20)),
-                    create_transcript_id_with_height(3, Height::from(200)),
-                );
-
-                // Support 1: height <= current_height, in_progress (not purged)
-                let (_, support_1) = create_support(id_1, NODE_2, NODE_3);
-
-                // Dealing 2: height <= current_height, !in_progress (purged)
-                let (_, support_2) = create_support(id_2, NODE_2, NODE_3);
-                let msg_id_2 = support_2.message_id();
-
-                // Dealing 3: height > current_height (not purged)
-                let (_, support_3) = create_support(id_3, NODE_2, NODE_3);
-
-                let change_set = vec![
-                    EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaDealingSupport(support_1)),
-                    EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaDealingSupport(support_2)),
-                    EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaDealingSupport(support_3)),
-                ];
-                ecdsa_pool.apply_changes(change_set);
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, pre_signer) =
+                        create_pre_signer_dependencies(pool_config, logger);
+                    let (id_1, id_2, id_3) = (
+                        create_transcript_id_with_height(1, Height::from(20)),
+                        create_transcript_id_with_height(2, Height::from(20)),
+                        create_transcript_id_with_height(3, Height::from(200)),
+                    );
 
-                let t = create_transcript_param(id_1, &[NODE_2], &[NODE_4]);
-                let block_reader =
-                    TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
-                let change_set = pre_signer.purge_artifacts(&ecdsa_pool, &block_reader);
-                assert_eq!(change_set.len(), 1);
-                assert!(is_removed_from_validated(&change_set, &msg_id_2));
+                    // Support 1: height <= current_height, in_progress (not purged)
+                    let (_, support_1) = create_support(id_1, NODE_2, NODE_3);
+
+                    // Dealing 2: height <= current_height, !in_progress (purged)
+                    let (_, support_2) = create_support(id_2, NODE_2, NODE_3);
+                    let msg_id_2 = support_2.message_id();
+
+                    // Dealing 3: height > current_height (not purged)
+                    let (_, support_3) = create_support(id_3, NODE_2, NODE_3);
+
+                    let change_set = vec![
+                        EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaDealingSupport(
+                            support_1,
+                        )),
+                        EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaDealingSupport(
+                            support_2,
+                        )),
+                        EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaDealingSupport(
+                            support_3,
+                        )),
+                    ];
+                    ecdsa_pool.apply_changes(change_set);
+
+                    let t = create_transcript_param(id_1, &[NODE_2], &[NODE_4], algorithm);
+                    let block_reader =
+                        TestEcdsaBlockReader::for_pre_signer_test(Height::from(100), vec![t]);
+                    let change_set = pre_signer.purge_artifacts(&ecdsa_pool, &block_reader);
+                    assert_eq!(change_set.len(), 1);
+                    assert!(is_removed_from_validated(&change_set, &msg_id_2));
+                })
             })
-        })
+        }
     }
 }
@@ -638,64 +638,66 @@ mod tests {
     // Tests the Action logic
     #[test]
     fn test_ecdsa_signer_action() {
-        let mut uid_generator = EcdsaUIDGenerator::new(subnet_test_id(1), Height::new(0));
-        let height = Height::from(100);
-        let (id_1, id_2, id_3, id_4) = (
-            create_request_id(&mut uid_generator, height),
-            create_request_id(&mut uid_generator, Height::from(10)),
-            create_request_id(&mut uid_generator, height),
-            create_request_id(&mut uid_generator, Height::from(200)),
-        );
-
-        // The finalized block requests signatures 1, 2, 3
-        let block_reader = TestEcdsaBlockReader::for_signer_test(
-            height,
-            vec![
-                (id_1, create_sig_inputs(1)),
-                (id_2, create_sig_inputs(2)),
-                (id_3, create_sig_inputs(3)),
-            ],
-        );
-        let mut requested = BTreeMap::new();
-        for (request_id, sig_inputs_ref) in block_reader.requested_signatures() {
-            requested.insert(*request_id, sig_inputs_ref);
-        }
-
-        // Message from a node ahead of us
-        assert_eq!(
-            Action::action(&block_reader, &requested, &id_4),
-            Action::Defer
-        );
+        for algorithm in all_ecdsa_algorithms() {
+            let mut uid_generator = EcdsaUIDGenerator::new(subnet_test_id(1), Height::new(0));
+            let height = Height::from(100);
+            let (id_1, id_2, id_3, id_4) = (
+                create_request_id(&mut uid_generator, height),
+                create_request_id(&mut uid_generator, Height::from(10)),
+                create_request_id(&mut uid_generator, height),
+                create_request_id(&mut uid_generator, Height::from(200)),
+            );
+
+            // The finalized block requests signatures 1, 2, 3
+            let block_reader = TestEcdsaBlockReader::for_signer_test(
+                height,
+                vec![
+                    (id_1, create_sig_inputs(1, algorithm)),
+                    (id_2, create_sig_inputs(2, algorithm)),
+                    (id_3, create_sig_inputs(3, algorithm)),
+                ],
+            );
+            let mut requested = BTreeMap::new();
+            for (request_id, sig_inputs_ref) in block_reader.requested_signatures() {
+                requested.insert(*request_id, sig_inputs_ref);
+            }
 
-        // Messages for transcripts not being currently requested
-        assert_eq!(
-            Action::action(
-                &block_reader,
-                &requested,
-                &create_request_id(&mut uid_generator, Height::from(100),)
-            ),
-            Action::Drop
-        );
-        assert_eq!(
-            Action::action(
-                &block_reader,
-                &requested,
-                &create_request_id(&mut uid_generator, Height::from(10),)
-            ),
-            Action::Drop
-        );
+            // Message from a node ahead of us
+            assert_eq!(
+                Action::action(&block_reader, &requested, &id_4),
+                Action::Defer
+            );
+
+            // Messages for transcripts not being currently requested
+            assert_eq!(
+                Action::action(
+                    &block_reader,
+                    &requested,
+                    &create_request_id(&mut uid_generator, Height::from(100),)
+                ),
+                Action::Drop
+            );
+            assert_eq!(
+                Action::action(
+                    &block_reader,
+                    &requested,
+                    &create_request_id(&mut uid_generator, Height::from(10),)
+                ),
+                Action::Drop
+            );
 
-        // Messages for signatures currently requested
-        let action = Action::action(&block_reader, &requested, &id_1);
-        match action {
-            Action::Process(_) => {}
-            _ => panic!("Unexpected action: {:?}", action),
-        }
+            // Messages for signatures currently requested
+            let action = Action::action(&block_reader, &requested, &id_1);
+            match action {
+                Action::Process(_) => {}
+                _ => panic!("Unexpected action: {:?}", action),
+            }
 
-        let action = Action::action(&block_reader, &requested, &id_2);
-        match action {
-            Action::Process(_) => {}
-            _ => panic!("Unexpected action: {:?}", action),
+            let action = Action::action(&block_reader, &requested, &id_2);
+            match action {
+                Action::Process(_) => {}
+                _ => panic!("Unexpected action: {:?}", action),
+            }
         }
     }
 
@@ -703,367 +705,403 @@ mod tests {
     // in progress are filtered out.
     #[test]
     fn test_ecdsa_send_signature_shares() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, signer) = create_signer_dependencies(pool_config, logger);
-                let mut uid_generator = EcdsaUIDGenerator::new(subnet_test_id(1), Height::new(0));
-                let height = Height::from(100);
-                let (id_1, id_2, id_3, id_4, id_5) = (
-                    create_request_id(&mut uid_generator, height),
-                    create_request_id(&mut uid_generator, height),
-                    create_request_id(&mut uid_generator, height),
-                    create_request_id(&mut uid_generator, height),
-                    create_request_id(&mut uid_generator, height),
-                );
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, signer) = create_signer_dependencies(pool_config, logger);
+                    let mut uid_generator =
+                        EcdsaUIDGenerator::new(subnet_test_id(1), Height::new(0));
+                    let height = Height::from(100);
+                    let (id_1, id_2, id_3, id_4, id_5) = (
+                        create_request_id(&mut uid_generator, height),
+                        create_request_id(&mut uid_generator, height),
+                        create_request_id(&mut uid_generator, height),
+                        create_request_id(&mut uid_generator, height),
+                        create_request_id(&mut uid_generator, height),
+                    );
 
-                // Set up the ECDSA pool. Pool has shares for requests 1, 2, 3.
-                // Only the share for request 1 is issued by us
-                let share_1 = create_signature_share(NODE_1, id_1);
-                let share_2 = create_signature_share(NODE_2, id_2);
-                let share_3 = create_signature_share(NODE_3, id_3);
-                let change_set = vec![
-                    EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaSigShare(share_1)),
-                    EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaSigShare(share_2)),
-                    EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaSigShare(share_3)),
-                ];
-                ecdsa_pool.apply_changes(change_set);
-
-                // Set up the signature requests
-                // The block requests signatures 1, 4, 5
-                let block_reader = TestEcdsaBlockReader::for_signer_test(
-                    Height::from(100),
-                    vec![
-                        (id_1, create_sig_inputs(1)),
-                        (id_4, create_sig_inputs(4)),
-                        (id_5, create_sig_inputs(5)),
-                    ],
-                );
-                let transcript_loader: TestEcdsaTranscriptLoader = Default::default();
-
-                // Since request 1 is already in progress, we should issue
-                // shares only for transcripts 4, 5
-                let change_set =
-                    signer.send_signature_shares(&ecdsa_pool, &transcript_loader, &block_reader);
-                assert_eq!(change_set.len(), 2);
-                assert!(is_signature_share_added_to_validated(
-                    &change_set,
-                    &id_4,
-                    block_reader.tip_height()
-                ));
-                assert!(is_signature_share_added_to_validated(
-                    &change_set,
-                    &id_5,
-                    block_reader.tip_height()
-                ));
+                    // Set up the ECDSA pool. Pool has shares for requests 1, 2, 3.
+                    // Only the share for request 1 is issued by us
+                    let share_1 = create_signature_share(NODE_1, id_1);
+                    let share_2 = create_signature_share(NODE_2, id_2);
+                    let share_3 = create_signature_share(NODE_3, id_3);
+                    let change_set = vec![
+                        EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaSigShare(share_1)),
+                        EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaSigShare(share_2)),
+                        EcdsaChangeAction::AddToValidated(EcdsaMessage::EcdsaSigShare(share_3)),
+                    ];
+                    ecdsa_pool.apply_changes(change_set);
+
+                    // Set up the signature requests
+                    // The block requests signatures 1, 4, 5
+                    let block_reader = TestEcdsaBlockReader::for_signer_test(
+                        Height::from(100),
+                        vec![
+                            (id_1, create_sig_inputs(1, algorithm)),
+                            (id_4, create_sig_inputs(4, algorithm)),
+                            (id_5, create_sig_inputs(5, algorithm)),
+                        ],
+                    );
+                    let transcript_loader: TestEcdsaTranscriptLoader = Default::default();
+
+                    // Since request 1 is already in progress, we should issue
+                    // shares only for transcripts 4, 5
+                    let change_set = signer.send_signature_shares(
+                        &ecdsa_pool,
+                        &transcript_loader,
+                        &block_reader,
+                    );
+                    assert_eq!(change_set.len(), 2);
+                    assert!(is_signature_share_added_to_validated(
+                        &change_set,
+                        &id_4,
+                        block_reader.tip_height()
+                    ));
+                    assert!(is_signature_share_added_to_validated(
+                        &change_set,
+                        &id_5,
+                        block_reader.tip_height()
+                    ));
+                })
             })
-        })
+        }
     }
 
     // Tests that complaints are generated and added to the pool if loading transcript
     // results in complaints.
     #[test]
     fn test_ecdsa_send_signature_shares_with_complaints() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (ecdsa_pool, signer) = create_signer_dependencies(pool_config, logger);
-                let mut uid_generator = EcdsaUIDGenerator::new(subnet_test_id(1), Height::new(0));
-                let height = Height::from(100);
-                let (id_1, id_2, id_3) = (
-                    create_request_id(&mut uid_generator, height),
-                    create_request_id(&mut uid_generator, height),
-                    create_request_id(&mut uid_generator, height),
-                );
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (ecdsa_pool, signer) = create_signer_dependencies(pool_config, logger);
+                    let mut uid_generator =
+                        EcdsaUIDGenerator::new(subnet_test_id(1), Height::new(0));
+                    let height = Height::from(100);
+                    let (id_1, id_2, id_3) = (
+                        create_request_id(&mut uid_generator, height),
+                        create_request_id(&mut uid_generator, height),
+                        create_request_id(&mut uid_generator, height),
+                    );
 
-                // Set up the signature requests
-                // The block requests signatures 1, 2, 3
-                let block_reader = TestEcdsaBlockReader::for_signer_test(
-                    height,
-                    vec![
-                        (id_1, create_sig_inputs(1)),
-                        (id_2, create_sig_inputs(2)),
-                        (id_3, create_sig_inputs(3)),
-                    ],
-                );
-                let transcript_loader =
-                    TestEcdsaTranscriptLoader::new(TestTranscriptLoadStatus::Complaints);
-
-                let change_set =
-                    signer.send_signature_shares(&ecdsa_pool, &transcript_loader, &block_reader);
-                let complaints = transcript_loader.returned_complaints();
-                assert_eq!(change_set.len(), complaints.len());
-                assert_eq!(change_set.len(), 15);
-                for complaint in complaints {
-                    assert!(is_complaint_added_to_validated(
-                        &change_set,
-                        &complaint.content.idkg_complaint.transcript_id,
-                        &NODE_1,
-                        &NODE_1,
-                    ));
-                }
+                    // Set up the signature requests
+                    // The block requests signatures 1, 2, 3
+                    let block_reader = TestEcdsaBlockReader::for_signer_test(
+                        height,
+                        vec![
+                            (id_1, create_sig_inputs(1, algorithm)),
+                            (id_2, create_sig_inputs(2, algorithm)),
+                            (id_3, create_sig_inputs(3, algorithm)),
+                        ],
+                    );
+                    let transcript_loader =
+                        TestEcdsaTranscriptLoader::new(TestTranscriptLoadStatus::Complaints);
+
+                    let change_set = signer.send_signature_shares(
+                        &ecdsa_pool,
+                        &transcript_loader,
+                        &block_reader,
+                    );
+                    let complaints = transcript_loader.returned_complaints();
+                    assert_eq!(change_set.len(), complaints.len());
+                    assert_eq!(change_set.len(), 15);
+                    for complaint in complaints {
+                        assert!(is_complaint_added_to_validated(
+                            &change_set,
+                            &complaint.content.idkg_complaint.transcript_id,
+                            &NODE_1,
+                            &NODE_1,
+                        ));
+                    }
+                })
             })
-        })
+        }
     }
 
     // Tests that received dealings are accepted/processed for eligible signature
     // requests, and others dealings are either deferred or dropped.
     #[test]
     fn test_ecdsa_validate_signature_shares() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, signer) = create_signer_dependencies(pool_config, logger);
-                let mut uid_generator = EcdsaUIDGenerator::new(subnet_test_id(1), Height::new(0));
-                let time_source = FastForwardTimeSource::new();
-                let height = Height::from(100);
-                let (id_1, id_2, id_3, id_4) = (
-                    create_request_id(&mut uid_generator, Height::from(200)),
-                    create_request_id(&mut uid_generator, height),
-                    create_request_id(&mut uid_generator, Height::from(10)),
-                    create_request_id(&mut uid_generator, Height::from(5)),
-                );
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, signer) = create_signer_dependencies(pool_config, logger);
+                    let mut uid_generator =
+                        EcdsaUIDGenerator::new(subnet_test_id(1), Height::new(0));
+                    let time_source = FastForwardTimeSource::new();
+                    let height = Height::from(100);
+                    let (id_1, id_2, id_3, id_4) = (
+                        create_request_id(&mut uid_generator, Height::from(200)),
+                        create_request_id(&mut uid_generator, height),
+                        create_request_id(&mut uid_generator, Height::from(10)),
+                        create_request_id(&mut uid_generator, Height::from(5)),
+                    );
 
-                // Set up the transcript creation request
-                // The block requests transcripts 2, 3
-                let block_reader = TestEcdsaBlockReader::for_signer_test(
-                    height,
-                    vec![(id_2, create_sig_inputs(2)), (id_3, create_sig_inputs(3))],
-                );
+                    // Set up the transcript creation request
+                    // The block requests transcripts 2, 3
+                    let block_reader = TestEcdsaBlockReader::for_signer_test(
+                        height,
+                        vec![
+                            (id_2, create_sig_inputs(2, algorithm)),
+                            (id_3, create_sig_inputs(3, algorithm)),
+                        ],
+                    );
 
-                // Set up the ECDSA pool
-                // A share from a node ahead of us (deferred)
-                let share = create_signature_share(NODE_2, id_1);
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSigShare(share),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // A share for a request in the finalized block (accepted)
-                let share = create_signature_share(NODE_2, id_2);
-                let msg_id_2 = share.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSigShare(share),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // A share for a request in the finalized block (accepted)
-                let share = create_signature_share(NODE_2, id_3);
-                let msg_id_3 = share.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSigShare(share),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // A share for a request not in the finalized block (dropped)
-                let share = create_signature_share(NODE_2, id_4);
-                let msg_id_4 = share.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSigShare(share),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                let change_set = signer.validate_signature_shares(&ecdsa_pool, &block_reader);
-                assert_eq!(change_set.len(), 3);
-                assert!(is_moved_to_validated(&change_set, &msg_id_2));
-                assert!(is_moved_to_validated(&change_set, &msg_id_3));
-                assert!(is_removed_from_unvalidated(&change_set, &msg_id_4));
+                    // Set up the ECDSA pool
+                    // A share from a node ahead of us (deferred)
+                    let share = create_signature_share(NODE_2, id_1);
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSigShare(share),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // A share for a request in the finalized block (accepted)
+                    let share = create_signature_share(NODE_2, id_2);
+                    let msg_id_2 = share.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSigShare(share),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // A share for a request in the finalized block (accepted)
+                    let share = create_signature_share(NODE_2, id_3);
+                    let msg_id_3 = share.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSigShare(share),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // A share for a request not in the finalized block (dropped)
+                    let share = create_signature_share(NODE_2, id_4);
+                    let msg_id_4 = share.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSigShare(share),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    let change_set = signer.validate_signature_shares(&ecdsa_pool, &block_reader);
+                    assert_eq!(change_set.len(), 3);
+                    assert!(is_moved_to_validated(&change_set, &msg_id_2));
+                    assert!(is_moved_to_validated(&change_set, &msg_id_3));
+                    assert!(is_removed_from_unvalidated(&change_set, &msg_id_4));
+                })
             })
-        })
+        }
     }
 
     // Tests that duplicate shares from a signer for the same request
     // are dropped.
     #[test]
     fn test_ecdsa_duplicate_signature_shares() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, signer) = create_signer_dependencies(pool_config, logger);
-                let mut uid_generator = EcdsaUIDGenerator::new(subnet_test_id(1), Height::new(0));
-                let time_source = FastForwardTimeSource::new();
-                let id_2 = create_request_id(&mut uid_generator, Height::from(100));
-
-                // Set up the ECDSA pool
-                // Validated pool has: {signature share 2, signer = NODE_2}
-                let share = create_signature_share(NODE_2, id_2);
-                let change_set = vec![EcdsaChangeAction::AddToValidated(
-                    EcdsaMessage::EcdsaSigShare(share),
-                )];
-                ecdsa_pool.apply_changes(change_set);
-
-                // Unvalidated pool has: {signature share 2, signer = NODE_2, height = 100}
-                let share = create_signature_share(NODE_2, id_2);
-                let msg_id_2 = share.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSigShare(share),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                let block_reader = TestEcdsaBlockReader::for_signer_test(
-                    Height::from(100),
-                    vec![(id_2, create_sig_inputs(2))],
-                );
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, signer) = create_signer_dependencies(pool_config, logger);
+                    let mut uid_generator =
+                        EcdsaUIDGenerator::new(subnet_test_id(1), Height::new(0));
+                    let time_source = FastForwardTimeSource::new();
+                    let id_2 = create_request_id(&mut uid_generator, Height::from(100));
+
+                    // Set up the ECDSA pool
+                    // Validated pool has: {signature share 2, signer = NODE_2}
+                    let share = create_signature_share(NODE_2, id_2);
+                    let change_set = vec![EcdsaChangeAction::AddToValidated(
+                        EcdsaMessage::EcdsaSigShare(share),
+                    )];
+                    ecdsa_pool.apply_changes(change_set);
+
+                    // Unvalidated pool has: {signature share 2, signer = NODE_2, height = 100}
+                    let share = create_signature_share(NODE_2, id_2);
+                    let msg_id_2 = share.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSigShare(share),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    let block_reader = TestEcdsaBlockReader::for_signer_test(
+                        Height::from(100),
+                        vec![(id_2, create_sig_inputs(2, algorithm))],
+                    );
 
-                let change_set = signer.validate_signature_shares(&ecdsa_pool, &block_reader);
-                assert_eq!(change_set.len(), 1);
-                assert!(is_handle_invalid(&change_set, &msg_id_2));
+                    let change_set = signer.validate_signature_shares(&ecdsa_pool, &block_reader);
+                    assert_eq!(change_set.len(), 1);
+                    assert!(is_handle_invalid(&change_set, &msg_id_2));
+                })
             })
-        })
+        }
     }
 
     // Tests that duplicate shares from a signer for the same request
     // in the unvalidated pool are dropped.
     #[test]
     fn test_ecdsa_duplicate_signature_shares_in_batch() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, signer) = create_signer_dependencies(pool_config, logger);
-                let mut uid_generator = EcdsaUIDGenerator::new(subnet_test_id(1), Height::new(0));
-                let time_source = FastForwardTimeSource::new();
-                let id_1 = create_request_id(&mut uid_generator, Height::from(100));
-
-                // Unvalidated pool has: {signature share 1, signer = NODE_2}
-                let share = create_signature_share_with_nonce(NODE_2, id_1, 0);
-                let msg_id_1 = share.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSigShare(share),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // Unvalidated pool has: {signature share 2, signer = NODE_2}
-                let share = create_signature_share_with_nonce(NODE_2, id_1, 1);
-                let msg_id_2 = share.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSigShare(share),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // Unvalidated pool has: {signature share 2, signer = NODE_3}
-                let share = create_signature_share_with_nonce(NODE_3, id_1, 2);
-                let msg_id_3 = share.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSigShare(share),
-                    peer_id: NODE_3,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                let block_reader = TestEcdsaBlockReader::for_signer_test(
-                    Height::from(100),
-                    vec![(id_1, create_sig_inputs(2))],
-                );
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, signer) = create_signer_dependencies(pool_config, logger);
+                    let mut uid_generator =
+                        EcdsaUIDGenerator::new(subnet_test_id(1), Height::new(0));
+                    let time_source = FastForwardTimeSource::new();
+                    let id_1 = create_request_id(&mut uid_generator, Height::from(100));
+
+                    // Unvalidated pool has: {signature share 1, signer = NODE_2}
+                    let share = create_signature_share_with_nonce(NODE_2, id_1, 0);
+                    let msg_id_1 = share.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSigShare(share),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // Unvalidated pool has: {signature share 2, signer = NODE_2}
+                    let share = create_signature_share_with_nonce(NODE_2, id_1, 1);
+                    let msg_id_2 = share.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSigShare(share),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // Unvalidated pool has: {signature share 2, signer = NODE_3}
+                    let share = create_signature_share_with_nonce(NODE_3, id_1, 2);
+                    let msg_id_3 = share.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSigShare(share),
+                        peer_id: NODE_3,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    let block_reader = TestEcdsaBlockReader::for_signer_test(
+                        Height::from(100),
+                        vec![(id_1, create_sig_inputs(2, algorithm))],
+                    );
 
-                let change_set = signer.validate_signature_shares(&ecdsa_pool, &block_reader);
-                assert_eq!(change_set.len(), 3);
-                assert!(is_handle_invalid(&change_set, &msg_id_1));
-                assert!(is_handle_invalid(&change_set, &msg_id_2));
-                assert!(is_moved_to_validated(&change_set, &msg_id_3));
+                    let change_set = signer.validate_signature_shares(&ecdsa_pool, &block_reader);
+                    assert_eq!(change_set.len(), 3);
+                    assert!(is_handle_invalid(&change_set, &msg_id_1));
+                    assert!(is_handle_invalid(&change_set, &msg_id_2));
+                    assert!(is_moved_to_validated(&change_set, &msg_id_3));
+                })
             })
-        })
+        }
     }
 
     // Tests purging of signature shares from unvalidated pool
     #[test]
     fn test_ecdsa_purge_unvalidated_signature_shares() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, signer) = create_signer_dependencies(pool_config, logger);
-                let mut uid_generator = EcdsaUIDGenerator::new(subnet_test_id(1), Height::new(0));
-                let time_source = FastForwardTimeSource::new();
-                let (id_1, id_2, id_3) = (
-                    create_request_id(&mut uid_generator, Height::from(10)),
-                    create_request_id(&mut uid_generator, Height::from(20)),
-                    create_request_id(&mut uid_generator, Height::from(200)),
-                );
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, signer) = create_signer_dependencies(pool_config, logger);
+                    let mut uid_generator =
+                        EcdsaUIDGenerator::new(subnet_test_id(1), Height::new(0));
+                    let time_source = FastForwardTimeSource::new();
+                    let (id_1, id_2, id_3) = (
+                        create_request_id(&mut uid_generator, Height::from(10)),
+                        create_request_id(&mut uid_generator, Height::from(20)),
+                        create_request_id(&mut uid_generator, Height::from(200)),
+                    );
 
-                // Set up the transcript creation request
-                // The block requests transcripts 1, 3
-                let block_reader = TestEcdsaBlockReader::for_signer_test(
-                    Height::from(100),
-                    vec![(id_1, create_sig_inputs(1)), (id_3, create_sig_inputs(3))],
-                );
+                    // Set up the transcript creation request
+                    // The block requests transcripts 1, 3
+                    let block_reader = TestEcdsaBlockReader::for_signer_test(
+                        Height::from(100),
+                        vec![
+                            (id_1, create_sig_inputs(1, algorithm)),
+                            (id_3, create_sig_inputs(3, algorithm)),
+                        ],
+                    );
 
-                // Share 1: height <= current_height, in_progress (not purged)
-                let share = create_signature_share(NODE_2, id_1);
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSigShare(share),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // Share 2: height <= current_height, !in_progress (purged)
-                let share = create_signature_share(NODE_2, id_2);
-                let msg_id_2 = share.message_id();
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSigShare(share),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                // Share 3: height > current_height (not purged)
-                let share = create_signature_share(NODE_2, id_3);
-                ecdsa_pool.insert(UnvalidatedArtifact {
-                    message: EcdsaMessage::EcdsaSigShare(share),
-                    peer_id: NODE_2,
-                    timestamp: time_source.get_relative_time(),
-                });
-
-                let change_set = signer.purge_artifacts(&ecdsa_pool, &block_reader);
-                assert_eq!(change_set.len(), 1);
-                assert!(is_removed_from_unvalidated(&change_set, &msg_id_2));
+                    // Share 1: height <= current_height, in_progress (not purged)
+                    let share = create_signature_share(NODE_2, id_1);
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSigShare(share),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // Share 2: height <= current_height, !in_progress (purged)
+                    let share = create_signature_share(NODE_2, id_2);
+                    let msg_id_2 = share.message_id();
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSigShare(share),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    // Share 3: height > current_height (not purged)
+                    let share = create_signature_share(NODE_2, id_3);
+                    ecdsa_pool.insert(UnvalidatedArtifact {
+                        message: EcdsaMessage::EcdsaSigShare(share),
+                        peer_id: NODE_2,
+                        timestamp: time_source.get_relative_time(),
+                    });
+
+                    let change_set = signer.purge_artifacts(&ecdsa_pool, &block_reader);
+                    assert_eq!(change_set.len(), 1);
+                    assert!(is_removed_from_unvalidated(&change_set, &msg_id_2));
+                })
             })
-        })
+        }
     }
 
     // Tests purging of signature shares from validated pool
     #[test]
     fn test_ecdsa_purge_validated_signature_shares() {
-        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
-            with_test_replica_logger(|logger| {
-                let (mut ecdsa_pool, signer) = create_signer_dependencies(pool_config, logger);
-                let mut uid_generator = EcdsaUIDGenerator::new(subnet_test_id(1), Height::new(0));
-                let (id_1, id_2, id_3) = (
-                    create_request_id(&mut uid_generator, Height::from(10)),
-                    create_request_id(&mut uid_generator, Height::from(20)),
-                    create_request_id(&mut uid_generator, Height::from(200)),
-                );
+        for algorithm in all_ecdsa_algorithms() {
+            ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+                with_test_replica_logger(|logger| {
+                    let (mut ecdsa_pool, signer) = create_signer_dependencies(pool_config, logger);
+                    let mut uid_generator =
+                        EcdsaUIDGenerator::new(subnet_test_id(1), Height::new(0));
+                    let (id_1, id_2, id_3) = (
+                        create_request_id(&mut uid_generator, Height::from(10)),
+                        create_request_id(&mut uid_generator, Height::from(20)),
+                        create_request_id(&mut uid_generator, Height::from(200)),
+                    );
 
-                // Set up the transcript creation request
-                // The block requests transcripts 1, 3
-                let block_reader = TestEcdsaBlockReader::for_signer_test(
-                    Height::from(100),
-                    vec![(id_1, create_sig_inputs(1)), (id_3, create_sig_inputs(3))],
-                );
+                    // Set up the transcript creation request
+                    // The block requests transcripts 1, 3
+                    let block_reader = TestEcdsaBlockReader::for_signer_test(
+                        Height::from(100),
+                        vec![
+                            (id_1, create_sig_inputs(1, algorithm)),
+                            (id_3, create_sig_inputs(3, algorithm)),
+                        ],
+                    );
 
-                // Share 1: height <= current_height, in_progress (not purged)
-                let share = create_signature_share(NODE_2, id_1);
-                let change_set = vec![EcdsaChangeAction::AddToValidated(
-                    EcdsaMessage::EcdsaSigShare(share),
-                )];
-                ecdsa_pool.apply_changes(change_set);
-
-                // Share 2: height <= current_height, !in_progress (purged)
-                let share = create_signature_share(NODE_2, id_2);
-                let msg_id_2 = share.message_id();
-                let change_set = vec![EcdsaChangeAction::AddToValidated(
-                    EcdsaMessage::EcdsaSigShare(share),
-                )];
-                ecdsa_pool.apply_changes(change_set);
-
-                // Share 3: height > current_height (not purged)
-                let share = create_signature_share(NODE_2, id_3);
-                let change_set = vec![EcdsaChangeAction::AddToValidated(
-                    EcdsaMessage::EcdsaSigShare(share),
-                )];
-                ecdsa_pool.apply_changes(change_set);
-
-                let change_set = signer.purge_artifacts(&ecdsa_pool, &block_reader);
-                assert_eq!(change_set.len(), 1);
-                assert!(is_removed_from_validated(&change_set, &msg_id_2));
+                    // Share 1: height <= current_height, in_progress (not purged)
+                    let share = create_signature_share(NODE_2, id_1);
+                    let change_set = vec![EcdsaChangeAction::AddToValidated(
+                        EcdsaMessage::EcdsaSigShare(share),
+                    )];
+                    ecdsa_pool.apply_changes(change_set);
+
+                    // Share 2: height <= current_height, !in_progress (purged)
+                    let share = create_signature_share(NODE_2, id_2);
+                    let msg_id_2 = share.message_id();
+                    let change_set = vec![EcdsaChangeAction::AddToValidated(
+                        EcdsaMessage::EcdsaSigShare(share),
+                    )];
+                    ecdsa_pool.apply_changes(change_set);
+
+                    // Share 3: height > current_height (not purged)
+                    let share = create_signature_share(NODE_2, id_3);
+                    let change_set = vec![EcdsaChangeAction::AddToValidated(
+                        EcdsaMessage::EcdsaSigShare(share),
+                    )];
+                    ecdsa_pool.apply_changes(change_set);
+
+                    let change_set = signer.purge_artifacts(&ecdsa_pool, &block_reader);
+                    assert_eq!(change_set.len(), 1);
+                    assert!(is_removed_from_validated(&change_set, &msg_id_2));
+                })
             })
-        })
+        }
     }
 }
@@ -1,7 +1,7 @@
 //! Common utils for the ECDSA implementation.
 
 use crate::ecdsa::complaints::{EcdsaTranscriptLoader, TranscriptLoadStatus};
-use ic_ic00_types::EcdsaKeyId;
+use ic_ic00_types::{EcdsaCurve, EcdsaKeyId};
 use ic_interfaces::consensus_pool::ConsensusBlockChain;
 use ic_interfaces::ecdsa::{EcdsaChangeAction, EcdsaChangeSet, EcdsaPool};
 use ic_protobuf::registry::subnet::v1 as pb;
@@ -13,6 +13,7 @@ use ic_types::consensus::ecdsa::{
 use ic_types::crypto::canister_threshold_sig::idkg::{
     IDkgTranscript, IDkgTranscriptOperation, InitialIDkgDealings,
 };
+use ic_types::crypto::AlgorithmId;
 use ic_types::Height;
 use std::collections::BTreeSet;
 use std::convert::TryInto;
@@ -158,6 +159,14 @@ pub(crate) fn transcript_op_summary(op: &IDkgTranscriptOperation) -> String {
     }
 }
 
+/// Return the IDKG algorithm used for transcripts of the given key.
+pub(crate) fn algorithm_for_key_id(key_id: &EcdsaKeyId) -> AlgorithmId {
+    match key_id.curve {
+        EcdsaCurve::Secp256k1 => AlgorithmId::ThresholdEcdsaSecp256k1,
+        EcdsaCurve::Secp256r1 => AlgorithmId::ThresholdEcdsaSecp256r1,
+    }
+}
+
 /// Inspect ecdsa_initializations field in the CUPContent.
 /// Return key_id and dealings.
 pub(crate) fn inspect_ecdsa_initializations(
@@ -245,6 +254,14 @@ pub(crate) mod test_utils {
     use std::convert::TryFrom;
     use std::sync::Mutex;
 
+    // The algorithms of all supported threshold ECDSA curves
+    pub(crate) fn all_ecdsa_algorithms() -> [AlgorithmId; 2] {
+        [
+            AlgorithmId::ThresholdEcdsaSecp256k1,
+            AlgorithmId::ThresholdEcdsaSecp256r1,
+        ]
+    }
+
     pub(crate) fn empty_response() -> ic_types::messages::Response {
         ic_types::messages::Response {
             originator: ic_types::CanisterId::ic_00(),
@@ -333,7 +350,11 @@ pub(crate) mod test_utils {
             for transcript_ref in active_refs {
                 idkg_transcripts.insert(
                     transcript_ref,
-                    create_transcript(transcript_ref.transcript_id, &[NODE_2]),
+                    create_transcript(
+                        transcript_ref.transcript_id,
+                        &[NODE_2],
+                        AlgorithmId::ThresholdEcdsaSecp256k1,
+                    ),
                 );
             }
 
@@ -641,6 +662,7 @@ pub(crate) mod test_utils {
     pub(crate) fn create_transcript(
         transcript_id: IDkgTranscriptId,
         receiver_list: &[NodeId],
+        algorithm_id: AlgorithmId,
     ) -> IDkgTranscript {
         let mut receivers = BTreeSet::new();
         receiver_list.iter().for_each(|val| {
@@ -652,7 +674,7 @@ pub(crate) mod test_utils {
             registry_version: RegistryVersion::from(1),
             verified_dealings: BTreeMap::new(),
             transcript_type: IDkgTranscriptType::Masked(IDkgMaskedTranscriptOrigin::Random),
-            algorithm_id: AlgorithmId::ThresholdEcdsaSecp256k1,
+            algorithm_id,
             internal_transcript_raw: vec![],
         }
     }
@@ -662,6 +684,7 @@ pub(crate) mod test_utils {
         transcript_id: IDkgTranscriptId,
         dealer_list: &[NodeId],
         receiver_list: &[NodeId],
+        algorithm_id: AlgorithmId,
     ) -> TestTranscriptParams {
         let mut dealers = BTreeSet::new();
         dealer_list.iter().for_each(|val| {
@@ -674,17 +697,13 @@ pub(crate) mod test_utils {
 
         // The random transcript
         let random_transcript_id = create_transcript_id(transcript_id.id() * 214365 + 1);
-        let random_transcript = create_transcript(random_transcript_id, dealer_list);
+        let random_transcript = create_transcript(random_transcript_id, dealer_list, algorithm_id);
         let random_masked =
             MaskedTranscript::try_from((Height::new(0), &random_transcript)).unwrap();
         let mut idkg_transcripts = BTreeMap::new();
         idkg_transcripts.insert(*random_masked.as_ref(), random_transcript);
 
-        let attrs = IDkgTranscriptAttributes::new(
-            dealers,
-            AlgorithmId::ThresholdEcdsaSecp256k1,
-            RegistryVersion::from(0),
-        );
+        let attrs = IDkgTranscriptAttributes::new(dealers, algorithm_id, RegistryVersion::from(0));
 
         // The transcript that points to the random transcript
         let transcript_params_ref = ReshareOfMaskedParams::new(
@@ -719,7 +738,7 @@ pub(crate) mod test_utils {
             dealers,
             receivers,
             registry_version,
-            AlgorithmId::ThresholdEcdsaSecp256k1,
+            unmasked_transcript.algorithm_id,
             IDkgTranscriptOperation::ReshareOfUnmasked(unmasked_transcript.clone()),
         )
         .unwrap()
@@ -763,7 +782,11 @@ pub(crate) mod test_utils {
     }
 
     // Creates a test signature input
-    pub(crate) fn create_sig_inputs_with_height(caller: u8, height: Height) -> TestSigInputs {
+    pub(crate) fn create_sig_inputs_with_height(
+        caller: u8,
+        height: Height,
+        algorithm_id: AlgorithmId,
+    ) -> TestSigInputs {
         let transcript_id = |offset| {
             let val = caller as u64;
             create_transcript_id(val * 214365 + offset)
@@ -779,7 +802,7 @@ pub(crate) mod test_utils {
             transcript_type: IDkgTranscriptType::Unmasked(
                 IDkgUnmaskedTranscriptOrigin::ReshareMasked(key_masked_id),
             ),
-            algorithm_id: AlgorithmId::ThresholdEcdsaSecp256k1,
+            algorithm_id,
             internal_transcript_raw: vec![],
         };
         create_sig_inputs_with_args(caller, &receivers, key_unmasked, height)
@@ -797,6 +820,7 @@ pub(crate) mod test_utils {
             create_transcript_id(val * 214365 + offset)
         };
 
+        let algorithm_id = key_unmasked.algorithm_id;
         let kappa_masked_id = transcript_id(10);
         let kappa_unmasked_id = transcript_id(20);
         let lambda_masked_id = transcript_id(30);
@@ -811,7 +835,7 @@ pub(crate) mod test_utils {
             registry_version: RegistryVersion::from(1),
             verified_dealings: BTreeMap::new(),
             transcript_type: IDkgTranscriptType::Masked(IDkgMaskedTranscriptOrigin::Random),
-            algorithm_id: AlgorithmId::ThresholdEcdsaSecp256k1,
+            algorithm_id,
             internal_transcript_raw: vec![],
         };
         let kappa_masked_ref = MaskedTranscript::try_from((height, &kappa_masked)).unwrap();
@@ -825,7 +849,7 @@ pub(crate) mod test_utils {
             transcript_type: IDkgTranscriptType::Unmasked(
                 IDkgUnmaskedTranscriptOrigin::ReshareMasked(kappa_masked_id),
             ),
-            algorithm_id: AlgorithmId::ThresholdEcdsaSecp256k1,
+            algorithm_id,
             internal_transcript_raw: vec![],
         };
         let kappa_unmasked_ref = UnmaskedTranscript::try_from((height, &kappa_unmasked)).unwrap();
@@ -837,7 +861,7 @@ pub(crate) mod test_utils {
             registry_version: RegistryVersion::from(1),
             verified_dealings: BTreeMap::new(),
             transcript_type: IDkgTranscriptType::Masked(IDkgMaskedTranscriptOrigin::Random),
-            algorithm_id: AlgorithmId::ThresholdEcdsaSecp256k1,
+            algorithm_id,
             internal_transcript_raw: vec![],
         };
         let lambda_masked_ref = MaskedTranscript::try_from((height, &lambda_masked)).unwrap();
@@ -857,7 +881,7 @@ pub(crate) mod test_utils {
                     lambda_masked_id,
                 ),
             ),
-            algorithm_id: AlgorithmId::ThresholdEcdsaSecp256k1,
+            algorithm_id,
             internal_transcript_raw: vec![],
         };
         let kappa_unmasked_times_lambda_masked_ref =
@@ -875,7 +899,7 @@ pub(crate) mod test_utils {
             transcript_type: IDkgTranscriptType::Masked(
                 IDkgMaskedTranscriptOrigin::UnmaskedTimesMasked(key_unmasked_id, lambda_masked_id),
             ),
-            algorithm_id: AlgorithmId::ThresholdEcdsaSecp256k1,
+            algorithm_id,
             internal_transcript_raw: vec![],
         };
         let key_unmasked_times_lambda_masked_ref =
@@ -909,8 +933,8 @@ pub(crate) mod test_utils {
     }
 
     // Creates a test signature input
-    pub(crate) fn create_sig_inputs(caller: u8) -> TestSigInputs {
-        create_sig_inputs_with_height(caller, Height::new(0))
+    pub(crate) fn create_sig_inputs(caller: u8, algorithm_id: AlgorithmId) -> TestSigInputs {
+        create_sig_inputs_with_height(caller, Height::new(0), algorithm_id)
     }
 
     // Creates a test signature share
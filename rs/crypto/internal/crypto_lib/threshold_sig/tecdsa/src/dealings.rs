@@ -189,8 +189,14 @@ impl IDkgDealingInternal {
             ));
         }
 
+        let key_curve = recipients[0].curve_type();
+
+        if !is_supported_key_curve(key_curve, curve) {
+            return Err(ThresholdEcdsaError::InvalidRecipients);
+        }
+
         for recipient in recipients {
-            if recipient.curve_type() != curve {
+            if recipient.curve_type() != key_curve {
                 return Err(ThresholdEcdsaError::InvalidRecipients);
             }
         }
@@ -426,7 +432,9 @@ impl IDkgDealingInternal {
         dealer_index: NodeIndex,
         recipient_index: NodeIndex,
     ) -> ThresholdEcdsaResult<()> {
-        if private_key.curve_type() != curve_type || public_key.curve_type() != curve_type {
+        let key_curve = private_key.curve_type();
+
+        if public_key.curve_type() != key_curve || !is_supported_key_curve(key_curve, curve_type) {
             return Err(ThresholdEcdsaError::CurveMismatch);
         }
 
@@ -63,34 +63,53 @@ impl DerivationPath {
     ///
     /// Extended to support larger inputs, which is needed for
     /// deriving the canister public key
+    ///
+    /// For P-256 this follows SLIP-0010, which uses the same construction
+    /// as BIP32 with P-256 in place of secp256k1, but retries invalid keys
+    /// differently, see [Self::ckdpub_from_hmac_output].
     fn bip32_ckdpub(
         public_key: &EccPoint,
         chain_key: &[u8],
         index: &DerivationIndex,
     ) -> ThresholdEcdsaResult<(EccPoint, Vec<u8>, EccScalar)> {
-        // BIP32 is only defined for secp256k1
-        if public_key.curve_type() != EccCurveType::K256 {
-            return Err(ThresholdEcdsaError::CurveMismatch);
-        }
-
         let mut hmac = Hmac::<Sha512>::new(chain_key);
 
         hmac.write(&public_key.serialize());
         hmac.write(&index.0);
 
-        let hmac_output = hmac.finish();
+        Self::ckdpub_from_hmac_output(public_key, chain_key, index, &hmac.finish())
+    }
 
+    /// Derives the public child key from I = `hmac_output`
+    ///
+    /// If iL >= order or the new key is the point at infinity, BIP32 tries
+    /// again with the "next" index, while SLIP-0010 tries again with
+    /// I = HMAC-SHA512(c, 0x01 || IR || ser32(i)).
+    fn ckdpub_from_hmac_output(
+        public_key: &EccPoint,
+        chain_key: &[u8],
+        index: &DerivationIndex,
+        hmac_output: &[u8],
+    ) -> ThresholdEcdsaResult<(EccPoint, Vec<u8>, EccScalar)> {
         let key_offset = EccScalar::from_bytes_wide(public_key.curve_type(), &hmac_output[..32])?;
 
         let new_chain_key = hmac_output[32..].to_vec();
 
         let new_key = public_key.add_points(&EccPoint::mul_by_g(&key_offset)?)?;
 
-        // If iL >= order or new_key=inf, try again with the "next" index
-        if key_offset.serialize() != hmac_output[..32] || new_key.is_infinity()? {
-            Self::bip32_ckdpub(public_key, chain_key, &index.next())
-        } else {
-            Ok((new_key, new_chain_key, key_offset))
+        if key_offset.serialize() == hmac_output[..32] && !new_key.is_infinity()? {
+            return Ok((new_key, new_chain_key, key_offset));
+        }
+
+        match public_key.curve_type() {
+            EccCurveType::K256 => Self::bip32_ckdpub(public_key, chain_key, &index.next()),
+            EccCurveType::P256 => {
+                let mut hmac = Hmac::<Sha512>::new(chain_key);
+                hmac.write(&[0x01]);
+                hmac.write(&hmac_output[32..]);
+                hmac.write(&index.0);
+                Self::ckdpub_from_hmac_output(public_key, chain_key, index, &hmac.finish())
+            }
         }
     }
 
@@ -116,27 +135,57 @@ impl DerivationPath {
 
         let curve_type = master_public_key.curve_type();
 
-        if curve_type == EccCurveType::K256 {
-            let mut derived_key = *master_public_key;
-            let mut derived_chain_key = chain_code.to_vec();
-            let mut derived_offset = EccScalar::zero(curve_type);
-
-            for idx in &self.path {
-                let (next_derived_key, next_chain_key, next_offset) =
-                    Self::bip32_ckdpub(&derived_key, &derived_chain_key, idx)?;
+        let mut derived_key = *master_public_key;
+        let mut derived_chain_key = chain_code.to_vec();
+        let mut derived_offset = EccScalar::zero(curve_type);
 
-                derived_key = next_derived_key;
-                derived_chain_key = next_chain_key;
-                derived_offset = derived_offset.add(&next_offset)?;
-            }
+        for idx in &self.path {
+            let (next_derived_key, next_chain_key, next_offset) =
+                Self::bip32_ckdpub(&derived_key, &derived_chain_key, idx)?;
 
-            Ok((derived_offset, derived_chain_key))
-        } else {
-            // Key derivation is not currently defined for curves other than secp256k1
-            Err(ThresholdEcdsaError::InvalidArguments(format!(
-                "Currently key derivation not defined for {}",
-                curve_type
-            )))
+            derived_key = next_derived_key;
+            derived_chain_key = next_chain_key;
+            derived_offset = derived_offset.add(&next_offset)?;
         }
+
+        Ok((derived_offset, derived_chain_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_p256_key_offset_is_retried_as_in_slip10() -> ThresholdEcdsaResult<()> {
+        let public_key = EccPoint::generator_g(EccCurveType::P256)?;
+        let chain_key = [0x42; 32];
+        let index = DerivationIndex(vec![0, 0, 0, 7]);
+
+        // iL = 2**256-1 exceeds the group order.
+        let mut hmac_output = vec![0xff; 32];
+        hmac_output.extend_from_slice(&[0x17; 32]);
+
+        let mut hmac = Hmac::<Sha512>::new(&chain_key);
+        hmac.write(&[0x01]);
+        hmac.write(&[0x17; 32]);
+        hmac.write(&index.0);
+        let retried_output = hmac.finish();
+
+        let (key, chain, offset) =
+            DerivationPath::ckdpub_from_hmac_output(&public_key, &chain_key, &index, &hmac_output)?;
+        let (expected_key, expected_chain, expected_offset) =
+            DerivationPath::ckdpub_from_hmac_output(
+                &public_key,
+                &chain_key,
+                &index,
+                &retried_output,
+            )?;
+
+        assert_eq!(key, expected_key);
+        assert_eq!(chain, expected_chain);
+        assert_eq!(chain, retried_output[32..].to_vec());
+        assert_eq!(offset, expected_offset);
+        Ok(())
     }
 }
@@ -317,6 +317,7 @@ pub fn create_dealing(
 ) -> Result<IDkgDealingInternal, IdkgCreateDealingInternalError> {
     let curve = match algorithm_id {
         AlgorithmId::ThresholdEcdsaSecp256k1 => Ok(EccCurveType::K256),
+        AlgorithmId::ThresholdEcdsaSecp256r1 => Ok(EccCurveType::P256),
         _ => Err(IdkgCreateDealingInternalError::UnsupportedAlgorithm),
     }?;
 
@@ -368,6 +369,7 @@ pub fn create_transcript(
 ) -> Result<IDkgTranscriptInternal, IDkgCreateTranscriptInternalError> {
     let curve = match algorithm_id {
         AlgorithmId::ThresholdEcdsaSecp256k1 => Ok(EccCurveType::K256),
+        AlgorithmId::ThresholdEcdsaSecp256r1 => Ok(EccCurveType::P256),
         _ => Err(IDkgCreateTranscriptInternalError::UnsupportedAlgorithm),
     }?;
 
@@ -565,6 +567,7 @@ pub fn publicly_verify_dealing(
 ) -> Result<(), IDkgVerifyDealingInternalError> {
     let curve = match algorithm_id {
         AlgorithmId::ThresholdEcdsaSecp256k1 => Ok(EccCurveType::K256),
+        AlgorithmId::ThresholdEcdsaSecp256r1 => Ok(EccCurveType::P256),
         _ => Err(IDkgVerifyDealingInternalError::UnsupportedAlgorithm),
     }?;
 
@@ -597,6 +600,7 @@ pub fn privately_verify_dealing(
 ) -> Result<(), IDkgVerifyDealingInternalError> {
     let curve = match algorithm_id {
         AlgorithmId::ThresholdEcdsaSecp256k1 => Ok(EccCurveType::K256),
+        AlgorithmId::ThresholdEcdsaSecp256r1 => Ok(EccCurveType::P256),
         _ => Err(IDkgVerifyDealingInternalError::UnsupportedAlgorithm),
     }?;
 
@@ -660,6 +664,9 @@ fn signature_parameters(algorithm_id: AlgorithmId) -> Option<(EccCurveType, usiz
         AlgorithmId::ThresholdEcdsaSecp256k1 => {
             Some((EccCurveType::K256, EccCurveType::K256.scalar_bytes()))
         }
+        AlgorithmId::ThresholdEcdsaSecp256r1 => {
+            Some((EccCurveType::P256, EccCurveType::P256.scalar_bytes()))
+        }
         _ => None,
     }
 }
@@ -808,6 +815,7 @@ pub fn combine_sig_shares(
 ) -> Result<ThresholdEcdsaCombinedSigInternal, ThresholdEcdsaCombineSigSharesInternalError> {
     let curve_type = match algorithm_id {
         AlgorithmId::ThresholdEcdsaSecp256k1 => EccCurveType::K256,
+        AlgorithmId::ThresholdEcdsaSecp256r1 => EccCurveType::P256,
         _ => return Err(ThresholdEcdsaCombineSigSharesInternalError::UnsupportedAlgorithm),
     };
 
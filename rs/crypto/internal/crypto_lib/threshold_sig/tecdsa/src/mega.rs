@@ -206,21 +206,24 @@ impl MEGaCiphertext {
 
     /// Simple type verification for MEGa ciphertexts
     ///
-    /// Verifies that the ciphertext is of the expected type (single or pairs)
-    /// and is for the expected curve.
+    /// Verifies that the ciphertext is of the expected type (single or pairs),
+    /// that it encrypts values on the expected curve, and that the ephemeral
+    /// key is on a curve which may be used for encrypting such values.
     pub fn verify_is(
         &self,
         ctype: MEGaCiphertextType,
         curve: EccCurveType,
     ) -> ThresholdEcdsaResult<()> {
-        if self.ephemeral_key().curve_type() != curve {
+        let key_curve = self.ephemeral_key().curve_type();
+
+        if !is_supported_key_curve(key_curve, curve) {
             return Err(ThresholdEcdsaError::CurveMismatch);
         }
 
-        if self.pop_public_key().curve_type() != curve {
+        if self.pop_public_key().curve_type() != key_curve {
             return Err(ThresholdEcdsaError::CurveMismatch);
         }
-        if self.pop_proof().curve_type()? != curve {
+        if self.pop_proof().curve_type()? != key_curve {
             return Err(ThresholdEcdsaError::CurveMismatch);
         }
 
@@ -312,7 +315,7 @@ impl From<MEGaCiphertextPair> for MEGaCiphertext {
 fn check_plaintexts(
     plaintexts: &[EccScalar],
     recipients: &[MEGaPublicKey],
-) -> ThresholdEcdsaResult<EccCurveType> {
+) -> ThresholdEcdsaResult<(EccCurveType, EccCurveType)> {
     if plaintexts.len() != recipients.len() {
         return Err(ThresholdEcdsaError::InvalidArguments(
             "Must be as many plaintexts as recipients".to_string(),
@@ -333,19 +336,15 @@ fn check_plaintexts(
         }
     }
 
-    for recipient in recipients {
-        if recipient.curve_type() != curve_type {
-            return Err(ThresholdEcdsaError::CurveMismatch);
-        }
-    }
+    let key_curve = check_recipients(recipients, curve_type)?;
 
-    Ok(curve_type)
+    Ok((curve_type, key_curve))
 }
 
 fn check_plaintexts_pair(
     plaintexts: &[(EccScalar, EccScalar)],
     recipients: &[MEGaPublicKey],
-) -> ThresholdEcdsaResult<EccCurveType> {
+) -> ThresholdEcdsaResult<(EccCurveType, EccCurveType)> {
     if plaintexts.len() != recipients.len() {
         return Err(ThresholdEcdsaError::InvalidArguments(
             "Must be as many plaintexts as recipients".to_string(),
@@ -366,17 +365,49 @@ fn check_plaintexts_pair(
         }
     }
 
+    let key_curve = check_recipients(recipients, curve_type)?;
+
+    Ok((curve_type, key_curve))
+}
+
+/// Returns the curve of the recipients' public keys
+///
+/// All recipients must use the same curve, and that curve must be usable for
+/// encrypting plaintexts on `plaintext_curve`.
+fn check_recipients(
+    recipients: &[MEGaPublicKey],
+    plaintext_curve: EccCurveType,
+) -> ThresholdEcdsaResult<EccCurveType> {
+    let key_curve = recipients[0].curve_type();
+
+    if !is_supported_key_curve(key_curve, plaintext_curve) {
+        return Err(ThresholdEcdsaError::CurveMismatch);
+    }
+
     for recipient in recipients {
-        if recipient.curve_type() != curve_type {
+        if recipient.curve_type() != key_curve {
             return Err(ThresholdEcdsaError::CurveMismatch);
         }
     }
 
-    Ok(curve_type)
+    Ok(key_curve)
+}
+
+/// Returns true if MEGa keys on `key_curve` can encrypt values on `plaintext_curve`
+///
+/// Keys may always be used for values on their own curve. In addition
+/// secp256k1 keys may be used for values on any curve; this allows nodes to
+/// use their single secp256k1 MEGa key to receive P-256 dealings as well.
+pub(crate) fn is_supported_key_curve(
+    key_curve: EccCurveType,
+    plaintext_curve: EccCurveType,
+) -> bool {
+    key_curve == plaintext_curve || key_curve == EccCurveType::K256
 }
 
 fn mega_hash_to_scalars(
     ctype: MEGaCiphertextType,
+    plaintext_curve: EccCurveType,
     dealer_index: NodeIndex,
     recipient_index: NodeIndex,
     associated_data: &[u8],
@@ -384,8 +415,6 @@ fn mega_hash_to_scalars(
     ephemeral_key: &EccPoint,
     shared_secret: &EccPoint,
 ) -> ThresholdEcdsaResult<Vec<EccScalar>> {
-    let curve_type = public_key.curve_type();
-
     let count = match ctype {
         MEGaCiphertextType::Single => 1,
         MEGaCiphertextType::Pairs => 2,
@@ -398,7 +427,7 @@ fn mega_hash_to_scalars(
     ro.add_point("public_key", public_key)?;
     ro.add_point("ephemeral_key", ephemeral_key)?;
     ro.add_point("shared_secret", shared_secret)?;
-    ro.output_scalars(curve_type, count)
+    ro.output_scalars(plaintext_curve, count)
 }
 
 /// Compute the Proof Of Possession (PoP) base element
@@ -490,12 +519,12 @@ impl MEGaCiphertextSingle {
         dealer_index: NodeIndex,
         associated_data: &[u8],
     ) -> ThresholdEcdsaResult<Self> {
-        let curve_type = check_plaintexts(plaintexts, recipients)?;
+        let (curve_type, key_curve) = check_plaintexts(plaintexts, recipients)?;
 
         let ctype = MEGaCiphertextType::Single;
 
         let (beta, v, pop_public_key, pop_proof) =
-            compute_eph_key_and_pop(ctype, curve_type, seed, associated_data, dealer_index)?;
+            compute_eph_key_and_pop(ctype, key_curve, seed, associated_data, dealer_index)?;
 
         let mut ctexts = Vec::with_capacity(recipients.len());
 
@@ -504,6 +533,7 @@ impl MEGaCiphertextSingle {
 
             let hm = mega_hash_to_scalars(
                 ctype,
+                curve_type,
                 dealer_index,
                 index as NodeIndex,
                 associated_data,
@@ -556,6 +586,7 @@ impl MEGaCiphertextSingle {
 
         let hm = mega_hash_to_scalars(
             MEGaCiphertextType::Single,
+            self.ctexts[recipient_index as usize].curve_type(),
             dealer_index,
             recipient_index,
             associated_data,
@@ -601,12 +632,12 @@ impl MEGaCiphertextPair {
         dealer_index: NodeIndex,
         associated_data: &[u8],
     ) -> ThresholdEcdsaResult<Self> {
-        let curve_type = check_plaintexts_pair(plaintexts, recipients)?;
+        let (curve_type, key_curve) = check_plaintexts_pair(plaintexts, recipients)?;
 
         let ctype = MEGaCiphertextType::Pairs;
 
         let (beta, v, pop_public_key, pop_proof) =
-            compute_eph_key_and_pop(ctype, curve_type, seed, associated_data, dealer_index)?;
+            compute_eph_key_and_pop(ctype, key_curve, seed, associated_data, dealer_index)?;
 
         let mut ctexts = Vec::with_capacity(recipients.len());
 
@@ -615,6 +646,7 @@ impl MEGaCiphertextPair {
 
             let hm = mega_hash_to_scalars(
                 ctype,
+                curve_type,
                 dealer_index,
                 index as NodeIndex,
                 associated_data,
@@ -668,6 +700,7 @@ impl MEGaCiphertextPair {
 
         let hm = mega_hash_to_scalars(
             MEGaCiphertextType::Pairs,
+            self.ctexts[recipient_index as usize].0.curve_type(),
             dealer_index,
             recipient_index,
             associated_data,
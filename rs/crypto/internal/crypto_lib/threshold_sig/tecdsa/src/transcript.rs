@@ -399,7 +399,7 @@ impl CommitmentOpening {
         secret_key: &MEGaPrivateKey,
         public_key: &MEGaPublicKey,
     ) -> Result<Self, IDkgComputeSecretSharesInternalError> {
-        let curve = secret_key.curve_type();
+        let curve = transcript_commitment.commitment().curve_type();
         let mut openings = Vec::with_capacity(verified_dealings.len());
 
         for (dealer_index, dealing) in verified_dealings {
@@ -477,7 +477,7 @@ impl CommitmentOpening {
         secret_key: &MEGaPrivateKey,
         public_key: &MEGaPublicKey,
     ) -> Result<Self, IDkgComputeSecretSharesInternalError> {
-        let curve = secret_key.curve_type();
+        let curve = transcript_commitment.commitment().curve_type();
         let mut openings = Vec::with_capacity(verified_dealings.len());
 
         for (dealer_index, dealing) in verified_dealings {
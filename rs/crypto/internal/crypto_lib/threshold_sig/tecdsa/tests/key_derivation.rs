@@ -25,21 +25,26 @@ fn test_index_next_behavior() {
 }
 
 #[test]
-fn test_that_key_derivation_on_secp256r1_currently_fails() -> Result<(), ThresholdEcdsaError> {
+fn key_derivation_on_secp256r1_is_consistent_with_stepwise_derivation(
+) -> Result<(), ThresholdEcdsaError> {
     let mut rng = test_rng::test_rng();
-    let path = DerivationPath::new_bip32(&[1, 2, 3]);
     let master_key = EccPoint::hash_to_point(
         EccCurveType::P256,
         &rng.gen::<[u8; 32]>(),
         "public_key".as_bytes(),
     )?;
 
-    assert_eq!(
-        path.derive_tweak(&master_key),
-        Err(ThresholdEcdsaError::InvalidArguments(
-            "Currently key derivation not defined for secp256r1".to_string()
-        ))
-    );
+    let (offset, chain_key) = DerivationPath::new_bip32(&[1, 2, 3]).derive_tweak(&master_key)?;
+
+    let (first_offset, first_chain_key) =
+        DerivationPath::new_bip32(&[1]).derive_tweak(&master_key)?;
+    let first_key = master_key.add_points(&EccPoint::mul_by_g(&first_offset)?)?;
+    let (rest_offset, rest_chain_key) = DerivationPath::new_bip32(&[2, 3])
+        .derive_tweak_with_chain_code(&first_key, &first_chain_key)?;
+
+    assert_eq!(offset.curve_type(), EccCurveType::P256);
+    assert_eq!(offset, first_offset.add(&rest_offset)?);
+    assert_eq!(chain_key, rest_chain_key);
 
     Ok(())
 }
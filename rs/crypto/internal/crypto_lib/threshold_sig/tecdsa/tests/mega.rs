@@ -160,6 +160,58 @@ fn mega_pair_smoke_test() -> Result<(), ThresholdEcdsaError> {
     Ok(())
 }
 
+#[test]
+fn mega_k256_keys_should_encrypt_p256_plaintexts() -> Result<(), ThresholdEcdsaError> {
+    let key_curve = EccCurveType::K256;
+    let curve = EccCurveType::P256;
+
+    let mut rng = Seed::from_bytes(&[44; 32]).into_rng();
+
+    let sk = MEGaPrivateKey::generate(key_curve, &mut rng)?;
+    let pk = sk.public_key()?;
+
+    let associated_data = b"assoc_data_test";
+    let dealer_index = 0;
+
+    let ptext = EccScalar::random(curve, &mut rng);
+
+    let ctext = MEGaCiphertextSingle::encrypt(
+        Seed::from_rng(&mut rng),
+        &[ptext],
+        &[pk],
+        dealer_index,
+        associated_data,
+    )?;
+
+    let ctext = MEGaCiphertext::from(ctext);
+    assert!(ctext.verify_is(MEGaCiphertextType::Single, curve).is_ok());
+    assert_eq!(
+        ctext.verify_is(MEGaCiphertextType::Single, key_curve),
+        Err(ThresholdEcdsaError::CurveMismatch)
+    );
+
+    let opening = match &ctext {
+        MEGaCiphertext::Single(c) => c.decrypt(associated_data, dealer_index, 0, &sk, &pk)?,
+        MEGaCiphertext::Pairs(_) => panic!("Unexpected ciphertext type"),
+    };
+    assert_eq!(opening, ptext);
+
+    // The reverse is not allowed: P-256 keys cannot encrypt secp256k1 plaintexts
+    let p256_pk = MEGaPrivateKey::generate(curve, &mut rng)?.public_key()?;
+    assert_eq!(
+        MEGaCiphertextSingle::encrypt(
+            Seed::from_rng(&mut rng),
+            &[EccScalar::random(key_curve, &mut rng)],
+            &[p256_pk],
+            dealer_index,
+            associated_data,
+        ),
+        Err(ThresholdEcdsaError::CurveMismatch)
+    );
+
+    Ok(())
+}
+
 #[test]
 fn mega_should_reject_invalid_pop() -> Result<(), ThresholdEcdsaError> {
     let curve = EccCurveType::K256;
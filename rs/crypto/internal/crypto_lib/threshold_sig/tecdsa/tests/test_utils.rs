@@ -28,13 +28,12 @@ impl ProtocolSetup {
     ) -> Result<Self, ThresholdEcdsaError> {
         let alg = match curve {
             EccCurveType::K256 => AlgorithmId::ThresholdEcdsaSecp256k1,
-            _ => {
-                return Err(ThresholdEcdsaError::InvalidArguments(
-                    "Unsupported curve".to_string(),
-                ))
-            }
+            EccCurveType::P256 => AlgorithmId::ThresholdEcdsaSecp256r1,
         };
 
+        // Nodes only have secp256k1 MEGa keys, regardless of the signature curve
+        let key_curve = EccCurveType::K256;
+
         let mut rng = seed.into_rng();
         let ad = rng.gen::<[u8; 32]>().to_vec();
 
@@ -42,7 +41,7 @@ impl ProtocolSetup {
         let mut pk = Vec::with_capacity(receivers);
 
         for _i in 0..receivers {
-            let k = MEGaPrivateKey::generate(curve, &mut rng)?;
+            let k = MEGaPrivateKey::generate(key_curve, &mut rng)?;
             pk.push(k.public_key()?);
             sk.push(k);
         }
@@ -717,9 +716,14 @@ impl SignatureProtocolSetup {
     }
 
     pub fn public_key(&self, path: &DerivationPath) -> Result<EcdsaPublicKey, ThresholdEcdsaError> {
+        let constant_term = self.key.transcript.constant_term();
+        let algorithm_id = match constant_term.curve_type() {
+            EccCurveType::K256 => AlgorithmId::EcdsaSecp256k1,
+            EccCurveType::P256 => AlgorithmId::EcdsaP256,
+        };
         let master_public_key = MasterEcdsaPublicKey {
-            algorithm_id: AlgorithmId::EcdsaSecp256k1,
-            public_key: self.key.transcript.constant_term().serialize(),
+            algorithm_id,
+            public_key: constant_term.serialize(),
         };
         ic_crypto_internal_threshold_sig_ecdsa::sign::derive_public_key(&master_public_key, path)
     }
@@ -832,13 +836,27 @@ impl SignatureProtocolExecution {
 
         use k256::ecdsa::signature::{Signature, Verifier};
 
-        let vk = k256::ecdsa::VerifyingKey::from_sec1_bytes(&pk.public_key)
-            .expect("Failed to parse public key");
+        match pk.algorithm_id {
+            AlgorithmId::EcdsaSecp256k1 => {
+                let vk = k256::ecdsa::VerifyingKey::from_sec1_bytes(&pk.public_key)
+                    .expect("Failed to parse public key");
+
+                let sig = k256::ecdsa::Signature::from_bytes(&sig.serialize())
+                    .expect("Failed to parse signature");
 
-        let sig = k256::ecdsa::Signature::from_bytes(&sig.serialize())
-            .expect("Failed to parse signature");
+                assert!(vk.verify(&self.signed_message, &sig).is_ok());
+            }
+            AlgorithmId::EcdsaP256 => {
+                let vk = p256::ecdsa::VerifyingKey::from_sec1_bytes(&pk.public_key)
+                    .expect("Failed to parse public key");
+
+                let sig = p256::ecdsa::Signature::from_bytes(&sig.serialize())
+                    .expect("Failed to parse signature");
 
-        assert!(vk.verify(&self.signed_message, &sig).is_ok());
+                assert!(vk.verify(&self.signed_message, &sig).is_ok());
+            }
+            alg => panic!("Unexpected algorithm {:?}", alg),
+        }
 
         Ok(())
     }
@@ -1642,6 +1642,51 @@ fn should_run_threshold_ecdsa_protocol_with_single_node() {
         .is_ok());
 }
 
+#[test]
+fn should_run_threshold_ecdsa_protocol_over_secp256r1() {
+    let mut rng = thread_rng();
+
+    let subnet_size = rng.gen_range(1..10);
+    let env = CanisterThresholdSigTestEnvironment::new(subnet_size);
+
+    let key_transcript = generate_key_transcript(&env, AlgorithmId::ThresholdEcdsaSecp256r1);
+
+    let master_ecdsa_key =
+        get_tecdsa_master_public_key(&key_transcript).expect("Master key extraction failed");
+    assert_eq!(master_ecdsa_key.algorithm_id, AlgorithmId::EcdsaP256);
+    assert_eq!(master_ecdsa_key.public_key.len(), 33);
+
+    let derivation_path = ExtendedDerivationPath {
+        caller: PrincipalId::new_user_test_id(1),
+        derivation_path: vec![],
+    };
+    let derived_public_key = derive_tecdsa_public_key(&master_ecdsa_key, &derivation_path)
+        .expect("Public key derivation failed");
+    assert_eq!(derived_public_key.algorithm_id, AlgorithmId::EcdsaP256);
+
+    let nonce = Randomness::from(rng.gen::<[u8; 32]>());
+    let message_hash = &rng.gen::<[u8; 32]>();
+
+    let sig_inputs = generate_tecdsa_protocol_inputs(
+        &env,
+        &key_transcript,
+        message_hash,
+        nonce,
+        derivation_path,
+        AlgorithmId::ThresholdEcdsaSecp256r1,
+    );
+
+    let signature = run_tecdsa_protocol(&env, &sig_inputs);
+
+    let verifier_id = random_node_id_excluding(sig_inputs.receivers().get());
+    let verifier_crypto_component =
+        TempCryptoComponent::new(Arc::clone(&env.registry) as Arc<_>, verifier_id);
+
+    assert!(verifier_crypto_component
+        .verify_combined_sig(&sig_inputs, &signature)
+        .is_ok());
+}
+
 #[test]
 fn should_fail_combine_sig_shares_with_insufficient_shares() {
     let mut rng = thread_rng();
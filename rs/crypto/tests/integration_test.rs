@@ -780,11 +780,19 @@ fn should_succeed_check_keys_with_registry_if_idkg_key_requires_registration() {
 /// Ensure the structs are consistent and then update the test below.
 #[test]
 fn algorithm_id_should_match_algorithm_id_proto() {
-    let algorithm_id_variants = 17;
-    assert_eq!(AlgorithmId::iter().count(), algorithm_id_variants);
+    let algorithm_id_variants = 19;
+    // Values that are reserved in AlgorithmIdProto and absent in AlgorithmId.
+    let reserved_algorithm_ids = [17];
+    assert_eq!(
+        AlgorithmId::iter().count(),
+        algorithm_id_variants - reserved_algorithm_ids.len()
+    );
 
     for i in 0..algorithm_id_variants {
-        assert!(AlgorithmIdProto::from_i32(i as i32).is_some());
+        assert_eq!(
+            AlgorithmIdProto::from_i32(i as i32).is_some(),
+            !reserved_algorithm_ids.contains(&i)
+        );
     }
     assert!(AlgorithmIdProto::from_i32(algorithm_id_variants as i32).is_none());
 
@@ -853,6 +861,10 @@ fn algorithm_id_should_match_algorithm_id_proto() {
         AlgorithmId::MegaSecp256k1 as i32,
         AlgorithmIdProto::MegaSecp256k1 as i32
     );
+    assert_eq!(
+        AlgorithmId::ThresholdEcdsaSecp256r1 as i32,
+        AlgorithmIdProto::ThresholdEcdsaSecp256r1 as i32
+    );
 }
 
 fn get_logger(log_filename: &std::path::Path) -> LoggerImpl {
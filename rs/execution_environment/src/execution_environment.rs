@@ -2068,6 +2068,9 @@ pub struct ExecuteCanisterResult {
     pub ingress_status: Option<(MessageId, IngressStatus)>,
     // The description of the executed task or message.
     pub description: Option<String>,
+    // Whether an ingress message or a request from a canister started
+    // executing, i.e. whether a new update transaction was started.
+    pub started_update_transaction: bool,
 }
 
 /// Executes the given input message.
@@ -2098,6 +2101,7 @@ fn execute_message(
         heap_delta,
         ingress_status,
         description: Some(msg_info),
+        started_update_transaction: false,
     }
 }
 
@@ -2119,6 +2123,7 @@ pub fn execute_canister(
                 heap_delta: NumBytes::from(0),
                 ingress_status: None,
                 description: None,
+                started_update_transaction: false,
             };
         }
         NextExecution::StartNew | NextExecution::ContinueLong => {}
@@ -2141,6 +2146,7 @@ pub fn execute_canister(
                     heap_delta,
                     ingress_status: None,
                     description: Some("heartbeat".to_string()),
+                    started_update_transaction: false,
                 }
             }
             ExecutionTask::PausedExecution(id) => {
@@ -2159,6 +2165,7 @@ pub fn execute_canister(
                     heap_delta,
                     ingress_status,
                     description: Some("paused execution".to_string()),
+                    started_update_transaction: false,
                 }
             }
             ExecutionTask::AbortedExecution(message) => execute_message(
@@ -2177,16 +2184,23 @@ pub fn execute_canister(
         },
         None => {
             let message = canister.pop_input().unwrap();
-            execute_message(
-                message,
-                exec_env,
-                canister,
-                instruction_limits,
-                network_topology,
-                time,
-                round_limits,
-                subnet_size,
-            )
+            let started_update_transaction = match message {
+                CanisterInputMessage::Ingress(_) | CanisterInputMessage::Request(_) => true,
+                CanisterInputMessage::Response(_) => false,
+            };
+            ExecuteCanisterResult {
+                started_update_transaction,
+                ..execute_message(
+                    message,
+                    exec_env,
+                    canister,
+                    instruction_limits,
+                    network_topology,
+                    time,
+                    round_limits,
+                    subnet_size,
+                )
+            }
         }
     }
 }
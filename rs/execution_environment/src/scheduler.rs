@@ -458,21 +458,26 @@ impl SchedulerImpl {
             drop(preparation_timer);
 
             let instructions_before = round_limits.instructions;
-            let (executed_canisters, mut loop_ingress_execution_results, heap_delta) = self
-                .execute_canisters_in_inner_round(
-                    active_canisters_partitioned_by_cores,
-                    current_round,
-                    state.time(),
-                    Arc::new(state.metadata.network_topology.clone()),
-                    &measurement_scope,
-                    round_limits,
-                    subnet_size,
-                );
+            let (
+                executed_canisters,
+                mut loop_ingress_execution_results,
+                heap_delta,
+                update_transactions,
+            ) = self.execute_canisters_in_inner_round(
+                active_canisters_partitioned_by_cores,
+                current_round,
+                state.time(),
+                Arc::new(state.metadata.network_topology.clone()),
+                &measurement_scope,
+                round_limits,
+                subnet_size,
+            );
             let instructions_consumed = instructions_before - round_limits.instructions;
 
             let finalization_timer = self.metrics.round_inner_iteration_fin.start_timer();
             total_heap_delta += heap_delta;
             state.metadata.heap_delta_estimate += heap_delta;
+            state.metadata.subnet_metrics.update_transactions_total += update_transactions;
 
             // Put back the executed canisters into the canisters map. Since usually most
             // canisters have no messages to execute, this is likely to be more efficient
@@ -606,6 +611,7 @@ impl SchedulerImpl {
         Vec<CanisterState>,
         Vec<(MessageId, IngressStatus)>,
         NumBytes,
+        u64,
     ) {
         let thread_pool = &mut self.thread_pool.borrow_mut();
         let exec_env = self.exec_env.as_ref();
@@ -617,6 +623,7 @@ impl SchedulerImpl {
                 canisters_by_thread.into_iter().flatten().collect(),
                 vec![],
                 NumBytes::from(0),
+                0,
             );
         }
 
@@ -685,6 +692,7 @@ impl SchedulerImpl {
         let mut total_instructions_executed = NumInstructions::from(0);
         let mut max_instructions_executed_per_thread = NumInstructions::from(0);
         let mut heap_delta = NumBytes::from(0);
+        let mut update_transactions = 0;
         for mut result in results_by_thread.into_iter() {
             canisters.append(&mut result.canisters);
             ingress_results.append(&mut result.ingress_results);
@@ -704,6 +712,7 @@ impl SchedulerImpl {
             // to `execution_round_inner_iteration`.
             measurement_scope.add(instructions_executed, result.messages_executed);
             heap_delta += result.heap_delta;
+            update_transactions += result.update_transactions;
         }
 
         // Since there are multiple threads, we update the global limit using
@@ -713,7 +722,7 @@ impl SchedulerImpl {
         self.metrics
             .instructions_consumed_per_round
             .observe(total_instructions_executed.get() as f64);
-        (canisters, ingress_results, heap_delta)
+        (canisters, ingress_results, heap_delta, update_transactions)
     }
 
     fn process_stopping_canisters(&self, state: ReplicatedState) -> ReplicatedState {
@@ -1342,6 +1351,10 @@ impl Scheduler for SchedulerImpl {
                 );
             }
 
+            let subnet_metrics = &mut state.metadata.subnet_metrics;
+            subnet_metrics.num_canisters = state.canister_states.len() as u64;
+            subnet_metrics.canister_state_bytes = total_canister_memory_usage;
+
             // Check if the invariants are still valid after the execution for active canisters.
             self.check_canister_invariants(
                 &round_log,
@@ -1439,6 +1452,8 @@ struct ExecutionThreadResult {
     canisters: Vec<CanisterState>,
     ingress_results: Vec<(MessageId, IngressStatus)>,
     messages_executed: NumMessages,
+    // The number of ingress messages and canister requests that started executing.
+    update_transactions: u64,
     heap_delta: NumBytes,
     round_limits: RoundLimits,
 }
@@ -1472,6 +1487,7 @@ fn execute_canisters_on_thread(
     let mut canisters = vec![];
     let mut ingress_results = vec![];
     let mut total_messages_executed = NumMessages::from(0);
+    let mut total_update_transactions = 0;
     let mut total_heap_delta = NumBytes::from(0);
 
     let instruction_limits = InstructionLimits::new(
@@ -1522,6 +1538,7 @@ fn execute_canisters_on_thread(
                 heap_delta,
                 ingress_status,
                 description,
+                started_update_transaction,
             } = execute_canister(
                 exec_env,
                 canister,
@@ -1546,6 +1563,9 @@ fn execute_canisters_on_thread(
             round_limits.instructions -=
                 as_round_instructions(config.instruction_overhead_per_message);
             total_messages_executed.inc_assign();
+            if started_update_transaction {
+                total_update_transactions += 1;
+            }
             total_heap_delta += heap_delta;
             if rate_limiting_of_heap_delta == FlagStatus::Enabled {
                 canister.scheduler_state.heap_delta_debit += heap_delta;
@@ -1581,6 +1601,7 @@ fn execute_canisters_on_thread(
         canisters,
         ingress_results,
         messages_executed: total_messages_executed,
+        update_transactions: total_update_transactions,
         heap_delta: total_heap_delta,
         round_limits,
     }
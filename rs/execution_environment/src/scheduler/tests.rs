@@ -1181,6 +1181,39 @@ fn execute_heartbeat_once_per_round_in_system_subnet() {
     assert_eq!(metrics.round_inner.messages.get_sample_sum(), 4.0);
 }
 
+#[test]
+fn update_transactions_total_counts_ingress_but_not_heartbeats() {
+    // This test sets up a canister with a heartbeat method and three
+    // messages. Only the messages are expected to count as update
+    // transactions, and the count accumulates over rounds.
+    let mut test = SchedulerTestBuilder::new()
+        .with_scheduler_config(SchedulerConfig {
+            scheduler_cores: 2,
+            ..SchedulerConfig::application_subnet()
+        })
+        .build();
+    let canister = test.create_canister_with(
+        Cycles::new(1_000_000_000_000),
+        ComputeAllocation::zero(),
+        MemoryAllocation::BestEffort,
+        Some(SystemMethod::CanisterHeartbeat),
+        None,
+    );
+    test.send_ingress(canister, ingress(1));
+    test.send_ingress(canister, ingress(1));
+    test.send_ingress(canister, ingress(1));
+    test.expect_heartbeat(canister, instructions(1));
+    test.execute_round(ExecutionRoundType::OrdinaryRound);
+    let subnet_metrics = &test.state().metadata.subnet_metrics;
+    assert_eq!(subnet_metrics.update_transactions_total, 3);
+
+    test.send_ingress(canister, ingress(1));
+    test.expect_heartbeat(canister, instructions(1));
+    test.execute_round(ExecutionRoundType::OrdinaryRound);
+    let subnet_metrics = &test.state().metadata.subnet_metrics;
+    assert_eq!(subnet_metrics.update_transactions_total, 4);
+}
+
 #[test]
 fn execute_heartbeat_before_messages() {
     // This test sets up a canister on a system subnet with a heartbeat method and
@@ -1,13 +1,19 @@
-//! Module that deals with requests to /api/v2/canister/.../call
+//! Module that deals with requests to /api/v2/canister/.../call and
+//! /api/v3/canister/.../call
 
 use crate::{
     body::BodyReceiverLayer,
-    common::{get_cors_headers, make_plaintext_response, make_response, map_box_error_to_response},
+    common::{
+        cbor_response, get_cors_headers, into_cbor, make_http_error_response,
+        make_plaintext_response, make_response, map_box_error_to_response,
+    },
+    state_reader_executor::StateReaderExecutor,
     types::{to_legacy_request_type, ApiReqType},
     validator_executor::ValidatorExecutor,
     EndpointService, HttpError, HttpHandlerMetrics, IngressFilterService, UNKNOWN_LABEL,
 };
 use hyper::{Body, Response, StatusCode};
+use ic_crypto_tree_hash::{sparse_labeled_tree_from_paths, Label, Path};
 use ic_interfaces::registry::RegistryClient;
 use ic_interfaces_p2p::{IngressError, IngressIngestionService};
 use ic_logger::{error, info_sample, warn, ReplicaLogger};
@@ -17,17 +23,75 @@ use ic_registry_client_helpers::{
 };
 use ic_registry_provisional_whitelist::ProvisionalWhitelist;
 use ic_types::{
+    ingress::{IngressState, IngressStatus},
     malicious_flags::MaliciousFlags,
-    messages::{SignedIngress, SignedRequestBytes},
-    CountBytes, RegistryVersion, SubnetId,
+    messages::{
+        Blob, Certificate, CertificateDelegation, HttpReadStateResponse, MessageId, SignedIngress,
+        SignedRequestBytes,
+    },
+    CountBytes, Height, RegistryVersion, SubnetId,
 };
 use std::convert::{Infallible, TryInto};
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::{watch, Semaphore};
+use tokio::time::sleep;
 use tower::{load_shed::LoadShed, util::BoxCloneService, Service, ServiceBuilder, ServiceExt};
 
+/// Maximum number of synchronous calls that wait for their certified reply
+/// at the same time. Calls beyond that get a `202 Accepted` right away.
+pub(crate) const MAX_CONCURRENT_SYNC_CALL_WAITERS: usize = 1000;
+
+/// How often the latest certified height is checked by the task that wakes
+/// up the waiting synchronous calls.
+const CERTIFIED_HEIGHT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Shared state of the waiters of the synchronous call endpoint.
+#[derive(Clone)]
+pub(crate) struct SyncCallWaiters {
+    timeout: Duration,
+    certified_height: watch::Receiver<Height>,
+    permits: Arc<Semaphore>,
+}
+
+impl SyncCallWaiters {
+    pub(crate) fn new(
+        timeout: Duration,
+        certified_height: watch::Receiver<Height>,
+        max_waiters: usize,
+    ) -> Self {
+        Self {
+            timeout,
+            certified_height,
+            permits: Arc::new(Semaphore::new(max_waiters)),
+        }
+    }
+}
+
+/// Spawns a task that publishes the latest certified height, so that waiters
+/// only re-read the certified state when a new certification is available.
+/// The task stops at the first change after all receivers are dropped.
+pub(crate) fn watch_certified_height(
+    state_reader_executor: StateReaderExecutor,
+) -> watch::Receiver<Height> {
+    let (sender, receiver) = watch::channel(Height::from(0));
+    tokio::spawn(async move {
+        loop {
+            if let Ok(height) = state_reader_executor.get_latest_certified_height().await {
+                let changed = *sender.borrow() != height;
+                if changed && sender.send(height).is_err() {
+                    break;
+                }
+            }
+            sleep(CERTIFIED_HEIGHT_POLL_INTERVAL).await;
+        }
+    });
+    receiver
+}
+
 #[derive(Clone)]
 pub(crate) struct CallService {
     log: ReplicaLogger,
@@ -38,6 +102,12 @@ pub(crate) struct CallService {
     ingress_sender: IngressIngestionService,
     ingress_filter: LoadShed<IngressFilterService>,
     malicious_flags: MaliciousFlags,
+    state_reader_executor: StateReaderExecutor,
+    delegation_from_nns: Arc<RwLock<Option<CertificateDelegation>>>,
+    // If set, the service waits for the message to reach a terminal state in
+    // the certified state and returns the certificate instead of a plain
+    // `202 Accepted`.
+    sync_call: Option<SyncCallWaiters>,
 }
 
 impl CallService {
@@ -51,6 +121,9 @@ impl CallService {
         ingress_sender: IngressIngestionService,
         ingress_filter: IngressFilterService,
         malicious_flags: MaliciousFlags,
+        state_reader_executor: StateReaderExecutor,
+        delegation_from_nns: Arc<RwLock<Option<CertificateDelegation>>>,
+        sync_call: Option<SyncCallWaiters>,
     ) -> EndpointService {
         let base_service = BoxCloneService::new(ServiceBuilder::new().service(Self {
             log,
@@ -61,6 +134,9 @@ impl CallService {
             ingress_sender,
             ingress_filter: ServiceBuilder::new().load_shed().service(ingress_filter),
             malicious_flags,
+            state_reader_executor,
+            delegation_from_nns,
+            sync_call,
         }));
         BoxCloneService::new(
             ServiceBuilder::new()
@@ -118,7 +194,70 @@ fn get_registry_data(
     Ok((settings, provisional_whitelist))
 }
 
-/// Handles a call to /api/v2/canister/../call
+/// Waits until the certified state contains a terminal status for
+/// `message_id` and returns a `read_state` response carrying the certificate
+/// for that status. The certified state is re-read whenever the certified
+/// height changes. Returns `None` if this does not happen before the timeout.
+async fn wait_for_certified_status(
+    state_reader_executor: &StateReaderExecutor,
+    delegation_from_nns: Arc<RwLock<Option<CertificateDelegation>>>,
+    message_id: &MessageId,
+    timeout: Duration,
+    mut certified_height: watch::Receiver<Height>,
+) -> Option<Response<Body>> {
+    let mut paths = vec![
+        Path::new(vec![
+            Label::from("request_status"),
+            Label::from(message_id.clone()),
+        ]),
+        Path::from(Label::from("time")),
+    ];
+    let labeled_tree = sparse_labeled_tree_from_paths(&mut paths);
+    let wait = async {
+        loop {
+            // Mark the current height as seen before reading, so that a
+            // certification arriving during the read is not missed.
+            certified_height.borrow_and_update();
+            match state_reader_executor
+                .read_certified_state(&labeled_tree)
+                .await
+            {
+                Ok(Some((state, tree, certification))) => {
+                    if is_terminal(&state.get_ingress_status(message_id)) {
+                        let signature = certification.signed.signature.signature.get().0;
+                        let delegation = delegation_from_nns.read().unwrap().clone();
+                        let res = HttpReadStateResponse {
+                            certificate: Blob(into_cbor(&Certificate {
+                                tree,
+                                signature: Blob(signature),
+                                delegation,
+                            })),
+                        };
+                        return Some(cbor_response(&res));
+                    }
+                }
+                Ok(None) => (),
+                Err(_) => return None,
+            }
+            if certified_height.changed().await.is_err() {
+                return None;
+            }
+        }
+    };
+    tokio::time::timeout(timeout, wait).await.ok().flatten()
+}
+
+fn is_terminal(status: &IngressStatus) -> bool {
+    matches!(
+        status,
+        IngressStatus::Known {
+            state: IngressState::Completed(_) | IngressState::Failed(_) | IngressState::Done,
+            ..
+        }
+    )
+}
+
+/// Handles a call to /api/v2/canister/../call and /api/v3/canister/../call
 impl Service<Vec<u8>> for CallService {
     type Response = Response<Body>;
     type Error = Infallible;
@@ -130,12 +269,17 @@ impl Service<Vec<u8>> for CallService {
     }
 
     fn call(&mut self, body: Vec<u8>) -> Self::Future {
+        let api_req_type = if self.sync_call.is_some() {
+            ApiReqType::SyncCall
+        } else {
+            ApiReqType::Call
+        };
         // Actual parsing.
         self.metrics
             .requests_body_size_bytes
             .with_label_values(&[
-                to_legacy_request_type(ApiReqType::Call),
-                ApiReqType::Call.into(),
+                to_legacy_request_type(api_req_type),
+                api_req_type.into(),
                 UNKNOWN_LABEL,
             ])
             .observe(body.len() as f64);
@@ -200,13 +344,16 @@ impl Service<Vec<u8>> for CallService {
         let log = self.log.clone();
         let validator_executor = self.validator_executor.clone();
         let malicious_flags = self.malicious_flags.clone();
+        let state_reader_executor = self.state_reader_executor.clone();
+        let delegation_from_nns = Arc::clone(&self.delegation_from_nns);
+        let sync_call = self.sync_call.clone();
 
         Box::pin(async move {
             if let Err(http_err) = validator_executor
                 .validate_signed_ingress(&msg, registry_version, &malicious_flags)
                 .await
             {
-                let res = make_plaintext_response(http_err.status, http_err.message);
+                let res = make_http_error_response(http_err);
                 return Ok(res);
             }
 
@@ -242,7 +389,23 @@ impl Service<Vec<u8>> for CallService {
                         "ingress_message_submit";
                         ingress_message => ingress_log_entry
                     );
-                    make_accepted_response()
+                    match sync_call {
+                        Some(sync_call) => match sync_call.permits.try_acquire_owned() {
+                            Ok(_permit) => wait_for_certified_status(
+                                &state_reader_executor,
+                                delegation_from_nns,
+                                &message_id,
+                                sync_call.timeout,
+                                sync_call.certified_height,
+                            )
+                            .await
+                            .unwrap_or_else(make_accepted_response),
+                            // Too many calls are already waiting, the client
+                            // has to poll for the status instead.
+                            Err(_) => make_accepted_response(),
+                        },
+                        None => make_accepted_response(),
+                    }
                 }
             };
             Ok(response)
@@ -260,11 +423,217 @@ fn make_accepted_response() -> Response<Body> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::rate_limiter::RateLimiters;
+    use ic_config::http_handler::Config;
+    use ic_crypto_tree_hash::MixedHashTree;
+    use ic_error_types::UserError;
+    use ic_logger::replica_logger::no_op_logger;
+    use ic_metrics::MetricsRegistry;
+    use ic_test_utilities::{
+        crypto::temp_crypto_component_with_fake_registry,
+        state::ReplicatedStateBuilder,
+        state_manager::MockStateManager,
+        types::ids::{canister_test_id, node_test_id, subnet_test_id, user_test_id},
+    };
+    use ic_test_utilities_registry::{setup_registry, SubnetRecordBuilder};
     use ic_types::{
-        messages::{Blob, HttpCallContent, HttpCanisterUpdate, HttpRequestEnvelope},
-        time::current_time_and_expiry_time,
+        consensus::certification::{Certification, CertificationContent},
+        crypto::{
+            threshold_sig::ni_dkg::{NiDkgId, NiDkgTag, NiDkgTargetSubnet},
+            CombinedThresholdSig, CombinedThresholdSigOf, CryptoHash, Signed,
+        },
+        ingress::WasmResult,
+        messages::{
+            Blob, HttpCallContent, HttpCanisterUpdate, HttpRequestEnvelope, SignedIngressContent,
+        },
+        signature::ThresholdSignature,
+        time::{current_time_and_expiry_time, UNIX_EPOCH},
+        CryptoHashOfPartialState, NumBytes,
     };
     use std::convert::TryFrom;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tower::service_fn;
+
+    fn anonymous_call() -> HttpRequestEnvelope<HttpCallContent> {
+        HttpRequestEnvelope::<HttpCallContent> {
+            content: HttpCallContent::Call {
+                update: HttpCanisterUpdate {
+                    canister_id: Blob(canister_test_id(1).get().into_vec()),
+                    method_name: "foo".to_string(),
+                    arg: Blob(vec![]),
+                    nonce: None,
+                    sender: Blob(vec![0x04]),
+                    ingress_expiry: current_time_and_expiry_time().1.as_nanos_since_unix_epoch(),
+                },
+            },
+            sender_sig: None,
+            sender_pubkey: None,
+            sender_delegation: None,
+        }
+    }
+
+    fn certification() -> Certification {
+        Certification {
+            height: Height::from(1),
+            signed: Signed {
+                signature: ThresholdSignature {
+                    signer: NiDkgId {
+                        start_block_height: Height::from(0),
+                        dealer_subnet: subnet_test_id(0),
+                        dkg_tag: NiDkgTag::HighThreshold,
+                        target_subnet: NiDkgTargetSubnet::Local,
+                    },
+                    signature: CombinedThresholdSigOf::new(CombinedThresholdSig(vec![])),
+                },
+                content: CertificationContent::new(CryptoHashOfPartialState::from(CryptoHash(
+                    vec![],
+                ))),
+            },
+        }
+    }
+
+    /// Returns a synchronous call service that accepts every valid message.
+    fn sync_call_service(
+        state_manager: MockStateManager,
+        sync_call: SyncCallWaiters,
+    ) -> EndpointService {
+        let subnet_id = subnet_test_id(1);
+        let metrics = HttpHandlerMetrics::new(&MetricsRegistry::new());
+        let validator_executor = ValidatorExecutor::new(
+            Arc::new(temp_crypto_component_with_fake_registry(node_test_id(0))),
+            Arc::new(RateLimiters::new(&Config::default(), metrics.clone())),
+            no_op_logger(),
+        );
+        let ingress_sender: IngressIngestionService =
+            BoxCloneService::new(service_fn(|_: SignedIngress| async {
+                Ok::<_, Infallible>(Ok(()))
+            }));
+        let ingress_filter =
+            ServiceBuilder::new()
+                .concurrency_limit(1)
+                .service(BoxCloneService::new(service_fn(
+                    |_: (ProvisionalWhitelist, SignedIngressContent)| async {
+                        Ok::<Result<(), UserError>, Infallible>(Ok(()))
+                    },
+                )));
+        CallService::new_service(
+            no_op_logger(),
+            metrics,
+            subnet_id,
+            setup_registry(subnet_id, vec![(1, SubnetRecordBuilder::new().build())]),
+            validator_executor,
+            ingress_sender,
+            ingress_filter,
+            MaliciousFlags::default(),
+            StateReaderExecutor::new(Arc::new(state_manager)),
+            Arc::new(RwLock::new(None)),
+            Some(sync_call),
+        )
+    }
+
+    #[tokio::test]
+    async fn sync_call_returns_certificate_once_status_is_certified() {
+        let request = anonymous_call();
+        let message_id = SignedIngress::try_from(request.clone()).unwrap().id();
+        let completed = Arc::new(AtomicBool::new(false));
+        let (reads_sender, mut reads) = tokio::sync::mpsc::unbounded_channel();
+
+        let mut state_manager = MockStateManager::new();
+        let is_completed = Arc::clone(&completed);
+        state_manager
+            .expect_read_certified_state()
+            .returning(move |_labeled_tree| {
+                let mut state = ReplicatedStateBuilder::new().build();
+                if is_completed.load(Ordering::SeqCst) {
+                    state.set_ingress_status(
+                        message_id.clone(),
+                        IngressStatus::Known {
+                            receiver: canister_test_id(1).get(),
+                            user_id: user_test_id(0),
+                            time: UNIX_EPOCH,
+                            state: IngressState::Completed(WasmResult::Reply(vec![])),
+                        },
+                        NumBytes::from(u64::MAX),
+                    );
+                }
+                reads_sender.send(()).unwrap();
+                Some((
+                    Arc::new(state),
+                    MixedHashTree::Leaf(vec![]),
+                    certification(),
+                ))
+            });
+
+        let (certified_height, certified_height_receiver) = watch::channel(Height::from(0));
+        let mut service = sync_call_service(
+            state_manager,
+            SyncCallWaiters::new(Duration::from_secs(600), certified_height_receiver, 1),
+        );
+        let response = tokio::spawn(
+            service
+                .ready()
+                .await
+                .unwrap()
+                .call(Body::from(serde_cbor::to_vec(&request).unwrap())),
+        );
+
+        // The message is not executed yet at the first read, so the waiter
+        // must only read again once a new height is certified.
+        reads.recv().await.unwrap();
+        completed.store(true, Ordering::SeqCst);
+        certified_height.send(Height::from(1)).unwrap();
+
+        let response = response.await.unwrap().unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let response: HttpReadStateResponse = serde_cbor::from_slice(&body).unwrap();
+        let certificate: Certificate = serde_cbor::from_slice(&response.certificate.0).unwrap();
+        assert_eq!(certificate.tree, MixedHashTree::Leaf(vec![]));
+        assert!(reads.try_recv().is_ok());
+    }
+
+    #[tokio::test]
+    async fn sync_call_is_accepted_without_waiting_if_too_many_calls_wait() {
+        let mut state_manager = MockStateManager::new();
+        state_manager.expect_read_certified_state().never();
+
+        let (_certified_height, certified_height_receiver) = watch::channel(Height::from(0));
+        let mut service = sync_call_service(
+            state_manager,
+            SyncCallWaiters::new(Duration::from_secs(600), certified_height_receiver, 0),
+        );
+        let response = service
+            .ready()
+            .await
+            .unwrap()
+            .call(Body::from(serde_cbor::to_vec(&anonymous_call()).unwrap()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    }
+
+    #[test]
+    fn only_final_states_are_terminal() {
+        use ic_error_types::ErrorCode;
+
+        let status = |state| IngressStatus::Known {
+            receiver: canister_test_id(0).get(),
+            user_id: user_test_id(0),
+            time: UNIX_EPOCH,
+            state,
+        };
+        assert!(!is_terminal(&IngressStatus::Unknown));
+        assert!(!is_terminal(&status(IngressState::Received)));
+        assert!(!is_terminal(&status(IngressState::Processing)));
+        assert!(is_terminal(&status(IngressState::Completed(
+            WasmResult::Reply(vec![])
+        ))));
+        assert!(is_terminal(&status(IngressState::Failed(UserError::new(
+            ErrorCode::CanisterTrapped,
+            "trapped"
+        )))));
+        assert!(is_terminal(&status(IngressState::Done)));
+    }
 
     #[test]
     fn check_request_id() {
@@ -1,6 +1,10 @@
+use crate::rate_limiter::RETRY_AFTER_SECONDS;
 use crate::state_reader_executor::StateReaderExecutor;
 use crate::HttpError;
-use hyper::{Body, HeaderMap, Response, StatusCode};
+use hyper::{
+    header::{self, HeaderValue},
+    Body, HeaderMap, Response, StatusCode,
+};
 use ic_crypto_tree_hash::Path;
 use ic_crypto_tree_hash::{sparse_labeled_tree_from_paths, Label};
 use ic_error_types::UserError;
@@ -65,6 +69,17 @@ pub(crate) fn make_plaintext_response(status: StatusCode, message: String) -> Re
     resp
 }
 
+/// Converts an `HttpError` into a plaintext response, telling throttled
+/// clients when to retry.
+pub(crate) fn make_http_error_response(err: HttpError) -> Response<Body> {
+    let mut resp = make_plaintext_response(err.status, err.message);
+    if err.status == StatusCode::TOO_MANY_REQUESTS {
+        resp.headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(RETRY_AFTER_SECONDS));
+    }
+    resp
+}
+
 /// Converts a user error into an HTTP response.
 ///
 /// We need this conversion because we validate user requests twice:
@@ -13,6 +13,7 @@ mod dashboard;
 mod metrics;
 mod pprof;
 mod query;
+mod rate_limiter;
 mod read_state;
 mod state_reader_executor;
 mod status;
@@ -20,17 +21,21 @@ mod types;
 mod validator_executor;
 
 use crate::{
-    call::CallService,
+    call::{
+        watch_certified_height, CallService, SyncCallWaiters, MAX_CONCURRENT_SYNC_CALL_WAITERS,
+    },
     catch_up_package::CatchUpPackageService,
     common::{
-        get_cors_headers, get_root_public_key, make_plaintext_response, map_box_error_to_response,
+        get_cors_headers, get_root_public_key, make_http_error_response, make_plaintext_response,
+        map_box_error_to_response,
     },
     dashboard::DashboardService,
     metrics::{
         LABEL_REQUEST_TYPE, LABEL_STATUS, LABEL_TYPE, REQUESTS_LABEL_NAMES, REQUESTS_NUM_LABELS,
     },
     query::QueryService,
-    read_state::ReadStateService,
+    rate_limiter::RateLimiters,
+    read_state::{verify_subnet_id_in_path, ReadStateScope, ReadStateService},
     state_reader_executor::StateReaderExecutor,
     status::StatusService,
     types::*,
@@ -47,7 +52,7 @@ use ic_crypto_tree_hash::{lookup_path, LabeledTree, Path};
 use ic_crypto_utils_threshold_sig_der::parse_threshold_sig_key_from_der;
 use ic_interfaces::{
     consensus_pool::ConsensusPoolCache,
-    crypto::IngressSigVerifier,
+    crypto::{BasicSigner, IngressSigVerifier},
     execution_environment::{IngressFilterService, QueryExecutionService},
     registry::RegistryClient,
 };
@@ -62,17 +67,17 @@ use ic_types::{
     malicious_flags::MaliciousFlags,
     messages::{
         Blob, Certificate, CertificateDelegation, HttpReadState, HttpReadStateContent,
-        HttpReadStateResponse, HttpRequestEnvelope, ReplicaHealthStatus,
+        HttpReadStateResponse, HttpRequestEnvelope, QueryResponseHash, ReplicaHealthStatus,
     },
     time::current_time_and_expiry_time,
-    SubnetId,
+    NodeId, SubnetId,
 };
 use metrics::HttpHandlerMetrics;
 use rand::Rng;
 use std::{
     convert::TryFrom,
     io::{Error, Write},
-    net::SocketAddr,
+    net::{IpAddr, SocketAddr},
     path::PathBuf,
     sync::{Arc, RwLock},
     time::Duration,
@@ -157,13 +162,17 @@ pub(crate) type EndpointService = BoxCloneService<Body, Response<Body>, BoxError
 /// This is collection of thread-safe data members.
 #[derive(Clone)]
 struct HttpHandler {
+    subnet_id: SubnetId,
     registry_client: Arc<dyn RegistryClient>,
+    rate_limiters: Arc<RateLimiters>,
     call_service: EndpointService,
+    sync_call_service: EndpointService,
     query_service: EndpointService,
     catchup_service: EndpointService,
     dashboard_service: EndpointService,
     status_service: EndpointService,
     read_state_service: EndpointService,
+    subnet_read_state_service: EndpointService,
 }
 
 // Crates a detached tokio blocking task that initializes the server (reading
@@ -277,6 +286,8 @@ pub fn start_server(
     registry_client: Arc<dyn RegistryClient>,
     tls_handshake: Arc<dyn TlsHandshake + Send + Sync>,
     ingress_verifier: Arc<dyn IngressSigVerifier + Send + Sync>,
+    query_signer: Arc<dyn BasicSigner<QueryResponseHash> + Send + Sync>,
+    node_id: NodeId,
     subnet_id: SubnetId,
     nns_subnet_id: SubnetId,
     log: ReplicaLogger,
@@ -299,9 +310,24 @@ pub fn start_server(
         let delegation_from_nns = Arc::new(RwLock::new(None));
         let health_status = Arc::new(RwLock::new(ReplicaHealthStatus::Starting));
         let state_reader_executor = StateReaderExecutor::new(state_reader);
-        let validator_executor = ValidatorExecutor::new(ingress_verifier, log.clone());
+        let rate_limiters = Arc::new(RateLimiters::new(&config, metrics.clone()));
+        let validator_executor =
+            ValidatorExecutor::new(ingress_verifier, Arc::clone(&rate_limiters), log.clone());
 
         let call_service = CallService::new_service(
+            log.clone(),
+            metrics.clone(),
+            subnet_id,
+            Arc::clone(&registry_client),
+            validator_executor.clone(),
+            ingress_sender.clone(),
+            ingress_filter.clone(),
+            malicious_flags.clone(),
+            state_reader_executor.clone(),
+            Arc::clone(&delegation_from_nns),
+            None,
+        );
+        let sync_call_service = CallService::new_service(
             log.clone(),
             metrics.clone(),
             subnet_id,
@@ -310,6 +336,13 @@ pub fn start_server(
             ingress_sender,
             ingress_filter,
             malicious_flags.clone(),
+            state_reader_executor.clone(),
+            Arc::clone(&delegation_from_nns),
+            Some(SyncCallWaiters::new(
+                Duration::from_secs(config.ingress_message_certificate_timeout_seconds),
+                watch_certified_height(state_reader_executor.clone()),
+                MAX_CONCURRENT_SYNC_CALL_WAITERS,
+            )),
         );
         let query_service = QueryService::new_service(
             log.clone(),
@@ -320,9 +353,23 @@ pub fn start_server(
             Arc::clone(&registry_client),
             query_execution_service,
             malicious_flags.clone(),
+            node_id,
+            query_signer,
         );
         let read_state_service = ReadStateService::new_service(
             log.clone(),
+            ReadStateScope::Canister,
+            metrics.clone(),
+            Arc::clone(&health_status),
+            Arc::clone(&delegation_from_nns),
+            state_reader_executor.clone(),
+            validator_executor.clone(),
+            Arc::clone(&registry_client),
+            malicious_flags.clone(),
+        );
+        let subnet_read_state_service = ReadStateService::new_service(
+            log.clone(),
+            ReadStateScope::Subnet,
             metrics.clone(),
             Arc::clone(&health_status),
             Arc::clone(&delegation_from_nns),
@@ -361,13 +408,17 @@ pub fn start_server(
         );
 
         let http_handler = HttpHandler {
+            subnet_id,
             registry_client,
+            rate_limiters,
             call_service,
+            sync_call_service,
             query_service,
             status_service,
             catchup_service,
             dashboard_service,
             read_state_service,
+            subnet_read_state_service,
         };
 
         // If addr == 0, then a random port will be assigned. In this case it
@@ -465,12 +516,15 @@ fn create_main_service(
     metrics: HttpHandlerMetrics,
     http_handler: HttpHandler,
     app_layer: AppLayer,
+    peer_ip: Option<IpAddr>,
 ) -> BoxService<Request<Body>, Response<Body>, HttpError> {
     let metrics_for_map_request = metrics.clone();
     let route_service = service_fn(move |req: RequestWithTimer| {
         let metrics = metrics.clone();
         let http_handler = http_handler.clone();
-        async move { Ok::<_, HttpError>(make_router(metrics, http_handler, app_layer, req).await) }
+        async move {
+            Ok::<_, HttpError>(make_router(metrics, http_handler, app_layer, peer_ip, req).await)
+        }
     });
     BoxService::new(
         ServiceBuilder::new()
@@ -513,10 +567,11 @@ async fn serve_connection(
     metrics: HttpHandlerMetrics,
     connection_start_time: Instant,
 ) {
-    let service = create_main_service(metrics.clone(), http_handler.clone(), app_layer);
+    let peer_addr = tcp_stream.peer_addr();
+    let peer_ip = peer_addr.as_ref().ok().map(|addr| addr.ip());
+    let service = create_main_service(metrics.clone(), http_handler.clone(), app_layer, peer_ip);
     let connection_result = match app_layer {
         AppLayer::Https => {
-            let peer_addr = tcp_stream.peer_addr();
             let tls_stream = match tls_handshake
                 .perform_tls_server_handshake_without_client_auth(
                     tcp_stream,
@@ -581,14 +636,17 @@ async fn make_router(
     metrics: HttpHandlerMetrics,
     http_handler: HttpHandler,
     app_layer: AppLayer,
+    peer_ip: Option<IpAddr>,
     (req, mut timer): RequestWithTimer,
 ) -> ResponseWithTimer {
     let call_service = http_handler.call_service.clone();
+    let sync_call_service = http_handler.sync_call_service.clone();
     let query_service = http_handler.query_service.clone();
     let status_service = http_handler.status_service.clone();
     let catch_up_package_service = http_handler.catchup_service.clone();
     let dashboard_service = http_handler.dashboard_service.clone();
     let read_state_service = http_handler.read_state_service.clone();
+    let subnet_read_state_service = http_handler.subnet_read_state_service.clone();
 
     metrics
         .protocol_version_total
@@ -625,6 +683,10 @@ async fn make_router(
                     set_timer_labels(&mut timer, ApiReqType::Call);
                     call_service
                 }
+                ["", "api", "v3", "canister", _, "call"] => {
+                    set_timer_labels(&mut timer, ApiReqType::SyncCall);
+                    sync_call_service
+                }
                 ["", "api", "v2", "canister", _, "query"] => {
                     set_timer_labels(&mut timer, ApiReqType::Query);
                     query_service
@@ -633,6 +695,13 @@ async fn make_router(
                     set_timer_labels(&mut timer, ApiReqType::ReadState);
                     read_state_service
                 }
+                ["", "api", "v2", "subnet", subnet_id, "read_state"] => {
+                    set_timer_labels(&mut timer, ApiReqType::ReadStateSubnet);
+                    if let Err(err) = verify_subnet_id_in_path(subnet_id, http_handler.subnet_id) {
+                        return (make_http_error_response(err), timer);
+                    }
+                    subnet_read_state_service
+                }
                 ["", "_", "catch_up_package"] => {
                     set_timer_labels(&mut timer, ApiReqType::CatchUpPackage);
                     catch_up_package_service
@@ -703,6 +772,17 @@ async fn make_router(
             );
         }
     };
+
+    // Throttle clients sending too many API requests before receiving the
+    // request body and validating signatures.
+    if let Some(peer_ip) = peer_ip {
+        if req.uri().path().starts_with("/api/") {
+            if let Err(err) = http_handler.rate_limiters.check_client_ip(peer_ip) {
+                return (make_http_error_response(err), timer);
+            }
+        }
+    }
+
     (
         LoadShed::new(svc)
             .ready()
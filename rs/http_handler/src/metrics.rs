@@ -7,6 +7,7 @@ use prometheus::{HistogramVec, IntCounter, IntCounterVec, IntGauge};
 use tokio::time::Instant;
 
 pub const LABEL_DETAIL: &str = "detail";
+pub const LABEL_KEY: &str = "key";
 pub const LABEL_PROTOCOL: &str = "protocol";
 pub const LABEL_REQUEST_TYPE: &str = "request_type";
 pub const LABEL_STATUS: &str = "status";
@@ -29,6 +30,7 @@ pub(crate) struct HttpHandlerMetrics {
     pub(crate) protocol_version_total: IntCounterVec,
     pub(crate) connections: IntGauge,
     pub(crate) connections_total: IntCounter,
+    pub(crate) throttled_requests_total: IntCounterVec,
     connection_setup_duration: HistogramVec,
     connection_duration: HistogramVec,
 }
@@ -80,6 +82,11 @@ impl HttpHandlerMetrics {
                 "replica_http_tcp_connections_total",
                 "Total number of accepted TCP connections."
             ),
+            throttled_requests_total: metrics_registry.int_counter_vec(
+                "replica_http_throttled_requests_total",
+                "Number of requests rejected by the rate limits, by the key they were throttled by (sender, canister, client IP).",
+                &[LABEL_KEY],
+            ),
             connection_setup_duration: metrics_registry.histogram_vec(
                 "replica_http_connection_setup_duration_seconds",
                 "HTTP connection setup durations, by status and detail (protocol on status=\"success\", error type on status=\"error\").",
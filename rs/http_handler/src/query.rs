@@ -2,21 +2,24 @@
 
 use crate::{
     body::BodyReceiverLayer,
-    common::{cbor_response, make_plaintext_response},
+    common::{cbor_response, make_http_error_response, make_plaintext_response},
     types::{to_legacy_request_type, ApiReqType},
     validator_executor::ValidatorExecutor,
     EndpointService, HttpHandlerMetrics, ReplicaHealthStatus, UNKNOWN_LABEL,
 };
-use futures_util::FutureExt;
 use hyper::{Body, Response, StatusCode};
-use ic_interfaces::{execution_environment::QueryExecutionService, registry::RegistryClient};
-use ic_logger::{trace, ReplicaLogger};
+use ic_interfaces::{
+    crypto::BasicSigner, execution_environment::QueryExecutionService, registry::RegistryClient,
+};
+use ic_logger::{error, trace, ReplicaLogger};
 use ic_types::{
     malicious_flags::MaliciousFlags,
     messages::{
-        CertificateDelegation, HttpQueryContent, HttpRequest, HttpRequestEnvelope,
-        SignedRequestBytes, UserQuery,
+        Blob, CertificateDelegation, HttpQueryContent, HttpRequest, HttpRequestEnvelope,
+        HttpSignedQueryResponse, NodeSignature, QueryResponseHash, SignedRequestBytes, UserQuery,
     },
+    time::current_time,
+    NodeId,
 };
 use std::convert::{Infallible, TryFrom};
 use std::future::Future;
@@ -35,6 +38,8 @@ pub(crate) struct QueryService {
     registry_client: Arc<dyn RegistryClient>,
     query_execution_service: QueryExecutionService,
     malicious_flags: MaliciousFlags,
+    node_id: NodeId,
+    query_signer: Arc<dyn BasicSigner<QueryResponseHash> + Send + Sync>,
 }
 
 impl QueryService {
@@ -48,6 +53,8 @@ impl QueryService {
         registry_client: Arc<dyn RegistryClient>,
         query_execution_service: QueryExecutionService,
         malicious_flags: MaliciousFlags,
+        node_id: NodeId,
+        query_signer: Arc<dyn BasicSigner<QueryResponseHash> + Send + Sync>,
     ) -> EndpointService {
         let base_service = BoxCloneService::new(ServiceBuilder::new().service(Self {
             log,
@@ -58,6 +65,8 @@ impl QueryService {
             registry_client,
             query_execution_service,
             malicious_flags,
+            node_id,
+            query_signer,
         }));
         BoxCloneService::new(
             ServiceBuilder::new()
@@ -150,9 +159,17 @@ impl Service<Vec<u8>> for QueryService {
         let registry_client = self.registry_client.get_latest_version();
         let malicious_flags = self.malicious_flags.clone();
         let validator_executor = self.validator_executor.clone();
+        let log = self.log.clone();
+        let node_id = self.node_id;
+        let query_signer = Arc::clone(&self.query_signer);
         Box::pin(async move {
             match validator_executor
-                .get_authorized_canisters(&request, registry_client, &malicious_flags)
+                .get_authorized_canisters(
+                    &request,
+                    Some(request.content().receiver),
+                    registry_client,
+                    &malicious_flags,
+                )
                 .await
             {
                 Ok(targets) => {
@@ -162,17 +179,48 @@ impl Service<Vec<u8>> for QueryService {
                     }
                 }
                 Err(http_err) => {
-                    let res = make_plaintext_response(http_err.status, http_err.message);
+                    let res = make_http_error_response(http_err);
                     return Ok(res);
                 }
             };
-            old_query_execution_service
+            let request_id = request.id();
+            let response = old_query_execution_service
                 .call((request.take_content(), delegation_from_nns))
-                .map(|result| {
-                    let v = result?;
-                    Ok(cbor_response(&v))
-                })
-                .await
+                .await?;
+
+            // Sign the response so that clients can hold this replica
+            // accountable for it. Signing may block on the crypto vault.
+            let timestamp = current_time();
+            let hash = QueryResponseHash::new(&response, &request_id, timestamp);
+            let signature = tokio::task::spawn_blocking(move || {
+                query_signer.sign_basic(&hash, node_id, registry_client)
+            })
+            .await;
+            let signature = match signature {
+                Ok(Ok(signature)) => signature,
+                Ok(Err(err)) => {
+                    error!(log, "Failed to sign query response: {}", err);
+                    return Ok(make_plaintext_response(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "Failed to sign the query response.".to_string(),
+                    ));
+                }
+                Err(err) => {
+                    error!(log, "Query response signing task failed: {}", err);
+                    return Ok(make_plaintext_response(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "Failed to sign the query response.".to_string(),
+                    ));
+                }
+            };
+            Ok(cbor_response(&HttpSignedQueryResponse {
+                response,
+                signatures: vec![NodeSignature {
+                    timestamp: timestamp.as_nanos_since_unix_epoch(),
+                    signature: Blob(signature.get().0),
+                    identity: Blob(node_id.get().into_vec()),
+                }],
+            }))
         })
     }
 }
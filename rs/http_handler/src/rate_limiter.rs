@@ -0,0 +1,328 @@
+//! Token bucket rate limiting of API requests, keyed by sender principal,
+//! target canister and client IP address.
+use crate::{metrics::HttpHandlerMetrics, HttpError};
+use hyper::StatusCode;
+use ic_config::http_handler::{Config, RateLimitConfig};
+use ic_types::{CanisterId, UserId};
+use lru::LruCache;
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::Instant;
+use strum::IntoStaticStr;
+
+/// Upper bound on the number of keys tracked by a single limiter. When it is
+/// reached, the bucket of the least recently seen key is dropped.
+const MAX_TRACKED_KEYS: usize = 100_000;
+
+/// The number of seconds clients are asked to wait before retrying a
+/// throttled request. Rates are whole requests per second, so a new token is
+/// always available within this time.
+pub(crate) const RETRY_AFTER_SECONDS: u64 = 1;
+
+/// The key a request was throttled by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+pub(crate) enum RateLimitKey {
+    Sender,
+    Canister,
+    ClientIp,
+}
+
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// A set of token buckets, one per key, sharing the same limit.
+struct TokenBucketLimiter<K> {
+    config: RateLimitConfig,
+    buckets: Mutex<LruCache<K, TokenBucket>>,
+}
+
+impl<K: Hash + Eq + Clone> TokenBucketLimiter<K> {
+    fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(LruCache::new(MAX_TRACKED_KEYS)),
+        }
+    }
+
+    /// Returns the tokens `bucket` holds at `now`. Keys without a bucket yet
+    /// have a full one.
+    fn tokens(&self, bucket: Option<&TokenBucket>, now: Instant) -> f64 {
+        let rate = self.config.requests_per_second as f64;
+        let capacity = self.config.burst_size as f64;
+        bucket.map_or(capacity, |bucket| {
+            let elapsed = now.saturating_duration_since(bucket.last_refill);
+            (bucket.tokens + elapsed.as_secs_f64() * rate).min(capacity)
+        })
+    }
+
+    /// Takes a token from the bucket of `key`, which must have one.
+    fn take_token(&self, buckets: &mut LruCache<K, TokenBucket>, key: K, now: Instant) {
+        let tokens = self.tokens(buckets.get(&key), now);
+        debug_assert!(tokens >= 1.0);
+        // Evicts the least recently used bucket if the limiter is full.
+        buckets.put(
+            key,
+            TokenBucket {
+                tokens: tokens - 1.0,
+                last_refill: now,
+            },
+        );
+    }
+
+    /// Takes a token from the bucket of `key`, returning `false` if the
+    /// bucket is empty.
+    fn try_acquire(&self, key: K, now: Instant) -> bool {
+        let mut buckets = self.buckets.lock().unwrap();
+        if self.tokens(buckets.get(&key), now) < 1.0 {
+            return false;
+        }
+        self.take_token(&mut buckets, key, now);
+        true
+    }
+}
+
+/// The rate limiters configured for the HTTP handler. Limits that are not
+/// configured admit all requests.
+pub(crate) struct RateLimiters {
+    sender: Option<TokenBucketLimiter<UserId>>,
+    canister: Option<TokenBucketLimiter<CanisterId>>,
+    client_ip: Option<TokenBucketLimiter<IpAddr>>,
+    metrics: HttpHandlerMetrics,
+}
+
+impl RateLimiters {
+    pub(crate) fn new(config: &Config, metrics: HttpHandlerMetrics) -> Self {
+        Self {
+            sender: config.sender_rate_limit.map(TokenBucketLimiter::new),
+            canister: config.canister_rate_limit.map(TokenBucketLimiter::new),
+            client_ip: config.ip_rate_limit.map(TokenBucketLimiter::new),
+            metrics,
+        }
+    }
+
+    /// Rejects a request if the bucket of its sender or, if known, of the
+    /// canister it targets is empty, without taking a token from either. This
+    /// is meant for requests that are not authenticated yet.
+    pub(crate) fn peek_request(
+        &self,
+        sender: UserId,
+        canister_id: Option<CanisterId>,
+    ) -> Result<(), HttpError> {
+        self.limit_request(sender, canister_id, false)
+    }
+
+    /// Checks the limits of the sender of a request and, if known, of the
+    /// canister it targets, and takes a token from both buckets if neither is
+    /// empty.
+    pub(crate) fn check_request(
+        &self,
+        sender: UserId,
+        canister_id: Option<CanisterId>,
+    ) -> Result<(), HttpError> {
+        self.limit_request(sender, canister_id, true)
+    }
+
+    fn limit_request(
+        &self,
+        sender: UserId,
+        canister_id: Option<CanisterId>,
+        take_tokens: bool,
+    ) -> Result<(), HttpError> {
+        let now = Instant::now();
+        // Both limiters stay locked until the tokens are taken, so that a
+        // token is never taken from one bucket when the other one is empty.
+        // They are always locked in this order.
+        let mut sender_limiter = self
+            .sender
+            .as_ref()
+            .map(|limiter| (limiter, limiter.buckets.lock().unwrap()));
+        let mut canister_limiter = match (&self.canister, canister_id) {
+            (Some(limiter), Some(canister_id)) => {
+                Some((limiter, canister_id, limiter.buckets.lock().unwrap()))
+            }
+            _ => None,
+        };
+
+        if let Some((limiter, buckets)) = &sender_limiter {
+            if limiter.tokens(buckets.peek(&sender), now) < 1.0 {
+                return Err(self.throttled(RateLimitKey::Sender));
+            }
+        }
+        if let Some((limiter, canister_id, buckets)) = &canister_limiter {
+            if limiter.tokens(buckets.peek(canister_id), now) < 1.0 {
+                return Err(self.throttled(RateLimitKey::Canister));
+            }
+        }
+
+        if take_tokens {
+            if let Some((limiter, buckets)) = &mut sender_limiter {
+                limiter.take_token(buckets, sender, now);
+            }
+            if let Some((limiter, canister_id, buckets)) = &mut canister_limiter {
+                limiter.take_token(buckets, *canister_id, now);
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the limit of the client IP address a request was received from.
+    pub(crate) fn check_client_ip(&self, ip: IpAddr) -> Result<(), HttpError> {
+        match &self.client_ip {
+            Some(limiter) if !limiter.try_acquire(ip, Instant::now()) => {
+                Err(self.throttled(RateLimitKey::ClientIp))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn throttled(&self, key: RateLimitKey) -> HttpError {
+        self.metrics
+            .throttled_requests_total
+            .with_label_values(&[key.into()])
+            .inc();
+        let key: &'static str = key.into();
+        HttpError {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            message: format!(
+                "Too many requests for this {}. Retry after {} second(s).",
+                key.replace('_', " "),
+                RETRY_AFTER_SECONDS
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_metrics::MetricsRegistry;
+    use ic_test_utilities::types::ids::{canister_test_id, user_test_id};
+    use std::time::Duration;
+
+    fn limit(requests_per_second: u32, burst_size: u32) -> Option<RateLimitConfig> {
+        Some(RateLimitConfig {
+            requests_per_second,
+            burst_size,
+        })
+    }
+
+    #[test]
+    fn token_bucket_admits_bursts_and_refills() {
+        let limiter = TokenBucketLimiter::new(limit(2, 3).unwrap());
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.try_acquire(1, start));
+        }
+        assert!(!limiter.try_acquire(1, start));
+        // Other keys have their own bucket.
+        assert!(limiter.try_acquire(2, start));
+
+        // Two tokens are added per second.
+        let later = start + Duration::from_millis(500);
+        assert!(limiter.try_acquire(1, later));
+        assert!(!limiter.try_acquire(1, later));
+
+        // The bucket never holds more than the burst size.
+        let much_later = start + Duration::from_secs(60);
+        for _ in 0..3 {
+            assert!(limiter.try_acquire(1, much_later));
+        }
+        assert!(!limiter.try_acquire(1, much_later));
+    }
+
+    #[test]
+    fn token_bucket_evicts_least_recently_used_keys() {
+        let limiter = TokenBucketLimiter::new(limit(1, 1).unwrap());
+        let now = Instant::now();
+
+        for key in 0..MAX_TRACKED_KEYS {
+            assert!(limiter.try_acquire(key, now));
+        }
+        // Keys that keep sending requests stay throttled when new keys arrive.
+        assert!(!limiter.try_acquire(0, now));
+        assert!(limiter.try_acquire(MAX_TRACKED_KEYS, now));
+        assert!(!limiter.try_acquire(0, now));
+        // The bucket of the least recently seen key was dropped.
+        assert!(limiter.try_acquire(1, now));
+    }
+
+    #[test]
+    fn requests_are_throttled_by_sender_and_canister() {
+        let config = Config {
+            sender_rate_limit: limit(1, 1),
+            canister_rate_limit: limit(1, 2),
+            ..Default::default()
+        };
+        let metrics = HttpHandlerMetrics::new(&MetricsRegistry::new());
+        let limiters = RateLimiters::new(&config, metrics.clone());
+
+        let canister_id = Some(canister_test_id(1));
+        assert_eq!(limiters.check_request(user_test_id(1), canister_id), Ok(()));
+        assert_eq!(
+            limiters
+                .check_request(user_test_id(1), canister_id)
+                .unwrap_err()
+                .status,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(limiters.check_request(user_test_id(2), canister_id), Ok(()));
+        assert_eq!(
+            limiters
+                .check_request(user_test_id(3), canister_id)
+                .unwrap_err()
+                .status,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        // No token was taken from the sender, as the canister was throttled.
+        assert_eq!(limiters.check_request(user_test_id(3), None), Ok(()));
+        // Requests without a target canister are only limited by sender.
+        assert_eq!(limiters.check_request(user_test_id(4), None), Ok(()));
+        // The client IP limit is not configured.
+        assert_eq!(limiters.check_client_ip([127, 0, 0, 1].into()), Ok(()));
+
+        let throttled = |key: RateLimitKey| {
+            metrics
+                .throttled_requests_total
+                .with_label_values(&[key.into()])
+                .get()
+        };
+        assert_eq!(throttled(RateLimitKey::Sender), 1);
+        assert_eq!(throttled(RateLimitKey::Canister), 1);
+        assert_eq!(throttled(RateLimitKey::ClientIp), 0);
+    }
+
+    #[test]
+    fn peeking_does_not_take_tokens() {
+        let config = Config {
+            sender_rate_limit: limit(1, 1),
+            canister_rate_limit: limit(1, 1),
+            ..Default::default()
+        };
+        let limiters = RateLimiters::new(&config, HttpHandlerMetrics::new(&MetricsRegistry::new()));
+
+        let canister_id = Some(canister_test_id(1));
+        for _ in 0..3 {
+            assert_eq!(limiters.peek_request(user_test_id(1), canister_id), Ok(()));
+        }
+        assert_eq!(limiters.check_request(user_test_id(1), canister_id), Ok(()));
+        assert_eq!(
+            limiters
+                .peek_request(user_test_id(1), None)
+                .unwrap_err()
+                .status,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(
+            limiters
+                .peek_request(user_test_id(2), canister_id)
+                .unwrap_err()
+                .status,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+}
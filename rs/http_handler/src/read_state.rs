@@ -1,8 +1,9 @@
-//! Module that deals with requests to /api/v2/canister/.../read_state
+//! Module that deals with requests to /api/v2/canister/.../read_state and
+//! /api/v2/subnet/.../read_state
 
 use crate::{
     body::BodyReceiverLayer,
-    common::{cbor_response, into_cbor, make_plaintext_response},
+    common::{cbor_response, into_cbor, make_http_error_response, make_plaintext_response},
     state_reader_executor::StateReaderExecutor,
     types::{to_legacy_request_type, ApiReqType},
     validator_executor::ValidatorExecutor,
@@ -20,12 +21,13 @@ use ic_types::{
         HttpRequest, HttpRequestEnvelope, MessageId, ReadState, SignedRequestBytes,
         EXPECTED_MESSAGE_ID_LENGTH,
     },
-    CanisterId, UserId,
+    CanisterId, PrincipalId, SubnetId, UserId,
 };
 use ic_validator::CanisterIdSet;
 use std::convert::{Infallible, TryFrom};
 use std::future::Future;
 use std::pin::Pin;
+use std::str::FromStr;
 use std::sync::{Arc, RwLock};
 use std::task::{Context, Poll};
 use tower::{
@@ -35,9 +37,29 @@ use tower::{
 const MAX_READ_STATE_REQUEST_IDS: u8 = 100;
 const MAX_READ_STATE_CONCURRENT_REQUESTS: usize = 100;
 
+/// The endpoint a `read_state` request was sent to, which determines the
+/// paths that may be read.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum ReadStateScope {
+    /// `/api/v2/canister/<canister_id>/read_state`
+    Canister,
+    /// `/api/v2/subnet/<subnet_id>/read_state`
+    Subnet,
+}
+
+impl ReadStateScope {
+    fn api_req_type(self) -> ApiReqType {
+        match self {
+            ReadStateScope::Canister => ApiReqType::ReadState,
+            ReadStateScope::Subnet => ApiReqType::ReadStateSubnet,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub(crate) struct ReadStateService {
     log: ReplicaLogger,
+    scope: ReadStateScope,
     metrics: HttpHandlerMetrics,
     health_status: Arc<RwLock<ReplicaHealthStatus>>,
     delegation_from_nns: Arc<RwLock<Option<CertificateDelegation>>>,
@@ -51,6 +73,7 @@ impl ReadStateService {
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn new_service(
         log: ReplicaLogger,
+        scope: ReadStateScope,
         metrics: HttpHandlerMetrics,
         health_status: Arc<RwLock<ReplicaHealthStatus>>,
         delegation_from_nns: Arc<RwLock<Option<CertificateDelegation>>>,
@@ -61,6 +84,7 @@ impl ReadStateService {
     ) -> EndpointService {
         let base_service = Self {
             log,
+            scope,
             metrics,
             health_status,
             delegation_from_nns,
@@ -95,12 +119,13 @@ impl Service<Vec<u8>> for ReadStateService {
     }
 
     fn call(&mut self, body: Vec<u8>) -> Self::Future {
-        trace!(self.log, "in handle read_state");
+        trace!(self.log, "in handle read_state ({:?})", self.scope);
+        let api_req_type = self.scope.api_req_type();
         self.metrics
             .requests_body_size_bytes
             .with_label_values(&[
-                to_legacy_request_type(ApiReqType::ReadState),
-                ApiReqType::ReadState.into(),
+                to_legacy_request_type(api_req_type),
+                api_req_type.into(),
                 UNKNOWN_LABEL,
             ])
             .observe(body.len() as f64);
@@ -150,26 +175,32 @@ impl Service<Vec<u8>> for ReadStateService {
         let malicious_flags = self.malicious_flags.clone();
         let state_reader_executor = self.state_reader_executor.clone();
         let validator_executor = self.validator_executor.clone();
+        let scope = self.scope;
         Box::pin(async move {
             let targets = match validator_executor
-                .get_authorized_canisters(&request, registry_client, &malicious_flags)
+                .get_authorized_canisters(&request, None, registry_client, &malicious_flags)
                 .await
             {
                 Ok(targets) => targets,
                 Err(http_err) => {
-                    let res = make_plaintext_response(http_err.status, http_err.message);
+                    let res = make_http_error_response(http_err);
                     return Ok(res);
                 }
             };
             // Verify authorization for requested paths.
-            if let Err(HttpError { status, message }) = verify_paths(
-                &state_reader_executor,
-                &read_state.source,
-                &read_state.paths,
-                &targets,
-            )
-            .await
-            {
+            let verified = match scope {
+                ReadStateScope::Canister => {
+                    verify_paths(
+                        &state_reader_executor,
+                        &read_state.source,
+                        &read_state.paths,
+                        &targets,
+                    )
+                    .await
+                }
+                ReadStateScope::Subnet => verify_subnet_paths(&read_state.paths),
+            };
+            if let Err(HttpError { status, message }) = verified {
                 return Ok(make_plaintext_response(status, message));
             }
 
@@ -247,6 +278,9 @@ async fn verify_paths(
             [b"subnet"] => {}
             [b"subnet", _subnet_id, b"public_key"] => {}
             [b"subnet", _subnet_id, b"canister_ranges"] => {}
+            [b"subnet", _subnet_id, b"node"] => {}
+            [b"subnet", _subnet_id, b"node", _node_id] => {}
+            [b"subnet", _subnet_id, b"node", _node_id, b"public_key"] => {}
             [b"request_status", request_id] | [b"request_status", request_id, ..] => {
                 num_request_ids += 1;
 
@@ -299,6 +333,58 @@ async fn verify_paths(
     Ok(())
 }
 
+// Verifies that the `paths` requested from the subnet endpoint only refer to
+// subnet information, which is public.
+fn verify_subnet_paths(paths: &[Path]) -> Result<(), HttpError> {
+    for path in paths {
+        let path: Vec<&[u8]> = path.iter().map(|label| label.as_bytes()).collect();
+        match path.as_slice() {
+            [b"time"] => {}
+            [b"subnet"] => {}
+            [b"subnet", _subnet_id] => {}
+            [b"subnet", _subnet_id, b"public_key"] => {}
+            [b"subnet", _subnet_id, b"canister_ranges"] => {}
+            [b"subnet", _subnet_id, b"metrics"] => {}
+            [b"subnet", _subnet_id, b"node"] => {}
+            [b"subnet", _subnet_id, b"node", _node_id] => {}
+            [b"subnet", _subnet_id, b"node", _node_id, b"public_key"] => {}
+            _ => {
+                // Canister and request status paths must be read through the
+                // canister endpoint; all other paths are unsupported.
+                return Err(HttpError {
+                    status: StatusCode::NOT_FOUND,
+                    message: "Invalid path requested.".to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Verifies that `subnet_id`, taken from the path of a request to the subnet
+/// endpoint, is the id of the subnet of this replica. A replica can only
+/// certify the state of its own subnet.
+pub(crate) fn verify_subnet_id_in_path(
+    subnet_id: &str,
+    own_subnet_id: SubnetId,
+) -> Result<(), HttpError> {
+    let subnet_id = PrincipalId::from_str(subnet_id).map_err(|err| HttpError {
+        status: StatusCode::BAD_REQUEST,
+        message: format!("Could not parse subnet id {}: {}", subnet_id, err),
+    })?;
+    if SubnetId::from(subnet_id) != own_subnet_id {
+        return Err(HttpError {
+            status: StatusCode::NOT_FOUND,
+            message: format!(
+                "Subnet {} is not served by this replica, which belongs to subnet {}.",
+                subnet_id, own_subnet_id
+            ),
+        });
+    }
+    Ok(())
+}
+
 fn can_read_canister_metadata(
     user: &UserId,
     canister_id: &CanisterId,
@@ -350,7 +436,9 @@ fn can_read_canister_metadata(
 mod test {
     use crate::{
         common::test::{array, assert_cbor_ser_equal, bytes, int},
-        read_state::{can_read_canister_metadata, verify_paths},
+        read_state::{
+            can_read_canister_metadata, verify_paths, verify_subnet_id_in_path, verify_subnet_paths,
+        },
         state_reader_executor::StateReaderExecutor,
         HttpError,
     };
@@ -363,7 +451,7 @@ mod test {
         mock_time,
         state::insert_dummy_canister,
         state_manager::MockStateManager,
-        types::ids::{canister_test_id, subnet_test_id, user_test_id},
+        types::ids::{canister_test_id, node_test_id, subnet_test_id, user_test_id},
     };
     use ic_types::Height;
     use ic_validator::CanisterIdSet;
@@ -522,5 +610,87 @@ mod test {
             .await,
             Ok(())
         );
+        assert_eq!(
+            verify_paths(
+                &sre,
+                &user_test_id(1),
+                &[Path::new(vec![
+                    Label::from("subnet"),
+                    Label::from(subnet_id.get().into_vec()),
+                    Label::from("node"),
+                    Label::from(node_test_id(1).get().into_vec()),
+                    Label::from("public_key"),
+                ])],
+                &CanisterIdSet::All
+            )
+            .await,
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn subnet_paths_are_restricted_to_subnet_information() {
+        let subnet_id = subnet_test_id(1);
+        let subnet_path = |labels: &[&[u8]]| {
+            let mut path = vec![
+                Label::from("subnet"),
+                Label::from(subnet_id.get().into_vec()),
+            ];
+            path.extend(labels.iter().map(|label| Label::from(label.to_vec())));
+            Path::new(path)
+        };
+
+        assert_eq!(
+            verify_subnet_paths(&[
+                Path::from(Label::from("time")),
+                Path::from(Label::from("subnet")),
+                subnet_path(&[b"public_key"]),
+                subnet_path(&[b"canister_ranges"]),
+                subnet_path(&[b"metrics"]),
+                subnet_path(&[b"node"]),
+                subnet_path(&[b"node", node_test_id(1).get().as_slice(), b"public_key"]),
+            ]),
+            Ok(())
+        );
+
+        let canister_id = canister_test_id(1).get().into_vec();
+        for path in [
+            Path::new(vec![
+                Label::from("canister"),
+                Label::from(canister_id),
+                Label::from("module_hash"),
+            ]),
+            Path::new(vec![
+                Label::from("request_status"),
+                Label::from(vec![0; 32]),
+            ]),
+            subnet_path(&[b"unknown"]),
+        ] {
+            assert_eq!(
+                verify_subnet_paths(&[path]),
+                Err(HttpError {
+                    status: StatusCode::NOT_FOUND,
+                    message: "Invalid path requested.".to_string(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn only_the_own_subnet_id_is_accepted_in_the_path() {
+        let own_subnet_id = subnet_test_id(1);
+        assert_eq!(
+            verify_subnet_id_in_path(&own_subnet_id.to_string(), own_subnet_id),
+            Ok(())
+        );
+        assert_eq!(
+            verify_subnet_id_in_path(&subnet_test_id(2).to_string(), own_subnet_id)
+                .map_err(|err| err.status),
+            Err(StatusCode::NOT_FOUND)
+        );
+        assert_eq!(
+            verify_subnet_id_in_path("not-a-subnet-id", own_subnet_id).map_err(|err| err.status),
+            Err(StatusCode::BAD_REQUEST)
+        );
     }
 }
@@ -6,7 +6,7 @@ use hyper::StatusCode;
 use ic_crypto_tree_hash::{LabeledTree, MixedHashTree};
 use ic_interfaces_state_manager::{Labeled, StateReader};
 use ic_replicated_state::ReplicatedState;
-use ic_types::consensus::certification::Certification;
+use ic_types::{consensus::certification::Certification, Height};
 use std::sync::{Arc, Mutex};
 use threadpool::ThreadPool;
 use tokio::sync::oneshot;
@@ -43,6 +43,21 @@ impl StateReaderExecutor {
         })
     }
 
+    pub async fn get_latest_certified_height(&self) -> Result<Height, HttpError> {
+        let (tx, rx) = oneshot::channel();
+        let state = self.state_reader.clone();
+        self.threadpool.lock().unwrap().execute(move || {
+            if !tx.is_closed() {
+                let _ = tx.send(state.latest_certified_height());
+            }
+        });
+
+        rx.await.map_err(|e| HttpError {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            message: format!("Internal Error: {}.", e),
+        })
+    }
+
     pub async fn read_certified_state(
         &self,
         labeled_tree: &LabeledTree<()>,
@@ -114,6 +129,20 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn async_get_latest_certified_height() {
+        let mut mock_state_manager = MockStateManager::new();
+        mock_state_manager
+            .expect_latest_certified_height()
+            .returning(|| Height::from(17));
+
+        let sre = StateReaderExecutor::new(Arc::new(mock_state_manager));
+        assert_eq!(
+            sre.get_latest_certified_height().await.unwrap(),
+            Height::from(17)
+        );
+    }
+
     #[tokio::test]
     async fn async_read_certified_state_none() {
         let mut mock_state_manager = MockStateManager::new();
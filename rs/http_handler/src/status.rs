@@ -80,12 +80,17 @@ impl Service<Body> for StatusService {
             } else {
                 None
             };
+            let certified_height = state_reader_executor
+                .get_latest_certified_height()
+                .await
+                .ok();
             let response = HttpStatusResponse {
                 ic_api_version: IC_API_VERSION.to_string(),
                 root_key,
                 impl_version: Some(ReplicaVersion::default().to_string()),
                 impl_hash: REPLICA_BINARY_HASH.get().map(|s| s.to_string()),
                 replica_health_status: Some(replica_health_status),
+                certified_height,
             };
 
             Ok(common::cbor_response(&response))
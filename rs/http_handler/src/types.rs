@@ -10,10 +10,14 @@ use strum::IntoStaticStr;
 pub(crate) enum ApiReqType {
     /// `call`
     Call,
+    /// `call` on the synchronous (v3) endpoint
+    SyncCall,
     /// `query`
     Query,
     /// `read_state`
     ReadState,
+    /// `read_state` on the subnet endpoint
+    ReadStateSubnet,
     /// In case an error occurred and the request type is unknown.
     CatchUpPackage,
     Status,
@@ -57,8 +61,13 @@ mod tests {
     fn test_label_values_do_not_change() {
         type StaticStr = &'static str;
         assert_eq!(StaticStr::from(ApiReqType::Call), "call");
+        assert_eq!(StaticStr::from(ApiReqType::SyncCall), "sync_call");
         assert_eq!(StaticStr::from(ApiReqType::Query), "query");
         assert_eq!(StaticStr::from(ApiReqType::ReadState), "read_state");
+        assert_eq!(
+            StaticStr::from(ApiReqType::ReadStateSubnet),
+            "read_state_subnet"
+        );
         assert_eq!(StaticStr::from(ApiReqType::Status), "status");
         assert_eq!(
             StaticStr::from(ApiReqType::CatchUpPackage),
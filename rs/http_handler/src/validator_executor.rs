@@ -1,5 +1,5 @@
 // The valiadator executor provides non blocking access to the crypto services needed in the http handler.
-use crate::{common::validation_error_to_http_error, HttpError};
+use crate::{common::validation_error_to_http_error, rate_limiter::RateLimiters, HttpError};
 use http::StatusCode;
 use ic_interfaces::crypto::IngressSigVerifier;
 use ic_logger::{debug, ReplicaLogger};
@@ -7,7 +7,7 @@ use ic_types::{
     malicious_flags::MaliciousFlags,
     messages::{HttpRequest, HttpRequestContent, SignedIngress},
     time::current_time,
-    RegistryVersion,
+    CanisterId, RegistryVersion,
 };
 use ic_validator::{get_authorized_canisters, validate_request, CanisterIdSet};
 use std::sync::{Arc, Mutex};
@@ -21,17 +21,20 @@ const VALIDATOR_EXECUTOR_THREADS: usize = 1;
 pub(crate) struct ValidatorExecutor {
     validator: Arc<dyn IngressSigVerifier + Send + Sync>,
     threadpool: Arc<Mutex<ThreadPool>>,
+    rate_limiters: Arc<RateLimiters>,
     logger: ReplicaLogger,
 }
 
 impl ValidatorExecutor {
     pub fn new(
         validator: Arc<dyn IngressSigVerifier + Send + Sync>,
+        rate_limiters: Arc<RateLimiters>,
         logger: ReplicaLogger,
     ) -> Self {
         ValidatorExecutor {
             validator,
             threadpool: Arc::new(Mutex::new(ThreadPool::new(VALIDATOR_EXECUTOR_THREADS))),
+            rate_limiters,
             logger,
         }
     }
@@ -42,6 +45,11 @@ impl ValidatorExecutor {
         registry_version: RegistryVersion,
         malicious_flags: &MaliciousFlags,
     ) -> Result<(), HttpError> {
+        // Requests whose limits are exhausted are rejected before spending
+        // time on validating them. Tokens are only taken once the sender is
+        // authenticated.
+        self.rate_limiters
+            .peek_request(request.sender(), Some(request.canister_id()))?;
         let (tx, rx) = oneshot::channel();
 
         let r = request.clone();
@@ -66,15 +74,25 @@ impl ValidatorExecutor {
             .map_err(|val_err| {
                 debug!(self.logger, "Failed to validate request: {}", val_err);
                 validation_error_to_http_error(request.id(), val_err, &self.logger)
-            })
+            })?;
+        // Only take tokens for authenticated requests, so that requests with
+        // forged senders cannot exhaust the limits of others.
+        self.rate_limiters
+            .check_request(request.sender(), Some(request.canister_id()))
     }
 
+    /// `canister_id` is the canister targeted by the request, if any, and is
+    /// only used for rate limiting.
     pub async fn get_authorized_canisters<C: HttpRequestContent + Clone + Send + Sync + 'static>(
         &self,
         request: &HttpRequest<C>,
+        canister_id: Option<CanisterId>,
         registry_version: RegistryVersion,
         #[allow(unused_variables)] malicious_flags: &MaliciousFlags,
     ) -> Result<CanisterIdSet, HttpError> {
+        // See `validate_signed_ingress`.
+        self.rate_limiters
+            .peek_request(request.sender(), canister_id)?;
         let (tx, rx) = oneshot::channel();
 
         let r = request.clone();
@@ -91,7 +109,8 @@ impl ValidatorExecutor {
                 ));
             }
         });
-        rx.await
+        let authorized_canisters = rx
+            .await
             .map_err(|recv_err| HttpError {
                 status: StatusCode::INTERNAL_SERVER_ERROR,
                 message: format!("Internal Error: {}.", recv_err),
@@ -102,14 +121,20 @@ impl ValidatorExecutor {
                     "Failed to get authorized canister: {}", val_err
                 );
                 validation_error_to_http_error(request.id(), val_err, &self.logger)
-            })
+            })?;
+        self.rate_limiters
+            .check_request(request.sender(), canister_id)?;
+        Ok(authorized_canisters)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{validate_request, validation_error_to_http_error, ValidatorExecutor};
+    use crate::{metrics::HttpHandlerMetrics, rate_limiter::RateLimiters};
+    use ic_config::http_handler::Config;
     use ic_logger::replica_logger::no_op_logger;
+    use ic_metrics::MetricsRegistry;
     use ic_test_utilities::{
         crypto::temp_crypto_component_with_fake_registry,
         types::{
@@ -151,12 +176,20 @@ mod tests {
         };
         let request = HttpRequest::<UserQuery>::try_from(request).unwrap();
         let sig_verifier = Arc::new(temp_crypto_component_with_fake_registry(node_test_id(0)));
-        let validator = ValidatorExecutor::new(sig_verifier.clone(), no_op_logger());
+        let validator = ValidatorExecutor::new(
+            sig_verifier.clone(),
+            Arc::new(RateLimiters::new(
+                &Config::default(),
+                HttpHandlerMetrics::new(&MetricsRegistry::new()),
+            )),
+            no_op_logger(),
+        );
 
         assert_eq!(
             validator
                 .get_authorized_canisters(
                     &request,
+                    None,
                     RegistryVersion::from(0),
                     &MaliciousFlags::default()
                 )
@@ -183,7 +216,14 @@ mod tests {
             .nonce(42)
             .build();
         let sig_verifier = Arc::new(temp_crypto_component_with_fake_registry(node_test_id(0)));
-        let validator = ValidatorExecutor::new(sig_verifier.clone(), no_op_logger());
+        let validator = ValidatorExecutor::new(
+            sig_verifier.clone(),
+            Arc::new(RateLimiters::new(
+                &Config::default(),
+                HttpHandlerMetrics::new(&MetricsRegistry::new()),
+            )),
+            no_op_logger(),
+        );
 
         assert_eq!(
             validator
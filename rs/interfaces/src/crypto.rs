@@ -41,7 +41,7 @@ use ic_types::consensus::{
     RandomBeaconContent, RandomTapeContent,
 };
 use ic_types::crypto::canister_threshold_sig::idkg::{IDkgDealing, SignedIDkgDealing};
-use ic_types::messages::{MessageId, WebAuthnEnvelope};
+use ic_types::messages::{MessageId, QueryResponseHash, WebAuthnEnvelope};
 
 /// The functionality offered by the crypto component
 pub trait Crypto:
@@ -84,6 +84,8 @@ pub trait Crypto:
     // CanisterHttpResponse
     + BasicSigner<CanisterHttpResponseMetadata>
     + BasicSigVerifier<CanisterHttpResponseMetadata>
+    // Query responses
+    + BasicSigner<QueryResponseHash>
     // RequestId/WebAuthn
     + BasicSigVerifierByPublicKey<MessageId>
     + BasicSigVerifierByPublicKey<WebAuthnEnvelope>
@@ -140,6 +142,7 @@ impl<T> Crypto for T where
         + BasicSigVerifier<EcdsaOpeningContent>
         + BasicSigner<CanisterHttpResponseMetadata>
         + BasicSigVerifier<CanisterHttpResponseMetadata>
+        + BasicSigner<QueryResponseHash>
         + IDkgProtocol
         + ThresholdEcdsaSigner
         + ThresholdEcdsaSigVerifier
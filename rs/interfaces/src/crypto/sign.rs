@@ -35,7 +35,7 @@ use ic_types::crypto::{
     BasicSigOf, CanisterSigOf, CombinedMultiSigOf, CryptoResult, IndividualMultiSigOf,
     SignedBytesWithoutDomainSeparator, UserPublicKey,
 };
-use ic_types::messages::{Delegation, MessageId, WebAuthnEnvelope};
+use ic_types::messages::{Delegation, MessageId, QueryResponseHash, WebAuthnEnvelope};
 use ic_types::signature::BasicSignatureBatch;
 use ic_types::{
     consensus::{
@@ -58,6 +58,7 @@ pub mod canister_threshold_sig;
 
 const SIG_DOMAIN_IC_REQUEST_AUTH_DELEGATION: &str = "ic-request-auth-delegation";
 const SIG_DOMAIN_IC_REQUEST: &str = "ic-request";
+const SIG_DOMAIN_IC_RESPONSE: &str = "ic-response";
 
 /// `Signable` represents an object whose byte-vector representation
 /// can be signed using a digital signature scheme.
@@ -107,6 +108,7 @@ mod private {
     impl SignatureDomainSeal for Delegation {}
     impl SignatureDomainSeal for CanisterHttpResponseMetadata {}
     impl SignatureDomainSeal for MessageId {}
+    impl SignatureDomainSeal for QueryResponseHash {}
     impl SignatureDomainSeal for CertificationContent {}
     impl SignatureDomainSeal for CatchUpContent {}
     impl SignatureDomainSeal for CatchUpContentProtobufBytes {}
@@ -195,6 +197,12 @@ impl SignatureDomain for MessageId {
     }
 }
 
+impl SignatureDomain for QueryResponseHash {
+    fn domain(&self) -> Vec<u8> {
+        domain_with_prepended_length(SIG_DOMAIN_IC_RESPONSE)
+    }
+}
+
 impl SignatureDomain for CertificationContent {
     fn domain(&self) -> Vec<u8> {
         domain_with_prepended_length(DOMAIN_CERTIFICATION_CONTENT)
@@ -4,6 +4,7 @@ use crate::{
 };
 use ic_config::execution_environment::Config as HypervisorConfig;
 use ic_constants::SMALL_APP_SUBNET_MAX_SIZE;
+use ic_crypto_utils_basic_sig::conversions::node_signing_public_key_to_der;
 use ic_cycles_account_manager::CyclesAccountManager;
 use ic_ic00_types::EcdsaKeyId;
 use ic_interfaces::{
@@ -31,6 +32,7 @@ use ic_registry_subnet_type::SubnetType;
 use ic_replicated_state::{NetworkTopology, NodeTopology, ReplicatedState, SubnetTopology};
 use ic_types::{
     batch::Batch,
+    crypto::KeyPurpose,
     registry::RegistryClientError,
     xnet::{StreamHeader, StreamIndex},
     Height, NodeId, NumBytes, RegistryVersion, SubnetId,
@@ -478,11 +480,37 @@ impl BatchProcessorImpl {
                     }
                 };
 
+                // A missing or malformed signing key only affects the
+                // verifiability of this node's query responses, so the node is
+                // kept in the topology with an empty key.
+                let public_key = match self
+                    .registry
+                    .get_crypto_key_for_node(node_id, KeyPurpose::NodeSigning, registry_version)?
+                    .map(|pk| node_signing_public_key_to_der(&pk))
+                {
+                    Some(Ok(public_key)) => public_key,
+                    Some(Err(err)) => {
+                        warn!(
+                            self.log,
+                            "Invalid node signing public key for node {}: {:?}", node_id, err,
+                        );
+                        vec![]
+                    }
+                    None => {
+                        warn!(
+                            self.log,
+                            "No node signing public key found for node {}", node_id
+                        );
+                        vec![]
+                    }
+                };
+
                 nodes.insert(
                     node_id,
                     NodeTopology {
                         ip_address: http_info.ip_addr,
                         http_port,
+                        public_key,
                     },
                 );
             }
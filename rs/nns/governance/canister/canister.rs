@@ -58,7 +58,7 @@ use ic_nns_governance::{
         ClaimOrRefreshNeuronFromAccountResponse, ExecuteNnsFunction, Governance as GovernanceProto,
         GovernanceError, ListKnownNeuronsResponse, ListNeurons, ListNeuronsResponse,
         ListProposalInfo, ListProposalInfoResponse, ManageNeuron, ManageNeuronResponse, Neuron,
-        NeuronInfo, NnsFunction, Proposal, ProposalInfo, Vote,
+        NeuronInfo, NnsFunction, Proposal, ProposalInfo, QueryNeurons, QueryNeuronsResponse, Vote,
     },
 };
 
@@ -620,6 +620,17 @@ fn list_neurons_(req: ListNeurons) -> ListNeuronsResponse {
     governance().list_neurons_by_principal(&req, &caller())
 }
 
+#[export_name = "canister_query query_neurons"]
+fn query_neurons() {
+    println!("{}query_neurons", LOG_PREFIX);
+    over(candid_one, query_neurons_)
+}
+
+#[candid_method(query, rename = "query_neurons")]
+fn query_neurons_(req: QueryNeurons) -> QueryNeuronsResponse {
+    governance().query_neurons(&req, &caller())
+}
+
 #[export_name = "canister_update get_monthly_node_provider_rewards"]
 fn get_monthly_node_provider_rewards() {
     println!("{}get_monthly_node_provider_rewards", LOG_PREFIX);
@@ -757,6 +768,12 @@ fn list_neurons_pb() {
     over(protobuf, list_neurons_)
 }
 
+#[export_name = "canister_query query_neurons_pb"]
+fn query_neurons_pb() {
+    println!("{}query_neurons_pb", LOG_PREFIX);
+    over(protobuf, query_neurons_)
+}
+
 #[export_name = "canister_update update_node_provider"]
 fn update_node_provider() {
     println!("{}update_node_provider", LOG_PREFIX);
@@ -87,6 +87,15 @@ pub struct NeuronInfo {
     /// If this neuron is a known neuron, this is data associated with it, including the neuron's name and (optionally) a description.
     #[prost(message, optional, tag = "10")]
     pub known_neuron_data: ::core::option::Option<KnownNeuronData>,
+    /// See \[Neuron::voting_power_refreshed_timestamp_seconds\] for a description.
+    #[prost(uint64, optional, tag = "11")]
+    pub voting_power_refreshed_timestamp_seconds: ::core::option::Option<u64>,
+    /// The voting power that the neuron currently uses for deciding
+    /// proposals, i.e., `voting_power` reduced if the neuron has not
+    /// refreshed its voting power recently. See
+    /// \[NetworkEconomics.VotingPowerEconomics\].
+    #[prost(uint64, optional, tag = "12")]
+    pub deciding_voting_power: ::core::option::Option<u64>,
 }
 /// A transfer performed from some account to stake a new neuron.
 #[derive(
@@ -225,6 +234,16 @@ pub struct Neuron {
     /// If set, the neuron belongs to the "known neurons". It has been given a name and maybe a description.
     #[prost(message, optional, tag = "18")]
     pub known_neuron_data: ::core::option::Option<KnownNeuronData>,
+    /// The last time that the neuron's voting power was refreshed, in
+    /// seconds since the Unix epoch. The voting power is refreshed when
+    /// the neuron votes directly (including by making a proposal), when
+    /// it sets its following, and when it is explicitly refreshed with
+    /// `ManageNeuron.RefreshVotingPower`.
+    ///
+    /// See \[NetworkEconomics.VotingPowerEconomics\] for how this affects
+    /// the neuron's deciding voting power.
+    #[prost(uint64, optional, tag = "20")]
+    pub voting_power_refreshed_timestamp_seconds: ::core::option::Option<u64>,
     /// At any time, at most one of `when_dissolved` and
     /// `dissolve_delay` are specified.
     ///
@@ -998,6 +1017,19 @@ pub mod manage_neuron {
         #[prost(enumeration = "super::Vote", tag = "2")]
         pub vote: i32,
     }
+    /// Refresh the voting power of the neuron, i.e., mark it as active
+    /// without having to vote or change its following. This prevents the
+    /// neuron's deciding voting power from being reduced, and its
+    /// following from being cleared, due to inactivity.
+    #[derive(
+        candid::CandidType,
+        candid::Deserialize,
+        comparable::Comparable,
+        Clone,
+        PartialEq,
+        ::prost::Message,
+    )]
+    pub struct RefreshVotingPower {}
     /// Claim a new neuron or refresh the stake of an existing neuron.
     #[derive(
         candid::CandidType,
@@ -1099,6 +1131,8 @@ pub mod manage_neuron {
         MergeMaturity(MergeMaturity),
         #[prost(message, tag = "14")]
         Merge(Merge),
+        #[prost(message, tag = "15")]
+        RefreshVotingPower(RefreshVotingPower),
     }
 }
 /// The response of the ManageNeuron command
@@ -1248,6 +1282,15 @@ pub mod manage_neuron_response {
         #[prost(message, optional, tag = "1")]
         pub refreshed_neuron_id: ::core::option::Option<::ic_nns_common::pb::v1::NeuronId>,
     }
+    #[derive(
+        candid::CandidType,
+        candid::Deserialize,
+        comparable::Comparable,
+        Clone,
+        PartialEq,
+        ::prost::Message,
+    )]
+    pub struct RefreshVotingPowerResponse {}
     #[derive(
         candid::CandidType,
         candid::Deserialize,
@@ -1281,6 +1324,8 @@ pub mod manage_neuron_response {
         MergeMaturity(MergeMaturityResponse),
         #[prost(message, tag = "12")]
         Merge(MergeResponse),
+        #[prost(message, tag = "13")]
+        RefreshVotingPower(RefreshVotingPowerResponse),
     }
 }
 #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
@@ -1454,6 +1499,11 @@ pub struct ProposalData {
     /// This is populated when an OpenSnsTokenSwap proposal is first made.
     #[prost(uint64, optional, tag = "17")]
     pub original_total_community_fund_maturity_e8s_equivalent: ::core::option::Option<u64>,
+    /// The proposal's payload rendered as text, for display in text/UI frontends.
+    /// This is set for ExecuteNnsFunction proposals, whose payload is decoded
+    /// when the proposal is submitted.
+    #[prost(string, optional, tag = "18")]
+    pub payload_text_rendering: ::core::option::Option<::prost::alloc::string::String>,
 }
 /// Stores data relevant to the "wait for quiet" implementation.
 #[derive(
@@ -1528,6 +1578,9 @@ pub struct ProposalInfo {
     pub reward_status: i32,
     #[prost(uint64, optional, tag = "19")]
     pub deadline_timestamp_seconds: ::core::option::Option<u64>,
+    /// See \[ProposalData::payload_text_rendering\].
+    #[prost(string, optional, tag = "20")]
+    pub payload_text_rendering: ::core::option::Option<::prost::alloc::string::String>,
 }
 /// Network economics contains the parameters for several operations related
 /// to the economy of the network. When submitting a NetworkEconomics proposal
@@ -1587,6 +1640,41 @@ pub struct NetworkEconomics {
     /// If unspecified or zero, all proposals are kept.
     #[prost(uint32, tag = "10")]
     pub max_proposals_to_keep_per_topic: u32,
+    /// If unset, the voting power of neurons is never reduced.
+    #[prost(message, optional, tag = "11")]
+    pub voting_power_economics: ::core::option::Option<network_economics::VotingPowerEconomics>,
+}
+/// Nested message and enum types in `NetworkEconomics`.
+pub mod network_economics {
+    /// Parameters that control how the deciding voting power of neurons
+    /// that have not been active for a long time is reduced.
+    ///
+    /// A neuron is active when it votes directly, sets its following, or
+    /// refreshes its voting power explicitly. If a neuron has not been
+    /// active for `start_reducing_voting_power_after_seconds`, its deciding
+    /// voting power (the voting power recorded in its ballots, and hence
+    /// used for voting rewards) starts decreasing linearly, reaching zero
+    /// after another `clear_following_after_seconds`. At that point, the
+    /// neuron's following (except on the NeuronManagement topic) is
+    /// cleared.
+    ///
+    /// In a ManageNetworkEconomics proposal, only the non-zero fields are
+    /// changed. Both fields must be non-zero after the proposal is executed,
+    /// i.e. both must be set when the reduction is turned on.
+    #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+    #[self_describing]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct VotingPowerEconomics {
+        #[prost(uint64, tag = "1")]
+        pub start_reducing_voting_power_after_seconds: u64,
+        #[prost(uint64, tag = "2")]
+        pub clear_following_after_seconds: u64,
+        /// Only used in ManageNetworkEconomics proposals: if set, the reduction
+        /// of voting power is turned off, i.e. `voting_power_economics` is
+        /// unset. The other fields must be zero then.
+        #[prost(bool, tag = "3")]
+        pub disable: bool,
+    }
 }
 /// A reward event is an event at which neuron maturity is increased
 #[derive(
@@ -1935,6 +2023,67 @@ pub struct ListNeuronsResponse {
     #[prost(message, repeated, tag = "2")]
     pub full_neurons: ::prost::alloc::vec::Vec<Neuron>,
 }
+/// A request to page through all neurons, ordered by neuron ID.
+///
+/// Only neurons matching all of the specified filters are returned. To
+/// fetch the next page, pass the `next_start_after_neuron_id` of the
+/// previous response as `start_after_neuron_id`. Since neuron IDs never
+/// change, paging in this way neither skips nor repeats a neuron that
+/// exists for the whole duration of the paging, even if other neurons
+/// are created or removed in between.
+#[derive(candid::CandidType, candid::Deserialize, Clone, PartialEq, ::prost::Message)]
+pub struct QueryNeurons {
+    /// If specified, only neurons with an ID strictly greater than this
+    /// are returned. If not specified, start with the lowest neuron ID.
+    #[prost(fixed64, optional, tag = "1")]
+    pub start_after_neuron_id: ::core::option::Option<u64>,
+    /// Limit on the number of neurons to return. If no value is
+    /// specified, or if a value greater than 100 is specified, 100 will
+    /// be used.
+    #[prost(uint32, tag = "2")]
+    pub limit: u32,
+    /// Include neurons that are in one of these states (see
+    /// \[NeuronState\]). If this list is empty, no restriction is applied.
+    #[prost(enumeration = "NeuronState", repeated, tag = "3")]
+    pub include_dissolve_states: ::prost::alloc::vec::Vec<i32>,
+    /// If specified, only neurons that follow this neuron are returned.
+    #[prost(message, optional, tag = "4")]
+    pub followee: ::core::option::Option<::ic_nns_common::pb::v1::NeuronId>,
+    /// If specified, only following on this topic is considered. Otherwise,
+    /// following on any topic counts. Must only be specified together with
+    /// `followee`.
+    #[prost(enumeration = "Topic", optional, tag = "5")]
+    pub followee_topic: ::core::option::Option<i32>,
+    /// If specified, only neurons with at least this stake are returned.
+    #[prost(uint64, optional, tag = "6")]
+    pub min_stake_e8s: ::core::option::Option<u64>,
+    /// If specified, only neurons with at most this stake are returned.
+    #[prost(uint64, optional, tag = "7")]
+    pub max_stake_e8s: ::core::option::Option<u64>,
+    /// If true, the response also contains the full neuron data of the
+    /// returned neurons that the caller is authorized to read.
+    #[prost(bool, tag = "8")]
+    pub include_full_neurons_readable_by_caller: bool,
+}
+/// A response to a `QueryNeurons` request.
+#[derive(candid::CandidType, candid::Deserialize, Clone, PartialEq, ::prost::Message)]
+pub struct QueryNeuronsResponse {
+    /// The `NeuronInfo` of each neuron in this page, keyed by neuron ID.
+    #[prost(map = "fixed64, message", tag = "1")]
+    pub neuron_infos: ::std::collections::HashMap<u64, NeuronInfo>,
+    /// The full neuron data of the neurons in this page that the caller is
+    /// authorized to read, if requested.
+    #[prost(message, repeated, tag = "2")]
+    pub full_neurons: ::prost::alloc::vec::Vec<Neuron>,
+    /// If set, there may be more matching neurons, and this value should
+    /// be passed as `start_after_neuron_id` to fetch the next page.
+    #[prost(fixed64, optional, tag = "3")]
+    pub next_start_after_neuron_id: ::core::option::Option<u64>,
+    /// Set if the request is invalid, in which case no neurons are
+    /// returned.
+    #[prost(message, optional, tag = "4")]
+    pub error: ::core::option::Option<GovernanceError>,
+}
 /// A response to "ListKnownNeurons"
 #[derive(candid::CandidType, candid::Deserialize, Clone, PartialEq, ::prost::Message)]
 pub struct ListKnownNeuronsResponse {
@@ -2287,6 +2436,10 @@ pub enum NnsFunction {
     CompleteCanisterMigration = 29,
     //// Add a new SNS canister WASM
     AddSnsWasm = 30,
+    //// Add nodes to and remove nodes from a subnet in a single registry
+    //// version, e.g., to replace faulty nodes. The nodes to add must be
+    //// unassigned and the nodes to remove must be members of the subnet.
+    ChangeSubnetMembership = 31,
 }
 /// The proposal status, with respect to decision making and execution.
 /// See also ProposalRewardStatus.
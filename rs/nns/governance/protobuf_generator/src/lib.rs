@@ -176,6 +176,10 @@ pub fn generate_prost_files(proto: ProtoPaths<'_>, out: &Path) {
         "ic_nns_governance.pb.v1.ManageNeuron.RegisterVote",
         ["#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]"].join(" "),
     );
+    config.type_attribute(
+        "ic_nns_governance.pb.v1.ManageNeuron.RefreshVotingPower",
+        ["#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]"].join(" "),
+    );
     config.type_attribute(
         "ic_nns_governance.pb.v1.ManageNeuron.ClaimOrRefresh",
         ["#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]"].join(" "),
@@ -252,6 +256,10 @@ pub fn generate_prost_files(proto: ProtoPaths<'_>, out: &Path) {
         "ic_nns_governance.pb.v1.ManageNeuronResponse.ClaimOrRefreshResponse",
         ["#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]"].join(" "),
     );
+    config.type_attribute(
+        "ic_nns_governance.pb.v1.ManageNeuronResponse.RefreshVotingPowerResponse",
+        ["#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]"].join(" "),
+    );
     config.type_attribute(
         "ic_nns_governance.pb.v1.ManageNeuronResponse.command",
         ["#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]"].join(" "),
@@ -272,6 +280,14 @@ pub fn generate_prost_files(proto: ProtoPaths<'_>, out: &Path) {
         ]
         .join(" "),
     );
+    config.type_attribute(
+        "ic_nns_governance.pb.v1.NetworkEconomics.VotingPowerEconomics",
+        [
+            "#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]",
+            "#[self_describing]",
+        ]
+        .join(" "),
+    );
     config.type_attribute(
         "ic_nns_governance.pb.v1.Motion",
         [
@@ -390,6 +406,14 @@ pub fn generate_prost_files(proto: ProtoPaths<'_>, out: &Path) {
         "ic_nns_governance.pb.v1.ListNeuronsResponse",
         "#[derive(candid::CandidType, candid::Deserialize)]",
     );
+    config.type_attribute(
+        "ic_nns_governance.pb.v1.QueryNeurons",
+        "#[derive(candid::CandidType, candid::Deserialize)]",
+    );
+    config.type_attribute(
+        "ic_nns_governance.pb.v1.QueryNeuronsResponse",
+        "#[derive(candid::CandidType, candid::Deserialize)]",
+    );
     config.type_attribute(
         "ic_nns_governance.pb.v1.ListKnownNeurons",
         "#[derive(candid::CandidType, candid::Deserialize)]",
@@ -3,7 +3,7 @@ use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::convert::TryFrom;
 use std::convert::TryInto;
 use std::fmt;
-use std::ops::RangeInclusive;
+use std::ops::{Bound, RangeInclusive};
 use std::string::ToString;
 
 use crate::pb::v1::{
@@ -17,6 +17,7 @@ use crate::pb::v1::{
         ClaimOrRefresh, Command, NeuronIdOrSubaccount,
     },
     manage_neuron_response,
+    network_economics::VotingPowerEconomics,
     neuron::DissolveState,
     neuron::Followees,
     proposal,
@@ -26,8 +27,9 @@ use crate::pb::v1::{
     ListProposalInfo, ListProposalInfoResponse, ManageNeuron, ManageNeuronResponse,
     MostRecentMonthlyNodeProviderRewards, Motion, NetworkEconomics, Neuron, NeuronInfo,
     NeuronState, NnsFunction, NodeProvider, OpenSnsTokenSwap, Proposal, ProposalData, ProposalInfo,
-    ProposalRewardStatus, ProposalStatus, RewardEvent, RewardNodeProvider, RewardNodeProviders,
-    SetSnsTokenSwapOpenTimeWindow, Tally, Topic, UpdateNodeProvider, Vote,
+    ProposalRewardStatus, ProposalStatus, QueryNeurons, QueryNeuronsResponse, RewardEvent,
+    RewardNodeProvider, RewardNodeProviders, SetSnsTokenSwapOpenTimeWindow, Tally, Topic,
+    UpdateNodeProvider, Vote,
 };
 
 use async_trait::async_trait;
@@ -45,6 +47,8 @@ use ic_sns_swap::pb::v1 as sns_swap_pb;
 use ledger_canister::{AccountIdentifier, Subaccount, DEFAULT_TRANSFER_FEE};
 use registry_canister::mutations::do_add_node_operator::AddNodeOperatorPayload;
 
+use crate::nns_function_payload::validate_and_render_nns_function_payload;
+
 #[cfg(target_arch = "wasm32")]
 use dfn_core::println;
 
@@ -126,6 +130,13 @@ pub const MAX_NUMBER_OF_NEURONS: usize = 200_000;
 /// The maximum number results returned by the method `list_proposals`.
 pub const MAX_LIST_PROPOSAL_RESULTS: u32 = 100;
 
+/// The maximum number of neurons returned by the method `query_neurons`.
+pub const MAX_QUERY_NEURONS_RESULTS: u32 = 100;
+
+/// The maximum number of neurons whose following is checked for being
+/// cleared in a single call of `run_periodic_tasks`.
+pub const MAX_NEURONS_TO_CHECK_FOR_INACTIVE_FOLLOWING: usize = 1_000;
+
 /// The number of e8s per ICP;
 const E8S_PER_ICP: u64 = TOKEN_SUBDIVIDABLE_BY;
 
@@ -176,6 +187,8 @@ impl NetworkEconomics {
             minimum_icp_xdr_rate: 100,                                  // 1 XDR
             transaction_fee_e8s: DEFAULT_TRANSFER_FEE.get_e8s(),
             max_proposals_to_keep_per_topic: 100,
+            // Voting power is not reduced until enabled by proposal.
+            voting_power_economics: None,
         }
     }
 }
@@ -425,6 +438,14 @@ impl ManageNeuronResponse {
             )),
         }
     }
+
+    pub fn refresh_voting_power_response() -> Self {
+        ManageNeuronResponse {
+            command: Some(manage_neuron_response::Command::RefreshVotingPower(
+                manage_neuron_response::RefreshVotingPowerResponse {},
+            )),
+        }
+    }
 }
 
 impl NnsFunction {
@@ -486,6 +507,9 @@ impl NnsFunction {
                 (REGISTRY_CANISTER_ID, "complete_canister_migration")
             }
             NnsFunction::AddSnsWasm => (SNS_WASM_CANISTER_ID, "add_wasm"),
+            NnsFunction::ChangeSubnetMembership => {
+                (REGISTRY_CANISTER_ID, "change_subnet_membership")
+            }
         };
         Ok((canister_id, method))
     }
@@ -597,6 +621,60 @@ impl Neuron {
         std::cmp::min(ad_stake, u64::MAX as u128) as u64
     }
 
+    /// Return the voting power that this neuron uses to decide
+    /// proposals, i.e., the voting power recorded in its ballots.
+    ///
+    /// This is the neuron's `voting_power`, reduced according to
+    /// `voting_power_economics` if the neuron has not refreshed its
+    /// voting power for a long time. If `voting_power_economics` is
+    /// not set, the voting power is never reduced.
+    pub fn deciding_voting_power(
+        &self,
+        voting_power_economics: Option<&VotingPowerEconomics>,
+        now_seconds: u64,
+    ) -> u64 {
+        let potential_voting_power = self.voting_power(now_seconds);
+        match voting_power_economics {
+            Some(economics) => crate::reward::deciding_voting_power(
+                potential_voting_power,
+                now_seconds.saturating_sub(self.voting_power_refreshed_timestamp_seconds()),
+                economics.start_reducing_voting_power_after_seconds,
+                economics.clear_following_after_seconds,
+            ),
+            None => potential_voting_power,
+        }
+    }
+
+    /// Return the last time the voting power of this neuron was
+    /// refreshed. Neurons that were never refreshed are considered to
+    /// have been refreshed when they were created.
+    pub fn voting_power_refreshed_timestamp_seconds(&self) -> u64 {
+        self.voting_power_refreshed_timestamp_seconds
+            .unwrap_or(self.created_timestamp_seconds)
+    }
+
+    /// Record that the voting power of this neuron was refreshed at
+    /// `now_seconds`, i.e., that the neuron is active.
+    pub fn refresh_voting_power(&mut self, now_seconds: u64) {
+        self.voting_power_refreshed_timestamp_seconds = Some(now_seconds);
+    }
+
+    /// Returns true if this neuron has been inactive for so long that
+    /// its deciding voting power has dropped to zero, and thus its
+    /// following should be cleared.
+    fn is_inactive_for_clearing_following(
+        &self,
+        voting_power_economics: &VotingPowerEconomics,
+        now_seconds: u64,
+    ) -> bool {
+        let inactive_seconds =
+            now_seconds.saturating_sub(self.voting_power_refreshed_timestamp_seconds());
+        inactive_seconds
+            >= voting_power_economics
+                .start_reducing_voting_power_after_seconds
+                .saturating_add(voting_power_economics.clear_following_after_seconds)
+    }
+
     /// Given the specified `ballots`: determine how this neuron would
     /// vote on a proposal of `topic` based on which neurons this
     /// neuron follows on this topic (or on the default topic if this
@@ -1002,7 +1080,14 @@ impl Neuron {
     }
 
     /// Get the 'public' information associated with this neuron.
-    pub fn get_neuron_info(&self, now_seconds: u64) -> NeuronInfo {
+    ///
+    /// The deciding voting power is computed according to
+    /// `voting_power_economics`, see [Neuron::deciding_voting_power].
+    pub fn get_neuron_info(
+        &self,
+        now_seconds: u64,
+        voting_power_economics: Option<&VotingPowerEconomics>,
+    ) -> NeuronInfo {
         NeuronInfo {
             retrieved_at_timestamp_seconds: now_seconds,
             state: self.state(now_seconds) as i32,
@@ -1014,6 +1099,12 @@ impl Neuron {
             stake_e8s: self.stake_e8s(),
             joined_community_fund_timestamp_seconds: self.joined_community_fund_timestamp_seconds,
             known_neuron_data: self.known_neuron_data.as_ref().cloned(),
+            voting_power_refreshed_timestamp_seconds: Some(
+                self.voting_power_refreshed_timestamp_seconds(),
+            ),
+            deciding_voting_power: Some(
+                self.deciding_voting_power(voting_power_economics, now_seconds),
+            ),
         }
     }
 
@@ -1111,6 +1202,7 @@ impl Proposal {
                             | NnsFunction::AddNodeToSubnet
                             | NnsFunction::RecoverSubnet
                             | NnsFunction::RemoveNodesFromSubnet
+                            | NnsFunction::ChangeSubnetMembership
                             | NnsFunction::UpdateConfigOfSubnet
                             | NnsFunction::BlessReplicaVersion
                             | NnsFunction::UpdateSubnetReplicaVersion => Topic::SubnetManagement,
@@ -1964,6 +2056,11 @@ pub struct Governance {
     /// This set is cached and will be removed and recreated when the state is saved and restored.
     pub known_neuron_name_set: HashSet<String>,
 
+    /// The IDs of all neurons, in order, to page through the neurons.
+    ///
+    /// This set is cached and will be removed and recreated when the state is saved and restored.
+    neuron_ids: BTreeSet<u64>,
+
     /// Timestamp, in seconds since the unix epoch, until which no proposal
     /// needs to be processed.
     closest_proposal_deadline_timestamp_seconds: u64,
@@ -1974,6 +2071,12 @@ pub struct Governance {
 
     /// The number of proposals after the last time GC was run.
     pub latest_gc_num_proposals: usize,
+
+    /// The IDs of the neurons whose following is yet to be checked in the
+    /// current pass of clearing the following of inactive neurons, which is
+    /// spread over several calls of `run_periodic_tasks`. Empty if no pass is
+    /// in progress.
+    neurons_to_check_for_inactive_following: Vec<u64>,
 }
 
 pub fn governance_minting_account() -> AccountIdentifier {
@@ -2014,9 +2117,11 @@ impl Governance {
             topic_followee_index: BTreeMap::new(),
             principal_to_neuron_ids_index: BTreeMap::new(),
             known_neuron_name_set: HashSet::new(),
+            neuron_ids: BTreeSet::new(),
             closest_proposal_deadline_timestamp_seconds: 0,
             latest_gc_timestamp_seconds: 0,
             latest_gc_num_proposals: 0,
+            neurons_to_check_for_inactive_following: vec![],
         };
 
         gov.initialize_indices();
@@ -2068,6 +2173,7 @@ impl Governance {
         self.topic_followee_index = self.proto.build_topic_followee_index();
         self.principal_to_neuron_ids_index = self.proto.build_principal_to_neuron_ids_index();
         self.known_neuron_name_set = self.proto.build_known_neuron_name_index();
+        self.neuron_ids = self.proto.neurons.keys().copied().collect();
     }
 
     fn transaction_fee(&self) -> u64 {
@@ -2264,6 +2370,7 @@ impl Governance {
             &neuron,
         );
 
+        self.neuron_ids.insert(neuron_id);
         self.proto.neurons.insert(neuron_id, neuron);
 
         Ok(())
@@ -2294,6 +2401,7 @@ impl Governance {
             &neuron,
         );
 
+        self.neuron_ids.remove(&neuron_id);
         self.proto.neurons.remove(&neuron_id);
 
         Ok(())
@@ -2350,7 +2458,7 @@ impl Governance {
                     self.proto
                         .neurons
                         .get(x)
-                        .map(|y| (*x, y.get_neuron_info(now)))
+                        .map(|y| (*x, y.get_neuron_info(now, self.voting_power_economics())))
                 })
                 .collect(),
             full_neurons: requested_list()
@@ -2359,6 +2467,109 @@ impl Governance {
         }
     }
 
+    /// Returns a page of the neurons matching the filters of `req`,
+    /// ordered by neuron ID.
+    ///
+    /// If a followee is specified, the candidates are taken from the
+    /// topic followee index, otherwise all neurons are candidates. The
+    /// full neuron data is only included for the neurons the `caller` is
+    /// authorized to read (see `get_full_neuron`).
+    pub fn query_neurons(&self, req: &QueryNeurons, caller: &PrincipalId) -> QueryNeuronsResponse {
+        if req.followee.is_none() && req.followee_topic.is_some() {
+            return QueryNeuronsResponse {
+                error: Some(GovernanceError::new_with_message(
+                    ErrorType::InvalidCommand,
+                    "followee_topic can only be specified together with followee",
+                )),
+                ..Default::default()
+            };
+        }
+        let now = self.env.now();
+        let limit = if req.limit == 0 || req.limit > MAX_QUERY_NEURONS_RESULTS {
+            MAX_QUERY_NEURONS_RESULTS
+        } else {
+            req.limit
+        } as usize;
+        let after_cursor = (
+            match req.start_after_neuron_id {
+                Some(start_after) => Bound::Excluded(start_after),
+                None => Bound::Unbounded,
+            },
+            Bound::Unbounded,
+        );
+        let include_dissolve_states: HashSet<i32> =
+            req.include_dissolve_states.iter().cloned().collect();
+        let filter_all = |neuron: &Neuron| -> bool {
+            if !(include_dissolve_states.is_empty()
+                || include_dissolve_states.contains(&(neuron.state(now) as i32)))
+            {
+                return false;
+            }
+            let stake_e8s = neuron.stake_e8s();
+            if req.min_stake_e8s.map_or(false, |min| stake_e8s < min) {
+                return false;
+            }
+            !req.max_stake_e8s.map_or(false, |max| stake_e8s > max)
+        };
+
+        let candidate_ids: Box<dyn Iterator<Item = u64> + '_> = match &req.followee {
+            Some(followee) => {
+                // The followers in the index are already sorted, but a
+                // neuron may follow the same followee on several topics.
+                let mut followers = BTreeSet::new();
+                for (topic, followee_to_followers) in &self.topic_followee_index {
+                    if let Some(followee_topic) = req.followee_topic {
+                        if *topic as i32 != followee_topic {
+                            continue;
+                        }
+                    }
+                    if let Some(ids) = followee_to_followers.get(&followee.id) {
+                        followers.extend(ids.range(after_cursor));
+                    }
+                }
+                Box::new(followers.into_iter())
+            }
+            None => Box::new(self.neuron_ids.range(after_cursor).copied()),
+        };
+
+        // Take one more neuron than requested to find out whether there
+        // is a next page.
+        let mut page: Vec<(u64, &Neuron)> = candidate_ids
+            .into_iter()
+            .filter_map(|id| self.proto.neurons.get(&id).map(|neuron| (id, neuron)))
+            .filter(|(_, neuron)| filter_all(neuron))
+            .take(limit + 1)
+            .collect();
+        let next_start_after_neuron_id = if page.len() > limit {
+            page.truncate(limit);
+            page.last().map(|(id, _)| *id)
+        } else {
+            None
+        };
+
+        let full_neurons = if req.include_full_neurons_readable_by_caller {
+            page.iter()
+                .filter_map(|(id, _)| self.get_full_neuron(&NeuronId { id: *id }, caller).ok())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        QueryNeuronsResponse {
+            neuron_infos: page
+                .into_iter()
+                .map(|(id, neuron)| {
+                    (
+                        id,
+                        neuron.get_neuron_info(now, self.voting_power_economics()),
+                    )
+                })
+                .collect(),
+            full_neurons,
+            next_start_after_neuron_id,
+            error: None,
+        }
+    }
+
     /// Returns a neuron, given a subaccount.
     ///
     /// Currently we just do linear search on the neurons. We tried an index at
@@ -2834,6 +3045,8 @@ impl Governance {
                 .joined_community_fund_timestamp_seconds,
             known_neuron_data: None,
             spawn_at_timestamp_seconds: None,
+            voting_power_refreshed_timestamp_seconds: parent_neuron
+                .voting_power_refreshed_timestamp_seconds,
         };
 
         // Add the child neuron to the set of neurons undergoing ledger updates.
@@ -3330,6 +3543,7 @@ impl Governance {
                 dissolve_and_spawn_at_timestamp_seconds,
             )),
             spawn_at_timestamp_seconds: Some(dissolve_and_spawn_at_timestamp_seconds),
+            voting_power_refreshed_timestamp_seconds: None,
             followees: parent_neuron.followees.clone(),
             recent_ballots: Vec::new(),
             kyc_verified: parent_neuron.kyc_verified,
@@ -3676,6 +3890,7 @@ impl Governance {
             joined_community_fund_timestamp_seconds: None,
             known_neuron_data: None,
             spawn_at_timestamp_seconds: None,
+            voting_power_refreshed_timestamp_seconds: None,
         };
 
         self.add_neuron(child_nid.id, child_neuron.clone())?;
@@ -3783,7 +3998,7 @@ impl Governance {
             .get(&id.id)
             .ok_or_else(|| GovernanceError::new(ErrorType::NotFound))?;
         let now = self.env.now();
-        Ok(neuron.get_neuron_info(now))
+        Ok(neuron.get_neuron_info(now, self.voting_power_economics()))
     }
 
     /// Returns the neuron info for a neuron identified by id or subaccount.
@@ -3795,7 +4010,7 @@ impl Governance {
     ) -> Result<NeuronInfo, GovernanceError> {
         let neuron = self.find_neuron(by)?;
         let now = self.env.now();
-        Ok(neuron.get_neuron_info(now))
+        Ok(neuron.get_neuron_info(now, self.voting_power_economics()))
     }
 
     /// Returns the complete neuron data for a given neuron `id` or
@@ -3984,6 +4199,7 @@ impl Governance {
             deadline_timestamp_seconds: Some(
                 data.get_deadline_timestamp_seconds(voting_period_seconds),
             ),
+            payload_text_rendering: data.payload_text_rendering.clone(),
         }
     }
 
@@ -4314,6 +4530,7 @@ impl Governance {
                     joined_community_fund_timestamp_seconds: None,
                     known_neuron_data: None,
                     spawn_at_timestamp_seconds: None,
+                    voting_power_refreshed_timestamp_seconds: None,
                 };
                 self.add_neuron(nid.id, neuron)
             }
@@ -4527,6 +4744,28 @@ impl Governance {
                 }
             }
             proposal::Action::ManageNetworkEconomics(ne) => {
+                // The proposal was validated when it was made, but the
+                // voting power economics may have changed since.
+                let voting_power_economics = match ne
+                    .voting_power_economics
+                    .as_ref()
+                    .map(|new_vpe| {
+                        merge_voting_power_economics(self.voting_power_economics(), new_vpe)
+                    })
+                    .transpose()
+                {
+                    Ok(voting_power_economics) => voting_power_economics,
+                    Err(err) => {
+                        self.set_proposal_execution_status(
+                            pid,
+                            Err(GovernanceError::new_with_message(
+                                ErrorType::InvalidProposal,
+                                err,
+                            )),
+                        );
+                        return;
+                    }
+                };
                 if let Some(economics) = &mut self.proto.economics {
                     // The semantics of the proposal is to modify all values specified with a
                     // non-default value in the proposed new `NetworkEconomics`.
@@ -4558,10 +4797,28 @@ impl Governance {
                         economics.max_proposals_to_keep_per_topic =
                             ne.max_proposals_to_keep_per_topic
                     }
+                    if let Some(new_vpe) = voting_power_economics {
+                        if new_vpe.is_some() && economics.voting_power_economics.is_none() {
+                            // The voting power of inactive neurons starts being
+                            // reduced. Neurons that were never refreshed are
+                            // considered refreshed now, so that they are not
+                            // affected right away.
+                            let now = self.env.now();
+                            for neuron in self.proto.neurons.values_mut() {
+                                if neuron.voting_power_refreshed_timestamp_seconds.is_none() {
+                                    neuron.refresh_voting_power(now);
+                                }
+                            }
+                        }
+                        economics.voting_power_economics = new_vpe;
+                    }
                 } else {
                     // If for some reason, we don't have an
                     // 'economics' proto, use the proposed one.
-                    self.proto.economics = Some(ne)
+                    self.proto.economics = Some(NetworkEconomics {
+                        voting_power_economics: voting_power_economics.flatten(),
+                        ..ne
+                    })
                 }
                 self.set_proposal_execution_status(pid, Ok(()));
             }
@@ -4990,6 +5247,15 @@ impl Governance {
             .expect("NetworkEconomics not present")
     }
 
+    /// Returns the parameters used to reduce the voting power of
+    /// inactive neurons, if any.
+    fn voting_power_economics(&self) -> Option<&VotingPowerEconomics> {
+        self.proto
+            .economics
+            .as_ref()
+            .and_then(|economics| economics.voting_power_economics.as_ref())
+    }
+
     /// Inserts a proposals that has already been validated in the state.
     ///
     /// This is a low-level function that makes no verification whatsoever.
@@ -5015,7 +5281,12 @@ impl Governance {
             .map_or(1, |(k, _)| k + 1)
     }
 
-    fn validate_proposal(&mut self, proposal: &Proposal) -> Result<(), GovernanceError> {
+    /// Validates a proposal and, for `ExecuteNnsFunction` proposals, returns a
+    /// displayable text rendering of the payload.
+    fn validate_proposal(
+        &mut self,
+        proposal: &Proposal,
+    ) -> Result<Option<String>, GovernanceError> {
         let invalid_proposal = |message| {
             Err(GovernanceError::new_with_message(
                 ErrorType::InvalidProposal,
@@ -5057,119 +5328,148 @@ impl Governance {
 
         // Finally, perform Action-specific validation.
         match action {
-            Action::ExecuteNnsFunction(execute_nns_function) => {
-                self.validate_execute_nns_function(execute_nns_function)
-            }
+            Action::ExecuteNnsFunction(execute_nns_function) => self
+                .validate_execute_nns_function(execute_nns_function)
+                .map(Some),
 
-            Action::Motion(motion) => validate_motion(motion),
+            Action::Motion(motion) => validate_motion(motion).map(|_| None),
 
             Action::SetSnsTokenSwapOpenTimeWindow(set_sns_token_swap_open_time_window) => {
                 validate_set_sns_token_swap_open_time_window(set_sns_token_swap_open_time_window)
+                    .map(|_| None)
             }
 
-            Action::OpenSnsTokenSwap(open_sns_token_swap) => {
-                self.validate_open_sns_token_swap(open_sns_token_swap)
+            Action::OpenSnsTokenSwap(open_sns_token_swap) => self
+                .validate_open_sns_token_swap(open_sns_token_swap)
+                .map(|_| None),
+
+            Action::ManageNetworkEconomics(network_economics) => {
+                match &network_economics.voting_power_economics {
+                    Some(new_vpe) => {
+                        merge_voting_power_economics(self.voting_power_economics(), new_vpe)
+                            .map(|_| None)
+                            .or_else(invalid_proposal)
+                    }
+                    None => Ok(None),
+                }
             }
 
             Action::ManageNeuron(_)
-            | Action::ManageNetworkEconomics(_)
             | Action::ApproveGenesisKyc(_)
             | Action::AddOrRemoveNodeProvider(_)
             | Action::RewardNodeProvider(_)
             | Action::SetDefaultFollowees(_)
             | Action::RewardNodeProviders(_)
-            | Action::RegisterKnownNeuron(_) => Ok(()),
+            | Action::RegisterKnownNeuron(_) => Ok(None),
         }
     }
 
+    /// Validates an `ExecuteNnsFunction` action and returns a displayable
+    /// text rendering of its payload.
+    ///
+    /// The payload must decode into the argument type of the method that the
+    /// NNS function calls. Some NNS functions are additionally checked
+    /// against the current state of governance.
     fn validate_execute_nns_function(
         &self,
         update: &ExecuteNnsFunction,
-    ) -> Result<(), GovernanceError> {
-        let error_str = {
-            if update.nns_function != NnsFunction::NnsCanisterUpgrade as i32
-                && update.nns_function != NnsFunction::NnsCanisterInstall as i32
-                && update.nns_function != NnsFunction::NnsRootUpgrade as i32
-                && update.nns_function != NnsFunction::AddSnsWasm as i32
-                && update.payload.len() > PROPOSAL_EXECUTE_NNS_FUNCTION_PAYLOAD_BYTES_MAX
-            {
-                format!(
-                    "The maximum NNS function payload size in a proposal action is {} bytes, this payload is: {} bytes",
-                    PROPOSAL_EXECUTE_NNS_FUNCTION_PAYLOAD_BYTES_MAX,
-                    update.payload.len(),
-                )
-            } else if update.nns_function == NnsFunction::IcpXdrConversionRate as i32 {
-                match Decode!(&update.payload, UpdateIcpXdrConversionRatePayload) {
-                    Ok(payload) => {
-                        if payload.xdr_permyriad_per_icp
-                            < self
-                                .proto
-                                .economics
-                                .as_ref()
-                                .ok_or_else(|| GovernanceError::new(ErrorType::Unavailable))?
-                                .minimum_icp_xdr_rate
-                        {
-                            format!(
-                                "The proposed rate {} is below the minimum allowable rate",
-                                payload.xdr_permyriad_per_icp
-                            )
-                        } else {
-                            return Ok(());
-                        }
-                    }
-                    Err(e) => format!(
-                        "The payload could not be decoded into a UpdateIcpXdrConversionRatePayload: {}",
-                        e
-                    ),
+    ) -> Result<String, GovernanceError> {
+        let invalid_proposal = |message: String| {
+            Err(GovernanceError::new_with_message(
+                ErrorType::InvalidProposal,
+                message,
+            ))
+        };
+
+        let nns_function = match NnsFunction::from_i32(update.nns_function) {
+            Some(nns_function) => nns_function,
+            None => {
+                return invalid_proposal(format!("Unknown NNS function: {}", update.nns_function))
+            }
+        };
+
+        if !matches!(
+            nns_function,
+            NnsFunction::NnsCanisterUpgrade
+                | NnsFunction::NnsCanisterInstall
+                | NnsFunction::NnsRootUpgrade
+                | NnsFunction::AddSnsWasm
+        ) && update.payload.len() > PROPOSAL_EXECUTE_NNS_FUNCTION_PAYLOAD_BYTES_MAX
+        {
+            return invalid_proposal(format!(
+                "The maximum NNS function payload size in a proposal action is {} bytes, this payload is: {} bytes",
+                PROPOSAL_EXECUTE_NNS_FUNCTION_PAYLOAD_BYTES_MAX,
+                update.payload.len(),
+            ));
+        }
+
+        let rendering =
+            match validate_and_render_nns_function_payload(nns_function, &update.payload) {
+                Ok(rendering) => rendering,
+                Err(err) => return invalid_proposal(err),
+            };
+
+        match nns_function {
+            NnsFunction::IcpXdrConversionRate => {
+                // Decoding cannot fail, as the payload has been rendered above.
+                let payload =
+                    Decode!(&update.payload, UpdateIcpXdrConversionRatePayload).map_err(|e| {
+                        GovernanceError::new_with_message(ErrorType::InvalidProposal, e.to_string())
+                    })?;
+                if payload.xdr_permyriad_per_icp
+                    < self
+                        .proto
+                        .economics
+                        .as_ref()
+                        .ok_or_else(|| GovernanceError::new(ErrorType::Unavailable))?
+                        .minimum_icp_xdr_rate
+                {
+                    return invalid_proposal(format!(
+                        "The proposed rate {} is below the minimum allowable rate",
+                        payload.xdr_permyriad_per_icp
+                    ));
                 }
-            } else if update.nns_function == NnsFunction::AssignNoid as i32 {
-                match Decode!(&update.payload, AddNodeOperatorPayload) {
-                    Ok(payload) => match payload.node_provider_principal_id {
-                        Some(id) => {
-                            let is_registered = self
-                                .get_node_providers()
-                                .iter()
-                                .any(|np| np.id.unwrap() == id);
-                            if !is_registered {
+            }
+            NnsFunction::AssignNoid => {
+                let payload = Decode!(&update.payload, AddNodeOperatorPayload).map_err(|e| {
+                    GovernanceError::new_with_message(ErrorType::InvalidProposal, e.to_string())
+                })?;
+                match payload.node_provider_principal_id {
+                    Some(id) => {
+                        let is_registered = self
+                            .get_node_providers()
+                            .iter()
+                            .any(|np| np.id.unwrap() == id);
+                        if !is_registered {
+                            return invalid_proposal(
                                 "The node provider specified in the payload is not registered"
-                                    .to_string()
-                            } else {
-                                return Ok(());
-                            }
-                        }
-                        None => {
-                            "The payload's node_provider_principal_id field was None".to_string()
+                                    .to_string(),
+                            );
                         }
-                    },
-                    Err(e) => format!(
-                        "The payload could not be decoded into a AddNodeOperatorPayload: {}",
-                        e
-                    ),
+                    }
+                    None => {
+                        return invalid_proposal(
+                            "The payload's node_provider_principal_id field was None".to_string(),
+                        );
+                    }
                 }
-            } else if update.nns_function == NnsFunction::AddOrRemoveDataCenters as i32 {
-                match Decode!(&update.payload, AddOrRemoveDataCentersProposalPayload) {
-                    Ok(payload) => match payload.validate() {
-                        Ok(_) => {
-                            return Ok(());
-                        }
-                        Err(e) => {
-                            format!("The given AddOrRemoveDataCentersProposalPayload is invalid: {}", e)
-                        }
-                    },
-                    Err(e) => format!(
-                        "The payload could not be decoded into a AddOrRemoveDataCentersProposalPayload: {}",
+            }
+            NnsFunction::AddOrRemoveDataCenters => {
+                let payload = Decode!(&update.payload, AddOrRemoveDataCentersProposalPayload)
+                    .map_err(|e| {
+                        GovernanceError::new_with_message(ErrorType::InvalidProposal, e.to_string())
+                    })?;
+                if let Err(e) = payload.validate() {
+                    return invalid_proposal(format!(
+                        "The given AddOrRemoveDataCentersProposalPayload is invalid: {}",
                         e
-                    ),
+                    ));
                 }
-            } else {
-                return Ok(());
             }
-        };
+            _ => {}
+        }
 
-        Err(GovernanceError::new_with_message(
-            ErrorType::InvalidProposal,
-            &error_str,
-        ))
+        Ok(rendering)
     }
 
     /// There can be at most one OpenSnsTokenSwap proposal at a time.
@@ -5218,7 +5518,7 @@ impl Governance {
         let now_seconds = self.env.now();
 
         // Validate proposal
-        self.validate_proposal(proposal)?;
+        let payload_text_rendering = self.validate_proposal(proposal)?;
 
         if let Some(proposal::Action::ManageNeuron(m)) = &proposal.action {
             assert_eq!(topic, Topic::NeuronManagement);
@@ -5318,6 +5618,7 @@ impl Governance {
         );
         let mut electoral_roll = HashMap::<u64, Ballot>::new();
         let mut total_power: u128 = 0;
+        let voting_power_economics = self.voting_power_economics();
         for (k, v) in self.proto.neurons.iter() {
             // If this neuron is eligible to vote, record its
             // voting power at the time of making the
@@ -5328,7 +5629,15 @@ impl Governance {
                 // Not eligible due to dissolve delay.
                 continue;
             }
-            let power = v.voting_power(now_seconds);
+            // Making a proposal refreshes the voting power of the
+            // proposer, so it votes with its full voting power. All
+            // other neurons vote with their deciding voting power,
+            // which is reduced if they have been inactive for long.
+            let power = if *k == proposer_id.id {
+                v.voting_power(now_seconds)
+            } else {
+                v.deciding_voting_power(voting_power_economics, now_seconds)
+            };
             total_power += power as u128;
             electoral_roll.insert(
                 *k,
@@ -5375,6 +5684,7 @@ impl Governance {
             proposal_timestamp_seconds: now_seconds,
             ballots: electoral_roll,
             original_total_community_fund_maturity_e8s_equivalent,
+            payload_text_rendering,
             ..Default::default()
         };
 
@@ -5388,11 +5698,14 @@ impl Governance {
         // - It prevents a neuron from having too many proposals outstanding.
         // - It reduces the voting power of the submitter so that for every proposal
         //   outstanding the submitter will have less voting power to get it approved.
-        self.proto
+        let proposer = self
+            .proto
             .neurons
             .get_mut(&proposer_id.id)
-            .expect("Proposer not found.")
-            .neuron_fees_e8s += info.reject_cost_e8s;
+            .expect("Proposer not found.");
+        proposer.neuron_fees_e8s += info.reject_cost_e8s;
+        // Making a proposal counts as voting directly.
+        proposer.refresh_voting_power(now_seconds);
 
         // Cast self-vote, including following.
         Governance::cast_vote_and_cascade_follow(
@@ -5548,6 +5861,7 @@ impl Governance {
         caller: &PrincipalId,
         pb: &manage_neuron::RegisterVote,
     ) -> Result<(), GovernanceError> {
+        let now_seconds = self.env.now();
         let neuron = self.proto.neurons.get_mut(&neuron_id.id).ok_or_else(||
             // The specified neuron is not present.
             GovernanceError::new_with_message(ErrorType::NotFound, "Neuron not found"))?;
@@ -5604,6 +5918,11 @@ impl Governance {
             );
         }
 
+        // Voting directly refreshes the voting power of the neuron.
+        if let Some(neuron) = self.proto.neurons.get_mut(&neuron_id.id) {
+            neuron.refresh_voting_power(now_seconds);
+        }
+
         self.process_proposal(proposal_id.id);
 
         Ok(())
@@ -5624,6 +5943,7 @@ impl Governance {
         // The implementation of this method is complicated by the
         // fact that we have to maintain a reverse index of all follow
         // relationships, i.e., the `topic_followee_index`.
+        let now_seconds = self.env.now();
 
         // Find the neuron to modify.
         let neuron = self.proto.neurons.get_mut(&id.id).ok_or_else(||
@@ -5702,6 +6022,7 @@ impl Governance {
                     let all_followers = cache.entry(followee.id).or_insert_with(BTreeSet::new);
                     all_followers.insert(id.id);
                 }
+                neuron.refresh_voting_power(now_seconds);
                 Ok(())
             } else {
                 // Attempt to follow for an invalid topic: the set
@@ -5715,10 +6036,33 @@ impl Governance {
         } else {
             // This operation clears the followees for the given topic.
             neuron.followees.remove(&f.topic);
+            neuron.refresh_voting_power(now_seconds);
             Ok(())
         }
     }
 
+    /// Refresh the voting power of a neuron, so that its deciding
+    /// voting power is not reduced, and its following is not cleared,
+    /// due to inactivity.
+    ///
+    /// The caller must be the controller or a hot key of the neuron.
+    fn refresh_voting_power(
+        &mut self,
+        id: &NeuronId,
+        caller: &PrincipalId,
+    ) -> Result<(), GovernanceError> {
+        let now_seconds = self.env.now();
+        let neuron = self.get_neuron_mut(id)?;
+        if !neuron.is_authorized_to_vote(caller) {
+            return Err(GovernanceError::new_with_message(
+                ErrorType::NotAuthorized,
+                "Caller is not authorized to refresh the voting power of the neuron.",
+            ));
+        }
+        neuron.refresh_voting_power(now_seconds);
+        Ok(())
+    }
+
     fn configure_neuron(
         &mut self,
         id: &NeuronId,
@@ -5939,6 +6283,7 @@ impl Governance {
             joined_community_fund_timestamp_seconds: None,
             known_neuron_data: None,
             spawn_at_timestamp_seconds: None,
+            voting_power_refreshed_timestamp_seconds: None,
         };
 
         // This also verifies that there are not too many neurons already.
@@ -6182,6 +6527,9 @@ impl Governance {
             Some(manage_neuron::Command::RegisterVote(v)) => self
                 .register_vote(&id, caller, v)
                 .map(|_| ManageNeuronResponse::register_vote_response()),
+            Some(manage_neuron::Command::RefreshVotingPower(_)) => self
+                .refresh_voting_power(&id, caller)
+                .map(|_| ManageNeuronResponse::refresh_voting_power_response()),
             Some(manage_neuron::Command::ClaimOrRefresh(_)) => {
                 panic!("This should have already returned")
             }
@@ -6273,6 +6621,9 @@ impl Governance {
                 Ok(supply) => {
                     if self.should_distribute_rewards() {
                         self.distribute_rewards(supply);
+                        // Inactive neurons are checked at the same (daily)
+                        // frequency as rewards are distributed.
+                        self.start_clearing_following_of_inactive_neurons();
                     }
                 }
                 Err(e) => println!(
@@ -6305,6 +6656,7 @@ impl Governance {
             self.spawn_neurons().await;
         }
 
+        self.clear_following_of_inactive_neurons();
         self.maybe_gc();
     }
 
@@ -6493,6 +6845,82 @@ impl Governance {
         self.env.now() >= reward_available_at
     }
 
+    /// Starts a pass over all neurons that clears the following of the
+    /// inactive ones, see [Governance::clear_following_of_inactive_neurons].
+    /// A pass that is still in progress is restarted.
+    fn start_clearing_following_of_inactive_neurons(&mut self) {
+        if self.voting_power_economics().is_none() {
+            return;
+        }
+        self.neurons_to_check_for_inactive_following = self.proto.neurons.keys().copied().collect();
+    }
+
+    /// Clears the following of neurons that have been inactive for so
+    /// long that their deciding voting power has dropped to zero (see
+    /// [Neuron::deciding_voting_power]).
+    ///
+    /// Following on the NeuronManagement topic is kept, as it
+    /// determines who can manage the neuron rather than how it votes.
+    ///
+    /// To stay within the instruction limit of a single message, at most
+    /// [MAX_NEURONS_TO_CHECK_FOR_INACTIVE_FOLLOWING] neurons of the current
+    /// pass are checked per call.
+    fn clear_following_of_inactive_neurons(&mut self) {
+        if self.neurons_to_check_for_inactive_following.is_empty() {
+            return;
+        }
+        let voting_power_economics = match self.voting_power_economics() {
+            Some(voting_power_economics) => voting_power_economics.clone(),
+            None => {
+                self.neurons_to_check_for_inactive_following.clear();
+                return;
+            }
+        };
+        let batch_start = self
+            .neurons_to_check_for_inactive_following
+            .len()
+            .saturating_sub(MAX_NEURONS_TO_CHECK_FOR_INACTIVE_FOLLOWING);
+        let batch = self
+            .neurons_to_check_for_inactive_following
+            .split_off(batch_start);
+        let now_seconds = self.env.now();
+        let mut num_cleared: usize = 0;
+        for neuron_id in batch {
+            // The neuron may have been removed since the pass started.
+            let neuron = match self.proto.neurons.get_mut(&neuron_id) {
+                Some(neuron) => neuron,
+                None => continue,
+            };
+            let has_voting_followees = neuron
+                .followees
+                .keys()
+                .any(|topic| *topic != Topic::NeuronManagement as i32);
+            if !has_voting_followees
+                || !neuron.is_inactive_for_clearing_following(&voting_power_economics, now_seconds)
+            {
+                continue;
+            }
+            GovernanceProto::remove_neuron_from_topic_followee_index(
+                &mut self.topic_followee_index,
+                neuron,
+            );
+            neuron
+                .followees
+                .retain(|topic, _| *topic == Topic::NeuronManagement as i32);
+            GovernanceProto::add_neuron_to_topic_followee_index(
+                &mut self.topic_followee_index,
+                neuron,
+            );
+            num_cleared += 1;
+        }
+        if num_cleared > 0 {
+            println!(
+                "{}Cleared the following of {} inactive neurons.",
+                LOG_PREFIX, num_cleared
+            );
+        }
+    }
+
     /// Create a reward event.
     ///
     /// This method:
@@ -6947,6 +7375,49 @@ fn draw_funds_from_the_community_fund(
     result
 }
 
+/// Returns the voting power economics that result from applying the
+/// `proposed` ones of a `ManageNetworkEconomics` proposal to the `current`
+/// ones, where `None` means that the voting power of neurons is not reduced.
+///
+/// Only the non-zero fields of `proposed` are applied, and the result must
+/// have both fields set, as a zero value would reduce the deciding voting
+/// power of all neurons to zero.
+fn merge_voting_power_economics(
+    current: Option<&VotingPowerEconomics>,
+    proposed: &VotingPowerEconomics,
+) -> Result<Option<VotingPowerEconomics>, String> {
+    if proposed.disable {
+        if proposed.start_reducing_voting_power_after_seconds != 0
+            || proposed.clear_following_after_seconds != 0
+        {
+            return Err(
+                "Voting power economics that disable the reduction of voting power must not set any other field"
+                    .to_string(),
+            );
+        }
+        return Ok(None);
+    }
+
+    let mut merged = current.cloned().unwrap_or_default();
+    if proposed.start_reducing_voting_power_after_seconds != 0 {
+        merged.start_reducing_voting_power_after_seconds =
+            proposed.start_reducing_voting_power_after_seconds;
+    }
+    if proposed.clear_following_after_seconds != 0 {
+        merged.clear_following_after_seconds = proposed.clear_following_after_seconds;
+    }
+    if merged.start_reducing_voting_power_after_seconds == 0
+        || merged.clear_following_after_seconds == 0
+    {
+        return Err(format!(
+            "Both start_reducing_voting_power_after_seconds and clear_following_after_seconds \
+             must be non-zero, but the proposal would result in {:?}",
+            merged
+        ));
+    }
+    Ok(Some(merged))
+}
+
 fn validate_motion(motion: &Motion) -> Result<(), GovernanceError> {
     if motion.motion_text.len() > PROPOSAL_MOTION_TEXT_BYTES_MAX {
         return Err(GovernanceError::new_with_message(
@@ -131,6 +131,7 @@
 /// subnetworks that participate in the Internet Computer (IC).
 pub mod governance;
 pub mod init;
+pub mod nns_function_payload;
 pub mod pb;
 pub mod proposal_submission;
 mod reward;
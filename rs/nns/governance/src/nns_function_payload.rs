@@ -0,0 +1,328 @@
+//! Decoding and rendering of the candid payloads carried by
+//! `ExecuteNnsFunction` proposals.
+//!
+//! The payload of an `ExecuteNnsFunction` proposal is an opaque blob of
+//! candid-encoded bytes that is eventually passed as the argument of the
+//! method returned by `NnsFunction::canister_and_function`. In order for
+//! voters to see what a proposal actually does, governance decodes the
+//! payload at submission time into the type that the target method expects,
+//! rejects the proposal if this fails, and stores a human-readable rendering
+//! of the decoded payload in the `ProposalData`.
+use crate::pb::v1::NnsFunction;
+
+use candid::{CandidType, Decode};
+use cycles_minting_canister::SetAuthorizedSubnetworkListArgs;
+use ic_crypto_sha::Sha256;
+use ic_nervous_system_root::{
+    AddCanisterProposal, CanisterIdRecord, ChangeCanisterProposal, StopOrStartCanisterProposal,
+};
+use ic_nns_common::types::UpdateIcpXdrConversionRatePayload;
+use ic_protobuf::registry::{
+    dc::v1::AddOrRemoveDataCentersProposalPayload, node_operator::v1::RemoveNodeOperatorsPayload,
+    node_rewards::v2::UpdateNodeRewardsTableProposalPayload,
+};
+use registry_canister::mutations::{
+    complete_canister_migration::CompleteCanisterMigrationPayload,
+    do_add_node_operator::AddNodeOperatorPayload,
+    do_add_nodes_to_subnet::AddNodesToSubnetPayload,
+    do_bless_replica_version::BlessReplicaVersionPayload,
+    do_change_subnet_membership::ChangeSubnetMembershipPayload,
+    do_create_subnet::CreateSubnetPayload,
+    do_recover_subnet::RecoverSubnetPayload,
+    do_remove_nodes_from_subnet::RemoveNodesFromSubnetPayload,
+    do_set_firewall_config::SetFirewallConfigPayload,
+    do_update_node_operator_config::UpdateNodeOperatorConfigPayload,
+    do_update_subnet::UpdateSubnetPayload,
+    do_update_subnet_replica::UpdateSubnetReplicaVersionPayload,
+    do_update_unassigned_nodes_config::UpdateUnassignedNodesConfigPayload,
+    firewall::{AddFirewallRulesPayload, RemoveFirewallRulesPayload, UpdateFirewallRulesPayload},
+    node_management::do_remove_nodes::RemoveNodesPayload,
+    prepare_canister_migration::PrepareCanisterMigrationPayload,
+    reroute_canister_ranges::RerouteCanisterRangesPayload,
+};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::fmt;
+
+/// The payload of a proposal to upgrade the root canister.
+///
+/// The "authoritative" data structure is the one defined in `lifeline.mo` and
+/// this should stay in sync with it.
+#[derive(CandidType, Deserialize)]
+pub struct UpgradeRootProposalPayload {
+    pub wasm_module: Vec<u8>,
+    pub module_arg: Vec<u8>,
+    pub stop_upgrade_start: bool,
+}
+
+impl fmt::Debug for UpgradeRootProposalPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UpgradeRootProposalPayload")
+            .field("stop_upgrade_start", &self.stop_upgrade_start)
+            .field("wasm_module_sha256", &sha256_hex(&self.wasm_module))
+            .field("module_arg_sha256", &sha256_hex(&self.module_arg))
+            .finish()
+    }
+}
+
+/// The subset of `ic_sns_wasm::pb::v1::SnsWasm` that governance needs in
+/// order to render an `AddSnsWasm` proposal.
+#[derive(CandidType, Deserialize)]
+pub struct SnsWasm {
+    pub wasm: Vec<u8>,
+    pub canister_type: i32,
+}
+
+/// The subset of `ic_sns_wasm::pb::v1::AddWasmRequest` that governance needs
+/// in order to render an `AddSnsWasm` proposal.
+///
+/// Candid allows decoding a record into a type with fewer fields, so this
+/// keeps working if fields are added to the request in SNS-W.
+#[derive(CandidType, Deserialize)]
+pub struct AddWasmRequest {
+    pub wasm: Option<SnsWasm>,
+    pub hash: Vec<u8>,
+}
+
+impl fmt::Debug for AddWasmRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("AddWasmRequest");
+        if let Some(wasm) = &self.wasm {
+            debug
+                .field("canister_type", &wasm.canister_type)
+                .field("wasm_sha256", &sha256_hex(&wasm.wasm));
+        }
+        debug.field("hash", &hex_encode(&self.hash)).finish()
+    }
+}
+
+/// Decodes `payload` as the argument expected by the canister method that
+/// `nns_function` calls, and returns a displayable text rendering of it.
+///
+/// Returns an error if the payload cannot be decoded, in which case the
+/// proposal would fail at execution time anyway.
+pub fn validate_and_render_nns_function_payload(
+    nns_function: NnsFunction,
+    payload: &[u8],
+) -> Result<String, String> {
+    let rendered_payload = match nns_function {
+        NnsFunction::Unspecified => {
+            return Err("The NNS function of the proposal is unspecified".to_string())
+        }
+        NnsFunction::AssignNoid => decode_and_render::<AddNodeOperatorPayload>(payload),
+        NnsFunction::CreateSubnet => decode_and_render::<CreateSubnetPayload>(payload),
+        NnsFunction::AddNodeToSubnet => decode_and_render::<AddNodesToSubnetPayload>(payload),
+        NnsFunction::RemoveNodesFromSubnet => {
+            decode_and_render::<RemoveNodesFromSubnetPayload>(payload)
+        }
+        NnsFunction::ChangeSubnetMembership => {
+            decode_and_render::<ChangeSubnetMembershipPayload>(payload)
+        }
+        NnsFunction::NnsCanisterInstall => decode_and_render::<AddCanisterProposal>(payload),
+        NnsFunction::NnsCanisterUpgrade => decode_and_render::<ChangeCanisterProposal>(payload),
+        NnsFunction::NnsRootUpgrade => decode_and_render::<UpgradeRootProposalPayload>(payload),
+        NnsFunction::RecoverSubnet => decode_and_render::<RecoverSubnetPayload>(payload),
+        NnsFunction::BlessReplicaVersion => {
+            decode_and_render::<BlessReplicaVersionPayload>(payload)
+        }
+        NnsFunction::UpdateNodeOperatorConfig => {
+            decode_and_render::<UpdateNodeOperatorConfigPayload>(payload)
+        }
+        NnsFunction::UpdateSubnetReplicaVersion => {
+            decode_and_render::<UpdateSubnetReplicaVersionPayload>(payload)
+        }
+        NnsFunction::UpdateConfigOfSubnet => decode_and_render::<UpdateSubnetPayload>(payload),
+        NnsFunction::IcpXdrConversionRate => {
+            decode_and_render::<UpdateIcpXdrConversionRatePayload>(payload)
+        }
+        NnsFunction::ClearProvisionalWhitelist => decode_and_render::<()>(payload),
+        NnsFunction::SetAuthorizedSubnetworks => {
+            decode_and_render::<SetAuthorizedSubnetworkListArgs>(payload)
+        }
+        NnsFunction::SetFirewallConfig => decode_and_render::<SetFirewallConfigPayload>(payload),
+        NnsFunction::AddFirewallRules => decode_and_render::<AddFirewallRulesPayload>(payload),
+        NnsFunction::RemoveFirewallRules => {
+            decode_and_render::<RemoveFirewallRulesPayload>(payload)
+        }
+        NnsFunction::UpdateFirewallRules => {
+            decode_and_render::<UpdateFirewallRulesPayload>(payload)
+        }
+        NnsFunction::StopOrStartNnsCanister => {
+            decode_and_render::<StopOrStartCanisterProposal>(payload)
+        }
+        NnsFunction::RemoveNodes => decode_and_render::<RemoveNodesPayload>(payload),
+        NnsFunction::UninstallCode => decode_and_render::<CanisterIdRecord>(payload),
+        NnsFunction::UpdateNodeRewardsTable => {
+            decode_and_render::<UpdateNodeRewardsTableProposalPayload>(payload)
+        }
+        NnsFunction::AddOrRemoveDataCenters => {
+            decode_and_render::<AddOrRemoveDataCentersProposalPayload>(payload)
+        }
+        NnsFunction::UpdateUnassignedNodesConfig => {
+            decode_and_render::<UpdateUnassignedNodesConfigPayload>(payload)
+        }
+        NnsFunction::RemoveNodeOperators => {
+            decode_and_render::<RemoveNodeOperatorsPayload>(payload)
+        }
+        NnsFunction::RerouteCanisterRanges => {
+            decode_and_render::<RerouteCanisterRangesPayload>(payload)
+        }
+        NnsFunction::PrepareCanisterMigration => {
+            decode_and_render::<PrepareCanisterMigrationPayload>(payload)
+        }
+        NnsFunction::CompleteCanisterMigration => {
+            decode_and_render::<CompleteCanisterMigrationPayload>(payload)
+        }
+        NnsFunction::AddSnsWasm => decode_and_render::<AddWasmRequest>(payload),
+    }?;
+
+    Ok(format!(
+        r"# Proposal to execute NNS function {:?}:
+
+## Payload:
+
+{}",
+        nns_function, rendered_payload
+    ))
+}
+
+/// Decodes `payload` as a single candid value of type `T` and renders it
+/// using its (pretty-printed) `Debug` implementation.
+fn decode_and_render<T>(payload: &[u8]) -> Result<String, String>
+where
+    T: CandidType + DeserializeOwned + fmt::Debug,
+{
+    Decode!(payload, T)
+        .map(|decoded| format!("{:#?}", decoded))
+        .map_err(|e| {
+            format!(
+                "The payload could not be decoded into a {}: {}",
+                short_type_name::<T>(),
+                e
+            )
+        })
+}
+
+fn short_type_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    let mut state = Sha256::new();
+    state.write(bytes);
+    hex_encode(&state.finish())
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::Encode;
+    use ic_base_types::{CanisterId, PrincipalId};
+    use ic_nervous_system_root::CanisterAction;
+
+    #[test]
+    fn renders_update_icp_xdr_conversion_rate() {
+        let payload = Encode!(&UpdateIcpXdrConversionRatePayload {
+            data_source: "the data source".to_string(),
+            timestamp_seconds: 111_222_333,
+            xdr_permyriad_per_icp: 9256,
+        })
+        .unwrap();
+
+        let rendering =
+            validate_and_render_nns_function_payload(NnsFunction::IcpXdrConversionRate, &payload)
+                .unwrap();
+
+        assert!(rendering.contains("IcpXdrConversionRate"), "{}", rendering);
+        assert!(rendering.contains("9256"), "{}", rendering);
+        assert!(rendering.contains("the data source"), "{}", rendering);
+    }
+
+    #[test]
+    fn renders_clear_provisional_whitelist() {
+        let payload = Encode!(&()).unwrap();
+
+        assert!(validate_and_render_nns_function_payload(
+            NnsFunction::ClearProvisionalWhitelist,
+            &payload
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn renders_wasm_modules_as_hashes() {
+        let payload = Encode!(&UpgradeRootProposalPayload {
+            wasm_module: vec![0, 0x61, 0x73, 0x6d, 1, 0, 0, 0],
+            module_arg: vec![],
+            stop_upgrade_start: true,
+        })
+        .unwrap();
+
+        let rendering =
+            validate_and_render_nns_function_payload(NnsFunction::NnsRootUpgrade, &payload)
+                .unwrap();
+
+        assert!(rendering.contains("wasm_module_sha256"), "{}", rendering);
+        assert!(!rendering.contains("wasm_module:"), "{}", rendering);
+    }
+
+    #[test]
+    fn renders_stop_or_start_nns_canister() {
+        let payload = Encode!(&StopOrStartCanisterProposal {
+            canister_id: CanisterId::new(PrincipalId::new_user_test_id(42)).unwrap(),
+            action: CanisterAction::Stop,
+        })
+        .unwrap();
+
+        let rendering =
+            validate_and_render_nns_function_payload(NnsFunction::StopOrStartNnsCanister, &payload)
+                .unwrap();
+
+        assert!(rendering.contains("Stop"), "{}", rendering);
+    }
+
+    #[test]
+    fn rejects_payload_of_the_wrong_type() {
+        let payload = Encode!(&UpdateIcpXdrConversionRatePayload::default()).unwrap();
+
+        let error =
+            validate_and_render_nns_function_payload(NnsFunction::UpdateConfigOfSubnet, &payload)
+                .unwrap_err();
+
+        assert!(
+            error.contains("could not be decoded into a UpdateSubnetPayload"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn rejects_garbage_payload() {
+        for nns_function in [
+            NnsFunction::CreateSubnet,
+            NnsFunction::NnsCanisterUpgrade,
+            NnsFunction::RerouteCanisterRanges,
+            NnsFunction::AddSnsWasm,
+        ] {
+            assert!(
+                validate_and_render_nns_function_payload(nns_function, &[1, 2, 3]).is_err(),
+                "{:?}",
+                nns_function
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unspecified_nns_function() {
+        assert!(validate_and_render_nns_function_payload(
+            NnsFunction::Unspecified,
+            &Encode!(&()).unwrap()
+        )
+        .is_err());
+    }
+}
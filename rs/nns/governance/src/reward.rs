@@ -96,6 +96,36 @@ pub fn rewards_pool_to_distribute_in_supply_fraction_for_one_day(
     rate * ONE_DAY
 }
 
+/// Computes the voting power that a neuron uses to decide proposals, and
+/// hence that is recorded in its ballots and counts towards its share of the
+/// voting rewards.
+///
+/// `potential_voting_power` is the neuron's voting power computed from its
+/// stake, dissolve delay and age. It is fully available for
+/// `start_reducing_after_seconds` after the neuron last refreshed its voting
+/// power; afterwards, it decreases linearly and reaches zero
+/// `clear_following_after_seconds` later.
+///
+/// Unlike the rest of this module, this uses integer arithmetic, as voting
+/// power is not a fraction of the ICP supply and must be exact.
+pub fn deciding_voting_power(
+    potential_voting_power: u64,
+    seconds_since_refresh: u64,
+    start_reducing_after_seconds: u64,
+    clear_following_after_seconds: u64,
+) -> u64 {
+    let seconds_reducing = seconds_since_refresh.saturating_sub(start_reducing_after_seconds);
+    if seconds_reducing == 0 {
+        return potential_voting_power;
+    }
+    if seconds_reducing >= clear_following_after_seconds {
+        return 0;
+    }
+    let remaining_seconds = (clear_following_after_seconds - seconds_reducing) as u128;
+    ((potential_voting_power as u128) * remaining_seconds / (clear_following_after_seconds as u128))
+        as u64
+}
+
 // ---- REAL-CODE ENDS HERE ---------------------------------------------
 
 // Explication for the implementation of
@@ -264,4 +294,28 @@ mod test {
             assert!(a + c > 2.0 * b);
         }
     }
+
+    #[test]
+    fn deciding_voting_power_decreases_linearly_after_inactivity() {
+        let start = 100;
+        let clear = 40;
+        assert_eq!(deciding_voting_power(1000, 0, start, clear), 1000);
+        assert_eq!(deciding_voting_power(1000, start, start, clear), 1000);
+        assert_eq!(deciding_voting_power(1000, start + 10, start, clear), 750);
+        assert_eq!(deciding_voting_power(1000, start + 20, start, clear), 500);
+        assert_eq!(deciding_voting_power(1000, start + 39, start, clear), 25);
+        assert_eq!(deciding_voting_power(1000, start + 40, start, clear), 0);
+        assert_eq!(deciding_voting_power(1000, u64::MAX, start, clear), 0);
+    }
+
+    #[test]
+    fn deciding_voting_power_drops_to_zero_without_clearing_period() {
+        assert_eq!(deciding_voting_power(1000, 100, 100, 0), 1000);
+        assert_eq!(deciding_voting_power(1000, 101, 100, 0), 0);
+    }
+
+    #[test]
+    fn deciding_voting_power_does_not_overflow() {
+        assert_eq!(deciding_voting_power(u64::MAX, 50, 0, 100), u64::MAX / 2);
+    }
 }
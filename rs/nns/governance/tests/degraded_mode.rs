@@ -2,10 +2,14 @@
 //! the heap cannot grow very much.
 use assert_matches::assert_matches;
 use async_trait::async_trait;
+use candid::Encode;
 use futures::future::FutureExt;
 use ic_base_types::{CanisterId, PrincipalId};
+use ic_ic00_types::CanisterInstallMode;
 use ic_nervous_system_common::{ledger::Ledger, NervousSystemError};
+use ic_nervous_system_root::ChangeCanisterProposal;
 use ic_nns_common::pb::v1::NeuronId;
+use ic_nns_constants::GOVERNANCE_CANISTER_ID;
 use ic_nns_governance::{
     governance::{Environment, Governance, CMC},
     pb::v1::{
@@ -179,7 +183,12 @@ fn test_can_submit_nns_canister_upgrade_in_degraded_mode() {
                 summary: "proposal 1".to_string(),
                 action: Some(proposal::Action::ExecuteNnsFunction(ExecuteNnsFunction {
                     nns_function: NnsFunction::NnsCanisterUpgrade as i32,
-                    payload: Vec::new(),
+                    payload: Encode!(&ChangeCanisterProposal::new(
+                        true,
+                        CanisterInstallMode::Upgrade,
+                        GOVERNANCE_CANISTER_ID,
+                    ))
+                    .unwrap(),
                 })),
                 ..Default::default()
             },
@@ -12,9 +12,11 @@ use dfn_protobuf::ToProto;
 use futures::future::FutureExt;
 use ic_base_types::{CanisterId, PrincipalId};
 use ic_crypto_sha::Sha256;
+use ic_ic00_types::CanisterInstallMode;
 use ic_nervous_system_common_test_keys::{
     TEST_NEURON_1_OWNER_PRINCIPAL, TEST_NEURON_2_OWNER_PRINCIPAL,
 };
+use ic_nervous_system_root::ChangeCanisterProposal;
 use ic_nns_common::pb::v1::{NeuronId, ProposalId};
 use ic_nns_common::types::UpdateIcpXdrConversionRatePayload;
 use ic_nns_constants::GOVERNANCE_CANISTER_ID;
@@ -31,10 +33,11 @@ use ic_nns_governance::{
     governance::{
         subaccount_from_slice, validate_proposal_title, Environment, Governance,
         HeapGrowthPotential, EXECUTE_NNS_FUNCTION_PAYLOAD_LISTING_BYTES_MAX,
-        MAX_DISSOLVE_DELAY_SECONDS, MAX_NEURON_AGE_FOR_AGE_BONUS,
-        MAX_NUMBER_OF_PROPOSALS_WITH_BALLOTS, MIN_DISSOLVE_DELAY_FOR_VOTE_ELIGIBILITY_SECONDS,
-        ONE_DAY_SECONDS, ONE_YEAR_SECONDS, PROPOSAL_MOTION_TEXT_BYTES_MAX,
-        REWARD_DISTRIBUTION_PERIOD_SECONDS, WAIT_FOR_QUIET_DEADLINE_INCREASE_SECONDS,
+        MAX_DISSOLVE_DELAY_SECONDS, MAX_NEURONS_TO_CHECK_FOR_INACTIVE_FOLLOWING,
+        MAX_NEURON_AGE_FOR_AGE_BONUS, MAX_NUMBER_OF_PROPOSALS_WITH_BALLOTS,
+        MIN_DISSOLVE_DELAY_FOR_VOTE_ELIGIBILITY_SECONDS, ONE_DAY_SECONDS, ONE_YEAR_SECONDS,
+        PROPOSAL_MOTION_TEXT_BYTES_MAX, REWARD_DISTRIBUTION_PERIOD_SECONDS,
+        WAIT_FOR_QUIET_DEADLINE_INCREASE_SECONDS,
     },
     init::GovernanceCanisterInitPayloadBuilder,
     pb::v1::{
@@ -54,19 +57,20 @@ use ic_nns_governance::{
             NeuronIdOrSubaccount, SetDissolveTimestamp, Spawn, Split, StartDissolving,
         },
         manage_neuron_response::{self, Command as CommandResponse, MergeMaturityResponse},
+        network_economics::VotingPowerEconomics,
         neuron::{self, DissolveState, Followees},
         proposal::{self, Action},
         reward_node_provider::{RewardMode, RewardToAccount, RewardToNeuron},
         AddOrRemoveNodeProvider, Ballot, BallotInfo, Empty, ExecuteNnsFunction,
         Governance as GovernanceProto, GovernanceError, KnownNeuron, KnownNeuronData, ListNeurons,
-        ListNeuronsResponse, ListProposalInfo, ManageNeuron, Motion, NetworkEconomics, Neuron,
-        NeuronState, NnsFunction, NodeProvider, OpenSnsTokenSwap, Proposal, ProposalData,
-        ProposalRewardStatus,
+        ListNeuronsResponse, ListProposalInfo, ManageNeuron, ManageNeuronResponse, Motion,
+        NetworkEconomics, Neuron, NeuronState, NnsFunction, NodeProvider, OpenSnsTokenSwap,
+        Proposal, ProposalData, ProposalRewardStatus,
         ProposalRewardStatus::{AcceptVotes, ReadyToSettle},
         ProposalStatus,
         ProposalStatus::Rejected,
-        RewardEvent, RewardNodeProvider, RewardNodeProviders, SetDefaultFollowees, Tally, Topic,
-        UpdateNodeProvider, Vote,
+        QueryNeurons, RewardEvent, RewardNodeProvider, RewardNodeProviders, SetDefaultFollowees,
+        Tally, Topic, UpdateNodeProvider, Vote,
     },
 };
 use ic_sns_swap::pb::v1 as sns_swap_pb;
@@ -192,16 +196,21 @@ fn test_single_neuron_proposal_new() {
             NNSStateChange::GovernanceProto(vec![
                 GovernanceChange::Neurons(vec![MapChange::Changed(
                     0,
-                    vec![NeuronChange::RecentBallots(vec![VecChange::Added(
-                        0,
-                        vec![
-                            BallotInfoChange::ProposalId(OptionChange::Different(
-                                None,
-                                Some(ProposalId { id: 1 }),
-                            )),
-                            BallotInfoChange::Vote(I32Change(0, 1)),
-                        ],
-                    )])],
+                    vec![
+                        NeuronChange::RecentBallots(vec![VecChange::Added(
+                            0,
+                            vec![
+                                BallotInfoChange::ProposalId(OptionChange::Different(
+                                    None,
+                                    Some(ProposalId { id: 1 }),
+                                )),
+                                BallotInfoChange::Vote(I32Change(0, 1)),
+                            ],
+                        )]),
+                        NeuronChange::VotingPowerRefreshedTimestampSeconds(
+                            OptionChange::Different(None, Some(999111000)),
+                        ),
+                    ],
                 )]),
                 GovernanceChange::Proposals(vec![MapChange::Added(
                     1,
@@ -770,6 +779,10 @@ fn test_cascade_following_new() {
                             BallotInfoChange::Vote(I32Change(0, 1)),
                         ],
                     )]),
+                    NeuronChange::VotingPowerRefreshedTimestampSeconds(OptionChange::Different(
+                        None,
+                        Some(999111000),
+                    )),
                 ],
             )]),
             GovernanceChange::Proposals(vec![MapChange::Added(
@@ -902,16 +915,22 @@ fn test_cascade_following_new() {
         Changed::Changed(vec![NNSStateChange::GovernanceProto(vec![
             GovernanceChange::Neurons(vec![MapChange::Changed(
                 5,
-                vec![NeuronChange::RecentBallots(vec![VecChange::Added(
-                    0,
-                    vec![
-                        BallotInfoChange::ProposalId(OptionChange::Different(
-                            None,
-                            Some(ProposalId { id: 1 }),
-                        )),
-                        BallotInfoChange::Vote(I32Change(0, 1)),
-                    ],
-                )])],
+                vec![
+                    NeuronChange::RecentBallots(vec![VecChange::Added(
+                        0,
+                        vec![
+                            BallotInfoChange::ProposalId(OptionChange::Different(
+                                None,
+                                Some(ProposalId { id: 1 }),
+                            )),
+                            BallotInfoChange::Vote(I32Change(0, 1)),
+                        ],
+                    )]),
+                    NeuronChange::VotingPowerRefreshedTimestampSeconds(OptionChange::Different(
+                        None,
+                        Some(999111000),
+                    )),
+                ],
             )]),
             GovernanceChange::Proposals(vec![MapChange::Changed(
                 1,
@@ -1005,16 +1024,21 @@ fn test_cascade_following_new() {
                 ),
                 MapChange::Changed(
                     6,
-                    vec![NeuronChange::RecentBallots(vec![VecChange::Added(
-                        0,
-                        vec![
-                            BallotInfoChange::ProposalId(OptionChange::Different(
-                                None,
-                                Some(ProposalId { id: 1 }),
-                            )),
-                            BallotInfoChange::Vote(I32Change(0, 1)),
-                        ],
-                    )])],
+                    vec![
+                        NeuronChange::RecentBallots(vec![VecChange::Added(
+                            0,
+                            vec![
+                                BallotInfoChange::ProposalId(OptionChange::Different(
+                                    None,
+                                    Some(ProposalId { id: 1 }),
+                                )),
+                                BallotInfoChange::Vote(I32Change(0, 1)),
+                            ],
+                        )]),
+                        NeuronChange::VotingPowerRefreshedTimestampSeconds(
+                            OptionChange::Different(None, Some(999111000)),
+                        ),
+                    ],
                 ),
             ]),
             GovernanceChange::Proposals(vec![MapChange::Changed(
@@ -1334,6 +1358,350 @@ fn test_minimum_icp_xdr_conversion_rate() {
     .unwrap();
 }
 
+/// Tests that the payload of an ExecuteNnsFunction proposal is decoded when
+/// the proposal is made, that malformed payloads are rejected, and that the
+/// rendering of the payload is exposed in the proposal info.
+#[test]
+fn test_execute_nns_function_payload_is_validated_and_rendered() {
+    let driver = fake::FakeDriver::default();
+    let mut gov = Governance::new(
+        fixture_for_following(),
+        driver.get_fake_env(),
+        driver.get_fake_ledger(),
+        driver.get_fake_cmc(),
+    );
+
+    // A payload that is not a candid-encoded UpdateSubnetPayload is rejected.
+    let err = gov
+        .make_proposal(
+            &NeuronId { id: 1 },
+            // Must match neuron 1's serialized_id.
+            &PrincipalId::try_from(b"SID1".to_vec()).unwrap(),
+            &Proposal {
+                title: Some("A Reasonable Title".to_string()),
+                summary: "test".to_string(),
+                action: Some(proposal::Action::ExecuteNnsFunction(ExecuteNnsFunction {
+                    nns_function: NnsFunction::UpdateConfigOfSubnet as i32,
+                    payload: Encode!(&UpdateIcpXdrConversionRatePayload::default()).unwrap(),
+                })),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err.error_type, ErrorType::InvalidProposal as i32);
+    assert!(
+        err.error_message.contains("UpdateSubnetPayload"),
+        "{}",
+        err.error_message
+    );
+
+    // A well-formed payload is accepted and rendered.
+    let pid = gov
+        .make_proposal(
+            &NeuronId { id: 1 },
+            // Must match neuron 1's serialized_id.
+            &PrincipalId::try_from(b"SID1".to_vec()).unwrap(),
+            &Proposal {
+                title: Some("A Reasonable Title".to_string()),
+                summary: "test".to_string(),
+                action: Some(proposal::Action::ExecuteNnsFunction(ExecuteNnsFunction {
+                    nns_function: NnsFunction::IcpXdrConversionRate as i32,
+                    payload: Encode!(&UpdateIcpXdrConversionRatePayload {
+                        xdr_permyriad_per_icp: 9256,
+                        data_source: "the data source".to_string(),
+                        timestamp_seconds: 111_222_333,
+                    })
+                    .unwrap(),
+                })),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let rendering = gov
+        .get_proposal_info(&PrincipalId::new_anonymous(), pid)
+        .unwrap()
+        .payload_text_rendering
+        .unwrap();
+    assert!(rendering.contains("9256"), "{}", rendering);
+    assert!(rendering.contains("the data source"), "{}", rendering);
+
+    let listed = gov.list_proposals(
+        &PrincipalId::new_anonymous(),
+        &ListProposalInfo {
+            limit: 10,
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        listed.proposal_info[0].payload_text_rendering,
+        Some(rendering)
+    );
+}
+
+/// Neurons that have not refreshed their voting power for longer than
+/// `start_reducing_voting_power_after_seconds` get ballots with reduced
+/// voting power, down to zero after `clear_following_after_seconds` more.
+#[test]
+fn test_deciding_voting_power_of_inactive_neurons_is_reduced() {
+    let mut driver = fake::FakeDriver::default();
+    driver.advance_time_by(1000);
+    let now = driver.now();
+
+    let mut fixture = fixture_for_following();
+    fixture.economics.as_mut().unwrap().voting_power_economics = Some(VotingPowerEconomics {
+        start_reducing_voting_power_after_seconds: 100,
+        clear_following_after_seconds: 40,
+        ..Default::default()
+    });
+    for neuron in fixture.neurons.values_mut() {
+        neuron.voting_power_refreshed_timestamp_seconds = Some(now);
+    }
+    // Neuron 4 is half-way through the reduction period, while neuron 9
+    // (and the proposer, neuron 1) have been inactive for long.
+    fixture
+        .neurons
+        .get_mut(&4)
+        .unwrap()
+        .voting_power_refreshed_timestamp_seconds = Some(now - 120);
+    fixture
+        .neurons
+        .get_mut(&9)
+        .unwrap()
+        .voting_power_refreshed_timestamp_seconds = Some(now - 1000);
+    fixture
+        .neurons
+        .get_mut(&1)
+        .unwrap()
+        .voting_power_refreshed_timestamp_seconds = Some(now - 1000);
+
+    let mut gov = Governance::new(
+        fixture,
+        driver.get_fake_env(),
+        driver.get_fake_ledger(),
+        driver.get_fake_cmc(),
+    );
+    // All neurons in the fixture have the same potential voting power.
+    let full_voting_power = gov
+        .get_neuron(&NeuronId { id: 5 })
+        .unwrap()
+        .voting_power(now);
+    assert_eq!(
+        gov.get_neuron_info(&NeuronId { id: 4 })
+            .unwrap()
+            .deciding_voting_power,
+        Some(full_voting_power / 2)
+    );
+
+    let pid = gov
+        .make_proposal(
+            &NeuronId { id: 1 },
+            &principal(1),
+            &Proposal {
+                title: Some("A Reasonable Title".to_string()),
+                summary: "test".to_string(),
+                action: Some(proposal::Action::Motion(Motion {
+                    motion_text: "me like proposals".to_string(),
+                })),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let ballots = &gov.get_proposal_data(pid).unwrap().ballots;
+    // Making a proposal refreshes the voting power of the proposer.
+    assert_eq!(ballots[&1].voting_power, full_voting_power);
+    assert_eq!(ballots[&5].voting_power, full_voting_power);
+    assert_eq!(ballots[&4].voting_power, full_voting_power / 2);
+    assert_eq!(ballots[&9].voting_power, 0);
+    assert_eq!(
+        gov.get_neuron(&NeuronId { id: 1 })
+            .unwrap()
+            .voting_power_refreshed_timestamp_seconds,
+        Some(now)
+    );
+}
+
+#[test]
+fn test_refresh_voting_power() {
+    let mut driver = fake::FakeDriver::default();
+    let mut gov = Governance::new(
+        fixture_for_following(),
+        driver.get_fake_env(),
+        driver.get_fake_ledger(),
+        driver.get_fake_cmc(),
+    );
+    driver.advance_time_by(42);
+
+    let refresh = ManageNeuron {
+        id: None,
+        neuron_id_or_subaccount: Some(NeuronIdOrSubaccount::NeuronId(NeuronId { id: 5 })),
+        command: Some(manage_neuron::Command::RefreshVotingPower(
+            manage_neuron::RefreshVotingPower {},
+        )),
+    };
+
+    // Only the controller or a hot key can refresh the voting power.
+    let response = gov
+        .manage_neuron(&principal(6), &refresh)
+        .now_or_never()
+        .unwrap();
+    assert_matches!(
+        response.command,
+        Some(CommandResponse::Error(err)) if err.error_type == NotAuthorized as i32
+    );
+    assert_eq!(
+        gov.get_neuron(&NeuronId { id: 5 })
+            .unwrap()
+            .voting_power_refreshed_timestamp_seconds,
+        None
+    );
+
+    let response = gov
+        .manage_neuron(&principal(5), &refresh)
+        .now_or_never()
+        .unwrap();
+    assert_eq!(
+        response,
+        ManageNeuronResponse::refresh_voting_power_response()
+    );
+    assert_eq!(
+        gov.get_neuron(&NeuronId { id: 5 })
+            .unwrap()
+            .voting_power_refreshed_timestamp_seconds,
+        Some(driver.now())
+    );
+    assert_eq!(
+        gov.get_neuron_info(&NeuronId { id: 5 })
+            .unwrap()
+            .voting_power_refreshed_timestamp_seconds,
+        Some(driver.now())
+    );
+}
+
+/// Once the deciding voting power of a neuron has dropped to zero, its
+/// following is cleared, except on the NeuronManagement topic.
+#[test]
+fn test_following_of_inactive_neurons_is_cleared() {
+    let mut driver = fake::FakeDriver::default();
+    let mut fixture = fixture_for_following();
+    fixture.economics.as_mut().unwrap().voting_power_economics = Some(VotingPowerEconomics {
+        start_reducing_voting_power_after_seconds: REWARD_DISTRIBUTION_PERIOD_SECONDS,
+        clear_following_after_seconds: REWARD_DISTRIBUTION_PERIOD_SECONDS / 2,
+        ..Default::default()
+    });
+    for neuron in fixture.neurons.values_mut() {
+        neuron.voting_power_refreshed_timestamp_seconds = Some(driver.now());
+    }
+    fixture.neurons.get_mut(&2).unwrap().controller = Some(principal(2));
+    fixture.neurons.get_mut(&3).unwrap().followees.insert(
+        Topic::NeuronManagement as i32,
+        Followees {
+            followees: vec![NeuronId { id: 1 }],
+        },
+    );
+    let mut gov = Governance::new(
+        fixture,
+        driver.get_fake_env(),
+        driver.get_fake_ledger(),
+        driver.get_fake_cmc(),
+    );
+
+    driver.advance_time_by(2 * REWARD_DISTRIBUTION_PERIOD_SECONDS);
+    // Neuron 2 stays active by setting its following again.
+    gov.manage_neuron(
+        &principal(2),
+        &ManageNeuron {
+            id: None,
+            neuron_id_or_subaccount: Some(NeuronIdOrSubaccount::NeuronId(NeuronId { id: 2 })),
+            command: Some(manage_neuron::Command::Follow(Follow {
+                topic: Topic::NetworkEconomics as i32,
+                followees: vec![NeuronId { id: 1 }],
+            })),
+        },
+    )
+    .now_or_never()
+    .unwrap();
+    gov.run_periodic_tasks().now_or_never();
+
+    assert_eq!(
+        gov.get_neuron(&NeuronId { id: 2 })
+            .unwrap()
+            .followees
+            .keys()
+            .collect::<Vec<_>>(),
+        vec![&(Topic::NetworkEconomics as i32)]
+    );
+    assert_eq!(
+        gov.get_neuron(&NeuronId { id: 3 })
+            .unwrap()
+            .followees
+            .keys()
+            .collect::<Vec<_>>(),
+        vec![&(Topic::NeuronManagement as i32)]
+    );
+    // The followee index no longer lists neuron 3 as following neuron 5.
+    assert!(!gov
+        .topic_followee_index
+        .get(&Topic::Unspecified)
+        .and_then(|followers_by_followee| followers_by_followee.get(&5))
+        .map_or(false, |followers| followers.contains(&3)));
+    assert!(gov.topic_followee_index[&Topic::NeuronManagement][&1].contains(&3));
+}
+
+/// The following of inactive neurons is cleared in batches, over several
+/// calls of `run_periodic_tasks`.
+#[test]
+fn test_following_of_inactive_neurons_is_cleared_in_batches() {
+    let mut driver = fake::FakeDriver::default();
+    let mut fixture = fixture_for_following();
+    fixture.economics.as_mut().unwrap().voting_power_economics = Some(VotingPowerEconomics {
+        start_reducing_voting_power_after_seconds: REWARD_DISTRIBUTION_PERIOD_SECONDS,
+        clear_following_after_seconds: REWARD_DISTRIBUTION_PERIOD_SECONDS / 2,
+        ..Default::default()
+    });
+    let follower = fixture.neurons[&2].clone();
+    for id in 100..100 + 2 * MAX_NEURONS_TO_CHECK_FOR_INACTIVE_FOLLOWING as u64 {
+        let mut neuron = follower.clone();
+        neuron.id = Some(NeuronId { id });
+        neuron.account = id.to_be_bytes().to_vec();
+        fixture.neurons.insert(id, neuron);
+    }
+    for neuron in fixture.neurons.values_mut() {
+        neuron.voting_power_refreshed_timestamp_seconds = Some(driver.now());
+    }
+    let mut gov = Governance::new(
+        fixture,
+        driver.get_fake_env(),
+        driver.get_fake_ledger(),
+        driver.get_fake_cmc(),
+    );
+    let num_following = |gov: &Governance| {
+        gov.proto
+            .neurons
+            .values()
+            .filter(|neuron| {
+                neuron
+                    .followees
+                    .keys()
+                    .any(|topic| *topic != Topic::NeuronManagement as i32)
+            })
+            .count()
+    };
+    let num_following_before = num_following(&gov);
+
+    driver.advance_time_by(2 * REWARD_DISTRIBUTION_PERIOD_SECONDS);
+    gov.run_periodic_tasks().now_or_never();
+    // Only the first batch of neurons has been checked.
+    let num_following_after_first_batch = num_following(&gov);
+    assert!(num_following_after_first_batch < num_following_before);
+    assert!(num_following_after_first_batch >= MAX_NEURONS_TO_CHECK_FOR_INACTIVE_FOLLOWING);
+
+    gov.run_periodic_tasks().now_or_never();
+    gov.run_periodic_tasks().now_or_never();
+    assert_eq!(num_following(&gov), 0);
+}
+
 #[test]
 fn test_node_provider_must_be_registered() {
     let driver = fake::FakeDriver::default();
@@ -3313,7 +3681,7 @@ fn create_mature_neuron(dissolved: bool) -> (fake::FakeDriver, Governance, Neuro
         // The neuron state should now be "Dissolved", meaning we can
         // now disburse the neuron.
         assert_eq!(
-            neuron.get_neuron_info(driver.now()).state(),
+            neuron.get_neuron_info(driver.now(), None).state(),
             NeuronState::Dissolved
         );
     } else {
@@ -4132,7 +4500,7 @@ fn test_neuron_split_fails() {
         .neuron_minimum_stake_e8s;
 
     assert_eq!(
-        neuron.get_neuron_info(driver.now()).state(),
+        neuron.get_neuron_info(driver.now(), None).state(),
         NeuronState::NotDissolving
     );
 
@@ -4237,7 +4605,7 @@ fn test_neuron_split() {
     let transaction_fee = gov.proto.economics.as_ref().unwrap().transaction_fee_e8s;
 
     assert_eq!(
-        neuron.get_neuron_info(driver.now()).state(),
+        neuron.get_neuron_info(driver.now(), None).state(),
         NeuronState::NotDissolving
     );
 
@@ -5062,7 +5430,7 @@ fn test_neuron_spawn() {
     let neuron = gov.get_neuron_mut(&id).expect("Neuron did not exist");
 
     assert_eq!(
-        neuron.get_neuron_info(driver.now()).state(),
+        neuron.get_neuron_info(driver.now(), None).state(),
         NeuronState::NotDissolving
     );
 
@@ -5225,7 +5593,7 @@ fn test_neuron_spawn_with_subaccount() {
     let neuron = gov.get_neuron_mut(&id).expect("Neuron did not exist");
 
     assert_eq!(
-        neuron.get_neuron_info(driver.now()).state(),
+        neuron.get_neuron_info(driver.now(), None).state(),
         NeuronState::NotDissolving
     );
 
@@ -5399,7 +5767,7 @@ fn assert_neuron_spawn_partial(
 
     let neuron = gov.get_neuron_mut(&id).expect("Neuron did not exist");
     assert_eq!(
-        neuron.get_neuron_info(driver.now()).state(),
+        neuron.get_neuron_info(driver.now(), None).state(),
         NeuronState::NotDissolving
     );
 
@@ -5597,7 +5965,7 @@ fn test_disburse_to_neuron() {
     // The neuron state should now be "Dissolved", meaning we can
     // now disburse the neuron.
     assert_eq!(
-        parent_neuron.get_neuron_info(driver.now()).state(),
+        parent_neuron.get_neuron_info(driver.now(), None).state(),
         NeuronState::Dissolved
     );
 
@@ -6728,6 +7096,130 @@ fn test_network_economics_proposal() {
     );
 }
 
+/// Proposals that would leave either field of the voting power economics at
+/// zero are rejected, as that would reduce the deciding voting power of all
+/// neurons to zero.
+#[test]
+fn test_voting_power_economics_proposals() {
+    let p = match std::env::var("NEURON_CSV_PATH") {
+        Ok(v) => PathBuf::from(v),
+        Err(_) => PathBuf::from("tests/neurons.csv"),
+    };
+    let mut builder = GovernanceCanisterInitPayloadBuilder::new();
+    let init_neurons = &mut builder.add_all_neurons_from_csv_file(&p).proto.neurons;
+
+    let voter_pid = *init_neurons[&42].controller.as_ref().unwrap();
+    let voter_neuron = init_neurons[&42].id.as_ref().unwrap().clone();
+    init_neurons.get_mut(&42).unwrap().dissolve_state = Some(DissolveState::DissolveDelaySeconds(
+        MIN_DISSOLVE_DELAY_FOR_VOTE_ELIGIBILITY_SECONDS,
+    ));
+    let (_, mut gov) =
+        governance_with_neurons(&init_neurons.values().cloned().collect::<Vec<Neuron>>());
+
+    let propose = |gov: &mut Governance, voting_power_economics: VotingPowerEconomics| {
+        gov.manage_neuron(
+            &voter_pid,
+            &ManageNeuron {
+                id: None,
+                neuron_id_or_subaccount: Some(NeuronIdOrSubaccount::NeuronId(voter_neuron.clone())),
+                command: Some(manage_neuron::Command::MakeProposal(Box::new(Proposal {
+                    title: Some("Voting power economics change".to_string()),
+                    summary: "Change the voting power economics.".to_string(),
+                    url: "".to_string(),
+                    action: Some(proposal::Action::ManageNetworkEconomics(NetworkEconomics {
+                        voting_power_economics: Some(voting_power_economics),
+                        ..Default::default()
+                    })),
+                }))),
+            },
+        )
+        .now_or_never()
+        .unwrap()
+        .command
+        .unwrap()
+    };
+
+    // Turning the reduction on requires both fields.
+    assert_matches!(
+        propose(
+            &mut gov,
+            VotingPowerEconomics {
+                start_reducing_voting_power_after_seconds: 100,
+                ..Default::default()
+            }
+        ),
+        manage_neuron_response::Command::Error(err)
+            if err.error_type == ErrorType::InvalidProposal as i32
+    );
+    assert_matches!(
+        propose(
+            &mut gov,
+            VotingPowerEconomics {
+                start_reducing_voting_power_after_seconds: 100,
+                clear_following_after_seconds: 40,
+                ..Default::default()
+            }
+        ),
+        manage_neuron_response::Command::MakeProposal(_)
+    );
+    assert_eq!(
+        gov.proto.economics.as_ref().unwrap().voting_power_economics,
+        Some(VotingPowerEconomics {
+            start_reducing_voting_power_after_seconds: 100,
+            clear_following_after_seconds: 40,
+            ..Default::default()
+        })
+    );
+
+    // Once it is on, a single field can be changed.
+    assert_matches!(
+        propose(
+            &mut gov,
+            VotingPowerEconomics {
+                clear_following_after_seconds: 50,
+                ..Default::default()
+            }
+        ),
+        manage_neuron_response::Command::MakeProposal(_)
+    );
+    assert_eq!(
+        gov.proto.economics.as_ref().unwrap().voting_power_economics,
+        Some(VotingPowerEconomics {
+            start_reducing_voting_power_after_seconds: 100,
+            clear_following_after_seconds: 50,
+            ..Default::default()
+        })
+    );
+
+    // Disabling the reduction must not set other fields.
+    assert_matches!(
+        propose(
+            &mut gov,
+            VotingPowerEconomics {
+                clear_following_after_seconds: 50,
+                disable: true,
+                ..Default::default()
+            }
+        ),
+        manage_neuron_response::Command::Error(err)
+            if err.error_type == ErrorType::InvalidProposal as i32
+    );
+    assert_matches!(
+        propose(
+            &mut gov,
+            VotingPowerEconomics {
+                disable: true,
+                ..Default::default()
+            }
+        ),
+        manage_neuron_response::Command::MakeProposal(_)
+    );
+    assert_eq!(
+        gov.proto.economics.as_ref().unwrap().voting_power_economics,
+        None
+    );
+}
+
 #[test]
 fn test_default_followees() {
     let p = match std::env::var("NEURON_CSV_PATH") {
@@ -7762,6 +8254,174 @@ fn test_list_neurons() {
     );
 }
 
+// Test that querying neurons pages through all neurons in order of
+// their IDs, and that the dissolve state, stake and followee filters
+// are applied.
+#[test]
+fn test_query_neurons() {
+    let driver = fake::FakeDriver::default();
+    // Create 30 neurons with IDs 1-30, where the neurons with an even ID
+    // are dissolving, and neuron N has a stake of N ICP.
+    let mut proto = GovernanceProto {
+        neurons: (1..=30)
+            .map(|id| {
+                let dissolve_state = if id % 2 == 0 {
+                    DissolveState::WhenDissolvedTimestampSeconds(driver.now() + ONE_YEAR_SECONDS)
+                } else {
+                    DissolveState::DissolveDelaySeconds(ONE_YEAR_SECONDS)
+                };
+                (
+                    id,
+                    Neuron {
+                        id: Some(NeuronId { id }),
+                        cached_neuron_stake_e8s: id * 100_000_000,
+                        dissolve_state: Some(dissolve_state),
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect::<HashMap<u64, Neuron>>(),
+        ..Default::default()
+    };
+    // Neurons 10-14 follow neuron 1 on the governance topic, and neuron 12
+    // also follows neuron 1 on the network economics topic.
+    for id in 10..=14 {
+        proto.neurons.get_mut(&id).unwrap().followees = hashmap! {
+            Topic::Governance as i32 => Followees {
+                followees: vec![NeuronId { id: 1 }],
+            },
+        };
+    }
+    proto.neurons.get_mut(&12).unwrap().followees.insert(
+        Topic::NetworkEconomics as i32,
+        Followees {
+            followees: vec![NeuronId { id: 1 }],
+        },
+    );
+    proto.neurons.get_mut(&3).unwrap().controller = Some(principal(1));
+    let gov = Governance::new(
+        proto,
+        driver.get_fake_env(),
+        driver.get_fake_ledger(),
+        driver.get_fake_cmc(),
+    );
+    let query_ids = |req: QueryNeurons| -> Vec<u64> {
+        let mut ids: Vec<u64> = gov
+            .query_neurons(&req, &principal(1))
+            .neuron_infos
+            .keys()
+            .cloned()
+            .collect();
+        ids.sort_unstable();
+        ids
+    };
+
+    // Page through all neurons, 7 at a time.
+    let mut all_ids = Vec::new();
+    let mut start_after_neuron_id = None;
+    loop {
+        let response = gov.query_neurons(
+            &QueryNeurons {
+                start_after_neuron_id,
+                limit: 7,
+                ..Default::default()
+            },
+            &principal(1),
+        );
+        assert!(response.neuron_infos.len() <= 7);
+        assert!(response.full_neurons.is_empty());
+        let mut ids: Vec<u64> = response.neuron_infos.keys().cloned().collect();
+        ids.sort_unstable();
+        all_ids.extend(ids);
+        match response.next_start_after_neuron_id {
+            Some(id) => {
+                assert_eq!(Some(&id), all_ids.last());
+                start_after_neuron_id = Some(id);
+            }
+            None => break,
+        }
+    }
+    assert_eq!(all_ids, (1..=30).collect::<Vec<u64>>());
+
+    // Filter by stake.
+    assert_eq!(
+        query_ids(QueryNeurons {
+            min_stake_e8s: Some(5 * 100_000_000),
+            max_stake_e8s: Some(8 * 100_000_000),
+            ..Default::default()
+        }),
+        vec![5, 6, 7, 8]
+    );
+
+    // Filter by dissolve state, starting after neuron 20.
+    assert_eq!(
+        query_ids(QueryNeurons {
+            start_after_neuron_id: Some(20),
+            include_dissolve_states: vec![NeuronState::Dissolving as i32],
+            ..Default::default()
+        }),
+        vec![22, 24, 26, 28, 30]
+    );
+
+    // Filter by followee, on any topic and on a specific topic.
+    assert_eq!(
+        query_ids(QueryNeurons {
+            followee: Some(NeuronId { id: 1 }),
+            ..Default::default()
+        }),
+        vec![10, 11, 12, 13, 14]
+    );
+    assert_eq!(
+        query_ids(QueryNeurons {
+            followee: Some(NeuronId { id: 1 }),
+            followee_topic: Some(Topic::NetworkEconomics as i32),
+            ..Default::default()
+        }),
+        vec![12]
+    );
+    assert_eq!(
+        query_ids(QueryNeurons {
+            followee: Some(NeuronId { id: 2 }),
+            ..Default::default()
+        }),
+        Vec::<u64>::new()
+    );
+
+    // Only the full neurons the caller is authorized to read are returned.
+    let response = gov.query_neurons(
+        &QueryNeurons {
+            limit: 5,
+            include_full_neurons_readable_by_caller: true,
+            ..Default::default()
+        },
+        &principal(1),
+    );
+    assert_eq!(response.neuron_infos.len(), 5);
+    assert_eq!(response.next_start_after_neuron_id, Some(5));
+    assert_eq!(
+        response
+            .full_neurons
+            .iter()
+            .map(|n| n.id.as_ref().unwrap().id)
+            .collect::<Vec<u64>>(),
+        vec![3]
+    );
+
+    // A followee topic without a followee is rejected.
+    let response = gov.query_neurons(
+        &QueryNeurons {
+            followee_topic: Some(Topic::Governance as i32),
+            ..Default::default()
+        },
+        &principal(1),
+    );
+    assert_eq!(
+        response.error.map(|e| e.error_type),
+        Some(ErrorType::InvalidCommand as i32)
+    );
+    assert!(response.neuron_infos.is_empty());
+}
+
 #[test]
 fn test_max_number_of_proposals_with_ballots() {
     let mut fake_driver = fake::FakeDriver::default();
@@ -7822,7 +8482,12 @@ fn test_max_number_of_proposals_with_ballots() {
                 summary: "NnsCanisterUpgrade should go through despite the limit".to_string(),
                 action: Some(proposal::Action::ExecuteNnsFunction(ExecuteNnsFunction {
                     nns_function: NnsFunction::NnsCanisterUpgrade as i32,
-                    payload: Vec::new(),
+                    payload: Encode!(&ChangeCanisterProposal::new(
+                        true,
+                        CanisterInstallMode::Upgrade,
+                        GOVERNANCE_CANISTER_ID,
+                    ))
+                    .unwrap(),
                 })),
                 ..Default::default()
             },
@@ -183,7 +183,7 @@ async fn main() {
 
         let agent = if args.use_hsm {
             let sender = make_hsm_sender(&args.hsm_slot, &args.key_id, &args.pin);
-            Agent::new(url.clone(), sender)
+            Agent::new(url.clone(), sender).with_insecure_skip_certificate_verification()
         } else {
             // Use the special identity that has superpowers, like calling
             // ic00::Method::ProvisionalCreateCanisterWithCycles.
@@ -191,6 +191,7 @@ async fn main() {
                 url.clone(),
                 Sender::from_keypair(&ic_test_identity::TEST_IDENTITY_KEYPAIR),
             )
+            .with_insecure_skip_certificate_verification()
         };
 
         // Don't let the "Test" distract you -- the RemoteTestRuntime is simply a
@@ -12,19 +12,23 @@ use ic_ic00_types::{
     CreateCanisterArgs, InstallCodeArgs, Method, UpdateSettingsArgs,
 };
 use ic_nns_constants::GOVERNANCE_CANISTER_ID;
+use ic_sns_governance::pb::v1::{GetRunningSnsVersionRequest, GetRunningSnsVersionResponse};
+use ic_sns_root::pb::v1::{ListSnsCanistersRequest, ListSnsCanistersResponse};
 use ic_sns_wasm::canister_api::CanisterApi;
 use ic_sns_wasm::canister_stable_memory::CanisterStableMemory;
 use ic_sns_wasm::init::SnsWasmCanisterInitPayload;
 use ic_sns_wasm::pb::v1::{
     AddWasmRequest, AddWasmResponse, DeployNewSnsRequest, DeployNewSnsResponse,
     GetNextSnsVersionRequest, GetNextSnsVersionResponse, GetWasmRequest, GetWasmResponse,
-    ListDeployedSnsesRequest, ListDeployedSnsesResponse,
+    ListDeployedSnsesRequest, ListDeployedSnsesResponse, ListUpgradeStepsRequest,
+    ListUpgradeStepsResponse, RecordSnsUpgradeRequest, RecordSnsUpgradeResponse,
 };
 use ic_sns_wasm::sns_wasm::SnsWasmCanister;
 use ic_types::{CanisterId, Cycles};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::convert::TryInto;
+use std::time::SystemTime;
 
 pub const LOG_PREFIX: &str = "[SNS-WASM] ";
 
@@ -47,6 +51,11 @@ impl CanisterApi for CanisterApiImpl {
         dfn_core::api::id()
     }
 
+    /// See CanisterApi::now_seconds
+    fn now_seconds(&self) -> u64 {
+        now_seconds()
+    }
+
     /// See CanisterApi::create_canister
     async fn create_canister(
         &self,
@@ -187,6 +196,51 @@ impl CanisterApi for CanisterApiImpl {
             target
         )))
     }
+
+    /// See CanisterApi::list_sns_canisters
+    async fn list_sns_canisters(
+        &self,
+        root_canister: CanisterId,
+    ) -> Result<ListSnsCanistersResponse, String> {
+        let response: Result<ListSnsCanistersResponse, (Option<i32>, String)> = dfn_core::call(
+            root_canister,
+            "list_sns_canisters",
+            candid_one,
+            ListSnsCanistersRequest {},
+        )
+        .await;
+
+        response.map_err(handle_call_error(format!(
+            "Failed to list the canisters of SNS root {}",
+            root_canister
+        )))
+    }
+
+    /// See CanisterApi::get_running_sns_version
+    async fn get_running_sns_version(
+        &self,
+        governance_canister: CanisterId,
+    ) -> Result<GetRunningSnsVersionResponse, String> {
+        let response: Result<GetRunningSnsVersionResponse, (Option<i32>, String)> = dfn_core::call(
+            governance_canister,
+            "get_running_sns_version",
+            candid_one,
+            GetRunningSnsVersionRequest {},
+        )
+        .await;
+
+        response.map_err(handle_call_error(format!(
+            "Failed to get the running version of SNS governance {}",
+            governance_canister
+        )))
+    }
+}
+
+fn now_seconds() -> u64 {
+    dfn_core::api::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("Could not get the duration.")
+        .as_secs()
 }
 
 /// This handles the errors returned from dfn_core::call (and related methods)
@@ -304,6 +358,10 @@ fn canister_post_upgrade() {
 
     SNS_WASM.with(|c| c.replace(SnsWasmCanister::<CanisterStableMemory>::from_stable_memory()));
 
+    // Calls to other canisters cannot be made here, so deployed SNSes that were recorded
+    // without their canister IDs or upgrade history are backfilled by the first call of
+    // reconcile_deployed_snses after the upgrade.
+
     println!("{}Completed post upgrade", LOG_PREFIX);
 }
 
@@ -373,6 +431,44 @@ fn list_deployed_snses_(request: ListDeployedSnsesRequest) -> ListDeployedSnsesR
     SNS_WASM.with(|sns_wasm| sns_wasm.borrow().list_deployed_snses(request))
 }
 
+#[export_name = "canister_update record_sns_upgrade"]
+fn record_sns_upgrade() {
+    over(candid_one, record_sns_upgrade_)
+}
+
+#[candid_method(update, rename = "record_sns_upgrade")]
+fn record_sns_upgrade_(request: RecordSnsUpgradeRequest) -> RecordSnsUpgradeResponse {
+    SNS_WASM.with(|sns_wasm| {
+        sns_wasm
+            .borrow_mut()
+            .record_sns_upgrade(caller(), request, now_seconds())
+    })
+}
+
+/// Reconciles the deployed SNSes with the SNSes themselves. This calls into the SNS canisters,
+/// which SNS-W does not control, so it is an explicit method rather than a heartbeat: an
+/// unresponsive SNS only keeps the call context of this method open, not one that SNS-W opens
+/// on its own.
+#[export_name = "canister_update reconcile_deployed_snses"]
+fn reconcile_deployed_snses() {
+    over_async(candid_one, reconcile_deployed_snses_)
+}
+
+#[candid_method(update, rename = "reconcile_deployed_snses")]
+async fn reconcile_deployed_snses_(_: ()) {
+    SnsWasmCanister::reconcile_deployed_snses(&SNS_WASM, &canister_api(), now_seconds()).await
+}
+
+#[export_name = "canister_query list_upgrade_steps"]
+fn list_upgrade_steps() {
+    over(candid_one, list_upgrade_steps_)
+}
+
+#[candid_method(query, rename = "list_upgrade_steps")]
+fn list_upgrade_steps_(request: ListUpgradeStepsRequest) -> ListUpgradeStepsResponse {
+    SNS_WASM.with(|sns_wasm| sns_wasm.borrow().list_upgrade_steps(request))
+}
+
 /// This makes this Candid service self-describing, so that for example Candid
 /// UI, but also other tools, can seamlessly integrate with it.
 /// The concrete interface (__get_candid_interface_tmp_hack) is provisional, but
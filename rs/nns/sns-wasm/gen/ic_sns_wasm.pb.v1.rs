@@ -181,6 +181,31 @@ pub struct DeployedSns {
     /// PrincipalId of the root canister of the sns.
     #[prost(message, optional, tag = "1")]
     pub root_canister_id: ::core::option::Option<::ic_base_types::PrincipalId>,
+    /// PrincipalId of the governance canister of the sns.
+    #[prost(message, optional, tag = "2")]
+    pub governance_canister_id: ::core::option::Option<::ic_base_types::PrincipalId>,
+    /// PrincipalId of the ledger canister of the sns.
+    #[prost(message, optional, tag = "3")]
+    pub ledger_canister_id: ::core::option::Option<::ic_base_types::PrincipalId>,
+    /// PrincipalId of the swap canister of the sns.
+    #[prost(message, optional, tag = "4")]
+    pub swap_canister_id: ::core::option::Option<::ic_base_types::PrincipalId>,
+    /// The versions the sns has been running, oldest first. The first entry is the version the sns
+    /// was deployed with. Empty for SNSes deployed before upgrade history was recorded.
+    #[prost(message, repeated, tag = "5")]
+    pub upgrade_history: ::prost::alloc::vec::Vec<SnsUpgradeRecord>,
+}
+/// Records that an SNS started running a particular SnsVersion.
+#[derive(
+    candid::CandidType, candid::Deserialize, serde::Serialize, Clone, PartialEq, ::prost::Message,
+)]
+pub struct SnsUpgradeRecord {
+    /// The version the SNS is running from this point on.
+    #[prost(message, optional, tag = "1")]
+    pub version: ::core::option::Option<SnsVersion>,
+    /// The time at which the version was recorded, in seconds since the UNIX epoch.
+    #[prost(uint64, tag = "2")]
+    pub timestamp_seconds: u64,
 }
 /// Specifies the version of an SNS.
 #[derive(
@@ -226,6 +251,51 @@ pub struct GetNextSnsVersionResponse {
     #[prost(message, optional, tag = "1")]
     pub next_version: ::core::option::Option<SnsVersion>,
 }
+/// The request type accepted by the record_sns_upgrade canister method, which an SNS governance
+/// canister calls once it has finished upgrading its SNS to the next version.
+#[derive(
+    candid::CandidType, candid::Deserialize, serde::Serialize, Clone, PartialEq, ::prost::Message,
+)]
+pub struct RecordSnsUpgradeRequest {
+    /// The version the SNS is now running.
+    #[prost(message, optional, tag = "1")]
+    pub new_version: ::core::option::Option<SnsVersion>,
+}
+/// The response type returned by the record_sns_upgrade canister method.
+#[derive(
+    candid::CandidType, candid::Deserialize, serde::Serialize, Clone, PartialEq, ::prost::Message,
+)]
+pub struct RecordSnsUpgradeResponse {
+    /// Error when the request fails.
+    #[prost(message, optional, tag = "1")]
+    pub error: ::core::option::Option<SnsWasmError>,
+}
+/// The request type accepted by the list_upgrade_steps canister method.
+#[derive(
+    candid::CandidType, candid::Deserialize, serde::Serialize, Clone, PartialEq, ::prost::Message,
+)]
+pub struct ListUpgradeStepsRequest {
+    /// The version to start listing the upgrade path at. If not set, the version currently run by
+    /// the SNS with the given governance canister is used, or else the start of the upgrade path.
+    #[prost(message, optional, tag = "1")]
+    pub starting_at: ::core::option::Option<SnsVersion>,
+    /// The governance canister of the SNS whose current version to start at.
+    #[prost(message, optional, tag = "2")]
+    pub sns_governance_canister_id: ::core::option::Option<::ic_base_types::PrincipalId>,
+    /// The maximum number of steps to return. If 0, all steps are returned.
+    #[prost(uint32, tag = "3")]
+    pub limit: u32,
+}
+/// The response type returned by the list_upgrade_steps canister method.
+#[derive(
+    candid::CandidType, candid::Deserialize, serde::Serialize, Clone, PartialEq, ::prost::Message,
+)]
+pub struct ListUpgradeStepsResponse {
+    /// The versions on the upgrade path, starting with the requested version and ending with the
+    /// latest version (unless limited). Empty if the starting version is not on the upgrade path.
+    #[prost(message, repeated, tag = "1")]
+    pub steps: ::prost::alloc::vec::Vec<SnsVersion>,
+}
 /// The type of canister a particular WASM is intended to be installed on.
 #[derive(
     candid::CandidType,
@@ -1,5 +1,7 @@
 use async_trait::async_trait;
 use ic_base_types::{PrincipalId, SubnetId};
+use ic_sns_governance::pb::v1::GetRunningSnsVersionResponse;
+use ic_sns_root::pb::v1::ListSnsCanistersResponse;
 use ic_types::{CanisterId, Cycles};
 
 #[async_trait]
@@ -7,6 +9,9 @@ pub trait CanisterApi {
     /// Get the CanisterId of the running canister
     fn local_canister_id(&self) -> CanisterId;
 
+    /// Get the current time in seconds since the UNIX epoch
+    fn now_seconds(&self) -> u64;
+
     /// Create a canister on a subnet with cycles assigned to a given controller.
     async fn create_canister(
         &self,
@@ -45,4 +50,16 @@ pub trait CanisterApi {
         target_canister: CanisterId,
         cycles: u64,
     ) -> Result<(), String>;
+
+    /// List the canisters of the SNS with the given root canister.
+    async fn list_sns_canisters(
+        &self,
+        root_canister: CanisterId,
+    ) -> Result<ListSnsCanistersResponse, String>;
+
+    /// Get the version run by the SNS with the given governance canister.
+    async fn get_running_sns_version(
+        &self,
+        governance_canister: CanisterId,
+    ) -> Result<GetRunningSnsVersionResponse, String>;
 }
@@ -4,3 +4,4 @@ pub mod init;
 pub mod pb;
 pub mod sns_wasm;
 pub mod stable_memory;
+pub mod wasm_metadata;
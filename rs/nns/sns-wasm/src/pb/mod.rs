@@ -8,6 +8,7 @@ use crate::stable_memory::SnsWasmStableMemory;
 use ic_base_types::CanisterId;
 use ic_cdk::api::stable::StableMemory;
 use ic_crypto_sha::Sha256;
+use ic_sns_governance::pb::v1::governance::Version;
 use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
 use std::fmt::{Display, Write};
@@ -80,6 +81,18 @@ impl From<SnsVersion> for GetNextSnsVersionResponse {
     }
 }
 
+impl From<Version> for SnsVersion {
+    fn from(version: Version) -> Self {
+        SnsVersion {
+            root_wasm_hash: version.root_wasm_hash,
+            governance_wasm_hash: version.governance_wasm_hash,
+            ledger_wasm_hash: version.ledger_wasm_hash,
+            swap_wasm_hash: version.swap_wasm_hash,
+            archive_wasm_hash: version.archive_wasm_hash,
+        }
+    }
+}
+
 impl Display for SnsVersion {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut versions_str = HashMap::<&str, String>::new();
@@ -156,6 +169,7 @@ impl<M: StableMemory + Clone + Default> From<StableCanisterState> for SnsWasmCan
             upgrade_path,
             stable_memory: SnsWasmStableMemory::<M>::default(),
             access_controls_enabled: stable_canister_state.access_controls_enabled,
+            last_reconciliation_timestamp_seconds: 0,
         }
     }
 }
@@ -3,21 +3,24 @@ use crate::pb::hash_to_hex_string;
 use crate::pb::v1::{
     add_wasm_response, AddWasmRequest, AddWasmResponse, DeployNewSnsRequest, DeployNewSnsResponse,
     DeployedSns, GetNextSnsVersionRequest, GetNextSnsVersionResponse, GetWasmRequest,
-    GetWasmResponse, ListDeployedSnsesRequest, ListDeployedSnsesResponse, SnsCanisterIds,
-    SnsCanisterType, SnsVersion, SnsWasm, SnsWasmError, SnsWasmStableIndex, StableCanisterState,
+    GetWasmResponse, ListDeployedSnsesRequest, ListDeployedSnsesResponse, ListUpgradeStepsRequest,
+    ListUpgradeStepsResponse, RecordSnsUpgradeRequest, RecordSnsUpgradeResponse, SnsCanisterIds,
+    SnsCanisterType, SnsUpgradeRecord, SnsVersion, SnsWasm, SnsWasmError, SnsWasmStableIndex,
+    StableCanisterState,
 };
 use crate::stable_memory::SnsWasmStableMemory;
+use crate::wasm_metadata::validate_sns_wasm;
 use candid::Encode;
 #[cfg(target_arch = "wasm32")]
 use dfn_core::println;
-use ic_base_types::CanisterId;
+use ic_base_types::{CanisterId, PrincipalId};
 use ic_cdk::api::stable::StableMemory;
 use ic_nns_constants::ROOT_CANISTER_ID;
 use ic_sns_governance::pb::v1::governance::Version;
 use ic_sns_init::SnsCanisterInitPayloads;
 use ic_types::{Cycles, SubnetId};
 use std::cell::RefCell;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::TryInto;
 use std::iter::zip;
 use std::thread::LocalKey;
@@ -43,11 +46,17 @@ where
     /// If true, updates (e.g. add_wasm) can only be made by NNS Governance
     /// (via proposal execution), otherwise updates can be made by any caller
     pub access_controls_enabled: bool,
+    /// When the deployed SNSes were last reconciled. This is not persisted, so that they can be
+    /// reconciled right after every upgrade.
+    pub last_reconciliation_timestamp_seconds: u64,
 }
 const ONE_TRILLION: u64 = 1_000_000_000_000;
 const ONE_BILLION: u64 = 1_000_000_000;
 
 const SNS_CREATION_FEE: u64 = 50 * ONE_TRILLION;
+
+/// How often deployed SNSes are reconciled, see SnsWasmCanister::reconcile_deployed_snses.
+const RECONCILIATION_INTERVAL_SECONDS: u64 = 60 * 60;
 const INITIAL_CANISTER_CREATION_CYCLES: u64 = 500 * ONE_BILLION;
 
 /// Internal implementation to give the wasms we explicitly handle a name (instead of Vec<u8>) for
@@ -237,6 +246,16 @@ where
             };
         }
 
+        if let Err(message) = validate_sns_wasm(
+            &wasm,
+            sns_canister_type,
+            self.read_latest_wasm(sns_canister_type).as_ref(),
+        ) {
+            println!("{}add_wasm invalid WASM: {}", LOG_PREFIX, &message);
+
+            return AddWasmResponse::error(message);
+        }
+
         let result = match self.stable_memory.write_wasm(wasm) {
             Ok((offset, size)) => {
                 self.wasm_indexes.insert(
@@ -264,6 +283,22 @@ where
         AddWasmResponse { result }
     }
 
+    /// Read the WASM of the given type in the latest SNS version, if there is one.
+    fn read_latest_wasm(&self, canister_type: SnsCanisterType) -> Option<SnsWasm> {
+        let version = &self.upgrade_path.latest_version;
+        let hash = match canister_type {
+            SnsCanisterType::Unspecified => return None,
+            SnsCanisterType::Root => &version.root_wasm_hash,
+            SnsCanisterType::Governance => &version.governance_wasm_hash,
+            SnsCanisterType::Ledger => &version.ledger_wasm_hash,
+            SnsCanisterType::Swap => &version.swap_wasm_hash,
+            SnsCanisterType::Archive => &version.archive_wasm_hash,
+        };
+        vec_to_hash(hash.clone())
+            .ok()
+            .and_then(|hash| self.read_wasm(&hash))
+    }
+
     /// Returns a list of Deployed SNS root CanisterId's and the subnet they were deployed to.
     pub fn list_deployed_snses(
         &self,
@@ -356,11 +391,11 @@ where
             .build_canister_payloads(
                 &sns_init_canister_ids,
                 Some(Version {
-                    root_wasm_hash: latest_version.root_wasm_hash,
-                    governance_wasm_hash: latest_version.governance_wasm_hash,
-                    ledger_wasm_hash: latest_version.ledger_wasm_hash,
-                    swap_wasm_hash: latest_version.swap_wasm_hash,
-                    archive_wasm_hash: latest_version.archive_wasm_hash,
+                    root_wasm_hash: latest_version.root_wasm_hash.clone(),
+                    governance_wasm_hash: latest_version.governance_wasm_hash.clone(),
+                    ledger_wasm_hash: latest_version.ledger_wasm_hash.clone(),
+                    swap_wasm_hash: latest_version.swap_wasm_hash.clone(),
+                    archive_wasm_hash: latest_version.archive_wasm_hash.clone(),
                 }),
             )
             // NOTE: This error path is not under test, because validate(), called above, should
@@ -391,6 +426,13 @@ where
                 .deployed_sns_list
                 .push(DeployedSns {
                     root_canister_id: canisters.root,
+                    governance_canister_id: canisters.governance,
+                    ledger_canister_id: canisters.ledger,
+                    swap_canister_id: canisters.swap,
+                    upgrade_history: vec![SnsUpgradeRecord {
+                        version: Some(latest_version),
+                        timestamp_seconds: canister_api.now_seconds(),
+                    }],
                 })
         });
 
@@ -736,6 +778,233 @@ where
         GetNextSnsVersionResponse { next_version }
     }
 
+    /// Records that the SNS whose governance canister is `caller` now runs
+    /// `request.new_version`, which must be the next version on the upgrade path after the
+    /// version the SNS was last recorded to run.
+    pub fn record_sns_upgrade(
+        &mut self,
+        caller: PrincipalId,
+        request: RecordSnsUpgradeRequest,
+        now_seconds: u64,
+    ) -> RecordSnsUpgradeResponse {
+        let error = |message: String| {
+            println!("{}record_sns_upgrade failed: {}", LOG_PREFIX, &message);
+            RecordSnsUpgradeResponse {
+                error: Some(SnsWasmError { message }),
+            }
+        };
+
+        let new_version = match request.new_version {
+            Some(new_version) => new_version,
+            None => return error("new_version is a required field".to_string()),
+        };
+
+        let deployed_sns = match self
+            .deployed_sns_list
+            .iter_mut()
+            .find(|sns| sns.governance_canister_id == Some(caller))
+        {
+            Some(deployed_sns) => deployed_sns,
+            None => {
+                return error(format!(
+                    "Caller {} is not the governance canister of a deployed SNS",
+                    caller
+                ))
+            }
+        };
+
+        match deployed_sns
+            .upgrade_history
+            .last()
+            .and_then(|record| record.version.as_ref())
+        {
+            // Already recorded, e.g. by reconcile_deployed_snses.
+            Some(current_version) if *current_version == new_version => {
+                return RecordSnsUpgradeResponse { error: None }
+            }
+            Some(current_version)
+                if self.upgrade_path.upgrade_path.get(current_version) != Some(&new_version) =>
+            {
+                return error(format!(
+                    "Version {} is not the next version on the upgrade path after {}",
+                    new_version, current_version
+                ));
+            }
+            // Either the next version on the upgrade path, or the first version recorded for an
+            // SNS deployed before upgrade history was recorded.
+            _ => {}
+        }
+
+        deployed_sns.upgrade_history.push(SnsUpgradeRecord {
+            version: Some(new_version),
+            timestamp_seconds: now_seconds,
+        });
+
+        RecordSnsUpgradeResponse { error: None }
+    }
+
+    /// Reconciles the deployed SNSes with the SNSes themselves, at most once every
+    /// RECONCILIATION_INTERVAL_SECONDS (more frequent calls do nothing):
+    ///   1. SNSes deployed before their canister IDs were recorded get them from their root.
+    ///   2. The version run by each SNS is appended to its upgrade history, unless it is the last
+    ///      recorded version. This backfills the history of SNSes deployed before upgrade history
+    ///      was recorded, and of SNSes whose governance failed to call record_sns_upgrade.
+    pub async fn reconcile_deployed_snses(
+        thread_safe_sns: &'static LocalKey<RefCell<SnsWasmCanister<M>>>,
+        canister_api: &impl CanisterApi,
+        now_seconds: u64,
+    ) {
+        let deployed_snses: Vec<_> = thread_safe_sns.with(|sns_canister| {
+            let mut sns_canister = sns_canister.borrow_mut();
+            if sns_canister.last_reconciliation_timestamp_seconds + RECONCILIATION_INTERVAL_SECONDS
+                > now_seconds
+            {
+                return vec![];
+            }
+            sns_canister.last_reconciliation_timestamp_seconds = now_seconds;
+            sns_canister
+                .deployed_sns_list
+                .iter()
+                .map(|sns| (sns.root_canister_id, sns.governance_canister_id))
+                .enumerate()
+                .collect()
+        });
+
+        // Entries are never removed from deployed_sns_list, so indices stay valid across awaits.
+        for (index, (root_canister_id, governance_canister_id)) in deployed_snses {
+            let governance_canister_id = match governance_canister_id {
+                Some(governance_canister_id) => Some(governance_canister_id),
+                None => {
+                    Self::backfill_sns_canister_ids(
+                        thread_safe_sns,
+                        canister_api,
+                        index,
+                        root_canister_id,
+                    )
+                    .await
+                }
+            };
+            let governance_canister_id =
+                match governance_canister_id.and_then(|id| CanisterId::new(id).ok()) {
+                    Some(governance_canister_id) => governance_canister_id,
+                    None => continue,
+                };
+
+            match canister_api
+                .get_running_sns_version(governance_canister_id)
+                .await
+            {
+                Ok(response) => {
+                    if let Some(version) = response.deployed_version {
+                        thread_safe_sns.with(|sns_canister| {
+                            sns_canister.borrow_mut().record_running_version(
+                                index,
+                                version.into(),
+                                now_seconds,
+                            )
+                        });
+                    }
+                }
+                Err(message) => println!(
+                    "{}Could not reconcile the upgrade history of SNS governance {}: {}",
+                    LOG_PREFIX, governance_canister_id, message
+                ),
+            }
+        }
+    }
+
+    /// Sets the canister IDs of the deployed SNS at `index` to those listed by its root, and
+    /// returns its governance canister ID if successful.
+    async fn backfill_sns_canister_ids(
+        thread_safe_sns: &'static LocalKey<RefCell<SnsWasmCanister<M>>>,
+        canister_api: &impl CanisterApi,
+        index: usize,
+        root_canister_id: Option<PrincipalId>,
+    ) -> Option<PrincipalId> {
+        let root_canister_id = root_canister_id.and_then(|id| CanisterId::new(id).ok())?;
+        let canisters = match canister_api.list_sns_canisters(root_canister_id).await {
+            Ok(canisters) => canisters,
+            Err(message) => {
+                println!(
+                    "{}Could not backfill the canister IDs of SNS root {}: {}",
+                    LOG_PREFIX, root_canister_id, message
+                );
+                return None;
+            }
+        };
+
+        thread_safe_sns.with(|sns_canister| {
+            let deployed_sns = &mut sns_canister.borrow_mut().deployed_sns_list[index];
+            deployed_sns.governance_canister_id = canisters.governance;
+            deployed_sns.ledger_canister_id = canisters.ledger;
+            deployed_sns.swap_canister_id = canisters.swap;
+        });
+
+        canisters.governance
+    }
+
+    /// Appends `version` to the upgrade history of the deployed SNS at `index`, unless it is
+    /// the last recorded version.
+    fn record_running_version(&mut self, index: usize, version: SnsVersion, now_seconds: u64) {
+        let deployed_sns = &mut self.deployed_sns_list[index];
+        let last_version = deployed_sns
+            .upgrade_history
+            .last()
+            .and_then(|record| record.version.as_ref());
+        if last_version == Some(&version) {
+            return;
+        }
+
+        println!(
+            "{}Recording version {} for SNS governance {:?}",
+            LOG_PREFIX, version, deployed_sns.governance_canister_id
+        );
+        deployed_sns.upgrade_history.push(SnsUpgradeRecord {
+            version: Some(version),
+            timestamp_seconds: now_seconds,
+        });
+    }
+
+    /// Lists the versions on the upgrade path, starting at the requested version (see
+    /// ListUpgradeStepsRequest) and following the upgrade path up to the latest version.
+    pub fn list_upgrade_steps(&self, request: ListUpgradeStepsRequest) -> ListUpgradeStepsResponse {
+        let starting_at = request.starting_at.or_else(|| {
+            let governance_canister_id = request.sns_governance_canister_id?;
+            self.deployed_sns_list
+                .iter()
+                .find(|sns| sns.governance_canister_id == Some(governance_canister_id))
+                .and_then(|sns| sns.upgrade_history.last())
+                .and_then(|record| record.version.clone())
+        });
+        let starting_at = starting_at.unwrap_or_default();
+
+        let is_on_upgrade_path = starting_at == self.upgrade_path.latest_version
+            || self.upgrade_path.upgrade_path.contains_key(&starting_at);
+        if !is_on_upgrade_path {
+            return ListUpgradeStepsResponse { steps: vec![] };
+        }
+
+        let limit = match request.limit {
+            0 => usize::MAX,
+            limit => limit as usize,
+        };
+
+        // Adding a WASM that is already part of the latest version maps that version to itself,
+        // so we stop at the first version that was already listed.
+        let mut seen = HashSet::new();
+        let mut steps = vec![];
+        let mut next = Some(starting_at);
+        while let Some(version) = next {
+            if steps.len() >= limit || !seen.insert(version.clone()) {
+                break;
+            }
+            next = self.upgrade_path.upgrade_path.get(&version).cloned();
+            steps.push(version);
+        }
+
+        ListUpgradeStepsResponse { steps }
+    }
+
     /// Gets the latest/current SNS version in a human-readable format
     pub fn get_latest_sns_version_pretty(&self) -> HashMap<String, String> {
         let version = &self.upgrade_path.latest_version;
@@ -884,13 +1153,16 @@ mod test {
     use ic_crypto_sha::Sha256;
     use ic_icrc1_ledger::InitArgs as LedgerInitArgs;
     use ic_nns_constants::ROOT_CANISTER_ID;
+    use ic_sns_governance::pb::v1::GetRunningSnsVersionResponse;
     use ic_sns_init::pb::v1::SnsInitPayload;
+    use ic_sns_root::pb::v1::ListSnsCanistersResponse;
     use ic_test_utilities::types::ids::{canister_test_id, subnet_test_id};
     use pretty_assertions::{assert_eq, assert_ne};
     use std::sync::{Arc, Mutex};
     use std::vec;
 
     const CANISTER_CREATION_CYCLES: u64 = INITIAL_CANISTER_CREATION_CYCLES * 4;
+    const TEST_NOW_SECONDS: u64 = 1_660_000_000;
 
     struct TestCanisterApi {
         canisters_created: Arc<Mutex<u64>>,
@@ -910,6 +1182,9 @@ mod test {
         pub errors_on_set_controller: Arc<Mutex<Vec<Option<String>>>>,
         pub errors_on_delete_canister: Arc<Mutex<Vec<Option<String>>>>,
         pub errors_on_install_wasms: Arc<Mutex<Vec<Option<String>>>>,
+        // The canisters listed by SNS roots, and the versions run by SNS governance canisters
+        pub sns_canisters: Arc<Mutex<HashMap<CanisterId, ListSnsCanistersResponse>>>,
+        pub running_sns_versions: Arc<Mutex<HashMap<CanisterId, Version>>>,
     }
 
     #[async_trait]
@@ -918,6 +1193,10 @@ mod test {
             canister_test_id(0)
         }
 
+        fn now_seconds(&self) -> u64 {
+            TEST_NOW_SECONDS
+        }
+
         async fn create_canister(
             &self,
             _target_subnet: SubnetId,
@@ -1023,6 +1302,33 @@ mod test {
 
             Ok(cycles)
         }
+
+        async fn list_sns_canisters(
+            &self,
+            root_canister: CanisterId,
+        ) -> Result<ListSnsCanistersResponse, String> {
+            self.sns_canisters
+                .lock()
+                .unwrap()
+                .get(&root_canister)
+                .cloned()
+                .ok_or_else(|| format!("Unknown SNS root {}", root_canister))
+        }
+
+        async fn get_running_sns_version(
+            &self,
+            governance_canister: CanisterId,
+        ) -> Result<GetRunningSnsVersionResponse, String> {
+            self.running_sns_versions
+                .lock()
+                .unwrap()
+                .get(&governance_canister)
+                .map(|version| GetRunningSnsVersionResponse {
+                    deployed_version: Some(version.clone()),
+                    pending_version: None,
+                })
+                .ok_or_else(|| format!("Unknown SNS governance {}", governance_canister))
+        }
     }
 
     fn new_canister_api() -> TestCanisterApi {
@@ -1038,6 +1344,8 @@ mod test {
             errors_on_set_controller: Arc::new(Mutex::new(vec![])),
             errors_on_delete_canister: Arc::new(Mutex::new(vec![])),
             errors_on_install_wasms: Arc::new(Mutex::new(vec![])),
+            sns_canisters: Arc::new(Mutex::new(HashMap::new())),
+            running_sns_versions: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -1940,12 +2248,12 @@ mod test {
             static CANISTER_WRAPPER: RefCell<SnsWasmCanister<TestCanisterStableMemory>> = RefCell::new(new_wasm_canister()) ;
         }
 
-        CANISTER_WRAPPER.with(|c| {
+        let version = CANISTER_WRAPPER.with(|c| {
             c.borrow_mut().set_sns_subnets(vec![test_id]);
-            add_mock_wasms(&mut c.borrow_mut());
+            add_mock_wasms(&mut c.borrow_mut())
         });
 
-        let root_canister_1 = SnsWasmCanister::deploy_new_sns(
+        let canisters_1 = SnsWasmCanister::deploy_new_sns(
             &CANISTER_WRAPPER,
             &canister_api,
             DeployNewSnsRequest {
@@ -1954,8 +2262,6 @@ mod test {
         )
         .await
         .canisters
-        .unwrap()
-        .root
         .unwrap();
 
         // Add more cycles so our second call works
@@ -1969,9 +2275,9 @@ mod test {
         )
         .await;
 
-        let root_canister_2 = response.canisters.unwrap().root.unwrap();
+        let canisters_2 = response.canisters.unwrap();
 
-        assert_ne!(root_canister_1, root_canister_2);
+        assert_ne!(canisters_1.root, canisters_2.root);
 
         let known_deployments_response = CANISTER_WRAPPER.with(|canister| {
             canister
@@ -1979,18 +2285,289 @@ mod test {
                 .list_deployed_snses(ListDeployedSnsesRequest {})
         });
 
+        let deployed_sns = |canisters: SnsCanisterIds| DeployedSns {
+            root_canister_id: canisters.root,
+            governance_canister_id: canisters.governance,
+            ledger_canister_id: canisters.ledger,
+            swap_canister_id: canisters.swap,
+            upgrade_history: vec![SnsUpgradeRecord {
+                version: Some(version.clone()),
+                timestamp_seconds: TEST_NOW_SECONDS,
+            }],
+        };
         assert_eq!(
             known_deployments_response,
             ListDeployedSnsesResponse {
-                instances: vec![
-                    DeployedSns {
-                        root_canister_id: Some(root_canister_1),
-                    },
-                    DeployedSns {
-                        root_canister_id: Some(root_canister_2),
-                    },
-                ],
+                instances: vec![deployed_sns(canisters_1), deployed_sns(canisters_2)],
+            },
+        )
+    }
+
+    /// Adds a new WASM of the given type with distinct bytes, and returns the resulting
+    /// latest SnsVersion
+    fn add_new_wasm(
+        canister: &mut SnsWasmCanister<TestCanisterStableMemory>,
+        canister_type: SnsCanisterType,
+        last_byte: u8,
+    ) -> SnsVersion {
+        let wasm = SnsWasm {
+            wasm: vec![0, 0x61, 0x73, 0x6D, 1, 0, 0, last_byte],
+            canister_type: i32::from(canister_type),
+        };
+        let hash = wasm.sha256_hash().to_vec();
+        canister.add_wasm(AddWasmRequest {
+            wasm: Some(wasm),
+            hash,
+        });
+        canister.upgrade_path.latest_version.clone()
+    }
+
+    /// Registers a deployed SNS running `version` with the given governance canister
+    fn add_deployed_sns(
+        canister: &mut SnsWasmCanister<TestCanisterStableMemory>,
+        governance_canister_id: PrincipalId,
+        version: SnsVersion,
+    ) {
+        canister.deployed_sns_list.push(DeployedSns {
+            governance_canister_id: Some(governance_canister_id),
+            upgrade_history: vec![SnsUpgradeRecord {
+                version: Some(version),
+                timestamp_seconds: TEST_NOW_SECONDS,
+            }],
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_add_wasm_rejects_non_wasm_bytes() {
+        let mut canister = new_wasm_canister();
+        let wasm = SnsWasm {
+            wasm: b"not a wasm".to_vec(),
+            canister_type: i32::from(SnsCanisterType::Governance),
+        };
+
+        let response = canister.add_wasm(AddWasmRequest {
+            wasm: Some(wasm.clone()),
+            hash: wasm.sha256_hash().to_vec(),
+        });
+
+        assert_eq!(
+            response,
+            AddWasmResponse::error("The provided bytes are not a WASM module.".to_string())
+        );
+        assert_eq!(canister.upgrade_path, UpgradePath::default());
+    }
+
+    #[test]
+    fn test_record_sns_upgrade_follows_upgrade_path() {
+        let mut canister = new_wasm_canister();
+        let version_1 = add_mock_wasms(&mut canister);
+        let version_2 = add_new_wasm(&mut canister, SnsCanisterType::Governance, 10);
+        let version_3 = add_new_wasm(&mut canister, SnsCanisterType::Ledger, 11);
+        let governance_canister_id = PrincipalId::new_user_test_id(1);
+        add_deployed_sns(&mut canister, governance_canister_id, version_1.clone());
+
+        // Only the governance canister of a deployed SNS can record upgrades.
+        let response = canister.record_sns_upgrade(
+            PrincipalId::new_user_test_id(2),
+            RecordSnsUpgradeRequest {
+                new_version: Some(version_2.clone()),
+            },
+            TEST_NOW_SECONDS + 1,
+        );
+        assert!(response.error.is_some());
+
+        // Skipping a version is not allowed.
+        let response = canister.record_sns_upgrade(
+            governance_canister_id,
+            RecordSnsUpgradeRequest {
+                new_version: Some(version_3.clone()),
+            },
+            TEST_NOW_SECONDS + 1,
+        );
+        assert!(response.error.is_some());
+
+        let response = canister.record_sns_upgrade(
+            governance_canister_id,
+            RecordSnsUpgradeRequest {
+                new_version: Some(version_2.clone()),
+            },
+            TEST_NOW_SECONDS + 1,
+        );
+        assert_eq!(response, RecordSnsUpgradeResponse { error: None });
+
+        assert_eq!(
+            canister.deployed_sns_list[0].upgrade_history,
+            vec![
+                SnsUpgradeRecord {
+                    version: Some(version_1),
+                    timestamp_seconds: TEST_NOW_SECONDS,
+                },
+                SnsUpgradeRecord {
+                    version: Some(version_2),
+                    timestamp_seconds: TEST_NOW_SECONDS + 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_upgrade_steps() {
+        let mut canister = new_wasm_canister();
+        let version_1 = add_mock_wasms(&mut canister);
+        let version_2 = add_new_wasm(&mut canister, SnsCanisterType::Governance, 10);
+        let version_3 = add_new_wasm(&mut canister, SnsCanisterType::Ledger, 11);
+        let governance_canister_id = PrincipalId::new_user_test_id(1);
+        add_deployed_sns(&mut canister, governance_canister_id, version_2.clone());
+
+        // Starting at an explicit version.
+        assert_eq!(
+            canister
+                .list_upgrade_steps(ListUpgradeStepsRequest {
+                    starting_at: Some(version_1.clone()),
+                    ..Default::default()
+                })
+                .steps,
+            vec![version_1.clone(), version_2.clone(), version_3.clone()]
+        );
+
+        // Starting at the version of a deployed SNS, with a limit.
+        assert_eq!(
+            canister
+                .list_upgrade_steps(ListUpgradeStepsRequest {
+                    sns_governance_canister_id: Some(governance_canister_id),
+                    limit: 1,
+                    ..Default::default()
+                })
+                .steps,
+            vec![version_2]
+        );
+
+        // Without a starting point, the whole upgrade path is listed.
+        let steps = canister
+            .list_upgrade_steps(ListUpgradeStepsRequest::default())
+            .steps;
+        assert_eq!(steps.first(), Some(&SnsVersion::default()));
+        assert_eq!(steps.last(), Some(&version_3));
+
+        // Versions that are not on the upgrade path have no steps.
+        assert_eq!(
+            canister
+                .list_upgrade_steps(ListUpgradeStepsRequest {
+                    starting_at: Some(SnsVersion {
+                        root_wasm_hash: vec![1, 2, 3],
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+                .steps,
+            vec![]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_deployed_snses_backfills_old_snses() {
+        let canister_api = new_canister_api();
+        let root_canister_id = canister_test_id(10);
+        let governance_canister_id = canister_test_id(11);
+
+        thread_local! {
+            static CANISTER_WRAPPER: RefCell<SnsWasmCanister<TestCanisterStableMemory>> = RefCell::new(new_wasm_canister()) ;
+        }
+
+        let (version_1, version_2) = CANISTER_WRAPPER.with(|c| {
+            let mut canister = c.borrow_mut();
+            let version_1 = add_mock_wasms(&mut canister);
+            let version_2 = add_new_wasm(&mut canister, SnsCanisterType::Governance, 10);
+            // An SNS deployed before canister IDs and upgrade history were recorded.
+            canister.deployed_sns_list.push(DeployedSns {
+                root_canister_id: Some(root_canister_id.get()),
+                ..Default::default()
+            });
+            (version_1, version_2)
+        });
+
+        canister_api.sns_canisters.lock().unwrap().insert(
+            root_canister_id,
+            ListSnsCanistersResponse {
+                root: Some(root_canister_id.get()),
+                governance: Some(governance_canister_id.get()),
+                ledger: Some(canister_test_id(12).get()),
+                swap: Some(canister_test_id(13).get()),
+                ..Default::default()
             },
+        );
+        canister_api
+            .running_sns_versions
+            .lock()
+            .unwrap()
+            .insert(governance_canister_id, version_1.clone().into());
+
+        SnsWasmCanister::reconcile_deployed_snses(
+            &CANISTER_WRAPPER,
+            &canister_api,
+            TEST_NOW_SECONDS,
+        )
+        .await;
+
+        let deployed_sns = CANISTER_WRAPPER.with(|c| c.borrow().deployed_sns_list[0].clone());
+        assert_eq!(
+            deployed_sns,
+            DeployedSns {
+                root_canister_id: Some(root_canister_id.get()),
+                governance_canister_id: Some(governance_canister_id.get()),
+                ledger_canister_id: Some(canister_test_id(12).get()),
+                swap_canister_id: Some(canister_test_id(13).get()),
+                upgrade_history: vec![SnsUpgradeRecord {
+                    version: Some(version_1.clone()),
+                    timestamp_seconds: TEST_NOW_SECONDS,
+                }],
+            }
+        );
+
+        // The SNS upgrades, but fails to record it. Reconciliation picks up the new version, but
+        // only once the reconciliation interval has passed.
+        canister_api
+            .running_sns_versions
+            .lock()
+            .unwrap()
+            .insert(governance_canister_id, version_2.clone().into());
+        SnsWasmCanister::reconcile_deployed_snses(
+            &CANISTER_WRAPPER,
+            &canister_api,
+            TEST_NOW_SECONDS + 1,
         )
+        .await;
+        let upgrade_history =
+            CANISTER_WRAPPER.with(|c| c.borrow().deployed_sns_list[0].upgrade_history.clone());
+        assert_eq!(upgrade_history.len(), 1);
+
+        let later = TEST_NOW_SECONDS + RECONCILIATION_INTERVAL_SECONDS;
+        SnsWasmCanister::reconcile_deployed_snses(&CANISTER_WRAPPER, &canister_api, later).await;
+        let upgrade_history =
+            CANISTER_WRAPPER.with(|c| c.borrow().deployed_sns_list[0].upgrade_history.clone());
+        assert_eq!(
+            upgrade_history.last(),
+            Some(&SnsUpgradeRecord {
+                version: Some(version_2.clone()),
+                timestamp_seconds: later,
+            })
+        );
+
+        // A late call of record_sns_upgrade for the same version is accepted, but not recorded
+        // twice.
+        let response = CANISTER_WRAPPER.with(|c| {
+            c.borrow_mut().record_sns_upgrade(
+                governance_canister_id.get(),
+                RecordSnsUpgradeRequest {
+                    new_version: Some(version_2),
+                },
+                later + 1,
+            )
+        });
+        assert_eq!(response, RecordSnsUpgradeResponse { error: None });
+        let upgrade_history =
+            CANISTER_WRAPPER.with(|c| c.borrow().deployed_sns_list[0].upgrade_history.clone());
+        assert_eq!(upgrade_history.len(), 2);
     }
 }
@@ -164,7 +164,9 @@ impl<M: StableMemory + Clone> SnsWasmStableMemory<M> {
 mod test {
     use super::*;
     use crate::canister_stable_memory::TestCanisterStableMemory;
-    use crate::pb::v1::{DeployedSns, SnsUpgrade, SnsVersion, SnsWasmStableIndex, UpgradePath};
+    use crate::pb::v1::{
+        DeployedSns, SnsUpgrade, SnsUpgradeRecord, SnsVersion, SnsWasmStableIndex, UpgradePath,
+    };
     use ic_base_types::PrincipalId;
 
     #[test]
@@ -198,6 +200,16 @@ mod test {
         let sns_subnet_ids = vec![PrincipalId::new_subnet_test_id(34)];
         let deployed_sns_list = vec![DeployedSns {
             root_canister_id: Some(PrincipalId::new_subnet_test_id(560)),
+            governance_canister_id: Some(PrincipalId::new_subnet_test_id(561)),
+            ledger_canister_id: Some(PrincipalId::new_subnet_test_id(562)),
+            swap_canister_id: Some(PrincipalId::new_subnet_test_id(563)),
+            upgrade_history: vec![SnsUpgradeRecord {
+                version: Some(SnsVersion {
+                    governance_wasm_hash: [9u8; 32].to_vec(),
+                    ..Default::default()
+                }),
+                timestamp_seconds: 1_660_000_000,
+            }],
         }];
 
         let sns_version1 = SnsVersion {
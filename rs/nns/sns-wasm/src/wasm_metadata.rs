@@ -0,0 +1,296 @@
+use crate::pb::v1::{SnsCanisterType, SnsWasm};
+use candid::parser::types::{Dec, IDLProg, IDLType};
+use std::collections::BTreeSet;
+
+/// The magic bytes every (uncompressed) WASM module starts with.
+const WASM_MAGIC_BYTES: [u8; 4] = [0, 0x61, 0x73, 0x6D];
+/// The magic bytes every gzip-compressed file starts with.
+const GZIP_MAGIC_BYTES: [u8; 2] = [0x1F, 0x8B];
+
+/// The names of the custom sections in which the candid interface of a canister is embedded.
+const CANDID_SERVICE_SECTION_NAMES: [&str; 2] =
+    ["icp:public candid:service", "icp:private candid:service"];
+
+/// Sanity checks a WASM that is about to be added to SNS-WASM.
+///
+/// The WASM must start like a WASM module (or a gzip-compressed file). If it is uncompressed and
+/// embeds its candid interface as metadata, that interface must expose the methods
+/// characteristic of `canister_type`, and must keep every method of `previous_wasm` (the WASM of
+/// the same type in the latest SNS version) if that WASM also declares its interface.
+///
+/// Only method names are compared, not their argument or result types, and neither the module
+/// itself nor WASMs without candid metadata are validated any further. This catches WASMs added
+/// for the wrong canister type, not incompatible interfaces.
+pub fn validate_sns_wasm(
+    wasm: &SnsWasm,
+    canister_type: SnsCanisterType,
+    previous_wasm: Option<&SnsWasm>,
+) -> Result<(), String> {
+    if wasm.wasm.starts_with(&GZIP_MAGIC_BYTES) {
+        // The metadata of compressed modules cannot be inspected here.
+        return Ok(());
+    }
+    if !wasm.wasm.starts_with(&WASM_MAGIC_BYTES) {
+        return Err("The provided bytes are not a WASM module.".to_string());
+    }
+
+    let methods = match candid_service_methods(&wasm.wasm)? {
+        Some(methods) => methods,
+        None => return Ok(()),
+    };
+
+    let missing_methods: Vec<&str> = expected_methods(canister_type)
+        .iter()
+        .filter(|method| !methods.contains(**method))
+        .cloned()
+        .collect();
+    if !missing_methods.is_empty() {
+        return Err(format!(
+            "The candid interface of the WASM does not match canister type {:?}. Missing \
+             methods: {}",
+            canister_type,
+            missing_methods.join(", ")
+        ));
+    }
+
+    let previous_methods = match previous_wasm {
+        Some(previous_wasm) if previous_wasm.wasm.starts_with(&WASM_MAGIC_BYTES) => {
+            candid_service_methods(&previous_wasm.wasm).unwrap_or_default()
+        }
+        _ => None,
+    };
+    if let Some(previous_methods) = previous_methods {
+        let removed_methods: Vec<&str> = previous_methods
+            .iter()
+            .filter(|method| !methods.contains(*method))
+            .map(|method| method.as_str())
+            .collect();
+        if !removed_methods.is_empty() {
+            return Err(format!(
+                "The candid interface of the WASM is not compatible with the current {:?} WASM. \
+                 Removed methods: {}",
+                canister_type,
+                removed_methods.join(", ")
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// The methods an SNS canister of the given type is expected to expose.
+fn expected_methods(canister_type: SnsCanisterType) -> &'static [&'static str] {
+    match canister_type {
+        SnsCanisterType::Unspecified => &[],
+        SnsCanisterType::Root => &["get_sns_canisters_summary"],
+        SnsCanisterType::Governance => &["manage_neuron", "get_running_sns_version"],
+        SnsCanisterType::Ledger => &["icrc1_transfer", "icrc1_balance_of"],
+        SnsCanisterType::Swap => &["refresh_buyer_tokens", "finalize_swap"],
+        SnsCanisterType::Archive => &["append_blocks", "get_transactions"],
+    }
+}
+
+/// Returns the names of the methods of the candid service embedded in the WASM's metadata,
+/// or None if the WASM does not declare its interface.
+fn candid_service_methods(wasm: &[u8]) -> Result<Option<BTreeSet<String>>, String> {
+    let section = CANDID_SERVICE_SECTION_NAMES
+        .iter()
+        .map(|name| read_custom_section(wasm, name))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .next();
+    let section = match section {
+        Some(section) => section,
+        None => return Ok(None),
+    };
+
+    let candid = String::from_utf8(section)
+        .map_err(|e| format!("The candid:service metadata is not valid UTF-8: {}", e))?;
+    let prog: IDLProg = candid
+        .parse()
+        .map_err(|e| format!("Could not parse the candid:service metadata: {}", e))?;
+
+    let mut actor = prog
+        .actor
+        .clone()
+        .ok_or_else(|| "The candid:service metadata does not define a service.".to_string())?;
+    // Resolve service constructors and references to named service types.
+    loop {
+        actor = match actor {
+            IDLType::ServT(bindings) => {
+                return Ok(Some(bindings.into_iter().map(|b| b.id).collect()));
+            }
+            IDLType::ClassT(_, service) => *service,
+            IDLType::VarT(name) => prog
+                .decs
+                .iter()
+                .find_map(|dec| match dec {
+                    Dec::TypD(binding) if binding.id == name => Some(binding.typ.clone()),
+                    _ => None,
+                })
+                .ok_or_else(|| format!("Unknown type {} in the candid:service metadata.", name))?,
+            _ => return Err("The candid:service metadata does not define a service.".to_string()),
+        }
+    }
+}
+
+/// Returns the content of the custom section with the given name, if the WASM has one.
+fn read_custom_section(wasm: &[u8], name: &str) -> Result<Option<Vec<u8>>, String> {
+    const CUSTOM_SECTION_ID: u8 = 0;
+
+    // Skip the magic bytes and the version.
+    let mut position = 8;
+    while position < wasm.len() {
+        let section_id = wasm[position];
+        position += 1;
+        let section_size = read_leb128_u32(wasm, &mut position)? as usize;
+        let section_end = position
+            .checked_add(section_size)
+            .filter(|end| *end <= wasm.len())
+            .ok_or_else(|| "WASM section exceeds the module size.".to_string())?;
+
+        if section_id == CUSTOM_SECTION_ID {
+            let name_size = read_leb128_u32(wasm, &mut position)? as usize;
+            let name_end = position
+                .checked_add(name_size)
+                .filter(|end| *end <= section_end)
+                .ok_or_else(|| "WASM custom section name exceeds the section.".to_string())?;
+            if &wasm[position..name_end] == name.as_bytes() {
+                return Ok(Some(wasm[name_end..section_end].to_vec()));
+            }
+        }
+        position = section_end;
+    }
+    Ok(None)
+}
+
+/// Reads an unsigned LEB128-encoded u32 starting at `position`, and advances `position` past it.
+fn read_leb128_u32(bytes: &[u8], position: &mut usize) -> Result<u32, String> {
+    let mut result: u32 = 0;
+    for shift in (0..35).step_by(7) {
+        let byte = *bytes
+            .get(*position)
+            .ok_or_else(|| "Unexpected end of WASM module.".to_string())?;
+        *position += 1;
+        result |= ((byte & 0x7F) as u32)
+            .checked_shl(shift)
+            .ok_or_else(|| "Invalid LEB128 integer in WASM module.".to_string())?;
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+    }
+    Err("Invalid LEB128 integer in WASM module.".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a minimal WASM module with a single custom section.
+    fn wasm_with_custom_section(name: &str, content: &str) -> Vec<u8> {
+        let mut section = vec![name.len() as u8];
+        section.extend(name.as_bytes());
+        section.extend(content.as_bytes());
+
+        let mut wasm = vec![0, 0x61, 0x73, 0x6D, 1, 0, 0, 0];
+        wasm.push(0);
+        // Encode the section size as LEB128.
+        let mut size = section.len();
+        loop {
+            let byte = (size & 0x7F) as u8;
+            size >>= 7;
+            if size == 0 {
+                wasm.push(byte);
+                break;
+            }
+            wasm.push(byte | 0x80);
+        }
+        wasm.extend(section);
+        wasm
+    }
+
+    fn governance_wasm(service: &str) -> SnsWasm {
+        SnsWasm {
+            wasm: wasm_with_custom_section("icp:public candid:service", service),
+            canister_type: i32::from(SnsCanisterType::Governance),
+        }
+    }
+
+    const GOVERNANCE_SERVICE: &str = "service : (record {}) -> { \
+        manage_neuron : (record {}) -> (record {}); \
+        get_running_sns_version : (record {}) -> (record {}) query; \
+        list_neurons : (record {}) -> (record {}) query; \
+    }";
+
+    #[test]
+    fn test_wasm_without_metadata_is_valid() {
+        let wasm = SnsWasm {
+            wasm: vec![0, 0x61, 0x73, 0x6D, 1, 0, 0, 0],
+            canister_type: i32::from(SnsCanisterType::Root),
+        };
+        assert_eq!(
+            validate_sns_wasm(&wasm, SnsCanisterType::Root, None),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_non_wasm_bytes_are_invalid() {
+        let wasm = SnsWasm {
+            wasm: b"not a wasm".to_vec(),
+            canister_type: i32::from(SnsCanisterType::Root),
+        };
+        assert!(validate_sns_wasm(&wasm, SnsCanisterType::Root, None).is_err());
+    }
+
+    #[test]
+    fn test_candid_service_methods_are_read_from_metadata() {
+        let wasm = governance_wasm(GOVERNANCE_SERVICE);
+        assert_eq!(
+            candid_service_methods(&wasm.wasm).unwrap().unwrap(),
+            ["get_running_sns_version", "list_neurons", "manage_neuron"]
+                .iter()
+                .map(|m| m.to_string())
+                .collect()
+        );
+        assert_eq!(
+            validate_sns_wasm(&wasm, SnsCanisterType::Governance, None),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_metadata_must_match_canister_type() {
+        let wasm = governance_wasm(GOVERNANCE_SERVICE);
+        let error = validate_sns_wasm(&wasm, SnsCanisterType::Ledger, None).unwrap_err();
+        assert!(error.contains("icrc1_transfer"), "{}", error);
+    }
+
+    #[test]
+    fn test_methods_of_previous_wasm_must_be_kept() {
+        let previous = governance_wasm(GOVERNANCE_SERVICE);
+        let compatible = governance_wasm(
+            "service : (record {}) -> { \
+                manage_neuron : (record {}) -> (record {}); \
+                get_running_sns_version : (record {}) -> (record {}) query; \
+                list_neurons : (record {}) -> (record {}) query; \
+                list_proposals : (record {}) -> (record {}) query; \
+            }",
+        );
+        let incompatible = governance_wasm(
+            "service : (record {}) -> { \
+                manage_neuron : (record {}) -> (record {}); \
+                get_running_sns_version : (record {}) -> (record {}) query; \
+            }",
+        );
+
+        assert_eq!(
+            validate_sns_wasm(&compatible, SnsCanisterType::Governance, Some(&previous)),
+            Ok(())
+        );
+        let error = validate_sns_wasm(&incompatible, SnsCanisterType::Governance, Some(&previous))
+            .unwrap_err();
+        assert!(error.contains("list_neurons"), "{}", error);
+    }
+}
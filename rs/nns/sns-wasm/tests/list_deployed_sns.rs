@@ -2,7 +2,7 @@ use canister_test::Project;
 use ic_nns_test_utils::sns_wasm;
 use ic_nns_test_utils::state_test_helpers::set_up_universal_canister;
 use ic_sns_init::pb::v1::SnsInitPayload;
-use ic_sns_wasm::pb::v1::{DeployedSns, ListDeployedSnsesResponse};
+use ic_sns_wasm::pb::v1::ListUpgradeStepsRequest;
 use ic_types::Cycles;
 pub mod common;
 use common::set_up_state_machine_with_nns;
@@ -52,16 +52,28 @@ fn list_deployed_snses_lists_created_sns_instances() {
     let response = sns_wasm::list_deployed_snses(&machine, SNS_WASM_CANISTER_ID);
 
     assert_eq!(
-        response,
-        ListDeployedSnsesResponse {
-            instances: vec![
-                DeployedSns {
-                    root_canister_id: root_1,
-                },
-                DeployedSns {
-                    root_canister_id: root_2,
-                },
-            ]
-        }
+        response
+            .instances
+            .iter()
+            .map(|sns| sns.root_canister_id)
+            .collect::<Vec<_>>(),
+        vec![root_1, root_2]
     );
+    // Each deployed SNS records the version it was deployed with.
+    let latest_version = sns_wasm::list_upgrade_steps(
+        &machine,
+        SNS_WASM_CANISTER_ID,
+        ListUpgradeStepsRequest::default(),
+    )
+    .steps
+    .pop()
+    .unwrap();
+    for sns in response.instances {
+        assert!(sns.governance_canister_id.is_some());
+        assert_eq!(sns.upgrade_history.len(), 1);
+        assert_eq!(
+            sns.upgrade_history[0].version.as_ref(),
+            Some(&latest_version)
+        );
+    }
 }
@@ -19,7 +19,8 @@ use ic_sns_init::pb::v1::SnsInitPayload;
 use ic_sns_wasm::pb::v1::{
     AddWasmRequest, AddWasmResponse, DeployNewSnsRequest, DeployNewSnsResponse,
     GetNextSnsVersionRequest, GetNextSnsVersionResponse, GetWasmRequest, GetWasmResponse,
-    ListDeployedSnsesRequest, ListDeployedSnsesResponse, SnsCanisterType, SnsWasm,
+    ListDeployedSnsesRequest, ListDeployedSnsesResponse, ListUpgradeStepsRequest,
+    ListUpgradeStepsResponse, SnsCanisterType, SnsWasm,
 };
 use ic_state_machine_tests::StateMachine;
 use maplit::hashmap;
@@ -202,6 +203,23 @@ pub fn get_next_sns_version(
     Decode!(&response_bytes, GetNextSnsVersionResponse).unwrap()
 }
 
+/// Make list_upgrade_steps request to a canister in the StateMachine
+pub fn list_upgrade_steps(
+    env: &StateMachine,
+    sns_wasm_canister_id: CanisterId,
+    request: ListUpgradeStepsRequest,
+) -> ListUpgradeStepsResponse {
+    let response_bytes = query(
+        env,
+        sns_wasm_canister_id,
+        "list_upgrade_steps",
+        Encode!(&request).unwrap(),
+    )
+    .unwrap();
+
+    Decode!(&response_bytes, ListUpgradeStepsResponse).unwrap()
+}
+
 /// Adds non-functional wasms to the SNS-WASM canister (to avoid expensive init process in certain tests)
 pub fn add_dummy_wasms_to_sns_wasms(machine: &StateMachine) {
     let root_wasm = test_wasm(SnsCanisterType::Root);
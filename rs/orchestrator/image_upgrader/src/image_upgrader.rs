@@ -126,6 +126,24 @@ pub trait ImageUpgrader<V: Clone + Debug + PartialEq + Eq + Send + Sync, R: Send
         }
     }
 
+    /// Calls a corresponding script to abandon the current, not yet confirmed
+    /// image and reboot into the previous one. Only returns if the rollback
+    /// could not be executed.
+    async fn rollback(&self) -> UpgradeResult<()> {
+        let mut c = Command::new(self.binary_dir().join("manageboot.sh").into_os_string());
+        let out = c
+            .arg("rollback")
+            .output()
+            .await
+            .map_err(|e| UpgradeError::file_command_error(e, &c))?;
+        if !out.status.success() {
+            warn!(self.log(), "rollback has failed");
+            return Err(UpgradeError::GenericError("rollback failed".to_string()));
+        }
+        info!(self.log(), "Rebooting into the previous image {:?}", out);
+        exit(42);
+    }
+
     /// Downloads release package associated with the given version
     ///
     /// Releases are downloaded using [`FileDownloader::download_file()`] which
@@ -1,3 +1,4 @@
+use crate::store_changelog;
 use ic_interfaces::registry::{RegistryClient, ZERO_REGISTRY_VERSION};
 use ic_logger::{warn, ReplicaLogger};
 use ic_protobuf::{
@@ -17,7 +18,9 @@ use ic_registry_keys::{
     make_routing_table_record_key, make_subnet_list_record_key, make_subnet_record_key,
     ROOT_SUBNET_ID_KEY,
 };
-use ic_registry_local_store::{Changelog, ChangelogEntry, KeyMutation, LocalStore};
+use ic_registry_local_store::{
+    Changelog, ChangelogEntry, KeyMutation, LocalStore, DEFAULT_SNAPSHOT_INTERVAL,
+};
 use ic_registry_nns_data_provider::registry::RegistryCanister;
 use ic_registry_routing_table::{CanisterIdRange, RoutingTable};
 use ic_types::crypto::threshold_sig::ThresholdSigPublicKey;
@@ -27,10 +30,15 @@ use std::convert::TryFrom;
 use std::fmt::Debug;
 use std::net::IpAddr;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use url::Url;
 
+/// The number of most recent registry versions whose changelog entries are kept
+/// in the local store. Older entries are compacted into a snapshot.
+const RETAINED_REGISTRY_VERSIONS: u64 = 10_000;
+
 /// The `InternalState` encompasses a locally persisted registry changelog which
 /// is kept up to date by repeated calls to [`Self::poll()`]. If this node is
 /// part of a subnet that is starting up as the NNS after a switch-over, the
@@ -53,6 +61,12 @@ pub(crate) struct InternalState {
     nns_urls: Vec<Url>,
     registry_canister: Option<Arc<RegistryCanister>>,
     poll_delay: Duration,
+    /// The version up to which the local store has been compacted during the
+    /// lifetime of this object.
+    compacted_version: RegistryVersion,
+    /// The local store is never compacted beyond this version, see
+    /// [crate::RegistryReplicator::retain_versions_since].
+    retained_version: Arc<AtomicU64>,
 }
 
 impl InternalState {
@@ -62,6 +76,7 @@ impl InternalState {
         registry_client: Arc<dyn RegistryClient>,
         local_store: Arc<dyn LocalStore>,
         poll_delay: Duration,
+        retained_version: Arc<AtomicU64>,
     ) -> Self {
         Self {
             logger,
@@ -73,6 +88,8 @@ impl InternalState {
             nns_urls: vec![],
             registry_canister: None,
             poll_delay,
+            compacted_version: ZERO_REGISTRY_VERSION,
+            retained_version,
         }
     }
 
@@ -146,9 +163,34 @@ impl InternalState {
                 .expect("Could not store certified time");
         }
 
+        self.compact_local_store(self.registry_client.get_latest_version());
         Ok(())
     }
 
+    /// Compacts the changelog entries of the local store that are more than
+    /// [`RETAINED_REGISTRY_VERSIONS`] versions older than `latest_version`,
+    /// in steps of [`DEFAULT_SNAPSHOT_INTERVAL`] versions. Versions from the
+    /// retained version on remain readable.
+    fn compact_local_store(&mut self, latest_version: RegistryVersion) {
+        let target = latest_version
+            .get()
+            .saturating_sub(RETAINED_REGISTRY_VERSIONS)
+            .min(self.retained_version.load(Ordering::Relaxed))
+            / DEFAULT_SNAPSHOT_INTERVAL
+            * DEFAULT_SNAPSHOT_INTERVAL;
+        if target == 0 || target <= self.compacted_version.get() {
+            return;
+        }
+        let target = RegistryVersion::from(target);
+        match self.local_store.compact(target) {
+            Ok(()) => self.compacted_version = target,
+            Err(e) => warn!(
+                self.logger,
+                "Could not compact registry local store up to version {}: {:?}", target, e
+            ),
+        }
+    }
+
     /// Iff at version `latest_version` the node id of this node appears on a
     /// subnet record that has the `start_as_nns`-flag set, this function will
     /// adjust the registry such that the aforementioned subnet will become the
@@ -203,26 +245,30 @@ impl InternalState {
         };
 
         // IOErrors are treated as fatal.
-        let mut changelog = self
+        let (start, mut changelog) = self
             .local_store
-            .get_changelog_since_version(RegistryVersion::from(0))
+            .get_changelog_or_snapshot_since_version(ZERO_REGISTRY_VERSION)
             .expect("Could not read changelog from disk.");
-        changelog.truncate(k.get() as usize);
+        if k <= start {
+            return Err(format!(
+                "Versions up to {} have been compacted, cannot switch over at version {}",
+                start + RegistryVersion::from(1),
+                k
+            ));
+        }
+        changelog.truncate((k - start).get() as usize);
 
         self.apply_switch_over_to_last_changelog_entry(
             changelog.as_mut_slice(),
+            k,
             subnet_id,
             subnet_record,
         );
         self.local_store
             .clear()
             .expect("Could not clear registry local store");
-
-        for (v, cle) in changelog.into_iter().enumerate() {
-            self.local_store
-                .store(RegistryVersion::from((v + 1) as u64), cle)
-                .expect("Could not store change log entry");
-        }
+        store_changelog(self.local_store.as_ref(), start, changelog)
+            .expect("Could not store change log entry");
 
         warn!(
             self.logger,
@@ -231,7 +277,8 @@ impl InternalState {
         std::process::exit(1);
     }
 
-    /// Given a `changelog`, this function adjusts the following entries of the
+    /// Given a `changelog` whose last entry produces the registry at
+    /// `registry_version`, this function adjusts the following entries of the
     /// last registry changelog entry:
     /// * Update subnet type to be `system`
     /// * Update root subnet ID to be new NNS subnet ID
@@ -240,11 +287,11 @@ impl InternalState {
     fn apply_switch_over_to_last_changelog_entry(
         &self,
         changelog: &mut [ChangelogEntry],
+        registry_version: RegistryVersion,
         new_nns_subnet_id: SubnetId,
         mut new_nns_subnet_record: SubnetRecord,
     ) {
         use prost::Message;
-        let registry_version = RegistryVersion::from(changelog.len() as u64);
 
         let routing_table = self
             .registry_client
@@ -360,10 +407,10 @@ impl InternalState {
             self.nns_urls = urls.clone();
 
             // reinitialize client
-            self.registry_canister = Some(Arc::new(RegistryCanister::new_with_query_timeout(
-                urls,
-                self.poll_delay,
-            )));
+            self.registry_canister = Some(Arc::new(
+                RegistryCanister::new_with_query_timeout(urls, self.poll_delay)
+                    .with_root_key(pub_key),
+            ));
         }
         Ok(())
     }
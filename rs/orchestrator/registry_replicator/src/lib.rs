@@ -45,7 +45,7 @@ use ic_types::{NodeId, RegistryVersion};
 use metrics::RegistryreplicatorMetrics;
 use std::io::{Error, ErrorKind};
 use std::net::SocketAddr;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tempfile::TempDir;
@@ -65,6 +65,9 @@ pub struct RegistryReplicator {
     cancelled: Arc<AtomicBool>,
     poll_delay: Duration,
     metrics: Arc<RegistryreplicatorMetrics>,
+    /// The oldest registry version that must remain readable from the local
+    /// store, see [Self::retain_versions_since].
+    retained_version: Arc<AtomicU64>,
 }
 
 impl RegistryReplicator {
@@ -102,6 +105,7 @@ impl RegistryReplicator {
             cancelled: Arc::new(AtomicBool::new(false)),
             poll_delay,
             metrics,
+            retained_version: Arc::new(AtomicU64::new(ZERO_REGISTRY_VERSION.get())),
         }
     }
 
@@ -202,21 +206,23 @@ impl RegistryReplicator {
     ) {
         if self
             .local_store
-            .get_changelog_since_version(ZERO_REGISTRY_VERSION)
+            .get_changelog_or_snapshot_since_version(ZERO_REGISTRY_VERSION)
             .expect("Could not read registry local store.")
+            .1
             .is_empty()
         {
             let nns_pub_key = nns_pub_key
                 .expect("Registry Local Store is empty and no NNS Public Key is provided.");
 
-            let registry_canister = RegistryCanister::new(nns_urls);
+            let registry_canister = RegistryCanister::new(nns_urls).with_root_key(nns_pub_key);
 
             // While the local registry changelog is empty, fill it by polling the registry
             // canister. Retry every 30 seconds
             while self
                 .local_store
-                .get_changelog_since_version(ZERO_REGISTRY_VERSION)
+                .get_changelog_or_snapshot_since_version(ZERO_REGISTRY_VERSION)
                 .expect("Could not read registry local store.")
+                .1
                 .is_empty()
             {
                 // Note, code duplicate in internal_state.rs poll()
@@ -287,6 +293,7 @@ impl RegistryReplicator {
             self.registry_client.clone(),
             self.local_store.clone(),
             self.poll_delay,
+            Arc::clone(&self.retained_version),
         );
 
         let logger = self.logger.clone();
@@ -323,19 +330,16 @@ impl RegistryReplicator {
     /// store.
     fn set_local_registry_data(&self, source_registry: &dyn LocalStore) {
         // Read the registry data.
-        let changelog = source_registry
-            .get_changelog_since_version(RegistryVersion::from(0))
+        let (start, changelog) = source_registry
+            .get_changelog_or_snapshot_since_version(ZERO_REGISTRY_VERSION)
             .expect("Could not read changelog from source registry.");
 
         // Reset the local store and fill it with the read registry data.
         self.local_store
             .clear()
             .expect("Could not clear registry local store");
-        for (v, cle) in changelog.into_iter().enumerate() {
-            self.local_store
-                .store(RegistryVersion::from((v + 1) as u64), cle)
-                .expect("Could not store change log entry");
-        }
+        store_changelog(self.local_store.as_ref(), start, changelog)
+            .expect("Could not store change log entry");
     }
 
     pub fn stop_polling_and_set_local_registry_data(&self, source_registry: &dyn LocalStore) {
@@ -343,6 +347,19 @@ impl RegistryReplicator {
         self.set_local_registry_data(source_registry);
     }
 
+    /// Allows the local store to be compacted up to `version`, so that the
+    /// registry remains readable at `version` and all later versions. Readers
+    /// starting from the compacted local store, like a restarted replica,
+    /// cannot read the registry at earlier versions.
+    ///
+    /// The orchestrator calls this with the registry version of the latest
+    /// CUP, below which the replica does not read the registry. The local
+    /// store is not compacted before this is called.
+    pub fn retain_versions_since(&self, version: RegistryVersion) {
+        self.retained_version
+            .fetch_max(version.get(), Ordering::Relaxed);
+    }
+
     pub fn stop_polling(&self) {
         self.cancelled.fetch_or(true, Ordering::Relaxed);
     }
@@ -356,6 +373,25 @@ impl RegistryReplicator {
     }
 }
 
+/// Stores `changelog` in the empty `local_store`, where `changelog[i]`
+/// produces the registry at version `start+i+1`. If `start` is not zero, the
+/// first entry is a snapshot of the full registry, as returned by
+/// [ic_registry_local_store::LocalStoreReader::get_changelog_or_snapshot_since_version].
+pub(crate) fn store_changelog(
+    local_store: &dyn LocalStore,
+    start: RegistryVersion,
+    changelog: Changelog,
+) -> Result<(), Error> {
+    changelog.into_iter().enumerate().try_for_each(|(i, cle)| {
+        let v = start + RegistryVersion::from(i as u64 + 1);
+        if i == 0 && start != ZERO_REGISTRY_VERSION {
+            local_store.store_snapshot(v, cle)
+        } else {
+            local_store.store(v, cle)
+        }
+    })
+}
+
 impl Drop for RegistryReplicator {
     fn drop(&mut self) {
         self.stop_polling();
@@ -60,6 +60,13 @@ pub struct OrchestratorArgs {
     /// If not provided, the relevant data are not persisted to the disk.
     #[clap(long, parse(from_os_str))]
     pub(crate) orchestrator_data_directory: Option<PathBuf>,
+
+    /// The number of seconds an upgraded replica has to produce a new CUP
+    /// while its subnet does, before the orchestrator rolls back to the
+    /// previous image. Requires `orchestrator_data_directory`; 0 disables the
+    /// verification.
+    #[clap(long, default_value = "3600")]
+    pub(crate) upgrade_verification_timeout_secs: u64,
 }
 
 impl OrchestratorArgs {
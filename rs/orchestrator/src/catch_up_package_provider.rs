@@ -102,6 +102,16 @@ impl CatchUpPackageProvider {
         None
     }
 
+    /// Returns the CUP served by the replica of the node with the given record.
+    pub(crate) async fn get_node_cup(
+        &self,
+        node_record: &NodeRecord,
+        subnet_id: SubnetId,
+    ) -> Option<CUPWithOriginalProtobuf> {
+        self.fetch_verify_and_deserialize_catch_up_package(node_record, None, subnet_id)
+            .await
+    }
+
     // Download CUP from the given node.
     //
     // If `param` is given, download only CUPs that are newer than the
@@ -1,6 +1,7 @@
 use crate::{
     catch_up_package_provider::CatchUpPackageProvider, registry_helper::RegistryHelper,
     replica_process::ReplicaProcess, ssh_access_manager::SshAccessParameters,
+    upgrade_verification::UpgradeVerificationStatus,
 };
 use async_trait::async_trait;
 pub use ic_dashboard::Dashboard;
@@ -22,6 +23,7 @@ pub(crate) struct OrchestratorDashboard {
     subnet_id: Arc<RwLock<Option<SubnetId>>>,
     replica_version: ReplicaVersion,
     cup_provider: Arc<CatchUpPackageProvider>,
+    upgrade_verification_status: Arc<RwLock<UpgradeVerificationStatus>>,
     logger: ReplicaLogger,
 }
 
@@ -41,6 +43,7 @@ impl Dashboard for OrchestratorDashboard {
              replica version: {}\n\
              scheduled upgrade: {}\n\
              {}\n\
+             {}\n\
              firewall config registry version: {}\n\
              {}\n\
              readonly keys: {}\n\
@@ -53,6 +56,7 @@ impl Dashboard for OrchestratorDashboard {
             self.get_pid(),
             self.replica_version,
             self.get_scheduled_upgrade().await,
+            self.upgrade_verification_status.read().await.describe(),
             self.get_local_cup_info(),
             *self.last_applied_firewall_version.read().await,
             self.display_last_applied_ssh_parameters().await,
@@ -77,6 +81,7 @@ impl OrchestratorDashboard {
         subnet_id: Arc<RwLock<Option<SubnetId>>>,
         replica_version: ReplicaVersion,
         cup_provider: Arc<CatchUpPackageProvider>,
+        upgrade_verification_status: Arc<RwLock<UpgradeVerificationStatus>>,
         logger: ReplicaLogger,
     ) -> Self {
         Self {
@@ -88,6 +93,7 @@ impl OrchestratorDashboard {
             subnet_id,
             replica_version,
             cup_provider,
+            upgrade_verification_status,
             logger,
         }
     }
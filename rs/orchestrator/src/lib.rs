@@ -44,3 +44,4 @@ mod registry_helper;
 mod replica_process;
 mod ssh_access_manager;
 mod upgrade;
+mod upgrade_verification;
@@ -11,6 +11,10 @@ pub struct OrchestratorMetrics {
     pub ssh_access_registry_version: IntGauge,
     pub firewall_registry_version: IntGauge,
     pub reboot_duration: IntGauge,
+    /// 1 while the boot into an upgraded image awaits the replica's health
+    /// verification, 0 otherwise
+    pub upgrade_verification_pending: IntGauge,
+    pub upgrade_rollbacks: IntCounter,
 }
 
 impl OrchestratorMetrics {
@@ -40,6 +44,14 @@ impl OrchestratorMetrics {
                 "reboot_duration_seconds",
                 "The time it took for the node to reboot",
             ),
+            upgrade_verification_pending: metrics_registry.int_gauge(
+                "upgrade_verification_pending",
+                "Whether the upgraded replica still has to become healthy before the boot is confirmed",
+            ),
+            upgrade_rollbacks: metrics_registry.int_counter(
+                "upgrade_rollbacks_total",
+                "Number of upgrades rolled back because the upgraded replica did not become healthy",
+            ),
         }
     }
 }
@@ -8,6 +8,7 @@ use crate::registry_helper::RegistryHelper;
 use crate::replica_process::ReplicaProcess;
 use crate::ssh_access_manager::SshAccessManager;
 use crate::upgrade::Upgrade;
+use crate::upgrade_verification::UpgradeVerificationStatus;
 use ic_config::metrics::{Config as MetricsConfig, Exporter};
 use ic_crypto::utils::get_node_keys_or_generate_if_missing;
 use ic_crypto::{CryptoComponent, CryptoComponentForNonReplicaProcess};
@@ -168,6 +169,9 @@ impl Orchestrator {
         );
         let metrics = Arc::new(metrics);
 
+        let upgrade_verification_status: Arc<RwLock<UpgradeVerificationStatus>> =
+            Default::default();
+
         let upgrade = Some(
             Upgrade::new(
                 Arc::clone(&registry),
@@ -182,6 +186,8 @@ impl Orchestrator {
                 args.replica_binary_dir.clone(),
                 logger.clone(),
                 args.orchestrator_data_directory.clone(),
+                Duration::from_secs(args.upgrade_verification_timeout_secs),
+                Arc::clone(&upgrade_verification_status),
             )
             .await,
         );
@@ -208,6 +214,7 @@ impl Orchestrator {
             Arc::clone(&subnet_id),
             replica_version,
             cup_provider,
+            upgrade_verification_status,
             logger.clone(),
         ));
 
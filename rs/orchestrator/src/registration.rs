@@ -19,7 +19,7 @@ use ic_nns_constants::REGISTRY_CANISTER_ID;
 use ic_protobuf::registry::crypto::v1::PublicKey;
 use ic_registry_local_store::LocalStore;
 use ic_sys::utility_command::UtilityCommand;
-use ic_types::{messages::MessageId, NodeId};
+use ic_types::{crypto::threshold_sig::ThresholdSigPublicKey, messages::MessageId, NodeId};
 use prost::Message;
 use rand::prelude::*;
 use registry_canister::mutations::do_update_node_directly::UpdateNodeDirectlyPayload;
@@ -121,7 +121,11 @@ impl NodeRegistration {
                 Some(url) => url,
                 None => continue,
             };
-            let agent = Agent::new(nns_url, sender);
+            let nns_public_key = match self.get_nns_public_key() {
+                Some(public_key) => public_key,
+                None => continue,
+            };
+            let agent = Agent::new(nns_url, sender).with_root_key(nns_public_key);
 
             if let Err(e) = agent
                 .execute_update(
@@ -206,6 +210,10 @@ impl NodeRegistration {
             Some(url) => url,
             None => return,
         };
+        let nns_public_key = match self.get_nns_public_key() {
+            Some(public_key) => public_key,
+            None => return,
+        };
         let node_id = self.node_id;
 
         let node_pub_key = if let Some(pk) = self.key_handler.node_public_keys().node_signing_pk {
@@ -231,7 +239,7 @@ impl NodeRegistration {
             sign: Arc::new(sign_cmd),
         };
 
-        let agent = Agent::new(nns_url.clone(), sender);
+        let agent = Agent::new(nns_url.clone(), sender).with_root_key(nns_public_key);
         let update_node_payload = UpdateNodeDirectlyPayload {
             idkg_dealing_encryption_pk: Some(protobuf_to_vec(idkg_pk)),
         };
@@ -263,6 +271,31 @@ impl NodeRegistration {
         }
     }
 
+    /// Returns the public key of the NNS subnet, against which the replies of
+    /// the NNS are verified.
+    fn get_nns_public_key(&self) -> Option<ThresholdSigPublicKey> {
+        use ic_registry_client_helpers::{crypto::CryptoRegistry, subnet::SubnetRegistry};
+        let version = self.registry_client.get_latest_version();
+        let result = self
+            .registry_client
+            .get_root_subnet_id(version)
+            .map_err(|e| format!("Error when fetching NNS subnet ID: {:?}", e))
+            .and_then(|subnet_id| subnet_id.ok_or_else(|| "NNS subnet ID not defined".to_string()))
+            .and_then(|subnet_id| {
+                self.registry_client
+                    .get_threshold_signing_public_key_for_subnet(subnet_id, version)
+                    .map_err(|e| format!("Error when fetching NNS public key: {:?}", e))?
+                    .ok_or_else(|| "NNS public key not defined".to_string())
+            });
+        match result {
+            Ok(public_key) => Some(public_key),
+            Err(e) => {
+                warn!(self.log, "Failed to get NNS public key: {}", e);
+                None
+            }
+        }
+    }
+
     async fn collect_shuffled_nns_urls(&self) -> Result<Vec<Url>, String> {
         let version = self.registry_client.get_latest_version();
         if version == ZERO_REGISTRY_VERSION {
@@ -3,6 +3,11 @@ use crate::error::{OrchestratorError, OrchestratorResult};
 use crate::metrics::OrchestratorMetrics;
 use crate::registry_helper::RegistryHelper;
 use crate::replica_process::ReplicaProcess;
+use crate::upgrade_verification::{
+    load_pending_upgrade, load_rolled_back_version, persist_pending_upgrade,
+    persist_rolled_back_version, remove_pending_upgrade, PendingUpgrade, UpgradeDecision,
+    UpgradeObservation, UpgradeVerification, UpgradeVerificationStatus,
+};
 use async_trait::async_trait;
 use ic_http_utils::file_downloader::FileDownloader;
 use ic_image_upgrader::error::{UpgradeError, UpgradeResult};
@@ -16,7 +21,10 @@ use ic_registry_replicator::RegistryReplicator;
 use ic_types::consensus::{CatchUpPackage, HasHeight};
 use ic_types::{Height, NodeId, RegistryVersion, ReplicaVersion, SubnetId};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::RwLock;
 
 /// Provides function to continuously check the Registry to determine if this
 /// node should upgrade to a new release package, and if so, downloads and
@@ -24,6 +32,7 @@ use std::sync::{Arc, Mutex};
 /// within.
 pub(crate) struct Upgrade {
     pub registry: Arc<RegistryHelper>,
+    metrics: Arc<OrchestratorMetrics>,
     replica_process: Arc<Mutex<ReplicaProcess>>,
     cup_provider: Arc<CatchUpPackageProvider>,
     replica_version: ReplicaVersion,
@@ -36,6 +45,13 @@ pub(crate) struct Upgrade {
     /// The replica version that is prepared by 'prepare_upgrade' to upgrade to.
     pub prepared_upgrade_version: Option<ReplicaVersion>,
     pub orchestrator_data_directory: Option<PathBuf>,
+    /// How long an upgraded replica has to become healthy before the boot
+    /// into its image is rolled back. Zero disables the verification.
+    upgrade_verification_timeout: Duration,
+    upgrade_verification_status: Arc<RwLock<UpgradeVerificationStatus>>,
+    /// How often the replica process was started since the orchestrator
+    /// started.
+    replica_starts: AtomicUsize,
 }
 
 impl Upgrade {
@@ -53,9 +69,12 @@ impl Upgrade {
         release_content_dir: PathBuf,
         logger: ReplicaLogger,
         orchestrator_data_directory: Option<PathBuf>,
+        upgrade_verification_timeout: Duration,
+        upgrade_verification_status: Arc<RwLock<UpgradeVerificationStatus>>,
     ) -> Self {
         let value = Self {
             registry,
+            metrics: Arc::clone(&metrics),
             replica_process,
             cup_provider,
             node_id,
@@ -67,14 +86,232 @@ impl Upgrade {
             logger: logger.clone(),
             prepared_upgrade_version: None,
             orchestrator_data_directory,
+            upgrade_verification_timeout,
+            upgrade_verification_status,
+            replica_starts: AtomicUsize::new(0),
         };
         if let Err(e) = value.report_reboot_time(metrics) {
             warn!(logger, "Cannot report the reboot time: {}", e);
         }
-        value.confirm_boot().await;
+        if let Err(e) = value.start_upgrade_verification().await {
+            warn!(logger, "Cannot verify the upgrade: {}", e);
+            value.confirm_boot().await;
+        }
         value
     }
 
+    /// Confirms the boot into the current image, unless the image was booted
+    /// into by an upgrade which still has to be verified. Also detects if the
+    /// last upgrade was rolled back, in which case the boot loader brought us
+    /// back to the previous image.
+    async fn start_upgrade_verification(&self) -> OrchestratorResult<()> {
+        let data_dir = match &self.orchestrator_data_directory {
+            Some(dir) => dir,
+            None => {
+                self.confirm_boot().await;
+                return Ok(());
+            }
+        };
+        let mut status = self.upgrade_verification_status.write().await;
+        status.rolled_back_version = load_rolled_back_version(data_dir)?;
+        match load_pending_upgrade(data_dir)? {
+            Some(upgrade)
+                if upgrade.target_version == self.replica_version
+                    && !self.upgrade_verification_timeout.is_zero() =>
+            {
+                let start_height = self
+                    .cup_provider
+                    .get_local_cup()
+                    .map(|cup| cup.cup.content.height());
+                info!(
+                    self.logger,
+                    "Verifying the upgrade {} -> {}: the boot will be confirmed once a CUP above height {:?} is found",
+                    upgrade.previous_version,
+                    upgrade.target_version,
+                    start_height
+                );
+                self.metrics.upgrade_verification_pending.set(1);
+                status.pending = Some(UpgradeVerification::new(
+                    upgrade,
+                    start_height,
+                    self.upgrade_verification_timeout,
+                ));
+                // The boot is confirmed by `verify_upgrade()`.
+                return Ok(());
+            }
+            Some(upgrade) if upgrade.previous_version == self.replica_version => {
+                warn!(
+                    self.logger,
+                    "The upgrade to {} was rolled back to {}; it will not be retried",
+                    upgrade.target_version,
+                    upgrade.previous_version
+                );
+                persist_rolled_back_version(data_dir, &upgrade.target_version)?;
+                status.rolled_back_version = Some(upgrade.target_version);
+                self.metrics.upgrade_rollbacks.inc();
+            }
+            _ => {}
+        }
+        remove_pending_upgrade(data_dir)?;
+        self.confirm_boot().await;
+        Ok(())
+    }
+
+    /// Confirms the boot once the upgraded replica is healthy, or rolls back to
+    /// the previous image if the replica is broken, see
+    /// [UpgradeVerification::decide].
+    async fn verify_upgrade(&self, check_result: &OrchestratorResult<Option<SubnetId>>) {
+        let verification = match &self.upgrade_verification_status.read().await.pending {
+            Some(verification) => verification.clone(),
+            None => return,
+        };
+        let replica_restarts = self
+            .replica_starts
+            .load(Ordering::Relaxed)
+            .saturating_sub(1);
+        let observation = match check_result {
+            Ok(None) => UpgradeObservation::default(),
+            Ok(Some(subnet_id)) => UpgradeObservation {
+                assigned: true,
+                replica_restarts,
+                replica_cup_height: self.get_replica_cup_height(*subnet_id).await,
+                subnet_cup_height: self
+                    .cup_provider
+                    .get_local_cup()
+                    .map(|cup| cup.cup.content.height()),
+            },
+            Err(_) => UpgradeObservation {
+                assigned: true,
+                replica_restarts,
+                ..Default::default()
+            },
+        };
+
+        match verification.decide(&observation) {
+            UpgradeDecision::Confirm => {
+                info!(
+                    self.logger,
+                    "The upgrade to {} is verified, confirming the boot",
+                    verification.upgrade.target_version
+                );
+                self.confirm_boot().await;
+                if let Some(dir) = &self.orchestrator_data_directory {
+                    if let Err(e) = remove_pending_upgrade(dir) {
+                        warn!(self.logger, "Cannot remove the pending upgrade: {}", e);
+                    }
+                }
+                self.upgrade_verification_status.write().await.pending = None;
+                self.metrics.upgrade_verification_pending.set(0);
+            }
+            UpgradeDecision::RollBack => {
+                warn!(
+                    self.logger,
+                    "The replica is broken after the upgrade to {} ({:?}), rolling back to {}",
+                    verification.upgrade.target_version,
+                    observation,
+                    verification.upgrade.previous_version
+                );
+                if let Err(e) = self.rollback().await {
+                    warn!(self.logger, "Cannot roll back the upgrade: {}", e);
+                }
+            }
+            UpgradeDecision::Wait if verification.is_expired() => warn!(
+                self.logger,
+                "The upgrade to {} is not verified after {:?}, but the subnet does not progress either; not rolling back ({:?})",
+                verification.upgrade.target_version,
+                self.upgrade_verification_timeout,
+                observation
+            ),
+            UpgradeDecision::Wait => {}
+        }
+    }
+
+    /// Returns the height of the CUP served by the replica of this node.
+    async fn get_replica_cup_height(&self, subnet_id: SubnetId) -> Option<Height> {
+        let node_record = self
+            .registry
+            .registry_client
+            .get_transport_info(self.node_id, self.registry.get_latest_version())
+            .ok()
+            .flatten()?;
+        self.cup_provider
+            .get_node_cup(&node_record, subnet_id)
+            .await
+            .map(|cup| cup.cup.content.height())
+    }
+
+    /// Returns true if an upgrade to the given version may be prepared or
+    /// executed. This is not the case while the boot into the current image
+    /// is unconfirmed, as it would overwrite the image to roll back to, nor
+    /// for a version that was rolled back before.
+    async fn may_upgrade_to(&self, version: &ReplicaVersion) -> bool {
+        let status = self.upgrade_verification_status.read().await;
+        if status.pending.is_some() {
+            info!(
+                self.logger,
+                "Postponing the upgrade to {} until the current version is verified", version
+            );
+            return false;
+        }
+        if status.rolled_back_version.as_ref() == Some(version) {
+            warn!(
+                self.logger,
+                "Skipping the upgrade to {}, which was rolled back before", version
+            );
+            return false;
+        }
+        true
+    }
+
+    /// Forgets the version this node rolled back from once the registry
+    /// requires a different version, so that the rolled back version is not
+    /// refused forever.
+    async fn clear_superseded_rolled_back_version(&self, registry_version: &ReplicaVersion) {
+        let mut status = self.upgrade_verification_status.write().await;
+        match status.clear_superseded_rolled_back_version(
+            self.orchestrator_data_directory.as_deref(),
+            registry_version,
+        ) {
+            Ok(Some(version)) => info!(
+                self.logger,
+                "The registry replica version moved from the rolled back version {} to {}",
+                version,
+                registry_version
+            ),
+            Ok(None) => {}
+            Err(e) => warn!(self.logger, "Cannot remove the rolled back version: {}", e),
+        }
+    }
+
+    /// Executes the upgrade to the given version, after persisting the
+    /// current version so the upgrade can be verified after the reboot.
+    async fn execute_verified_upgrade<T>(
+        &mut self,
+        version: &ReplicaVersion,
+    ) -> OrchestratorResult<T> {
+        let data_dir = self.orchestrator_data_directory.clone();
+        if let Some(dir) = &data_dir {
+            persist_pending_upgrade(
+                dir,
+                &PendingUpgrade {
+                    previous_version: self.replica_version.clone(),
+                    target_version: version.clone(),
+                },
+            )?;
+        }
+        let result = self
+            .execute_upgrade(version)
+            .await
+            .map_err(OrchestratorError::from);
+        // We only get here if the upgrade failed before the reboot.
+        if let Some(dir) = &data_dir {
+            if let Err(e) = remove_pending_upgrade(dir) {
+                warn!(self.logger, "Cannot remove the pending upgrade: {}", e);
+            }
+        }
+        result
+    }
+
     fn report_reboot_time(&self, metrics: Arc<OrchestratorMetrics>) -> OrchestratorResult<()> {
         let elapsed_time = self.get_time_since_last_reboot_trigger()?;
         metrics.reboot_duration.set(elapsed_time.as_secs() as i64);
@@ -120,6 +357,11 @@ impl Upgrade {
             .get_latest_cup(local_cup, subnet_id)
             .await?;
 
+        // The replica does not read the registry below the version of the
+        // latest CUP, so older versions may be compacted in the local store.
+        self.registry_replicator
+            .retain_versions_since(cup.cup.content.registry_version());
+
         // If the CUP is unsigned, it's a registry CUP and we're in a genesis or subnet
         // recovery scenario. Check if we're in an NNS subnet recovery case and download
         // the new registry if needed.
@@ -161,7 +403,11 @@ impl Upgrade {
         let new_replica_version = self
             .registry
             .get_replica_version(subnet_id, cup_registry_version)?;
-        if new_replica_version != self.replica_version {
+        self.clear_superseded_rolled_back_version(&new_replica_version)
+            .await;
+        if new_replica_version != self.replica_version
+            && self.may_upgrade_to(&new_replica_version).await
+        {
             info!(
                 self.logger,
                 "Starting version upgrade at CUP registry version {}: {} -> {}",
@@ -172,13 +418,11 @@ impl Upgrade {
             // Only downloads the new image if it doesn't already exists locally, i.e. it
             // was previously downloaded by `prepare_upgrade_if_scheduled()`, see
             // below.
-            return self
-                .execute_upgrade(&new_replica_version)
-                .await
-                .map_err(OrchestratorError::from);
+            return self.execute_verified_upgrade(&new_replica_version).await;
         }
 
-        // If we arrive here, we are on the newest replica version.
+        // If we arrive here, we are on the newest replica version, or an upgrade
+        // is postponed or skipped by the upgrade verification.
         // Now we check if a subnet recovery is in progress.
         // If it is, we restart to pass the unsigned CUP to consensus.
         self.stop_replica_if_new_recovery_cup(&cup.cup, old_cup_height);
@@ -252,7 +496,9 @@ impl Upgrade {
     ) -> OrchestratorResult<()> {
         let (expected_replica_version, registry_version) =
             self.registry.get_expected_replica_version(subnet_id)?;
-        if expected_replica_version != self.replica_version {
+        if expected_replica_version != self.replica_version
+            && self.may_upgrade_to(&expected_replica_version).await
+        {
             info!(
                 self.logger,
                 "Replica version upgrade detected at registry version {}: {} -> {}",
@@ -270,7 +516,9 @@ impl Upgrade {
         let replica_version = self
             .registry
             .get_unassigned_replica_version(registry_version)?;
-        if self.replica_version == replica_version {
+        self.clear_superseded_rolled_back_version(&replica_version)
+            .await;
+        if self.replica_version == replica_version || !self.may_upgrade_to(&replica_version).await {
             return Ok(());
         }
         info!(
@@ -279,9 +527,7 @@ impl Upgrade {
             self.replica_version,
             replica_version
         );
-        self.execute_upgrade(&replica_version)
-            .await
-            .map_err(OrchestratorError::from)
+        self.execute_verified_upgrade(&replica_version).await
     }
 
     /// Stop the current replica process.
@@ -351,7 +597,9 @@ impl Upgrade {
             .start(replica_binary, replica_version, cmd)
             .map_err(|e| {
                 OrchestratorError::IoError("Error when attempting to start new replica".into(), e)
-            })
+            })?;
+        self.replica_starts.fetch_add(1, Ordering::Relaxed);
+        Ok(())
     }
 }
 
@@ -396,7 +644,9 @@ impl ImageUpgrader<ReplicaVersion, Option<SubnetId>> for Upgrade {
     }
 
     async fn check_for_upgrade(&mut self) -> UpgradeResult<Option<SubnetId>> {
-        self.check().await.map_err(UpgradeError::from)
+        let result = self.check().await;
+        self.verify_upgrade(&result).await;
+        result.map_err(UpgradeError::from)
     }
 }
 
@@ -0,0 +1,429 @@
+use crate::error::{OrchestratorError, OrchestratorResult};
+use ic_types::{Height, ReplicaVersion};
+use std::convert::TryFrom;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const PENDING_UPGRADE_FILENAME: &str = "pending_upgrade.txt";
+const ROLLED_BACK_VERSION_FILENAME: &str = "rolled_back_version.txt";
+
+/// An upgrade that was executed but whose image has not been confirmed yet.
+///
+/// It is persisted in the orchestrator data directory right before rebooting
+/// into the new image, so that both the new image (to verify the upgrade) and
+/// the previous one (to detect a rollback) can find it after the reboot.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct PendingUpgrade {
+    pub previous_version: ReplicaVersion,
+    pub target_version: ReplicaVersion,
+}
+
+/// The number of times the upgraded replica may be restarted after exiting
+/// before the upgrade is rolled back.
+const MAX_REPLICA_RESTARTS: usize = 3;
+
+/// Tracks the verification of an upgraded replica after the reboot.
+#[derive(Clone, Debug)]
+pub(crate) struct UpgradeVerification {
+    pub upgrade: PendingUpgrade,
+    /// The height of the local CUP when the orchestrator started. The upgraded
+    /// replica is considered healthy once it produced a CUP above it.
+    pub start_height: Option<Height>,
+    pub deadline: Instant,
+}
+
+/// What the orchestrator observed about the upgraded node after a check.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct UpgradeObservation {
+    /// Whether the node is assigned to a subnet, i.e. runs a replica.
+    pub assigned: bool,
+    /// How often the replica had to be restarted since the orchestrator
+    /// started.
+    pub replica_restarts: usize,
+    /// The height of the CUP served by the local replica, if any.
+    pub replica_cup_height: Option<Height>,
+    /// The height of the latest CUP of the subnet, as obtained from the peers,
+    /// the registry or the disk.
+    pub subnet_cup_height: Option<Height>,
+}
+
+/// The outcome of verifying an upgrade.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum UpgradeDecision {
+    /// The upgraded replica is healthy, the boot is confirmed.
+    Confirm,
+    /// The verification continues.
+    Wait,
+    /// The upgraded replica is broken, the upgrade is rolled back.
+    RollBack,
+}
+
+/// The state of the upgrade verification shared with the dashboard.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct UpgradeVerificationStatus {
+    /// Set while the boot into the current version is unconfirmed.
+    pub pending: Option<UpgradeVerification>,
+    /// The version this node last rolled back from, if any. Upgrades to this
+    /// version are not retried until the registry requires a different one.
+    pub rolled_back_version: Option<ReplicaVersion>,
+}
+
+impl UpgradeVerificationStatus {
+    /// Returns a human-readable description used by the dashboard.
+    pub(crate) fn describe(&self) -> String {
+        let pending = match &self.pending {
+            None => "None".to_string(),
+            Some(verification) => format!(
+                "{} -> {}, waiting for a CUP above height {}, {}s left",
+                verification.upgrade.previous_version,
+                verification.upgrade.target_version,
+                verification
+                    .start_height
+                    .map_or_else(|| "None".to_string(), |h| h.to_string()),
+                verification
+                    .deadline
+                    .saturating_duration_since(Instant::now())
+                    .as_secs()
+            ),
+        };
+        let rolled_back = match &self.rolled_back_version {
+            None => "None".to_string(),
+            Some(version) => version.to_string(),
+        };
+        format!(
+            "upgrade verification: {}\nrolled back version: {}",
+            pending, rolled_back
+        )
+    }
+
+    /// Forgets the rolled back version once the registry requires a different
+    /// version for this node, e.g. a fixed release or the current version, so
+    /// that a later upgrade to the rolled back version is attempted again.
+    /// Returns the forgotten version, if any.
+    pub(crate) fn clear_superseded_rolled_back_version(
+        &mut self,
+        data_dir: Option<&Path>,
+        registry_version: &ReplicaVersion,
+    ) -> OrchestratorResult<Option<ReplicaVersion>> {
+        match &self.rolled_back_version {
+            Some(version) if version != registry_version => {
+                if let Some(dir) = data_dir {
+                    remove_rolled_back_version(dir)?;
+                }
+                Ok(self.rolled_back_version.take())
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+impl UpgradeVerification {
+    pub(crate) fn new(
+        upgrade: PendingUpgrade,
+        start_height: Option<Height>,
+        timeout: Duration,
+    ) -> Self {
+        Self {
+            upgrade,
+            start_height,
+            deadline: Instant::now() + timeout,
+        }
+    }
+
+    /// Returns true if a CUP at the given height shows that the upgraded
+    /// replica makes progress.
+    pub(crate) fn is_reached(&self, cup_height: Option<Height>) -> bool {
+        match (cup_height, self.start_height) {
+            (Some(height), Some(start_height)) => height > start_height,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    pub(crate) fn is_expired(&self) -> bool {
+        Instant::now() >= self.deadline
+    }
+
+    /// Decides whether the upgrade is verified, given what was observed after
+    /// a check.
+    ///
+    /// Unassigned nodes do not run a replica and are healthy. Assigned nodes
+    /// are healthy once their replica serves a CUP above the start height.
+    /// The upgrade is only rolled back on signals local to this node: the
+    /// replica exiting repeatedly, or the subnet producing CUPs above the
+    /// start height while the replica does not until the deadline. If the
+    /// whole subnet stalls, all nodes keep the upgraded version, as rolling
+    /// them all back would not make the subnet progress.
+    pub(crate) fn decide(&self, observation: &UpgradeObservation) -> UpgradeDecision {
+        if !observation.assigned || self.is_reached(observation.replica_cup_height) {
+            UpgradeDecision::Confirm
+        } else if observation.replica_restarts >= MAX_REPLICA_RESTARTS
+            || (self.is_expired() && self.is_reached(observation.subnet_cup_height))
+        {
+            UpgradeDecision::RollBack
+        } else {
+            UpgradeDecision::Wait
+        }
+    }
+}
+
+pub(crate) fn persist_pending_upgrade(
+    data_dir: &Path,
+    upgrade: &PendingUpgrade,
+) -> OrchestratorResult<()> {
+    let path = data_dir.join(PENDING_UPGRADE_FILENAME);
+    std::fs::write(
+        &path,
+        format!("{}\n{}\n", upgrade.previous_version, upgrade.target_version),
+    )
+    .map_err(|e| OrchestratorError::file_write_error(&path, e))
+}
+
+pub(crate) fn load_pending_upgrade(data_dir: &Path) -> OrchestratorResult<Option<PendingUpgrade>> {
+    let path = data_dir.join(PENDING_UPGRADE_FILENAME);
+    let contents = match read_if_exists(&path)? {
+        Some(contents) => contents,
+        None => return Ok(None),
+    };
+    let mut lines = contents.lines();
+    let mut next_version = || {
+        let line = lines.next().unwrap_or_default();
+        ReplicaVersion::try_from(line.trim()).map_err(|e| {
+            OrchestratorError::UpgradeError(format!(
+                "Couldn't parse the pending upgrade in {:?}: {}",
+                path, e
+            ))
+        })
+    };
+    Ok(Some(PendingUpgrade {
+        previous_version: next_version()?,
+        target_version: next_version()?,
+    }))
+}
+
+pub(crate) fn remove_pending_upgrade(data_dir: &Path) -> OrchestratorResult<()> {
+    remove_if_exists(&data_dir.join(PENDING_UPGRADE_FILENAME))
+}
+
+pub(crate) fn persist_rolled_back_version(
+    data_dir: &Path,
+    version: &ReplicaVersion,
+) -> OrchestratorResult<()> {
+    let path = data_dir.join(ROLLED_BACK_VERSION_FILENAME);
+    std::fs::write(&path, version.as_ref())
+        .map_err(|e| OrchestratorError::file_write_error(&path, e))
+}
+
+pub(crate) fn load_rolled_back_version(
+    data_dir: &Path,
+) -> OrchestratorResult<Option<ReplicaVersion>> {
+    let path = data_dir.join(ROLLED_BACK_VERSION_FILENAME);
+    match read_if_exists(&path)? {
+        None => Ok(None),
+        Some(contents) => ReplicaVersion::try_from(contents.trim())
+            .map(Some)
+            .map_err(OrchestratorError::ReplicaVersionParseError),
+    }
+}
+
+pub(crate) fn remove_rolled_back_version(data_dir: &Path) -> OrchestratorResult<()> {
+    remove_if_exists(&data_dir.join(ROLLED_BACK_VERSION_FILENAME))
+}
+
+fn read_if_exists(path: &Path) -> OrchestratorResult<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(OrchestratorError::IoError(
+            format!("Failed to read file: {:?}", path),
+            e,
+        )),
+    }
+}
+
+fn remove_if_exists(path: &Path) -> OrchestratorResult<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(OrchestratorError::IoError(
+            format!("Failed to remove file: {:?}", path),
+            e,
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(v: &str) -> ReplicaVersion {
+        ReplicaVersion::try_from(v).unwrap()
+    }
+
+    #[test]
+    fn pending_upgrade_roundtrips_through_the_data_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(load_pending_upgrade(dir.path()).unwrap(), None);
+
+        let upgrade = PendingUpgrade {
+            previous_version: version("0.8.0"),
+            target_version: version("0.9.0"),
+        };
+        persist_pending_upgrade(dir.path(), &upgrade).unwrap();
+        assert_eq!(load_pending_upgrade(dir.path()).unwrap(), Some(upgrade));
+
+        remove_pending_upgrade(dir.path()).unwrap();
+        assert_eq!(load_pending_upgrade(dir.path()).unwrap(), None);
+        // Removing it again is a no-op.
+        remove_pending_upgrade(dir.path()).unwrap();
+    }
+
+    #[test]
+    fn rolled_back_version_roundtrips_through_the_data_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(load_rolled_back_version(dir.path()).unwrap(), None);
+
+        persist_rolled_back_version(dir.path(), &version("0.9.0")).unwrap();
+        assert_eq!(
+            load_rolled_back_version(dir.path()).unwrap(),
+            Some(version("0.9.0"))
+        );
+
+        remove_rolled_back_version(dir.path()).unwrap();
+        assert_eq!(load_rolled_back_version(dir.path()).unwrap(), None);
+    }
+
+    #[test]
+    fn rolled_back_version_is_cleared_once_superseded() {
+        let dir = tempfile::tempdir().unwrap();
+        persist_rolled_back_version(dir.path(), &version("0.9.0")).unwrap();
+        let mut status = UpgradeVerificationStatus {
+            pending: None,
+            rolled_back_version: load_rolled_back_version(dir.path()).unwrap(),
+        };
+
+        // The registry still requires the rolled back version.
+        assert_eq!(
+            status
+                .clear_superseded_rolled_back_version(Some(dir.path()), &version("0.9.0"))
+                .unwrap(),
+            None
+        );
+        assert_eq!(status.rolled_back_version, Some(version("0.9.0")));
+        assert_eq!(
+            load_rolled_back_version(dir.path()).unwrap(),
+            Some(version("0.9.0"))
+        );
+
+        // The registry moved on to another version.
+        assert_eq!(
+            status
+                .clear_superseded_rolled_back_version(Some(dir.path()), &version("0.9.1"))
+                .unwrap(),
+            Some(version("0.9.0"))
+        );
+        assert_eq!(status.rolled_back_version, None);
+        assert_eq!(load_rolled_back_version(dir.path()).unwrap(), None);
+    }
+
+    #[test]
+    fn verification_requires_a_cup_above_the_start_height() {
+        let upgrade = PendingUpgrade {
+            previous_version: version("0.8.0"),
+            target_version: version("0.9.0"),
+        };
+        let verification =
+            UpgradeVerification::new(upgrade.clone(), Some(Height::from(100)), Duration::ZERO);
+        assert!(verification.is_expired());
+        assert!(!verification.is_reached(None));
+        assert!(!verification.is_reached(Some(Height::from(100))));
+        assert!(verification.is_reached(Some(Height::from(200))));
+
+        let verification = UpgradeVerification::new(upgrade, None, Duration::from_secs(3600));
+        assert!(!verification.is_expired());
+        assert!(verification.is_reached(Some(Height::from(1))));
+    }
+
+    fn verification(timeout: Duration) -> UpgradeVerification {
+        UpgradeVerification::new(
+            PendingUpgrade {
+                previous_version: version("0.8.0"),
+                target_version: version("0.9.0"),
+            },
+            Some(Height::from(100)),
+            timeout,
+        )
+    }
+
+    fn assigned(
+        replica_restarts: usize,
+        replica_cup_height: Option<u64>,
+        subnet_cup_height: Option<u64>,
+    ) -> UpgradeObservation {
+        UpgradeObservation {
+            assigned: true,
+            replica_restarts,
+            replica_cup_height: replica_cup_height.map(Height::from),
+            subnet_cup_height: subnet_cup_height.map(Height::from),
+        }
+    }
+
+    #[test]
+    fn unassigned_node_is_confirmed() {
+        let observation = UpgradeObservation::default();
+        assert_eq!(
+            verification(Duration::ZERO).decide(&observation),
+            UpgradeDecision::Confirm
+        );
+    }
+
+    #[test]
+    fn replica_producing_a_new_cup_is_confirmed() {
+        let observation = assigned(1, Some(200), Some(200));
+        assert_eq!(
+            verification(Duration::ZERO).decide(&observation),
+            UpgradeDecision::Confirm
+        );
+    }
+
+    #[test]
+    fn verification_waits_for_the_replica_before_the_deadline() {
+        let verification = verification(Duration::from_secs(3600));
+        for observation in [
+            assigned(0, None, None),
+            assigned(0, Some(100), Some(200)),
+            assigned(MAX_REPLICA_RESTARTS - 1, Some(100), Some(100)),
+        ] {
+            assert_eq!(verification.decide(&observation), UpgradeDecision::Wait);
+        }
+    }
+
+    #[test]
+    fn crash_looping_replica_is_rolled_back() {
+        let observation = assigned(MAX_REPLICA_RESTARTS, None, None);
+        assert_eq!(
+            verification(Duration::from_secs(3600)).decide(&observation),
+            UpgradeDecision::RollBack
+        );
+    }
+
+    #[test]
+    fn replica_behind_its_subnet_is_rolled_back_after_the_deadline() {
+        let observation = assigned(0, Some(100), Some(200));
+        assert_eq!(
+            verification(Duration::ZERO).decide(&observation),
+            UpgradeDecision::RollBack
+        );
+    }
+
+    #[test]
+    fn stalled_subnet_is_not_rolled_back() {
+        let verification = verification(Duration::ZERO);
+        for observation in [
+            assigned(0, Some(100), Some(100)),
+            assigned(0, None, Some(100)),
+            assigned(0, None, None),
+        ] {
+            assert_eq!(verification.decide(&observation), UpgradeDecision::Wait);
+        }
+    }
+}
@@ -1096,6 +1096,7 @@ impl DownloadManagerImpl {
             artifact_id: advert_tracker.advert.artifact_id.clone(),
             integrity_hash: advert_tracker.advert.integrity_hash.clone(),
             chunk_id,
+            accepts_encoded_chunk: true,
         })
     }
 
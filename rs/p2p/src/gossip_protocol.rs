@@ -145,6 +145,10 @@ pub struct GossipChunkRequest {
     pub integrity_hash: CryptoHash,
     /// The chunk ID.
     pub(crate) chunk_id: ChunkId,
+    /// Whether the requesting node accepts an encoded chunk, see
+    /// [`ic_types::chunkable::ChunkableArtifact::get_encoded_chunk`]. Always
+    /// false in requests from nodes that predate encoded chunks.
+    pub(crate) accepts_encoded_chunk: bool,
 }
 
 /// A re-transmission request. A filter is used to restrict the set of
@@ -275,23 +279,29 @@ impl GossipImpl {
     }
 
     /// The method returns the artifact chunk matching the given chunk request
-    /// (if available).
+    /// (if available). The chunk is encoded only if the requesting node
+    /// accepts encoded chunks.
     fn serve_chunk(&self, gossip_request: &GossipChunkRequest) -> P2PResult<ArtifactChunk> {
-        self.artifact_manager
+        let artifact = self
+            .artifact_manager
             .get_validated_by_identifier(&gossip_request.artifact_id)
             .ok_or_else(|| {
                 self.metrics.chunk_req_not_found.inc();
                 P2PError {
                     p2p_error_code: P2PErrorCode::NotFound,
                 }
-            })?
-            .get_chunk(gossip_request.chunk_id)
-            .ok_or_else(|| {
-                self.metrics.chunk_req_not_found.inc();
-                P2PError {
-                    p2p_error_code: P2PErrorCode::NotFound,
-                }
-            })
+            })?;
+        let chunk = if gossip_request.accepts_encoded_chunk {
+            artifact.get_encoded_chunk(gossip_request.chunk_id)
+        } else {
+            artifact.get_chunk(gossip_request.chunk_id)
+        };
+        chunk.ok_or_else(|| {
+            self.metrics.chunk_req_not_found.inc();
+            P2PError {
+                p2p_error_code: P2PErrorCode::NotFound,
+            }
+        })
     }
 
     /// The method reacts in a malicious way when receiving a chunk
@@ -510,6 +520,7 @@ impl From<GossipChunkRequest> for pb::GossipChunkRequest {
             chunk_id: gossip_chunk_request.chunk_id.get(),
             integrity_hash: serialize(&gossip_chunk_request.integrity_hash)
                 .expect("Local value serialization should succeed"),
+            accepts_encoded_chunk: gossip_chunk_request.accepts_encoded_chunk,
         }
     }
 }
@@ -524,6 +535,7 @@ impl TryFrom<pb::GossipChunkRequest> for GossipChunkRequest {
             artifact_id: deserialize(&gossip_chunk_request.artifact_id)?,
             chunk_id: ChunkId::from(gossip_chunk_request.chunk_id),
             integrity_hash: deserialize(&gossip_chunk_request.integrity_hash)?,
+            accepts_encoded_chunk: gossip_chunk_request.accepts_encoded_chunk,
         })
     }
 }
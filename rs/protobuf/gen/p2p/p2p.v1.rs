@@ -36,6 +36,10 @@ pub struct GossipChunkRequest {
     pub chunk_id: u32,
     #[prost(bytes = "vec", tag = "3")]
     pub integrity_hash: ::prost::alloc::vec::Vec<u8>,
+    /// Set if the requesting node accepts the chunk as `encoded_chunk`. Nodes
+    /// that don't know this field always respond with a plain chunk.
+    #[prost(bool, tag = "4")]
+    pub accepts_encoded_chunk: bool,
 }
 #[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, ::prost::Message)]
 pub struct ArtifactFilter {
@@ -98,7 +102,7 @@ pub mod gossip_chunk {
 pub struct ArtifactChunk {
     #[prost(bytes = "vec", repeated, tag = "1")]
     pub witnesses: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
-    #[prost(oneof = "artifact_chunk::Data", tags = "2, 3")]
+    #[prost(oneof = "artifact_chunk::Data", tags = "2, 3, 4")]
     pub data: ::core::option::Option<artifact_chunk::Data>,
 }
 /// Nested message and enum types in `ArtifactChunk`.
@@ -110,6 +114,10 @@ pub mod artifact_chunk {
         Artifact(::prost::alloc::vec::Vec<u8>),
         #[prost(bytes, tag = "3")]
         Chunk(::prost::alloc::vec::Vec<u8>),
+        /// A chunk in an encoding of the artifact, only sent in response to
+        /// requests with `accepts_encoded_chunk` set.
+        #[prost(bytes, tag = "4")]
+        EncodedChunk(::prost::alloc::vec::Vec<u8>),
     }
 }
 #[derive(
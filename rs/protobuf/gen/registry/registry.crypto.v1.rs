@@ -65,6 +65,7 @@ pub enum AlgorithmId {
     RsaSha256 = 14,
     ThresholdEcdsaSecp256k1 = 15,
     MegaSecp256k1 = 16,
+    ThresholdEcdsaSecp256r1 = 18,
 }
 /// Types of curves that can be used for ECDSA signatures.
 #[derive(
@@ -85,4 +86,5 @@ pub enum AlgorithmId {
 pub enum EcdsaCurve {
     Unspecified = 0,
     Secp256k1 = 1,
+    Secp256r1 = 2,
 }
@@ -4,6 +4,9 @@ pub struct NodeTopology {
     pub ip_address: ::prost::alloc::string::String,
     #[prost(uint32, tag = "2")]
     pub http_port: u32,
+    /// The DER-encoded Ed25519 node signing public key.
+    #[prost(bytes = "vec", tag = "3")]
+    pub public_key: ::prost::alloc::vec::Vec<u8>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SubnetTopologyEntry {
@@ -176,6 +179,12 @@ pub struct SubnetMetrics {
     #[prost(message, optional, tag = "1")]
     pub consumed_cycles_by_deleted_canisters:
         ::core::option::Option<super::super::super::types::v1::NominalCycles>,
+    #[prost(uint64, tag = "2")]
+    pub num_canisters: u64,
+    #[prost(uint64, tag = "3")]
+    pub canister_state_bytes: u64,
+    #[prost(uint64, tag = "4")]
+    pub update_transactions_total: u64,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SystemMetadata {
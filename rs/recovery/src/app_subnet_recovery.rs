@@ -4,6 +4,7 @@ use crate::{error::RecoveryError, RecoveryArgs};
 use clap::Parser;
 use ic_base_types::{NodeId, SubnetId};
 use ic_types::ReplicaVersion;
+use serde::{Deserialize, Serialize};
 use slog::Logger;
 use std::net::IpAddr;
 use strum::IntoEnumIterator;
@@ -11,7 +12,7 @@ use strum_macros::EnumIter;
 
 use crate::{NeuronArgs, Recovery, Step};
 
-#[derive(Debug, Copy, Clone, EnumIter)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, EnumIter, Serialize, Deserialize)]
 pub enum StepType {
     Halt,
     DownloadState,
@@ -26,7 +27,7 @@ pub enum StepType {
     Cleanup,
 }
 
-#[derive(Parser)]
+#[derive(Clone, Debug, PartialEq, Parser, Serialize, Deserialize)]
 #[clap(version = "1.0")]
 pub struct AppSubnetRecoveryArgs {
     /// Id of the broken subnet
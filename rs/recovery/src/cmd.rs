@@ -6,7 +6,7 @@ use url::Url;
 use crate::{
     app_subnet_recovery::AppSubnetRecoveryArgs,
     nns_recovery_failover_nodes::NNSRecoveryFailoverNodesArgs,
-    nns_recovery_same_nodes::NNSRecoverySameNodesArgs,
+    nns_recovery_same_nodes::NNSRecoverySameNodesArgs, recovery_plan::RecoveryPlanArgs,
 };
 
 /// Subcommands for recovery procedures (application subnets, NNS with failover nodes, etc...)
@@ -18,6 +18,9 @@ pub enum SubCommand {
     NNSRecoveryFailoverNodes(Box<NNSRecoveryFailoverNodesArgs>),
     /// NNS recovery on the same nodes.
     NNSRecoverySameNodes(NNSRecoverySameNodesArgs),
+    /// Non-interactive recovery following a plan file. Progress is saved
+    /// after each step, and running the same plan again resumes the recovery.
+    RecoveryPlan(RecoveryPlanArgs),
 }
 
 #[derive(Parser)]
@@ -19,6 +19,7 @@ use ic_replay::cmd::{AddAndBlessReplicaVersionCmd, AddRegistryContentCmd, SubCom
 use ic_replay::player::StateParams;
 use ic_types::messages::HttpStatusResponse;
 use ic_types::{Height, ReplicaVersion, SubnetId};
+use serde::Deserialize;
 use slog::{info, warn, Logger};
 use ssh_helper::SshHelper;
 use std::net::IpAddr;
@@ -40,6 +41,7 @@ pub mod file_sync_helper;
 pub mod nns_recovery_failover_nodes;
 pub mod nns_recovery_same_nodes;
 pub mod recovery_iterator;
+pub mod recovery_plan;
 pub mod replay_helper;
 pub(crate) mod ssh_helper;
 pub mod steps;
@@ -56,8 +58,9 @@ pub const IC_REGISTRY_LOCAL_STORE: &str = "ic_registry_local_store";
 pub const CHECKPOINTS: &str = "checkpoints";
 pub const ADMIN: &str = "admin";
 pub const READONLY: &str = "readonly";
+pub const PLAN_STATE_FILE: &str = "plan_state.json";
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize)]
 pub struct NeuronArgs {
     dfx_hsm_pin: String,
     slot: String,
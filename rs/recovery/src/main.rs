@@ -3,8 +3,9 @@
 use clap::Parser;
 use ic_recovery::cli;
 use ic_recovery::cmd::{RecoveryToolArgs, SubCommand};
+use ic_recovery::recovery_plan;
 use ic_recovery::RecoveryArgs;
-use slog::{o, Drain};
+use slog::{error, o, Drain};
 
 fn main() {
     let decorator = slog_term::TermDecorator::new().build();
@@ -42,5 +43,13 @@ fn main() {
                 args.test,
             )
         }
+        SubCommand::RecoveryPlan(plan_args) => {
+            if let Err(e) =
+                recovery_plan::execute_plan(logger.clone(), recovery_args, plan_args, args.test)
+            {
+                error!(logger, "Recovery plan failed: {}", e);
+                std::process::exit(1);
+            }
+        }
     }
 }
@@ -6,6 +6,7 @@ use crate::{NeuronArgs, RecoveryResult, IC_REGISTRY_LOCAL_STORE};
 use clap::Parser;
 use ic_base_types::SubnetId;
 use ic_types::{NodeId, ReplicaVersion};
+use serde::{Deserialize, Serialize};
 use slog::Logger;
 use std::net::IpAddr;
 use std::path::PathBuf;
@@ -19,7 +20,7 @@ use crate::{Recovery, Step};
 /// Caller id that will be used to mutate the registry canister.
 pub const CANISTER_CALLER_ID: &str = "r7inp-6aaaa-aaaaa-aaabq-cai";
 
-#[derive(Debug, Copy, Clone, EnumIter)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, EnumIter, Serialize, Deserialize)]
 pub enum StepType {
     StopReplica,
     DownloadState,
@@ -36,7 +37,7 @@ pub enum StepType {
     Cleanup,
 }
 
-#[derive(Parser)]
+#[derive(Clone, Debug, PartialEq, Parser, Serialize, Deserialize)]
 #[clap(version = "1.0")]
 pub struct NNSRecoveryFailoverNodesArgs {
     /// Id of the broken subnet
@@ -5,6 +5,7 @@ use crate::{error::RecoveryError, RecoveryArgs};
 use clap::Parser;
 use ic_base_types::SubnetId;
 use ic_types::ReplicaVersion;
+use serde::{Deserialize, Serialize};
 use slog::Logger;
 use std::net::IpAddr;
 use std::path::PathBuf;
@@ -13,7 +14,7 @@ use strum_macros::EnumIter;
 
 use crate::{Recovery, Step};
 
-#[derive(Debug, Copy, Clone, EnumIter)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, EnumIter, Serialize, Deserialize)]
 pub enum StepType {
     StopReplica,
     DownloadState,
@@ -29,7 +30,7 @@ pub enum StepType {
     Cleanup,
 }
 
-#[derive(Parser)]
+#[derive(Clone, Debug, PartialEq, Parser, Serialize, Deserialize)]
 #[clap(version = "1.0")]
 pub struct NNSRecoverySameNodesArgs {
     /// Id of the broken subnet
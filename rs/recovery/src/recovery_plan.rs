@@ -0,0 +1,426 @@
+//! Non-interactive execution of recoveries described by a plan file. The
+//! progress of a plan is persisted to a state file after each completed step,
+//! so that an interrupted recovery can be resumed by running the same plan
+//! again.
+use crate::app_subnet_recovery::{self, AppSubnetRecovery, AppSubnetRecoveryArgs};
+use crate::cli::print_step;
+use crate::error::{RecoveryError, RecoveryResult};
+use crate::file_sync_helper::{read_file, write_file};
+use crate::nns_recovery_failover_nodes::{
+    self, NNSRecoveryFailoverNodes, NNSRecoveryFailoverNodesArgs,
+};
+use crate::nns_recovery_same_nodes::{self, NNSRecoverySameNodes, NNSRecoverySameNodesArgs};
+use crate::recovery_iterator::RecoveryIterator;
+use crate::{NeuronArgs, RecoveryArgs, PLAN_STATE_FILE};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use slog::{info, Logger};
+use std::fmt::Debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Parser)]
+#[clap(version = "1.0")]
+pub struct RecoveryPlanArgs {
+    /// Path to the JSON file describing the recovery to perform
+    #[clap(long, parse(from_os_str))]
+    pub plan: PathBuf,
+}
+
+/// A recovery plan, as read from a JSON plan file.
+#[derive(Deserialize)]
+pub struct RecoveryPlan {
+    /// The recovery to perform.
+    pub recovery: PlannedRecovery,
+
+    /// The neuron used to submit proposals. Required by the recoveries that
+    /// submit proposals, unless the tool runs in test mode.
+    #[serde(default)]
+    pub neuron: Option<NeuronArgs>,
+}
+
+/// The recovery procedure of a plan, with the parameters that would otherwise
+/// be entered interactively, and the steps that should not be executed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlannedRecovery {
+    AppSubnetRecovery {
+        params: AppSubnetRecoveryArgs,
+        #[serde(default)]
+        skip_steps: Vec<app_subnet_recovery::StepType>,
+    },
+    NnsRecoverySameNodes {
+        params: NNSRecoverySameNodesArgs,
+        #[serde(default)]
+        skip_steps: Vec<nns_recovery_same_nodes::StepType>,
+    },
+    NnsRecoveryFailoverNodes {
+        params: Box<NNSRecoveryFailoverNodesArgs>,
+        #[serde(default)]
+        skip_steps: Vec<nns_recovery_failover_nodes::StepType>,
+    },
+}
+
+/// The progress of a plan, persisted after each completed step.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlanState {
+    /// The recovery the progress belongs to.
+    pub recovery: PlannedRecovery,
+    /// The names of the steps completed so far, in order of execution.
+    pub completed_steps: Vec<String>,
+}
+
+impl PlanState {
+    /// Load the state of `recovery` from `file`, or return a fresh state if
+    /// the file does not exist. Fails if the file belongs to another recovery,
+    /// as resuming would then execute steps with the wrong parameters.
+    pub fn load_or_new(file: &Path, recovery: &PlannedRecovery) -> RecoveryResult<Self> {
+        if !file.exists() {
+            return Ok(Self {
+                recovery: recovery.clone(),
+                completed_steps: vec![],
+            });
+        }
+        let state: PlanState = serde_json::from_str(&read_file(file)?).map_err(|e| {
+            RecoveryError::invalid_output_error(format!(
+                "Failed to deserialize plan state {:?}: {}",
+                file, e
+            ))
+        })?;
+        if &state.recovery != recovery {
+            return Err(RecoveryError::UnexpectedError(format!(
+                "Plan state {:?} belongs to a different recovery plan. Remove it to start over.",
+                file
+            )));
+        }
+        Ok(state)
+    }
+
+    /// Persist the state to `file`. The state is written to a temporary file
+    /// first, so that a crash never leaves a truncated state behind.
+    pub fn save(&self, file: &Path) -> RecoveryResult<()> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| {
+            RecoveryError::invalid_output_error(format!("Failed to serialize plan state: {}", e))
+        })?;
+        let tmp_file = file.with_extension("tmp");
+        write_file(&tmp_file, json)?;
+        fs::rename(&tmp_file, file).map_err(|e| RecoveryError::file_error(file, e))
+    }
+}
+
+/// Read the recovery plan from the given file.
+pub fn read_plan(file: &Path) -> RecoveryResult<RecoveryPlan> {
+    serde_json::from_str(&read_file(file)?).map_err(|e| {
+        RecoveryError::invalid_output_error(format!(
+            "Failed to deserialize recovery plan {:?}: {}",
+            file, e
+        ))
+    })
+}
+
+/// Execute the recovery described by the plan file in `plan_args`, without
+/// asking for input. Steps completed by a previous run of the same plan are
+/// not executed again.
+pub fn execute_plan(
+    logger: Logger,
+    args: RecoveryArgs,
+    plan_args: RecoveryPlanArgs,
+    test: bool,
+) -> RecoveryResult<()> {
+    let plan = read_plan(&plan_args.plan)?;
+    let state_file = args.dir.join("recovery").join(PLAN_STATE_FILE);
+
+    let neuron_args = plan_neuron_args(&plan, test)?;
+
+    // The recovery creates the directory containing the state file.
+    match plan.recovery.clone() {
+        PlannedRecovery::AppSubnetRecovery { params, skip_steps } => {
+            let mut recovery = AppSubnetRecovery::new(logger.clone(), args, neuron_args, params);
+            let mut state = PlanState::load_or_new(&state_file, &plan.recovery)?;
+            execute_steps(&logger, &mut recovery, &skip_steps, &mut state, &state_file)
+        }
+        PlannedRecovery::NnsRecoverySameNodes { params, skip_steps } => {
+            let mut recovery = NNSRecoverySameNodes::new(logger.clone(), args, params, test);
+            let mut state = PlanState::load_or_new(&state_file, &plan.recovery)?;
+            execute_steps(&logger, &mut recovery, &skip_steps, &mut state, &state_file)
+        }
+        PlannedRecovery::NnsRecoveryFailoverNodes { params, skip_steps } => {
+            let mut recovery =
+                NNSRecoveryFailoverNodes::new(logger.clone(), args, neuron_args, *params);
+            let mut state = PlanState::load_or_new(&state_file, &plan.recovery)?;
+            execute_steps(&logger, &mut recovery, &skip_steps, &mut state, &state_file)
+        }
+    }
+}
+
+/// Returns the neuron of the plan. Fails if the planned recovery submits
+/// proposals but the plan has no neuron, as plans are executed without asking
+/// for input.
+fn plan_neuron_args(plan: &RecoveryPlan, test: bool) -> RecoveryResult<Option<NeuronArgs>> {
+    match &plan.neuron {
+        None if !test && !matches!(plan.recovery, PlannedRecovery::NnsRecoverySameNodes { .. }) => {
+            Err(RecoveryError::UnexpectedError(
+                "The recovery plan has no neuron to submit proposals with".to_string(),
+            ))
+        }
+        neuron => Ok(neuron.clone()),
+    }
+}
+
+/// Execute all steps of `recovery` that are neither in `skip_steps` nor
+/// already completed according to `state`, and record each completed step in
+/// `state_file`. Stops at the first failing step.
+pub fn execute_steps<T: Copy + Debug + PartialEq>(
+    logger: &Logger,
+    recovery: &mut impl RecoveryIterator<T>,
+    skip_steps: &[T],
+    state: &mut PlanState,
+    state_file: &Path,
+) -> RecoveryResult<()> {
+    while let Some(step_type) = recovery.get_step_iterator().next() {
+        let step_name = format!("{:?}", step_type);
+        if state.completed_steps.contains(&step_name) {
+            info!(logger, "Step {} was already completed", step_name);
+            continue;
+        }
+        if skip_steps.contains(&step_type) {
+            info!(
+                logger,
+                "Skipping step {} as requested by the plan", step_name
+            );
+            continue;
+        }
+
+        let step = match recovery.get_step_impl(step_type) {
+            Ok(step) => step,
+            Err(RecoveryError::StepSkipped) => {
+                info!(logger, "Skipping step {}", step_name);
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        print_step(logger, &step_name);
+        info!(logger, "{}", step.descr());
+        step.exec()?;
+
+        state.completed_steps.push(step_name);
+        state.save(state_file)?;
+    }
+    info!(logger, "All steps of the plan were completed");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::steps::Step;
+    use ic_types::PrincipalId;
+    use slog::o;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum TestStep {
+        First,
+        Second,
+        Third,
+    }
+
+    struct RecordingStep {
+        name: TestStep,
+        executed: Rc<RefCell<Vec<TestStep>>>,
+        fail: bool,
+    }
+
+    impl Step for RecordingStep {
+        fn descr(&self) -> String {
+            format!("{:?}", self.name)
+        }
+
+        fn exec(&self) -> RecoveryResult<()> {
+            if self.fail {
+                return Err(RecoveryError::UnexpectedError("step failed".to_string()));
+            }
+            self.executed.borrow_mut().push(self.name);
+            Ok(())
+        }
+    }
+
+    struct TestRecovery {
+        step_iterator: Box<dyn Iterator<Item = TestStep>>,
+        executed: Rc<RefCell<Vec<TestStep>>>,
+        failing_step: Option<TestStep>,
+        logger: Logger,
+    }
+
+    impl TestRecovery {
+        fn new(executed: Rc<RefCell<Vec<TestStep>>>, failing_step: Option<TestStep>) -> Self {
+            Self {
+                step_iterator: Box::new(
+                    vec![TestStep::First, TestStep::Second, TestStep::Third].into_iter(),
+                ),
+                executed,
+                failing_step,
+                logger: Logger::root(slog::Discard, o!()),
+            }
+        }
+    }
+
+    impl RecoveryIterator<TestStep> for TestRecovery {
+        fn get_step_iterator(&mut self) -> &mut Box<dyn Iterator<Item = TestStep>> {
+            &mut self.step_iterator
+        }
+
+        fn get_step_impl(&self, step_type: TestStep) -> RecoveryResult<Box<dyn Step>> {
+            Ok(Box::new(RecordingStep {
+                name: step_type,
+                executed: self.executed.clone(),
+                fail: self.failing_step == Some(step_type),
+            }))
+        }
+
+        fn get_logger(&self) -> &Logger {
+            &self.logger
+        }
+    }
+
+    fn planned_recovery() -> PlannedRecovery {
+        PlannedRecovery::AppSubnetRecovery {
+            params: AppSubnetRecoveryArgs {
+                subnet_id: PrincipalId::new_subnet_test_id(1).into(),
+                upgrade_version: None,
+                replacement_nodes: None,
+                pub_key: None,
+                download_node: None,
+                upload_node: None,
+                ecdsa_subnet_id: None,
+            },
+            skip_steps: vec![app_subnet_recovery::StepType::DownloadState],
+        }
+    }
+
+    #[test]
+    fn plan_is_resumed_after_the_last_completed_step() {
+        let dir = tempfile::tempdir().unwrap();
+        let state_file = dir.path().join(PLAN_STATE_FILE);
+        let logger = Logger::root(slog::Discard, o!());
+        let executed = Rc::new(RefCell::new(vec![]));
+
+        let mut state = PlanState::load_or_new(&state_file, &planned_recovery()).unwrap();
+        let mut recovery = TestRecovery::new(executed.clone(), Some(TestStep::Third));
+        assert!(execute_steps(&logger, &mut recovery, &[], &mut state, &state_file).is_err());
+        assert_eq!(*executed.borrow(), vec![TestStep::First, TestStep::Second]);
+
+        let mut state = PlanState::load_or_new(&state_file, &planned_recovery()).unwrap();
+        assert_eq!(state.completed_steps, vec!["First", "Second"]);
+        let mut recovery = TestRecovery::new(executed.clone(), None);
+        execute_steps(&logger, &mut recovery, &[], &mut state, &state_file).unwrap();
+        assert_eq!(
+            *executed.borrow(),
+            vec![TestStep::First, TestStep::Second, TestStep::Third]
+        );
+    }
+
+    #[test]
+    fn skipped_steps_are_not_executed() {
+        let dir = tempfile::tempdir().unwrap();
+        let state_file = dir.path().join(PLAN_STATE_FILE);
+        let logger = Logger::root(slog::Discard, o!());
+        let executed = Rc::new(RefCell::new(vec![]));
+
+        let mut state = PlanState::load_or_new(&state_file, &planned_recovery()).unwrap();
+        let mut recovery = TestRecovery::new(executed.clone(), None);
+        execute_steps(
+            &logger,
+            &mut recovery,
+            &[TestStep::Second],
+            &mut state,
+            &state_file,
+        )
+        .unwrap();
+
+        assert_eq!(*executed.borrow(), vec![TestStep::First, TestStep::Third]);
+    }
+
+    #[test]
+    fn state_of_another_plan_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let state_file = dir.path().join(PLAN_STATE_FILE);
+        PlanState::load_or_new(&state_file, &planned_recovery())
+            .unwrap()
+            .save(&state_file)
+            .unwrap();
+
+        let mut other_recovery = planned_recovery();
+        if let PlannedRecovery::AppSubnetRecovery { skip_steps, .. } = &mut other_recovery {
+            skip_steps.clear();
+        }
+
+        assert!(PlanState::load_or_new(&state_file, &other_recovery).is_err());
+    }
+
+    #[test]
+    fn plan_can_be_parsed() {
+        let dir = tempfile::tempdir().unwrap();
+        let plan_file = dir.path().join("plan.json");
+        write_file(
+            &plan_file,
+            r#"{
+                "recovery": {
+                    "app_subnet_recovery": {
+                        "params": {
+                            "subnet_id": "bn3el-jdvcs-a3syn-gyqwo-umlu3-avgud-vq6yl-hunln-3jejb-226vq-mae",
+                            "download_node": "2a00:fb01:400:42::1"
+                        },
+                        "skip_steps": ["BlessVersion", "UpgradeVersion"]
+                    }
+                }
+            }"#
+            .to_string(),
+        )
+        .unwrap();
+
+        let plan = read_plan(&plan_file).unwrap();
+
+        match plan.recovery {
+            PlannedRecovery::AppSubnetRecovery { params, skip_steps } => {
+                assert!(params.download_node.is_some());
+                assert!(params.upload_node.is_none());
+                assert_eq!(
+                    skip_steps,
+                    vec![
+                        app_subnet_recovery::StepType::BlessVersion,
+                        app_subnet_recovery::StepType::UpgradeVersion
+                    ]
+                );
+            }
+            _ => panic!("Unexpected recovery"),
+        }
+        assert!(plan.neuron.is_none());
+    }
+
+    #[test]
+    fn plan_without_neuron_is_rejected_if_proposals_are_submitted() {
+        let plan = RecoveryPlan {
+            recovery: planned_recovery(),
+            neuron: None,
+        };
+        assert!(plan_neuron_args(&plan, false).is_err());
+        assert!(plan_neuron_args(&plan, true).unwrap().is_none());
+
+        let plan = RecoveryPlan {
+            recovery: PlannedRecovery::NnsRecoverySameNodes {
+                params: NNSRecoverySameNodesArgs {
+                    subnet_id: PrincipalId::new_subnet_test_id(1).into(),
+                    upgrade_version: None,
+                    pub_key: None,
+                    download_node: None,
+                    upload_node: None,
+                },
+                skip_steps: vec![],
+            },
+            neuron: None,
+        };
+        assert!(plan_neuron_args(&plan, false).unwrap().is_none());
+    }
+}
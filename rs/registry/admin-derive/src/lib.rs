@@ -87,7 +87,9 @@ pub fn derive_common_proposal_fields(_: TokenStream, item: TokenStream) -> Token
                             summary_file: Option<PathBuf>,
 
                             /// If set, the fully formed proposal payload will be printed but not
-                            /// submitted.
+                            /// submitted. Proposals executed by the registry canister are also
+                            /// applied to a local copy of the registry, and the resulting changes
+                            /// or invariant violations are printed.
                             #[clap(long)]
                             pub dry_run: bool,
 
@@ -13,6 +13,7 @@ use ic_canister_client::{Agent, Sender};
 use ic_config::subnet_config::SchedulerConfig;
 use ic_crypto_sha::Sha256;
 use ic_crypto_utils_basic_sig::conversions::Ed25519SecretKeyConversions;
+use ic_crypto_utils_threshold_sig_der::parse_threshold_sig_key;
 use ic_http_utils::file_downloader::{check_file_hash, extract_tar_gz_into_dir, FileDownloader};
 use ic_prep_lib::subnet_configuration;
 use ic_registry_client_helpers::deserialize_registry_value;
@@ -31,7 +32,9 @@ use ic_nervous_system_root::{
     StopOrStartCanisterProposal,
 };
 use ic_nns_common::types::{NeuronId, ProposalId, UpdateIcpXdrConversionRatePayload};
-use ic_nns_constants::{memory_allocation_of, GOVERNANCE_CANISTER_ID, ROOT_CANISTER_ID};
+use ic_nns_constants::{
+    memory_allocation_of, GOVERNANCE_CANISTER_ID, REGISTRY_CANISTER_ID, ROOT_CANISTER_ID,
+};
 use ic_nns_governance::pb::v1::{
     add_or_remove_node_provider::Change, manage_neuron::Command, proposal::Action,
     AddOrRemoveNodeProvider, GovernanceError, ManageNeuron, NodeProvider, Proposal,
@@ -52,19 +55,21 @@ use ic_protobuf::registry::node_rewards::v2::{
     NodeRewardsTable, UpdateNodeRewardsTableProposalPayload,
 };
 use ic_protobuf::registry::{
-    crypto::v1::{PublicKey, X509PublicKeyCert},
+    crypto::v1::{EcdsaSigningSubnetList, PublicKey, X509PublicKeyCert},
+    nns::v1::NnsCanisterRecords,
     node::v1::NodeRecord,
     node_operator::v1::NodeOperatorRecord,
     provisional_whitelist::v1::ProvisionalWhitelist as ProvisionalWhitelistProto,
     replica_version::v1::{BlessedReplicaVersions, ReplicaVersionRecord},
     routing_table::v1::{CanisterMigrations, RoutingTable},
-    subnet::v1::{SubnetListRecord, SubnetRecord as SubnetRecordProto},
+    subnet::v1::{CatchUpPackageContents, SubnetListRecord, SubnetRecord as SubnetRecordProto},
     unassigned_nodes_config::v1::UnassignedNodesConfigRecord,
 };
 use ic_protobuf::registry::{
     dc::v1::{AddOrRemoveDataCentersProposalPayload, DataCenterRecord},
     node_operator::v1::RemoveNodeOperatorsPayload,
 };
+use ic_protobuf::types::v1::SubnetId as SubnetIdProto;
 use ic_registry_client::client::RegistryClientImpl;
 use ic_registry_client_helpers::{crypto::CryptoRegistry, subnet::SubnetRegistry};
 use ic_registry_keys::{
@@ -72,10 +77,13 @@ use ic_registry_keys::{
     make_canister_migrations_record_key, make_crypto_node_key,
     make_crypto_threshold_signing_pubkey_key, make_crypto_tls_cert_key,
     make_data_center_record_key, make_firewall_config_record_key, make_firewall_rules_record_key,
-    make_node_operator_record_key, make_node_record_key, make_provisional_whitelist_record_key,
-    make_replica_version_key, make_routing_table_record_key, make_subnet_list_record_key,
-    make_subnet_record_key, make_unassigned_nodes_config_record_key, FirewallRulesScope,
-    NODE_OPERATOR_RECORD_KEY_PREFIX, NODE_REWARDS_TABLE_KEY, ROOT_SUBNET_ID_KEY,
+    make_nns_canister_records_key, make_node_operator_record_key, make_node_record_key,
+    make_provisional_whitelist_record_key, make_replica_version_key, make_routing_table_record_key,
+    make_subnet_list_record_key, make_subnet_record_key, make_unassigned_nodes_config_record_key,
+    FirewallRulesScope, CRYPTO_RECORD_KEY_PREFIX, CRYPTO_THRESHOLD_SIGNING_KEY_PREFIX,
+    CRYPTO_TLS_CERT_KEY_PREFIX, DATA_CENTER_KEY_PREFIX, ECDSA_SIGNING_SUBNET_LIST_KEY_PREFIX,
+    NODE_OPERATOR_RECORD_KEY_PREFIX, NODE_RECORD_KEY_PREFIX, NODE_REWARDS_TABLE_KEY,
+    REPLICA_VERSION_KEY_PREFIX, ROOT_SUBNET_ID_KEY, SUBNET_RECORD_KEY_PREFIX,
 };
 use ic_registry_local_store::{
     Changelog, ChangelogEntry, KeyMutation, LocalStoreImpl, LocalStoreWriter,
@@ -85,6 +93,7 @@ use ic_registry_nns_data_provider::registry::RegistryCanister;
 use ic_registry_routing_table::CanisterIdRange;
 use ic_registry_subnet_features::{EcdsaConfig, SubnetFeatures, DEFAULT_ECDSA_MAX_QUEUE_SIZE};
 use ic_registry_subnet_type::SubnetType;
+use ic_registry_transport::pb::v1::RegistryDelta;
 use ic_registry_transport::Error;
 use ic_sns_wasm::pb::v1::{AddWasmRequest, SnsCanisterType, SnsWasm};
 use ic_types::{
@@ -105,8 +114,9 @@ use registry_canister::mutations::node_management::do_remove_nodes::RemoveNodesP
 use registry_canister::mutations::{
     complete_canister_migration::CompleteCanisterMigrationPayload,
     do_add_node_operator::AddNodeOperatorPayload, do_add_nodes_to_subnet::AddNodesToSubnetPayload,
-    do_bless_replica_version::BlessReplicaVersionPayload, do_create_subnet::CreateSubnetPayload,
-    do_recover_subnet::RecoverSubnetPayload,
+    do_bless_replica_version::BlessReplicaVersionPayload,
+    do_change_subnet_membership::ChangeSubnetMembershipPayload,
+    do_create_subnet::CreateSubnetPayload, do_recover_subnet::RecoverSubnetPayload,
     do_remove_nodes_from_subnet::RemoveNodesFromSubnetPayload,
     do_update_node_operator_config::UpdateNodeOperatorConfigPayload,
     do_update_subnet::UpdateSubnetPayload,
@@ -144,6 +154,13 @@ struct Opts {
     /// the NNS.
     secret_key_pem: Option<PathBuf>,
 
+    #[clap(long)]
+    /// The pem file containing the threshold public key of the root subnet
+    /// (a.k.a. NNS public key), against which the certificates of calls to
+    /// NNS canisters are verified. If not set, the key is read from the
+    /// registry.
+    nns_public_key: Option<PathBuf>,
+
     #[clap(subcommand)]
     subcmd: SubCommand,
 
@@ -259,6 +276,9 @@ enum SubCommand {
     ProposeToCreateSubnet(ProposeToCreateSubnetCmd),
     /// Submits a proposal to update an existing subnet.
     ProposeToAddNodesToSubnet(ProposeToAddNodesToSubnetCmd),
+    /// Submits a proposal to add nodes to and remove nodes from a subnet at
+    /// once, e.g., to replace faulty nodes.
+    ProposeToChangeSubnetMembership(ProposeToChangeSubnetMembershipCmd),
     /// Submits a proposal to update a subnet's recovery CUP
     ProposeToUpdateRecoveryCup(ProposeToUpdateRecoveryCupCmd),
     /// Submits a proposal to update an existing subnet's configuration.
@@ -314,6 +334,12 @@ enum SubCommand {
     ProposeToAddOrRemoveNodeProvider(ProposeToAddOrRemoveNodeProviderCmd),
     // Get latest registry version number
     GetRegistryVersion,
+    /// Get the keys whose value changed between two registry versions, with
+    /// their old and new values.
+    GetRegistryDiff(GetRegistryDiffCmd),
+    /// Get every version at which a registry key was set or deleted, with
+    /// the value it was set to.
+    GetKeyHistory(GetKeyHistoryCmd),
     // Submit a root proposal to the root canister to upgrade the governance canister.
     SubmitRootProposalToUpgradeGovernanceCanister(SubmitRootProposalToUpgradeGovernanceCanisterCmd),
     // Get the pending proposals to upgrade the governance canister.
@@ -535,6 +561,35 @@ struct GetNodeListSinceCmd {
     version: u64,
 }
 
+/// Sub-command to fetch the changes between two registry versions.
+#[derive(Parser)]
+struct GetRegistryDiffCmd {
+    /// The registry version to compare from.
+    #[clap(long)]
+    from: u64,
+
+    /// The registry version to compare to. Defaults to the latest version.
+    #[clap(long)]
+    to: Option<u64>,
+
+    /// If set, only keys starting with this prefix are compared, e.g.,
+    /// `subnet_record_`.
+    #[clap(long)]
+    prefix: Option<String>,
+}
+
+/// Sub-command to fetch all the versions of a registry key.
+#[derive(Parser)]
+struct GetKeyHistoryCmd {
+    /// The registry key, e.g., `subnet_list`.
+    key: String,
+
+    /// Only the versions after this registry version are listed. Defaults to
+    /// 0, listing the full history.
+    #[clap(long, default_value = "0")]
+    from: u64,
+}
+
 /// Sub-command to fetch a replica version from the registry.
 #[derive(Parser)]
 struct GetReplicaVersionCmd {
@@ -1192,6 +1247,58 @@ impl ProposalTitleAndPayload<AddNodesToSubnetPayload> for ProposeToAddNodesToSub
     }
 }
 
+/// Sub-command to submit a proposal to add nodes to and remove nodes from an
+/// existing subnet in a single registry version.
+#[derive_common_proposal_fields]
+#[derive(ProposalMetadata, Parser)]
+struct ProposeToChangeSubnetMembershipCmd {
+    #[clap(long, required = true, alias = "subnet-id")]
+    /// The subnet to modify
+    subnet: SubnetDescriptor,
+
+    #[clap(long, multiple_values(true))]
+    /// The node IDs of the unassigned nodes that will be added to the subnet.
+    pub node_ids_add: Vec<PrincipalId>,
+
+    #[clap(long, multiple_values(true))]
+    /// The node IDs of the nodes that will be removed from the subnet.
+    pub node_ids_remove: Vec<PrincipalId>,
+}
+
+#[async_trait]
+impl ProposalTitleAndPayload<ChangeSubnetMembershipPayload> for ProposeToChangeSubnetMembershipCmd {
+    fn title(&self) -> String {
+        match &self.proposal_title {
+            Some(title) => title.clone(),
+            None => format!(
+                "Add nodes: {}, remove nodes: {} in subnet: {}",
+                shortened_pids_string(&self.node_ids_add),
+                shortened_pids_string(&self.node_ids_remove),
+                shortened_subnet_string(&self.subnet)
+            ),
+        }
+    }
+
+    async fn payload(&self, nns_url: Url) -> ChangeSubnetMembershipPayload {
+        let registry_canister = RegistryCanister::new(vec![nns_url.clone()]);
+        ChangeSubnetMembershipPayload {
+            subnet_id: self.subnet.get_id(&registry_canister).await.get(),
+            node_ids_add: self
+                .node_ids_add
+                .iter()
+                .cloned()
+                .map(NodeId::from)
+                .collect(),
+            node_ids_remove: self
+                .node_ids_remove
+                .iter()
+                .cloned()
+                .map(NodeId::from)
+                .collect(),
+        }
+    }
+}
+
 /// Sub-command to submit a proposal to update the recovery CUP of a subnet.
 #[derive_common_proposal_fields]
 #[derive(ProposalMetadata, Parser)]
@@ -2860,6 +2967,7 @@ async fn get_subnet_record(
 async fn main() {
     let opts: Opts = Opts::parse();
     let registry_canister = RegistryCanister::new(vec![opts.nns_url.clone()]);
+    let nns_public_key = opts.nns_public_key.clone();
 
     let sender = if opts.secret_key_pem.is_some() || opts.use_hsm {
         // Make sure to let the user know that we only actually use the sender
@@ -2870,6 +2978,7 @@ async fn main() {
             SubCommand::ProposeToUpdateSubnetReplicaVersion(_) => (),
             SubCommand::ProposeToCreateSubnet(_) => (),
             SubCommand::ProposeToAddNodesToSubnet(_) => (),
+            SubCommand::ProposeToChangeSubnetMembership(_) => (),
             SubCommand::ProposeToRemoveNodes(_) => (),
             SubCommand::ProposeToRemoveNodesFromSubnet(_) => (),
             SubCommand::ProposeToChangeNnsCanister(_) => (),
@@ -2950,6 +3059,7 @@ async fn main() {
                 NnsFunction::RemoveNodesFromSubnet,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3121,6 +3231,7 @@ async fn main() {
                 NnsFunction::UpdateSubnetReplicaVersion,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3144,6 +3255,7 @@ async fn main() {
                 NnsFunction::BlessReplicaVersion,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3153,6 +3265,7 @@ async fn main() {
                 NnsFunction::BlessReplicaVersion,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3163,6 +3276,7 @@ async fn main() {
                 NnsFunction::CreateSubnet,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3172,6 +3286,17 @@ async fn main() {
                 NnsFunction::AddNodeToSubnet,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
+            )
+            .await;
+        }
+        SubCommand::ProposeToChangeSubnetMembership(cmd) => {
+            propose_external_proposal_from_command(
+                cmd,
+                NnsFunction::ChangeSubnetMembership,
+                opts.nns_url,
+                sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3181,6 +3306,7 @@ async fn main() {
                 NnsFunction::RecoverSubnet,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3190,6 +3316,7 @@ async fn main() {
                 NnsFunction::UpdateConfigOfSubnet,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3199,6 +3326,7 @@ async fn main() {
                 NnsFunction::NnsCanisterInstall,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3207,13 +3335,25 @@ async fn main() {
                 propose_external_proposal_from_command::<
                     UpgradeRootProposalPayload,
                     ProposeToChangeNnsCanisterCmd,
-                >(cmd, NnsFunction::NnsRootUpgrade, opts.nns_url, sender)
+                >(
+                    cmd,
+                    NnsFunction::NnsRootUpgrade,
+                    opts.nns_url,
+                    sender,
+                    nns_public_key.as_deref(),
+                )
                 .await;
             } else {
                 propose_external_proposal_from_command::<
                     ChangeCanisterProposal,
                     ProposeToChangeNnsCanisterCmd,
-                >(cmd, NnsFunction::NnsCanisterUpgrade, opts.nns_url, sender)
+                >(
+                    cmd,
+                    NnsFunction::NnsCanisterUpgrade,
+                    opts.nns_url,
+                    sender,
+                    nns_public_key.as_deref(),
+                )
                 .await;
             }
         }
@@ -3223,6 +3363,7 @@ async fn main() {
                 NnsFunction::UninstallCode,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3232,6 +3373,7 @@ async fn main() {
                 NnsFunction::IcpXdrConversionRate,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3241,6 +3383,7 @@ async fn main() {
                 NnsFunction::StopOrStartNnsCanister,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3250,6 +3393,7 @@ async fn main() {
                 NnsFunction::StopOrStartNnsCanister,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3259,6 +3403,7 @@ async fn main() {
                 NnsFunction::ClearProvisionalWhitelist,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3268,6 +3413,7 @@ async fn main() {
                 NnsFunction::SetAuthorizedSubnetworks,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3287,6 +3433,7 @@ async fn main() {
                 NnsFunction::RemoveNodes,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3296,6 +3443,7 @@ async fn main() {
                 NnsFunction::AssignNoid,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3342,6 +3490,7 @@ async fn main() {
                 NnsFunction::UpdateNodeOperatorConfig,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3358,6 +3507,7 @@ async fn main() {
                 NnsFunction::SetFirewallConfig,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3370,6 +3520,7 @@ async fn main() {
                     NnsFunction::AddFirewallRules,
                     opts.nns_url,
                     sender,
+                    nns_public_key.as_deref(),
                 )
                 .await;
             }
@@ -3383,6 +3534,7 @@ async fn main() {
                     NnsFunction::RemoveFirewallRules,
                     opts.nns_url,
                     sender,
+                    nns_public_key.as_deref(),
                 )
                 .await;
             }
@@ -3396,6 +3548,7 @@ async fn main() {
                     NnsFunction::UpdateFirewallRules,
                     opts.nns_url,
                     sender,
+                    nns_public_key.as_deref(),
                 )
                 .await;
             }
@@ -3410,20 +3563,49 @@ async fn main() {
             get_firewall_ruleset_hash(cmd);
         }
         SubCommand::ProposeToAddOrRemoveNodeProvider(cmd) => {
-            propose_to_add_or_remove_node_provider(cmd, opts.nns_url, sender).await
+            propose_to_add_or_remove_node_provider(
+                cmd,
+                opts.nns_url,
+                sender,
+                nns_public_key.as_deref(),
+            )
+            .await
         }
         SubCommand::GetRegistryVersion => {
             let latest_version = registry_canister.get_latest_version().await.unwrap();
             println!("{}", latest_version)
         }
+        SubCommand::GetRegistryDiff(cmd) => {
+            print_registry_diff(cmd, &registry_canister).await;
+        }
+        SubCommand::GetKeyHistory(cmd) => {
+            print_key_history(cmd, &registry_canister).await;
+        }
         SubCommand::SubmitRootProposalToUpgradeGovernanceCanister(cmd) => {
-            submit_root_proposal_to_upgrade_governance_canister(cmd, opts.nns_url, sender).await
+            submit_root_proposal_to_upgrade_governance_canister(
+                cmd,
+                opts.nns_url,
+                sender,
+                nns_public_key.as_deref(),
+            )
+            .await
         }
         SubCommand::GetPendingRootProposalsToUpgradeGovernanceCanister => {
-            get_pending_root_proposals_to_upgrade_governance_canister(opts.nns_url, sender).await
+            get_pending_root_proposals_to_upgrade_governance_canister(
+                opts.nns_url,
+                sender,
+                nns_public_key.as_deref(),
+            )
+            .await
         }
         SubCommand::VoteOnRootProposalToUpgradeGovernanceCanister(cmd) => {
-            vote_on_root_proposal_to_upgrade_governance_canister(cmd, opts.nns_url, sender).await
+            vote_on_root_proposal_to_upgrade_governance_canister(
+                cmd,
+                opts.nns_url,
+                sender,
+                nns_public_key.as_deref(),
+            )
+            .await
         }
         SubCommand::GetDataCenter(cmd) => {
             let (bytes, _) = registry_canister
@@ -3440,6 +3622,7 @@ async fn main() {
                 NnsFunction::AddOrRemoveDataCenters,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3458,6 +3641,7 @@ async fn main() {
                 NnsFunction::UpdateNodeRewardsTable,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3467,6 +3651,7 @@ async fn main() {
                 NnsFunction::UpdateUnassignedNodesConfig,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3480,12 +3665,16 @@ async fn main() {
             .await;
         }
         SubCommand::GetMonthlyNodeProviderRewards => {
-            let canister_client = GovernanceCanisterClient(make_canister_client(
-                opts.nns_url.clone(),
-                GOVERNANCE_CANISTER_ID,
-                sender,
-                None,
-            ));
+            let canister_client = GovernanceCanisterClient(
+                make_canister_client(
+                    opts.nns_url.clone(),
+                    GOVERNANCE_CANISTER_ID,
+                    sender,
+                    None,
+                    nns_public_key.as_deref(),
+                )
+                .await,
+            );
 
             let response = canister_client.get_monthly_node_provider_rewards().await;
             println!("{:?}", response);
@@ -3496,6 +3685,7 @@ async fn main() {
                 NnsFunction::RemoveNodeOperators,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3505,6 +3695,7 @@ async fn main() {
                 NnsFunction::RerouteCanisterRanges,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3514,6 +3705,7 @@ async fn main() {
                 NnsFunction::PrepareCanisterMigration,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3523,6 +3715,7 @@ async fn main() {
                 NnsFunction::CompleteCanisterMigration,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3539,6 +3732,7 @@ async fn main() {
                 NnsFunction::AddSnsWasm,
                 opts.nns_url,
                 sender,
+                nns_public_key.as_deref(),
             )
             .await;
         }
@@ -3632,6 +3826,268 @@ async fn print_and_get_last_value<T: Message + Default + serde::Serialize>(
         .unwrap()
 }
 
+/// Fetches all deltas of the registry, from the first to the latest version.
+async fn get_all_registry_deltas(registry_canister: &RegistryCanister) -> Vec<RegistryDelta> {
+    get_registry_deltas_since(registry_canister, 0).await
+}
+
+/// Returns the registry deltas with the values set or deleted after `version`.
+async fn get_registry_deltas_since(
+    registry_canister: &RegistryCanister,
+    mut version: u64,
+) -> Vec<RegistryDelta> {
+    let mut deltas = vec![];
+    // The response of the registry canister is limited in size, so the deltas
+    // are fetched in several rounds.
+    loop {
+        let (mut new_deltas, latest_version) = registry_canister
+            .get_changes_since(version)
+            .await
+            .unwrap_or_else(|e| panic!("Error fetching the registry deltas: {:?}", e));
+        let max_version = new_deltas
+            .iter()
+            .flat_map(|delta| delta.values.iter().map(|value| value.version))
+            .max()
+            .unwrap_or(latest_version);
+        deltas.append(&mut new_deltas);
+        if max_version >= latest_version {
+            return deltas;
+        }
+        version = max_version;
+    }
+}
+
+/// Returns a human readable representation of a registry value, decoded
+/// according to the type stored under `key`. Values of unknown keys are
+/// printed as hex.
+fn registry_value_to_string(key: &str, value: &[u8]) -> String {
+    fn decode<T: Message + Default>(value: &[u8]) -> String {
+        match T::decode(value) {
+            Ok(decoded) => format!("{:#?}", decoded),
+            Err(e) => format!("<undecodable value {}: {}>", hex::encode(value), e),
+        }
+    }
+
+    if key.starts_with(NODE_RECORD_KEY_PREFIX) {
+        decode::<NodeRecord>(value)
+    } else if key.starts_with(NODE_OPERATOR_RECORD_KEY_PREFIX) {
+        decode::<NodeOperatorRecord>(value)
+    } else if key.starts_with(SUBNET_RECORD_KEY_PREFIX) {
+        decode::<SubnetRecordProto>(value)
+    } else if key.starts_with(REPLICA_VERSION_KEY_PREFIX) {
+        decode::<ReplicaVersionRecord>(value)
+    } else if key.starts_with(CRYPTO_RECORD_KEY_PREFIX) {
+        decode::<PublicKey>(value)
+    } else if key.starts_with(CRYPTO_TLS_CERT_KEY_PREFIX) {
+        decode::<X509PublicKeyCert>(value)
+    } else if key.starts_with(CRYPTO_THRESHOLD_SIGNING_KEY_PREFIX) {
+        decode::<PublicKey>(value)
+    } else if key.starts_with(DATA_CENTER_KEY_PREFIX) {
+        decode::<DataCenterRecord>(value)
+    } else if key.starts_with(ECDSA_SIGNING_SUBNET_LIST_KEY_PREFIX) {
+        decode::<EcdsaSigningSubnetList>(value)
+    } else if key.starts_with("catch_up_package_contents_") {
+        decode::<CatchUpPackageContents>(value)
+    } else if key.starts_with("firewall_rules_") {
+        decode::<FirewallRuleSet>(value)
+    } else if key == make_blessed_replica_version_key() {
+        decode::<BlessedReplicaVersions>(value)
+    } else if key == make_subnet_list_record_key() {
+        decode::<SubnetListRecord>(value)
+    } else if key == make_routing_table_record_key() {
+        decode::<RoutingTable>(value)
+    } else if key == make_canister_migrations_record_key() {
+        decode::<CanisterMigrations>(value)
+    } else if key == make_provisional_whitelist_record_key() {
+        decode::<ProvisionalWhitelistProto>(value)
+    } else if key == make_unassigned_nodes_config_record_key() {
+        decode::<UnassignedNodesConfigRecord>(value)
+    } else if key == make_firewall_config_record_key() {
+        decode::<FirewallConfig>(value)
+    } else if key == make_nns_canister_records_key() {
+        decode::<NnsCanisterRecords>(value)
+    } else if key == NODE_REWARDS_TABLE_KEY {
+        decode::<NodeRewardsTable>(value)
+    } else if key == ROOT_SUBNET_ID_KEY {
+        decode::<SubnetIdProto>(value)
+    } else {
+        hex::encode(value)
+    }
+}
+
+/// Prints the old and the new value of a registry key.
+fn print_key_change(key: &str, old_value: Option<&[u8]>, new_value: Option<&[u8]>) {
+    println!("\n{}", key);
+    for (label, value) in [("old", old_value), ("new", new_value)] {
+        match value {
+            Some(value) => println!("  {}: {}", label, registry_value_to_string(key, value)),
+            None => println!("  {}: <absent>", label),
+        }
+    }
+}
+
+/// Groups the values of the given deltas by key, sorted by ascending version.
+/// A key can appear in several deltas if it was fetched in several rounds.
+fn registry_values_by_key(
+    deltas: Vec<RegistryDelta>,
+) -> BTreeMap<Vec<u8>, Vec<ic_registry_transport::pb::v1::RegistryValue>> {
+    let mut values_by_key: BTreeMap<Vec<u8>, Vec<_>> = BTreeMap::new();
+    for delta in deltas {
+        values_by_key
+            .entry(delta.key)
+            .or_default()
+            .extend(delta.values);
+    }
+    for values in values_by_key.values_mut() {
+        values.sort_by_key(|value| value.version);
+    }
+    values_by_key
+}
+
+/// Returns the value of a key at `version`, given all its values sorted by
+/// ascending version, or `None` if the key was absent at that version.
+fn registry_value_at(
+    values: &[ic_registry_transport::pb::v1::RegistryValue],
+    version: u64,
+) -> Option<&[u8]> {
+    values
+        .iter()
+        .rev()
+        .find(|value| value.version <= version)
+        .filter(|value| !value.deletion_marker)
+        .map(|value| value.value.as_slice())
+}
+
+/// Returns the value of `key` at `version`, or `None` if the key was absent at
+/// that version.
+async fn get_registry_value_at(
+    registry_canister: &RegistryCanister,
+    key: &[u8],
+    version: u64,
+) -> Option<Vec<u8>> {
+    if version == 0 {
+        return None;
+    }
+    match registry_canister
+        .get_value(key.to_vec(), Some(version))
+        .await
+    {
+        Ok((value, _)) => Some(value),
+        Err(Error::KeyNotPresent(_)) => None,
+        Err(e) => panic!(
+            "Error fetching the value of {} at version {}: {:?}",
+            String::from_utf8_lossy(key),
+            version,
+            e
+        ),
+    }
+}
+
+/// Prints the keys whose value differs between the two versions given in
+/// `cmd`, along with their decoded old and new values.
+///
+/// Only the deltas after the `--from` version are fetched. The values at the
+/// `--from` version are fetched for the keys changed since.
+async fn print_registry_diff(cmd: GetRegistryDiffCmd, registry_canister: &RegistryCanister) {
+    let to = match cmd.to {
+        Some(to) => to,
+        None => registry_canister.get_latest_version().await.unwrap(),
+    };
+    if cmd.from > to {
+        eprintln!(
+            "The --from version {} is greater than the --to version {}.",
+            cmd.from, to
+        );
+        exit(1);
+    }
+
+    let prefix = cmd.prefix.unwrap_or_default();
+    let deltas = get_registry_deltas_since(registry_canister, cmd.from).await;
+    println!("Changes from registry version {} to {}:", cmd.from, to);
+    for (key, values) in registry_values_by_key(deltas) {
+        if !key.starts_with(prefix.as_bytes()) || !values.iter().any(|value| value.version <= to) {
+            continue;
+        }
+        let old_value = get_registry_value_at(registry_canister, &key, cmd.from).await;
+        let new_value = registry_value_at(&values, to);
+        if old_value.as_deref() != new_value {
+            print_key_change(
+                &String::from_utf8_lossy(&key),
+                old_value.as_deref(),
+                new_value,
+            );
+        }
+    }
+}
+
+/// Prints every version after the `--from` version given in `cmd` at which
+/// the key was set or deleted.
+async fn print_key_history(cmd: GetKeyHistoryCmd, registry_canister: &RegistryCanister) {
+    let deltas = get_registry_deltas_since(registry_canister, cmd.from).await;
+    let values = registry_values_by_key(deltas)
+        .remove(cmd.key.as_bytes())
+        .unwrap_or_else(|| {
+            eprintln!("Key not changed since version {}: {}", cmd.from, cmd.key);
+            exit(1);
+        });
+    for value in values {
+        if value.deletion_marker {
+            println!("Version {}: deleted", value.version);
+        } else {
+            println!(
+                "Version {}: {}",
+                value.version,
+                registry_value_to_string(&cmd.key, &value.value)
+            );
+        }
+    }
+}
+
+/// Applies the payload of a proposal calling the registry canister to a copy
+/// of the current registry and prints the keys it would change. Exits with an
+/// error if the registry canister would reject the proposal, e.g., because an
+/// invariant would no longer hold.
+async fn dry_run_registry_proposal<C: CandidType>(
+    payload: &C,
+    nns_function: NnsFunction,
+    nns_url: Url,
+) {
+    let method = match nns_function.canister_and_function() {
+        Ok((canister_id, method)) if canister_id == REGISTRY_CANISTER_ID => method,
+        _ => return,
+    };
+
+    let registry_canister = RegistryCanister::new(vec![nns_url]);
+    let registry = registry_canister::registry::Registry::from_deltas(
+        get_all_registry_deltas(&registry_canister).await,
+    );
+    let payload = Encode!(payload).expect("Error encoding the proposal payload.");
+
+    match registry_canister::dry_run::dry_run(&registry, method, &payload) {
+        Ok(changes) => {
+            println!(
+                "Executing the proposal at registry version {} would change {} key(s):",
+                registry.latest_version(),
+                changes.len()
+            );
+            for change in changes {
+                print_key_change(
+                    &String::from_utf8_lossy(&change.key),
+                    change.old_value.as_deref(),
+                    change.new_value.as_deref(),
+                );
+            }
+        }
+        Err(e) => {
+            eprintln!(
+                "The proposal would fail when executed by the registry: {}",
+                e
+            );
+            exit(1);
+        }
+    }
+}
+
 /// Extracts a proposal payload from the provided command and uses it to submit
 /// a proposal to the governance canister.
 async fn propose_external_proposal_from_command<
@@ -3642,19 +4098,25 @@ async fn propose_external_proposal_from_command<
     nns_function: NnsFunction,
     nns_url: Url,
     sender: Sender,
+    nns_public_key: Option<&Path>,
 ) {
     let (proposer, sender) = cmd.proposer_and_sender(sender);
-    let canister_client = GovernanceCanisterClient(make_canister_client(
-        nns_url.clone(),
-        GOVERNANCE_CANISTER_ID,
-        sender,
-        Some(proposer),
-    ));
-
-    let payload = cmd.payload(nns_url).await;
+    let canister_client = GovernanceCanisterClient(
+        make_canister_client(
+            nns_url.clone(),
+            GOVERNANCE_CANISTER_ID,
+            sender,
+            Some(proposer),
+            nns_public_key,
+        )
+        .await,
+    );
+
+    let payload = cmd.payload(nns_url.clone()).await;
     print_payload(&payload, &cmd);
 
     if cmd.is_dry_run() {
+        dry_run_registry_proposal(&payload, nns_function, nns_url).await;
         return;
     }
 
@@ -3863,11 +4325,9 @@ struct NodeAndNodeOperatorId {
     node_operator_id: String,
 }
 
-/// Fetches the list of nodes that were added since `version` to the registry.
-async fn get_node_list_since(
-    version: u64,
-    registry: RegistryCanister,
-) -> Vec<NodeAndNodeOperatorId> {
+/// Fetches the threshold signing public key of the root (NNS) subnet from the
+/// registry.
+async fn get_nns_pub_key(registry: &RegistryCanister) -> ThresholdSigPublicKey {
     let (nns_subnet_id_vec, _) = registry
         .get_value(ROOT_SUBNET_ID_KEY.as_bytes().to_vec(), None)
         .await
@@ -3885,9 +4345,15 @@ async fn get_node_list_since(
         )
         .await
         .unwrap();
-    let nns_pub_key =
-        ThresholdSigPublicKey::try_from(PublicKey::decode(nns_pub_key_vec.as_slice()).unwrap())
-            .unwrap();
+    ThresholdSigPublicKey::try_from(PublicKey::decode(nns_pub_key_vec.as_slice()).unwrap()).unwrap()
+}
+
+/// Fetches the list of nodes that were added since `version` to the registry.
+async fn get_node_list_since(
+    version: u64,
+    registry: RegistryCanister,
+) -> Vec<NodeAndNodeOperatorId> {
+    let nns_pub_key = get_nns_pub_key(&registry).await;
 
     let latest_version = registry.get_latest_version().await.unwrap();
 
@@ -4125,15 +4591,20 @@ async fn propose_to_add_or_remove_node_provider(
     cmd: ProposeToAddOrRemoveNodeProviderCmd,
     nns_url: Url,
     sender: Sender,
+    nns_public_key: Option<&Path>,
 ) {
     let (proposer, sender) =
         get_proposer_and_sender(cmd.proposer, sender, cmd.test_neuron_proposer);
-    let canister_client = GovernanceCanisterClient(make_canister_client(
-        nns_url,
-        GOVERNANCE_CANISTER_ID,
-        sender,
-        Some(proposer),
-    ));
+    let canister_client = GovernanceCanisterClient(
+        make_canister_client(
+            nns_url,
+            GOVERNANCE_CANISTER_ID,
+            sender,
+            Some(proposer),
+            nns_public_key,
+        )
+        .await,
+    );
     let node_provider = NodeProvider {
         id: Some(cmd.node_provider_pid),
         // TODO(NNS1-771): accept this data from the command line
@@ -4285,14 +4756,12 @@ async fn submit_root_proposal_to_upgrade_governance_canister(
     cmd: SubmitRootProposalToUpgradeGovernanceCanisterCmd,
     nns_url: Url,
     sender: Sender,
+    nns_public_key: Option<&Path>,
 ) {
     let sender = get_test_sender_if_set(sender, cmd.test_user_proposer);
-    let canister_client = RootCanisterClient(make_canister_client(
-        nns_url,
-        ROOT_CANISTER_ID,
-        sender,
-        None,
-    ));
+    let canister_client = RootCanisterClient(
+        make_canister_client(nns_url, ROOT_CANISTER_ID, sender, None, nns_public_key).await,
+    );
     let result = canister_client
         .submit_root_proposal_to_upgrade_governance_canister(cmd)
         .await;
@@ -4307,13 +4776,14 @@ async fn submit_root_proposal_to_upgrade_governance_canister(
 
 /// Returns the current list of pending root proposals to upgrade the governance
 /// canister.
-async fn get_pending_root_proposals_to_upgrade_governance_canister(nns_url: Url, sender: Sender) {
-    let canister_client = RootCanisterClient(make_canister_client(
-        nns_url,
-        ROOT_CANISTER_ID,
-        sender,
-        None,
-    ));
+async fn get_pending_root_proposals_to_upgrade_governance_canister(
+    nns_url: Url,
+    sender: Sender,
+    nns_public_key: Option<&Path>,
+) {
+    let canister_client = RootCanisterClient(
+        make_canister_client(nns_url, ROOT_CANISTER_ID, sender, None, nns_public_key).await,
+    );
     let proposals = canister_client
         .get_pending_root_proposals_to_upgrade_governance_canister()
         .await;
@@ -4333,14 +4803,12 @@ async fn vote_on_root_proposal_to_upgrade_governance_canister(
     cmd: VoteOnRootProposalToUpgradeGovernanceCanisterCmd,
     nns_url: Url,
     sender: Sender,
+    nns_public_key: Option<&Path>,
 ) {
     let sender = get_test_sender_if_set(sender, cmd.test_user_voter);
-    let canister_client = RootCanisterClient(make_canister_client(
-        nns_url,
-        ROOT_CANISTER_ID,
-        sender,
-        None,
-    ));
+    let canister_client = RootCanisterClient(
+        make_canister_client(nns_url, ROOT_CANISTER_ID, sender, None, nns_public_key).await,
+    );
     let result = canister_client
         .vote_on_root_proposal_to_upgrade_governance_canister(cmd)
         .await;
@@ -4387,15 +4855,27 @@ struct GovernanceCanisterClient(NnsCanisterClient);
 /// A client for the root canister.
 struct RootCanisterClient(NnsCanisterClient);
 
-/// Build a new canister client.
-fn make_canister_client(
+/// Build a new canister client, which verifies certificates against the NNS
+/// public key at `nns_public_key`, or the one in the registry if not given.
+async fn make_canister_client(
     nns_url: Url,
     handler_id: CanisterId,
     sender: Sender,
     author: Option<NeuronId>,
+    nns_public_key: Option<&Path>,
 ) -> NnsCanisterClient {
+    let root_key = match nns_public_key {
+        Some(path) => parse_threshold_sig_key(path).unwrap_or_else(|e| {
+            panic!(
+                "failed to read NNS public key from {}: {}",
+                path.display(),
+                e
+            )
+        }),
+        None => get_nns_pub_key(&RegistryCanister::new(vec![nns_url.clone()])).await,
+    };
     NnsCanisterClient {
-        agent: Agent::new(nns_url, sender),
+        agent: Agent::new(nns_url, sender).with_root_key(root_key),
         handler_id,
         author,
     }
@@ -0,0 +1,204 @@
+//! Applies the mutations of a registry method to a local copy of the
+//! registry, so that the effect of a proposal can be checked before it is
+//! submitted.
+
+use crate::registry::Registry;
+use candid::{CandidType, Decode};
+use serde::de::DeserializeOwned;
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+
+/// A key whose value is changed by a dry run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyChange {
+    pub key: Vec<u8>,
+    /// The value before the dry run, or `None` if the key was not present.
+    pub old_value: Option<Vec<u8>>,
+    /// The value after the dry run, or `None` if the key was deleted.
+    pub new_value: Option<Vec<u8>>,
+}
+
+/// Calls the registry method `method` with the candid-encoded `payload` on a
+/// copy of `registry`, exactly as the registry canister does when it executes
+/// the corresponding proposal, including the checks of all invariants.
+///
+/// Returns the keys whose values changed, or the reason why executing the
+/// proposal would fail. `registry` itself is left untouched.
+pub fn dry_run(
+    registry: &Registry,
+    method: &str,
+    payload: &[u8],
+) -> Result<Vec<KeyChange>, String> {
+    let mut mutated = registry.clone();
+
+    // The mutations panic when they reject a payload or an invariant does not
+    // hold. Silence the default hook, as the panic message is returned.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        apply_method(&mut mutated, method, payload)
+    }));
+    panic::set_hook(hook);
+
+    match result {
+        Ok(Ok(())) => Ok(key_changes(registry, &mutated)),
+        Ok(Err(msg)) => Err(msg),
+        Err(panic) => Err(panic_message(panic)),
+    }
+}
+
+fn apply_method(registry: &mut Registry, method: &str, payload: &[u8]) -> Result<(), String> {
+    match method {
+        "add_node_operator" => registry.do_add_node_operator(decode(payload)?),
+        "add_nodes_to_subnet" => registry.do_add_nodes_to_subnet(decode(payload)?),
+        "change_subnet_membership" => registry.do_change_subnet_membership(decode(payload)?),
+        "remove_nodes_from_subnet" => registry.do_remove_nodes_from_subnet(decode(payload)?),
+        "remove_nodes" => registry.do_remove_nodes(decode(payload)?),
+        "bless_replica_version" => registry.do_bless_replica_version(decode(payload)?),
+        "update_subnet_replica_version" => {
+            registry.do_update_subnet_replica_version(decode(payload)?)
+        }
+        "update_node_operator_config" => registry.do_update_node_operator_config(decode(payload)?),
+        "remove_node_operators" => registry.do_remove_node_operators(decode(payload)?),
+        "update_subnet" => registry.do_update_subnet(decode(payload)?),
+        "clear_provisional_whitelist" => registry.do_clear_provisional_whitelist(),
+        "set_firewall_config" => registry.do_set_firewall_config(decode(payload)?),
+        "add_firewall_rules" => registry.do_add_firewall_rules(decode(payload)?),
+        "remove_firewall_rules" => registry.do_remove_firewall_rules(decode(payload)?),
+        "update_firewall_rules" => registry.do_update_firewall_rules(decode(payload)?),
+        "update_node_rewards_table" => registry.do_update_node_rewards_table(decode(payload)?),
+        "add_or_remove_data_centers" => registry.do_add_or_remove_data_centers(decode(payload)?),
+        "update_unassigned_nodes_config" => {
+            registry.do_update_unassigned_nodes_config(decode(payload)?)
+        }
+        "prepare_canister_migration" => registry.prepare_canister_migration(decode(payload)?)?,
+        "reroute_canister_ranges" => registry.reroute_canister_ranges(decode(payload)?)?,
+        "complete_canister_migration" => registry.complete_canister_migration(decode(payload)?)?,
+        "create_subnet" | "delete_subnet" | "recover_subnet" => {
+            return Err(format!(
+                "{} calls the management canister and cannot be run locally.",
+                method
+            ))
+        }
+        _ => return Err(format!("Unknown registry method {}.", method)),
+    }
+    Ok(())
+}
+
+fn decode<T: CandidType + DeserializeOwned>(payload: &[u8]) -> Result<T, String> {
+    Decode!(payload, T).map_err(|e| format!("Could not decode the payload: {}", e))
+}
+
+/// Returns the keys whose latest value differs between `before` and `after`.
+fn key_changes(before: &Registry, after: &Registry) -> Vec<KeyChange> {
+    after
+        .get_changes_since(before.latest_version(), None)
+        .into_iter()
+        .filter_map(|delta| {
+            let old_value = before
+                .get(&delta.key, before.latest_version())
+                .map(|v| v.value.clone());
+            // The values of a delta are sorted by descending version.
+            let new_value = delta
+                .values
+                .first()
+                .filter(|v| !v.deletion_marker)
+                .map(|v| v.value.clone());
+            (old_value != new_value).then(|| KeyChange {
+                key: delta.key,
+                old_value,
+                new_value,
+            })
+        })
+        .collect()
+}
+
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    match panic.downcast::<String>() {
+        Ok(msg) => *msg,
+        Err(panic) => match panic.downcast::<&str>() {
+            Ok(msg) => msg.to_string(),
+            Err(_) => "The registry method panicked.".to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::test_helpers::invariant_compliant_registry;
+    use crate::mutations::do_bless_replica_version::BlessReplicaVersionPayload;
+    use candid::Encode;
+    use ic_registry_keys::{make_blessed_replica_version_key, make_replica_version_key};
+
+    fn bless_replica_version_payload(release_package_url: &str) -> Vec<u8> {
+        Encode!(&BlessReplicaVersionPayload {
+            replica_version_id: "version_42".to_string(),
+            binary_url: "".to_string(),
+            sha256_hex: "".to_string(),
+            node_manager_binary_url: "".to_string(),
+            node_manager_sha256_hex: "".to_string(),
+            release_package_url: release_package_url.to_string(),
+            release_package_sha256_hex: "".to_string(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn dry_run_returns_changed_keys() {
+        let registry = invariant_compliant_registry();
+
+        let changes = dry_run(
+            &registry,
+            "bless_replica_version",
+            &bless_replica_version_payload("http://release_package.tar.gz"),
+        )
+        .unwrap();
+
+        let mut changed_keys = changes
+            .iter()
+            .map(|change| String::from_utf8(change.key.clone()).unwrap())
+            .collect::<Vec<_>>();
+        changed_keys.sort();
+        assert_eq!(
+            changed_keys,
+            vec![
+                make_blessed_replica_version_key(),
+                make_replica_version_key("version_42")
+            ]
+        );
+        let new_version = changes
+            .iter()
+            .find(|change| change.key == make_replica_version_key("version_42").into_bytes())
+            .unwrap();
+        assert_eq!(new_version.old_value, None);
+        assert!(new_version.new_value.is_some());
+    }
+
+    #[test]
+    fn dry_run_reports_invariant_violations() {
+        let registry = invariant_compliant_registry();
+
+        let error = dry_run(
+            &registry,
+            "bless_replica_version",
+            &bless_replica_version_payload(""),
+        )
+        .unwrap_err();
+
+        assert!(
+            error.contains("release package URL must be set"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn dry_run_rejects_unknown_methods_and_payloads() {
+        let registry = invariant_compliant_registry();
+
+        assert!(dry_run(&registry, "create_subnet", &[]).is_err());
+        assert!(dry_run(&registry, "no_such_method", &[]).is_err());
+        assert!(dry_run(&registry, "update_subnet", &Encode!(&42_u64).unwrap()).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 pub mod certification;
 pub mod common;
+pub mod dry_run;
 pub mod get_node_operators_and_dcs_of_node_provider;
 pub mod get_node_providers_monthly_xdr_rewards;
 pub mod init;
@@ -0,0 +1,186 @@
+use crate::{common::LOG_PREFIX, registry::Registry};
+
+use std::collections::HashSet;
+use std::convert::TryFrom;
+
+use candid::{CandidType, Deserialize};
+#[cfg(target_arch = "wasm32")]
+use dfn_core::println;
+use serde::Serialize;
+
+use ic_base_types::{NodeId, PrincipalId, SubnetId};
+
+impl Registry {
+    /// Adds nodes to and removes nodes from an existing subnet, in a single
+    /// registry version.
+    ///
+    /// This method is called by the governance canister, after a proposal
+    /// for changing the membership of a subnet has been accepted. Unlike
+    /// removing nodes and adding their replacements in two proposals, the
+    /// subnet never runs with fewer nodes in between.
+    pub fn do_change_subnet_membership(&mut self, payload: ChangeSubnetMembershipPayload) {
+        println!(
+            "{}do_change_subnet_membership started: {:?}",
+            LOG_PREFIX, payload
+        );
+
+        let subnet_id = SubnetId::from(payload.subnet_id);
+        let subnet_record = self.get_subnet_or_panic(subnet_id);
+        let existing_nodes: Vec<NodeId> = subnet_record
+            .membership
+            .iter()
+            .map(|bytes| NodeId::from(PrincipalId::try_from(bytes).unwrap()))
+            .collect();
+
+        let nodes_to_add: HashSet<NodeId> = payload.node_ids_add.iter().cloned().collect();
+        let nodes_to_remove: HashSet<NodeId> = payload.node_ids_remove.iter().cloned().collect();
+        if let Some(node_id) = nodes_to_add.intersection(&nodes_to_remove).next() {
+            panic!(
+                "{}do_change_subnet_membership: node {} is both added to and removed from subnet {}.",
+                LOG_PREFIX, node_id, subnet_id
+            );
+        }
+        if let Some(node_id) = nodes_to_add.iter().find(|n| existing_nodes.contains(n)) {
+            panic!(
+                "{}do_change_subnet_membership: node {} is already a member of subnet {}.",
+                LOG_PREFIX, node_id, subnet_id
+            );
+        }
+        if let Some(node_id) = nodes_to_remove.iter().find(|n| !existing_nodes.contains(n)) {
+            panic!(
+                "{}do_change_subnet_membership: node {} is not a member of subnet {}.",
+                LOG_PREFIX, node_id, subnet_id
+            );
+        }
+
+        let mut new_membership: Vec<NodeId> = existing_nodes
+            .into_iter()
+            .filter(|node_id| !nodes_to_remove.contains(node_id))
+            .collect();
+        for node_id in &payload.node_ids_add {
+            if !new_membership.contains(node_id) {
+                new_membership.push(*node_id);
+            }
+        }
+
+        let mutations =
+            vec![self.make_replace_subnet_membership_mutation(subnet_id, new_membership)];
+
+        // Check invariants before applying mutations
+        self.maybe_apply_mutation_internal(mutations);
+
+        println!(
+            "{}do_change_subnet_membership finished: {:?}",
+            LOG_PREFIX, payload
+        );
+    }
+}
+
+/// The payload of a proposal to add nodes to and remove nodes from an
+/// existing subnet at the same time, e.g., to replace faulty nodes.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ChangeSubnetMembershipPayload {
+    /// The subnet ID whose membership is changed.
+    pub subnet_id: PrincipalId,
+    /// The list of node IDs that will be added to the subnet. The nodes must
+    /// not be assigned to any subnet.
+    pub node_ids_add: Vec<NodeId>,
+    /// The list of node IDs that will be removed from the subnet. The nodes
+    /// must currently be members of the subnet.
+    pub node_ids_remove: Vec<NodeId>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::test_helpers::{
+        add_fake_subnet, get_invariant_compliant_subnet_record, invariant_compliant_registry,
+        prepare_registry_with_nodes,
+    };
+    use ic_test_utilities::types::ids::subnet_test_id;
+
+    /// Returns a registry with a subnet of `subnet_size` nodes and `spare`
+    /// unassigned nodes, along with the subnet's members and the spare nodes.
+    fn registry_with_subnet(
+        subnet_size: u64,
+        spare: u64,
+    ) -> (Registry, SubnetId, Vec<NodeId>, Vec<NodeId>) {
+        let mut registry = invariant_compliant_registry();
+        let (mutate_request, mut node_ids) = prepare_registry_with_nodes(subnet_size + spare);
+        registry.maybe_apply_mutation_internal(mutate_request.mutations);
+
+        let spare_nodes = node_ids.split_off(subnet_size as usize);
+        let subnet_id = subnet_test_id(1000);
+        let mut subnet_list_record = registry.get_subnet_list_record();
+        registry.maybe_apply_mutation_internal(add_fake_subnet(
+            subnet_id,
+            &mut subnet_list_record,
+            get_invariant_compliant_subnet_record(node_ids.clone()),
+        ));
+
+        (registry, subnet_id, node_ids, spare_nodes)
+    }
+
+    fn membership(registry: &Registry, subnet_id: SubnetId) -> HashSet<NodeId> {
+        registry
+            .get_subnet_or_panic(subnet_id)
+            .membership
+            .iter()
+            .map(|bytes| NodeId::from(PrincipalId::try_from(bytes).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn replaces_nodes_in_a_single_version() {
+        let (mut registry, subnet_id, members, spare_nodes) = registry_with_subnet(2, 1);
+        let version_before = registry.latest_version();
+
+        registry.do_change_subnet_membership(ChangeSubnetMembershipPayload {
+            subnet_id: subnet_id.get(),
+            node_ids_add: vec![spare_nodes[0]],
+            node_ids_remove: vec![members[0]],
+        });
+
+        assert_eq!(registry.latest_version(), version_before + 1);
+        assert_eq!(
+            membership(&registry, subnet_id),
+            vec![members[1], spare_nodes[0]].into_iter().collect()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "is not a member of subnet")]
+    fn cannot_remove_nodes_outside_of_the_subnet() {
+        let (mut registry, subnet_id, _, spare_nodes) = registry_with_subnet(2, 2);
+
+        registry.do_change_subnet_membership(ChangeSubnetMembershipPayload {
+            subnet_id: subnet_id.get(),
+            node_ids_add: vec![spare_nodes[0]],
+            node_ids_remove: vec![spare_nodes[1]],
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "is already a member of subnet")]
+    fn cannot_add_existing_members() {
+        let (mut registry, subnet_id, members, _) = registry_with_subnet(2, 0);
+
+        registry.do_change_subnet_membership(ChangeSubnetMembershipPayload {
+            subnet_id: subnet_id.get(),
+            node_ids_add: vec![members[0]],
+            node_ids_remove: vec![],
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "is both added to and removed from subnet")]
+    fn cannot_add_and_remove_the_same_node() {
+        let (mut registry, subnet_id, members, _) = registry_with_subnet(2, 0);
+
+        registry.do_change_subnet_membership(ChangeSubnetMembershipPayload {
+            subnet_id: subnet_id.get(),
+            node_ids_add: vec![members[0]],
+            node_ids_remove: vec![members[0]],
+        });
+    }
+}
@@ -305,6 +305,37 @@ impl Registry {
         self.apply_mutations(mutations);
     }
 
+    /// Rebuilds a registry from the deltas returned by `get_changes_since`,
+    /// e.g. to work on a local copy of a remote registry. The values of a key
+    /// may be spread over several deltas and given in any order. No invariants
+    /// are checked.
+    pub fn from_deltas(deltas: Vec<RegistryDelta>) -> Self {
+        let mut mutations_by_version = BTreeMap::<Version, Vec<RegistryMutation>>::new();
+        for delta in deltas {
+            for v in delta.values {
+                mutations_by_version
+                    .entry(v.version)
+                    .or_default()
+                    .push(RegistryMutation {
+                        mutation_type: if v.deletion_marker {
+                            Type::Delete
+                        } else {
+                            Type::Upsert
+                        } as i32,
+                        key: delta.key.clone(),
+                        value: v.value,
+                    });
+            }
+        }
+
+        let mut registry = Self::new();
+        for (version, mutations) in mutations_by_version {
+            registry.apply_mutations_as_version(mutations, version);
+            registry.version = version;
+        }
+        registry
+    }
+
     /// Serializes the registry contents using the specified version of stable
     /// representation.
     fn serializable_form_at(&self, repr_version: ReprVersion) -> RegistryStableStorage {
@@ -660,6 +691,35 @@ mod tests {
         serialize_then_deserialize(registry);
     }
 
+    #[test]
+    fn test_from_deltas() {
+        let mut registry = Registry::new();
+        let key1 = vec![1, 2, 3, 4];
+        let key2 = vec![5, 6, 7, 8];
+        let value1 = vec![5, 6, 7, 8];
+        let value2 = vec![9, 10, 11, 12];
+        for mutations in [
+            vec![insert(&key1, &value1)],
+            vec![insert(&key2, &value1), update(&key1, &value2)],
+            vec![delete(&key1), update(&key2, &value2)],
+            vec![insert(&key1, &value1)],
+        ] {
+            assert_empty!(apply_mutations_skip_invariant_checks(
+                &mut registry,
+                mutations
+            ));
+        }
+
+        assert_eq!(
+            Registry::from_deltas(registry.get_changes_since(0, None)),
+            registry
+        );
+        // The deltas can be fetched in several pages.
+        let mut deltas = registry.get_changes_since(2, None);
+        deltas.append(&mut registry.get_changes_since(0, Some(2)));
+        assert_eq!(Registry::from_deltas(deltas), registry);
+    }
+
     #[test]
     fn test_insert() {
         let mut registry = Registry::new();
@@ -0,0 +1,137 @@
+use candid::Encode;
+use dfn_candid::candid;
+
+use ic_base_types::{NodeId, PrincipalId};
+use ic_nns_test_utils::{
+    itest_helpers::{
+        forward_call_via_universal_canister, local_test_on_nns_subnet, set_up_registry_canister,
+        set_up_universal_canister,
+    },
+    registry::{get_value_or_panic, prepare_registry},
+};
+use ic_protobuf::registry::subnet::v1::SubnetRecord;
+use ic_registry_keys::make_subnet_record_key;
+use registry_canister::{
+    init::RegistryCanisterInitPayloadBuilder,
+    mutations::do_change_subnet_membership::ChangeSubnetMembershipPayload,
+};
+
+use assert_matches::assert_matches;
+use std::convert::TryFrom;
+
+#[test]
+fn test_the_anonymous_user_cannot_change_subnet_membership() {
+    local_test_on_nns_subnet(|runtime| {
+        async move {
+            let num_nodes_in_subnet = 4_usize;
+            let (init_mutate, subnet_id, unassigned_node_ids, _) =
+                prepare_registry(num_nodes_in_subnet, 1);
+            let registry = set_up_registry_canister(
+                &runtime,
+                RegistryCanisterInitPayloadBuilder::new()
+                    .push_init_mutate_request(init_mutate)
+                    .build(),
+            )
+            .await;
+            let subnet_record_before = get_value_or_panic::<SubnetRecord>(
+                &registry,
+                make_subnet_record_key(subnet_id).as_bytes(),
+            )
+            .await;
+
+            let payload = ChangeSubnetMembershipPayload {
+                subnet_id: subnet_id.get(),
+                node_ids_add: unassigned_node_ids,
+                node_ids_remove: vec![],
+            };
+
+            // The anonymous end-user tries to change the membership of a subnet,
+            // bypassing the proposals. This should be rejected.
+            let response: Result<(), String> = registry
+                .update_("change_subnet_membership", candid, (payload,))
+                .await;
+            assert_matches!(response,
+                Err(s) if s.contains("is not authorized to call this method: change_subnet_membership"));
+
+            // .. And there should therefore be no updates to the subnet record
+            let subnet_record = get_value_or_panic::<SubnetRecord>(
+                &registry,
+                make_subnet_record_key(subnet_id).as_bytes(),
+            )
+            .await;
+            assert_eq!(subnet_record, subnet_record_before);
+
+            Ok(())
+        }
+    });
+}
+
+#[test]
+fn test_change_subnet_membership_replaces_nodes() {
+    local_test_on_nns_subnet(|runtime| {
+        async move {
+            let num_nodes_in_subnet = 4_usize;
+            let num_unassigned_nodes = 2_usize;
+            let (init_mutate, subnet_id, unassigned_node_ids, _) =
+                prepare_registry(num_nodes_in_subnet, num_unassigned_nodes);
+            let registry = set_up_registry_canister(
+                &runtime,
+                RegistryCanisterInitPayloadBuilder::new()
+                    .push_init_mutate_request(init_mutate)
+                    .build(),
+            )
+            .await;
+
+            // Install the universal canister in place of the proposals canister
+            let fake_proposal_canister = set_up_universal_canister(&runtime).await;
+            // Since it takes the id reserved for the proposal canister, it can impersonate
+            // it
+            assert_eq!(
+                fake_proposal_canister.canister_id(),
+                ic_nns_constants::GOVERNANCE_CANISTER_ID
+            );
+
+            let subnet_record = get_value_or_panic::<SubnetRecord>(
+                &registry,
+                make_subnet_record_key(subnet_id).as_bytes(),
+            )
+            .await;
+            let nodes_to_remove = subnet_record.membership[..num_unassigned_nodes].to_vec();
+            let payload = ChangeSubnetMembershipPayload {
+                subnet_id: subnet_id.get(),
+                node_ids_add: unassigned_node_ids.clone(),
+                node_ids_remove: nodes_to_remove
+                    .iter()
+                    .map(|bytes| NodeId::from(PrincipalId::try_from(bytes).unwrap()))
+                    .collect(),
+            };
+
+            assert!(
+                forward_call_via_universal_canister(
+                    &fake_proposal_canister,
+                    &registry,
+                    "change_subnet_membership",
+                    Encode!(&payload).unwrap()
+                )
+                .await
+            );
+
+            // The subnet keeps its size, with the removed nodes replaced by the
+            // previously unassigned ones.
+            let subnet_record = get_value_or_panic::<SubnetRecord>(
+                &registry,
+                make_subnet_record_key(subnet_id).as_bytes(),
+            )
+            .await;
+            assert_eq!(subnet_record.membership.len(), num_nodes_in_subnet);
+            for node_id in nodes_to_remove {
+                assert!(!subnet_record.membership.contains(&node_id));
+            }
+            for node_id in unassigned_node_ids {
+                assert!(subnet_record.membership.contains(&node_id.get().to_vec()));
+            }
+
+            Ok(())
+        }
+    });
+}
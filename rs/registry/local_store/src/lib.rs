@@ -1,15 +1,17 @@
 use ic_interfaces::registry::{
     LocalStoreCertifiedTimeReader, RegistryDataProvider, RegistryTransportRecord,
+    ZERO_REGISTRY_VERSION,
 };
 use ic_registry_common_proto::pb::local_store::v1::{
     CertifiedTime as PbCertifiedTime, ChangelogEntry as PbChangelogEntry, Delta as PbDelta,
-    KeyMutation as PbKeyMutation, MutationType,
+    KeyMutation as PbKeyMutation, MutationType, Snapshot as PbSnapshot,
 };
 use ic_types::registry::RegistryDataProviderError;
 use ic_types::RegistryVersion;
 use ic_utils::fs::write_protobuf_using_tmp_file;
 use prost::Message;
 use std::{
+    collections::{BTreeMap, BTreeSet},
     io::{self},
     path::{Path, PathBuf},
     sync::{Arc, Mutex},
@@ -41,7 +43,30 @@ pub trait LocalStoreReader: Send + Sync {
     /// `cl` where the subsequence `cl[0..i]`, `0 <= i <= len(ds)`, applied
     /// to a registry at latest version `v` represents the registry at
     /// version `v+i+1`.
+    ///
+    /// If the changelog entries following `version` have been compacted into
+    /// a snapshot, `cl[0]` is the snapshot, and applying `cl` to the registry
+    /// at `version` still produces the latest registry. The versions produced
+    /// by the individual entries are then offset by the compacted versions,
+    /// see [Self::get_changelog_or_snapshot_since_version]. As the registry at
+    /// version 0 is empty, the deletions of the snapshot are omitted if
+    /// `version` is 0.
     fn get_changelog_since_version(&self, version: RegistryVersion) -> io::Result<Changelog>;
+
+    /// Like [Self::get_changelog_since_version], but also serves versions
+    /// whose changelog entries have been compacted into a snapshot.
+    ///
+    /// Returns a version `s` and a changelog `cl` where `cl[i]` produces the
+    /// registry at version `s+i+1`. If the entries following `version` are
+    /// stored, `s` is `version`. Otherwise, if they have been compacted into a
+    /// snapshot at version `t`, `s` is `t-1` and `cl[0]` is the snapshot, which
+    /// sets or deletes every key of the registry, so that applying `cl` to the
+    /// registry at `version` still produces the latest registry. Intermediate
+    /// versions before `t` can no longer be reconstructed.
+    fn get_changelog_or_snapshot_since_version(
+        &self,
+        version: RegistryVersion,
+    ) -> io::Result<(RegistryVersion, Changelog)>;
 }
 
 pub trait LocalStoreWriter: Send + Sync {
@@ -53,6 +78,16 @@ pub trait LocalStoreWriter: Send + Sync {
     /// (2) The given change log entry must be nonempty list of KeyMutations.
     fn store(&self, version: RegistryVersion, v: ChangelogEntry) -> io::Result<()>;
 
+    /// Stores `snapshot`, a changelog entry setting or deleting every key of
+    /// the registry at `version`, as the first version of an empty store.
+    /// Subsequent versions can be stored on top of it using [Self::store].
+    fn store_snapshot(&self, version: RegistryVersion, snapshot: ChangelogEntry) -> io::Result<()>;
+
+    /// Folds all changelog entries up to and including `version` into a
+    /// snapshot at `version` and removes them, see
+    /// [LocalStoreReader::get_changelog_or_snapshot_since_version].
+    fn compact(&self, version: RegistryVersion) -> io::Result<()>;
+
     /// Clears the Local Store.
     ///
     /// Note: This clears registry versions, stored in directories, but not the
@@ -63,11 +98,36 @@ pub trait LocalStoreWriter: Send + Sync {
     fn update_certified_time(&self, unix_epoch_nanos: u64) -> io::Result<()>;
 }
 
+/// The default number of registry versions between two consecutive snapshots.
+pub const DEFAULT_SNAPSHOT_INTERVAL: u64 = 1000;
+
+/// The name of the directory, within the local store, holding the snapshots.
+const SNAPSHOTS_DIR: &str = "snapshots";
+
+/// How often a changelog is read before giving up, if the files being read are
+/// removed by a concurrent compaction.
+const MAX_READ_ATTEMPTS: usize = 3;
+
+/// A local store keeping one .pb file per registry version, and periodic
+/// snapshots of the full registry.
+///
+/// Every `snapshot_interval` versions, [LocalStoreImpl::store] writes a
+/// snapshot of all keys at that version. [LocalStoreWriter::compact] folds the
+/// changelog entries up to a version into the snapshot at that version and
+/// removes them, so that readers don't need to replay every version since 1.
+///
+/// Stores written before snapshots were introduced need no conversion: they
+/// are read as before, their first snapshot is computed by replaying their
+/// changelog, and they can be compacted directly.
 #[derive(Clone, Debug)]
 pub struct LocalStoreImpl {
     /// Directory with one .pb file per registry version.
     path: PathBuf,
 
+    /// Number of versions between two snapshots written by `store`. `0`
+    /// disables periodic snapshots.
+    snapshot_interval: u64,
+
     /// Cached certified local store time, indicating instant at which the cache
     /// was last updated and the last time value. A time value of `0` indicates
     /// that no value was read thus far.
@@ -79,10 +139,18 @@ impl LocalStoreImpl {
         let now = Instant::now();
         Self {
             path: PathBuf::from(path.as_ref()),
+            snapshot_interval: DEFAULT_SNAPSHOT_INTERVAL,
             certified_time: Arc::new(Mutex::new((now, 0))),
         }
     }
 
+    /// Sets the number of versions between two snapshots written when storing
+    /// changelog entries. `0` disables periodic snapshots.
+    pub fn with_snapshot_interval(mut self, snapshot_interval: u64) -> Self {
+        self.snapshot_interval = snapshot_interval;
+        self
+    }
+
     // precondition: version > 0
     // there exists no path for a version 0, as version 0 represents the empty
     // registry.
@@ -102,6 +170,146 @@ impl LocalStoreImpl {
         self.path.join(v_path.as_path())
     }
 
+    fn get_snapshot_path(&self, version: u64) -> PathBuf {
+        self.path
+            .join(SNAPSHOTS_DIR)
+            .join(format!("{:016x}.pb", version))
+    }
+
+    /// Returns the versions of all snapshots in the store, in ascending order.
+    fn snapshot_versions(&self) -> io::Result<Vec<u64>> {
+        let entries = match std::fs::read_dir(self.path.join(SNAPSHOTS_DIR)) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e),
+        };
+        let mut versions = entries.try_fold(vec![], |mut versions, entry| {
+            let file_name = entry?.file_name();
+            // Skip temporary files of snapshots being written.
+            if let Some(version) = file_name
+                .to_str()
+                .and_then(|name| name.strip_suffix(".pb"))
+                .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+            {
+                versions.push(version);
+            }
+            Ok::<_, io::Error>(versions)
+        })?;
+        versions.sort_unstable();
+        Ok(versions)
+    }
+
+    /// A version exists if either its changelog entry or a snapshot at that
+    /// version is stored.
+    fn version_exists(&self, version: u64) -> bool {
+        self.get_path(version).exists() || self.get_snapshot_path(version).exists()
+    }
+
+    fn read_snapshot(&self, version: u64) -> io::Result<PbSnapshot> {
+        let bytes = std::fs::read(self.get_snapshot_path(version))?;
+        PbSnapshot::decode(bytes.as_slice())
+            .map_err(|e| io::Error::new(std::io::ErrorKind::Other, e))
+    }
+
+    /// Reads the changelog entries from version `start` up to the last
+    /// consecutive version that is stored.
+    fn read_changelog(&self, start: u64) -> io::Result<Changelog> {
+        (start..)
+            .map(|i| self.get_path(i))
+            .take_while(|p| p.exists())
+            .try_fold(vec![], |mut res, p| {
+                res.push(changelog_entry_try_from_proto(Self::read_changelog_entry(
+                    p,
+                )?)?);
+                Ok(res)
+            })
+    }
+
+    /// Returns the changelog since `version`, starting with the first snapshot
+    /// after `version` if the changelog entries following `version` have been
+    /// compacted, see [LocalStoreReader::get_changelog_or_snapshot_since_version].
+    fn read_changelog_or_snapshot(
+        &self,
+        version: RegistryVersion,
+    ) -> io::Result<(RegistryVersion, Changelog)> {
+        let start = version.get() + 1;
+        if self.get_path(start).exists() {
+            return Ok((version, self.read_changelog(start)?));
+        }
+
+        // Either there is no version after `version`, or its changelog entry
+        // has been compacted into the first snapshot at or after `start`.
+        let snapshot_version = match self.snapshot_versions()?.into_iter().find(|v| *v >= start) {
+            Some(snapshot_version) => snapshot_version,
+            None => return Ok((version, vec![])),
+        };
+        let snapshot = self.read_snapshot(snapshot_version)?;
+        let mut changelog = vec![snapshot
+            .key_mutations
+            .iter()
+            .map(key_mutation_try_from_proto)
+            .collect::<io::Result<ChangelogEntry>>()?];
+        changelog.append(&mut self.read_changelog(snapshot_version + 1)?);
+        Ok((RegistryVersion::from(snapshot_version - 1), changelog))
+    }
+
+    /// Returns the value of every key mutated up to and including `version`,
+    /// where `None` means that the key has been deleted. Starts from the latest
+    /// snapshot at or below `version`, if any.
+    fn registry_at(&self, version: u64) -> io::Result<BTreeMap<String, Option<Vec<u8>>>> {
+        let mut registry = BTreeMap::new();
+        let snapshot_version = self
+            .snapshot_versions()?
+            .into_iter()
+            .filter(|v| *v <= version)
+            .last();
+        let start = match snapshot_version {
+            Some(snapshot_version) => {
+                let snapshot = self.read_snapshot(snapshot_version)?;
+                for km in snapshot.key_mutations.iter() {
+                    let km = key_mutation_try_from_proto(km)?;
+                    registry.insert(km.key, km.value);
+                }
+                snapshot_version + 1
+            }
+            None => 1,
+        };
+        for v in start..=version {
+            let entry =
+                changelog_entry_try_from_proto(Self::read_changelog_entry(self.get_path(v))?)?;
+            for km in entry {
+                registry.insert(km.key, km.value);
+            }
+        }
+        Ok(registry)
+    }
+
+    /// Writes a snapshot of the full registry at `version`.
+    ///
+    /// Fails if the changelog entries between the latest snapshot before
+    /// `version` and `version` are not all stored.
+    pub fn write_snapshot(&self, version: RegistryVersion) -> io::Result<()> {
+        let version = version.get();
+        if version == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Cannot write a snapshot of version 0.",
+            ));
+        }
+        let key_mutations = self
+            .registry_at(version)?
+            .into_iter()
+            .map(|(key, value)| key_mutation_to_protobuf(&KeyMutation { key, value }))
+            .collect();
+        let pb = PbSnapshot {
+            registry_version: version,
+            key_mutations,
+        };
+        let path = self.get_snapshot_path(version);
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        write_protobuf_using_tmp_file(path, &pb)
+    }
+
     fn read_changelog_entry<P: AsRef<Path>>(p: P) -> io::Result<PbChangelogEntry> {
         let bytes = std::fs::read(p)?;
         PbChangelogEntry::decode(bytes.as_slice())
@@ -116,7 +324,7 @@ impl LocalStoreImpl {
         if version == 0 {
             panic!("Version must be > 0.")
         }
-        if version > 1 && !self.get_path(version - 1).exists() {
+        if version > 1 && !self.version_exists(version - 1) {
             return Err(io::Error::new(
                 std::io::ErrorKind::NotFound,
                 format!("Version {} does not exist.", version - 1),
@@ -157,16 +365,30 @@ impl LocalStore for LocalStoreImpl {}
 
 impl LocalStoreReader for LocalStoreImpl {
     fn get_changelog_since_version(&self, version: RegistryVersion) -> io::Result<Changelog> {
-        let start = version.get() + 1;
-        (start..)
-            .map(|i| self.get_path(i))
-            .take_while(|p| p.exists())
-            .try_fold(vec![], |mut res, p| {
-                res.push(changelog_entry_try_from_proto(Self::read_changelog_entry(
-                    p,
-                )?)?);
-                Ok(res)
-            })
+        let (start, mut changelog) = self.get_changelog_or_snapshot_since_version(version)?;
+        if start != version && version == ZERO_REGISTRY_VERSION {
+            if let Some(snapshot) = changelog.first_mut() {
+                snapshot.retain(|km| km.value.is_some());
+            }
+        }
+        Ok(changelog)
+    }
+
+    fn get_changelog_or_snapshot_since_version(
+        &self,
+        version: RegistryVersion,
+    ) -> io::Result<(RegistryVersion, Changelog)> {
+        // A concurrent compaction may remove the files being read. Reading
+        // again then finds the snapshot the removed files were folded into.
+        let mut attempt = 1;
+        loop {
+            match self.read_changelog_or_snapshot(version) {
+                Err(e) if e.kind() == io::ErrorKind::NotFound && attempt < MAX_READ_ATTEMPTS => {
+                    attempt += 1
+                }
+                res => return res,
+            }
+        }
     }
 }
 
@@ -174,7 +396,80 @@ impl LocalStoreWriter for LocalStoreImpl {
     // precondition: version > 0
     fn store(&self, version: RegistryVersion, ce: ChangelogEntry) -> io::Result<()> {
         let pb_ce = changelog_entry_to_protobuf(ce);
-        self.write_changelog_entry(version.get(), pb_ce)
+        self.write_changelog_entry(version.get(), pb_ce)?;
+        if self.snapshot_interval > 0 && version.get() % self.snapshot_interval == 0 {
+            self.write_snapshot(version)?;
+        }
+        Ok(())
+    }
+
+    fn store_snapshot(&self, version: RegistryVersion, snapshot: ChangelogEntry) -> io::Result<()> {
+        if version.get() == 0 || snapshot.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "A snapshot must be nonempty and have a version > 0.",
+            ));
+        }
+        if self.get_path(1).exists() || !self.snapshot_versions()?.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "Snapshots can only be stored in an empty store.",
+            ));
+        }
+        let pb = PbSnapshot {
+            registry_version: version.get(),
+            key_mutations: snapshot.iter().map(key_mutation_to_protobuf).collect(),
+        };
+        let path = self.get_snapshot_path(version.get());
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        write_protobuf_using_tmp_file(path, &pb)
+    }
+
+    /// Folds all changelog entries up to and including `version` into a
+    /// snapshot at `version`, and removes them together with all older
+    /// snapshots.
+    ///
+    /// Afterwards, changelogs since versions below `version` are served from
+    /// the snapshot (see
+    /// [LocalStoreReader::get_changelog_or_snapshot_since_version]).
+    ///
+    /// The snapshot is written to a temporary file and renamed into place
+    /// before anything is removed, so that readers always find either the
+    /// changelog entries or the snapshot replacing them; readers racing with
+    /// the removal retry from the snapshot. An interrupted compaction leaves
+    /// the store readable, and can be retried.
+    fn compact(&self, version: RegistryVersion) -> io::Result<()> {
+        if !self.get_snapshot_path(version.get()).exists() {
+            self.write_snapshot(version)?;
+        }
+
+        // Remove older snapshots first, so that no snapshot refers to changelog
+        // entries that are about to be removed.
+        for v in self.snapshot_versions()? {
+            if v < version.get() {
+                std::fs::remove_file(self.get_snapshot_path(v))?;
+            }
+        }
+
+        let mut dirs = BTreeSet::new();
+        for v in 1..=version.get() {
+            let path = self.get_path(v);
+            if path.exists() {
+                std::fs::remove_file(&path)?;
+                dirs.extend(
+                    path.ancestors()
+                        .skip(1)
+                        .take_while(|dir| *dir != self.path.as_path())
+                        .map(Path::to_path_buf),
+                );
+            }
+        }
+        // Remove the directories that became empty, deepest first. Directories
+        // that still hold changelog entries cannot be removed.
+        for dir in dirs.iter().rev() {
+            let _ = std::fs::remove_dir(dir);
+        }
+        Ok(())
     }
 
     fn clear(&self) -> io::Result<()> {
@@ -201,20 +496,20 @@ impl RegistryDataProvider for LocalStoreImpl {
         &self,
         version: RegistryVersion,
     ) -> Result<Vec<RegistryTransportRecord>, RegistryDataProviderError> {
-        let changelog = self.get_changelog_since_version(version).map_err(|e| {
-            RegistryDataProviderError::Transfer {
+        let (start, changelog) = self
+            .get_changelog_or_snapshot_since_version(version)
+            .map_err(|e| RegistryDataProviderError::Transfer {
                 source: ic_registry_transport::Error::MalformedMessage(format!(
                     "Error when reading changelog from local storage: {:?}",
                     e
                 )),
-            }
-        })?;
+            })?;
         let res: Vec<_> = changelog
             .iter()
             .enumerate()
             .flat_map(|(i, cle)| cle.iter().map(move |km| (i, km)))
             .map(|(i, km)| RegistryTransportRecord {
-                version: version + RegistryVersion::from((i as u64) + 1),
+                version: start + RegistryVersion::from((i as u64) + 1),
                 key: km.key.clone(),
                 value: km.value.clone(),
             })
@@ -355,24 +650,23 @@ pub fn changelog_to_compact_delta(
 
 fn changelog_entry_to_protobuf(ce: ChangelogEntry) -> PbChangelogEntry {
     assert!(!ce.is_empty());
-    let key_mutations = ce
-        .iter()
-        .map(|km| {
-            let mutation_type = if km.value.is_some() {
-                MutationType::Set as i32
-            } else {
-                MutationType::Unset as i32
-            };
-            PbKeyMutation {
-                key: km.key.clone(),
-                value: km.value.clone().unwrap_or_default(),
-                mutation_type,
-            }
-        })
-        .collect();
+    let key_mutations = ce.iter().map(key_mutation_to_protobuf).collect();
     PbChangelogEntry { key_mutations }
 }
 
+fn key_mutation_to_protobuf(km: &KeyMutation) -> PbKeyMutation {
+    let mutation_type = if km.value.is_some() {
+        MutationType::Set as i32
+    } else {
+        MutationType::Unset as i32
+    };
+    PbKeyMutation {
+        key: km.key.clone(),
+        value: km.value.clone().unwrap_or_default(),
+        mutation_type,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -459,6 +753,194 @@ mod tests {
         }
     }
 
+    #[test]
+    fn snapshots_are_written_periodically() {
+        let tempdir = TempDir::new().unwrap();
+        let store = LocalStoreImpl::new(tempdir.path()).with_snapshot_interval(10);
+        let mut rng = rand::thread_rng();
+
+        let changelog = get_random_changelog(25, &mut rng);
+        changelog.iter().enumerate().for_each(|(i, c)| {
+            store
+                .store(RegistryVersion::from((i + 1) as u64), c.clone())
+                .unwrap()
+        });
+
+        assert_eq!(store.snapshot_versions().unwrap(), vec![10, 20]);
+        assert_eq!(store.registry_at(20).unwrap(), apply(&changelog[..20]));
+        assert_eq!(store.registry_at(25).unwrap(), apply(&changelog));
+    }
+
+    #[test]
+    fn changelog_is_served_from_snapshot_after_compaction() {
+        let tempdir = TempDir::new().unwrap();
+        let store = LocalStoreImpl::new(tempdir.path()).with_snapshot_interval(10);
+        let mut rng = rand::thread_rng();
+
+        let mut changelog = get_random_changelog(50, &mut rng);
+        changelog.iter().enumerate().for_each(|(i, c)| {
+            store
+                .store(RegistryVersion::from((i + 1) as u64), c.clone())
+                .unwrap()
+        });
+
+        store.compact(RegistryVersion::from(30)).unwrap();
+        assert_eq!(store.snapshot_versions().unwrap(), vec![30, 40, 50]);
+        assert!(!store.get_path(1).exists());
+        assert!(!store.get_path(30).exists());
+
+        for i in 0..30 {
+            let (start, cl) = store
+                .get_changelog_or_snapshot_since_version(RegistryVersion::from(i as u64))
+                .unwrap();
+            assert_eq!(start, RegistryVersion::from(29));
+            assert_eq!(cl.len(), changelog.len() - 29);
+            assert!(cl.iter().all(|entry| !entry.is_empty()));
+            assert_eq!(&changelog[30..], &cl[1..]);
+
+            let mut registry = apply(&changelog[..i]);
+            registry.extend(apply(&cl));
+            assert_eq!(registry, apply(&changelog));
+
+            // The changelog since a compacted version also starts with the
+            // snapshot, without the deletions if it is applied to the empty
+            // registry.
+            let since = store
+                .get_changelog_since_version(RegistryVersion::from(i as u64))
+                .unwrap();
+            assert_eq!(&since[1..], &cl[1..]);
+            if i == 0 {
+                assert!(since[0].iter().all(|km| km.value.is_some()));
+                assert_eq!(
+                    apply(&since),
+                    apply(&changelog)
+                        .into_iter()
+                        .filter(|(_, value)| value.is_some())
+                        .collect()
+                );
+            } else {
+                assert_eq!(since, cl);
+            }
+        }
+        for i in 30..=changelog.len() {
+            let cl = store
+                .get_changelog_since_version(RegistryVersion::from(i as u64))
+                .unwrap();
+            assert_eq!(&changelog[i..], cl.as_slice());
+        }
+
+        // The store can be extended after compaction.
+        let mut new_changelog = get_random_changelog(5, &mut rng);
+        new_changelog.iter().enumerate().for_each(|(i, c)| {
+            store
+                .store(RegistryVersion::from((i + 51) as u64), c.clone())
+                .unwrap()
+        });
+        changelog.append(&mut new_changelog);
+        let (start, cl) = store
+            .get_changelog_or_snapshot_since_version(RegistryVersion::from(0))
+            .unwrap();
+        assert_eq!(start.get() as usize + cl.len(), changelog.len());
+        assert_eq!(apply(&cl), apply(&changelog));
+    }
+
+    #[test]
+    fn compacted_store_can_be_copied_using_store() {
+        let tempdir = TempDir::new().unwrap();
+        let store = LocalStoreImpl::new(tempdir.path()).with_snapshot_interval(10);
+        let mut rng = rand::thread_rng();
+
+        let changelog = get_random_changelog(25, &mut rng);
+        changelog.iter().enumerate().for_each(|(i, c)| {
+            store
+                .store(RegistryVersion::from((i + 1) as u64), c.clone())
+                .unwrap()
+        });
+        store.compact(RegistryVersion::from(20)).unwrap();
+
+        // Copy the store, the way the registry replicator restores a store
+        // from another one.
+        let (start, cl) = store
+            .get_changelog_or_snapshot_since_version(RegistryVersion::from(0))
+            .unwrap();
+        assert_eq!(start, RegistryVersion::from(19));
+        let copy_dir = TempDir::new().unwrap();
+        let copy = LocalStoreImpl::new(copy_dir.path()).with_snapshot_interval(10);
+        let mut entries = cl.into_iter();
+        copy.store_snapshot(RegistryVersion::from(20), entries.next().unwrap())
+            .unwrap();
+        for (i, entry) in entries.enumerate() {
+            copy.store(RegistryVersion::from((i + 21) as u64), entry)
+                .unwrap();
+        }
+
+        assert_eq!(
+            copy.get_changelog_or_snapshot_since_version(RegistryVersion::from(0))
+                .unwrap(),
+            store
+                .get_changelog_or_snapshot_since_version(RegistryVersion::from(0))
+                .unwrap()
+        );
+        assert_eq!(
+            copy.get_changelog_since_version(RegistryVersion::from(20))
+                .unwrap(),
+            changelog[20..].to_vec()
+        );
+        // Versions served from the snapshot are reported at the snapshot
+        // version.
+        let records = copy.get_updates_since(RegistryVersion::from(0)).unwrap();
+        assert!(records
+            .iter()
+            .all(|record| record.version >= RegistryVersion::from(20)));
+        assert_eq!(records.last().unwrap().version, RegistryVersion::from(25));
+
+        // Snapshots can only be stored in empty stores.
+        assert!(copy
+            .store_snapshot(RegistryVersion::from(30), changelog[0].clone())
+            .is_err());
+    }
+
+    #[test]
+    fn store_without_snapshots_can_be_compacted() {
+        let tempdir = TempDir::new().unwrap();
+        let store = LocalStoreImpl::new(tempdir.path());
+        let mut rng = rand::thread_rng();
+
+        // Written without snapshots, like stores predating them.
+        let changelog = get_random_changelog(20, &mut rng);
+        changelog.iter().enumerate().for_each(|(i, c)| {
+            store
+                .write_changelog_entry_unsafe((i + 1) as u64, c.clone())
+                .unwrap()
+        });
+        assert!(store.snapshot_versions().unwrap().is_empty());
+
+        store.compact(RegistryVersion::from(15)).unwrap();
+
+        let (start, cl) = store
+            .get_changelog_or_snapshot_since_version(RegistryVersion::from(0))
+            .unwrap();
+        assert_eq!(start, RegistryVersion::from(14));
+        assert_eq!(cl.len(), changelog.len() - 14);
+        assert_eq!(apply(&cl), apply(&changelog));
+
+        store.clear().unwrap();
+        assert!(store.snapshot_versions().unwrap().is_empty());
+        assert!(store
+            .get_changelog_since_version(RegistryVersion::from(0))
+            .unwrap()
+            .is_empty());
+    }
+
+    /// Applies the changelog to an empty registry.
+    fn apply(changelog: &[ChangelogEntry]) -> BTreeMap<String, Option<Vec<u8>>> {
+        changelog
+            .iter()
+            .flatten()
+            .map(|km| (km.key.clone(), km.value.clone()))
+            .collect()
+    }
+
     #[test]
     fn can_store_and_read_certified_time() {
         use std::time::{SystemTime, UNIX_EPOCH};
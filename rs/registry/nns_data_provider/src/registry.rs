@@ -53,6 +53,28 @@ impl RegistryCanister {
         }
     }
 
+    /// Verifies the certificates of update calls, e.g. to `atomic_mutate`,
+    /// against `root_key`. Without a root key, update calls fail.
+    pub fn with_root_key(self, root_key: ThresholdSigPublicKey) -> Self {
+        self.map_agents(|agent| agent.with_root_key(root_key))
+    }
+
+    /// Accepts the certificates of update calls without verifying them. Only
+    /// use it against test networks whose root key is not known.
+    pub fn with_insecure_skip_certificate_verification(self) -> Self {
+        self.map_agents(Agent::with_insecure_skip_certificate_verification)
+    }
+
+    fn map_agents<F>(self, f: F) -> Self
+    where
+        F: FnMut(Agent) -> Agent,
+    {
+        RegistryCanister {
+            canister_id: self.canister_id,
+            agent: self.agent.into_iter().map(f).collect(),
+        }
+    }
+
     /// Returns an `Agent` chosen at random
     fn choose_random_agent(&self) -> &Agent {
         self.agent
@@ -34,6 +34,17 @@ pub struct Delta {
     #[prost(message, repeated, tag = "2")]
     pub changelog: ::prost::alloc::vec::Vec<ChangelogEntry>,
 }
+/// The full registry at a specific version: the latest mutation of every key
+/// that was mutated up to and including `registry_version`. Keys that were
+/// deleted are kept as `UNSET` mutations, so that applying a snapshot to the
+/// registry at any earlier version produces the registry at `registry_version`.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Snapshot {
+    #[prost(uint64, tag = "1")]
+    pub registry_version: u64,
+    #[prost(message, repeated, tag = "2")]
+    pub key_mutations: ::prost::alloc::vec::Vec<KeyMutation>,
+}
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum MutationType {
@@ -370,6 +370,8 @@ impl TryFrom<pb_metadata::SubnetTopology> for SubnetTopology {
 pub struct NodeTopology {
     pub ip_address: String,
     pub http_port: u16,
+    /// The DER-encoded Ed25519 node signing public key.
+    pub public_key: Vec<u8>,
 }
 
 impl From<&NodeTopology> for pb_metadata::NodeTopology {
@@ -377,6 +379,7 @@ impl From<&NodeTopology> for pb_metadata::NodeTopology {
         Self {
             ip_address: item.ip_address.clone(),
             http_port: item.http_port as u32,
+            public_key: item.public_key.clone(),
         }
     }
 }
@@ -387,6 +390,7 @@ impl TryFrom<pb_metadata::NodeTopology> for NodeTopology {
         Ok(Self {
             ip_address: item.ip_address,
             http_port: item.http_port as u16,
+            public_key: item.public_key,
         })
     }
 }
@@ -394,6 +398,13 @@ impl TryFrom<pb_metadata::NodeTopology> for NodeTopology {
 #[derive(Clone, Debug, PartialEq, Eq, Default)]
 pub struct SubnetMetrics {
     pub consumed_cycles_by_deleted_canisters: NominalCycles,
+    /// The number of canisters on the subnet at the end of the last round.
+    pub num_canisters: u64,
+    /// The memory used by all canisters at the end of the last round.
+    pub canister_state_bytes: NumBytes,
+    /// The total number of ingress messages and canister requests executed by
+    /// canisters on the subnet.
+    pub update_transactions_total: u64,
 }
 
 impl From<&SubnetMetrics> for pb_metadata::SubnetMetrics {
@@ -402,6 +413,9 @@ impl From<&SubnetMetrics> for pb_metadata::SubnetMetrics {
             consumed_cycles_by_deleted_canisters: Some(
                 (&item.consumed_cycles_by_deleted_canisters).into(),
             ),
+            num_canisters: item.num_canisters,
+            canister_state_bytes: item.canister_state_bytes.get(),
+            update_transactions_total: item.update_transactions_total,
         }
     }
 }
@@ -414,6 +428,9 @@ impl TryFrom<pb_metadata::SubnetMetrics> for SubnetMetrics {
                 item.consumed_cycles_by_deleted_canisters,
                 "SubnetMetrics::consumed_cycles_by_deleted_canisters",
             )?,
+            num_canisters: item.num_canisters,
+            canister_state_bytes: NumBytes::from(item.canister_state_bytes),
+            update_transactions_total: item.update_transactions_total,
         })
     }
 }
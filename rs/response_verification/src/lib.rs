@@ -0,0 +1,397 @@
+//! Verification of responses to `http_request` calls of canisters serving
+//! certified assets.
+//!
+//! A canister certifies its assets by setting its certified data to the root
+//! hash of a tree that maps `http_assets/<path>` to the SHA-256 hash of the
+//! body served for `<path>`. Responses carry an `IC-Certificate` header of the
+//! form
+//!
+//! ```text
+//! IC-Certificate: certificate=:<base64 CBOR certificate>:, tree=:<base64 CBOR hash tree>:
+//! ```
+//!
+//! where the certificate attests the canister's certified data and the tree
+//! is a witness for the path of the request.
+//!
+//! Nothing in this repository verifies asset responses yet. The library is
+//! meant to back such checks in boundary node tooling.
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::Read;
+use std::time::Duration;
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+
+use ic_certification::{verify_certificate, CertificateValidationError};
+use ic_crypto_sha::Sha256;
+use ic_crypto_tree_hash::MixedHashTree;
+use ic_types::{crypto::threshold_sig::ThresholdSigPublicKey, CanisterId, Time};
+
+#[cfg(test)]
+mod tests;
+
+/// Name of the header carrying the certificate and the hash tree of a
+/// response.
+pub const CERTIFICATE_HEADER_NAME: &str = "IC-Certificate";
+
+/// How far the time of a certificate may be off the local time, as accepted
+/// by the service worker.
+pub const DEFAULT_MAX_CERT_TIME_OFFSET: Duration = Duration::from_secs(5 * 60);
+
+/// Upper bound on the size of a decompressed response body, so that a
+/// malicious response cannot exhaust the memory of the verifier.
+pub const MAX_DECODED_BODY_SIZE: usize = 10 * 1024 * 1024;
+
+/// Path of the asset served when the requested path is not certified, which
+/// is how asset canisters serve single page applications.
+const FALLBACK_PATH: &str = "/index.html";
+
+/// Describes an error that occurred during verification of a response to an
+/// `http_request` call.
+#[derive(Debug)]
+pub enum ResponseVerificationError {
+    /// The response has no `IC-Certificate` header.
+    MissingCertificateHeader,
+    /// The `IC-Certificate` header could not be parsed.
+    MalformedCertificateHeader(String),
+    /// The hash tree of the `IC-Certificate` header could not be parsed or
+    /// is not well-formed.
+    MalformedHashTree(String),
+    /// The certificate is invalid, e.g. it is not signed by the subnet of the
+    /// canister or does not certify the root hash of the tree.
+    InvalidCertificate(CertificateValidationError),
+    /// The time of the certificate is too far off the local time.
+    StaleCertificate { certificate_time: Time, now: Time },
+    /// The URL of the request could not be percent-decoded.
+    MalformedUrl(String),
+    /// The tree certifies neither the requested path nor, if it proves that
+    /// the requested path is absent, the fallback path.
+    AssetNotCertified(String),
+    /// The body of the response could not be decoded according to its
+    /// `Content-Encoding`.
+    BodyDecodingError(String),
+    /// The hash of the response body does not match the hash in the tree.
+    BodyHashMismatch {
+        certified: Vec<u8>,
+        computed: Vec<u8>,
+    },
+}
+
+impl fmt::Display for ResponseVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingCertificateHeader => {
+                write!(f, "response has no {} header", CERTIFICATE_HEADER_NAME)
+            }
+            Self::MalformedCertificateHeader(err) => write!(
+                f,
+                "failed to parse {} header: {}",
+                CERTIFICATE_HEADER_NAME, err
+            ),
+            Self::MalformedHashTree(err) => write!(f, "hash tree is not well-formed: {}", err),
+            Self::InvalidCertificate(err) => write!(f, "invalid certificate: {}", err),
+            Self::StaleCertificate {
+                certificate_time,
+                now,
+            } => write!(
+                f,
+                "certificate time {} is too far off the current time {}",
+                certificate_time, now
+            ),
+            Self::MalformedUrl(url) => write!(f, "failed to decode URL {}", url),
+            Self::AssetNotCertified(path) => {
+                write!(f, "no certified asset for path {}", path)
+            }
+            Self::BodyDecodingError(err) => write!(f, "failed to decode body: {}", err),
+            Self::BodyHashMismatch {
+                certified,
+                computed,
+            } => write!(
+                f,
+                "body hash does not match: certified hash is {}, body hash is {}",
+                hex::encode(certified),
+                hex::encode(computed)
+            ),
+        }
+    }
+}
+
+impl From<CertificateValidationError> for ResponseVerificationError {
+    fn from(err: CertificateValidationError) -> Self {
+        Self::InvalidCertificate(err)
+    }
+}
+
+/// The contents of an `IC-Certificate` header.
+#[derive(Debug)]
+pub struct CertificateHeader {
+    /// The CBOR encoded certificate.
+    pub certificate: Vec<u8>,
+    pub tree: MixedHashTree,
+}
+
+impl CertificateHeader {
+    /// Parses the value of an `IC-Certificate` header.
+    pub fn parse(value: &str) -> Result<Self, ResponseVerificationError> {
+        let malformed = ResponseVerificationError::MalformedCertificateHeader;
+
+        let (mut certificate, mut tree) = (None, None);
+        for field in value.split(',') {
+            let (name, value) = field
+                .trim()
+                .split_once('=')
+                .ok_or_else(|| malformed(format!("invalid field {}", field)))?;
+            let value = value
+                .strip_prefix(':')
+                .and_then(|value| value.strip_suffix(':'))
+                .ok_or_else(|| malformed(format!("field {} is not a byte sequence", name)))?;
+            let value = base64::decode(value)
+                .map_err(|err| malformed(format!("invalid base64 in field {}: {}", name, err)))?;
+            match name {
+                "certificate" => certificate = Some(value),
+                "tree" => tree = Some(value),
+                // Unknown fields are ignored for forward compatibility.
+                _ => {}
+            }
+        }
+
+        let certificate = certificate.ok_or_else(|| malformed("missing certificate".into()))?;
+        let tree = tree.ok_or_else(|| malformed("missing tree".into()))?;
+        let tree = serde_cbor::from_slice(&tree).map_err(|err| {
+            ResponseVerificationError::MalformedHashTree(format!(
+                "failed to decode hash tree: {}",
+                err
+            ))
+        })?;
+
+        Ok(Self { certificate, tree })
+    }
+}
+
+/// Verifies the response of canister `canister_id` to an `http_request`
+/// call for `url`.
+///
+/// Verification ensures that
+/// * the response has a well-formed `IC-Certificate` header,
+/// * the certificate is valid w.r.t. `root_pk` and certifies the root hash of
+///   the tree as the certified data of `canister_id`, including the checks of
+///   subnet delegations and canister ranges done by
+///   `ic_certification::verify_certificate`,
+/// * the time of the certificate is at most `max_cert_time_offset` off `now`,
+/// * the tree contains the hash of the asset for the path of `url`, or, if
+///   the path is not certified, the hash of `/index.html`, and
+/// * the hash of the body, decoded according to its `Content-Encoding`,
+///   matches that hash.
+///
+/// `headers` and `body` are those of the complete response, i.e. bodies of
+/// streaming responses must be assembled before verification.
+///
+/// Returns the certificate's timestamp, if verification is successful.
+pub fn verify_response(
+    canister_id: &CanisterId,
+    url: &str,
+    headers: &[(String, String)],
+    body: &[u8],
+    root_pk: &ThresholdSigPublicKey,
+    now: Time,
+    max_cert_time_offset: Duration,
+) -> Result<Time, ResponseVerificationError> {
+    let header = find_header(headers, CERTIFICATE_HEADER_NAME)
+        .ok_or(ResponseVerificationError::MissingCertificateHeader)?;
+    let CertificateHeader { certificate, tree } = CertificateHeader::parse(header)?;
+
+    let certificate_time =
+        verify_certificate(&certificate, canister_id, root_pk, tree.digest().as_bytes())?;
+    verify_freshness(certificate_time, now, max_cert_time_offset)?;
+
+    let path = url_path(url)?;
+    let certified_hash = lookup_asset_hash(&tree, &path)?;
+
+    let body = decode_body(body, find_header(headers, "Content-Encoding"))?;
+    let computed_hash = Sha256::hash(&body);
+    if certified_hash != computed_hash {
+        return Err(ResponseVerificationError::BodyHashMismatch {
+            certified: certified_hash,
+            computed: computed_hash.to_vec(),
+        });
+    }
+
+    Ok(certificate_time)
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+fn verify_freshness(
+    certificate_time: Time,
+    now: Time,
+    max_cert_time_offset: Duration,
+) -> Result<(), ResponseVerificationError> {
+    let (certificate_nanos, now_nanos) = (
+        certificate_time.as_nanos_since_unix_epoch(),
+        now.as_nanos_since_unix_epoch(),
+    );
+    let offset = Duration::from_nanos(if certificate_nanos > now_nanos {
+        certificate_nanos - now_nanos
+    } else {
+        now_nanos - certificate_nanos
+    });
+    if offset > max_cert_time_offset {
+        return Err(ResponseVerificationError::StaleCertificate {
+            certificate_time,
+            now,
+        });
+    }
+    Ok(())
+}
+
+/// Returns the percent-decoded path of `url`, without query and fragment.
+fn url_path(url: &str) -> Result<String, ResponseVerificationError> {
+    let path = url
+        .split(|c| c == '?' || c == '#')
+        .next()
+        .unwrap_or_default();
+
+    let invalid = || ResponseVerificationError::MalformedUrl(url.to_string());
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut chars = path.bytes();
+    while let Some(b) = chars.next() {
+        if b == b'%' {
+            let hex = [
+                chars.next().ok_or_else(invalid)?,
+                chars.next().ok_or_else(invalid)?,
+            ];
+            let hex = std::str::from_utf8(&hex).map_err(|_| invalid())?;
+            bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+        } else {
+            bytes.push(b);
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+/// Looks up the certified hash of the asset at `path`, falling back to
+/// `/index.html` only if the tree proves that `path` is not certified.
+fn lookup_asset_hash(
+    tree: &MixedHashTree,
+    path: &str,
+) -> Result<Vec<u8>, ResponseVerificationError> {
+    for candidate in [path, FALLBACK_PATH] {
+        match lookup(tree, &[b"http_assets", candidate.as_bytes()]) {
+            Lookup::Found(hash) => return Ok(hash.to_vec()),
+            Lookup::Absent => continue,
+            Lookup::Unknown => break,
+        }
+    }
+    Err(ResponseVerificationError::AssetNotCertified(
+        path.to_string(),
+    ))
+}
+
+/// Result of looking up a path in a witness.
+enum Lookup<'a> {
+    /// The path leads to a leaf with the given value.
+    Found(&'a [u8]),
+    /// The witness proves that the path is not in the tree.
+    Absent,
+    /// The path may be in a pruned part of the witness, or does not lead to
+    /// a leaf.
+    Unknown,
+}
+
+fn lookup<'a>(tree: &'a MixedHashTree, path: &[&[u8]]) -> Lookup<'a> {
+    let mut tree = tree;
+    for label in path {
+        tree = match find_label(tree, label) {
+            Search::Found(subtree) => subtree,
+            Search::Unknown => return Lookup::Unknown,
+            Search::Empty | Search::Less | Search::Greater | Search::Absent => {
+                return Lookup::Absent
+            }
+        };
+    }
+    match tree {
+        MixedHashTree::Leaf(value) => Lookup::Found(value),
+        _ => Lookup::Unknown,
+    }
+}
+
+/// Position of a label relative to the labels of a (sub)tree of forks.
+enum Search<'a> {
+    /// The label leads to the given subtree.
+    Found(&'a MixedHashTree),
+    /// The subtree has no labels.
+    Empty,
+    /// All labels of the subtree are smaller than the label.
+    Less,
+    /// All labels of the subtree are greater than the label.
+    Greater,
+    /// The label falls between two adjacent labels of the subtree.
+    Absent,
+    /// The label may be in a pruned part of the subtree.
+    Unknown,
+}
+
+fn find_label<'a>(tree: &'a MixedHashTree, label: &[u8]) -> Search<'a> {
+    match tree {
+        MixedHashTree::Empty => Search::Empty,
+        MixedHashTree::Labeled(l, subtree) => match l.as_bytes().cmp(label) {
+            Ordering::Equal => Search::Found(subtree),
+            Ordering::Less => Search::Less,
+            Ordering::Greater => Search::Greater,
+        },
+        MixedHashTree::Fork(lr) => match find_label(&lr.0, label) {
+            Search::Empty => find_label(&lr.1, label),
+            Search::Less => match find_label(&lr.1, label) {
+                Search::Empty => Search::Less,
+                Search::Greater => Search::Absent,
+                right => right,
+            },
+            Search::Unknown => match find_label(&lr.1, label) {
+                // Labels are sorted, so the pruned labels are smaller, too
+                Search::Less => Search::Less,
+                Search::Found(subtree) => Search::Found(subtree),
+                Search::Absent => Search::Absent,
+                Search::Empty | Search::Greater | Search::Unknown => Search::Unknown,
+            },
+            left => left,
+        },
+        MixedHashTree::Leaf(_) | MixedHashTree::Pruned(_) => Search::Unknown,
+    }
+}
+
+fn decode_body(
+    body: &[u8],
+    content_encoding: Option<&str>,
+) -> Result<Vec<u8>, ResponseVerificationError> {
+    let decoder: Box<dyn Read + '_> = match content_encoding.map(str::trim) {
+        None | Some("") | Some("identity") => return Ok(body.to_vec()),
+        Some(encoding) if encoding.eq_ignore_ascii_case("gzip") => Box::new(GzDecoder::new(body)),
+        Some(encoding) if encoding.eq_ignore_ascii_case("deflate") => {
+            Box::new(ZlibDecoder::new(body))
+        }
+        Some(encoding) => {
+            return Err(ResponseVerificationError::BodyDecodingError(format!(
+                "unsupported content encoding {}",
+                encoding
+            )))
+        }
+    };
+
+    let mut decoded = Vec::new();
+    decoder
+        .take(MAX_DECODED_BODY_SIZE as u64 + 1)
+        .read_to_end(&mut decoded)
+        .map_err(|err| ResponseVerificationError::BodyDecodingError(err.to_string()))?;
+    if decoded.len() > MAX_DECODED_BODY_SIZE {
+        return Err(ResponseVerificationError::BodyDecodingError(format!(
+            "decoded body exceeds {} bytes",
+            MAX_DECODED_BODY_SIZE
+        )));
+    }
+    Ok(decoded)
+}
@@ -0,0 +1,352 @@
+use std::io::Write;
+
+use flate2::{write::GzEncoder, Compression};
+
+use ic_certification_test_utils::{
+    mixed_hash_tree, serialize_to_cbor, CertificateBuilder, CertificateData::CanisterData,
+    CertificateData::SubnetData,
+};
+use ic_crypto_tree_hash::{flatmap, FlatMap, Label, LabeledTree};
+use ic_types::{PrincipalId, SubnetId};
+
+use super::*;
+
+const CERTIFICATE_TIME: u64 = 1_650_000_000_000_000_000;
+const BODY: &[u8] = b"<html><body>Hello, certified world!</body></html>";
+
+fn canister_id(id: u64) -> CanisterId {
+    CanisterId::from_u64(id)
+}
+
+fn now() -> Time {
+    Time::from_nanos_since_unix_epoch(CERTIFICATE_TIME)
+}
+
+fn asset_tree(assets: &[(&str, &[u8])]) -> MixedHashTree {
+    let paths = FlatMap::from_key_values(
+        assets
+            .iter()
+            .map(|(path, body)| {
+                (
+                    Label::from(*path),
+                    LabeledTree::Leaf(Sha256::hash(body).to_vec()),
+                )
+            })
+            .collect(),
+    );
+    mixed_hash_tree(&LabeledTree::SubTree(flatmap![
+        Label::from("http_assets") => LabeledTree::SubTree(paths),
+    ]))
+}
+
+/// Replaces the subtree under `label` by its digest, keeping the label if
+/// `keep_label` is set, as asset canisters do for the neighbours of the
+/// requested path.
+fn prune(tree: MixedHashTree, label: &str, keep_label: bool) -> MixedHashTree {
+    match tree {
+        MixedHashTree::Fork(lr) => {
+            let (left, right) = *lr;
+            MixedHashTree::Fork(Box::new((
+                prune(left, label, keep_label),
+                prune(right, label, keep_label),
+            )))
+        }
+        MixedHashTree::Labeled(l, subtree) if l.as_bytes() == label.as_bytes() => {
+            if keep_label {
+                MixedHashTree::Labeled(l, Box::new(MixedHashTree::Pruned(subtree.digest())))
+            } else {
+                MixedHashTree::Pruned(MixedHashTree::Labeled(l, subtree).digest())
+            }
+        }
+        MixedHashTree::Labeled(l, subtree) => {
+            MixedHashTree::Labeled(l, Box::new(prune(*subtree, label, keep_label)))
+        }
+        tree => tree,
+    }
+}
+
+fn certificate_builder(canister_id: CanisterId, tree: &MixedHashTree) -> CertificateBuilder {
+    CertificateBuilder::new(CanisterData {
+        canister_id,
+        certified_data: tree.digest(),
+    })
+    .with_time(CERTIFICATE_TIME)
+}
+
+fn certificate_header(certificate: &[u8], tree: &MixedHashTree) -> (String, String) {
+    (
+        CERTIFICATE_HEADER_NAME.to_string(),
+        format!(
+            "certificate=:{}:, tree=:{}:",
+            base64::encode(certificate),
+            base64::encode(serialize_to_cbor(tree))
+        ),
+    )
+}
+
+/// Returns the headers of a response certified for `canister_id(1)`, and the
+/// root public key.
+fn certified_headers(assets: &[(&str, &[u8])]) -> (Vec<(String, String)>, ThresholdSigPublicKey) {
+    let tree = asset_tree(assets);
+    let (_cert, pk, cbor) = certificate_builder(canister_id(1), &tree).build();
+    (vec![certificate_header(&cbor, &tree)], pk)
+}
+
+fn verify(
+    url: &str,
+    headers: &[(String, String)],
+    body: &[u8],
+    pk: &ThresholdSigPublicKey,
+) -> Result<Time, ResponseVerificationError> {
+    verify_response(
+        &canister_id(1),
+        url,
+        headers,
+        body,
+        pk,
+        now(),
+        DEFAULT_MAX_CERT_TIME_OFFSET,
+    )
+}
+
+#[test]
+fn should_verify_certified_asset() {
+    let (headers, pk) = certified_headers(&[("/", b"root"), ("/index.html", BODY)]);
+
+    let time = verify("/index.html", &headers, BODY, &pk).expect("expect valid response");
+    assert_eq!(time, now());
+}
+
+#[test]
+fn should_verify_certified_asset_with_delegation() {
+    let tree = asset_tree(&[("/index.html", BODY)]);
+    let (_cert, pk, cbor) = certificate_builder(canister_id(1), &tree)
+        .with_delegation(CertificateBuilder::new(SubnetData {
+            subnet_id: SubnetId::from(PrincipalId::new_subnet_test_id(123)),
+            canister_id_ranges: vec![(canister_id(0), canister_id(10))],
+        }))
+        .build();
+
+    verify(
+        "/index.html",
+        &[certificate_header(&cbor, &tree)],
+        BODY,
+        &pk,
+    )
+    .expect("expect valid response");
+}
+
+#[test]
+fn should_reject_delegation_for_other_canister_range() {
+    let tree = asset_tree(&[("/index.html", BODY)]);
+    let (_cert, pk, cbor) = certificate_builder(canister_id(1), &tree)
+        .with_delegation(CertificateBuilder::new(SubnetData {
+            subnet_id: SubnetId::from(PrincipalId::new_subnet_test_id(123)),
+            canister_id_ranges: vec![(canister_id(5), canister_id(10))],
+        }))
+        .build();
+
+    assert!(matches!(
+        verify(
+            "/index.html",
+            &[certificate_header(&cbor, &tree)],
+            BODY,
+            &pk
+        ),
+        Err(ResponseVerificationError::InvalidCertificate(
+            CertificateValidationError::CanisterIdOutOfRange
+        ))
+    ));
+}
+
+#[test]
+fn should_ignore_query_and_decode_path() {
+    let (headers, pk) = certified_headers(&[("/hello world.html", BODY)]);
+
+    verify("/hello%20world.html?lang=en#top", &headers, BODY, &pk).expect("expect valid response");
+}
+
+#[test]
+fn should_fall_back_to_index_html() {
+    let (headers, pk) = certified_headers(&[("/index.html", BODY)]);
+
+    verify("/some/route", &headers, BODY, &pk).expect("expect valid response");
+}
+
+/// Returns the headers of a response certified for `canister_id(1)` with
+/// the tree pruned by `prune_tree`, and the root public key.
+fn pruned_headers(
+    assets: &[(&str, &[u8])],
+    prune_tree: impl Fn(MixedHashTree) -> MixedHashTree,
+) -> (Vec<(String, String)>, ThresholdSigPublicKey) {
+    let tree = asset_tree(assets);
+    let (_cert, pk, cbor) = certificate_builder(canister_id(1), &tree).build();
+    let witness = prune_tree(tree);
+    (vec![certificate_header(&cbor, &witness)], pk)
+}
+
+#[test]
+fn should_fall_back_if_witness_proves_path_absent() {
+    let (headers, pk) = pruned_headers(
+        &[("/a.js", b"a"), ("/index.html", BODY), ("/z.js", b"z")],
+        |tree| prune(prune(tree, "/a.js", true), "/z.js", true),
+    );
+
+    verify("/b", &headers, BODY, &pk).expect("expect valid response");
+}
+
+#[test]
+fn should_not_fall_back_if_path_is_pruned() {
+    let (headers, pk) = pruned_headers(&[("/app.js", b"app"), ("/index.html", BODY)], |tree| {
+        prune(tree, "/app.js", true)
+    });
+
+    assert!(matches!(
+        verify("/app.js", &headers, BODY, &pk),
+        Err(ResponseVerificationError::AssetNotCertified(_))
+    ));
+}
+
+#[test]
+fn should_not_fall_back_if_path_may_be_in_pruned_subtree() {
+    let (headers, pk) = pruned_headers(&[("/app.js", b"app"), ("/index.html", BODY)], |tree| {
+        prune(tree, "/app.js", false)
+    });
+
+    assert!(matches!(
+        verify("/app.js", &headers, BODY, &pk),
+        Err(ResponseVerificationError::AssetNotCertified(_))
+    ));
+}
+
+#[test]
+fn should_verify_gzip_encoded_body() {
+    let (mut headers, pk) = certified_headers(&[("/index.html", BODY)]);
+    headers.push(("content-encoding".to_string(), "gzip".to_string()));
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(BODY).unwrap();
+    let body = encoder.finish().unwrap();
+
+    verify("/index.html", &headers, &body, &pk).expect("expect valid response");
+}
+
+#[test]
+fn should_reject_missing_header() {
+    let (_headers, pk) = certified_headers(&[("/index.html", BODY)]);
+
+    assert!(matches!(
+        verify("/index.html", &[], BODY, &pk),
+        Err(ResponseVerificationError::MissingCertificateHeader)
+    ));
+}
+
+#[test]
+fn should_reject_malformed_header() {
+    let (_headers, pk) = certified_headers(&[("/index.html", BODY)]);
+    let headers = vec![(
+        CERTIFICATE_HEADER_NAME.to_string(),
+        "certificate=:not base64:".to_string(),
+    )];
+
+    assert!(matches!(
+        verify("/index.html", &headers, BODY, &pk),
+        Err(ResponseVerificationError::MalformedCertificateHeader(_))
+    ));
+}
+
+#[test]
+fn should_reject_tampered_body() {
+    let (headers, pk) = certified_headers(&[("/index.html", BODY)]);
+
+    assert!(matches!(
+        verify("/index.html", &headers, b"<html>forged</html>", &pk),
+        Err(ResponseVerificationError::BodyHashMismatch { .. })
+    ));
+}
+
+#[test]
+fn should_reject_uncertified_path() {
+    let (headers, pk) = certified_headers(&[("/app.js", BODY)]);
+
+    assert!(matches!(
+        verify("/index.html", &headers, BODY, &pk),
+        Err(ResponseVerificationError::AssetNotCertified(_))
+    ));
+}
+
+#[test]
+fn should_reject_tree_not_matching_certified_data() {
+    let tree = asset_tree(&[("/index.html", BODY)]);
+    let (_cert, pk, cbor) = certificate_builder(canister_id(1), &tree).build();
+    let forged_tree = asset_tree(&[("/index.html", b"forged")]);
+
+    assert!(matches!(
+        verify(
+            "/index.html",
+            &[certificate_header(&cbor, &forged_tree)],
+            b"forged",
+            &pk
+        ),
+        Err(ResponseVerificationError::InvalidCertificate(
+            CertificateValidationError::CertifiedDataMismatch { .. }
+        ))
+    ));
+}
+
+#[test]
+fn should_reject_certificate_for_other_canister() {
+    let tree = asset_tree(&[("/index.html", BODY)]);
+    let (_cert, pk, cbor) = certificate_builder(canister_id(2), &tree).build();
+
+    assert!(matches!(
+        verify(
+            "/index.html",
+            &[certificate_header(&cbor, &tree)],
+            BODY,
+            &pk
+        ),
+        Err(ResponseVerificationError::InvalidCertificate(
+            CertificateValidationError::MalformedHashTree(_)
+        ))
+    ));
+}
+
+#[test]
+fn should_reject_invalid_signature() {
+    let tree = asset_tree(&[("/index.html", BODY)]);
+    let (_cert, pk, cbor) = certificate_builder(canister_id(1), &tree)
+        .with_invalid_sig()
+        .build();
+
+    assert!(matches!(
+        verify(
+            "/index.html",
+            &[certificate_header(&cbor, &tree)],
+            BODY,
+            &pk
+        ),
+        Err(ResponseVerificationError::InvalidCertificate(
+            CertificateValidationError::InvalidSignature(_)
+        ))
+    ));
+}
+
+#[test]
+fn should_reject_stale_certificate() {
+    let (headers, pk) = certified_headers(&[("/index.html", BODY)]);
+    let later = now() + DEFAULT_MAX_CERT_TIME_OFFSET + Duration::from_secs(1);
+
+    assert!(matches!(
+        verify_response(
+            &canister_id(1),
+            "/index.html",
+            &headers,
+            BODY,
+            &pk,
+            later,
+            DEFAULT_MAX_CERT_TIME_OFFSET,
+        ),
+        Err(ResponseVerificationError::StaleCertificate { .. })
+    ));
+}
@@ -53,10 +53,10 @@ pub fn lookup_or_else_create_canisters(args: &DeployArgs) -> SnsCanisterIds {
 /// If all the SNS canisters have already been created, return them.
 fn lookup(args: &DeployArgs) -> Option<SnsCanisterIds> {
     Some(SnsCanisterIds {
-        governance: get_canister_id("sns_governance", args)?,
-        ledger: get_canister_id("sns_ledger", args)?,
-        root: get_canister_id("sns_root", args)?,
-        swap: get_canister_id("sns_swap", args)?,
+        governance: get_canister_id("sns_governance", &args.network, args.verbose)?,
+        ledger: get_canister_id("sns_ledger", &args.network, args.verbose)?,
+        root: get_canister_id("sns_root", &args.network, args.verbose)?,
+        swap: get_canister_id("sns_swap", &args.network, args.verbose)?,
     })
 }
 
@@ -78,16 +78,13 @@ fn create_canisters(args: &DeployArgs) -> SnsCanisterIds {
 }
 
 /// Return the canister ID of the canister given by `canister_name`
-pub fn get_canister_id(canister_name: &str, args: &DeployArgs) -> Option<PrincipalId> {
-    println!(
-        "dfx canister --network {} id {}",
-        &args.network, canister_name
-    );
-    let output = call_dfx(&["canister", "--network", &args.network, "id", canister_name]);
+pub fn get_canister_id(canister_name: &str, network: &str, verbose: bool) -> Option<PrincipalId> {
+    println!("dfx canister --network {} id {}", network, canister_name);
+    let output = call_dfx(&["canister", "--network", network, "id", canister_name]);
 
     let canister_id = String::from_utf8(output.stdout)
         .map_err(|e| {
-            if args.verbose {
+            if verbose {
                 println!(
                     "Could not parse the output of 'dfx canister id {}' as a string, error: {}",
                     canister_name, e
@@ -98,7 +95,7 @@ pub fn get_canister_id(canister_name: &str, args: &DeployArgs) -> Option<Princip
 
     PrincipalId::from_str(canister_id.trim())
         .map_err(|e| {
-            if args.verbose {
+            if verbose {
                 println!(
                     "Could not parse the output of 'dfx canister id {}' as a PrincipalId, error: {}",
                     canister_name, e
@@ -0,0 +1,409 @@
+//! Contains the logic for interacting with the SNS governance canister: making proposals,
+//! voting and following with a neuron, and listing proposals.
+
+use crate::call_canister;
+use crate::deploy::get_canister_id;
+use anyhow::anyhow;
+use candid::IDLArgs;
+use clap::Parser;
+use ic_sns_governance::pb::v1::manage_neuron::{Command, Follow, RegisterVote};
+use ic_sns_governance::pb::v1::proposal::Action;
+use ic_sns_governance::pb::v1::{
+    ExecuteGenericNervousSystemFunction, ListProposals, ManageNeuron, Motion, NeuronId, Proposal,
+    ProposalId, Vote,
+};
+use std::process::exit;
+
+/// The arguments identifying the SNS governance canister to interact with.
+#[derive(Debug, Parser)]
+pub struct GovernanceCanisterArgs {
+    /// The network the SNS is deployed to. This can be "local", "ic", or the URL of an IC network.
+    #[structopt(default_value = "local", long)]
+    network: String,
+
+    /// The canister ID of the SNS governance canister. If not specified, the ID of the
+    /// `sns_governance` canister of the current dfx project is used.
+    #[clap(long)]
+    governance_canister_id: Option<String>,
+}
+
+/// The arguments used to make a proposal
+#[derive(Debug, Parser)]
+pub struct MakeProposalArgs {
+    #[clap(flatten)]
+    governance: GovernanceCanisterArgs,
+
+    /// The ID of the neuron making the proposal, i.e. the hex encoding of its subaccount.
+    #[clap(long)]
+    neuron_id: String,
+
+    /// The title of the proposal.
+    #[clap(long)]
+    title: String,
+
+    /// The summary of the proposal.
+    #[clap(long)]
+    summary: String,
+
+    /// A URL pointing to more information about the proposal.
+    #[clap(long, default_value = "")]
+    url: String,
+
+    /// Make a motion proposal with the given motion text.
+    #[clap(long, conflicts_with = "function_id")]
+    motion_text: Option<String>,
+
+    /// Make a proposal to execute the generic nervous system function with the given ID.
+    #[clap(long, requires = "payload")]
+    function_id: Option<u64>,
+
+    /// The arguments of the generic nervous system function, in candid text format,
+    /// e.g. '(record { name = "foo" })'.
+    #[clap(long)]
+    payload: Option<String>,
+}
+
+/// The arguments used to vote on a proposal with a neuron
+#[derive(Debug, Parser)]
+pub struct VoteArgs {
+    #[clap(flatten)]
+    governance: GovernanceCanisterArgs,
+
+    /// The ID of the voting neuron, i.e. the hex encoding of its subaccount.
+    #[clap(long)]
+    neuron_id: String,
+
+    /// The ID of the proposal to vote on.
+    #[clap(long)]
+    proposal_id: u64,
+
+    /// The vote to cast, either "yes" or "no".
+    #[clap(long)]
+    vote: String,
+}
+
+/// The arguments used to set the followees of a neuron
+#[derive(Debug, Parser)]
+pub struct FollowArgs {
+    #[clap(flatten)]
+    governance: GovernanceCanisterArgs,
+
+    /// The ID of the following neuron, i.e. the hex encoding of its subaccount.
+    #[clap(long)]
+    neuron_id: String,
+
+    /// The ID of the nervous system function for which the followees are set. Use 0 to set the
+    /// followees of all functions that don't have explicit followees.
+    #[clap(long)]
+    function_id: u64,
+
+    /// The ID of a neuron to follow. Can be given multiple times. If not given, the neuron
+    /// stops following any neuron on `function_id`.
+    #[clap(long)]
+    followee: Vec<String>,
+}
+
+/// The arguments used to list proposals
+#[derive(Debug, Parser)]
+pub struct ListProposalsArgs {
+    #[clap(flatten)]
+    governance: GovernanceCanisterArgs,
+
+    /// The maximum number of proposals to list, from 1 to 100.
+    #[clap(long, default_value = "10")]
+    limit: u32,
+
+    /// Only list proposals with an ID strictly lower than this one. Used to page through
+    /// older proposals.
+    #[clap(long)]
+    before_proposal: Option<u64>,
+}
+
+impl GovernanceCanisterArgs {
+    /// Return the canister ID (as text) of the SNS governance canister to call.
+    fn canister_id(&self) -> String {
+        match &self.governance_canister_id {
+            Some(canister_id) => canister_id.clone(),
+            None => get_canister_id("sns_governance", &self.network, true)
+                .unwrap_or_else(|| {
+                    eprintln!(
+                        "Could not find the sns_governance canister, use \
+                         --governance-canister-id to specify it"
+                    );
+                    exit(1);
+                })
+                .to_string(),
+        }
+    }
+}
+
+impl MakeProposalArgs {
+    /// Build the `ManageNeuron` request making the proposal described by the args.
+    fn to_manage_neuron(&self) -> anyhow::Result<ManageNeuron> {
+        let action = match (&self.motion_text, self.function_id) {
+            (Some(motion_text), None) => Action::Motion(Motion {
+                motion_text: motion_text.clone(),
+            }),
+            (None, Some(function_id)) => {
+                let payload = self.payload.as_deref().unwrap_or("()");
+                let payload = payload
+                    .parse::<IDLArgs>()
+                    .and_then(|args| args.to_bytes())
+                    .map_err(|err| anyhow!("Could not encode the payload as candid: {}", err))?;
+                Action::ExecuteGenericNervousSystemFunction(ExecuteGenericNervousSystemFunction {
+                    function_id,
+                    payload,
+                })
+            }
+            _ => {
+                return Err(anyhow!(
+                    "Exactly one of --motion-text and --function-id must be specified"
+                ))
+            }
+        };
+
+        Ok(ManageNeuron {
+            subaccount: parse_neuron_id(&self.neuron_id)?.id,
+            command: Some(Command::MakeProposal(Proposal {
+                title: self.title.clone(),
+                summary: self.summary.clone(),
+                url: self.url.clone(),
+                action: Some(action),
+            })),
+        })
+    }
+}
+
+impl VoteArgs {
+    /// Build the `ManageNeuron` request registering the vote described by the args.
+    fn to_manage_neuron(&self) -> anyhow::Result<ManageNeuron> {
+        let vote = match self.vote.to_lowercase().as_str() {
+            "yes" => Vote::Yes,
+            "no" => Vote::No,
+            vote => return Err(anyhow!("Unknown vote {}, expected yes or no", vote)),
+        };
+
+        Ok(ManageNeuron {
+            subaccount: parse_neuron_id(&self.neuron_id)?.id,
+            command: Some(Command::RegisterVote(RegisterVote {
+                proposal: Some(ProposalId {
+                    id: self.proposal_id,
+                }),
+                vote: vote as i32,
+            })),
+        })
+    }
+}
+
+impl FollowArgs {
+    /// Build the `ManageNeuron` request setting the followees described by the args.
+    fn to_manage_neuron(&self) -> anyhow::Result<ManageNeuron> {
+        let followees = self
+            .followee
+            .iter()
+            .map(|followee| parse_neuron_id(followee))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(ManageNeuron {
+            subaccount: parse_neuron_id(&self.neuron_id)?.id,
+            command: Some(Command::Follow(Follow {
+                function_id: self.function_id,
+                followees,
+            })),
+        })
+    }
+}
+
+/// Parse the hex encoding of a neuron's subaccount as a `NeuronId`.
+fn parse_neuron_id(neuron_id: &str) -> anyhow::Result<NeuronId> {
+    let id = hex::decode(neuron_id)
+        .map_err(|err| anyhow!("Could not parse {} as a neuron ID: {}", neuron_id, err))?;
+    if id.len() != 32 {
+        return Err(anyhow!(
+            "Could not parse {} as a neuron ID: expected 32 bytes, got {}",
+            neuron_id,
+            id.len()
+        ));
+    }
+    Ok(NeuronId { id })
+}
+
+/// Send the `ManageNeuron` request built by `build` to the governance canister, or exit if the
+/// request could not be built.
+fn manage_neuron(
+    governance: &GovernanceCanisterArgs,
+    build: impl FnOnce() -> anyhow::Result<ManageNeuron>,
+) {
+    let manage_neuron = build().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        exit(1);
+    });
+    call_canister(
+        &governance.network,
+        &governance.canister_id(),
+        "manage_neuron",
+        &manage_neuron,
+        false,
+    );
+}
+
+/// Make a proposal with the neuron given in `MakeProposalArgs`.
+pub fn make_proposal(args: MakeProposalArgs) {
+    manage_neuron(&args.governance, || args.to_manage_neuron());
+}
+
+/// Vote on a proposal with the neuron given in `VoteArgs`.
+pub fn vote(args: VoteArgs) {
+    manage_neuron(&args.governance, || args.to_manage_neuron());
+}
+
+/// Set the followees of the neuron given in `FollowArgs`.
+pub fn follow(args: FollowArgs) {
+    manage_neuron(&args.governance, || args.to_manage_neuron());
+}
+
+/// Print the proposals selected by `ListProposalsArgs`, latest first.
+pub fn list_proposals(args: ListProposalsArgs) {
+    let list_proposals = ListProposals {
+        limit: args.limit,
+        before_proposal: args.before_proposal.map(|id| ProposalId { id }),
+        ..Default::default()
+    };
+    call_canister(
+        &args.governance.network,
+        &args.governance.canister_id(),
+        "list_proposals",
+        &list_proposals,
+        true,
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use candid::{Decode, Encode};
+
+    const NEURON_ID: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+
+    fn governance_args() -> GovernanceCanisterArgs {
+        GovernanceCanisterArgs {
+            network: "local".to_string(),
+            governance_canister_id: None,
+        }
+    }
+
+    fn make_proposal_args() -> MakeProposalArgs {
+        MakeProposalArgs {
+            governance: governance_args(),
+            neuron_id: NEURON_ID.to_string(),
+            title: "Title".to_string(),
+            summary: "Summary".to_string(),
+            url: "".to_string(),
+            motion_text: None,
+            function_id: None,
+            payload: None,
+        }
+    }
+
+    #[test]
+    fn test_make_motion_proposal() {
+        let args = MakeProposalArgs {
+            motion_text: Some("Motion".to_string()),
+            ..make_proposal_args()
+        };
+
+        let manage_neuron = args.to_manage_neuron().unwrap();
+
+        assert_eq!(manage_neuron.subaccount, vec![1; 32]);
+        match manage_neuron.command {
+            Some(Command::MakeProposal(proposal)) => {
+                assert_eq!(proposal.title, "Title");
+                assert_eq!(
+                    proposal.action,
+                    Some(Action::Motion(Motion {
+                        motion_text: "Motion".to_string()
+                    }))
+                );
+            }
+            command => panic!("Unexpected command {:?}", command),
+        }
+    }
+
+    #[test]
+    fn test_make_generic_function_proposal_encodes_payload() {
+        let args = MakeProposalArgs {
+            function_id: Some(1000),
+            payload: Some("(\"foo\", 42 : nat64)".to_string()),
+            ..make_proposal_args()
+        };
+
+        let manage_neuron = args.to_manage_neuron().unwrap();
+
+        let payload = match manage_neuron.command {
+            Some(Command::MakeProposal(Proposal {
+                action:
+                    Some(Action::ExecuteGenericNervousSystemFunction(
+                        ExecuteGenericNervousSystemFunction {
+                            function_id: 1000,
+                            payload,
+                        },
+                    )),
+                ..
+            })) => payload,
+            command => panic!("Unexpected command {:?}", command),
+        };
+        assert_eq!(payload, Encode!(&"foo", &42_u64).unwrap());
+        assert_eq!(
+            Decode!(&payload, String, u64).unwrap(),
+            ("foo".to_string(), 42)
+        );
+    }
+
+    #[test]
+    fn test_make_proposal_requires_exactly_one_action() {
+        assert!(make_proposal_args().to_manage_neuron().is_err());
+        let args = MakeProposalArgs {
+            function_id: Some(1000),
+            payload: Some("not candid".to_string()),
+            ..make_proposal_args()
+        };
+        assert!(args.to_manage_neuron().is_err());
+    }
+
+    #[test]
+    fn test_vote_and_follow() {
+        let vote = VoteArgs {
+            governance: governance_args(),
+            neuron_id: NEURON_ID.to_string(),
+            proposal_id: 7,
+            vote: "Yes".to_string(),
+        };
+        assert_eq!(
+            vote.to_manage_neuron().unwrap().command,
+            Some(Command::RegisterVote(RegisterVote {
+                proposal: Some(ProposalId { id: 7 }),
+                vote: Vote::Yes as i32,
+            }))
+        );
+
+        let follow = FollowArgs {
+            governance: governance_args(),
+            neuron_id: NEURON_ID.to_string(),
+            function_id: 0,
+            followee: vec!["02".repeat(32)],
+        };
+        assert_eq!(
+            follow.to_manage_neuron().unwrap().command,
+            Some(Command::Follow(Follow {
+                function_id: 0,
+                followees: vec![NeuronId { id: vec![2; 32] }],
+            }))
+        );
+
+        let follow = FollowArgs {
+            followee: vec!["02".to_string()],
+            ..follow
+        };
+        assert!(follow.to_manage_neuron().is_err());
+    }
+}
@@ -1,10 +1,14 @@
 //! A command-line tool to initialize, deploy and interact with a SNS (Service Nervous System)
 
 mod deploy;
+mod governance;
 mod init_config_file;
+mod swap;
 
 use crate::deploy::{DirectSnsDeployerForTests, SnsWasmSnsDeployer};
+use crate::governance::{FollowArgs, ListProposalsArgs, MakeProposalArgs, VoteArgs};
 use crate::init_config_file::{InitConfigFileArgs, SnsCliInitConfig};
+use crate::swap::{BuyerStateArgs, SwapCanisterArgs};
 use anyhow::anyhow;
 use candid::{CandidType, Encode, IDLArgs};
 use clap::Parser;
@@ -47,6 +51,18 @@ enum SubCommand {
     AccountBalance(AccountBalanceArgs),
     /// Manage the config file where the initial sns parameters are set.
     InitConfigFile(InitConfigFileArgs),
+    /// Make a proposal with a neuron.
+    MakeProposal(MakeProposalArgs),
+    /// Vote on a proposal with a neuron.
+    Vote(VoteArgs),
+    /// Set the followees of a neuron for a nervous system function.
+    Follow(FollowArgs),
+    /// List the proposals of the SNS, latest first.
+    ListProposals(ListProposalsArgs),
+    /// Display the state of the swap.
+    SwapState(SwapCanisterArgs),
+    /// Display the state of a buyer in the swap.
+    BuyerState(BuyerStateArgs),
 }
 
 /// The arguments used to configure a SNS deployment
@@ -183,6 +199,12 @@ fn main() {
         SubCommand::AddSnsWasmForTests(args) => add_sns_wasm_for_tests(args),
         SubCommand::AccountBalance(args) => print_account_balance(args),
         SubCommand::InitConfigFile(args) => init_config_file::exec(args),
+        SubCommand::MakeProposal(args) => governance::make_proposal(args),
+        SubCommand::Vote(args) => governance::vote(args),
+        SubCommand::Follow(args) => governance::follow(args),
+        SubCommand::ListProposals(args) => governance::list_proposals(args),
+        SubCommand::SwapState(args) => swap::print_swap_state(args),
+        SubCommand::BuyerState(args) => swap::print_buyer_state(args),
     }
 }
 
@@ -335,6 +357,35 @@ fn call_dfx(args: &[&str]) -> Output {
     output
 }
 
+/// Calls `method` of the canister with ID `canister_id` with the candid encoding of `arg`. The
+/// argument is passed through a temporary file, as it may be too large for the command line.
+/// Exits if the call fails.
+pub fn call_canister(
+    network: &str,
+    canister_id: &str,
+    method: &str,
+    arg: &impl CandidType,
+    query: bool,
+) -> Output {
+    let idl = IDLArgs::from_bytes(&Encode!(arg).unwrap()).unwrap();
+    let mut argument_file = NamedTempFile::new().expect("Could not open temp file");
+    argument_file
+        .write_all(format!("{}", idl).as_bytes())
+        .expect("Could not write the argument to temp file");
+    let argument_path = argument_file.path().as_os_str().to_str().unwrap();
+
+    let mut args = vec!["canister", "--network", network, "call"];
+    if query {
+        args.push("--query");
+    }
+    args.extend(["--argument-file", argument_path, canister_id, method]);
+    let output = call_dfx(&args);
+    if !output.status.success() {
+        std::process::exit(output.status.code().unwrap_or(1));
+    }
+    output
+}
+
 /// Given a `CandidType`, return the hex encoding of this object.
 pub fn hex_encode_candid(candid: impl CandidType) -> String {
     let bytes = Encode!(&candid).unwrap();
@@ -0,0 +1,84 @@
+//! Contains the logic for inspecting the state of the SNS swap canister.
+
+use crate::deploy::get_canister_id;
+use crate::{call_canister, get_identity};
+use clap::Parser;
+use ic_base_types::PrincipalId;
+use ic_sns_swap::pb::v1::{GetBuyerStateRequest, GetStateRequest};
+use std::process::exit;
+use std::str::FromStr;
+
+/// The arguments identifying the SNS swap canister to interact with.
+#[derive(Debug, Parser)]
+pub struct SwapCanisterArgs {
+    /// The network the SNS is deployed to. This can be "local", "ic", or the URL of an IC network.
+    #[structopt(default_value = "local", long)]
+    network: String,
+
+    /// The canister ID of the SNS swap canister. If not specified, the ID of the `sns_swap`
+    /// canister of the current dfx project is used.
+    #[clap(long)]
+    swap_canister_id: Option<String>,
+}
+
+/// The arguments used to display the state of a buyer in the swap
+#[derive(Debug, Parser)]
+pub struct BuyerStateArgs {
+    #[clap(flatten)]
+    swap: SwapCanisterArgs,
+
+    /// The principal ID of the buyer. If not specified, the principal of the current dfx
+    /// identity is used.
+    #[clap(long)]
+    principal_id: Option<String>,
+}
+
+impl SwapCanisterArgs {
+    /// Return the canister ID (as text) of the SNS swap canister to call.
+    fn canister_id(&self) -> String {
+        match &self.swap_canister_id {
+            Some(canister_id) => canister_id.clone(),
+            None => get_canister_id("sns_swap", &self.network, true)
+                .unwrap_or_else(|| {
+                    eprintln!(
+                        "Could not find the sns_swap canister, use --swap-canister-id to \
+                         specify it"
+                    );
+                    exit(1);
+                })
+                .to_string(),
+        }
+    }
+}
+
+/// Print the state of the swap: its init parameters, lifecycle and buyers.
+pub fn print_swap_state(args: SwapCanisterArgs) {
+    call_canister(
+        &args.network,
+        &args.canister_id(),
+        "get_state",
+        &GetStateRequest {},
+        true,
+    );
+}
+
+/// Print the swap state of the buyer given in `BuyerStateArgs` if given, else print the state
+/// of the principal of the current dfx identity.
+pub fn print_buyer_state(args: BuyerStateArgs) {
+    let principal_id = if let Some(principal_str) = args.principal_id {
+        PrincipalId::from_str(&principal_str)
+            .unwrap_or_else(|_| panic!("Could not parse {} as a PrincipalId", principal_str))
+    } else {
+        get_identity("get-principal", &args.swap.network)
+    };
+
+    call_canister(
+        &args.swap.network,
+        &args.swap.canister_id(),
+        "get_buyer_state",
+        &GetBuyerStateRequest {
+            principal_id: Some(principal_id),
+        },
+        true,
+    );
+}
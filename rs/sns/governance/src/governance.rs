@@ -65,7 +65,8 @@ use crate::proposal::{
 
 use crate::pb::v1::governance::{UpgradeInProgress, Version};
 use crate::sns_upgrade::{
-    get_all_sns_canisters, get_running_version, get_upgrade_params, get_wasm, UpgradeSnsParams,
+    get_all_sns_canisters, get_running_version, get_upgrade_params, get_wasm, record_sns_upgrade,
+    UpgradeSnsParams,
 };
 use crate::types::{is_registered_function_id, Environment, HeapGrowthPotential, LedgerUpdateLock};
 use candid::Encode;
@@ -3745,8 +3746,19 @@ impl Governance {
                 self.env.now(),
                 target_version
             );
-            self.proto.deployed_version = Some(target_version);
+            self.proto.deployed_version = Some(target_version.clone());
             self.proto.pending_version = None;
+
+            // The upgrade is complete regardless of whether SNS-W manages to record it. If it
+            // does not, SNS-W records the new version the next time it reconciles the upgrade
+            // history of the SNSes it deployed with their running versions.
+            if let Err(message) = record_sns_upgrade(&*self.env, &target_version).await {
+                println!(
+                    "{}Could not record the upgrade in SNS-W: {}",
+                    log_prefix(),
+                    message
+                );
+            }
         }
     }
 
@@ -3914,7 +3926,8 @@ mod tests {
     use crate::sns_upgrade::{
         CanisterSummary, GetNextSnsVersionRequest, GetNextSnsVersionResponse,
         GetSnsCanistersSummaryRequest, GetSnsCanistersSummaryResponse, GetWasmRequest,
-        GetWasmResponse, SnsCanisterType, SnsVersion, SnsWasm,
+        GetWasmResponse, RecordSnsUpgradeRequest, RecordSnsUpgradeResponse, SnsCanisterType,
+        SnsVersion, SnsWasm,
     };
     use crate::{
         pb::v1::{
@@ -5246,6 +5259,19 @@ mod tests {
             Ok(Encode!(&std_sns_canisters_summary_response()).unwrap()),
         );
 
+        // Once the upgrade is complete, it is recorded in SNS-W.
+        env.require_call_canister_invocation(
+            SNS_WASM_CANISTER_ID,
+            "record_sns_upgrade",
+            Encode!(&RecordSnsUpgradeRequest {
+                new_version: Some(next_version.clone())
+            })
+            .unwrap(),
+            Some(Ok(
+                Encode!(&RecordSnsUpgradeResponse { error: None }).unwrap()
+            )),
+        );
+
         let current_version = {
             let mut version = next_version.clone();
             version.archive_wasm_hash = vec![1, 1, 1];
@@ -242,6 +242,31 @@ async fn get_next_version(env: &dyn Environment, current_version: &Version) -> O
     response.next_version.map(|v| v.into())
 }
 
+/// Records in SNS-W that the SNS now runs `new_version`, so that SNS-W can keep track of the
+/// upgrade history of this SNS.
+pub(crate) async fn record_sns_upgrade(
+    env: &dyn Environment,
+    new_version: &Version,
+) -> Result<(), String> {
+    let arg = Encode!(&RecordSnsUpgradeRequest {
+        new_version: Some(new_version.clone().into())
+    })
+    .unwrap();
+
+    let response = env
+        .call_canister(SNS_WASM_CANISTER_ID, "record_sns_upgrade", arg)
+        .await
+        .map_err(|e| format!("Request failed for record_sns_upgrade: {:?}", e))?;
+
+    let response = Decode!(&response, RecordSnsUpgradeResponse)
+        .map_err(|e| format!("Failed to decode response: {:?}", e))?;
+
+    match response.error {
+        Some(error) => Err(error.message),
+        None => Ok(()),
+    }
+}
+
 /// Returns all SNS canisters known by the Root canister.
 pub(crate) async fn get_all_sns_canisters(
     env: &dyn Environment,
@@ -386,6 +411,30 @@ pub(crate) struct SnsVersion {
     pub archive_wasm_hash: ::prost::alloc::vec::Vec<u8>,
 }
 
+/// Duplicated from ic-sns-wasms to avoid circular dependency as a temporary workaround
+/// The request type accepted by the record_sns_upgrade canister method
+#[derive(candid::CandidType, candid::Deserialize, Clone, PartialEq, ::prost::Message)]
+pub(crate) struct RecordSnsUpgradeRequest {
+    #[prost(message, optional, tag = "1")]
+    pub new_version: ::core::option::Option<SnsVersion>,
+}
+
+/// Duplicated from ic-sns-wasms to avoid circular dependency as a temporary workaround
+/// The response type returned by the record_sns_upgrade canister method
+#[derive(candid::CandidType, candid::Deserialize, Clone, PartialEq, ::prost::Message)]
+pub(crate) struct RecordSnsUpgradeResponse {
+    #[prost(message, optional, tag = "1")]
+    pub error: ::core::option::Option<SnsWasmError>,
+}
+
+/// Duplicated from ic-sns-wasms to avoid circular dependency as a temporary workaround
+/// The error response returned in response objects on failed or partially failed operations
+#[derive(candid::CandidType, candid::Deserialize, Clone, PartialEq, ::prost::Message)]
+pub(crate) struct SnsWasmError {
+    #[prost(string, tag = "1")]
+    pub message: ::prost::alloc::string::String,
+}
+
 /// Copied from ic-sns-root
 #[derive(PartialEq, Eq, Debug, candid::CandidType, candid::Deserialize)]
 pub(crate) struct GetSnsCanistersSummaryRequest {
@@ -197,6 +197,10 @@ impl Default for BitcoinStateBits {
 /// │              ├── stable_memory.(pbuf|bin)
 /// │              └── software.wasm
 /// │
+/// ├── state_sync
+/// │   ├── scratchpad_<hex(round)>[.manifest, .missing, .fetched]
+/// │   └── cache_<hex(round)>[.manifest, .missing, .fetched]
+/// │
 /// └── tmp
 /// └── fs_tmp
 /// ```
@@ -236,15 +240,19 @@ impl Default for BitcoinStateBits {
 /// ## Promoting a State Sync artifact to a checkpoint
 ///
 ///   1. Create state files directly in
-///      "<state_root>/fs_tmp/state_sync_scratchpad_<height>".
+///      "<state_root>/state_sync/scratchpad_<height>".
 ///
 ///   2. When all the writes are complete, call sync_and_mark_files_readonly()
-///      on "<state_root>/fs_tmp/state_sync_scratchpad_<height>".  This function
+///      on "<state_root>/state_sync/scratchpad_<height>".  This function
 ///      syncs all the files and directories under the scratchpad directory,
 ///      including the scratchpad directory itself.
 ///
-///   3. Rename "<state_root>/fs_tmp/state_sync_scratchpad_<height>" to
+///   3. Rename "<state_root>/state_sync/scratchpad_<height>" to
 ///      "<state_root>/checkpoints/<height>", sync "<state_root>/checkpoints".
+///
+/// Unlike "tmp" and "fs_tmp", the "state_sync" directory is not cleaned
+/// during restart, so that an interrupted state sync can resume from the
+/// chunks it already fetched.
 
 #[derive(Clone)]
 pub struct StateLayout {
@@ -267,6 +275,7 @@ impl StateLayout {
         WriteOnly::check_dir(&self.checkpoints())?;
         WriteOnly::check_dir(&self.diverged_checkpoints())?;
         WriteOnly::check_dir(&self.fs_tmp())?;
+        WriteOnly::check_dir(&self.state_sync_root())?;
         WriteOnly::check_dir(&self.tip_path())?;
         WriteOnly::check_dir(&self.tmp())
     }
@@ -324,17 +333,24 @@ impl StateLayout {
         self.root.join("states_metadata.pbuf")
     }
 
+    /// Returns the path to the directory holding state sync scratchpads and
+    /// caches. This directory is preserved during restart of a node.
+    pub fn state_sync_root(&self) -> PathBuf {
+        self.root.join("state_sync")
+    }
+
     /// Returns scratchpad used during statesync
     pub fn state_sync_scratchpad(&self, height: Height) -> Result<PathBuf, LayoutError> {
         Ok(self
-            .tmp()
-            .join(format!("state_sync_scratchpad_{:016x}", height.get())))
+            .state_sync_root()
+            .join(format!("scratchpad_{:016x}", height.get())))
     }
 
     /// Returns the path to cache an unfinished statesync at `height`
     pub fn state_sync_cache(&self, height: Height) -> Result<PathBuf, LayoutError> {
-        let tmp = self.tmp();
-        Ok(tmp.join(format!("state_sync_cache_{:016x}", height.get())))
+        Ok(self
+            .state_sync_root()
+            .join(format!("cache_{:016x}", height.get())))
     }
 
     fn cleanup_tip(&self) -> Result<(), LayoutError> {
@@ -633,7 +633,8 @@ impl StateMachine {
     /// Imports a directory containing a canister snapshot into the state machine.
     ///
     /// After you import the canister, you can execute methods on it and upgrade it.
-    /// The original directory is not modified.
+    /// The original directory is not modified. Use `state_tool export-canister` to
+    /// extract such a directory from a checkpoint.
     ///
     /// The function is currently not used in code, but it is useful for local
     /// testing and debugging. Do not remove it.
@@ -119,6 +119,8 @@ pub struct StateSyncMetrics {
     remaining: IntGauge,
     corrupted_chunks_critical: IntCounter,
     corrupted_chunks: IntCounterVec,
+    compression_saved_bytes: IntCounter,
+    recovered_bytes: IntCounter,
 }
 
 #[derive(Clone)]
@@ -361,6 +363,16 @@ impl StateSyncMetrics {
             corrupted_chunks.with_label_values(&[*source]);
         }
 
+        let compression_saved_bytes = metrics_registry.int_counter(
+            "state_sync_compression_saved_bytes_total",
+            "Number of bytes not transferred during state sync thanks to chunk compression.",
+        );
+
+        let recovered_bytes = metrics_registry.int_counter(
+            "state_sync_recovered_bytes_total",
+            "Size of the chunks of unfinished state syncs recovered after a restart in bytes.",
+        );
+
         Self {
             size,
             duration,
@@ -368,6 +380,8 @@ impl StateSyncMetrics {
             remaining,
             corrupted_chunks_critical,
             corrupted_chunks,
+            compression_saved_bytes,
+            recovered_bytes,
         }
     }
 }
@@ -1381,8 +1395,16 @@ impl StateManagerImpl {
 
         report_last_diverged_checkpoint(&log, &metrics, &state_layout);
 
+        // Pick up where state syncs interrupted by the restart left off.
+        let state_sync_refs = StateSyncRefs::new(log.clone());
+        state_sync_refs.cache.write().recover(
+            &state_layout,
+            last_checkpoint.unwrap_or(Self::INITIAL_STATE_HEIGHT),
+            &metrics.state_sync_metrics,
+        );
+
         Self {
-            log: log.clone(),
+            log,
             metrics,
             state_layout,
             states,
@@ -1393,7 +1415,7 @@ impl StateManagerImpl {
             deallocation_sender,
             latest_state_height,
             latest_certified_height,
-            state_sync_refs: StateSyncRefs::new(log),
+            state_sync_refs,
             checkpoint_thread_pool,
             _state_hasher_handle,
             _deallocation_handle,
@@ -17,7 +17,7 @@ use ic_types::{
         ChunkId, Chunkable,
     },
     crypto::CryptoHash,
-    state_sync::{decode_manifest, Manifest, MANIFEST_CHUNK},
+    state_sync::{decode_chunk, decode_manifest, Manifest, MANIFEST_CHUNK},
     CryptoHashOfState, Height,
 };
 use std::os::unix::fs::FileExt;
@@ -29,6 +29,7 @@ use std::{
 };
 
 pub mod cache;
+mod progress;
 
 // If set to true, we validate chunks even in situations where it might not be
 // necessary.
@@ -73,6 +74,9 @@ pub struct IncompleteState {
     own_subnet_type: SubnetType,
     thread_pool: Arc<Mutex<scoped_threadpool::Pool>>,
     state_sync_refs: StateSyncRefs,
+    /// Records the fetched chunks so that the state sync can resume after a
+    /// restart. Not set if the progress couldn't be persisted.
+    fetched_chunks: Option<progress::FetchedChunks>,
 }
 
 impl Drop for IncompleteState {
@@ -184,6 +188,7 @@ impl IncompleteState {
             own_subnet_type,
             thread_pool,
             state_sync_refs,
+            fetched_chunks: None,
         }
     }
 
@@ -716,6 +721,44 @@ impl IncompleteState {
         metrics.remaining.sub(1);
     }
 
+    /// Persists the manifest and the chunks still to be fetched next to the
+    /// scratchpad. Failing to do so only means that the state sync cannot be
+    /// resumed after a restart.
+    fn persist_progress(
+        log: &ReplicaLogger,
+        root: &Path,
+        manifest: &Manifest,
+        fetch_chunks: &HashSet<usize>,
+    ) -> Option<progress::FetchedChunks> {
+        let missing_chunks = fetch_chunks.iter().map(|ix| ix - 1).collect();
+        match progress::persist(root, manifest, &missing_chunks)
+            .and_then(|()| progress::FetchedChunks::open(root))
+        {
+            Ok(fetched_chunks) => Some(fetched_chunks),
+            Err(err) => {
+                warn!(
+                    log,
+                    "Failed to persist the progress of the state sync at {}: {}",
+                    root.display(),
+                    err
+                );
+                Self::remove_progress(log, root);
+                None
+            }
+        }
+    }
+
+    fn remove_progress(log: &ReplicaLogger, root: &Path) {
+        if let Err(err) = progress::remove(root) {
+            warn!(
+                log,
+                "Failed to remove the progress of the state sync at {}: {}",
+                root.display(),
+                err
+            );
+        }
+    }
+
     fn build_artifact(
         state_layout: &StateLayout,
         height: Height,
@@ -890,7 +933,7 @@ impl IncompleteState {
                             // StateSyncCacheEntry, so cloning the path is safe
                             root_old: cache_entry.path().to_path_buf(),
                             height_old: cache_entry.height,
-                            validate_data: false,
+                            validate_data: cache_entry.recovered,
                         })
                     } else {
                         // This should be a special case that can only happen if the source of the
@@ -922,7 +965,7 @@ impl IncompleteState {
                     missing_chunks: cache_entry.missing_chunks.clone(),
                     root_old: cache_entry.path().to_path_buf(),
                     height_old: cache_entry.height,
-                    validate_data: false,
+                    validate_data: cache_entry.recovered,
                 }),
                 (None, Some((checkpoint_manifest, checkpoint_ref))) => {
                     let checkpoint_height = checkpoint_ref.0.height;
@@ -1096,28 +1139,67 @@ impl Chunkable for IncompleteState {
     }
 
     fn add_chunk(&mut self, artifact_chunk: ArtifactChunk) -> Result<Artifact, ArtifactErrorCode> {
-        let ix = artifact_chunk.chunk_id.get() as usize;
+        let chunk_id = artifact_chunk.chunk_id;
+        let ix = chunk_id.get() as usize;
 
-        let payload = match artifact_chunk.artifact_chunk_data {
-            ArtifactChunkData::SemiStructuredChunkData(ref payload) => payload,
+        // Peers that support it send data chunks encoded, see
+        // `ChunkableArtifact::get_encoded_chunk`, others send plain chunks.
+        let (payload, encoded) = match artifact_chunk.artifact_chunk_data {
+            ArtifactChunkData::SemiStructuredChunkData(ref payload) => (payload, false),
+            ArtifactChunkData::EncodedChunkData(ref payload) => (payload, true),
             other => {
                 warn!(self.log, "State sync chunk has wrong shape {:?}", other);
                 return Err(ChunkVerificationFailed);
             }
         };
 
+        let decoded;
+        let payload: &[u8] = if encoded {
+            // Only data chunks are sent in encoded form, and they are never
+            // larger than their entry in the chunk table.
+            let max_len = match &self.state {
+                DownloadState::Complete(ref artifact) => return Ok(*artifact.clone()),
+                DownloadState::Loading { manifest, .. }
+                    if ix > 0 && ix <= manifest.chunk_table.len() =>
+                {
+                    manifest.chunk_table[ix - 1].size_bytes as usize
+                }
+                _ => {
+                    warn!(
+                        self.log,
+                        "Received unexpected encoded chunk {} for state {}", ix, self.height
+                    );
+                    return Err(ChunkVerificationFailed);
+                }
+            };
+            decoded = decode_chunk(payload, max_len).map_err(|err| {
+                warn!(
+                    self.log,
+                    "Failed to decode state sync chunk {}: {}", ix, err
+                );
+                ChunkVerificationFailed
+            })?;
+            self.metrics
+                .state_sync_metrics
+                .compression_saved_bytes
+                .inc_by(decoded.len().saturating_sub(payload.len()) as u64);
+            &decoded
+        } else {
+            payload
+        };
+
         match &mut self.state {
             DownloadState::Complete(ref artifact) => {
                 debug!(
                     self.log,
-                    "Received chunk {} on completed state {}", artifact_chunk.chunk_id, self.height
+                    "Received chunk {} on completed state {}", chunk_id, self.height
                 );
 
                 Ok(*artifact.clone())
             }
 
             DownloadState::Blank => {
-                if artifact_chunk.chunk_id == MANIFEST_CHUNK {
+                if chunk_id == MANIFEST_CHUNK {
                     let manifest = decode_manifest(payload).map_err(|err| {
                         warn!(
                             self.log,
@@ -1174,6 +1256,8 @@ impl Chunkable for IncompleteState {
                             .register_successful_sync(self.height);
                         Ok(artifact)
                     } else {
+                        self.fetched_chunks =
+                            Self::persist_progress(&self.log, &self.root, &manifest, &fetch_chunks);
                         self.state = DownloadState::Loading {
                             manifest,
                             fetch_chunks,
@@ -1192,7 +1276,7 @@ impl Chunkable for IncompleteState {
                 ref manifest,
                 ref mut fetch_chunks,
             } => {
-                if artifact_chunk.chunk_id == MANIFEST_CHUNK {
+                if chunk_id == MANIFEST_CHUNK {
                     // Have already seen the manifest chunk
                     return Err(ChunksMoreNeeded);
                 }
@@ -1234,6 +1318,18 @@ impl Chunkable for IncompleteState {
                     manifest,
                 );
 
+                if let Some(fetched_chunks) = self.fetched_chunks.as_mut() {
+                    if let Err(err) = fetched_chunks.record(chunk_table_index) {
+                        warn!(
+                            self.log,
+                            "Failed to record fetched chunk {} of state {}: {}",
+                            ix,
+                            self.height,
+                            err
+                        );
+                    }
+                }
+
                 fetch_chunks.remove(&ix);
 
                 if fetch_chunks.is_empty() {
@@ -1253,6 +1349,8 @@ impl Chunkable for IncompleteState {
                         &self.state_layout,
                         self.own_subnet_type,
                     );
+                    self.fetched_chunks = None;
+                    Self::remove_progress(&self.log, &self.root);
 
                     let artifact = Self::build_artifact(
                         &self.state_layout,
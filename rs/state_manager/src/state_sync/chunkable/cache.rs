@@ -6,13 +6,33 @@ mod tests;
 /// Local helper function used to delete unfinished syncs from disk
 fn delete_folder(log: &ReplicaLogger, path: &Path) {
     if let Err(err) = std::fs::remove_dir_all(path) {
+        if err.kind() != std::io::ErrorKind::NotFound {
+            warn!(
+                log,
+                "Failed to remove incomplete state sync state at {}: {}",
+                path.display(),
+                err
+            );
+        }
+    };
+    if let Err(err) = progress::remove(path) {
         warn!(
             log,
-            "Failed to remove incomplete state sync state at {}: {}",
+            "Failed to remove the progress of the state sync at {}: {}",
             path.display(),
             err
         );
-    };
+    }
+}
+
+/// Parses the height from the name of a state sync scratchpad or cache
+/// directory.
+fn parse_state_sync_dir_height(path: &Path) -> Option<Height> {
+    let name = path.file_name()?.to_str()?;
+    let hex = name
+        .strip_prefix("scratchpad_")
+        .or_else(|| name.strip_prefix("cache_"))?;
+    u64::from_str_radix(hex, 16).ok().map(Height::new)
 }
 
 /// A cache for unfinished state sync artifacts.
@@ -36,6 +56,9 @@ pub struct StateSyncCacheEntry {
     pub height: Height,
     path: PathBuf,
     pub missing_chunks: HashSet<usize>,
+    /// Set if the entry was recovered after a restart. The chunks of a
+    /// recovered entry must be validated before they are reused.
+    pub recovered: bool,
     log: ReplicaLogger,
 }
 
@@ -86,6 +109,150 @@ impl StateSyncCache {
         }
     }
 
+    /// Recovers the state syncs that were interrupted by a restart.
+    ///
+    /// The most recent unfinished state sync above `latest_checkpoint_height`
+    /// whose progress is available becomes the cache entry, the leftovers of
+    /// all other state syncs are deleted.
+    pub fn recover(
+        &mut self,
+        state_layout: &StateLayout,
+        latest_checkpoint_height: Height,
+        metrics: &StateSyncMetrics,
+    ) {
+        let root = state_layout.state_sync_root();
+        let paths: Vec<PathBuf> = match std::fs::read_dir(&root) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .collect(),
+            Err(err) => {
+                warn!(
+                    self.log,
+                    "Failed to list unfinished state syncs in {}: {}",
+                    root.display(),
+                    err
+                );
+                return;
+            }
+        };
+
+        let mut candidates: Vec<(Height, PathBuf)> = paths
+            .iter()
+            .filter(|path| path.is_dir())
+            .filter_map(|path| {
+                parse_state_sync_dir_height(path)
+                    .filter(|height| *height > latest_checkpoint_height)
+                    .map(|height| (height, path.clone()))
+            })
+            .collect();
+        candidates.sort_by(|(lhs, _), (rhs, _)| rhs.cmp(lhs));
+
+        let mut recovered = None;
+        for (height, path) in candidates {
+            match progress::load(&path) {
+                Ok(Some(sync_progress)) => {
+                    recovered = Some((height, path, sync_progress));
+                    break;
+                }
+                Ok(None) => (),
+                Err(err) => warn!(
+                    self.log,
+                    "Failed to load the progress of the state sync at {}: {}",
+                    path.display(),
+                    err
+                ),
+            }
+        }
+
+        // Delete everything that is not part of the recovered state sync.
+        for path in paths.iter() {
+            let keep = recovered.as_ref().map_or(false, |(_, dir, _)| {
+                path == dir || progress::is_progress_file_of(path, dir)
+            });
+            if keep {
+                continue;
+            }
+            if path.is_dir() {
+                delete_folder(&self.log, path);
+            } else if let Err(err) = std::fs::remove_file(path) {
+                warn!(
+                    self.log,
+                    "Failed to remove {} from unfinished state syncs: {}",
+                    path.display(),
+                    err
+                );
+            }
+        }
+
+        let (height, path, sync_progress) = match recovered {
+            Some(recovered) => recovered,
+            None => return,
+        };
+
+        let cache_root = match state_layout.state_sync_cache(height) {
+            Ok(cache_root) => cache_root,
+            Err(err) => {
+                warn!(
+                    self.log,
+                    "Failed to recover the state sync at {}: {}",
+                    path.display(),
+                    err
+                );
+                delete_folder(&self.log, &path);
+                return;
+            }
+        };
+        if path != cache_root {
+            if let Err(err) = progress::remove(&path)
+                .and_then(|()| std::fs::rename(&path, &cache_root))
+                .and_then(|()| {
+                    progress::persist(
+                        &cache_root,
+                        &sync_progress.manifest,
+                        &sync_progress.missing_chunks,
+                    )
+                })
+            {
+                warn!(
+                    self.log,
+                    "Failed to recover the state sync at {}: {}",
+                    path.display(),
+                    err
+                );
+                delete_folder(&self.log, &path);
+                delete_folder(&self.log, &cache_root);
+                return;
+            }
+        }
+
+        let recovered_bytes: u64 = sync_progress
+            .manifest
+            .chunk_table
+            .iter()
+            .enumerate()
+            .filter(|(ix, _)| !sync_progress.missing_chunks.contains(ix))
+            .map(|(_, chunk)| chunk.size_bytes as u64)
+            .sum();
+        metrics.recovered_bytes.inc_by(recovered_bytes);
+        info!(
+            self.log,
+            "Recovered unfinished state sync at height {} with {} of {} chunks ({} bytes)",
+            height,
+            sync_progress.manifest.chunk_table.len() - sync_progress.missing_chunks.len(),
+            sync_progress.manifest.chunk_table.len(),
+            recovered_bytes
+        );
+
+        self.entry = Some(Arc::new(StateSyncCacheEntry {
+            manifest: sync_progress.manifest,
+            height,
+            path: cache_root,
+            missing_chunks: sync_progress.missing_chunks,
+            recovered: true,
+            log: self.log.clone(),
+        }));
+    }
+
     /// Pushes the state sync data to the cache without checking that
     /// the new state is newer that the stored one.
     ///
@@ -124,11 +291,22 @@ impl StateSyncCache {
             delete_folder(&self.log, &sync.root);
             return;
         }
+        if let Err(err) = progress::remove(&sync.root)
+            .and_then(|()| progress::persist(&cache_root, &manifest, &missing_chunks))
+        {
+            warn!(
+                self.log,
+                "Failed to persist the progress of the state sync cache at {}: {}",
+                cache_root.display(),
+                err
+            );
+        }
         let entry = StateSyncCacheEntry {
             manifest,
             height: sync.height,
             path: cache_root,
             missing_chunks,
+            recovered: false,
             log: self.log.clone(),
         };
         self.entry = Some(Arc::new(entry));
@@ -87,6 +87,7 @@ fn incomplete_state_for_tests(
         SubnetType::Application,
         Arc::new(Mutex::new(scoped_threadpool::Pool::new(NUM_THREADS))),
         state_sync_refs,
+        false,
     );
 
     // The constructor doesn't create the directory, it gets created when we receive
@@ -327,3 +328,47 @@ fn existing_folder() {
         assert!(env.cache.read().get().is_none());
     })
 }
+
+// An unfinished state sync that was pushed to the cache survives a restart
+// and is recovered as long as it is newer than the latest checkpoint.
+#[test]
+fn recover_after_restart() {
+    with_test_replica_logger(|log| {
+        let env = TestEnvironment::new(log.clone());
+        let (state, manifest, _) = fake_loading(1);
+        drop(incomplete_state_for_tests(&env, Height::new(5), state));
+        let cache_dir = env.cache.read().get().unwrap().path().to_path_buf();
+
+        // A leftover scratchpad without any progress is deleted, even if it is
+        // newer than the cache.
+        let stray_root = env
+            .state_layout
+            .state_sync_scratchpad(Height::new(6))
+            .unwrap();
+        std::fs::create_dir(&stray_root).unwrap();
+
+        let mut cache = StateSyncCache::new(log.clone());
+        cache.recover(
+            &env.state_layout,
+            Height::new(4),
+            &env.metrics.state_sync_metrics,
+        );
+        let entry = cache.get().unwrap();
+        assert!(entry.recovered);
+        assert_eq!(entry.height, Height::new(5));
+        assert_eq!(entry.manifest, manifest);
+        assert_eq!(entry.path(), cache_dir.as_path());
+        assert!(cache_dir.join("1").exists());
+        assert!(!stray_root.exists());
+
+        // State syncs below the latest checkpoint are not recovered.
+        let mut cache = StateSyncCache::new(log);
+        cache.recover(
+            &env.state_layout,
+            Height::new(5),
+            &env.metrics.state_sync_metrics,
+        );
+        assert!(cache.get().is_none());
+        assert!(!cache_dir.exists());
+    })
+}
@@ -0,0 +1,222 @@
+//! Persistence of the progress of unfinished state syncs.
+//!
+//! The progress of the state sync whose files are written to `<dir>` is
+//! stored next to it, so that it never ends up in a checkpoint:
+//!
+//!   * `<dir>.manifest` holds the encoded manifest of the synced state.
+//!
+//!   * `<dir>.missing` holds the chunks that were missing when the sync
+//!     started, as little-endian `u32` indices into the chunk table.
+//!
+//!   * `<dir>.fetched` holds the chunks that were fetched and validated
+//!     since, appended as they are written.
+//!
+//! Chunk data is not synced to disk before a chunk is recorded as fetched,
+//! hence all chunks of a recovered state sync need to be validated before
+//! they are reused.
+
+use ic_types::state_sync::{decode_manifest, encode_manifest, Manifest};
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const MANIFEST_SUFFIX: &str = "manifest";
+const MISSING_SUFFIX: &str = "missing";
+const FETCHED_SUFFIX: &str = "fetched";
+
+/// The progress of an unfinished state sync recovered from disk.
+pub(crate) struct SyncProgress {
+    pub manifest: Manifest,
+    /// Indices into the manifest's chunk table of the chunks that are not
+    /// available on disk.
+    pub missing_chunks: HashSet<usize>,
+}
+
+/// Appends the chunks fetched by a state sync to its progress.
+pub(crate) struct FetchedChunks {
+    file: File,
+}
+
+impl FetchedChunks {
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(progress_file(dir, FETCHED_SUFFIX))?;
+        Ok(Self { file })
+    }
+
+    /// Records that the chunk with the given index into the chunk table was
+    /// written to disk.
+    pub fn record(&mut self, chunk_index: usize) -> io::Result<()> {
+        self.file.write_all(&(chunk_index as u32).to_le_bytes())
+    }
+}
+
+fn progress_file(dir: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(dir.as_os_str());
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Returns true if `path` is one of the progress files of the state sync
+/// in `dir`.
+pub(crate) fn is_progress_file_of(path: &Path, dir: &Path) -> bool {
+    [MANIFEST_SUFFIX, MISSING_SUFFIX, FETCHED_SUFFIX]
+        .iter()
+        .any(|suffix| path == progress_file(dir, suffix))
+}
+
+fn encode_chunk_indices(indices: &HashSet<usize>) -> Vec<u8> {
+    indices
+        .iter()
+        .flat_map(|ix| (*ix as u32).to_le_bytes())
+        .collect()
+}
+
+fn decode_chunk_indices(bytes: &[u8]) -> impl Iterator<Item = usize> + '_ {
+    // A trailing partial index stems from an interrupted write and is ignored.
+    bytes
+        .chunks_exact(4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+}
+
+/// Replaces the progress of the state sync in `dir` by the given manifest and
+/// missing chunks.
+pub(crate) fn persist(
+    dir: &Path,
+    manifest: &Manifest,
+    missing_chunks: &HashSet<usize>,
+) -> io::Result<()> {
+    remove(dir)?;
+    std::fs::write(
+        progress_file(dir, MANIFEST_SUFFIX),
+        encode_manifest(manifest),
+    )?;
+    // The missing chunks are written last and atomically: the progress is
+    // only considered valid once they are in place.
+    let missing = progress_file(dir, MISSING_SUFFIX);
+    let tmp = progress_file(dir, &format!("{}.tmp", MISSING_SUFFIX));
+    std::fs::write(&tmp, encode_chunk_indices(missing_chunks))?;
+    std::fs::rename(&tmp, &missing)
+}
+
+/// Loads the progress of the state sync in `dir`. Returns `None` if no
+/// complete progress was persisted.
+pub(crate) fn load(dir: &Path) -> io::Result<Option<SyncProgress>> {
+    let read_if_exists = |suffix| match std::fs::read(progress_file(dir, suffix)) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    };
+
+    let (manifest, missing) = match (
+        read_if_exists(MANIFEST_SUFFIX)?,
+        read_if_exists(MISSING_SUFFIX)?,
+    ) {
+        (Some(manifest), Some(missing)) => (manifest, missing),
+        _ => return Ok(None),
+    };
+    let manifest = decode_manifest(&manifest)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let fetched: HashSet<usize> =
+        decode_chunk_indices(&read_if_exists(FETCHED_SUFFIX)?.unwrap_or_default()).collect();
+    let missing_chunks = decode_chunk_indices(&missing)
+        .filter(|ix| *ix < manifest.chunk_table.len() && !fetched.contains(ix))
+        .collect();
+
+    Ok(Some(SyncProgress {
+        manifest,
+        missing_chunks,
+    }))
+}
+
+/// Removes the progress of the state sync in `dir`, if any.
+pub(crate) fn remove(dir: &Path) -> io::Result<()> {
+    for suffix in [MANIFEST_SUFFIX, MISSING_SUFFIX, FETCHED_SUFFIX] {
+        match std::fs::remove_file(progress_file(dir, suffix)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_types::state_sync::{ChunkInfo, FileInfo};
+
+    fn manifest_with_chunks(num_chunks: usize) -> Manifest {
+        Manifest {
+            version: 1,
+            file_table: vec![FileInfo {
+                relative_path: "file".into(),
+                size_bytes: num_chunks as u64,
+                hash: [0; 32],
+            }],
+            chunk_table: (0..num_chunks)
+                .map(|i| ChunkInfo {
+                    file_index: 0,
+                    size_bytes: 1,
+                    offset: i as u64,
+                    hash: [0; 32],
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn progress_tracks_fetched_chunks() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("scratchpad");
+        assert!(load(&dir).unwrap().is_none());
+
+        let manifest = manifest_with_chunks(4);
+        persist(&dir, &manifest, &maplit::hashset! {0, 2, 3}).unwrap();
+
+        let mut fetched = FetchedChunks::open(&dir).unwrap();
+        fetched.record(2).unwrap();
+        drop(fetched);
+
+        let progress = load(&dir).unwrap().unwrap();
+        assert_eq!(progress.manifest, manifest);
+        assert_eq!(progress.missing_chunks, maplit::hashset! {0, 3});
+        assert!(is_progress_file_of(
+            &progress_file(&dir, FETCHED_SUFFIX),
+            &dir
+        ));
+
+        remove(&dir).unwrap();
+        assert!(load(&dir).unwrap().is_none());
+        assert_eq!(tmp.path().read_dir().unwrap().count(), 0);
+    }
+
+    #[test]
+    fn progress_without_missing_chunks_is_incomplete() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("scratchpad");
+        persist(&dir, &manifest_with_chunks(2), &maplit::hashset! {1}).unwrap();
+        std::fs::remove_file(progress_file(&dir, MISSING_SUFFIX)).unwrap();
+
+        assert!(load(&dir).unwrap().is_none());
+    }
+
+    #[test]
+    fn persisting_resets_the_fetched_chunks() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("scratchpad");
+        let manifest = manifest_with_chunks(2);
+        persist(&dir, &manifest, &maplit::hashset! {0, 1}).unwrap();
+        FetchedChunks::open(&dir).unwrap().record(0).unwrap();
+
+        persist(&dir, &manifest, &maplit::hashset! {0, 1}).unwrap();
+        assert_eq!(
+            load(&dir).unwrap().unwrap().missing_chunks,
+            maplit::hashset! {0, 1}
+        );
+    }
+}
@@ -277,6 +277,8 @@ mod tests {
             "48BCE58420569AB8C30A8E0653FC597AF1B8C072118BF6E0A75D3BEC2059FFFB",
             "901C2CDB77697DBD3F024F2521FED2D92F1309A6073C8C50C52A348F76418D3F",
             "901C2CDB77697DBD3F024F2521FED2D92F1309A6073C8C50C52A348F76418D3F",
+            "C80A06D4C78A9EA1792DD8779DD6F71E4244CCB5C39C43FB51FA03DCC9071A42",
+            "1574EFCE3447BC9D06EFB27C8A22CFAADF335B6D3270A621E4647453C8A72D04",
         ];
         for certification_version in CertificationVersion::iter() {
             assert_partial_state_hash_matches(
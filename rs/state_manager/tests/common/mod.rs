@@ -22,6 +22,7 @@ use ic_test_utilities::{
 use ic_types::{
     artifact::{Artifact, StateSyncMessage},
     chunkable::{
+        ArtifactChunk,
         ArtifactErrorCode::{ChunkVerificationFailed, ChunksMoreNeeded},
         ChunkId, Chunkable, ChunkableArtifact,
     },
@@ -395,6 +396,27 @@ pub fn pipe_partial_state_sync(
     src: &StateSyncMessage,
     dst: &mut dyn Chunkable,
     omit: &HashSet<ChunkId>,
+) -> Option<StateSyncMessage> {
+    pipe_partial_state_sync_with(src, dst, omit, |src, id| src.get_chunk(id))
+}
+
+/// Pipe chunks from src to dst as they are sent to peers that accept encoded
+/// chunks.
+pub fn pipe_encoded_state_sync(
+    src: StateSyncMessage,
+    mut dst: Box<dyn Chunkable>,
+) -> StateSyncMessage {
+    pipe_partial_state_sync_with(&src, &mut *dst, &Default::default(), |src, id| {
+        src.get_encoded_chunk(id)
+    })
+    .expect("State sync not completed.")
+}
+
+fn pipe_partial_state_sync_with(
+    src: &StateSyncMessage,
+    dst: &mut dyn Chunkable,
+    omit: &HashSet<ChunkId>,
+    get_chunk: impl Fn(Box<StateSyncMessage>, ChunkId) -> Option<ArtifactChunk>,
 ) -> Option<StateSyncMessage> {
     while !dst.is_complete() {
         let ids: Vec<_> = dst.chunks_to_download().collect();
@@ -409,8 +431,7 @@ pub fn pipe_partial_state_sync(
                 omitted_chunks = true;
                 continue;
             }
-            let chunk = Box::new(src.clone())
-                .get_chunk(id)
+            let chunk = get_chunk(Box::new(src.clone()), id)
                 .unwrap_or_else(|| panic!("Requested unknown chunk {}", id));
 
             match dst.add_chunk(chunk) {
@@ -25,10 +25,12 @@ use ic_test_utilities::{
     },
     with_test_replica_logger,
 };
-use ic_test_utilities_metrics::{fetch_int_counter_vec, fetch_int_gauge, Labels};
+use ic_test_utilities_metrics::{
+    fetch_int_counter, fetch_int_counter_vec, fetch_int_gauge, Labels,
+};
 use ic_types::{
     artifact::{Priority, StateSyncArtifactId, StateSyncAttribute},
-    chunkable::ChunkId,
+    chunkable::{ArtifactChunk, ArtifactChunkData, ChunkId, ChunkableArtifact},
     crypto::CryptoHash,
     ingress::{IngressState, IngressStatus, WasmResult},
     messages::CallbackId,
@@ -1706,6 +1708,73 @@ fn can_do_simple_state_sync_transfer() {
     })
 }
 
+#[test]
+fn can_do_compressed_state_sync_transfer() {
+    state_manager_test(|src_metrics, src_state_manager| {
+        let (_height, mut state) = src_state_manager.take_tip();
+        insert_dummy_canister(&mut state, canister_test_id(100));
+        // A repetitive stable memory makes sure that some chunks compress well.
+        let canister_state = state.canister_state_mut(&canister_test_id(100)).unwrap();
+        let execution_state = canister_state.execution_state.as_mut().unwrap();
+        execution_state.stable_memory.size = NumWasmPages::new(1);
+        execution_state.stable_memory.page_map = PageMap::from(&[1; 4096][..]);
+
+        src_state_manager.commit_and_certify(state, height(1), CertificationScope::Full);
+        let hash = wait_for_checkpoint(&src_state_manager, height(1));
+        let id = StateSyncArtifactId {
+            height: height(1),
+            hash,
+        };
+
+        let state = src_state_manager.get_latest_state().take();
+
+        let msg = src_state_manager
+            .get_validated_by_identifier(&id)
+            .expect("failed to get state sync messages");
+
+        assert_error_counters(src_metrics);
+
+        state_manager_test(|dst_metrics, dst_state_manager| {
+            let mut chunkable = dst_state_manager.create_chunkable_state(&id);
+
+            // The manifest is sent as is, data chunks in encoded form.
+            assert!(matches!(
+                Box::new(msg.clone()).get_encoded_chunk(ChunkId::new(0)),
+                Some(ArtifactChunk {
+                    artifact_chunk_data: ArtifactChunkData::SemiStructuredChunkData(_),
+                    ..
+                })
+            ));
+            assert!(pipe_manifest(&msg, &mut *chunkable).is_none());
+            let ids: Vec<_> = chunkable.chunks_to_download().collect();
+            assert!(ids.iter().all(|id| matches!(
+                Box::new(msg.clone()).get_encoded_chunk(*id),
+                Some(ArtifactChunk {
+                    artifact_chunk_data: ArtifactChunkData::EncodedChunkData(_),
+                    ..
+                })
+            )));
+
+            let dst_msg = pipe_encoded_state_sync(msg, chunkable);
+            dst_state_manager
+                .check_artifact_acceptance(dst_msg, &node_test_id(0))
+                .expect("Failed to process state sync artifact");
+
+            let recovered_state = dst_state_manager
+                .get_state_at(height(1))
+                .expect("Destination state manager didn't receive the state")
+                .take();
+            assert_eq!(state, recovered_state);
+
+            assert!(
+                fetch_int_counter(dst_metrics, "state_sync_compression_saved_bytes_total").unwrap()
+                    > 0
+            );
+            assert_error_counters(dst_metrics);
+        })
+    })
+}
+
 #[test]
 fn can_state_sync_from_cache() {
     state_manager_test(|src_metrics, src_state_manager| {
@@ -2,6 +2,8 @@
 pub mod cdiff;
 pub mod chash;
 pub mod decode;
+pub mod export_canister;
+pub mod import_canister;
 pub mod import_state;
 pub mod list;
 pub mod manifest;
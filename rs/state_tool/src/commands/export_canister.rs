@@ -0,0 +1,68 @@
+//! Exports the state of a single canister from a checkpoint.
+
+use crate::commands::utils;
+use ic_state_layout::{CheckpointLayout, ReadOnly};
+use ic_types::Height;
+use std::path::{Path, PathBuf};
+
+/// Returns the height encoded in the name of the checkpoint directory, or 0 if
+/// the directory doesn't follow the naming scheme of the state layout.
+fn checkpoint_height(checkpoint_path: &Path) -> Height {
+    let height = checkpoint_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| u64::from_str_radix(name, 16).ok())
+        .unwrap_or(0);
+    Height::new(height)
+}
+
+/// Copies the directory of canister `canister_id` (Wasm module, heap, stable
+/// memory, queues and system state) out of the checkpoint at `state_path`
+/// into the new directory `output`.
+///
+/// The result can be loaded with `StateMachine::import_canister_state` or
+/// imported into a state root with `state_tool import-canister`.
+pub fn do_export_canister(
+    state_path: PathBuf,
+    canister_id: String,
+    output: PathBuf,
+) -> Result<(), String> {
+    let canister_id = utils::parse_canister_id(&canister_id)?;
+    let cp_layout =
+        CheckpointLayout::<ReadOnly>::new(state_path.clone(), checkpoint_height(&state_path))
+            .map_err(|e| format!("failed to open checkpoint layout: {}", e))?;
+
+    let canister_ids = cp_layout
+        .canister_ids()
+        .map_err(|e| format!("failed to enumerate canisters: {}", e))?;
+    if !canister_ids.contains(&canister_id) {
+        return Err(format!(
+            "Canister {} does not exist in checkpoint {}",
+            canister_id,
+            state_path.display()
+        ));
+    }
+
+    if output.exists() {
+        return Err(format!(
+            "Output directory {} already exists",
+            output.display()
+        ));
+    }
+
+    let canister_layout = cp_layout
+        .canister(&canister_id)
+        .map_err(|e| format!("failed to open canister layout: {}", e))?;
+    let canister_state = utils::load_canister(&canister_layout, &canister_id, cp_layout.height())?;
+
+    utils::copy_recursively(&canister_layout.raw_path(), &output)?;
+
+    utils::print_canister_summary(&canister_state);
+    println!(
+        "Successfully exported canister {} to {}",
+        canister_id,
+        output.display()
+    );
+
+    Ok(())
+}
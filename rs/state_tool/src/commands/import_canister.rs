@@ -0,0 +1,255 @@
+//! Imports the state of a single canister into a replica state directory.
+
+use crate::commands::utils;
+use ic_state_layout::{CanisterLayout, ReadOnly};
+use std::path::PathBuf;
+
+/// Creates a new checkpoint on top of the latest checkpoint in the state root
+/// indicated in the replica configuration, with the canister exported to
+/// `canister_dir` by `state_tool export-canister` added as `canister_id`.
+/// An existing canister with the same ID is replaced.
+///
+/// Fails if the queues of the exported canister contain messages sent to or
+/// by a different canister ID, as importing it as `canister_id` would
+/// misroute them. A canister with empty queues can be imported under any ID.
+///
+/// The new checkpoint is picked up by the next replica or `drun` started with
+/// the same configuration. Routing of messages to the canister is up to the
+/// registry of the local instance.
+///
+/// Function is not crash-safe. Caller is responsible to follow guidelines
+/// regarding crash-safe I/O.
+pub fn do_import_canister(
+    canister_dir: PathBuf,
+    canister_id: String,
+    config_path: PathBuf,
+) -> Result<(), String> {
+    let canister_id = utils::parse_canister_id(&canister_id)?;
+    let state_layout = utils::locate_state_root(config_path)?;
+
+    let latest_height = state_layout
+        .checkpoint_heights()
+        .map_err(|e| format!("failed to enumerate checkpoints: {}", e))?
+        .into_iter()
+        .max()
+        .ok_or_else(|| {
+            format!(
+                "State root {} doesn't contain any checkpoint to import the canister into",
+                state_layout.raw_path().display()
+            )
+        })?;
+    let height = latest_height.increment();
+
+    // Make sure the exported canister is usable before touching the state root.
+    let exported_layout = CanisterLayout::<ReadOnly>::new(canister_dir.clone())
+        .map_err(|e| format!("failed to open canister layout: {}", e))?;
+    utils::load_canister(&exported_layout, &canister_id, height)?;
+    let own_ids = utils::own_ids_in_queues(&exported_layout)?;
+    if let Some(other_id) = own_ids.iter().find(|id| **id != canister_id) {
+        return Err(format!(
+            "The queues of the canister in {} hold messages of canister {}, it cannot be imported as {}",
+            canister_dir.display(),
+            other_id,
+            canister_id
+        ));
+    }
+
+    let scratchpad = state_layout
+        .checkpoint_to_scratchpad(latest_height)
+        .map_err(|e| format!("failed to copy checkpoint {}: {}", latest_height, e))?;
+    let canister_layout = scratchpad
+        .canister(&canister_id)
+        .map_err(|e| format!("failed to create canister layout: {}", e))?;
+    let canister_root = canister_layout.raw_path();
+    std::fs::remove_dir_all(&canister_root).map_err(|e| {
+        format!(
+            "failed to remove canister directory {}: {}",
+            canister_root.display(),
+            e
+        )
+    })?;
+    utils::copy_recursively(&canister_dir, &canister_root)?;
+
+    let canister_state = utils::load_canister(&canister_layout, &canister_id, height)?;
+
+    let cp_layout = state_layout
+        .scratchpad_to_checkpoint(scratchpad, height)
+        .map_err(|e| e.to_string())?;
+
+    utils::print_canister_summary(&canister_state);
+    println!(
+        "Successfully imported canister {} into checkpoint {} at {}",
+        canister_id,
+        height,
+        cp_layout.raw_path().display()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::export_canister::do_export_canister;
+    use ic_base_types::NumSeconds;
+    use ic_config::state_manager::Config;
+    use ic_interfaces_state_manager::{CertificationScope, StateManager};
+    use ic_logger::replica_logger::no_op_logger;
+    use ic_metrics::MetricsRegistry;
+    use ic_registry_subnet_type::SubnetType;
+    use ic_replicated_state::{CanisterState, ReplicatedState};
+    use ic_state_manager::StateManagerImpl;
+    use ic_test_utilities::{
+        consensus::fake::FakeVerifier,
+        mock_time,
+        state::{initial_execution_state, new_canister_state},
+        types::{
+            ids::{canister_test_id, subnet_test_id, user_test_id},
+            messages::RequestBuilder,
+        },
+    };
+    use ic_types::{CanisterId, Cycles, Height};
+    use std::path::Path;
+    use std::sync::Arc;
+
+    fn state_manager(state_root: &Path) -> StateManagerImpl {
+        StateManagerImpl::new(
+            Arc::new(FakeVerifier::new()),
+            subnet_test_id(1),
+            SubnetType::Application,
+            no_op_logger(),
+            &MetricsRegistry::new(),
+            &Config::new(state_root.to_path_buf()),
+            None,
+            ic_types::malicious_flags::MaliciousFlags::default(),
+        )
+    }
+
+    fn canister_state(canister_id: CanisterId) -> CanisterState {
+        let mut canister_state = new_canister_state(
+            canister_id,
+            user_test_id(1).get(),
+            Cycles::new(1 << 36),
+            NumSeconds::from(100_000),
+        );
+        canister_state.execution_state = Some(initial_execution_state());
+        canister_state
+    }
+
+    /// Writes a checkpoint at height 1 containing `canister_state` into a new
+    /// state root in `dir`, and a replica config pointing to it. Returns the
+    /// paths of the state root, the checkpoint and the config.
+    fn setup(dir: &Path, canister_state: CanisterState) -> (PathBuf, PathBuf, PathBuf) {
+        let state_root = dir.join("state");
+        let state_manager = state_manager(&state_root);
+        let (_height, mut state) = state_manager.take_tip();
+        state.put_canister_state(canister_state);
+        state_manager.commit_and_certify(state, Height::new(1), CertificationScope::Full);
+        let checkpoint = state_manager
+            .state_layout()
+            .checkpoint(Height::new(1))
+            .unwrap()
+            .raw_path();
+
+        let config_path = dir.join("ic.json5");
+        std::fs::write(
+            &config_path,
+            format!(
+                "{{ state_manager: {{ state_root: {:?} }} }}",
+                state_root.display().to_string()
+            ),
+        )
+        .unwrap();
+        (state_root, checkpoint, config_path)
+    }
+
+    fn latest_state(state_root: &Path) -> (Height, ReplicatedState) {
+        let state_manager = state_manager(state_root);
+        let state = state_manager.get_latest_state();
+        (state.height(), state.take().as_ref().clone())
+    }
+
+    fn assert_same_canister(imported: &CanisterState, exported: &CanisterState) {
+        assert_eq!(
+            imported.system_state.balance(),
+            exported.system_state.balance()
+        );
+        assert_eq!(
+            imported.system_state.controllers,
+            exported.system_state.controllers
+        );
+        let (imported, exported) = (
+            imported.execution_state.as_ref().unwrap(),
+            exported.execution_state.as_ref().unwrap(),
+        );
+        assert_eq!(imported.wasm_binary, exported.wasm_binary);
+        assert_eq!(imported.exports, exported.exports);
+        assert_eq!(imported.metadata, exported.metadata);
+    }
+
+    #[test]
+    fn exported_canister_can_be_imported_under_the_same_and_a_new_id() {
+        let tmp = tempfile::tempdir().unwrap();
+        let canister_id = canister_test_id(1);
+        let new_canister_id = canister_test_id(2);
+        let exported = canister_state(canister_id);
+        let (state_root, checkpoint, config_path) = setup(tmp.path(), exported.clone());
+
+        let export_dir = tmp.path().join("export");
+        do_export_canister(checkpoint, canister_id.to_string(), export_dir.clone()).unwrap();
+
+        do_import_canister(
+            export_dir.clone(),
+            canister_id.to_string(),
+            config_path.clone(),
+        )
+        .unwrap();
+        let (height, state) = latest_state(&state_root);
+        assert_eq!(height, Height::new(2));
+        assert_eq!(state.canister_states.len(), 1);
+        assert_same_canister(state.canister_state(&canister_id).unwrap(), &exported);
+
+        do_import_canister(export_dir, new_canister_id.to_string(), config_path).unwrap();
+        let (height, state) = latest_state(&state_root);
+        assert_eq!(height, Height::new(3));
+        assert_eq!(state.canister_states.len(), 2);
+        assert_same_canister(state.canister_state(&canister_id).unwrap(), &exported);
+        let imported = state.canister_state(&new_canister_id).unwrap();
+        assert_eq!(imported.canister_id(), new_canister_id);
+        assert_same_canister(imported, &exported);
+    }
+
+    #[test]
+    fn canister_with_messages_cannot_be_imported_under_a_new_id() {
+        let tmp = tempfile::tempdir().unwrap();
+        let canister_id = canister_test_id(1);
+        let mut exported = canister_state(canister_id);
+        exported
+            .push_output_request(
+                Arc::new(
+                    RequestBuilder::new()
+                        .sender(canister_id)
+                        .receiver(canister_test_id(3))
+                        .build(),
+                ),
+                mock_time(),
+            )
+            .unwrap();
+        let (state_root, checkpoint, config_path) = setup(tmp.path(), exported);
+
+        let export_dir = tmp.path().join("export");
+        do_export_canister(checkpoint, canister_id.to_string(), export_dir.clone()).unwrap();
+
+        let err = do_import_canister(
+            export_dir.clone(),
+            canister_test_id(2).to_string(),
+            config_path.clone(),
+        )
+        .unwrap_err();
+        assert!(err.contains(&canister_id.to_string()), "{}", err);
+        assert_eq!(latest_state(&state_root).0, Height::new(1));
+
+        do_import_canister(export_dir, canister_id.to_string(), config_path).unwrap();
+        assert_eq!(latest_state(&state_root).0, Height::new(2));
+    }
+}
@@ -2,8 +2,18 @@
 
 use ic_config::{config_parser::ConfigSource, ConfigOptional};
 use ic_logger::replica_logger::no_op_logger;
-use ic_state_layout::StateLayout;
-use std::path::PathBuf;
+use ic_protobuf::{state::queues::v1 as pb_queues, types::v1 as pb_types};
+use ic_replicated_state::CanisterState;
+use ic_state_layout::{CanisterLayout, ReadPolicy, StateLayout};
+use ic_state_manager::checkpoint::load_canister_state;
+use ic_sys::fs::clone_file;
+use ic_types::{CanisterId, Height};
+use ic_utils::fs::copy_file_sparse;
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 /// Loads the location of the state root from the given `replica` configuration
 /// file.
@@ -24,3 +34,166 @@ pub fn locate_state_root(config_path: PathBuf) -> Result<StateLayout, String> {
 
     Ok(StateLayout::try_new(no_op_logger(), state_root).unwrap())
 }
+
+/// Copies SRC into DST recursively.
+///
+/// Function is not crash-safe. Caller is responsible to follow guidelines
+/// regarding crash-safe I/O.
+pub fn copy_recursively(src: &Path, dst: &Path) -> Result<(), String> {
+    enum CanCloneFiles {
+        Yes,
+        No,
+    }
+    fn go(src: &Path, dst: &Path, can_clone: &mut CanCloneFiles) -> Result<(), String> {
+        let src_metadata = src
+            .metadata()
+            .map_err(|e| format!("failed to get metadata of path {}: {}", src.display(), e))?;
+
+        if src_metadata.is_dir() {
+            let entries = src
+                .read_dir()
+                .map_err(|e| format!("failed to read directory {}: {}", src.display(), e))?;
+
+            fs::create_dir_all(&dst)
+                .map_err(|e| format!("failed to create directory {}: {}", dst.display(), e))?;
+
+            for entry_result in entries {
+                let entry = entry_result.map_err(|e| {
+                    format!("failed to read entry of directory {}: {}", src.display(), e)
+                })?;
+                let dst_entry = dst.join(entry.file_name());
+
+                go(&entry.path(), &dst_entry, can_clone)?;
+            }
+        } else {
+            if let CanCloneFiles::Yes = can_clone {
+                match clone_file(src, dst) {
+                    Ok(_) => return Ok(()),
+                    Err(_) => {
+                        *can_clone = CanCloneFiles::No;
+                    }
+                }
+            }
+
+            copy_file_sparse(src, dst).map_err(|e| {
+                format!(
+                    "Failed to copy {} -> {}: {}",
+                    src.display(),
+                    dst.display(),
+                    e
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+    // We try to clone files first because it's much faster for big files.
+    // If cloning fails (most likely, because SRC and DST are on different file
+    // systems), we fall back to usual copying.
+    let mut can_clone = CanCloneFiles::Yes;
+    go(src, dst, &mut can_clone)
+}
+
+/// Parses the textual representation of a canister ID.
+pub fn parse_canister_id(canister_id: &str) -> Result<CanisterId, String> {
+    CanisterId::from_str(canister_id)
+        .map_err(|e| format!("failed to parse canister ID {}: {}", canister_id, e))
+}
+
+/// Loads the state of canister `canister_id` stored in `canister_layout` to
+/// make sure it is complete and can be decoded.
+pub fn load_canister<P: ReadPolicy>(
+    canister_layout: &CanisterLayout<P>,
+    canister_id: &CanisterId,
+    height: Height,
+) -> Result<CanisterState, String> {
+    load_canister_state(canister_layout, canister_id, height)
+        .map(|(canister_state, _metrics)| canister_state)
+        .map_err(|e| {
+            format!(
+                "failed to load the state of canister {} from {}: {}",
+                canister_id,
+                canister_layout.raw_path().display(),
+                e
+            )
+        })
+}
+
+/// Returns the IDs under which the messages in the queues of the canister
+/// stored in `canister_layout` refer to the canister itself, i.e., the
+/// receivers of ingress messages and incoming requests, the originators of
+/// incoming responses, and the senders and respondents of outgoing messages.
+///
+/// The ID of a canister is not stored in its directory, so these are the only
+/// traces of the ID a canister was exported under.
+pub fn own_ids_in_queues<P: ReadPolicy>(
+    canister_layout: &CanisterLayout<P>,
+) -> Result<BTreeSet<CanisterId>, String> {
+    use pb_queues::request_or_response::R;
+
+    let queues = canister_layout.queues().deserialize().map_err(|e| {
+        format!(
+            "failed to read the queues of {}: {}",
+            canister_layout.raw_path().display(),
+            e
+        )
+    })?;
+
+    let mut ids = Vec::<Option<pb_types::CanisterId>>::new();
+    ids.extend(
+        queues
+            .ingress_queue
+            .into_iter()
+            .map(|ingress| ingress.receiver),
+    );
+    let messages = |entries: Vec<pb_queues::QueueEntry>| {
+        entries
+            .into_iter()
+            .filter_map(|entry| entry.queue)
+            .flat_map(|queue| queue.queue)
+            .filter_map(|msg| msg.r)
+    };
+    ids.extend(messages(queues.input_queues).map(|msg| match msg {
+        R::Request(request) => request.receiver,
+        R::Response(response) => response.originator,
+    }));
+    ids.extend(messages(queues.output_queues).map(|msg| match msg {
+        R::Request(request) => request.sender,
+        R::Response(response) => response.respondent,
+    }));
+
+    ids.into_iter()
+        .flatten()
+        .map(|id| {
+            CanisterId::try_from(id)
+                .map_err(|e| format!("failed to decode canister ID in queues: {}", e))
+        })
+        .collect()
+}
+
+/// Prints a short summary of the given canister state.
+pub fn print_canister_summary(canister_state: &CanisterState) {
+    println!("Canister ID:   {}", canister_state.canister_id());
+    println!(
+        "Status:        {}",
+        canister_state.system_state.status_string()
+    );
+    println!("Cycles:        {}", canister_state.system_state.balance());
+    match &canister_state.execution_state {
+        Some(execution_state) => {
+            println!(
+                "Module hash:   {}",
+                hex::encode(execution_state.wasm_binary.binary.module_hash())
+            );
+            println!(
+                "Heap size:     {} pages",
+                execution_state.wasm_memory.size.get()
+            );
+            println!(
+                "Stable memory: {} pages",
+                execution_state.stable_memory.size.get()
+            );
+        }
+        None => println!("Module hash:   none (empty canister)"),
+    }
+}
@@ -2,7 +2,8 @@
 //!
 //! A command-line tool to manage Internet Computer replicated states (decode
 //! persisted state files, diff checkpoints, compute partial state hashes and
-//! checkpoint manifests, import state trees).
+//! checkpoint manifests, import state trees, export and import the states of
+//! individual canisters).
 
 use clap::Parser;
 use std::path::PathBuf;
@@ -25,6 +26,39 @@ enum Opt {
         path: PathBuf,
     },
 
+    /// Exports the state of a single canister from a checkpoint.
+    #[clap(name = "export-canister")]
+    ExportCanister {
+        /// Path to a checkpoint.
+        #[clap(long = "state")]
+        path: PathBuf,
+
+        /// ID of the canister to export.
+        #[clap(long = "canister")]
+        canister_id: String,
+
+        /// Directory to export the canister state to. Must not exist.
+        #[clap(long = "output", short = 'o')]
+        output: PathBuf,
+    },
+
+    /// Imports the state of a single canister exported by `export-canister`
+    /// into a new checkpoint on top of the latest one.
+    #[clap(name = "import-canister")]
+    ImportCanister {
+        /// Path to the exported canister state.
+        #[clap(long = "canister-state")]
+        canister_dir: PathBuf,
+
+        /// ID to import the canister as.
+        #[clap(long = "canister")]
+        canister_id: String,
+
+        /// Path to the replica configuration (ic.json).
+        #[clap(long = "config")]
+        config: PathBuf,
+    },
+
     /// Imports replicated state from an external location.
     #[clap(name = "import")]
     ImportState {
@@ -83,6 +117,16 @@ fn main() {
     let result = match opt {
         Opt::CDiff { path_a, path_b } => commands::cdiff::do_diff(path_a, path_b),
         Opt::CHash { path } => commands::chash::do_hash(path),
+        Opt::ExportCanister {
+            path,
+            canister_id,
+            output,
+        } => commands::export_canister::do_export_canister(path, canister_id, output),
+        Opt::ImportCanister {
+            canister_dir,
+            canister_id,
+            config,
+        } => commands::import_canister::do_import_canister(canister_dir, canister_id, config),
         Opt::ImportState {
             state,
             config,
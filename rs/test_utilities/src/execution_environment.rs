@@ -1401,6 +1401,7 @@ impl ExecutionTestBuilder {
                         NodeTopology {
                             ip_address: "fake-ip-address".to_string(),
                             http_port: 1234,
+                            public_key: vec![],
                         },
                     );
                 }
@@ -1179,7 +1179,8 @@ pub fn install_nns_canisters(
         let agent = InternalAgent::new(
             url,
             Sender::from_keypair(&ic_test_identity::TEST_IDENTITY_KEYPAIR),
-        );
+        )
+        .with_insecure_skip_certificate_verification();
         let runtime = Runtime::Remote(RemoteTestRuntime { agent });
 
         NnsCanisters::set_up(&runtime, init_payloads.build()).await;
@@ -42,7 +42,8 @@ fn test(handle: IcHandle, ctx: &ic_fondue::pot::Context) {
             agent: Agent::new(
                 endpoint.url.clone(),
                 Sender::from_keypair(&ic_test_identity::TEST_IDENTITY_KEYPAIR),
-            ),
+            )
+            .with_insecure_skip_certificate_verification(),
         });
 
         let agent = assert_create_agent(endpoint.url.as_str()).await;
@@ -802,7 +802,8 @@ struct TestAgent {
 
 impl TestAgent {
     pub fn new(ic_url: &Url, agent_client: &HttpClient) -> Self {
-        let agent = Agent::new_with_client(agent_client.clone(), ic_url.clone(), Sender::Anonymous);
+        let agent = Agent::new_with_client(agent_client.clone(), ic_url.clone(), Sender::Anonymous)
+            .with_insecure_skip_certificate_verification();
         Self { agent }
     }
 
@@ -902,7 +903,8 @@ impl UserHandle {
             http_client.clone(),
             ic_url.clone(),
             Sender::from_keypair(user_keypair),
-        );
+        )
+        .with_insecure_skip_certificate_verification();
         Self {
             agent,
             ledger_id,
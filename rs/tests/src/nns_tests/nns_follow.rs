@@ -31,6 +31,7 @@ use crate::util::{get_random_nns_node_endpoint, runtime_from_url};
 use crate::driver::ic::InternetComputer;
 use ic_fondue::ic_manager::IcHandle;
 
+use ic_nns_governance::nns_function_payload::UpgradeRootProposalPayload;
 use ic_nns_governance::pb::v1::{
     governance_error::ErrorType,
     manage_neuron::{Command, Follow, NeuronIdOrSubaccount, RegisterVote, Split},
@@ -39,6 +40,7 @@ use ic_nns_governance::pb::v1::{
 };
 
 use crate::nns::NnsExt;
+use candid::Encode;
 use canister_test::Canister;
 use dfn_candid::{candid, candid_one};
 use ic_nns_test_utils::ids::{TEST_NEURON_1_ID, TEST_NEURON_2_ID, TEST_NEURON_3_ID};
@@ -579,7 +581,14 @@ async fn submit_proposal(
         url: "".to_string(),
         action: Some(proposal::Action::ExecuteNnsFunction(ExecuteNnsFunction {
             nns_function: update_type as i32,
-            payload: Vec::new(),
+            // Governance rejects payloads that do not decode into the argument of
+            // the NNS function, so this only supports root upgrades.
+            payload: Encode!(&UpgradeRootProposalPayload {
+                wasm_module: Vec::new(),
+                module_arg: Vec::new(),
+                stop_upgrade_start: true,
+            })
+            .unwrap(),
         })),
     };
 
@@ -66,7 +66,8 @@ pub fn runtime_from_url(url: Url) -> Runtime {
     let agent = DeprecatedAgent::new(
         url,
         Sender::from_keypair(&ic_test_identity::TEST_IDENTITY_KEYPAIR),
-    );
+    )
+    .with_insecure_skip_certificate_verification();
     Runtime::Remote(RemoteTestRuntime { agent })
 }
 
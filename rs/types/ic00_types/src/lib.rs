@@ -692,7 +692,7 @@ impl SetupInitialDKGResponse {
 
 /// Types of curves that can be used for ECDSA signing.
 /// ```text
-/// (variant { secp256k1; })
+/// (variant { secp256k1; secp256r1; })
 /// ```
 #[derive(
     CandidType, Copy, Clone, Debug, PartialOrd, Ord, PartialEq, Eq, Serialize, Deserialize, Hash,
@@ -700,6 +700,8 @@ impl SetupInitialDKGResponse {
 pub enum EcdsaCurve {
     #[serde(rename = "secp256k1")]
     Secp256k1,
+    #[serde(rename = "secp256r1")]
+    Secp256r1,
 }
 
 impl TryFrom<pb_registry_crypto::EcdsaCurve> for EcdsaCurve {
@@ -708,6 +710,7 @@ impl TryFrom<pb_registry_crypto::EcdsaCurve> for EcdsaCurve {
     fn try_from(item: pb_registry_crypto::EcdsaCurve) -> Result<Self, Self::Error> {
         match item {
             pb_registry_crypto::EcdsaCurve::Secp256k1 => Ok(EcdsaCurve::Secp256k1),
+            pb_registry_crypto::EcdsaCurve::Secp256r1 => Ok(EcdsaCurve::Secp256r1),
             pb_registry_crypto::EcdsaCurve::Unspecified => Err(ProxyDecodeError::ValueOutOfRange {
                 typ: "EcdsaCurve",
                 err: format!("Unable to convert {:?} to an EcdsaCurve", item),
@@ -720,6 +723,7 @@ impl From<EcdsaCurve> for pb_registry_crypto::EcdsaCurve {
     fn from(item: EcdsaCurve) -> Self {
         match item {
             EcdsaCurve::Secp256k1 => pb_registry_crypto::EcdsaCurve::Secp256k1,
+            EcdsaCurve::Secp256r1 => pb_registry_crypto::EcdsaCurve::Secp256r1,
         }
     }
 }
@@ -736,6 +740,7 @@ impl FromStr for EcdsaCurve {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "Secp256k1" => Ok(Self::Secp256k1),
+            "Secp256r1" => Ok(Self::Secp256r1),
             _ => Err(format!("{} is not a recognized ECDSA curve", s)),
         }
     }
@@ -743,12 +748,13 @@ impl FromStr for EcdsaCurve {
 
 #[test]
 fn ecdsa_curve_round_trip() {
-    assert_eq!(
-        format!("{}", EcdsaCurve::Secp256k1)
-            .parse::<EcdsaCurve>()
-            .unwrap(),
-        EcdsaCurve::Secp256k1
-    );
+    for curve in [EcdsaCurve::Secp256k1, EcdsaCurve::Secp256r1] {
+        assert_eq!(format!("{}", curve).parse::<EcdsaCurve>().unwrap(), curve);
+        assert_eq!(
+            EcdsaCurve::try_from(pb_registry_crypto::EcdsaCurve::from(curve)).unwrap(),
+            curve
+        );
+    }
 }
 
 /// Unique identifier for a key that can be used for ECDSA signatures. The name
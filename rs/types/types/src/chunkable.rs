@@ -48,6 +48,9 @@ const CHUNKID_UNIT_CHUNK: u32 = 0;
 pub enum ArtifactChunkData {
     UnitChunkData(Artifact), // Unit chunk data has 1:1 mapping with real artifacts
     SemiStructuredChunkData(Vec<u8>),
+    /// A chunk in an encoding of the artifact, see
+    /// [`ChunkableArtifact::get_encoded_chunk`].
+    EncodedChunkData(Vec<u8>),
 }
 
 /// An artifact chunk.
@@ -104,6 +107,13 @@ pub trait ChunkableArtifact {
     /// The chunk ID for single-chunked artifacts must be
     /// [`CHUNKID_UNIT_CHUNK`].
     fn get_chunk(self: Box<Self>, chunk_id: ChunkId) -> Option<ArtifactChunk>;
+
+    /// Retrieves the artifact chunk with the given ID for a peer that accepts
+    /// [`ArtifactChunkData::EncodedChunkData`]. Artifacts without an encoding
+    /// return the same chunk as [`Self::get_chunk`].
+    fn get_encoded_chunk(self: Box<Self>, chunk_id: ChunkId) -> Option<ArtifactChunk> {
+        self.get_chunk(chunk_id)
+    }
 }
 
 macro_rules! chunkable_artifact_impl {
@@ -140,10 +150,11 @@ chunkable_artifact_impl! {CanisterHttpResponseShare, |self|
     ArtifactChunkData::UnitChunkData(Artifact::CanisterHttpMessage(*self))
 }
 
-impl ChunkableArtifact for StateSyncMessage {
-    fn get_chunk(self: Box<Self>, chunk_id: ChunkId) -> Option<ArtifactChunk> {
-        let buf = if chunk_id == crate::state_sync::MANIFEST_CHUNK {
-            crate::state_sync::encode_manifest(&self.manifest)
+impl StateSyncMessage {
+    /// Reads the chunk with the given ID from the checkpoint.
+    fn read_chunk(&self, chunk_id: ChunkId) -> Option<Vec<u8>> {
+        if chunk_id == crate::state_sync::MANIFEST_CHUNK {
+            Some(crate::state_sync::encode_manifest(&self.manifest))
         } else if let Some(chunk) = self
             .manifest
             .chunk_table
@@ -154,16 +165,34 @@ impl ChunkableArtifact for StateSyncMessage {
                 .checkpoint_root
                 .join(&self.manifest.file_table[chunk.file_index as usize].relative_path);
             let get_state_sync_chunk = self.get_state_sync_chunk.unwrap();
-            get_state_sync_chunk(path, chunk.offset, chunk.size_bytes).ok()?
+            get_state_sync_chunk(path, chunk.offset, chunk.size_bytes).ok()
         } else {
-            return None;
-        };
+            None
+        }
+    }
+}
 
+impl ChunkableArtifact for StateSyncMessage {
+    fn get_chunk(self: Box<Self>, chunk_id: ChunkId) -> Option<ArtifactChunk> {
+        let buf = self.read_chunk(chunk_id)?;
         Some(ArtifactChunk::new(
             chunk_id,
             ArtifactChunkData::SemiStructuredChunkData(buf),
         ))
     }
+
+    /// Data chunks are compressed using [`crate::state_sync::encode_chunk`],
+    /// the manifest is always sent as is.
+    fn get_encoded_chunk(self: Box<Self>, chunk_id: ChunkId) -> Option<ArtifactChunk> {
+        if chunk_id == crate::state_sync::MANIFEST_CHUNK {
+            return self.get_chunk(chunk_id);
+        }
+        let buf = self.read_chunk(chunk_id)?;
+        Some(ArtifactChunk::new(
+            chunk_id,
+            ArtifactChunkData::EncodedChunkData(crate::state_sync::encode_chunk(buf)),
+        ))
+    }
 }
 
 // End repetition
@@ -218,6 +247,9 @@ impl From<ArtifactChunk> for pb::ArtifactChunk {
             ArtifactChunkData::SemiStructuredChunkData(chunk_data) => {
                 pb::artifact_chunk::Data::Chunk(chunk_data)
             }
+            ArtifactChunkData::EncodedChunkData(chunk_data) => {
+                pb::artifact_chunk::Data::EncodedChunk(chunk_data)
+            }
         };
         Self {
             witnesses: chunk
@@ -254,6 +286,7 @@ impl TryFrom<pb::ArtifactChunk> for ArtifactChunk {
                     ArtifactChunkData::UnitChunkData(deserialize(&a)?)
                 }
                 pb::artifact_chunk::Data::Chunk(d) => ArtifactChunkData::SemiStructuredChunkData(d),
+                pb::artifact_chunk::Data::EncodedChunk(d) => ArtifactChunkData::EncodedChunkData(d),
             },
         };
         Ok(Self {
@@ -156,6 +156,7 @@ pub enum AlgorithmId {
     RsaSha256 = 14,
     ThresholdEcdsaSecp256k1 = 15,
     MegaSecp256k1 = 16,
+    ThresholdEcdsaSecp256r1 = 18,
 }
 
 impl From<CspThresholdSigPublicKey> for AlgorithmId {
@@ -206,6 +207,7 @@ impl From<i32> for AlgorithmId {
             14 => AlgorithmId::RsaSha256,
             15 => AlgorithmId::ThresholdEcdsaSecp256k1,
             16 => AlgorithmId::MegaSecp256k1,
+            18 => AlgorithmId::ThresholdEcdsaSecp256r1,
             _ => AlgorithmId::Placeholder,
         }
     }
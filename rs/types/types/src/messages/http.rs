@@ -9,7 +9,7 @@ use crate::{
         message_id::hash_of_map, MessageId, ReadState, SignedIngressContent, UserQuery,
         UserSignature,
     },
-    Time, UserId,
+    Height, Time, UserId,
 };
 use ic_base_types::{CanisterId, CanisterIdError, PrincipalId};
 use ic_crypto_tree_hash::{MixedHashTree, Path};
@@ -17,7 +17,12 @@ use maplit::btreemap;
 #[cfg(test)]
 use proptest_derive::Arbitrary;
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeSet, convert::TryFrom, error::Error, fmt};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+    error::Error,
+    fmt,
+};
 
 /// Describes the fields of a canister update call as defined in
 /// https://sdk.dfinity.org/docs/interface-spec/index.html#api-update.
@@ -539,6 +544,7 @@ pub enum RawHttpRequestVal {
     String(String),
     U64(u64),
     Array(Vec<RawHttpRequestVal>),
+    Map(BTreeMap<String, RawHttpRequestVal>),
 }
 
 /// The reply to an update call.
@@ -570,6 +576,71 @@ pub struct HttpQueryResponseReply {
     pub arg: Blob,
 }
 
+/// A query response together with the signatures of the replica nodes that
+/// produced it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HttpSignedQueryResponse {
+    #[serde(flatten)]
+    pub response: HttpQueryResponse,
+    pub signatures: Vec<NodeSignature>,
+}
+
+/// The signature of a replica node over a [`QueryResponseHash`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NodeSignature {
+    /// The time of signing, in nanoseconds since the UNIX epoch.
+    pub timestamp: u64,
+    pub signature: Blob,
+    /// The id of the signing node.
+    pub identity: Blob,
+}
+
+/// The representation-independent hash of a query response, the id of the
+/// query it answers and the time of signing. This is what replica nodes sign
+/// to vouch for a query response.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct QueryResponseHash([u8; 32]);
+
+impl QueryResponseHash {
+    pub fn new(response: &HttpQueryResponse, request_id: &MessageId, timestamp: Time) -> Self {
+        use RawHttpRequestVal::*;
+        let mut map = btreemap! {
+            "request_id".to_string() => Bytes(request_id.as_bytes().to_vec()),
+            "timestamp".to_string() => U64(timestamp.as_nanos_since_unix_epoch()),
+        };
+        match response {
+            HttpQueryResponse::Replied { reply } => {
+                map.insert("status".to_string(), String("replied".to_string()));
+                map.insert(
+                    "reply".to_string(),
+                    Map(btreemap! {
+                        "arg".to_string() => Bytes(reply.arg.0.clone()),
+                    }),
+                );
+            }
+            HttpQueryResponse::Rejected {
+                reject_code,
+                reject_message,
+            } => {
+                map.insert("status".to_string(), String("rejected".to_string()));
+                map.insert("reject_code".to_string(), U64(*reject_code));
+                map.insert("reject_message".to_string(), String(reject_message.clone()));
+            }
+        }
+        Self(hash_of_map(&map))
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl SignedBytesWithoutDomainSeparator for QueryResponseHash {
+    fn as_signed_bytes_without_domain_separator(&self) -> Vec<u8> {
+        self.0.to_vec()
+    }
+}
+
 /// The response to a `read_state` request.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct HttpReadStateResponse {
@@ -617,6 +688,9 @@ pub struct HttpStatusResponse {
     pub impl_hash: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub replica_health_status: Option<ReplicaHealthStatus>,
+    /// The height of the latest state certified by the replica.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certified_height: Option<Height>,
 }
 
 #[cfg(test)]
@@ -682,6 +756,71 @@ mod test {
         );
     }
 
+    #[test]
+    fn encoding_signed_query_response() {
+        assert_cbor_ser_equal(
+            &HttpSignedQueryResponse {
+                response: HttpQueryResponse::Replied {
+                    reply: HttpQueryResponseReply {
+                        arg: Blob(b"some_bytes".to_vec()),
+                    },
+                },
+                signatures: vec![NodeSignature {
+                    timestamp: 1,
+                    signature: Blob(b"sig".to_vec()),
+                    identity: Blob(b"node".to_vec()),
+                }],
+            },
+            Value::Map(btreemap! {
+                text("status") => text("replied"),
+                text("reply") => Value::Map(btreemap!{
+                    text("arg") => bytes(b"some_bytes")
+                }),
+                text("signatures") => Value::Array(vec![Value::Map(btreemap!{
+                    text("timestamp") => int(1),
+                    text("signature") => bytes(b"sig"),
+                    text("identity") => bytes(b"node"),
+                })]),
+            }),
+        );
+    }
+
+    #[test]
+    fn query_response_hash_covers_all_fields() {
+        let reply = HttpQueryResponse::Replied {
+            reply: HttpQueryResponseReply {
+                arg: Blob(b"some_bytes".to_vec()),
+            },
+        };
+        let reject = HttpQueryResponse::Rejected {
+            reject_code: 1,
+            reject_message: "system error".to_string(),
+        };
+        let request_id = MessageId::from([1; 32]);
+        let hash = QueryResponseHash::new(&reply, &request_id, UNIX_EPOCH);
+
+        assert_eq!(
+            hash,
+            QueryResponseHash::new(&reply, &request_id, UNIX_EPOCH)
+        );
+        assert_ne!(
+            hash,
+            QueryResponseHash::new(&reject, &request_id, UNIX_EPOCH)
+        );
+        assert_ne!(
+            hash,
+            QueryResponseHash::new(&reply, &MessageId::from([2; 32]), UNIX_EPOCH)
+        );
+        assert_ne!(
+            hash,
+            QueryResponseHash::new(
+                &reply,
+                &request_id,
+                UNIX_EPOCH + std::time::Duration::from_nanos(1)
+            )
+        );
+    }
+
     #[test]
     fn encoding_status_without_root_key() {
         assert_cbor_ser_equal(
@@ -691,6 +830,7 @@ mod test {
                 impl_version: Some("0.0".to_string()),
                 impl_hash: None,
                 replica_health_status: Some(ReplicaHealthStatus::Starting),
+                certified_height: None,
             },
             Value::Map(btreemap! {
                 text("ic_api_version") => text("foobar"),
@@ -709,6 +849,7 @@ mod test {
                 impl_version: Some("0.0".to_string()),
                 impl_hash: None,
                 replica_health_status: Some(ReplicaHealthStatus::Healthy),
+                certified_height: None,
             },
             Value::Map(btreemap! {
                 text("ic_api_version") => text("foobar"),
@@ -719,6 +860,26 @@ mod test {
         );
     }
 
+    #[test]
+    fn encoding_status_with_certified_height() {
+        assert_cbor_ser_equal(
+            &HttpStatusResponse {
+                ic_api_version: "foobar".to_string(),
+                root_key: None,
+                impl_version: Some("0.0".to_string()),
+                impl_hash: None,
+                replica_health_status: Some(ReplicaHealthStatus::Healthy),
+                certified_height: Some(Height::from(42)),
+            },
+            Value::Map(btreemap! {
+                text("ic_api_version") => text("foobar"),
+                text("impl_version") => text("0.0"),
+                text("replica_health_status") => text("healthy"),
+                text("certified_height") => int(42),
+            }),
+        );
+    }
+
     #[test]
     fn encoding_status_without_health_status() {
         assert_cbor_ser_equal(
@@ -728,6 +889,7 @@ mod test {
                 impl_version: Some("0.0".to_string()),
                 impl_hash: None,
                 replica_health_status: None,
+                certified_height: None,
             },
             Value::Map(btreemap! {
                 text("ic_api_version") => text("foobar"),
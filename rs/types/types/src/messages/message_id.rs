@@ -155,6 +155,7 @@ fn hash_val(val: RawHttpRequestVal) -> Vec<u8> {
         RawHttpRequestVal::Bytes(bytes) => hash_bytes(bytes),
         RawHttpRequestVal::U64(integer) => hash_u64(integer),
         RawHttpRequestVal::Array(elements) => hash_array(elements),
+        RawHttpRequestVal::Map(map) => hash_of_map(&map).to_vec(),
     }
 }
 
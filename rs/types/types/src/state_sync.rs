@@ -233,3 +233,85 @@ pub fn decode_manifest(bytes: &[u8]) -> Result<Manifest, String> {
         .try_into()
         .map_err(|err| format!("failed to convert Manifest proto into an object: {}", err))
 }
+
+/// The encodings of a chunk payload, stored in its first byte.
+const CHUNK_ENCODING_IDENTITY: u8 = 0;
+const CHUNK_ENCODING_DEFLATE: u8 = 1;
+
+/// Encodes a data chunk for transfer to a peer that accepts encoded chunks.
+/// The chunk is compressed unless compression does not make it smaller, e.g.,
+/// for chunks of already compressed data.
+pub fn encode_chunk(raw: Vec<u8>) -> Vec<u8> {
+    use flate2::{write::DeflateEncoder, Compression};
+    use std::io::Write;
+
+    let mut encoder = DeflateEncoder::new(vec![CHUNK_ENCODING_DEFLATE], Compression::fast());
+    let compressed = encoder.write_all(&raw).and_then(|()| encoder.finish()).ok();
+    match compressed {
+        Some(compressed) if compressed.len() < raw.len() + 1 => compressed,
+        _ => {
+            let mut buf = Vec::with_capacity(raw.len() + 1);
+            buf.push(CHUNK_ENCODING_IDENTITY);
+            buf.extend_from_slice(&raw);
+            buf
+        }
+    }
+}
+
+/// Decodes a chunk produced by [`encode_chunk`]. Fails if the payload is
+/// malformed or decodes to more than `max_len` bytes.
+pub fn decode_chunk(encoded: &[u8], max_len: usize) -> Result<Vec<u8>, String> {
+    use std::io::Read;
+
+    let (encoding, payload) = encoded
+        .split_first()
+        .ok_or_else(|| "empty encoded chunk".to_string())?;
+    let raw = match *encoding {
+        CHUNK_ENCODING_IDENTITY => payload.to_vec(),
+        CHUNK_ENCODING_DEFLATE => {
+            let mut raw = Vec::new();
+            flate2::read::DeflateDecoder::new(payload)
+                .take(max_len as u64 + 1)
+                .read_to_end(&mut raw)
+                .map_err(|err| format!("failed to decompress chunk: {}", err))?;
+            raw
+        }
+        other => return Err(format!("unknown chunk encoding {}", other)),
+    };
+    if raw.len() > max_len {
+        return Err(format!(
+            "decoded chunk exceeds the maximum size of {} bytes",
+            max_len
+        ));
+    }
+    Ok(raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compressible_chunks_are_compressed() {
+        let raw = vec![7; 1 << 20];
+        let encoded = encode_chunk(raw.clone());
+        assert!(encoded.len() < raw.len() / 100);
+        assert_eq!(decode_chunk(&encoded, raw.len()), Ok(raw));
+    }
+
+    #[test]
+    fn incompressible_chunks_are_sent_as_is() {
+        let raw: Vec<u8> = (0..=255).collect();
+        let encoded = encode_chunk(raw.clone());
+        assert_eq!(encoded.len(), raw.len() + 1);
+        assert_eq!(decode_chunk(&encoded, raw.len()), Ok(raw));
+    }
+
+    #[test]
+    fn decoding_is_bounded_by_max_len() {
+        let encoded = encode_chunk(vec![0; 1 << 20]);
+        assert!(decode_chunk(&encoded, 1 << 10).is_err());
+        assert!(decode_chunk(&[], 1 << 10).is_err());
+        assert!(decode_chunk(&[CHUNK_ENCODING_DEFLATE + 1, 0], 1 << 10).is_err());
+    }
+}
@@ -65,7 +65,8 @@ pub(crate) async fn create_canister(
     // nonce, due to the presence of expiry_time. Therefore this function
     // will create a NEW canister id every time it is invoked.
     let agent = Agent::new_with_client(http_client, Url::parse(url).unwrap(), agent_sender)
-        .with_ingress_timeout(Duration::from_secs(5 * 60));
+        .with_ingress_timeout(Duration::from_secs(5 * 60))
+        .with_insecure_skip_certificate_verification();
 
     debug!("Create canister with agent: {:?}", agent);
     let creation_result = agent
@@ -108,7 +109,8 @@ pub(crate) async fn install_canister(
     wasm_file_path: Option<&Path>,
 ) -> Result<(), String> {
     let agent = Agent::new_with_client(http_client, Url::parse(url).unwrap(), agent_sender)
-        .with_ingress_timeout(Duration::from_secs(5 * 60));
+        .with_ingress_timeout(Duration::from_secs(5 * 60))
+        .with_insecure_skip_certificate_verification();
 
     let bytes = if let Some(wasm_file_path) = wasm_file_path {
         // Buffer to store bytes of the canister code
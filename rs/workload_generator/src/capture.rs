@@ -0,0 +1,468 @@
+//! Captured traffic that can be replayed against an IC.
+//!
+//! A capture is a JSON Lines file with one request per line, e.g.:
+//!
+//! ```json
+//! {"timestamp_ms": 1650000000000, "endpoint": "query", "canister_id": "rwlgt-iiaaa-aaaaa-aaaaa-cai", "method": "read", "payload": "", "sender": "2vxsx-fae", "reply": "01000000"}
+//! {"timestamp_ms": 1650000000250, "envelope": "d9d9f7a167636f6e74656e74..."}
+//! ```
+//!
+//! A request is given either by its fields or by `envelope`, the hex encoded
+//! CBOR body of the original `call` or `query` request, i.e. the bytes that
+//! were sent to `/api/v2/canister/<id>/call` or `.../query`. The optional
+//! `reply` is the hex encoded reply the request originally got; when present,
+//! replies of the replay are compared against it.
+use crate::scenario::CallKind;
+use ic_types::{
+    messages::{HttpCallContent, HttpQueryContent, HttpRequestEnvelope, SignedRequestBytes},
+    CanisterId, PrincipalId,
+};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    fmt,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    str::FromStr,
+    time::Duration,
+};
+
+/// A single request of a capture.
+#[derive(Clone, Debug)]
+pub struct CapturedRequest {
+    /// Milliseconds since the UNIX epoch at which the request was received.
+    pub timestamp_ms: u64,
+    pub endpoint: CallKind,
+    pub canister_id: CanisterId,
+    pub method: String,
+    pub payload: Vec<u8>,
+    /// The original sender. Replayed requests are signed by the workload
+    /// generator's identity instead, so this is only used for reporting.
+    pub sender: PrincipalId,
+    pub reply: Option<Vec<u8>>,
+}
+
+/// A line of a capture file, as written.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CaptureRecord {
+    timestamp_ms: u64,
+    endpoint: Option<CallKind>,
+    canister_id: Option<String>,
+    method: Option<String>,
+    payload: Option<String>,
+    sender: Option<String>,
+    envelope: Option<String>,
+    reply: Option<String>,
+}
+
+impl TryFrom<CaptureRecord> for CapturedRequest {
+    type Error = String;
+
+    fn try_from(record: CaptureRecord) -> Result<Self, Self::Error> {
+        let reply = record.reply.as_deref().map(decode_hex).transpose()?;
+        if let Some(envelope) = record.envelope {
+            return CapturedRequest::from_envelope(
+                record.timestamp_ms,
+                record.endpoint,
+                &decode_hex(&envelope)?,
+            )
+            .map(|request| CapturedRequest { reply, ..request });
+        }
+
+        let missing = |field: &str| format!("Missing field `{}` or `envelope`", field);
+        let canister_id = record.canister_id.ok_or_else(|| missing("canister_id"))?;
+        let canister_id = PrincipalId::from_str(&canister_id)
+            .map_err(|e| format!("Invalid canister id '{}': {}", canister_id, e))
+            .and_then(|principal_id| {
+                CanisterId::try_from(principal_id)
+                    .map_err(|e| format!("Invalid canister id '{}': {:?}", canister_id, e))
+            })?;
+        let sender = match record.sender {
+            Some(sender) => PrincipalId::from_str(&sender)
+                .map_err(|e| format!("Invalid sender '{}': {}", sender, e))?,
+            None => PrincipalId::new_anonymous(),
+        };
+        Ok(CapturedRequest {
+            timestamp_ms: record.timestamp_ms,
+            endpoint: record.endpoint.ok_or_else(|| missing("endpoint"))?,
+            canister_id,
+            method: record.method.ok_or_else(|| missing("method"))?,
+            payload: decode_hex(record.payload.as_deref().unwrap_or(""))?,
+            sender,
+            reply,
+        })
+    }
+}
+
+impl CapturedRequest {
+    /// Extracts the request from the CBOR body of a `call` or `query` request.
+    /// The endpoint is inferred from the request type of the envelope if not
+    /// given.
+    pub fn from_envelope(
+        timestamp_ms: u64,
+        endpoint: Option<CallKind>,
+        envelope: &[u8],
+    ) -> Result<Self, String> {
+        let bytes = SignedRequestBytes::from(envelope.to_vec());
+        let endpoint = match endpoint {
+            Some(endpoint) => endpoint,
+            None if HttpRequestEnvelope::<HttpCallContent>::try_from(&bytes).is_ok() => {
+                CallKind::Update
+            }
+            None => CallKind::Query,
+        };
+        let (canister_id, method, payload, sender) = match endpoint {
+            CallKind::Update => {
+                let HttpCallContent::Call { update } =
+                    HttpRequestEnvelope::<HttpCallContent>::try_from(&bytes)
+                        .map_err(|e| format!("Invalid call envelope: {}", e))?
+                        .content;
+                (
+                    update.canister_id,
+                    update.method_name,
+                    update.arg,
+                    update.sender,
+                )
+            }
+            CallKind::Query => {
+                let HttpQueryContent::Query { query } =
+                    HttpRequestEnvelope::<HttpQueryContent>::try_from(&bytes)
+                        .map_err(|e| format!("Invalid query envelope: {}", e))?
+                        .content;
+                (
+                    query.canister_id,
+                    query.method_name,
+                    query.arg,
+                    query.sender,
+                )
+            }
+        };
+        Ok(CapturedRequest {
+            timestamp_ms,
+            endpoint,
+            canister_id: CanisterId::try_from(canister_id.0.as_slice())
+                .map_err(|e| format!("Invalid canister id in envelope: {:?}", e))?,
+            method,
+            payload: payload.0,
+            sender: PrincipalId::try_from(sender.0.as_slice())
+                .map_err(|e| format!("Invalid sender in envelope: {}", e))?,
+            reply: None,
+        })
+    }
+}
+
+/// A capture, with requests ordered by the time they were received.
+pub struct Capture {
+    pub requests: Vec<CapturedRequest>,
+}
+
+impl Capture {
+    /// Reads the capture in the given JSON Lines file. Empty lines are
+    /// skipped.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file = File::open(path)
+            .map_err(|e| format!("Failed to open capture file {}: {}", path.display(), e))?;
+        let mut requests = vec![];
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let request = serde_json::from_str::<CaptureRecord>(&line)
+                .map_err(|e| e.to_string())
+                .and_then(CapturedRequest::try_from)
+                .map_err(|e| format!("{}:{}: {}", path.display(), index + 1, e))?;
+            requests.push(request);
+        }
+        if requests.is_empty() {
+            return Err(format!("Capture file {} is empty", path.display()));
+        }
+        requests.sort_by_key(|request| request.timestamp_ms);
+        Ok(Capture { requests })
+    }
+
+    /// The time since the start of the replay at which the `n`-th request is
+    /// issued. A `time_scale` of 2 replays the capture twice as fast.
+    pub fn offset(&self, n: usize, time_scale: f64) -> Duration {
+        let elapsed_ms = self.requests[n].timestamp_ms - self.requests[0].timestamp_ms;
+        Duration::from_millis(elapsed_ms).div_f64(time_scale)
+    }
+
+    /// The distinct canister methods of the capture, by which the replay is
+    /// reported, and the index of the method of each request.
+    pub fn methods(&self) -> (Vec<String>, Vec<usize>) {
+        let mut names: Vec<String> = vec![];
+        let mut index_by_name = HashMap::new();
+        let indices = self
+            .requests
+            .iter()
+            .map(|request| {
+                let name = format!("{}.{}", request.canister_id, request.method);
+                *index_by_name.entry(name.clone()).or_insert_with(|| {
+                    names.push(name);
+                    names.len() - 1
+                })
+            })
+            .collect();
+        (names, indices)
+    }
+}
+
+/// A replayed request whose reply differs from the captured one.
+pub struct ReplyDivergence {
+    pub request: CapturedRequest,
+    /// The reply of the replay, `None` if the request failed or was rejected.
+    pub actual: Option<Vec<u8>>,
+}
+
+impl fmt::Display for ReplyDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} {}.{} at {} ms, originally sent by {}: expected reply {}, got {}",
+            self.request.endpoint,
+            self.request.canister_id,
+            self.request.method,
+            self.request.timestamp_ms,
+            self.request.sender,
+            hex::encode(self.request.reply.as_deref().unwrap_or_default()),
+            self.actual
+                .as_ref()
+                .map(hex::encode)
+                .unwrap_or_else(|| "no reply".to_string()),
+        )
+    }
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, String> {
+    hex::decode(s).map_err(|e| format!("Invalid hex string: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_types::messages::{Blob, HttpCanisterUpdate, HttpUserQuery};
+
+    const CANISTER_ID: &str = "rwlgt-iiaaa-aaaaa-aaaaa-cai";
+
+    fn parse(line: &str) -> Result<CapturedRequest, String> {
+        serde_json::from_str::<CaptureRecord>(line)
+            .map_err(|e| e.to_string())
+            .and_then(CapturedRequest::try_from)
+    }
+
+    fn canister_id() -> CanisterId {
+        CanisterId::try_from(PrincipalId::from_str(CANISTER_ID).unwrap()).unwrap()
+    }
+
+    fn sender() -> PrincipalId {
+        PrincipalId::new_user_test_id(1)
+    }
+
+    fn call_envelope(method: &str, arg: &[u8]) -> Vec<u8> {
+        let envelope = HttpRequestEnvelope {
+            content: HttpCallContent::Call {
+                update: HttpCanisterUpdate {
+                    canister_id: Blob(canister_id().get().to_vec()),
+                    method_name: method.to_string(),
+                    arg: Blob(arg.to_vec()),
+                    sender: Blob(sender().to_vec()),
+                    ingress_expiry: 0,
+                    nonce: None,
+                },
+            },
+            sender_pubkey: None,
+            sender_sig: None,
+            sender_delegation: None,
+        };
+        SignedRequestBytes::try_from(envelope).unwrap().into()
+    }
+
+    fn query_envelope(method: &str, arg: &[u8]) -> Vec<u8> {
+        let envelope = HttpRequestEnvelope {
+            content: HttpQueryContent::Query {
+                query: HttpUserQuery {
+                    canister_id: Blob(canister_id().get().to_vec()),
+                    method_name: method.to_string(),
+                    arg: Blob(arg.to_vec()),
+                    sender: Blob(sender().to_vec()),
+                    ingress_expiry: 0,
+                    nonce: None,
+                },
+            },
+            sender_pubkey: None,
+            sender_sig: None,
+            sender_delegation: None,
+        };
+        SignedRequestBytes::try_from(envelope).unwrap().into()
+    }
+
+    fn request(timestamp_ms: u64, method: &str) -> CapturedRequest {
+        CapturedRequest {
+            timestamp_ms,
+            endpoint: CallKind::Query,
+            canister_id: canister_id(),
+            method: method.to_string(),
+            payload: vec![],
+            sender: PrincipalId::new_anonymous(),
+            reply: None,
+        }
+    }
+
+    #[test]
+    fn record_with_fields_is_parsed() {
+        let request = parse(&format!(
+            r#"{{"timestamp_ms": 10, "endpoint": "update", "canister_id": "{}", "method": "write", "payload": "0102", "sender": "{}", "reply": "ff"}}"#,
+            CANISTER_ID,
+            sender()
+        ))
+        .unwrap();
+        assert_eq!(request.timestamp_ms, 10);
+        assert_eq!(request.endpoint, CallKind::Update);
+        assert_eq!(request.canister_id, canister_id());
+        assert_eq!(request.method, "write");
+        assert_eq!(request.payload, vec![1, 2]);
+        assert_eq!(request.sender, sender());
+        assert_eq!(request.reply, Some(vec![0xff]));
+    }
+
+    #[test]
+    fn record_defaults_to_anonymous_sender_and_empty_payload() {
+        let request = parse(&format!(
+            r#"{{"timestamp_ms": 10, "endpoint": "call", "canister_id": "{}", "method": "write"}}"#,
+            CANISTER_ID
+        ))
+        .unwrap();
+        assert_eq!(request.endpoint, CallKind::Update);
+        assert_eq!(request.sender, PrincipalId::new_anonymous());
+        assert!(request.payload.is_empty());
+        assert_eq!(request.reply, None);
+    }
+
+    #[test]
+    fn record_without_fields_or_envelope_is_rejected() {
+        let err =
+            parse(r#"{"timestamp_ms": 10, "endpoint": "query", "method": "read"}"#).unwrap_err();
+        assert!(err.contains("canister_id"), "{}", err);
+
+        let err = parse(&format!(
+            r#"{{"timestamp_ms": 10, "canister_id": "{}", "method": "read"}}"#,
+            CANISTER_ID
+        ))
+        .unwrap_err();
+        assert!(err.contains("endpoint"), "{}", err);
+
+        let err = parse(&format!(
+            r#"{{"timestamp_ms": 10, "endpoint": "query", "canister_id": "{}"}}"#,
+            CANISTER_ID
+        ))
+        .unwrap_err();
+        assert!(err.contains("method"), "{}", err);
+    }
+
+    #[test]
+    fn record_with_invalid_values_is_rejected() {
+        assert!(parse(&format!(
+            r#"{{"timestamp_ms": 10, "endpoint": "query", "canister_id": "{}", "method": "read", "payload": "xyz"}}"#,
+            CANISTER_ID
+        ))
+        .is_err());
+        assert!(parse(
+            r#"{"timestamp_ms": 10, "endpoint": "query", "canister_id": "not-a-principal", "method": "read"}"#
+        )
+        .is_err());
+        assert!(parse(&format!(
+            r#"{{"timestamp_ms": 10, "endpoint": "query", "canister_id": "{}", "method": "read", "unknown": 1}}"#,
+            CANISTER_ID
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn record_with_envelope_keeps_reply() {
+        let request = parse(&format!(
+            r#"{{"timestamp_ms": 10, "envelope": "{}", "reply": "0102"}}"#,
+            hex::encode(call_envelope("write", &[7]))
+        ))
+        .unwrap();
+        assert_eq!(request.timestamp_ms, 10);
+        assert_eq!(request.endpoint, CallKind::Update);
+        assert_eq!(request.method, "write");
+        assert_eq!(request.payload, vec![7]);
+        assert_eq!(request.reply, Some(vec![1, 2]));
+    }
+
+    #[test]
+    fn from_envelope_infers_missing_endpoint() {
+        let request =
+            CapturedRequest::from_envelope(5, None, &call_envelope("write", &[1])).unwrap();
+        assert_eq!(request.endpoint, CallKind::Update);
+        assert_eq!(request.canister_id, canister_id());
+        assert_eq!(request.method, "write");
+        assert_eq!(request.payload, vec![1]);
+        assert_eq!(request.sender, sender());
+        assert_eq!(request.reply, None);
+
+        let request =
+            CapturedRequest::from_envelope(5, None, &query_envelope("read", &[2])).unwrap();
+        assert_eq!(request.endpoint, CallKind::Query);
+        assert_eq!(request.canister_id, canister_id());
+        assert_eq!(request.method, "read");
+        assert_eq!(request.payload, vec![2]);
+        assert_eq!(request.sender, sender());
+    }
+
+    #[test]
+    fn from_envelope_uses_given_endpoint() {
+        let request =
+            CapturedRequest::from_envelope(5, Some(CallKind::Query), &query_envelope("read", &[]))
+                .unwrap();
+        assert_eq!(request.endpoint, CallKind::Query);
+
+        assert!(CapturedRequest::from_envelope(
+            5,
+            Some(CallKind::Update),
+            &query_envelope("read", &[])
+        )
+        .is_err());
+        assert!(CapturedRequest::from_envelope(5, None, &[1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn offset_is_relative_to_first_request_and_scaled() {
+        let capture = Capture {
+            requests: vec![request(1000, "a"), request(1500, "a"), request(3000, "a")],
+        };
+        assert_eq!(capture.offset(0, 1.0), Duration::ZERO);
+        assert_eq!(capture.offset(1, 1.0), Duration::from_millis(500));
+        assert_eq!(capture.offset(2, 1.0), Duration::from_secs(2));
+        assert_eq!(capture.offset(2, 2.0), Duration::from_secs(1));
+        assert_eq!(capture.offset(1, 0.5), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn methods_are_listed_in_order_of_first_use() {
+        let capture = Capture {
+            requests: vec![
+                request(0, "b"),
+                request(1, "a"),
+                request(2, "b"),
+                request(3, "c"),
+                request(4, "a"),
+            ],
+        };
+        let (names, indices) = capture.methods();
+        assert_eq!(
+            names,
+            vec![
+                format!("{}.b", CANISTER_ID),
+                format!("{}.a", CANISTER_ID),
+                format!("{}.c", CANISTER_ID),
+            ]
+        );
+        assert_eq!(indices, vec![0, 1, 0, 2, 1]);
+    }
+}
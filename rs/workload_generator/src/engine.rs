@@ -1,9 +1,11 @@
 use crate::{
+    capture::{Capture, ReplyDivergence},
     collector,
     content_length::ContentLength,
     message::Message,
     metrics::{FUTURE_STARTED, REQUEST_STARTING},
     plan::{EngineCall, Plan},
+    scenario::{CallKind, LoadMode, Scenario},
     stats::Fact,
     RequestType,
 };
@@ -23,6 +25,10 @@ use std::{
     convert::TryFrom,
     env, fs,
     str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
     time::{Duration, Instant},
 };
 use tokio::{
@@ -46,6 +52,10 @@ const START_OFFSET: Duration = Duration::from_millis(500);
 
 const QUERY_TIMEOUT: Duration = Duration::from_secs(60 * 5);
 
+// Time an idle closed-loop client waits before checking the load of a scenario
+// again
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
 #[derive(PartialEq, Eq, Hash)]
 enum CallFailure {
     None,
@@ -58,6 +68,7 @@ pub struct CallResult {
     counter: Option<u32>,
     call_failure: CallFailure,
     err_msg: Option<String>,
+    reply: Option<Vec<u8>>,
 }
 
 /// The engine of making requests. The engine implements making the requests and
@@ -104,7 +115,8 @@ impl Engine {
                 agent_sender.clone(),
                 http_client_config.clone(),
             )
-            .with_query_timeout(QUERY_TIMEOUT);
+            .with_query_timeout(QUERY_TIMEOUT)
+            .with_insecure_skip_certificate_verification();
             agent.sender_field = sender_field.clone();
             agent
         });
@@ -166,7 +178,7 @@ impl Engine {
             request_type,
             canister_method_name,
         );
-        let (collector, rec_handle) = collector::start::<Fact>(plan.clone(), periodic_output);
+        let (collector, rec_handle) = collector::start::<Fact>(plan.requests, periodic_output);
 
         let (tx, rx) = channel(requests);
         let time_origin = Instant::now();
@@ -209,6 +221,257 @@ impl Engine {
         rec_handle.join().unwrap()
     }
 
+    /// Execute the given scenario, returning facts tagged with the index of
+    /// the request class they belong to.
+    ///
+    /// - `nonce` - Nonce to use for update calls
+    pub async fn execute_scenario(
+        &self,
+        scenario: Arc<Scenario>,
+        nonce: String,
+        periodic_output: bool,
+    ) -> Vec<Fact> {
+        let plans: Arc<Vec<Plan>> = Arc::new(
+            scenario
+                .classes
+                .iter()
+                .map(|class| class.plan(nonce.clone()))
+                .collect(),
+        );
+        let (collector, rec_handle) =
+            collector::start::<Fact>(scenario.expected_requests(), periodic_output);
+
+        let (tx, rx) = channel(scenario.expected_requests().max(1));
+        let time_origin = Instant::now();
+
+        let rx_handle =
+            tokio::task::spawn(Engine::evaluate_requests(rx, collector, None, time_origin));
+
+        let mut tx_handles = vec![];
+        match scenario.mode {
+            LoadMode::OpenLoop => {
+                let mut n = 0;
+                while let Some(arrival_time) = scenario.arrival_time(n) {
+                    let target_instant = time_origin + START_OFFSET + arrival_time;
+                    sleep_until(tokio::time::Instant::from_std(target_instant)).await;
+                    let tx = tx.clone();
+                    let scenario = scenario.clone();
+                    let plans = plans.clone();
+                    let agent = self.agents[n % self.agents.len()].clone();
+                    FUTURE_STARTED.inc();
+                    tx_handles.push(tokio::task::spawn(async move {
+                        REQUEST_STARTING.inc();
+                        Engine::execute_scenario_request(
+                            agent,
+                            tx,
+                            time_origin,
+                            &scenario,
+                            &plans,
+                            n,
+                        )
+                        .await;
+                    }));
+                    n += 1;
+                }
+            }
+            LoadMode::ClosedLoop => {
+                // Client `i` is active whenever the current load exceeds `i`.
+                let next_request = Arc::new(AtomicUsize::new(0));
+                for client in 0..scenario.max_load().floor() as usize {
+                    let tx = tx.clone();
+                    let scenario = scenario.clone();
+                    let plans = plans.clone();
+                    let agents = self.agents.clone();
+                    let next_request = next_request.clone();
+                    tx_handles.push(tokio::task::spawn(async move {
+                        sleep_until(tokio::time::Instant::from_std(time_origin + START_OFFSET))
+                            .await;
+                        while let Some(load) =
+                            scenario.load_at(time_origin.elapsed().saturating_sub(START_OFFSET))
+                        {
+                            if (client as f64) >= load.floor() {
+                                tokio::time::sleep(IDLE_POLL_INTERVAL).await;
+                                continue;
+                            }
+                            let n = next_request.fetch_add(1, Ordering::Relaxed);
+                            let agent = agents[n % agents.len()].clone();
+                            FUTURE_STARTED.inc();
+                            REQUEST_STARTING.inc();
+                            Engine::execute_scenario_request(
+                                agent,
+                                tx.clone(),
+                                time_origin,
+                                &scenario,
+                                &plans,
+                                n,
+                            )
+                            .await;
+                        }
+                    }));
+                }
+            }
+        }
+        for tx_handle in tx_handles {
+            tx_handle.await.unwrap_or_else(|_| {
+                panic!("Await the tx failed.");
+            });
+        }
+        std::mem::drop(tx);
+        rx_handle.await.unwrap_or_else(|_| {
+            panic!("Await the rx failed.");
+        });
+
+        rec_handle.join().unwrap()
+    }
+
+    /// Issues the `n`-th request of a scenario and forwards its result, tagged
+    /// with the request class, to `tx`.
+    async fn execute_scenario_request(
+        agent: Agent,
+        tx: Sender<CallResult>,
+        time_origin: Instant,
+        scenario: &Scenario,
+        plans: &[Plan],
+        n: usize,
+    ) {
+        let class_index = scenario.class_index(n);
+        let class = &scenario.classes[class_index];
+        Engine::execute_tagged_call(
+            &agent,
+            tx,
+            time_origin,
+            &plans[class_index],
+            class.kind,
+            class.method.clone(),
+            class.payload.generate(n),
+            n,
+            class_index,
+        )
+        .await;
+    }
+
+    /// Replay the given capture, issuing each request at its captured time
+    /// divided by `time_scale`.
+    ///
+    /// Returns the facts, tagged with the index of the method of the request
+    /// in [`Capture::methods`], and the requests whose reply differs from the
+    /// captured one.
+    /// - `nonce` - Nonce to use for update calls
+    pub async fn execute_replay(
+        &self,
+        capture: Arc<Capture>,
+        time_scale: f64,
+        nonce: String,
+        periodic_output: bool,
+    ) -> (Vec<Fact>, Vec<ReplyDivergence>) {
+        let requests = capture.requests.len();
+        let method_indices = Arc::new(capture.methods().1);
+        let (collector, rec_handle) = collector::start::<Fact>(requests, periodic_output);
+
+        let (tx, rx) = channel(requests);
+        let time_origin = Instant::now();
+
+        let rx_handle =
+            tokio::task::spawn(Engine::evaluate_requests(rx, collector, None, time_origin));
+
+        let divergences = Arc::new(Mutex::new(vec![]));
+        let mut tx_handles = vec![];
+        for n in 0..requests {
+            let target_instant = time_origin + START_OFFSET + capture.offset(n, time_scale);
+            sleep_until(tokio::time::Instant::from_std(target_instant)).await;
+            let tx = tx.clone();
+            let capture = capture.clone();
+            let method_indices = method_indices.clone();
+            let divergences = divergences.clone();
+            let nonce = nonce.clone();
+            let agent = self.agents[n % self.agents.len()].clone();
+            FUTURE_STARTED.inc();
+            tx_handles.push(tokio::task::spawn(async move {
+                REQUEST_STARTING.inc();
+                let request = &capture.requests[n];
+                let request_type = match request.endpoint {
+                    CallKind::Query => RequestType::Query,
+                    CallKind::Update => RequestType::Update,
+                };
+                let plan = Plan::new(
+                    0,
+                    nonce,
+                    Byte::from_bytes(0),
+                    vec![],
+                    request.canister_id,
+                    request_type,
+                    request.method.clone(),
+                );
+                let reply = Engine::execute_tagged_call(
+                    &agent,
+                    tx,
+                    time_origin,
+                    &plan,
+                    request.endpoint,
+                    request.method.clone(),
+                    request.payload.clone(),
+                    n,
+                    method_indices[n],
+                )
+                .await;
+                if request.reply.is_some() && request.reply != reply {
+                    divergences.lock().unwrap().push(ReplyDivergence {
+                        request: request.clone(),
+                        actual: reply,
+                    });
+                }
+            }));
+        }
+        for tx_handle in tx_handles {
+            tx_handle.await.unwrap_or_else(|_| {
+                panic!("Await the tx failed.");
+            });
+        }
+        std::mem::drop(tx);
+        rx_handle.await.unwrap_or_else(|_| {
+            panic!("Await the rx failed.");
+        });
+
+        let mut divergences = std::mem::take(&mut *divergences.lock().unwrap());
+        divergences.sort_by_key(|divergence| divergence.request.timestamp_ms);
+        (rec_handle.join().unwrap(), divergences)
+    }
+
+    /// Issues a call and forwards its result, tagged with `class`, to `tx`.
+    /// Returns the reply of the call, if any.
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_tagged_call(
+        agent: &Agent,
+        tx: Sender<CallResult>,
+        time_origin: Instant,
+        plan: &Plan,
+        kind: CallKind,
+        method: String,
+        arg: Vec<u8>,
+        n: usize,
+        class: usize,
+    ) -> Option<Vec<u8>> {
+        // Each call sends exactly one result.
+        let (call_tx, mut call_rx) = channel(1);
+        match kind {
+            CallKind::Query => {
+                Engine::execute_query(agent, call_tx, time_origin, plan, method, arg, n).await;
+            }
+            CallKind::Update => {
+                Engine::execute_update(agent, call_tx, time_origin, plan, method, arg, n).await;
+            }
+        }
+        let mut reply = None;
+        while let Some(mut result) = call_rx.recv().await {
+            result.fact = result.fact.with_class(class);
+            reply = result.reply.take();
+            tx.send(result).await.unwrap_or_else(|_| {
+                panic!("Sending a fact failed.");
+            });
+        }
+        reply
+    }
+
     #[allow(clippy::too_many_arguments)]
     async fn execute_request(
         agent: Agent,
@@ -275,6 +538,7 @@ impl Engine {
                     counter: None,
                     call_failure: CallFailure::OnWait,
                     err_msg: Some(err),
+                    reply: None,
                 })
                 .await
                 .unwrap_or_else(|_| {
@@ -352,6 +616,7 @@ impl Engine {
                     counter: None,
                     call_failure: CallFailure::OnSubmit,
                     err_msg: Some(err_msg),
+                    reply: None,
                 })
                 .await
                 .unwrap_or_else(|_| {
@@ -391,6 +656,7 @@ impl Engine {
                         counter: None,
                         call_failure: CallFailure::OnSubmit,
                         err_msg: Some(err_msg),
+                        reply: None,
                     })
                     .await
                     .unwrap_or_else(|_| {
@@ -430,7 +696,7 @@ impl Engine {
                     )
                     .await;
                     match wait {
-                        Ok((result, counter)) => {
+                        Ok((result, counter, reply)) => {
                             UPDATE_WAIT_REPLY
                                 .with_label_values(&[&format!("{:?}", result)])
                                 .inc();
@@ -461,6 +727,7 @@ impl Engine {
                                         counter: Some(counter),
                                         call_failure: CallFailure::None,
                                         err_msg: None,
+                                        reply,
                                     })
                                     .await
                                     .unwrap_or_else(|_| {
@@ -494,6 +761,7 @@ impl Engine {
                                         counter: None,
                                         call_failure: CallFailure::OnWait,
                                         err_msg: Some(err_msg),
+                                        reply: None,
                                     })
                                     .await
                                     .unwrap_or_else(|_| {
@@ -526,6 +794,7 @@ impl Engine {
                         counter: None,
                         call_failure: CallFailure::OnWait,
                         err_msg: Some(err_msg),
+                        reply: None,
                     })
                     .await
                     .unwrap_or_else(|_| {
@@ -556,7 +825,7 @@ impl Engine {
 
         tx.send(CallResult {
             fact: Fact::record(
-                ContentLength::new(resp.unwrap_or_default().len() as u64),
+                ContentLength::new(resp.as_ref().map_or(0, |r| r.len()) as u64),
                 200_u16,
                 time_query_start,
                 time_query_end,
@@ -565,6 +834,7 @@ impl Engine {
             counter,
             call_failure: CallFailure::None,
             err_msg: None,
+            reply: resp,
         })
         .await
         .unwrap_or_else(|_| {
@@ -632,7 +902,7 @@ impl Engine {
         request_id: MessageId,
         canister_id: &CanisterId,
         deadline: Instant,
-    ) -> Result<(String, Option<u32>), String> {
+    ) -> Result<(String, Option<u32>, Option<Vec<u8>>), String> {
         let call_response = agent
             .wait_ingress(request_id, deadline, canister_id)
             .await?;
@@ -650,6 +920,6 @@ impl Engine {
             .reply
             .as_ref()
             .map(|bytes| Engine::interpret_counter_canister_response(bytes));
-        Ok((call_response.status, counter_value))
+        Ok((call_response.status, counter_value, call_response.reply))
     }
 }
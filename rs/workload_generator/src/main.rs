@@ -14,12 +14,14 @@ use std::{
     path::{Path, PathBuf},
     process::Command,
     str::FromStr,
+    sync::Arc,
     time::Duration,
 };
 
 use std::{convert::TryFrom, net::SocketAddrV4};
 
 mod canister;
+mod capture;
 mod chart;
 mod collector;
 mod content_length;
@@ -27,14 +29,17 @@ mod engine;
 mod message;
 mod metrics;
 mod plan;
+mod scenario;
 mod stats;
 
+use capture::Capture;
 use ic_canister_client::{
     ed25519_public_key_to_der, HttpClient, HttpClientConfig, Sender as AgentSender,
 };
 use ic_config::metrics::{Config as MetricsConfig, Exporter};
 use ic_test_identity::{get_pair, TEST_IDENTITY_KEYPAIR, TEST_IDENTITY_KEYPAIR_HARD_CODED};
 use ic_types::{messages::Blob, CanisterId, PrincipalId, UserId};
+use scenario::Scenario;
 use stats::Summary;
 
 #[cfg(build = "debug")]
@@ -62,6 +67,9 @@ fn write_output_json(filename: &str, summaries: &[Summary]) -> io::Result<()> {
     Ok(())
 }
 
+// Number of diverging replies printed after a replay
+const MAX_REPORTED_DIVERGENCES: usize = 20;
+
 #[derive(Clone, Copy, Debug, ArgEnum)]
 #[clap(rename_all = "camel")]
 pub enum RequestType {
@@ -114,10 +122,33 @@ async fn main() {
         .arg(
             Arg::new("rps")
                 .short('r')
-                .required(true)
+                .required_unless_present_any(&["scenario", "replay"])
                 .takes_value(true)
                 .help("Requests per second to generate. Accepts fractional values, e.g. 1.5 rps."),
         )
+        .arg(
+            Arg::new("scenario")
+                .long("scenario")
+                .value_name("FILE")
+                .takes_value(true)
+                .conflicts_with_all(&["rps", "updates", "canister-id", "canister", "call-method", "payload", "payload-size"])
+                .help("Run the scenario described in the given JSON file instead of a single method at a fixed rate. The scenario defines weighted request classes against pre-installed canisters and the load phases. See src/scenario.rs for the format."),
+        )
+        .arg(
+            Arg::new("replay")
+                .long("replay")
+                .value_name("FILE")
+                .takes_value(true)
+                .conflicts_with_all(&["rps", "scenario", "updates", "canister-id", "canister", "call-method", "payload", "payload-size"])
+                .help("Replay the requests captured in the given JSON Lines file against pre-installed canisters, re-signed with the workload generator's identity. Replies that differ from the captured ones are reported. See src/capture.rs for the format."),
+        )
+        .arg(
+            Arg::new("time-scale")
+                .long("time-scale")
+                .takes_value(true)
+                .requires("replay")
+                .help("Speed-up of the replay relative to the capture, e.g. 2 replays the requests twice as fast. Defaults to 1."),
+        )
         .arg(
             Arg::new("evaluate-max-rps")
                 .long("evaluate-max-rps")
@@ -293,7 +324,10 @@ async fn main() {
         .unwrap()
         .parse::<usize>()
         .unwrap();
-    let rps = matches.value_of("rps").unwrap().parse::<f64>().unwrap();
+    let rps = matches
+        .value_of("rps")
+        .map(|rps| rps.parse::<f64>().unwrap())
+        .unwrap_or_default();
     let rpms = (rps * 1000f64).floor() as usize;
 
     let principal_id = matches
@@ -309,6 +343,21 @@ async fn main() {
         }
     };
 
+    let scenario = matches.value_of("scenario").map(|path| {
+        Arc::new(Scenario::load(Path::new(path)).unwrap_or_else(|err| panic!("{}", err)))
+    });
+
+    let capture = matches.value_of("replay").map(|path| {
+        Arc::new(Capture::load(Path::new(path)).unwrap_or_else(|err| panic!("{}", err)))
+    });
+    let time_scale = matches
+        .value_of("time-scale")
+        .unwrap_or("1")
+        .parse::<f64>()
+        .ok()
+        .filter(|time_scale| time_scale.is_finite() && *time_scale > 0.0)
+        .expect("--time-scale must be a positive number");
+
     let log = get_logger();
     let _guard = slog_scope::set_global_logger(log);
 
@@ -430,50 +479,6 @@ async fn main() {
                 eng.wait_for_all_agents_to_be_healthy().await;
             }
 
-            // use id of install canister if no id specified
-            let canister_id = if let Some(s) = matches.value_of("canister-id") {
-                let canister_id =
-                    CanisterId::try_from(PrincipalId::from_str(s).unwrap_or_else(|_| {
-                        panic!("Illegal value for option --canister-id: '{}'", s);
-                    }))
-                    .unwrap();
-                if let Some(wasm_file_path) = matches.value_of_os("canister").map(Path::new) {
-                    let mut install_succeeded = false;
-                    for url in install_endpoint {
-                        match canister::install_canister(
-                            http_client.clone(),
-                            sender.clone(),
-                            url,
-                            canister_id,
-                            Some(wasm_file_path),
-                        )
-                        .await
-                        {
-                            Ok(()) => {
-                                install_succeeded = true;
-                                break;
-                            }
-                            Err(err) => println!(
-                                "⚠️  Could not install canister at replica url {}. {}",
-                                url, err
-                            ),
-                        }
-                    }
-
-                    if !install_succeeded {
-                        panic!("Failed to install wasm to existing canister");
-                    }
-                }
-                canister_id
-            } else {
-                let wasm_file_path = matches.value_of_os("canister").map(Path::new);
-                canister::setup_canister(http_client, sender, install_endpoint, wasm_file_path)
-                    .await
-                    .unwrap_or_else(|err| {
-                        panic!("Failed to create canister: {}", err);
-                    })
-            };
-
             // case insensitive
             let chart_size = ChartSize::from_str(
                 matches
@@ -486,35 +491,142 @@ async fn main() {
             // Hold all summaries so we can serialize them later if needed
             let mut summaries: Vec<Summary> = Vec::new();
 
-            // Make sure to save the guard, see documentation for more information
-            println!(
-                "Running {:?} rps for {} seconds, req_type = {:?}",
-                rps, duration, request_type
-            );
-
-            let facts = eng
-                .execute_rps(
-                    rpms,
-                    request_type,
-                    canister_method_name,
-                    duration,
-                    nonce.clone(),
-                    call_payload_size,
-                    call_payload,
-                    &canister_id,
-                    periodic_output,
-                )
-                .await;
-
-            // Drop the engine with the hope that all client connections will be closed.
-            // Sometimes we may end up in situation where all file decriptors
-            // are consumed by the number of connections. We need a more
-            // sustainable solution where the file decriptors
-            // are not a bottleneck.
-            std::mem::drop(eng);
-            let summary = Summary::from_facts(&facts);
-            summaries.push(summary.clone());
-            println!("{}", summary.with_chart_size(chart_size));
+            if let Some(scenario) = scenario.as_ref() {
+                println!(
+                    "Running scenario with {} request classes for {} seconds, mode = {:?}",
+                    scenario.classes.len(),
+                    scenario.duration().as_secs(),
+                    scenario.mode
+                );
+
+                let mut facts = eng
+                    .execute_scenario(scenario.clone(), nonce.clone(), periodic_output)
+                    .await;
+                std::mem::drop(eng);
+
+                let summary = Summary::from_facts(&facts);
+                summaries.push(summary.clone());
+                println!("{}", summary.with_chart_size(chart_size));
+                for summary in Summary::per_class(&mut facts, &scenario.class_names()) {
+                    summaries.push(summary.clone());
+                    println!("{}", summary.with_chart_size(chart_size));
+                }
+            } else if let Some(capture) = capture.as_ref() {
+                let (method_names, _) = capture.methods();
+                println!(
+                    "Replaying {} requests to {} methods at {}x speed",
+                    capture.requests.len(),
+                    method_names.len(),
+                    time_scale
+                );
+
+                let (mut facts, divergences) = eng
+                    .execute_replay(capture.clone(), time_scale, nonce.clone(), periodic_output)
+                    .await;
+                std::mem::drop(eng);
+
+                let summary = Summary::from_facts(&facts);
+                summaries.push(summary.clone());
+                println!("{}", summary.with_chart_size(chart_size));
+                for summary in Summary::per_class(&mut facts, &method_names) {
+                    summaries.push(summary.clone());
+                    println!("{}", summary.with_chart_size(chart_size));
+                }
+
+                let compared = capture
+                    .requests
+                    .iter()
+                    .filter(|request| request.reply.is_some())
+                    .count();
+                println!(
+                    "Replies diverged for {} of {} requests with a captured reply",
+                    divergences.len(),
+                    compared
+                );
+                for divergence in divergences.iter().take(MAX_REPORTED_DIVERGENCES) {
+                    println!("  {}", divergence);
+                }
+                if divergences.len() > MAX_REPORTED_DIVERGENCES {
+                    println!(
+                        "  ... and {} more",
+                        divergences.len() - MAX_REPORTED_DIVERGENCES
+                    );
+                }
+            } else {
+                // use id of install canister if no id specified
+                let canister_id = if let Some(s) = matches.value_of("canister-id") {
+                    let canister_id =
+                        CanisterId::try_from(PrincipalId::from_str(s).unwrap_or_else(|_| {
+                            panic!("Illegal value for option --canister-id: '{}'", s);
+                        }))
+                        .unwrap();
+                    if let Some(wasm_file_path) = matches.value_of_os("canister").map(Path::new) {
+                        let mut install_succeeded = false;
+                        for url in install_endpoint {
+                            match canister::install_canister(
+                                http_client.clone(),
+                                sender.clone(),
+                                url,
+                                canister_id,
+                                Some(wasm_file_path),
+                            )
+                            .await
+                            {
+                                Ok(()) => {
+                                    install_succeeded = true;
+                                    break;
+                                }
+                                Err(err) => println!(
+                                    "⚠️  Could not install canister at replica url {}. {}",
+                                    url, err
+                                ),
+                            }
+                        }
+
+                        if !install_succeeded {
+                            panic!("Failed to install wasm to existing canister");
+                        }
+                    }
+                    canister_id
+                } else {
+                    let wasm_file_path = matches.value_of_os("canister").map(Path::new);
+                    canister::setup_canister(http_client, sender, install_endpoint, wasm_file_path)
+                        .await
+                        .unwrap_or_else(|err| {
+                            panic!("Failed to create canister: {}", err);
+                        })
+                };
+
+                // Make sure to save the guard, see documentation for more information
+                println!(
+                    "Running {:?} rps for {} seconds, req_type = {:?}",
+                    rps, duration, request_type
+                );
+
+                let facts = eng
+                    .execute_rps(
+                        rpms,
+                        request_type,
+                        canister_method_name,
+                        duration,
+                        nonce.clone(),
+                        call_payload_size,
+                        call_payload,
+                        &canister_id,
+                        periodic_output,
+                    )
+                    .await;
+
+                // Drop the engine with the hope that all client connections will be closed.
+                // Sometimes we may end up in situation where all file decriptors
+                // are consumed by the number of connections. We need a more
+                // sustainable solution where the file decriptors
+                // are not a bottleneck.
+                std::mem::drop(eng);
+                let summary = Summary::from_facts(&facts);
+                summaries.push(summary.clone());
+                println!("{}", summary.with_chart_size(chart_size));
+            }
 
             if let Some(metrics) = metrics_runtime.take() {
                 std::mem::drop(metrics);
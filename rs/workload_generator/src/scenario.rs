@@ -0,0 +1,527 @@
+//! Declarative workload scenarios.
+//!
+//! A scenario is a JSON file describing several weighted request classes and a
+//! sequence of load phases, e.g.:
+//!
+//! ```json
+//! {
+//!   "mode": "open_loop",
+//!   "phases": [
+//!     { "name": "ramp_up", "duration_secs": 60, "start_load": 0, "end_load": 200 },
+//!     { "name": "steady", "duration_secs": 300, "start_load": 200 },
+//!     { "name": "ramp_down", "duration_secs": 60, "start_load": 200, "end_load": 0 }
+//!   ],
+//!   "classes": [
+//!     {
+//!       "name": "reads", "weight": 9, "kind": "query",
+//!       "canister_id": "rwlgt-iiaaa-aaaaa-aaaaa-cai", "method": "read",
+//!       "payload": { "type": "empty" }
+//!     },
+//!     {
+//!       "name": "writes", "weight": 1, "kind": "update",
+//!       "canister_id": "rwlgt-iiaaa-aaaaa-aaaaa-cai", "method": "write",
+//!       "payload": { "type": "random", "size": 1024 }
+//!     }
+//!   ]
+//! }
+//! ```
+use crate::{plan::Plan, RequestType};
+use byte_unit::Byte;
+use ic_types::{CanisterId, PrincipalId};
+use rand_chacha::ChaChaRng;
+use rand_core::{RngCore, SeedableRng};
+use serde::{Deserialize, Deserializer};
+use std::{collections::BTreeSet, convert::TryFrom, fs, path::Path, str::FromStr, time::Duration};
+
+/// How load is generated during a scenario.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadMode {
+    /// Requests are issued at the rate given by the phases, regardless of how
+    /// long previous requests take to complete. The load of a phase is in
+    /// requests per second.
+    OpenLoop,
+    /// A number of clients each issue a request and wait for its completion
+    /// before issuing the next one. The load of a phase is the number of
+    /// concurrent clients.
+    ClosedLoop,
+}
+
+impl Default for LoadMode {
+    fn default() -> Self {
+        LoadMode::OpenLoop
+    }
+}
+
+/// A period of the scenario during which the load changes linearly from
+/// `start_load` to `end_load`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Phase {
+    pub name: String,
+    pub duration_secs: u64,
+    pub start_load: f64,
+    /// Defaults to `start_load`, i.e. a steady phase.
+    #[serde(default)]
+    pub end_load: Option<f64>,
+}
+
+impl Phase {
+    fn end_load(&self) -> f64 {
+        self.end_load.unwrap_or(self.start_load)
+    }
+
+    fn duration(&self) -> Duration {
+        Duration::from_secs(self.duration_secs)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CallKind {
+    Query,
+    #[serde(alias = "call")]
+    Update,
+}
+
+/// Generates the argument of each call of a request class.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum PayloadGenerator {
+    Empty,
+    /// The same bytes, given in hex, for every call.
+    Hex {
+        #[serde(deserialize_with = "deserialize_hex")]
+        value: Vec<u8>,
+    },
+    /// `size` zero bytes.
+    Zeros {
+        size: u64,
+    },
+    /// `size` pseudo-random bytes, deterministically derived from the request
+    /// number.
+    Random {
+        size: u64,
+    },
+}
+
+impl Default for PayloadGenerator {
+    fn default() -> Self {
+        PayloadGenerator::Empty
+    }
+}
+
+impl PayloadGenerator {
+    /// Returns the payload of the `n`-th request of the scenario.
+    pub fn generate(&self, n: usize) -> Vec<u8> {
+        match self {
+            PayloadGenerator::Empty => vec![],
+            PayloadGenerator::Hex { value } => value.clone(),
+            PayloadGenerator::Zeros { size } => vec![0; *size as usize],
+            PayloadGenerator::Random { size } => {
+                let mut payload = vec![0; *size as usize];
+                ChaChaRng::seed_from_u64(n as u64).fill_bytes(&mut payload);
+                payload
+            }
+        }
+    }
+}
+
+/// A kind of request issued during the scenario. Each class gets a share of
+/// the requests proportional to its weight, and is reported separately.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RequestClass {
+    pub name: String,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    pub kind: CallKind,
+    #[serde(deserialize_with = "deserialize_canister_id")]
+    pub canister_id: CanisterId,
+    pub method: String,
+    #[serde(default)]
+    pub payload: PayloadGenerator,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+impl RequestClass {
+    /// The plan the engine uses to issue the requests of this class.
+    pub fn plan(&self, nonce: String) -> Plan {
+        let request_type = match self.kind {
+            CallKind::Query => RequestType::Query,
+            CallKind::Update => RequestType::Update,
+        };
+        Plan::new(
+            0,
+            nonce,
+            Byte::from_bytes(0),
+            vec![],
+            self.canister_id,
+            request_type,
+            self.method.clone(),
+        )
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    #[serde(default)]
+    pub mode: LoadMode,
+    pub phases: Vec<Phase>,
+    pub classes: Vec<RequestClass>,
+}
+
+impl Scenario {
+    /// Reads and validates the scenario in the given JSON file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read scenario file {}: {}", path.display(), e))?;
+        let scenario: Scenario = serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse scenario file {}: {}", path.display(), e))?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.phases.is_empty() {
+            return Err("A scenario needs at least one phase".to_string());
+        }
+        for phase in &self.phases {
+            for load in [phase.start_load, phase.end_load()] {
+                if !load.is_finite() || load < 0.0 {
+                    return Err(format!("Invalid load {} in phase {}", load, phase.name));
+                }
+            }
+        }
+        if self.classes.is_empty() {
+            return Err("A scenario needs at least one request class".to_string());
+        }
+        if self.classes.iter().all(|class| class.weight == 0) {
+            return Err("At least one request class needs a non-zero weight".to_string());
+        }
+        let mut names = BTreeSet::new();
+        for class in &self.classes {
+            if !names.insert(&class.name) {
+                return Err(format!("Duplicate request class name {}", class.name));
+            }
+        }
+        Ok(())
+    }
+
+    /// The total duration of all phases.
+    pub fn duration(&self) -> Duration {
+        self.phases.iter().map(Phase::duration).sum()
+    }
+
+    /// The load at the given time since the start of the scenario, or `None`
+    /// once all phases are over.
+    pub fn load_at(&self, elapsed: Duration) -> Option<f64> {
+        let mut phase_start = Duration::ZERO;
+        for phase in &self.phases {
+            let phase_end = phase_start + phase.duration();
+            if elapsed < phase_end {
+                let progress =
+                    (elapsed - phase_start).as_secs_f64() / phase.duration().as_secs_f64();
+                return Some(phase.start_load + (phase.end_load() - phase.start_load) * progress);
+            }
+            phase_start = phase_end;
+        }
+        None
+    }
+
+    /// The time since the start of an open-loop scenario at which the `n`-th
+    /// request is issued, or `None` if the scenario issues fewer requests.
+    ///
+    /// The requests are spread such that the number of requests issued up to
+    /// any point in time follows the integral of the load.
+    pub fn arrival_time(&self, n: usize) -> Option<Duration> {
+        let mut remaining = n as f64;
+        let mut phase_start = Duration::ZERO;
+        for phase in &self.phases {
+            let (start, end) = (phase.start_load, phase.end_load());
+            let duration = phase.duration_secs as f64;
+            let requests = (start + end) / 2.0 * duration;
+            if remaining < requests {
+                let offset = if start == end {
+                    remaining / start
+                } else {
+                    // Solve `start * t + slope / 2 * t^2 = remaining` for `t`.
+                    let slope = (end - start) / duration;
+                    ((start * start + 2.0 * slope * remaining).max(0.0).sqrt() - start) / slope
+                };
+                return Some(phase_start + Duration::from_secs_f64(offset.clamp(0.0, duration)));
+            }
+            remaining -= requests;
+            phase_start += phase.duration();
+        }
+        None
+    }
+
+    /// The highest load of any phase.
+    pub fn max_load(&self) -> f64 {
+        self.phases
+            .iter()
+            .flat_map(|phase| [phase.start_load, phase.end_load()])
+            .fold(0.0, f64::max)
+    }
+
+    /// The number of requests an open-loop scenario issues. The number of
+    /// requests of a closed-loop scenario depends on the latency, so it is
+    /// not known in advance and 0 is returned.
+    pub fn expected_requests(&self) -> usize {
+        match self.mode {
+            LoadMode::OpenLoop => self
+                .phases
+                .iter()
+                .map(|phase| {
+                    (phase.start_load + phase.end_load()) / 2.0 * phase.duration_secs as f64
+                })
+                .sum::<f64>()
+                .ceil() as usize,
+            LoadMode::ClosedLoop => 0,
+        }
+    }
+
+    /// Returns the index of the class of the `n`-th request.
+    ///
+    /// Classes are picked along a low-discrepancy sequence, so that any run
+    /// of consecutive requests is split between classes close to their
+    /// weights, and the same requests get the same class on every run.
+    pub fn class_index(&self, n: usize) -> usize {
+        const GOLDEN_RATIO_CONJUGATE: f64 = 0.618_033_988_749_895;
+        let total_weight: u64 = self.classes.iter().map(|c| c.weight as u64).sum();
+        let point = ((n as f64 * GOLDEN_RATIO_CONJUGATE).fract() * total_weight as f64) as u64;
+        let mut cumulative_weight = 0;
+        for (index, class) in self.classes.iter().enumerate() {
+            cumulative_weight += class.weight as u64;
+            if point < cumulative_weight {
+                return index;
+            }
+        }
+        // Only reachable through rounding; fall back to the last weighted class.
+        self.classes
+            .iter()
+            .rposition(|class| class.weight > 0)
+            .unwrap_or(0)
+    }
+
+    pub fn class_names(&self) -> Vec<String> {
+        self.classes
+            .iter()
+            .map(|class| class.name.clone())
+            .collect()
+    }
+}
+
+fn deserialize_hex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    hex::decode(s).map_err(serde::de::Error::custom)
+}
+
+fn deserialize_canister_id<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<CanisterId, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    let principal_id = PrincipalId::from_str(&s)
+        .map_err(|e| serde::de::Error::custom(format!("Invalid canister id '{}': {}", s, e)))?;
+    CanisterId::try_from(principal_id)
+        .map_err(|e| serde::de::Error::custom(format!("Invalid canister id '{}': {:?}", s, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The scenario of the module documentation: a 60s ramp up to 200
+    /// requests per second, 300s at 200 and a 60s ramp down to 0.
+    fn ramp_scenario() -> Scenario {
+        let scenario: Scenario = serde_json::from_str(
+            r#"{
+                "mode": "open_loop",
+                "phases": [
+                    { "name": "ramp_up", "duration_secs": 60, "start_load": 0, "end_load": 200 },
+                    { "name": "steady", "duration_secs": 300, "start_load": 200 },
+                    { "name": "ramp_down", "duration_secs": 60, "start_load": 200, "end_load": 0 }
+                ],
+                "classes": [
+                    {
+                        "name": "reads", "weight": 9, "kind": "query",
+                        "canister_id": "rwlgt-iiaaa-aaaaa-aaaaa-cai", "method": "read",
+                        "payload": { "type": "empty" }
+                    },
+                    {
+                        "name": "writes", "weight": 1, "kind": "update",
+                        "canister_id": "rwlgt-iiaaa-aaaaa-aaaaa-cai", "method": "write",
+                        "payload": { "type": "random", "size": 1024 }
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        scenario.validate().unwrap();
+        scenario
+    }
+
+    fn phase(duration_secs: u64, start_load: f64, end_load: f64) -> Phase {
+        Phase {
+            name: format!("{}..{}", start_load, end_load),
+            duration_secs,
+            start_load,
+            end_load: Some(end_load),
+        }
+    }
+
+    fn class(name: &str, weight: u32) -> RequestClass {
+        RequestClass {
+            name: name.to_string(),
+            weight,
+            kind: CallKind::Query,
+            canister_id: CanisterId::from_u64(0),
+            method: "read".to_string(),
+            payload: PayloadGenerator::Empty,
+        }
+    }
+
+    fn scenario(phases: Vec<Phase>, classes: Vec<RequestClass>) -> Scenario {
+        Scenario {
+            mode: LoadMode::OpenLoop,
+            phases,
+            classes,
+        }
+    }
+
+    fn assert_secs(actual: Option<Duration>, expected_secs: f64) {
+        let actual = actual.unwrap().as_secs_f64();
+        assert!(
+            (actual - expected_secs).abs() < 1e-6,
+            "expected {}s, got {}s",
+            expected_secs,
+            actual
+        );
+    }
+
+    #[test]
+    fn load_at_interpolates_within_phases() {
+        let scenario = ramp_scenario();
+        let load_at = |secs| scenario.load_at(Duration::from_secs(secs)).unwrap();
+
+        assert_eq!(load_at(0), 0.0);
+        assert_eq!(load_at(30), 100.0);
+        assert_eq!(load_at(60), 200.0);
+        assert_eq!(load_at(200), 200.0);
+        assert_eq!(load_at(390), 100.0);
+        assert_eq!(scenario.load_at(Duration::from_secs(420)), None);
+        assert_eq!(scenario.duration(), Duration::from_secs(420));
+        assert_eq!(scenario.max_load(), 200.0);
+    }
+
+    #[test]
+    fn arrival_time_solves_ramps() {
+        let scenario = ramp_scenario();
+
+        assert_secs(scenario.arrival_time(0), 0.0);
+        // 200 / 60 * t^2 / 2 = 1500 requests are issued in the first 30s.
+        assert_secs(scenario.arrival_time(1_500), 30.0);
+        // The 6000 requests of the ramp up are followed by the steady phase.
+        assert_secs(scenario.arrival_time(6_000), 60.0);
+        assert_secs(scenario.arrival_time(8_000), 70.0);
+        // 200 * t - 200 / 60 * t^2 / 2 = 4500 requests are issued in the first
+        // 30s of the ramp down.
+        assert_secs(scenario.arrival_time(70_500), 390.0);
+        assert!(scenario.arrival_time(71_999).unwrap() < Duration::from_secs(420));
+        assert_eq!(scenario.arrival_time(72_000), None);
+    }
+
+    #[test]
+    fn arrival_times_are_increasing() {
+        let scenario = ramp_scenario();
+        let mut previous = Duration::ZERO;
+        for n in (0..scenario.expected_requests()).step_by(97) {
+            let arrival_time = scenario.arrival_time(n).unwrap();
+            assert!(arrival_time >= previous);
+            previous = arrival_time;
+        }
+    }
+
+    #[test]
+    fn zero_load_phases_issue_no_requests() {
+        let scenario = scenario(
+            vec![
+                phase(10, 0.0, 0.0),
+                phase(10, 10.0, 10.0),
+                phase(5, 0.0, 0.0),
+                phase(0, 10.0, 10.0),
+                phase(10, 10.0, 10.0),
+            ],
+            vec![class("reads", 1)],
+        );
+
+        assert_eq!(scenario.load_at(Duration::from_secs(5)), Some(0.0));
+        assert_eq!(scenario.load_at(Duration::from_secs(22)), Some(0.0));
+        assert_eq!(scenario.load_at(Duration::from_secs(25)), Some(10.0));
+
+        assert_secs(scenario.arrival_time(0), 10.0);
+        assert_secs(scenario.arrival_time(99), 19.9);
+        assert_secs(scenario.arrival_time(100), 25.0);
+        assert_secs(scenario.arrival_time(199), 34.9);
+        assert_eq!(scenario.arrival_time(200), None);
+        assert_eq!(scenario.expected_requests(), 200);
+    }
+
+    #[test]
+    fn expected_requests_covers_all_arrivals() {
+        assert_eq!(ramp_scenario().expected_requests(), 72_000);
+
+        // 1.5 requests are rounded up, matching the two arrival times.
+        let scenario = scenario(vec![phase(3, 0.5, 0.5)], vec![class("reads", 1)]);
+        assert_eq!(scenario.expected_requests(), 2);
+        assert!(scenario.arrival_time(1).is_some());
+        assert_eq!(scenario.arrival_time(2), None);
+
+        let closed_loop = Scenario {
+            mode: LoadMode::ClosedLoop,
+            ..ramp_scenario()
+        };
+        assert_eq!(closed_loop.expected_requests(), 0);
+    }
+
+    #[test]
+    fn class_index_follows_weights() {
+        let scenario = ramp_scenario();
+        let mut counts = [0; 2];
+        for n in 0..1_000 {
+            counts[scenario.class_index(n)] += 1;
+        }
+        assert_eq!(counts, [900, 100]);
+
+        // Any window of requests is split close to the weights.
+        for start in (0..5_000).step_by(37) {
+            let writes = (start..start + 100)
+                .filter(|n| scenario.class_index(*n) == 1)
+                .count();
+            assert!((8..=12).contains(&writes), "{} writes", writes);
+        }
+    }
+
+    #[test]
+    fn class_index_skips_classes_without_weight() {
+        let scenario = scenario(
+            vec![phase(1, 1.0, 1.0)],
+            vec![
+                class("never", 0),
+                class("often", 2),
+                class("also_never", 0),
+                class("sometimes", 1),
+            ],
+        );
+        let mut counts = [0; 4];
+        for n in 0..3_000 {
+            counts[scenario.class_index(n)] += 1;
+        }
+        assert_eq!(counts, [0, 2_001, 0, 999]);
+    }
+}
@@ -43,6 +43,8 @@ pub struct Fact {
     time_request_end: Instant,
     content_length: ContentLength,
     success: bool,
+    /// Index of the scenario request class the request belongs to.
+    class: usize,
 }
 
 impl Fact {
@@ -64,8 +66,14 @@ impl Fact {
             time_request_end,
             content_length,
             success,
+            class: 0,
         }
     }
+
+    pub fn with_class(mut self, class: usize) -> Fact {
+        self.class = class;
+        self
+    }
 }
 impl RequestInfo for Fact {
     fn is_succ(&self) -> bool {
@@ -181,6 +189,8 @@ pub struct Summary {
     latency_histogram: Vec<u32>,
     succ_rate_histogram: HashMap<usize, u32>,
     status_counts: HashMap<u16, u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    class: Option<String>,
     #[serde(skip_serializing)]
     chart_size: ChartSize,
 }
@@ -215,6 +225,24 @@ impl Summary {
         }
     }
 
+    /// Calculates the statistics of each request class of a scenario, in the
+    /// order of `class_names`.
+    pub fn per_class(facts: &mut [Fact], class_names: &[String]) -> Vec<Summary> {
+        facts.sort_by_key(|fact| fact.class);
+        class_names
+            .iter()
+            .enumerate()
+            .map(|(class, name)| {
+                let start = facts.partition_point(|fact| fact.class < class);
+                let end = facts.partition_point(|fact| fact.class <= class);
+                Summary {
+                    class: Some(name.clone()),
+                    ..Summary::from_facts(&facts[start..end])
+                }
+            })
+            .collect()
+    }
+
     #[allow(dead_code)]
     pub fn content_length(self) -> ContentLength {
         self.content_length
@@ -301,6 +329,7 @@ impl Summary {
             latency_histogram: vec![0; 0],
             succ_rate_histogram: HashMap::new(),
             status_counts: HashMap::new(),
+            class: None,
             chart_size: ChartSize::Medium,
         }
     }
@@ -338,7 +367,10 @@ where
 
 impl fmt::Display for Summary {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Summary")?;
+        match &self.class {
+            Some(class) => writeln!(f, "Summary of request class {}", class)?,
+            None => writeln!(f, "Summary")?,
+        }
         writeln!(
             f,
             "  Average:   {} ms (std: {} ms)",
@@ -346,6 +378,11 @@ impl fmt::Display for Summary {
             self.stddev.to_ms()
         )?;
         writeln!(f, "  Median:    {} ms", self.median.to_ms())?;
+        for p in [90, 99] {
+            if let Some(latency) = self.percentiles.get(p) {
+                writeln!(f, "  P{}:       {} ms", p, latency.to_ms())?;
+            }
+        }
         writeln!(f, "  Longest:   {} ms", self.max.to_ms())?;
         writeln!(f, "  Shortest:  {} ms", self.min.to_ms())?;
         writeln!(f, "  Requests:  {}", self.count)?;